@@ -0,0 +1,127 @@
+/* Generated by cbindgen from src/capi.rs, regenerate with `cbindgen --config cbindgen.toml --output include/voluta.h` */
+
+#ifndef VOLUTA_H
+#define VOLUTA_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Strategy used to resolve overlapping matches
+typedef enum VolutaPostProcess {
+  VolutaPostProcessNone = 0,
+  VolutaPostProcessMerge = 1,
+  VolutaPostProcessLongest = 2,
+  VolutaPostProcessPriority = 3,
+} VolutaPostProcess;
+
+// Opaque matcher handle
+typedef struct VolutaMatcher VolutaMatcher;
+
+// Opaque list of matches
+typedef struct VolutaMatches VolutaMatches;
+
+// Options of a matcher, see `voluta_options_default`
+typedef struct VolutaOptions {
+  bool overlapping;
+  bool case_insensitive;
+  bool whole_word;
+  enum VolutaPostProcess postprocess;
+  bool prefer_longer;
+} VolutaOptions;
+
+// A match, as byte offsets with `end` exclusive and the index of the matched pattern
+typedef struct VolutaMatch {
+  size_t start;
+  size_t end;
+  size_t pattern;
+} VolutaMatch;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Version of the library as a static NUL-terminated string
+const char *voluta_version(void);
+
+// Default options, the same defaults as the Python TextMatcher
+struct VolutaOptions voluta_options_default(void);
+
+// Build a matcher from `count` NUL-terminated UTF-8 patterns
+// A null `options` uses the defaults; returns null and sets `error` on failure
+//
+// # Safety
+// `patterns` must point to `count` valid NUL-terminated strings, `options` must be null or
+// point to a valid VolutaOptions and `error` must be null or writable
+struct VolutaMatcher *voluta_matcher_new(const char *const *patterns,
+                                         size_t count,
+                                         const struct VolutaOptions *options,
+                                         char **error);
+
+// Release a matcher, null is ignored
+//
+// # Safety
+// `matcher` must be null or returned by `voluta_matcher_new` and not released yet
+void voluta_matcher_free(struct VolutaMatcher *matcher);
+
+// Number of patterns of the matcher, empty patterns are dropped when building it
+//
+// # Safety
+// `matcher` must be a valid matcher
+size_t voluta_matcher_pattern_count(const struct VolutaMatcher *matcher);
+
+// Pattern at `index`, valid as long as the matcher, or null if out of range
+//
+// # Safety
+// `matcher` must be a valid matcher
+const char *voluta_matcher_pattern(const struct VolutaMatcher *matcher, size_t index);
+
+// Match `len` bytes at `data`
+//
+// # Safety
+// `matcher` must be a valid matcher and `data` must point to `len` readable bytes
+struct VolutaMatches *voluta_match_bytes(const struct VolutaMatcher *matcher,
+                                         const uint8_t *data,
+                                         size_t len);
+
+// Memory-map the file at `path` and match it in parallel chunks
+// Returns null and sets `error` if the file cannot be read
+//
+// # Safety
+// `matcher` must be a valid matcher, `path` a NUL-terminated string and `error` must be null
+// or writable
+struct VolutaMatches *voluta_match_file(const struct VolutaMatcher *matcher,
+                                        const char *path,
+                                        char **error);
+
+// Number of matches in the list
+//
+// # Safety
+// `matches` must be a valid list of matches
+size_t voluta_matches_len(const struct VolutaMatches *matches);
+
+// Copy the match at `index` to `out`, returns false if `index` is out of range
+//
+// # Safety
+// `matches` must be a valid list of matches and `out` must be writable
+bool voluta_matches_get(const struct VolutaMatches *matches, size_t index, struct VolutaMatch *out);
+
+// Release a list of matches, null is ignored
+//
+// # Safety
+// `matches` must be null or returned by a match function and not released yet
+void voluta_matches_free(struct VolutaMatches *matches);
+
+// Release an error message, null is ignored
+//
+// # Safety
+// `string` must be null or a message returned through an `error` out parameter
+void voluta_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* VOLUTA_H */
@@ -0,0 +1,52 @@
+/** Options of a TextMatcher, the defaults match the Python TextMatcher defaults */
+export interface TextMatcherOptions {
+  /** Report every match, including matches overlapping other matches (default true) */
+  overlapping?: boolean;
+  /** Match ASCII letters regardless of case (default true) */
+  caseInsensitive?: boolean;
+  /** Only report matches surrounded by non-word characters (default false) */
+  wholeWord?: boolean;
+  /** Strategy used to resolve overlapping matches */
+  postprocess?: "merge" | "longest" | "priority";
+  /** One priority per pattern, higher wins, implies the priority strategy */
+  priorities?: number[];
+  /** Break priority ties in favor of the longer match (default true) */
+  preferLonger?: boolean;
+}
+
+/** A match as byte offsets, `end` exclusive */
+export interface Match {
+  start: number;
+  end: number;
+  pattern: string;
+}
+
+/** A match of `matchFile`, with offsets relative to the line */
+export interface LineMatch {
+  line: number;
+  start: number;
+  end: number;
+  pattern: string;
+}
+
+export class TextMatcher {
+  constructor(patterns: string[], options?: TextMatcherOptions);
+  readonly patterns: string[];
+  readonly overlapping: boolean;
+  readonly caseInsensitive: boolean;
+  readonly wholeWord: boolean;
+  readonly preferLonger: boolean;
+  readonly postprocess: "merge" | "longest" | "priority" | null;
+
+  matchFile(path: string): LineMatch[];
+  matchFileMemmap(path: string, chunkSize?: number): Match[];
+  matchFileMemmapParallel(path: string, chunkSize?: number, nThreads?: number): Match[];
+  matchBytes(data: Uint8Array): Match[];
+  matchFileStream(path: string, bufferSize?: number, maxBytesPerSec?: number): Match[];
+  matchStream(data: Uint8Array, bufferSize?: number): Match[];
+
+  /** Scans the file on the libuv thread pool, resolving to matches sorted by offset */
+  matchFileAsync(path: string): Promise<Match[]>;
+  /** Copies the data and scans it on the libuv thread pool */
+  matchBytesAsync(data: Uint8Array): Promise<Match[]>;
+}
@@ -0,0 +1,2 @@
+// Native addon built from the voluta crate with the `node` feature, see `npm run build`
+module.exports = require("./voluta.node");
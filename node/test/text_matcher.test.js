@@ -0,0 +1,73 @@
+const assert = require("node:assert");
+const fs = require("node:fs");
+const os = require("node:os");
+const path = require("node:path");
+const test = require("node:test");
+const { TextMatcher } = require("..");
+
+function logFile() {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "voluta-"));
+  const file = path.join(dir, "app.log");
+  fs.writeFileSync(file, "error: disk full\nwarning: cpu hot\nerror again\n");
+  return file;
+}
+
+test("matchBytes returns byte offsets and patterns", () => {
+  const matcher = new TextMatcher(["error", "warning"]);
+  assert.deepStrictEqual(matcher.matchBytes(Buffer.from("error warning ERROR")), [
+    { start: 0, end: 5, pattern: "error" },
+    { start: 6, end: 13, pattern: "warning" },
+    { start: 14, end: 19, pattern: "error" },
+  ]);
+});
+
+test("options are applied and exposed", () => {
+  const matcher = new TextMatcher(["new", "new york"], {
+    wholeWord: true,
+    caseInsensitive: false,
+    postprocess: "longest",
+  });
+  assert.strictEqual(matcher.wholeWord, true);
+  assert.strictEqual(matcher.caseInsensitive, false);
+  assert.strictEqual(matcher.postprocess, "longest");
+  assert.deepStrictEqual(matcher.matchBytes(Buffer.from("new york renewal New")), [
+    { start: 0, end: 8, pattern: "new york" },
+  ]);
+});
+
+test("file methods agree with each other", () => {
+  const file = logFile();
+  const matcher = new TextMatcher(["error", "warning"]);
+  const expected = matcher.matchBytes(fs.readFileSync(file));
+
+  assert.deepStrictEqual(matcher.matchFileMemmap(file), expected);
+  assert.deepStrictEqual(matcher.matchFileMemmap(file, 8), expected);
+  assert.deepStrictEqual(
+    matcher.matchFileMemmapParallel(file, 8, 2).sort((a, b) => a.start - b.start),
+    expected,
+  );
+  assert.deepStrictEqual(matcher.matchFileStream(file), expected);
+  assert.deepStrictEqual(matcher.matchStream(fs.readFileSync(file), 16), expected);
+  assert.deepStrictEqual(matcher.matchFile(file)[1], {
+    line: 2,
+    start: 0,
+    end: 7,
+    pattern: "warning",
+  });
+});
+
+test("async methods resolve to the same matches", async () => {
+  const file = logFile();
+  const matcher = new TextMatcher(["error", "warning"]);
+  const data = fs.readFileSync(file);
+
+  assert.deepStrictEqual(await matcher.matchBytesAsync(data), matcher.matchBytes(data));
+  assert.deepStrictEqual(await matcher.matchFileAsync(file), matcher.matchBytes(data));
+  await assert.rejects(matcher.matchFileAsync("nonexistent_file.txt"));
+});
+
+test("invalid configuration throws", () => {
+  assert.throws(() => new TextMatcher([""]), /Pattern set cannot be empty/);
+  assert.throws(() => new TextMatcher(["a"], { postprocess: "nope" }), /Unknown postprocess/);
+  assert.throws(() => new TextMatcher(["error"]).matchFile("nonexistent_file.txt"));
+});
@@ -0,0 +1,195 @@
+//! Append-only audit log of scan runs: one JSON record per run saying who ran which matcher
+//! over which roots, when, and what it found, as evidence that the scan took place
+
+use crate::matcher::Matcher;
+use crate::report::format_timestamp;
+use crate::sink::new_scan_id;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{Result, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+/// JSON Lines file that audit records are appended to, created if missing
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    path: Arc<PathBuf>,
+    // Serializes the appends of the scans sharing the log
+    lock: Arc<Mutex<()>>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Arc::new(path.into()),
+            lock: Arc::default(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record as one line, synced to disk before returning
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.as_ref())?;
+        file.write_all(&line)?;
+        file.sync_data()
+    }
+}
+
+/// Matcher that ran a scan, as recorded in the audit log
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditMatcher {
+    /// See `Matcher::fingerprint`
+    pub fingerprint: String,
+    pub patterns: usize,
+    pub engine: &'static str,
+    pub overlapping: bool,
+    pub case_insensitive: bool,
+    pub whole_word: bool,
+    pub postprocess: Option<&'static str>,
+    pub hashed: bool,
+    pub fuzzy: bool,
+    pub ignore_separators: bool,
+    pub encoding: &'static str,
+}
+
+impl AuditMatcher {
+    fn new(matcher: &Matcher) -> Self {
+        Self {
+            fingerprint: matcher.fingerprint().to_string(),
+            patterns: matcher.patterns().len(),
+            engine: matcher.engine().name(),
+            overlapping: matcher.overlapping(),
+            case_insensitive: matcher.case_insensitive(),
+            whole_word: matcher.whole_word(),
+            postprocess: matcher.postprocess().map(|p| p.name()),
+            hashed: matcher.hashed(),
+            fuzzy: matcher.fuzzy().iter().any(|&d| d > 0),
+            ignore_separators: matcher.ignore_separators(),
+            encoding: matcher.encoding().name(),
+        }
+    }
+}
+
+/// Totals of a scan run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AuditTotals {
+    pub files_scanned: usize,
+    /// Files that could not be scanned, the run going on without them
+    pub files_failed: usize,
+    pub bytes_scanned: usize,
+    pub matches: usize,
+}
+
+/// One line of the audit log
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditRecord {
+    pub scan_id: String,
+    /// Method that ran the scan, e.g. "match_file_memmap" or "match_dir"
+    pub operation: &'static str,
+    /// When the scan started, as an RFC 3339 UTC timestamp
+    pub started_at: String,
+    /// Duration of the scan in seconds
+    pub duration: f64,
+    /// User running the process, from the environment
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub pid: u32,
+    pub voluta_version: &'static str,
+    pub matcher: AuditMatcher,
+    /// Files and directories the scan was asked to scan
+    pub roots: Vec<String>,
+    #[serde(flatten)]
+    pub totals: AuditTotals,
+    /// Why the scan failed
+    pub error: Option<String>,
+}
+
+/// A scan run of a matcher with an audit log, recorded once it ends
+pub(crate) struct AuditRun {
+    log: AuditLog,
+    operation: &'static str,
+    roots: Vec<String>,
+    scan_id: Option<String>,
+    started_at: String,
+    started: Instant,
+    matcher: AuditMatcher,
+}
+
+impl AuditRun {
+    /// Start a run over `roots`, None when the matcher has no audit log
+    pub(crate) fn start(
+        matcher: &Matcher,
+        operation: &'static str,
+        roots: &[&str],
+    ) -> Option<Self> {
+        let log = matcher.audit_log()?.clone();
+        Some(Self {
+            log,
+            operation,
+            roots: roots.iter().map(|root| root.to_string()).collect(),
+            scan_id: None,
+            started_at: format_timestamp(SystemTime::now()),
+            started: Instant::now(),
+            matcher: AuditMatcher::new(matcher),
+        })
+    }
+
+    /// Record the run under the identifier its results carry, a new one otherwise
+    pub(crate) fn with_scan_id(self, scan_id: String) -> Self {
+        Self {
+            scan_id: Some(scan_id),
+            ..self
+        }
+    }
+
+    /// Record the outcome of the run, if audited
+    /// A record that cannot be written fails the scan, which would otherwise go unevidenced
+    pub(crate) fn end<T>(
+        run: Option<Self>,
+        res: Result<T>,
+        totals: impl FnOnce(&T) -> AuditTotals,
+    ) -> Result<T> {
+        let Some(run) = run else {
+            return res;
+        };
+        let record = AuditRecord {
+            scan_id: run.scan_id.unwrap_or_else(new_scan_id),
+            operation: run.operation,
+            started_at: run.started_at,
+            duration: run.started.elapsed().as_secs_f64(),
+            user: ["USER", "USERNAME", "LOGNAME"]
+                .iter()
+                .find_map(|name| std::env::var(name).ok()),
+            host: hostname(),
+            pid: std::process::id(),
+            voluta_version: env!("CARGO_PKG_VERSION"),
+            matcher: run.matcher,
+            roots: run.roots,
+            totals: res.as_ref().map(totals).unwrap_or_default(),
+            error: res.as_ref().err().map(|e| e.to_string()),
+        };
+        match (run.log.append(&record), res) {
+            (Err(e), Ok(_)) => Err(e),
+            (_, res) => res,
+        }
+    }
+}
+
+pub(crate) fn hostname() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|name| name.trim().to_string())
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .filter(|name| !name.is_empty())
+}
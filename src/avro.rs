@@ -0,0 +1,563 @@
+//! Avro scans: the string and bytes fields of the records of Avro object container files,
+//! read block by block, decompressed and decoded with the schema of the file, and reported
+//! with the index of their record and their field path
+
+use crate::codecs;
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use flate2::read::DeflateDecoder;
+use serde_json::Value as Json;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind, Read, Result};
+
+/// First bytes of object container files
+const MAGIC: &[u8] = b"Obj\x01";
+/// Bytes a block or a metadata value takes at most in the file, so a corrupt size cannot
+/// exhaust memory; decompressed blocks are held to the matcher's max_decoded_size
+const MAX_BLOCK_SIZE: usize = 256 * 1024 * 1024;
+/// Nesting of values at most, which only recursive schemas reach
+const MAX_DEPTH: usize = 256;
+/// Bytes of values decoded ahead of their scan, scanned in parallel once reached
+const BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// Matches of the records of an Avro file
+pub struct AvroScan {
+    /// Path of every field with matches, like "address.city", "emails[1]" for the items of
+    /// arrays or "labels[env]" for the values of maps
+    pub fields: Vec<String>,
+    /// (record, field, start, end, pattern) matches, the record counting from 0 in file order
+    /// and the field indexing `fields`, sorted by record, then field position, then offset
+    pub matches: Vec<(u64, usize, usize, usize, PatternID)>,
+}
+
+impl AvroScan {
+    /// Scan the string and bytes fields of the Avro object container file at `path`,
+    /// compressed with the null, deflate or snappy codec
+    /// Map keys, enums, fixed values and decimals are not scanned
+    pub fn scan(matcher: &Matcher, path: &str) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader
+            .read_exact(&mut magic)
+            .ok()
+            .filter(|()| magic == MAGIC)
+            .ok_or_else(|| invalid("Not an Avro object container file".to_string()))?;
+
+        let metadata = read_metadata(&mut reader)?;
+        let schema = metadata
+            .get("avro.schema")
+            .ok_or_else(|| invalid("The Avro file has no schema".to_string()))?;
+        let schema: Json = serde_json::from_slice(schema)
+            .map_err(|e| invalid(format!("Invalid Avro schema: {}", e)))?;
+        let schemas = Schemas::parse(&schema)?;
+        let codec = match metadata.get("avro.codec") {
+            Some(codec) => String::from_utf8_lossy(codec).into_owned(),
+            None => "null".to_string(),
+        };
+        if !["null", "deflate", "snappy"].contains(&codec.as_str()) {
+            return Err(invalid(format!(
+                "Unsupported Avro codec '{}', expected null, deflate or snappy",
+                codec
+            )));
+        }
+        let mut sync = [0; 16];
+        reader.read_exact(&mut sync).map_err(|_| truncated())?;
+
+        let mut scan = Self {
+            fields: Vec::new(),
+            matches: Vec::new(),
+        };
+        let mut batch = Batch::default();
+        let mut record = 0;
+        while let Some(count) = read_long(&mut reader)? {
+            let count = u64::try_from(count)
+                .map_err(|_| invalid(format!("Invalid Avro block count {}", count)))?;
+            let size = read_long(&mut reader)?.ok_or_else(truncated)?;
+            let size = usize::try_from(size)
+                .ok()
+                .filter(|&size| size <= MAX_BLOCK_SIZE)
+                .ok_or_else(|| invalid(format!("Invalid Avro block size {}", size)))?;
+            let block = read_exact(&mut reader, size)?;
+            let mut marker = [0; 16];
+            reader.read_exact(&mut marker).map_err(|_| truncated())?;
+            if marker != sync {
+                return Err(invalid(
+                    "Avro block followed by a wrong sync marker".to_string(),
+                ));
+            }
+
+            let block = decompress(&codec, block, matcher.max_decoded_size())?;
+            let mut decoder = Decoder {
+                buf: &block,
+                pos: 0,
+            };
+            let mut path = String::new();
+            for done in 1..=count {
+                let pos = decoder.pos;
+                schemas.walk(
+                    schemas.root,
+                    &mut decoder,
+                    &mut path,
+                    0,
+                    &mut |path, value| batch.push(record, path, value),
+                )?;
+                record += 1;
+                if decoder.pos == pos {
+                    // Values taking no bytes hold no strings, and neither do the next ones
+                    record = record.saturating_add(count - done);
+                    break;
+                }
+            }
+            if batch.bytes >= BATCH_BYTES {
+                scan.extend(matcher, &mut batch);
+            }
+        }
+        scan.extend(matcher, &mut batch);
+        Ok(scan)
+    }
+
+    /// Scan the values of a batch and keep the matches, with the fields they were found in
+    fn extend(&mut self, matcher: &Matcher, batch: &mut Batch) {
+        let mut fields: HashMap<usize, usize> = HashMap::new();
+        for (value, start, end, pattern_idx) in matcher.match_values(&batch.values) {
+            let (record, path) = batch.owners[value];
+            let field = *fields.entry(path).or_insert_with(|| {
+                self.fields.push(batch.paths[path].clone());
+                self.fields.len() - 1
+            });
+            self.matches.push((record, field, start, end, pattern_idx));
+        }
+        *batch = Batch::default();
+    }
+}
+
+/// Values decoded and not scanned yet, with their record and the interned path of their field
+#[derive(Default)]
+struct Batch {
+    paths: Vec<String>,
+    path_ids: HashMap<String, usize>,
+    owners: Vec<(u64, usize)>,
+    values: Vec<Vec<u8>>,
+    bytes: usize,
+}
+
+impl Batch {
+    fn push(&mut self, record: u64, path: &str, value: &[u8]) {
+        let path = match self.path_ids.get(path) {
+            Some(&id) => id,
+            None => {
+                self.paths.push(path.to_string());
+                self.path_ids.insert(path.to_string(), self.paths.len() - 1);
+                self.paths.len() - 1
+            }
+        };
+        self.owners.push((record, path));
+        self.values.push(value.to_vec());
+        self.bytes += value.len();
+    }
+}
+
+/// Node of a parsed schema, named types being referenced by their index
+enum Schema {
+    Null,
+    Boolean,
+    /// Int or long, both zigzag varints
+    Long,
+    Float,
+    Double,
+    /// Bytes or string, scanned unless they hold decimals
+    Bytes {
+        scanned: bool,
+    },
+    Fixed(usize),
+    Enum,
+    Record(Vec<(String, usize)>),
+    Array(usize),
+    Map(usize),
+    Union(Vec<usize>),
+}
+
+/// Schema of a file, parsed into nodes
+struct Schemas {
+    nodes: Vec<Schema>,
+    /// Nodes of the named types, by full name
+    names: HashMap<String, usize>,
+    root: usize,
+}
+
+impl Schemas {
+    fn parse(schema: &Json) -> Result<Self> {
+        let mut schemas = Self {
+            nodes: Vec::new(),
+            names: HashMap::new(),
+            root: 0,
+        };
+        schemas.root = schemas.node(schema, "")?;
+        Ok(schemas)
+    }
+
+    fn push(&mut self, schema: Schema) -> usize {
+        self.nodes.push(schema);
+        self.nodes.len() - 1
+    }
+
+    /// Parse a schema in the namespace of its enclosing named type
+    fn node(&mut self, schema: &Json, namespace: &str) -> Result<usize> {
+        let invalid_schema = || invalid(format!("Invalid Avro schema: {}", schema));
+        match schema {
+            Json::String(name) => match primitive(name) {
+                Some(node) => Ok(self.push(node)),
+                None => {
+                    let full_name = full_name(name, namespace);
+                    self.names
+                        .get(&full_name)
+                        .or_else(|| self.names.get(name))
+                        .copied()
+                        .ok_or_else(|| invalid(format!("Unknown Avro type '{}'", name)))
+                }
+            },
+            Json::Array(branches) => {
+                let branches = branches
+                    .iter()
+                    .map(|branch| self.node(branch, namespace))
+                    .collect::<Result<_>>()?;
+                Ok(self.push(Schema::Union(branches)))
+            }
+            Json::Object(object) => {
+                let kind = match object.get("type") {
+                    Some(Json::String(kind)) => kind.as_str(),
+                    Some(nested) => return self.node(nested, namespace),
+                    None => return Err(invalid_schema()),
+                };
+                let name = || -> Result<(String, String)> {
+                    let name = object
+                        .get("name")
+                        .and_then(Json::as_str)
+                        .ok_or_else(invalid_schema)?;
+                    let namespace = object
+                        .get("namespace")
+                        .and_then(Json::as_str)
+                        .unwrap_or(namespace);
+                    let full_name = full_name(name, namespace);
+                    let namespace = full_name
+                        .rsplit_once('.')
+                        .map_or("", |(namespace, _)| namespace)
+                        .to_string();
+                    Ok((full_name, namespace))
+                };
+                match kind {
+                    "record" | "error" => {
+                        let (full_name, namespace) = name()?;
+                        // Registered before its fields, which may refer to it
+                        let node = self.push(Schema::Record(Vec::new()));
+                        self.names.insert(full_name, node);
+                        let mut fields = Vec::new();
+                        for field in object
+                            .get("fields")
+                            .and_then(Json::as_array)
+                            .ok_or_else(invalid_schema)?
+                        {
+                            let field_name = field
+                                .get("name")
+                                .and_then(Json::as_str)
+                                .ok_or_else(invalid_schema)?;
+                            let field_type = field.get("type").ok_or_else(invalid_schema)?;
+                            fields
+                                .push((field_name.to_string(), self.node(field_type, &namespace)?));
+                        }
+                        self.nodes[node] = Schema::Record(fields);
+                        Ok(node)
+                    }
+                    "enum" | "fixed" => {
+                        let (full_name, _) = name()?;
+                        let node = if kind == "enum" {
+                            Schema::Enum
+                        } else {
+                            let size = object
+                                .get("size")
+                                .and_then(Json::as_u64)
+                                .ok_or_else(invalid_schema)?;
+                            Schema::Fixed(size as usize)
+                        };
+                        let node = self.push(node);
+                        self.names.insert(full_name, node);
+                        Ok(node)
+                    }
+                    "array" => {
+                        let items = object.get("items").ok_or_else(invalid_schema)?;
+                        let items = self.node(items, namespace)?;
+                        Ok(self.push(Schema::Array(items)))
+                    }
+                    "map" => {
+                        let values = object.get("values").ok_or_else(invalid_schema)?;
+                        let values = self.node(values, namespace)?;
+                        Ok(self.push(Schema::Map(values)))
+                    }
+                    _ => {
+                        let node = primitive(kind).ok_or_else(invalid_schema)?;
+                        let decimal =
+                            object.get("logicalType").and_then(Json::as_str) == Some("decimal");
+                        Ok(self.push(match node {
+                            Schema::Bytes { .. } if decimal => Schema::Bytes { scanned: false },
+                            node => node,
+                        }))
+                    }
+                }
+            }
+            _ => Err(invalid_schema()),
+        }
+    }
+
+    /// Decode a value of the node, passing every string and bytes value in it to `found` with
+    /// its field path, `path` being the path of the value
+    fn walk(
+        &self,
+        node: usize,
+        decoder: &mut Decoder<'_>,
+        path: &mut String,
+        depth: usize,
+        found: &mut impl FnMut(&str, &[u8]),
+    ) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(invalid(format!(
+                "Avro values nested more than {} levels deep",
+                MAX_DEPTH
+            )));
+        }
+        match &self.nodes[node] {
+            Schema::Null => {}
+            Schema::Boolean => decoder.skip(1)?,
+            Schema::Long | Schema::Enum => {
+                decoder.long()?;
+            }
+            Schema::Float => decoder.skip(4)?,
+            Schema::Double => decoder.skip(8)?,
+            Schema::Fixed(size) => decoder.skip(*size)?,
+            Schema::Bytes { scanned } => {
+                let value = decoder.bytes()?;
+                if *scanned {
+                    found(path, value);
+                }
+            }
+            Schema::Record(fields) => {
+                for (name, field) in fields {
+                    let len = path.len();
+                    if len > 0 {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                    self.walk(*field, decoder, path, depth + 1, found)?;
+                    path.truncate(len);
+                }
+            }
+            Schema::Array(items) | Schema::Map(items) => {
+                let map = matches!(self.nodes[node], Schema::Map(_));
+                let mut idx = 0i64;
+                loop {
+                    let mut count = decoder.long()?;
+                    if count == 0 {
+                        break;
+                    }
+                    if count < 0 {
+                        // Negative counts are followed by the size of the block in bytes
+                        count = count.checked_neg().ok_or_else(truncated)?;
+                        decoder.long()?;
+                    }
+                    for done in 1..=count {
+                        let pos = decoder.pos;
+                        let len = path.len();
+                        if map {
+                            let key = decoder.bytes()?;
+                            let _ = write!(path, "[{}]", String::from_utf8_lossy(key));
+                        } else {
+                            let _ = write!(path, "[{}]", idx);
+                        }
+                        self.walk(*items, decoder, path, depth + 1, found)?;
+                        path.truncate(len);
+                        idx += 1;
+                        if decoder.pos == pos {
+                            // Items taking no bytes hold no strings, and neither do the next
+                            // ones
+                            idx = idx.saturating_add(count - done);
+                            break;
+                        }
+                    }
+                }
+            }
+            Schema::Union(branches) => {
+                let branch = decoder.long()?;
+                let branch = usize::try_from(branch)
+                    .ok()
+                    .and_then(|branch| branches.get(branch))
+                    .ok_or_else(|| invalid(format!("Invalid Avro union branch {}", branch)))?;
+                self.walk(*branch, decoder, path, depth + 1, found)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Node of a primitive type
+fn primitive(name: &str) -> Option<Schema> {
+    Some(match name {
+        "null" => Schema::Null,
+        "boolean" => Schema::Boolean,
+        "int" | "long" => Schema::Long,
+        "float" => Schema::Float,
+        "double" => Schema::Double,
+        "bytes" | "string" => Schema::Bytes { scanned: true },
+        _ => return None,
+    })
+}
+
+/// Full name of a named type, in `namespace` unless the name has one
+fn full_name(name: &str, namespace: &str) -> String {
+    if name.contains('.') || namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
+}
+
+/// Reader of the values of a decompressed block
+struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Zigzag varint of an int or a long
+    fn long(&mut self) -> Result<i64> {
+        let mut value = 0u64;
+        for shift in (0..70).step_by(7) {
+            let byte = *self.buf.get(self.pos).ok_or_else(truncated)?;
+            self.pos += 1;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        Err(invalid("Invalid Avro varint".to_string()))
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Length-prefixed bytes or string
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.long()?;
+        let len =
+            usize::try_from(len).map_err(|_| invalid(format!("Invalid Avro length {}", len)))?;
+        self.take(len)
+    }
+}
+
+/// Read the zigzag varint of a long from a file, None at the end of the file
+fn read_long(reader: &mut (impl Read + ?Sized)) -> Result<Option<i64>> {
+    let mut value = 0u64;
+    for shift in (0..70).step_by(7) {
+        let mut byte = [0];
+        if reader.read(&mut byte)? == 0 {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err(truncated())
+            };
+        }
+        value |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some((value >> 1) as i64 ^ -((value & 1) as i64)));
+        }
+    }
+    Err(invalid("Invalid Avro varint".to_string()))
+}
+
+/// Read `len` bytes, the buffer growing with the bytes read rather than trusting the length
+fn read_exact(reader: &mut (impl Read + ?Sized), len: usize) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(truncated());
+    }
+    Ok(bytes)
+}
+
+/// Read the metadata map of the file header
+fn read_metadata(reader: &mut impl Read) -> Result<HashMap<String, Vec<u8>>> {
+    let read_bytes = |reader: &mut dyn Read| -> Result<Vec<u8>> {
+        let len = read_long(reader)?.ok_or_else(truncated)?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= MAX_BLOCK_SIZE)
+            .ok_or_else(|| invalid(format!("Invalid Avro length {}", len)))?;
+        read_exact(reader, len)
+    };
+    let mut metadata = HashMap::new();
+    loop {
+        let mut count = read_long(reader)?.ok_or_else(truncated)?;
+        if count == 0 {
+            return Ok(metadata);
+        }
+        if count < 0 {
+            count = count.checked_neg().ok_or_else(truncated)?;
+            read_long(reader)?;
+        }
+        for _ in 0..count {
+            let key = read_bytes(reader)?;
+            let value = read_bytes(reader)?;
+            metadata.insert(String::from_utf8_lossy(&key).into_owned(), value);
+        }
+    }
+}
+
+/// Decompress a block with the codec of the file, to at most `max_len` bytes
+fn decompress(codec: &str, block: Vec<u8>, max_len: usize) -> Result<Vec<u8>> {
+    match codec {
+        "deflate" => {
+            let mut data = Vec::new();
+            codecs::decode(
+                DeflateDecoder::new(block.as_slice()),
+                &mut data,
+                max_len,
+                "Avro deflate",
+            )?;
+            Ok(data)
+        }
+        "snappy" => {
+            // The block ends with the big-endian CRC-32 of its decompressed bytes
+            let split = block.len().checked_sub(4).ok_or_else(truncated)?;
+            let data = codecs::snappy(&block[..split], max_len)?;
+            let crc = u32::from_be_bytes([
+                block[split],
+                block[split + 1],
+                block[split + 2],
+                block[split + 3],
+            ]);
+            if crc32fast::hash(&data) != crc {
+                return Err(invalid("Avro block failing its CRC check".to_string()));
+            }
+            Ok(data)
+        }
+        _ => Ok(block),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn truncated() -> io::Error {
+    invalid("Truncated Avro file".to_string())
+}
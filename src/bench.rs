@@ -0,0 +1,215 @@
+//! Benchmarks of engines, chunk sizes and thread counts on sample data, to pick a
+//! configuration from measurements rather than guesses
+
+use crate::engine::Engine;
+use crate::matcher::{ConfigError, Matcher, MatcherOptions};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::time::{Duration, Instant};
+
+/// One configuration to benchmark
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BenchConfig {
+    pub engine: Engine,
+    /// Bytes per chunk of a parallel chunked scan, None for the tuned size when `n_threads`
+    /// is set and a single pass over the data otherwise
+    pub chunk_size: Option<usize>,
+    /// Threads of the chunked scan, None for the global thread pool when `chunk_size` is set
+    /// and a single pass over the data otherwise
+    pub n_threads: Option<usize>,
+}
+
+impl BenchConfig {
+    fn chunked(&self) -> bool {
+        self.chunk_size.is_some() || self.n_threads.is_some()
+    }
+}
+
+/// Timings of one configuration
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct BenchResult {
+    pub config: BenchConfig,
+    /// Engine `config.engine` resolved to, as picked by Engine::Auto
+    pub resolved_engine: Engine,
+    /// Chunk size the scan ran with, the tuned one when not configured
+    pub chunk_size: Option<usize>,
+    pub build_time: Duration,
+    /// Wall time of every run
+    pub times: Vec<Duration>,
+    pub matches: usize,
+    pub bytes: usize,
+}
+
+impl BenchResult {
+    /// Fastest run
+    pub fn best(&self) -> Duration {
+        self.times.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.times.is_empty() {
+            return Duration::ZERO;
+        }
+        self.times.iter().sum::<Duration>() / self.times.len() as u32
+    }
+
+    /// Bytes per second of the fastest run
+    pub fn throughput(&self) -> f64 {
+        let secs = self.best().as_secs_f64();
+        if secs > 0.0 {
+            self.bytes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Build a matcher of `patterns` for every configuration and scan `data` with it `repeat`
+/// times, after a warmup run
+/// Configurations of engines that cannot search the pattern set, or are not compiled in, fail
+/// the whole benchmark
+pub fn benchmark(
+    patterns: &[String],
+    options: &MatcherOptions,
+    data: &[u8],
+    configs: &[BenchConfig],
+    repeat: usize,
+) -> Result<Vec<BenchResult>, ConfigError> {
+    if repeat == 0 {
+        return Err(ConfigError::Invalid(
+            "Benchmarks need at least one run".to_string(),
+        ));
+    }
+    configs
+        .iter()
+        .map(|config| {
+            let matcher = Matcher::new(
+                patterns.to_vec(),
+                MatcherOptions {
+                    engine: config.engine,
+                    ..options.clone()
+                },
+            )?;
+            let chunk_size = config
+                .chunk_size
+                .or_else(|| {
+                    config
+                        .chunked()
+                        .then(|| matcher.tune(data.len(), config.n_threads).chunk_size)
+                })
+                .map(|size| size.max(1));
+            let pool = match config.n_threads {
+                Some(threads) => Some(
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(threads)
+                        .build()
+                        .map_err(|e| ConfigError::Invalid(e.to_string()))?,
+                ),
+                None => None,
+            };
+            let scan = || match chunk_size {
+                Some(chunk_size) => matcher.match_bytes_chunked(data, chunk_size).len(),
+                None => matcher.match_bytes(data).len(),
+            };
+            let run = || match &pool {
+                Some(pool) => pool.install(scan),
+                None => scan(),
+            };
+
+            let matches = run();
+            let times = (0..repeat)
+                .map(|_| {
+                    let started = Instant::now();
+                    run();
+                    started.elapsed()
+                })
+                .collect();
+            Ok(BenchResult {
+                config: *config,
+                resolved_engine: matcher.engine(),
+                chunk_size,
+                build_time: matcher.build_time(),
+                times,
+                matches,
+                bytes: data.len(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BenchResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "BenchResult(engine='{}', chunk_size={}, n_threads={}, best={:.6}, throughput={:.0}, matches={})",
+            self.config.engine.name(),
+            self.chunk_size
+                .map_or_else(|| "None".to_string(), |size| size.to_string()),
+            self.config
+                .n_threads
+                .map_or_else(|| "None".to_string(), |threads| threads.to_string()),
+            self.best().as_secs_f64(),
+            self.throughput(),
+            self.matches
+        )
+    }
+
+    /// Engine as configured
+    #[getter]
+    fn engine(&self) -> &'static str {
+        self.config.engine.name()
+    }
+
+    /// Engine that ran, the one picked when configured as "auto"
+    #[getter]
+    fn resolved_engine(&self) -> &'static str {
+        self.resolved_engine.name()
+    }
+
+    /// Bytes per chunk, None for single-pass scans
+    #[getter]
+    fn chunk_size(&self) -> Option<usize> {
+        self.chunk_size
+    }
+
+    #[getter]
+    fn n_threads(&self) -> Option<usize> {
+        self.config.n_threads
+    }
+
+    /// Time to build the matcher in seconds
+    #[getter]
+    fn build_time(&self) -> f64 {
+        self.build_time.as_secs_f64()
+    }
+
+    /// Wall time of every run in seconds
+    #[getter]
+    fn times(&self) -> Vec<f64> {
+        self.times.iter().map(Duration::as_secs_f64).collect()
+    }
+
+    /// Fastest run in seconds
+    #[getter(best)]
+    fn py_best(&self) -> f64 {
+        self.best().as_secs_f64()
+    }
+
+    #[getter(mean)]
+    fn py_mean(&self) -> f64 {
+        self.mean().as_secs_f64()
+    }
+
+    /// Bytes per second of the fastest run
+    #[getter(throughput)]
+    fn py_throughput(&self) -> f64 {
+        self.throughput()
+    }
+
+    #[getter]
+    fn matches(&self) -> usize {
+        self.matches
+    }
+}
@@ -0,0 +1,187 @@
+//! Memory budget of the Aho-Corasick automaton of a matcher, checked against an estimate of its
+//! size before it is built, so a dictionary of millions of patterns fails fast or falls back to
+//! a compact automaton instead of exhausting the memory of the worker during the build
+
+use crate::matcher::ConfigError;
+use aho_corasick::AhoCorasickKind;
+
+/// Bytes of a transition, a premultiplied state ID
+const TRANSITION_BYTES: usize = 4;
+/// Bytes the DFA keeps per pattern, the match list of its final state and its length
+const DFA_PATTERN_BYTES: usize = 32;
+/// Bytes of a contiguous NFA state with its single sparse transition, twice that when ASCII
+/// case folding adds a transition for the other case
+const NFA_STATE_BYTES: usize = 12;
+const NFA_FOLDED_STATE_BYTES: usize = 20;
+/// Depth up to which the contiguous NFA stores dense states, one transition per byte class
+const NFA_DENSE_DEPTH: usize = 2;
+/// Bytes the contiguous NFA keeps per pattern, its match in the final state and its length
+const NFA_PATTERN_BYTES: usize = 16;
+
+/// What a build does when the automaton would exceed `max_memory_bytes`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnMemoryLimit {
+    /// Fail the build with a limit error
+    #[default]
+    Error,
+    /// Build a contiguous NFA instead of the DFA, slower to search but a fraction of its size,
+    /// failing only when that does not fit either
+    Downgrade,
+}
+
+impl OnMemoryLimit {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "downgrade" => Some(Self::Downgrade),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Downgrade => "downgrade",
+        }
+    }
+}
+
+/// Shape of the pattern trie the automata are built from
+pub(crate) struct TrieShape {
+    /// States of the trie, one per distinct pattern prefix (ASCII-folded when
+    /// case-insensitive) plus the root
+    pub(crate) states: usize,
+    /// States up to `NFA_DENSE_DEPTH`, the root included
+    shallow_states: usize,
+    /// Byte classes of the automata: one per byte of the patterns, both cases of the folded
+    /// letters, and one per run of the other bytes
+    alphabet_len: usize,
+    patterns: usize,
+    case_insensitive: bool,
+}
+
+impl TrieShape {
+    pub(crate) fn new<S: AsRef<str>>(patterns: &[S], case_insensitive: bool) -> Self {
+        let fold = |b: &u8| {
+            if case_insensitive {
+                b.to_ascii_lowercase()
+            } else {
+                *b
+            }
+        };
+        let mut bytes = [false; 256];
+        for &b in patterns.iter().flat_map(|p| p.as_ref().as_bytes()) {
+            bytes[b as usize] = true;
+            if case_insensitive {
+                bytes[b.to_ascii_lowercase() as usize] = true;
+                bytes[b.to_ascii_uppercase() as usize] = true;
+            }
+        }
+
+        // Sorted by reference, a dictionary of millions of patterns is not copied to be measured
+        let mut sorted: Vec<&[u8]> = patterns.iter().map(|p| p.as_ref().as_bytes()).collect();
+        sorted.sort_unstable_by(|a, b| a.iter().map(fold).cmp(b.iter().map(fold)));
+
+        // Each sorted pattern adds the states past its longest common prefix with the previous one
+        let mut states = 1;
+        let mut shallow_states = 1;
+        let mut previous: &[u8] = &[];
+        for &pattern in &sorted {
+            let common = pattern
+                .iter()
+                .zip(previous)
+                .take_while(|(a, b)| fold(a) == fold(b))
+                .count();
+            states += pattern.len() - common;
+            shallow_states += pattern.len().min(NFA_DENSE_DEPTH).saturating_sub(common);
+            previous = pattern;
+        }
+
+        Self {
+            states,
+            shallow_states,
+            alphabet_len: 1
+                + (1..bytes.len())
+                    .filter(|&b| bytes[b] || bytes[b - 1])
+                    .count(),
+            patterns: patterns.len(),
+            case_insensitive,
+        }
+    }
+
+    /// Estimated heap bytes of the automaton of this kind, within a few percent of the
+    /// `memory_usage()` of a DFA, and usually above it for a contiguous NFA
+    pub(crate) fn estimate(&self, kind: AhoCorasickKind) -> usize {
+        match kind {
+            AhoCorasickKind::DFA => {
+                // Rows are padded to a power of two so state IDs can be premultiplied
+                let stride = self.alphabet_len.next_power_of_two();
+                self.states * stride * TRANSITION_BYTES + self.patterns * DFA_PATTERN_BYTES
+            }
+            _ => {
+                let state_bytes = if self.case_insensitive {
+                    NFA_FOLDED_STATE_BYTES
+                } else {
+                    NFA_STATE_BYTES
+                };
+                self.states * state_bytes
+                    + self.shallow_states * self.alphabet_len * TRANSITION_BYTES
+                    + self.patterns * NFA_PATTERN_BYTES
+            }
+        }
+    }
+}
+
+/// Largest automaton a build may produce, and what to do about a larger one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct MemoryBudget {
+    pub(crate) max_bytes: usize,
+    pub(crate) on_limit: OnMemoryLimit,
+}
+
+impl MemoryBudget {
+    /// Kind of automaton to build for the patterns: `preferred` when its estimate fits, a
+    /// contiguous NFA in place of an oversized DFA when downgrading, an error otherwise
+    pub(crate) fn kind<S: AsRef<str>>(
+        &self,
+        patterns: &[S],
+        case_insensitive: bool,
+        preferred: AhoCorasickKind,
+    ) -> Result<AhoCorasickKind, ConfigError> {
+        let shape = TrieShape::new(patterns, case_insensitive);
+        let estimate = shape.estimate(preferred);
+        if estimate <= self.max_bytes {
+            return Ok(preferred);
+        }
+
+        let compact = AhoCorasickKind::ContiguousNFA;
+        if preferred == compact {
+            return Err(ConfigError::Limit(format!(
+                "The contiguous NFA of {} patterns would take about {} bytes, over max_memory_bytes={}",
+                shape.patterns, estimate, self.max_bytes
+            )));
+        }
+
+        let compact_estimate = shape.estimate(compact);
+        match (self.on_limit, compact_estimate <= self.max_bytes) {
+            (OnMemoryLimit::Downgrade, true) => Ok(compact),
+            (OnMemoryLimit::Error, true) => Err(ConfigError::Limit(format!(
+                "The DFA of {} patterns would take about {} bytes, over max_memory_bytes={}; on_memory_limit='downgrade' builds a contiguous NFA of about {} bytes",
+                shape.patterns, estimate, self.max_bytes, compact_estimate
+            ))),
+            (_, false) => Err(ConfigError::Limit(format!(
+                "The DFA of {} patterns would take about {} bytes and their contiguous NFA about {}, over max_memory_bytes={}",
+                shape.patterns, estimate, compact_estimate, self.max_bytes
+            ))),
+        }
+    }
+}
+
+/// Short name of an automaton kind, as reported by `Matcher::automaton`
+pub(crate) fn kind_name(kind: AhoCorasickKind) -> &'static str {
+    match kind {
+        AhoCorasickKind::DFA => "dfa",
+        AhoCorasickKind::ContiguousNFA => "contiguous_nfa",
+        _ => "noncontiguous_nfa",
+    }
+}
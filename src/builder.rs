@@ -0,0 +1,331 @@
+//! Step-by-step construction of a Matcher: patterns gathered from several sources with their
+//! metadata, rewritten by normalizers and checked by validators, every problem reported at once
+//! by `build()` rather than one constructor argument at a time
+
+use crate::engine::Engine;
+use crate::matcher::{ConfigError, Matcher, MatcherOptions, PatternMetadata};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// Validation failures listed in a build error before the rest are only counted
+const MAX_REPORTED_FAILURES: usize = 10;
+
+/// Rewrite applied to every pattern before it is validated and compiled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternNormalizer {
+    /// Strip leading and trailing whitespace
+    Trim,
+    /// Lowercase the pattern
+    Lowercase,
+    /// Replace runs of whitespace by a single space
+    CollapseWhitespace,
+}
+
+impl PatternNormalizer {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "trim" => Some(Self::Trim),
+            "lower" => Some(Self::Lowercase),
+            "collapse_whitespace" => Some(Self::CollapseWhitespace),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Trim => "trim",
+            Self::Lowercase => "lower",
+            Self::CollapseWhitespace => "collapse_whitespace",
+        }
+    }
+
+    fn apply(self, pattern: &str) -> String {
+        match self {
+            Self::Trim => pattern.trim().to_string(),
+            Self::Lowercase => pattern.to_lowercase(),
+            Self::CollapseWhitespace => pattern.split_whitespace().collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+/// Check every pattern must pass, run on the normalized patterns
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternValidator {
+    /// Reject empty patterns, which the matcher would otherwise drop silently
+    NonEmpty,
+    /// Reject patterns shorter than this many bytes
+    MinLength(usize),
+    /// Reject patterns longer than this many bytes
+    MaxLength(usize),
+    /// Reject patterns with non-ASCII characters
+    Ascii,
+    /// Reject patterns listed more than once
+    Unique,
+}
+
+impl PatternValidator {
+    /// Validator from its name and, for the length limits, its argument
+    pub fn parse(name: &str, arg: Option<usize>) -> Option<Self> {
+        match (name, arg) {
+            ("non_empty", None) => Some(Self::NonEmpty),
+            ("min_length", Some(n)) => Some(Self::MinLength(n)),
+            ("max_length", Some(n)) => Some(Self::MaxLength(n)),
+            ("ascii", None) => Some(Self::Ascii),
+            ("unique", None) => Some(Self::Unique),
+            _ => None,
+        }
+    }
+
+    /// Reason the pattern fails the check, if it does
+    fn check(self, pattern: &str, seen: &mut HashSet<String>) -> Option<String> {
+        match self {
+            Self::NonEmpty if pattern.is_empty() => Some("is empty".to_string()),
+            Self::MinLength(n) if pattern.len() < n => Some(format!("is shorter than {} bytes", n)),
+            Self::MaxLength(n) if pattern.len() > n => Some(format!("is longer than {} bytes", n)),
+            Self::Ascii if !pattern.is_ascii() => Some("is not ASCII".to_string()),
+            Self::Unique if !seen.insert(pattern.to_string()) => Some("is a duplicate".to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Builder of a Matcher, collecting patterns and options through chained calls
+///
+/// Nothing is checked until `prepare()` or `build()`, which run the normalizers in the order
+/// they were added, then every validator on every pattern, and fail with all the rejected
+/// patterns at once before handing the rest to `Matcher::new`.
+#[derive(Clone, Debug, Default)]
+pub struct MatcherBuilder {
+    patterns: Vec<String>,
+    metadata: Vec<PatternMetadata>,
+    tags: Vec<Vec<String>>,
+    options: MatcherOptions,
+    normalizers: Vec<PatternNormalizer>,
+    validators: Vec<PatternValidator>,
+}
+
+impl MatcherBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a pattern without metadata
+    pub fn pattern(self, pattern: impl Into<String>) -> Self {
+        self.pattern_with_metadata(pattern, PatternMetadata::new())
+    }
+
+    /// Add a pattern along with its metadata
+    pub fn pattern_with_metadata(
+        self,
+        pattern: impl Into<String>,
+        metadata: PatternMetadata,
+    ) -> Self {
+        self.tagged_pattern(pattern, metadata, Vec::new())
+    }
+
+    /// Add a pattern along with its metadata and the tags scans can be restricted to
+    pub fn tagged_pattern(
+        mut self,
+        pattern: impl Into<String>,
+        metadata: PatternMetadata,
+        tags: Vec<String>,
+    ) -> Self {
+        self.patterns.push(pattern.into());
+        self.metadata.push(metadata);
+        self.tags.push(tags);
+        self
+    }
+
+    /// Add patterns sharing the same metadata
+    pub fn patterns<I, S>(self, patterns: I, metadata: &PatternMetadata) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tagged_patterns(patterns, metadata, &[])
+    }
+
+    /// Add patterns sharing the same metadata and tags
+    pub fn tagged_patterns<I, S>(
+        mut self,
+        patterns: I,
+        metadata: &PatternMetadata,
+        tags: &[String],
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for pattern in patterns {
+            self = self.tagged_pattern(pattern, metadata.clone(), tags.to_vec());
+        }
+        self
+    }
+
+    /// Add one pattern per line of a reader, skipping blank lines and `#` comments
+    pub fn patterns_from_reader(
+        self,
+        reader: impl BufRead,
+        metadata: &PatternMetadata,
+    ) -> io::Result<Self> {
+        Ok(self.patterns(read_patterns(reader)?, metadata))
+    }
+
+    /// Add one pattern per line of a file, skipping blank lines and `#` comments
+    pub fn patterns_from_file(
+        self,
+        path: impl AsRef<Path>,
+        metadata: &PatternMetadata,
+    ) -> io::Result<Self> {
+        let file = File::open(path)?;
+        self.patterns_from_reader(BufReader::new(file), metadata)
+    }
+
+    /// Replace every option, the metadata and tags excepted as they follow the added patterns
+    pub fn options(mut self, options: MatcherOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.options.engine = engine;
+        self
+    }
+
+    pub fn overlapping(mut self, overlapping: bool) -> Self {
+        self.options.overlapping = overlapping;
+        self
+    }
+
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.options.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.options.whole_word = whole_word;
+        self
+    }
+
+    /// Rewrite every pattern, after the normalizers added before
+    pub fn normalize(mut self, normalizer: PatternNormalizer) -> Self {
+        self.normalizers.push(normalizer);
+        self
+    }
+
+    /// Check every pattern once normalized
+    pub fn validate(mut self, validator: PatternValidator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Number of patterns added so far
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Normalized patterns along with the validation failures found in them
+    pub(crate) fn normalized(&self) -> (Vec<String>, Vec<String>) {
+        let patterns: Vec<String> = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                self.normalizers
+                    .iter()
+                    .fold(pattern.clone(), |pattern, normalizer| {
+                        normalizer.apply(&pattern)
+                    })
+            })
+            .collect();
+
+        let mut failures = Vec::new();
+        for validator in &self.validators {
+            let mut seen = HashSet::new();
+            for (idx, pattern) in patterns.iter().enumerate() {
+                if let Some(reason) = validator.check(pattern, &mut seen) {
+                    failures.push(failure(idx, pattern, &reason));
+                }
+            }
+        }
+        (patterns, failures)
+    }
+
+    /// Normalized patterns and their metadata, or every validation failure
+    pub fn prepare(&self) -> Result<(Vec<String>, Vec<PatternMetadata>), ConfigError> {
+        let (patterns, failures) = self.normalized();
+        if !failures.is_empty() {
+            return Err(invalid_patterns(&failures));
+        }
+        Ok((patterns, self.metadata.clone()))
+    }
+
+    /// Metadata of each pattern, in the order they were added
+    pub fn metadata(&self) -> &[PatternMetadata] {
+        &self.metadata
+    }
+
+    /// Tags of each pattern, in the order they were added
+    pub fn tags(&self) -> &[Vec<String>] {
+        &self.tags
+    }
+
+    pub fn normalizers(&self) -> &[PatternNormalizer] {
+        &self.normalizers
+    }
+
+    pub fn validators(&self) -> &[PatternValidator] {
+        &self.validators
+    }
+
+    /// Normalize and validate the patterns, then build the matcher
+    pub fn build(self) -> Result<Matcher, ConfigError> {
+        let (patterns, metadata) = self.prepare()?;
+        let options = MatcherOptions {
+            metadata: Some(metadata),
+            tags: Some(self.tags),
+            ..self.options
+        };
+        Matcher::new(patterns, options)
+    }
+}
+
+/// Patterns of a pattern file, one per line, without blank lines and `#` comments
+pub(crate) fn read_patterns(reader: impl BufRead) -> io::Result<Vec<String>> {
+    let mut patterns = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        patterns.push(line.to_string());
+    }
+    Ok(patterns)
+}
+
+/// Validation failure of a pattern, as listed in build errors
+pub(crate) fn failure(idx: usize, pattern: &str, reason: &str) -> String {
+    format!("pattern {} '{}' {}", idx, pattern, reason)
+}
+
+/// Error listing the first validation failures and counting the rest
+pub(crate) fn invalid_patterns(failures: &[String]) -> ConfigError {
+    let mut message = format!(
+        "Patterns failed validation ({}): {}",
+        failures.len(),
+        failures[..failures.len().min(MAX_REPORTED_FAILURES)].join(", ")
+    );
+    if failures.len() > MAX_REPORTED_FAILURES {
+        message.push_str(&format!(
+            " and {} more",
+            failures.len() - MAX_REPORTED_FAILURES
+        ));
+    }
+    ConfigError::Invalid(message)
+}
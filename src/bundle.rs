@@ -0,0 +1,473 @@
+//! Matcher bundles: the patterns, per-pattern settings and options of a matcher compiled and
+//! validated ahead of time, in CI, written to one gzipped JSON file that runtime agents load
+//! without going through pattern files, normalizers and validators again
+//!
+//! The automaton itself is rebuilt on load, aho-corasick automata cannot be serialized, and
+//! the loaded matcher must have the fingerprint recorded when the bundle was written, so a
+//! bundle only loads into the voluta version that wrote it and finds the same matches.
+//!
+//! The content hash recorded next to the fingerprint is checked on load, catching bundles
+//! corrupted or edited after they were written. Whoever can write the bundle can record a new
+//! hash too, so only a hash pinned out of band, `Bundle::check_hash`, guards against tampering.
+//!
+//! A bundle can also be signed with an Ed25519 key held by the CI that compiles it, the
+//! signature covering the content hash. Agents given the public key check it with
+//! `Bundle::verify`, which needs no hash pinned per bundle.
+
+use crate::budget::OnMemoryLimit;
+use crate::codecs::DEFAULT_MAX_DECODED_SIZE;
+use crate::encoding::Encoding;
+use crate::engine::Engine;
+use crate::matcher::{
+    fingerprint, hex, ConfigError, InvalidUtf8, Matcher, MatcherOptions, PatternMetadata, WordChars,
+};
+use crate::mmap::{Madvise, MmapOptions};
+use crate::postprocess::PostProcess;
+use crate::progress::ProgressHook;
+use crate::sink::OutputSchema;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Value of the "format" field of bundles
+const FORMAT: &str = "voluta-bundle";
+
+/// Version of the bundle layout, bumped when fields change meaning
+const FORMAT_VERSION: u32 = 2;
+
+/// Bytes of Ed25519 signing keys (their seed) and verify keys
+pub const KEY_LEN: usize = 32;
+
+/// What a bundle holds, as recorded when it was written
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleInfo {
+    /// See `Matcher::fingerprint`
+    pub fingerprint: String,
+    /// Hex SHA-256 of the patterns, metadata, tags and options, see `Bundle::check_hash`
+    // Absent from version 1 bundles, which fail the format version check instead
+    #[serde(default)]
+    pub content_hash: String,
+    /// Version of voluta that wrote the bundle, the only one that loads it
+    pub voluta_version: String,
+    pub pattern_count: usize,
+    pub state_count: usize,
+    /// Engine the matcher searched with, which the loaded matcher searches with too
+    pub engine: String,
+    /// See `Matcher::automaton`
+    pub automaton: Option<String>,
+    /// Heap bytes of the matcher that was bundled
+    pub memory_usage: usize,
+    /// Whether the bundle carries a signature, checked by `Bundle::verify` only
+    #[serde(default)]
+    pub signed: bool,
+}
+
+/// Options of a bundled matcher, the runtime hooks and logs excepted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BundleOptions {
+    overlapping: bool,
+    case_insensitive: bool,
+    whole_word: bool,
+    word_chars: String,
+    postprocess: Option<String>,
+    priorities: Option<Vec<i64>>,
+    prefer_longer: bool,
+    fuzzy: Option<Vec<usize>>,
+    ignore_separators: bool,
+    min_counts: Option<Vec<usize>>,
+    exclude: Option<Vec<String>>,
+    line_filter: Option<Vec<String>>,
+    invalid_utf8: String,
+    encoding: String,
+    engine: String,
+    max_memory_bytes: Option<usize>,
+    on_memory_limit: String,
+    // Absent from the bundles written before the limit was configurable
+    #[serde(default = "default_max_decoded_size")]
+    max_decoded_size: usize,
+    madvise: String,
+    huge_pages: bool,
+    prefault: bool,
+    schema_version: u32,
+}
+
+fn default_max_decoded_size() -> usize {
+    DEFAULT_MAX_DECODED_SIZE
+}
+
+/// Compiled matcher as written to a bundle file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bundle {
+    format: String,
+    format_version: u32,
+    info: BundleInfo,
+    patterns: Vec<String>,
+    metadata: Vec<PatternMetadata>,
+    tags: Vec<Vec<String>>,
+    options: BundleOptions,
+    /// Hex Ed25519 signature of the content hash, see `Bundle::sign`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+impl Bundle {
+    /// Bundle of a matcher, whose patterns are kept as they were compiled, empty patterns
+    /// being dropped
+    /// Matchers built with a hash_key are rejected, the key would be written in the bundle
+    pub fn new(matcher: &Matcher) -> Result<Self, ConfigError> {
+        if matcher.hashed() {
+            return Err(ConfigError::Invalid(
+                "Matchers built with a hash_key cannot be bundled, the key would be written in \
+                 the bundle"
+                    .to_string(),
+            ));
+        }
+
+        let picks: Vec<(&Matcher, usize)> = (0..matcher.patterns().len())
+            .map(|idx| (matcher, idx))
+            .collect();
+        let (patterns, options) = matcher.picked_options(&picks);
+        let fingerprint = fingerprint(&patterns, &options);
+        let metadata = options.metadata.clone().unwrap_or_default();
+        let tags = options.tags.clone().unwrap_or_default();
+        let bundle_options = BundleOptions {
+            overlapping: options.overlapping,
+            case_insensitive: options.case_insensitive,
+            whole_word: options.whole_word,
+            word_chars: options.word_chars.spec(),
+            postprocess: options.postprocess.map(|p| p.name().to_string()),
+            priorities: options.priorities,
+            prefer_longer: options.prefer_longer,
+            fuzzy: options.fuzzy,
+            ignore_separators: options.ignore_separators,
+            min_counts: options.min_counts,
+            exclude: options.exclude,
+            line_filter: options.line_filter,
+            invalid_utf8: options.invalid_utf8.name().to_string(),
+            encoding: options.encoding.name().to_string(),
+            engine: matcher.engine().name().to_string(),
+            max_memory_bytes: options.max_memory_bytes,
+            on_memory_limit: options.on_memory_limit.name().to_string(),
+            max_decoded_size: options.max_decoded_size,
+            madvise: options.mmap.madvise.name().to_string(),
+            huge_pages: options.mmap.huge_pages,
+            prefault: options.mmap.prefault,
+            schema_version: options.output_schema.version(),
+        };
+        let info = BundleInfo {
+            fingerprint,
+            content_hash: content_hash(&patterns, &metadata, &tags, &bundle_options),
+            voluta_version: env!("CARGO_PKG_VERSION").to_string(),
+            pattern_count: patterns.len(),
+            state_count: matcher.state_count(),
+            engine: matcher.engine().name().to_string(),
+            automaton: matcher.automaton().map(str::to_string),
+            memory_usage: matcher.memory_usage(),
+            signed: false,
+        };
+        Ok(Self {
+            format: FORMAT.to_string(),
+            format_version: FORMAT_VERSION,
+            info,
+            patterns,
+            metadata,
+            tags,
+            options: bundle_options,
+            signature: None,
+        })
+    }
+
+    /// Sign the content hash with the Ed25519 key of `signing_key`, its 32-byte seed
+    pub fn sign(&mut self, signing_key: &[u8]) -> Result<(), ConfigError> {
+        let key_pair = key_pair(signing_key)?;
+        let signature = key_pair.sign(self.info.content_hash.as_bytes());
+        self.signature = Some(hex(signature.as_ref()));
+        self.info.signed = true;
+        Ok(())
+    }
+
+    pub fn info(&self) -> &BundleInfo {
+        &self.info
+    }
+
+    /// Write the bundle as gzipped JSON
+    pub fn write(&self, writer: impl Write) -> io::Result<()> {
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.finish()?.flush()
+    }
+
+    /// Write the bundle to a file, through a temporary file renamed over it once complete, so
+    /// agents never load a partly written bundle
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let res = File::create(&partial).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            self.write(&mut writer)?;
+            writer.into_inner().map_err(|e| e.into_error())?.sync_all()
+        });
+        match res.and_then(|()| fs::rename(&partial, path)) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let _ = fs::remove_file(&partial);
+                Err(e)
+            }
+        }
+    }
+
+    /// Read a bundle written by `write`, failing with InvalidData if the bytes are not one,
+    /// were written by another voluta version or do not have the recorded content hash
+    pub fn read(reader: impl Read) -> io::Result<Self> {
+        let bundle: Self = serde_json::from_reader(GzDecoder::new(reader))
+            .map_err(|e| invalid_bundle(format!("Invalid matcher bundle: {}", e)))?;
+        if bundle.format != FORMAT || bundle.format_version != FORMAT_VERSION {
+            return Err(invalid_bundle(format!(
+                "Unsupported bundle format '{}' version {}, expected '{}' version {}",
+                bundle.format, bundle.format_version, FORMAT, FORMAT_VERSION
+            )));
+        }
+        if bundle.info.voluta_version != env!("CARGO_PKG_VERSION") {
+            return Err(invalid_bundle(format!(
+                "The bundle was compiled by voluta {}, recompile it with voluta {}",
+                bundle.info.voluta_version,
+                env!("CARGO_PKG_VERSION")
+            )));
+        }
+        let hash = content_hash(
+            &bundle.patterns,
+            &bundle.metadata,
+            &bundle.tags,
+            &bundle.options,
+        );
+        if hash != bundle.info.content_hash {
+            return Err(invalid_bundle(format!(
+                "The bundle has content hash {} instead of the recorded {}, it was modified \
+                 after it was written",
+                hash, bundle.info.content_hash
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// Check the content hash against one pinned out of band, by the CI that compiled the
+    /// bundle, failing with InvalidData if they differ
+    /// The recorded hash only catches corruption and accidental edits, whoever can write the
+    /// bundle can rewrite it with the matching hash
+    pub fn check_hash(&self, expected: &str) -> io::Result<()> {
+        if !self.info.content_hash.eq_ignore_ascii_case(expected) {
+            return Err(invalid_bundle(format!(
+                "The bundle has content hash {}, expected {}",
+                self.info.content_hash, expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Ed25519 verify key of the 32-byte seed `signing_key`, handed to the agents checking
+    /// the bundles it signs
+    pub fn verify_key(signing_key: &[u8]) -> Result<Vec<u8>, ConfigError> {
+        Ok(key_pair(signing_key)?.public_key().as_ref().to_vec())
+    }
+
+    /// Check the signature against the 32-byte Ed25519 `verify_key`, failing with InvalidData
+    /// if the bundle is not signed or was not signed by its key
+    pub fn verify(&self, verify_key: &[u8]) -> io::Result<()> {
+        check_key_len("verify_key", verify_key)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        let Some(signature) = &self.signature else {
+            return Err(invalid_bundle("The bundle is not signed".to_string()));
+        };
+        let signature = unhex(signature)
+            .ok_or_else(|| invalid_bundle("The bundle has a malformed signature".to_string()))?;
+        UnparsedPublicKey::new(&ED25519, verify_key)
+            .verify(self.info.content_hash.as_bytes(), &signature)
+            .map_err(|_| {
+                invalid_bundle("The bundle signature does not match the verify key".to_string())
+            })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Build the bundled matcher, checking it against the recorded fingerprint
+    pub fn matcher(self, progress: Option<ProgressHook>) -> Result<Matcher, ConfigError> {
+        let options = self.options;
+        let name_error = |kind: &str, name: &str| {
+            ConfigError::Invalid(format!("Unknown {} '{}' in the bundle", kind, name))
+        };
+        let options = MatcherOptions {
+            overlapping: options.overlapping,
+            case_insensitive: options.case_insensitive,
+            whole_word: options.whole_word,
+            word_chars: WordChars::parse(&options.word_chars)?,
+            postprocess: match options.postprocess {
+                Some(name) => Some(
+                    PostProcess::parse(&name).ok_or_else(|| name_error("postprocess", &name))?,
+                ),
+                None => None,
+            },
+            priorities: options.priorities,
+            prefer_longer: options.prefer_longer,
+            hash_key: None,
+            fuzzy: options.fuzzy,
+            ignore_separators: options.ignore_separators,
+            min_counts: options.min_counts,
+            exclude: options.exclude,
+            line_filter: options.line_filter,
+            invalid_utf8: InvalidUtf8::parse(&options.invalid_utf8)
+                .ok_or_else(|| name_error("invalid_utf8 policy", &options.invalid_utf8))?,
+            encoding: Encoding::parse(&options.encoding)
+                .ok_or_else(|| name_error("encoding", &options.encoding))?,
+            engine: Engine::parse(&options.engine)
+                .ok_or_else(|| name_error("engine", &options.engine))?,
+            max_memory_bytes: options.max_memory_bytes,
+            on_memory_limit: OnMemoryLimit::parse(&options.on_memory_limit)
+                .ok_or_else(|| name_error("on_memory_limit policy", &options.on_memory_limit))?,
+            max_decoded_size: options.max_decoded_size,
+            mmap: MmapOptions {
+                madvise: Madvise::parse(&options.madvise)
+                    .ok_or_else(|| name_error("madvise hint", &options.madvise))?,
+                huge_pages: options.huge_pages,
+                prefault: options.prefault,
+            },
+            events: None,
+            audit_log: None,
+            output_schema: OutputSchema::from_version(options.schema_version)
+                .ok_or_else(|| name_error("schema version", &options.schema_version.to_string()))?,
+            metadata: Some(self.metadata),
+            tags: Some(self.tags),
+            progress,
+        };
+
+        let matcher = Matcher::new(self.patterns, options)?;
+        if matcher.fingerprint() != self.info.fingerprint {
+            return Err(ConfigError::Invalid(format!(
+                "The bundled matcher has fingerprint {} instead of the recorded {}",
+                matcher.fingerprint(),
+                self.info.fingerprint
+            )));
+        }
+        Ok(matcher)
+    }
+}
+
+/// Hex SHA-256 of the canonical JSON of the bundled content, metadata maps being sorted
+fn content_hash(
+    patterns: &[String],
+    metadata: &[PatternMetadata],
+    tags: &[Vec<String>],
+    options: &BundleOptions,
+) -> String {
+    // Serializing plain strings, integers and sorted maps cannot fail
+    let json = serde_json::to_vec(&(patterns, metadata, tags, options)).unwrap();
+    hex(&Sha256::digest(json))
+}
+
+fn key_pair(signing_key: &[u8]) -> Result<Ed25519KeyPair, ConfigError> {
+    check_key_len("signing_key", signing_key)?;
+    Ed25519KeyPair::from_seed_unchecked(signing_key)
+        .map_err(|e| ConfigError::Invalid(format!("Invalid signing_key: {}", e)))
+}
+
+pub(crate) fn check_key_len(name: &str, key: &[u8]) -> Result<(), ConfigError> {
+    if key.len() != KEY_LEN {
+        return Err(ConfigError::Invalid(format!(
+            "{} must be {} bytes, got {}",
+            name,
+            KEY_LEN,
+            key.len()
+        )));
+    }
+    Ok(())
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.is_ascii() || !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn invalid_bundle(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BundleInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "BundleInfo(fingerprint='{}', pattern_count={}, engine='{}')",
+            self.fingerprint, self.pattern_count, self.engine
+        )
+    }
+
+    #[getter]
+    fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    #[getter]
+    fn content_hash(&self) -> &str {
+        &self.content_hash
+    }
+
+    #[getter]
+    fn voluta_version(&self) -> &str {
+        &self.voluta_version
+    }
+
+    #[getter]
+    fn pattern_count(&self) -> usize {
+        self.pattern_count
+    }
+
+    #[getter]
+    fn state_count(&self) -> usize {
+        self.state_count
+    }
+
+    #[getter]
+    fn engine(&self) -> &str {
+        &self.engine
+    }
+
+    #[getter]
+    fn automaton(&self) -> Option<&str> {
+        self.automaton.as_deref()
+    }
+
+    #[getter]
+    fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    #[getter]
+    fn signed(&self) -> bool {
+        self.signed
+    }
+
+    /// The fields as a JSON object
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(self).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
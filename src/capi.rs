@@ -0,0 +1,337 @@
+//! C interface to the matcher, see `include/voluta.h`
+//!
+//! Every object returned by this module is owned by the caller and must be released with the
+//! matching `voluta_*_free` function. Errors are reported through an optional `char **error`
+//! out parameter, set to a message the caller releases with `voluta_string_free`.
+
+use crate::budget::OnMemoryLimit;
+use crate::codecs::DEFAULT_MAX_DECODED_SIZE;
+use crate::encoding::Encoding;
+use crate::engine::Engine;
+use crate::matcher::{InvalidUtf8, Matcher, MatcherOptions, WordChars};
+use crate::mmap::MmapOptions;
+use crate::postprocess::PostProcess;
+use crate::sink::OutputSchema;
+use aho_corasick::PatternID;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::slice;
+
+/// Strategy used to resolve overlapping matches
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolutaPostProcess {
+    VolutaPostProcessNone = 0,
+    VolutaPostProcessMerge = 1,
+    VolutaPostProcessLongest = 2,
+    VolutaPostProcessPriority = 3,
+}
+
+/// Options of a matcher, see `voluta_options_default`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VolutaOptions {
+    pub overlapping: bool,
+    pub case_insensitive: bool,
+    pub whole_word: bool,
+    pub postprocess: VolutaPostProcess,
+    pub prefer_longer: bool,
+}
+
+/// A match, as byte offsets with `end` exclusive and the index of the matched pattern
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct VolutaMatch {
+    pub start: usize,
+    pub end: usize,
+    pub pattern: usize,
+}
+
+/// Opaque matcher handle
+pub struct VolutaMatcher {
+    inner: Matcher,
+    // NUL-terminated copies of the patterns, handed out by `voluta_matcher_pattern`
+    patterns: Vec<CString>,
+}
+
+/// Opaque list of matches
+pub struct VolutaMatches {
+    matches: Vec<VolutaMatch>,
+}
+
+impl VolutaMatches {
+    fn new(matches: Vec<(usize, usize, PatternID)>) -> *mut Self {
+        let matches = matches
+            .into_iter()
+            .map(|(start, end, pattern_idx)| VolutaMatch {
+                start,
+                end,
+                pattern: pattern_idx.as_usize(),
+            })
+            .collect();
+        Box::into_raw(Box::new(Self { matches }))
+    }
+}
+
+/// Store `message` in `error` if the caller asked for it
+unsafe fn set_error(error: *mut *mut c_char, message: impl ToString) {
+    if !error.is_null() {
+        let message = message.to_string().replace('\0', " ");
+        *error = CString::new(message).unwrap().into_raw();
+    }
+}
+
+/// Version of the library as a static NUL-terminated string
+#[no_mangle]
+pub extern "C" fn voluta_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Default options, the same defaults as the Python TextMatcher
+#[no_mangle]
+pub extern "C" fn voluta_options_default() -> VolutaOptions {
+    let defaults = MatcherOptions::default();
+    VolutaOptions {
+        overlapping: defaults.overlapping,
+        case_insensitive: defaults.case_insensitive,
+        whole_word: defaults.whole_word,
+        postprocess: VolutaPostProcess::VolutaPostProcessNone,
+        prefer_longer: defaults.prefer_longer,
+    }
+}
+
+/// Build a matcher from `count` NUL-terminated UTF-8 patterns
+/// A null `options` uses the defaults; returns null and sets `error` on failure
+///
+/// # Safety
+/// `patterns` must point to `count` valid NUL-terminated strings, `options` must be null or
+/// point to a valid VolutaOptions and `error` must be null or writable
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matcher_new(
+    patterns: *const *const c_char,
+    count: usize,
+    options: *const VolutaOptions,
+    error: *mut *mut c_char,
+) -> *mut VolutaMatcher {
+    if patterns.is_null() && count > 0 {
+        set_error(error, "Patterns cannot be null");
+        return ptr::null_mut();
+    }
+
+    let patterns = if count == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(patterns, count)
+    };
+    let mut strings = Vec::with_capacity(count);
+    for (i, &pattern) in patterns.iter().enumerate() {
+        if pattern.is_null() {
+            set_error(error, format!("Pattern {i} is null"));
+            return ptr::null_mut();
+        }
+        match CStr::from_ptr(pattern).to_str() {
+            Ok(pattern) => strings.push(pattern.to_string()),
+            Err(_) => {
+                set_error(error, format!("Pattern {i} is not valid UTF-8"));
+                return ptr::null_mut();
+            }
+        }
+    }
+
+    let options = if options.is_null() {
+        voluta_options_default()
+    } else {
+        *options
+    };
+    let postprocess = match options.postprocess {
+        VolutaPostProcess::VolutaPostProcessNone => None,
+        VolutaPostProcess::VolutaPostProcessMerge => Some(PostProcess::Merge),
+        VolutaPostProcess::VolutaPostProcessLongest => Some(PostProcess::Longest),
+        VolutaPostProcess::VolutaPostProcessPriority => Some(PostProcess::Priority),
+    };
+    let options = MatcherOptions {
+        overlapping: options.overlapping,
+        case_insensitive: options.case_insensitive,
+        whole_word: options.whole_word,
+        word_chars: WordChars::default(),
+        postprocess,
+        priorities: None,
+        prefer_longer: options.prefer_longer,
+        hash_key: None,
+        fuzzy: None,
+        ignore_separators: false,
+        min_counts: None,
+        exclude: None,
+        line_filter: None,
+        invalid_utf8: InvalidUtf8::default(),
+        encoding: Encoding::default(),
+        engine: Engine::Auto,
+        max_memory_bytes: None,
+        on_memory_limit: OnMemoryLimit::default(),
+        max_decoded_size: DEFAULT_MAX_DECODED_SIZE,
+        mmap: MmapOptions::default(),
+        events: None,
+        audit_log: None,
+        output_schema: OutputSchema::Legacy,
+        metadata: None,
+        tags: None,
+        progress: None,
+    };
+
+    match Matcher::new(strings, options) {
+        Ok(inner) => {
+            let patterns = inner
+                .patterns()
+                .iter()
+                .map(|p| CString::new(p.as_str()).unwrap())
+                .collect();
+            Box::into_raw(Box::new(VolutaMatcher { inner, patterns }))
+        }
+        Err(e) => {
+            set_error(error, e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a matcher, null is ignored
+///
+/// # Safety
+/// `matcher` must be null or returned by `voluta_matcher_new` and not released yet
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matcher_free(matcher: *mut VolutaMatcher) {
+    if !matcher.is_null() {
+        drop(Box::from_raw(matcher));
+    }
+}
+
+/// Number of patterns of the matcher, empty patterns are dropped when building it
+///
+/// # Safety
+/// `matcher` must be a valid matcher
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matcher_pattern_count(matcher: *const VolutaMatcher) -> usize {
+    let matcher = &*matcher;
+    matcher.patterns.len()
+}
+
+/// Pattern at `index`, valid as long as the matcher, or null if out of range
+///
+/// # Safety
+/// `matcher` must be a valid matcher
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matcher_pattern(
+    matcher: *const VolutaMatcher,
+    index: usize,
+) -> *const c_char {
+    let matcher = &*matcher;
+    matcher
+        .patterns
+        .get(index)
+        .map_or(ptr::null(), |p| p.as_ptr())
+}
+
+/// Match `len` bytes at `data`
+///
+/// # Safety
+/// `matcher` must be a valid matcher and `data` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn voluta_match_bytes(
+    matcher: *const VolutaMatcher,
+    data: *const u8,
+    len: usize,
+) -> *mut VolutaMatches {
+    let matcher = &*matcher;
+    let data = if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    };
+    VolutaMatches::new(matcher.inner.match_bytes(data))
+}
+
+/// Memory-map the file at `path` and match it in parallel chunks
+/// Returns null and sets `error` if the file cannot be read
+///
+/// # Safety
+/// `matcher` must be a valid matcher, `path` a NUL-terminated string and `error` must be null
+/// or writable
+#[no_mangle]
+pub unsafe extern "C" fn voluta_match_file(
+    matcher: *const VolutaMatcher,
+    path: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut VolutaMatches {
+    let matcher = &*matcher;
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        set_error(error, "Path is not valid UTF-8");
+        return ptr::null_mut();
+    };
+    let matches = matcher.inner.tune_file(path, None).and_then(|tuning| {
+        matcher
+            .inner
+            .match_file_memmap_parallel(path, tuning.chunk_size, None)
+    });
+    match matches {
+        Ok(mut matches) => {
+            matches.sort_unstable();
+            VolutaMatches::new(matches)
+        }
+        Err(e) => {
+            set_error(error, e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Number of matches in the list
+///
+/// # Safety
+/// `matches` must be a valid list of matches
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matches_len(matches: *const VolutaMatches) -> usize {
+    let matches = &*matches;
+    matches.matches.len()
+}
+
+/// Copy the match at `index` to `out`, returns false if `index` is out of range
+///
+/// # Safety
+/// `matches` must be a valid list of matches and `out` must be writable
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matches_get(
+    matches: *const VolutaMatches,
+    index: usize,
+    out: *mut VolutaMatch,
+) -> bool {
+    let matches = &*matches;
+    match matches.matches.get(index) {
+        Some(mat) => {
+            *out = *mat;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Release a list of matches, null is ignored
+///
+/// # Safety
+/// `matches` must be null or returned by a match function and not released yet
+#[no_mangle]
+pub unsafe extern "C" fn voluta_matches_free(matches: *mut VolutaMatches) {
+    if !matches.is_null() {
+        drop(Box::from_raw(matches));
+    }
+}
+
+/// Release an error message, null is ignored
+///
+/// # Safety
+/// `string` must be null or a message returned through an `error` out parameter
+#[no_mangle]
+pub unsafe extern "C" fn voluta_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
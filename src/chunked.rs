@@ -0,0 +1,147 @@
+//! Offset model shared by the chunked scans of memory-mapped files, in-memory streams and
+//! readers: input arrives as consecutive chunks, each search picks up where the previous one
+//! stopped, and a match is reported by the chunk holding its start once every byte it and its
+//! checks read is known
+
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use std::cmp;
+
+/// Position of a scan of input seen chunk by chunk, in offsets into the whole input
+///
+/// A chunk owns the matches starting between the end of the chunk before it and `owned_end`.
+/// It is searched `overlap()` bytes past `owned_end`, enough for the longest match starting
+/// before it, and the bytes it is given must reach `context()` bytes past that for the word
+/// boundary and exclusion checks, or the end of the input. Without overlapping, the search
+/// resumes where the last engine match ended, filtered out by tag or not, as a single search of
+/// the whole input would; chunk sizes and buffer sizes therefore never change the matches
+/// found.
+#[derive(Clone, Copy)]
+pub(crate) struct ChunkedScanner<'m> {
+    matcher: &'m Matcher,
+    /// Matches starting before this offset have been reported
+    owned: usize,
+    /// Where a non-overlapping search resumes, the end of the last engine match
+    resume: usize,
+}
+
+impl<'m> ChunkedScanner<'m> {
+    /// Scanner of the input from `offset`, searched from there whatever precedes it
+    pub(crate) fn new(matcher: &'m Matcher, offset: usize) -> Self {
+        Self {
+            matcher,
+            owned: offset,
+            resume: offset,
+        }
+    }
+
+    /// Bytes a chunk is searched past the matches it owns, for the longest match
+    pub(crate) fn overlap(&self) -> usize {
+        self.matcher.max_pattern_len().saturating_sub(1)
+    }
+
+    /// Bytes on either side of a match read by its word boundary and exclusion checks
+    pub(crate) fn context(&self) -> usize {
+        self.matcher.lookahead()
+    }
+
+    /// Offset from which the input is still needed by the next chunk
+    pub(crate) fn needed_from(&self) -> usize {
+        self.search_from().saturating_sub(self.context())
+    }
+
+    /// End of the matches that can be reported once the input is known up to `end`, every
+    /// byte up to the end when it is the end of the input
+    pub(crate) fn settled(&self, end: usize, eof: bool) -> usize {
+        if eof {
+            end
+        } else {
+            cmp::max(
+                self.owned,
+                end.saturating_sub(self.overlap() + self.context()),
+            )
+        }
+    }
+
+    fn search_from(&self) -> usize {
+        if self.matcher.overlapping() {
+            self.owned
+        } else {
+            cmp::max(self.owned, self.resume)
+        }
+    }
+
+    /// Search the next chunk, `data` being the input from `data_offset` on, from at least
+    /// `needed_from()`, and append the matches starting before `owned_end` to `matches`,
+    /// searching up to `search_end`
+    /// Returns the start of the first engine match owned without overlapping, if any
+    pub(crate) fn scan(
+        &mut self,
+        data: &[u8],
+        data_offset: usize,
+        search_end: usize,
+        owned_end: usize,
+        matches: &mut Vec<(usize, usize, PatternID)>,
+    ) -> Option<usize> {
+        let from = self.search_from();
+        let mut first = None;
+        if from < owned_end {
+            let matcher = self.matcher;
+            let window = &data[from - data_offset..search_end - data_offset];
+            if matcher.overlapping() {
+                for mat in matcher.find_overlapping_iter(window) {
+                    let start_idx = from + mat.start();
+                    let end_idx = from + mat.end();
+
+                    // Boundaries and exclusion zones are checked against the bytes around it
+                    if start_idx < owned_end
+                        && matcher.is_reported_match(
+                            data,
+                            start_idx - data_offset,
+                            end_idx - data_offset,
+                        )
+                    {
+                        matches.push((start_idx, end_idx, mat.pattern()));
+                    }
+                }
+            } else {
+                for mat in matcher.find_unfiltered_iter(window) {
+                    let start_idx = from + mat.start();
+                    let end_idx = from + mat.end();
+
+                    // Matches come by increasing start, the rest belong to the next chunk
+                    if start_idx >= owned_end {
+                        break;
+                    }
+                    first.get_or_insert(start_idx);
+                    self.resume = end_idx;
+
+                    if matcher.is_active(mat.pattern())
+                        && matcher.is_reported_match(
+                            data,
+                            start_idx - data_offset,
+                            end_idx - data_offset,
+                        )
+                    {
+                        matches.push((start_idx, end_idx, mat.pattern()));
+                    }
+                }
+            }
+        }
+        self.owned = owned_end;
+        first
+    }
+
+    /// Whether the scanner of the next chunk, searched from its own start and whose first
+    /// owned engine match starts at `first`, found what resuming from here finds: it did
+    /// unless that match starts under the last match of this chunk
+    pub(crate) fn agrees(&self, first: Option<usize>) -> bool {
+        first.is_none_or(|first| first >= self.resume)
+    }
+
+    /// Move past the chunk `next` scanned, once it `agrees()`
+    pub(crate) fn join(&mut self, next: &Self) {
+        self.owned = next.owned;
+        self.resume = cmp::max(self.resume, next.resume);
+    }
+}
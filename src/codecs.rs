@@ -0,0 +1,191 @@
+//! Block decompressors of the codecs data-lake formats use besides deflate: raw Snappy, as in
+//! Avro and ORC, and raw LZ4 blocks, as in ORC, without their framing formats, and the limit
+//! every decompressed input is held to
+
+use std::io::{self, ErrorKind, Read, Result};
+
+/// Bytes an input decompresses to at most by default, a WARC record or payload, an Avro
+/// block or an ORC stream, so a compression bomb cannot exhaust memory
+pub const DEFAULT_MAX_DECODED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Append the output of a streaming decoder to `out`, failing on corrupt `format` data or
+/// once `out` holds more than `max_len` bytes
+pub(crate) fn decode(
+    decoder: impl Read,
+    out: &mut Vec<u8>,
+    max_len: usize,
+    format: &str,
+) -> Result<()> {
+    let room = max_len.saturating_sub(out.len()) as u64;
+    decoder
+        .take(room.saturating_add(1))
+        .read_to_end(out)
+        .map_err(|e| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("Corrupt {} data: {}", format, e),
+            )
+        })?;
+    if out.len() > max_len {
+        return Err(too_large(format, max_len));
+    }
+    Ok(())
+}
+
+/// Error of `format` data decompressing past `max_len` bytes
+pub(crate) fn too_large(format: &str, max_len: usize) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "{} data decompressing to more than {} bytes, see max_decoded_size",
+            format, max_len
+        ),
+    )
+}
+
+/// Decompress a raw Snappy block, failing beyond `max_len` bytes of output
+pub(crate) fn snappy(input: &[u8], max_len: usize) -> Result<Vec<u8>> {
+    let mut pos = 0;
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *input.get(pos).ok_or_else(|| corrupt("Snappy"))?;
+        pos += 1;
+        if shift > 28 {
+            return Err(corrupt("Snappy"));
+        }
+        len |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    if len > max_len {
+        return Err(too_large("Snappy", max_len));
+    }
+
+    let mut out = Vec::with_capacity(len);
+    while pos < input.len() {
+        let tag = input[pos];
+        pos += 1;
+        let (copy_len, offset) = match tag & 3 {
+            0 => {
+                let mut literal = (tag >> 2) as usize;
+                if literal >= 60 {
+                    let extra = literal - 59;
+                    let bytes = input
+                        .get(pos..pos + extra)
+                        .ok_or_else(|| corrupt("Snappy"))?;
+                    literal = bytes
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, &b| (acc << 8) | b as usize);
+                    pos += extra;
+                }
+                if out.len() + literal + 1 > len {
+                    return Err(corrupt("Snappy"));
+                }
+                let bytes = input
+                    .get(pos..pos + literal + 1)
+                    .ok_or_else(|| corrupt("Snappy"))?;
+                out.extend_from_slice(bytes);
+                pos += literal + 1;
+                continue;
+            }
+            1 => {
+                let low = *input.get(pos).ok_or_else(|| corrupt("Snappy"))? as usize;
+                pos += 1;
+                (
+                    ((tag >> 2) & 7) as usize + 4,
+                    ((tag >> 5) as usize) << 8 | low,
+                )
+            }
+            2 => {
+                let bytes = input.get(pos..pos + 2).ok_or_else(|| corrupt("Snappy"))?;
+                pos += 2;
+                (
+                    (tag >> 2) as usize + 1,
+                    u16::from_le_bytes([bytes[0], bytes[1]]) as usize,
+                )
+            }
+            _ => {
+                let bytes = input.get(pos..pos + 4).ok_or_else(|| corrupt("Snappy"))?;
+                pos += 4;
+                let offset = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                ((tag >> 2) as usize + 1, offset as usize)
+            }
+        };
+        copy_match(&mut out, offset, copy_len, len, "Snappy")?;
+    }
+    if out.len() != len {
+        return Err(corrupt("Snappy"));
+    }
+    Ok(out)
+}
+
+/// Decompress a raw LZ4 block, failing beyond `max_len` bytes of output
+pub(crate) fn lz4(input: &[u8], max_len: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    let length = |pos: &mut usize, mut len: usize| -> Result<usize> {
+        if len == 15 {
+            loop {
+                let byte = *input.get(*pos).ok_or_else(|| corrupt("LZ4"))?;
+                *pos += 1;
+                len += byte as usize;
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Ok(len)
+    };
+    loop {
+        let token = *input.get(pos).ok_or_else(|| corrupt("LZ4"))?;
+        pos += 1;
+        let literal = length(&mut pos, (token >> 4) as usize)?;
+        let bytes = input
+            .get(pos..pos + literal)
+            .ok_or_else(|| corrupt("LZ4"))?;
+        if out.len() + literal > max_len {
+            return Err(too_large("LZ4", max_len));
+        }
+        out.extend_from_slice(bytes);
+        pos += literal;
+        // The last sequence has literals only
+        if pos == input.len() {
+            return Ok(out);
+        }
+        let bytes = input.get(pos..pos + 2).ok_or_else(|| corrupt("LZ4"))?;
+        pos += 2;
+        let offset = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+        let copy_len = length(&mut pos, (token & 15) as usize)? + 4;
+        copy_match(&mut out, offset, copy_len, max_len, "LZ4")?;
+    }
+}
+
+/// Append `len` bytes copied from `offset` bytes back, the copy overlapping its output when
+/// the offset is shorter than the length
+fn copy_match(
+    out: &mut Vec<u8>,
+    offset: usize,
+    len: usize,
+    max_len: usize,
+    codec: &str,
+) -> Result<()> {
+    if offset == 0 || offset > out.len() {
+        return Err(corrupt(codec));
+    }
+    if out.len() + len > max_len {
+        return Err(too_large(codec, max_len));
+    }
+    let start = out.len() - offset;
+    for idx in start..start + len {
+        out.push(out[idx]);
+    }
+    Ok(())
+}
+
+fn corrupt(codec: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, format!("Corrupt {} block", codec))
+}
@@ -0,0 +1,391 @@
+use crate::highlight::{self, escape_html};
+use crate::python::{PyMatch, PyNames};
+use crate::sink::{JsonFinding, OutputSchema};
+use crate::tags::PatternTags;
+use aho_corasick::PatternID;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyDict, PyList, PyTuple};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Number of matches shown by the repr and the notebook table of a collection
+const PREVIEW_LEN: usize = 20;
+
+/// Serialized form of a single match
+#[derive(Serialize)]
+struct MatchRecord<'a> {
+    start: usize,
+    end: usize,
+    pattern: &'a str,
+}
+
+/// A collection of matches that keeps pattern indices internally and only creates
+/// Python objects when they are requested
+#[pyclass(frozen, sequence)]
+pub struct MatchCollection {
+    patterns: Arc<Vec<String>>,
+    // Shared with the collections derived from this one
+    names: Arc<PyNames>,
+    matches: Vec<(usize, usize, PatternID)>,
+    schema: OutputSchema,
+    // Tags of the patterns, none for collections of rules
+    tags: Arc<PatternTags>,
+}
+
+impl MatchCollection {
+    pub fn new(patterns: Arc<Vec<String>>, matches: Vec<(usize, usize, PatternID)>) -> Self {
+        Self {
+            patterns,
+            names: Arc::default(),
+            matches,
+            schema: OutputSchema::Legacy,
+            tags: Arc::default(),
+        }
+    }
+
+    /// The collection exporting JSON findings following `schema`
+    pub fn with_schema(self, schema: OutputSchema) -> Self {
+        Self { schema, ..self }
+    }
+
+    /// The collection filtering by the tags of its patterns, see `filter`
+    pub(crate) fn with_tags(self, tags: Arc<PatternTags>) -> Self {
+        Self { tags, ..self }
+    }
+
+    fn with_matches(&self, matches: Vec<(usize, usize, PatternID)>) -> Self {
+        Self {
+            patterns: Arc::clone(&self.patterns),
+            names: Arc::clone(&self.names),
+            matches,
+            schema: self.schema,
+            tags: Arc::clone(&self.tags),
+        }
+    }
+
+    fn pattern(&self, pattern_idx: PatternID) -> &str {
+        &self.patterns[pattern_idx.as_usize()]
+    }
+
+    /// Byte ranges of the matches
+    pub fn spans(&self) -> Vec<(usize, usize)> {
+        self.matches
+            .iter()
+            .map(|&(start, end, _)| (start, end))
+            .collect()
+    }
+
+    /// Columns of the matches, offsets as little-endian u64 and patterns as little-endian i32
+    /// codes into the names of the matched patterns, in pattern order
+    fn columns(&self) -> Columns<'_> {
+        let mut codes_by_pattern = vec![None; self.patterns.len()];
+        for &(_, _, pattern_idx) in &self.matches {
+            codes_by_pattern[pattern_idx.as_usize()] = Some(0);
+        }
+        // Patterns sharing a name share a code
+        let mut names = Vec::new();
+        let mut name_codes: HashMap<&str, i32> = HashMap::new();
+        for (idx, code) in codes_by_pattern.iter_mut().enumerate() {
+            if code.is_some() {
+                let name = self.patterns[idx].as_str();
+                let next = name_codes.len() as i32;
+                *code = Some(*name_codes.entry(name).or_insert_with(|| {
+                    names.push(name);
+                    next
+                }));
+            }
+        }
+
+        let mut columns = Columns {
+            len: self.matches.len(),
+            starts: Vec::with_capacity(8 * self.matches.len()),
+            ends: Vec::with_capacity(8 * self.matches.len()),
+            codes: Vec::with_capacity(4 * self.matches.len()),
+            names,
+        };
+        for &(start, end, pattern_idx) in &self.matches {
+            let code = codes_by_pattern[pattern_idx.as_usize()].unwrap_or_default();
+            columns
+                .starts
+                .extend_from_slice(&(start as u64).to_le_bytes());
+            columns.ends.extend_from_slice(&(end as u64).to_le_bytes());
+            columns.codes.extend_from_slice(&code.to_le_bytes());
+        }
+        columns
+    }
+
+    fn records(&self) -> impl Iterator<Item = MatchRecord<'_>> {
+        self.matches
+            .iter()
+            .map(|&(start, end, pattern_idx)| MatchRecord {
+                start,
+                end,
+                pattern: self.pattern(pattern_idx),
+            })
+    }
+}
+
+/// A string or a list of strings, as taken by the `name` argument
+fn extract_names(value: &Bound<'_, PyAny>, name: &str) -> PyResult<Vec<String>> {
+    if let Ok(single) = value.extract::<String>() {
+        return Ok(vec![single]);
+    }
+    value.extract().map_err(|_| {
+        PyValueError::new_err(format!("{} must be a string or a list of strings", name))
+    })
+}
+
+#[pymethods]
+impl MatchCollection {
+    fn __len__(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Shows the first matches, e.g. MatchCollection([(4, 9, 'quick'), (10, 13, 'fox')], len=2)
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let mut items = Vec::new();
+        for record in self.records().take(PREVIEW_LEN) {
+            let item = PyTuple::new(
+                py,
+                [
+                    record.start.into_pyobject(py)?.into_any(),
+                    record.end.into_pyobject(py)?.into_any(),
+                    record.pattern.into_pyobject(py)?.into_any(),
+                ],
+            )?;
+            items.push(item.repr()?.to_string());
+        }
+        if self.matches.len() > PREVIEW_LEN {
+            items.push("...".to_string());
+        }
+        Ok(format!(
+            "MatchCollection([{}], len={})",
+            items.join(", "),
+            self.matches.len()
+        ))
+    }
+
+    /// Table of the first matches, displayed by Jupyter
+    fn _repr_html_(&self) -> String {
+        let mut html = format!(
+            "<table class=\"voluta-matches\"><caption>MatchCollection of {} matches</caption>\
+             <thead><tr><th>start</th><th>end</th><th>pattern</th></tr></thead><tbody>",
+            self.matches.len()
+        );
+        for record in self.records().take(PREVIEW_LEN) {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                record.start,
+                record.end,
+                escape_html(record.pattern)
+            );
+        }
+        if self.matches.len() > PREVIEW_LEN {
+            let _ = write!(
+                html,
+                "<tr><td colspan=\"3\">… {} more</td></tr>",
+                self.matches.len() - PREVIEW_LEN
+            );
+        }
+        html.push_str("</tbody></table>");
+        html
+    }
+
+    /// Get a single (start_index, end_index, matched_pattern) tuple, negative indices count from the end
+    fn __getitem__(&self, py: Python<'_>, index: isize) -> PyResult<PyMatch> {
+        let len = self.matches.len() as isize;
+        let resolved = if index < 0 { index + len } else { index };
+        if resolved < 0 || resolved >= len {
+            return Err(PyIndexError::new_err("MatchCollection index out of range"));
+        }
+
+        let (start, end, pattern_idx) = self.matches[resolved as usize];
+        let name = &self.names.get(py, &self.patterns)[pattern_idx.as_usize()];
+        Ok((start, end, name.clone_ref(py)))
+    }
+
+    /// Keep only the matches of the given pattern(s), of the patterns with one of the given
+    /// tag(s), lying entirely inside `range`
+    /// `range` is a (start, end) tuple of byte offsets, end exclusive
+    #[pyo3(signature = (pattern=None, range=None, tag=None))]
+    fn filter(
+        &self,
+        pattern: Option<Bound<'_, PyAny>>,
+        range: Option<(usize, usize)>,
+        tag: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let selected = match pattern {
+            Some(pattern) => {
+                let names = extract_names(&pattern, "pattern")?;
+
+                let mut mask = vec![false; self.patterns.len()];
+                for (idx, existing) in self.patterns.iter().enumerate() {
+                    if names.contains(existing) {
+                        mask[idx] = true;
+                    }
+                }
+                Some(mask)
+            }
+            None => None,
+        };
+        let tag_filter = match tag {
+            Some(tag) => Some(self.tags.filter(&extract_names(&tag, "tag")?)?),
+            None => None,
+        };
+
+        let matches = self
+            .matches
+            .iter()
+            .filter(|(start, end, pattern_idx)| {
+                let pattern_ok = selected
+                    .as_ref()
+                    .is_none_or(|mask| mask[pattern_idx.as_usize()]);
+                let tag_ok = tag_filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.is_active(*pattern_idx));
+                let range_ok = range.is_none_or(|(lo, hi)| *start >= lo && *end <= hi);
+                pattern_ok && tag_ok && range_ok
+            })
+            .copied()
+            .collect();
+
+        Ok(self.with_matches(matches))
+    }
+
+    /// Split the collection into one collection per matched pattern
+    fn group_by_pattern<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let mut groups: HashMap<PatternID, Vec<(usize, usize, PatternID)>> = HashMap::new();
+        for &m in &self.matches {
+            groups.entry(m.2).or_default().push(m);
+        }
+
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_unstable_by_key(|(pattern_idx, _)| *pattern_idx);
+
+        let dict = PyDict::new(py);
+        for (pattern_idx, matches) in groups {
+            dict.set_item(self.pattern(pattern_idx), self.with_matches(matches))?;
+        }
+        Ok(dict)
+    }
+
+    /// Render the data the matches were found in as HTML, with matched regions highlighted
+    /// `context` keeps only that many bytes around each match, the whole data is shown otherwise
+    #[pyo3(signature = (data, context=None))]
+    fn to_html(&self, py: Python<'_>, data: &[u8], context: Option<usize>) -> String {
+        py.allow_threads(|| highlight::to_html(data, &self.matches, &self.patterns, context))
+    }
+
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples
+    fn to_list(&self, py: Python<'_>) -> Vec<PyMatch> {
+        self.names
+            .resolve(py, &self.patterns, self.matches.iter().copied())
+    }
+
+    /// Returns a list of {"start", "end", "pattern"} dicts
+    fn to_dicts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for record in self.records() {
+            let dict = PyDict::new(py);
+            dict.set_item("start", record.start)?;
+            dict.set_item("end", record.end)?;
+            dict.set_item("pattern", record.pattern)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// Serialize the matches as a JSON array of {"start", "end", "pattern"} objects, or of
+    /// findings of the versioned schema the matcher was built with
+    fn to_json(&self) -> PyResult<String> {
+        let json = match self.schema {
+            OutputSchema::Legacy => {
+                let records: Vec<MatchRecord> = self.records().collect();
+                serde_json::to_string(&records)
+            }
+            OutputSchema::V1 => {
+                let findings: Vec<JsonFinding> = self
+                    .records()
+                    .map(|r| JsonFinding::new(None, None, r.start, r.end, r.pattern, None))
+                    .collect();
+                serde_json::to_string(&findings)
+            }
+        };
+        json.map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Build a pyarrow Table with uint64 "start" and "end" columns and a dictionary-encoded
+    /// "pattern" column, handing pyarrow buffers built in Rust
+    fn to_arrow<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pyarrow = py.import("pyarrow")?;
+        let columns = py.allow_threads(|| self.columns());
+
+        let array = |bytes: &[u8], data_type: &str| -> PyResult<Bound<'py, PyAny>> {
+            let buffers = PyList::new(
+                py,
+                [
+                    py.None().into_bound(py),
+                    pyarrow.call_method1("py_buffer", (PyByteArray::new(py, bytes),))?,
+                ],
+            )?;
+            pyarrow.getattr("Array")?.call_method1(
+                "from_buffers",
+                (pyarrow.call_method0(data_type)?, columns.len, buffers),
+            )
+        };
+        let starts = array(&columns.starts, "uint64")?;
+        let ends = array(&columns.ends, "uint64")?;
+        let dictionary =
+            pyarrow.call_method1("array", (&columns.names, pyarrow.call_method0("string")?))?;
+        let patterns = pyarrow
+            .getattr("DictionaryArray")?
+            .call_method1("from_arrays", (array(&columns.codes, "int32")?, dictionary))?;
+
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("names", COLUMN_NAMES)?;
+        pyarrow.getattr("Table")?.call_method(
+            "from_arrays",
+            (PyList::new(py, [starts, ends, patterns])?,),
+            Some(&kwargs),
+        )
+    }
+
+    /// Build a pandas DataFrame with uint64 "start" and "end" columns and a categorical
+    /// "pattern" column, from NumPy arrays over buffers built in Rust
+    fn to_pandas<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pandas = py.import("pandas")?;
+        let numpy = py.import("numpy")?;
+        let columns = py.allow_threads(|| self.columns());
+
+        // Arrays over bytearrays are writable, so the frame owns its columns
+        let array = |bytes: &[u8], dtype: &str| {
+            numpy.call_method1("frombuffer", (PyByteArray::new(py, bytes), dtype))
+        };
+        let patterns = pandas.getattr("Categorical")?.call_method1(
+            "from_codes",
+            (array(&columns.codes, "<i4")?, &columns.names),
+        )?;
+
+        let frame = PyDict::new(py);
+        frame.set_item(COLUMN_NAMES[0], array(&columns.starts, "<u8")?)?;
+        frame.set_item(COLUMN_NAMES[1], array(&columns.ends, "<u8")?)?;
+        frame.set_item(COLUMN_NAMES[2], patterns)?;
+        pandas.call_method1("DataFrame", (frame,))
+    }
+}
+
+/// Columns of the exported tables
+const COLUMN_NAMES: [&str; 3] = ["start", "end", "pattern"];
+
+/// Columnar form of a collection, see `MatchCollection::columns`
+struct Columns<'a> {
+    len: usize,
+    starts: Vec<u8>,
+    ends: Vec<u8>,
+    codes: Vec<u8>,
+    names: Vec<&'a str>,
+}
@@ -0,0 +1,109 @@
+//! A/B scans of the same data by two matchers, e.g. a candidate rule set against the production
+//! one, their findings split into those only one of them finds and those both find
+
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+
+/// (start, end, pattern) findings of an A/B scan
+type Findings = Vec<(usize, usize, String)>;
+
+/// Findings of an A/B scan, each list sorted by offset then pattern
+/// Findings are told apart by their span and the name of their pattern, so patterns of hashed
+/// matchers only compare equal under the same key
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Comparison {
+    /// Findings of matcher A only
+    pub only_a: Findings,
+    /// Findings of matcher B only
+    pub only_b: Findings,
+    /// Findings of both matchers
+    pub common: Findings,
+}
+
+impl Comparison {
+    /// Scan `data` with both matchers in parallel
+    pub(crate) fn scan(a: &Matcher, b: &Matcher, data: &[u8]) -> Self {
+        let (matches_a, matches_b) = rayon::join(|| a.match_split(data), || b.match_split(data));
+        Self::split(Self::named(a, matches_a), Self::named(b, matches_b))
+    }
+
+    /// Findings named by their pattern, sorted
+    fn named(
+        matcher: &Matcher,
+        matches: Vec<(usize, usize, PatternID)>,
+    ) -> Vec<(usize, usize, &str)> {
+        let mut named: Vec<_> = matches
+            .into_iter()
+            .map(|(start, end, pattern)| (start, end, matcher.pattern(pattern)))
+            .collect();
+        named.sort_unstable();
+        named
+    }
+
+    /// Split two sorted lists of findings by a merge, repeated findings pairing up one to one
+    fn split(a: Vec<(usize, usize, &str)>, b: Vec<(usize, usize, &str)>) -> Self {
+        let owned = |(start, end, pattern): (usize, usize, &str)| (start, end, pattern.to_string());
+        let mut comparison = Self::default();
+        let mut a = a.into_iter().peekable();
+        let mut b = b.into_iter().peekable();
+        loop {
+            let order = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) => x.cmp(y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match order {
+                Ordering::Less => comparison.only_a.extend(a.next().map(owned)),
+                Ordering::Greater => comparison.only_b.extend(b.next().map(owned)),
+                Ordering::Equal => {
+                    b.next();
+                    comparison.common.extend(a.next().map(owned));
+                }
+            }
+        }
+        comparison
+    }
+
+    /// Number of findings of matcher A
+    pub fn matches_a(&self) -> usize {
+        self.only_a.len() + self.common.len()
+    }
+
+    /// Number of findings of matcher B
+    pub fn matches_b(&self) -> usize {
+        self.only_b.len() + self.common.len()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Comparison {
+    fn __repr__(&self) -> String {
+        format!(
+            "Comparison(only_a={}, only_b={}, common={})",
+            self.only_a.len(),
+            self.only_b.len(),
+            self.common.len()
+        )
+    }
+
+    #[getter]
+    fn only_a(&self) -> Findings {
+        self.only_a.clone()
+    }
+
+    #[getter]
+    fn only_b(&self) -> Findings {
+        self.only_b.clone()
+    }
+
+    #[getter]
+    fn common(&self) -> Findings {
+        self.common.clone()
+    }
+}
@@ -0,0 +1,218 @@
+//! Two-stage matching: literal anchors found by the Aho-Corasick engine gate regex
+//! confirmation on a small window around each hit
+
+use crate::matcher::{ConfigError, Matcher, MatcherOptions};
+use aho_corasick::PatternID;
+use memmap2::Mmap;
+use rayon::prelude::*;
+use regex::bytes::Regex;
+use std::cmp;
+use std::fs::File;
+use std::io::Result;
+use std::sync::Arc;
+
+/// A rule reporting the matches of `regex` around hits of any of its `anchors`
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub name: String,
+    /// Literals occurring in, or next to, every match of the regex
+    pub anchors: Vec<String>,
+    pub regex: String,
+}
+
+/// Options of a ConfirmMatcher
+#[derive(Clone, Debug)]
+pub struct ConfirmOptions {
+    /// Bytes around each anchor hit searched by the regex, on each side
+    /// Regex matches must fit inside the window to be found
+    pub window: usize,
+    /// Find anchors regardless of ASCII case, regexes use their own flags such as `(?i)`
+    pub case_insensitive: bool,
+}
+
+impl Default for ConfirmOptions {
+    fn default() -> Self {
+        Self {
+            window: 64,
+            case_insensitive: true,
+        }
+    }
+}
+
+/// Matcher confirming literal anchor hits with per-rule regexes
+/// Matches are (start, end, rule) tuples of byte offsets, end exclusive, and the rule index
+/// into `rules()`; a regex match is reported when it overlaps a hit of one of its anchors
+pub struct ConfirmMatcher {
+    anchors: Matcher,
+    // Rule of each anchor pattern of `anchors`
+    anchor_rules: Vec<usize>,
+    names: Arc<Vec<String>>,
+    regexes: Vec<Regex>,
+    window: usize,
+}
+
+impl ConfirmMatcher {
+    pub fn new(
+        rules: Vec<Rule>,
+        options: ConfirmOptions,
+    ) -> std::result::Result<Self, ConfigError> {
+        if rules.is_empty() {
+            return Err(ConfigError::Invalid("Rule set cannot be empty".to_string()));
+        }
+
+        let mut anchors = Vec::new();
+        let mut anchor_rules = Vec::new();
+        let mut names = Vec::with_capacity(rules.len());
+        let mut regexes = Vec::with_capacity(rules.len());
+        for (idx, rule) in rules.into_iter().enumerate() {
+            let regex = Regex::new(&rule.regex).map_err(|e| {
+                ConfigError::Invalid(format!("Invalid regex for rule '{}': {}", rule.name, e))
+            })?;
+
+            let before = anchors.len();
+            for anchor in rule.anchors.into_iter().filter(|a| !a.is_empty()) {
+                anchors.push(anchor);
+                anchor_rules.push(idx);
+            }
+            if anchors.len() == before {
+                return Err(ConfigError::Invalid(format!(
+                    "Rule '{}' needs at least one non-empty anchor",
+                    rule.name
+                )));
+            }
+
+            names.push(rule.name);
+            regexes.push(regex);
+        }
+
+        // Every anchor hit is needed, a hit hidden by an overlapping one could be the only
+        // anchor of a match
+        let anchors = Matcher::new(
+            anchors,
+            MatcherOptions {
+                overlapping: true,
+                case_insensitive: options.case_insensitive,
+                ..MatcherOptions::default()
+            },
+        )?;
+
+        Ok(Self {
+            anchors,
+            anchor_rules,
+            names: Arc::new(names),
+            regexes,
+            window: options.window,
+        })
+    }
+
+    /// Names of the rules
+    pub fn rules(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Shared handle on the rule names, for results that outlive a borrow of the matcher
+    pub fn shared_rules(&self) -> Arc<Vec<String>> {
+        Arc::clone(&self.names)
+    }
+
+    pub fn rule(&self, rule_idx: PatternID) -> &str {
+        &self.names[rule_idx.as_usize()]
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.anchors.case_insensitive()
+    }
+
+    /// Convert rule indices to rule names
+    pub fn resolve(&self, matches: Vec<(usize, usize, PatternID)>) -> Vec<(usize, usize, String)> {
+        matches
+            .into_iter()
+            .map(|(start, end, rule_idx)| (start, end, self.rule(rule_idx).to_string()))
+            .collect()
+    }
+
+    /// Match provided bytes, returning the confirmed matches sorted by offset
+    pub fn match_bytes(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let windows = self.windows(data);
+        let mut matches: Vec<_> = windows
+            .iter()
+            .flat_map(|window| self.confirm(data, window))
+            .collect();
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Match provided bytes, confirming anchor hits on the rayon thread pool
+    pub fn match_bytes_parallel(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let windows = self.windows(data);
+        let mut matches: Vec<_> = windows
+            .par_iter()
+            .flat_map_iter(|window| self.confirm(data, window))
+            .collect();
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Match a memory-mapped file, confirming anchor hits in parallel
+    pub fn match_file_memmap(&self, path: &str) -> Result<Vec<(usize, usize, PatternID)>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(self.match_bytes_parallel(&mmap))
+    }
+
+    /// Group anchor hits into windows, one regex search per group of nearby hits of a rule
+    fn windows(&self, data: &[u8]) -> Vec<Window> {
+        let mut hits: Vec<(usize, usize, usize)> = Vec::new();
+        self.anchors.for_each_match(data, |start, end, anchor_idx| {
+            hits.push((self.anchor_rules[anchor_idx.as_usize()], start, end))
+        });
+        hits.sort_unstable();
+
+        let mut windows: Vec<Window> = Vec::new();
+        for (rule, start, end) in hits {
+            let from = start.saturating_sub(self.window);
+            let to = cmp::min(end + self.window, data.len());
+            match windows.last_mut() {
+                Some(last) if last.rule == rule && from <= last.end => {
+                    last.end = cmp::max(last.end, to);
+                    last.hits.push((start, end));
+                }
+                _ => windows.push(Window {
+                    rule,
+                    start: from,
+                    end: to,
+                    hits: vec![(start, end)],
+                }),
+            }
+        }
+        windows
+    }
+
+    /// Regex matches inside a window overlapping one of its anchor hits
+    fn confirm(&self, data: &[u8], window: &Window) -> Vec<(usize, usize, PatternID)> {
+        let rule_idx = PatternID::must(window.rule);
+        self.regexes[window.rule]
+            .find_iter(&data[window.start..window.end])
+            .map(|mat| (window.start + mat.start(), window.start + mat.end()))
+            .filter(|&(start, end)| {
+                window
+                    .hits
+                    .iter()
+                    .any(|&(hit_start, hit_end)| hit_start < end && start < hit_end)
+            })
+            .map(|(start, end)| (start, end, rule_idx))
+            .collect()
+    }
+}
+
+/// Region of the data searched by the regex of a rule, and the anchor hits it surrounds
+struct Window {
+    rule: usize,
+    start: usize,
+    end: usize,
+    hits: Vec<(usize, usize)>,
+}
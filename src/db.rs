@@ -0,0 +1,404 @@
+//! Database scans: the text and blob values of the tables of an SQLite database, or of the
+//! rows of a query, streamed row by row and reported with their table, column and primary key,
+//! instead of exporting the database to files to scan them
+//!
+//! Other databases are scanned from the bindings, whose drivers read the rows and hand their
+//! values to `DbScan::scan_values`.
+
+use crate::matcher::Matcher;
+use crate::sink::sqlite_error;
+use aho_corasick::PatternID;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, Row};
+use std::io::{self, ErrorKind, Result};
+
+/// Bytes of values read ahead of their scan, scanned in parallel once reached
+const BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// Options of a database scan
+#[derive(Clone, Debug, Default)]
+pub struct DbOptions {
+    /// Tables scanned, every table but SQLite's own and virtual tables by default
+    pub tables: Option<Vec<String>>,
+    /// Columns scanned in every table, those declared with a text or blob type by default, or
+    /// in the query results, all of them by default
+    pub columns: Option<Vec<String>>,
+    /// Query whose results are scanned instead of the tables
+    pub query: Option<String>,
+    /// Columns identifying the rows, by default the primary key of tables, their rowid for
+    /// tables without one, and the row number of query results
+    pub key: Option<Vec<String>>,
+}
+
+/// Value of a key column
+#[derive(Clone, Debug, PartialEq)]
+pub enum DbValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl From<ValueRef<'_>> for DbValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => Self::Null,
+            ValueRef::Integer(n) => Self::Integer(n),
+            ValueRef::Real(x) => Self::Real(x),
+            ValueRef::Text(text) => Self::Text(String::from_utf8_lossy(text).into_owned()),
+            ValueRef::Blob(blob) => Self::Blob(blob.to_vec()),
+        }
+    }
+}
+
+/// A value with matches, and where it was read
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbCell {
+    /// Table of the value, None for query results
+    pub table: Option<String>,
+    pub column: String,
+    /// Values of the key columns of the row
+    pub key: Vec<DbValue>,
+}
+
+/// Matches of a database scan
+pub struct DbScan {
+    /// Every value with matches, in scan order
+    pub cells: Vec<DbCell>,
+    /// (cell, start, end, pattern) matches, the cell indexing `cells` and the offsets being
+    /// those of the UTF-8 text or the blob, sorted by cell then offset
+    pub matches: Vec<(usize, usize, usize, PatternID)>,
+}
+
+/// Values read and not scanned yet, with the row and column they were read from
+#[derive(Default)]
+struct Batch {
+    keys: Vec<Vec<DbValue>>,
+    cells: Vec<(usize, usize)>,
+    values: Vec<Vec<u8>>,
+    bytes: usize,
+}
+
+impl Batch {
+    /// Add the text and blob values of a row, its key read from `key`, or its row number
+    /// without, and its values from `scanned`, both indexing the row
+    fn push_row(
+        &mut self,
+        row: &Row<'_>,
+        row_number: i64,
+        key: Option<&[usize]>,
+        scanned: &[usize],
+    ) -> Result<()> {
+        let mut added = false;
+        for (column, &idx) in scanned.iter().enumerate() {
+            if let ValueRef::Text(value) | ValueRef::Blob(value) =
+                row.get_ref(idx).map_err(sqlite_error)?
+            {
+                self.cells.push((self.keys.len(), column));
+                self.values.push(value.to_vec());
+                self.bytes += value.len();
+                added = true;
+            }
+        }
+        if added {
+            let key = match key {
+                Some(key) => key
+                    .iter()
+                    .map(|&idx| row.get_ref(idx).map(DbValue::from))
+                    .collect::<rusqlite::Result<_>>()
+                    .map_err(sqlite_error)?,
+                None => vec![DbValue::Integer(row_number)],
+            };
+            self.keys.push(key);
+        }
+        Ok(())
+    }
+}
+
+impl DbScan {
+    /// Scan the SQLite database of `source`, a path, a `file:` URI or an `sqlite:///<path>`
+    /// URL, opened read-only
+    /// Values are scanned as they are stored, text as UTF-8 and blobs as bytes; other values
+    /// are skipped
+    pub fn scan(matcher: &Matcher, source: &str, options: &DbOptions) -> Result<Self> {
+        if options.query.is_some() && options.tables.is_some() {
+            return Err(invalid_input(
+                "tables and query cannot be combined, the query selects the rows scanned"
+                    .to_string(),
+            ));
+        }
+        let conn = open(source)?;
+        let mut scan = Self {
+            cells: Vec::new(),
+            matches: Vec::new(),
+        };
+        if let Some(query) = &options.query {
+            scan.scan_query(matcher, &conn, query, options)?;
+        } else {
+            let tables = match &options.tables {
+                Some(tables) => tables.clone(),
+                None => list_tables(&conn)?,
+            };
+            for table in &tables {
+                scan.scan_table(matcher, &conn, table, options)?;
+            }
+        }
+        Ok(scan)
+    }
+
+    /// (value, start, end, pattern) matches of values scanned in parallel, sorted by value
+    /// then offset
+    pub fn scan_values<V: AsRef<[u8]> + Sync>(
+        matcher: &Matcher,
+        values: &[V],
+    ) -> Vec<(usize, usize, usize, PatternID)> {
+        matcher.match_values(values)
+    }
+
+    fn scan_table(
+        &mut self,
+        matcher: &Matcher,
+        conn: &Connection,
+        table: &str,
+        options: &DbOptions,
+    ) -> Result<()> {
+        // (name, declared type, position in the primary key) of every column
+        let mut stmt = conn
+            .prepare("SELECT name, type, pk FROM pragma_table_info(?1)")
+            .map_err(sqlite_error)?;
+        let columns: Vec<(String, String, i64)> = stmt
+            .query_map([table], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .and_then(|rows| rows.collect())
+            .map_err(sqlite_error)?;
+        if columns.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                format!("No table '{}' in the database", table),
+            ));
+        }
+
+        let scanned: Vec<&str> = columns
+            .iter()
+            .filter(|(name, declared, _)| match &options.columns {
+                Some(wanted) => wanted.iter().any(|w| w.eq_ignore_ascii_case(name)),
+                None => holds_text(declared),
+            })
+            .map(|(name, _, _)| name.as_str())
+            .collect();
+        if scanned.is_empty() {
+            return Ok(());
+        }
+        // The rowid is selected unquoted, a quoted name that is not a column being a string
+        let key: Vec<String> = match &options.key {
+            Some(key) => {
+                if let Some(missing) = key.iter().find(|k| {
+                    !columns
+                        .iter()
+                        .any(|(name, _, _)| k.eq_ignore_ascii_case(name))
+                }) {
+                    return Err(invalid_input(format!(
+                        "Key column '{}' is not a column of table '{}'",
+                        missing, table
+                    )));
+                }
+                key.iter().map(|k| quote(k)).collect()
+            }
+            None => {
+                let mut primary: Vec<&(String, String, i64)> =
+                    columns.iter().filter(|(_, _, pk)| *pk > 0).collect();
+                primary.sort_by_key(|(_, _, pk)| *pk);
+                if primary.is_empty() {
+                    vec!["rowid".to_string()]
+                } else {
+                    primary.iter().map(|(name, _, _)| quote(name)).collect()
+                }
+            }
+        };
+
+        let select: Vec<String> = key
+            .iter()
+            .cloned()
+            .chain(scanned.iter().map(|name| quote(name)))
+            .collect();
+        let sql = format!("SELECT {} FROM {}", select.join(", "), quote(table));
+        let mut stmt = conn.prepare(&sql).map_err(sqlite_error)?;
+        let key_idx: Vec<usize> = (0..key.len()).collect();
+        let scanned_idx: Vec<usize> = (key.len()..key.len() + scanned.len()).collect();
+        let names: Vec<String> = scanned.iter().map(|name| name.to_string()).collect();
+        self.scan_rows(
+            matcher,
+            &mut stmt,
+            Some(table),
+            &names,
+            Some(&key_idx),
+            &scanned_idx,
+        )
+    }
+
+    fn scan_query(
+        &mut self,
+        matcher: &Matcher,
+        conn: &Connection,
+        query: &str,
+        options: &DbOptions,
+    ) -> Result<()> {
+        let mut stmt = conn.prepare(query).map_err(sqlite_error)?;
+        let names: Vec<String> = stmt
+            .column_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let position = |wanted: &str, role: &str| {
+            names
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(wanted))
+                .ok_or_else(|| {
+                    invalid_input(format!(
+                        "{} column '{}' is not in the query results",
+                        role, wanted
+                    ))
+                })
+        };
+        let key_idx = match &options.key {
+            Some(key) => Some(
+                key.iter()
+                    .map(|k| position(k, "Key"))
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            None => None,
+        };
+        let scanned_idx = match &options.columns {
+            Some(columns) => columns
+                .iter()
+                .map(|c| position(c, "Scanned"))
+                .collect::<Result<Vec<_>>>()?,
+            None => (0..names.len()).collect(),
+        };
+        let scanned: Vec<String> = scanned_idx.iter().map(|&idx| names[idx].clone()).collect();
+        self.scan_rows(
+            matcher,
+            &mut stmt,
+            None,
+            &scanned,
+            key_idx.as_deref(),
+            &scanned_idx,
+        )
+    }
+
+    /// Scan the rows of a statement batch by batch, the key of each row read from `key`, or
+    /// its row number without
+    fn scan_rows(
+        &mut self,
+        matcher: &Matcher,
+        stmt: &mut rusqlite::Statement<'_>,
+        table: Option<&str>,
+        columns: &[String],
+        key: Option<&[usize]>,
+        scanned: &[usize],
+    ) -> Result<()> {
+        let mut rows = stmt.query([]).map_err(sqlite_error)?;
+        let mut batch = Batch::default();
+        let mut row_number = 0;
+        while let Some(row) = rows.next().map_err(sqlite_error)? {
+            batch.push_row(row, row_number, key, scanned)?;
+            row_number += 1;
+            if batch.bytes >= BATCH_BYTES {
+                self.extend(matcher, table, columns, &mut batch);
+            }
+        }
+        self.extend(matcher, table, columns, &mut batch);
+        Ok(())
+    }
+
+    /// Scan the values of a batch and keep the cells with matches
+    fn extend(
+        &mut self,
+        matcher: &Matcher,
+        table: Option<&str>,
+        columns: &[String],
+        batch: &mut Batch,
+    ) {
+        let mut current: Option<(usize, usize)> = None;
+        for (value, start, end, pattern_idx) in Self::scan_values(matcher, &batch.values) {
+            let cell = match current {
+                Some((scanned, cell)) if scanned == value => cell,
+                _ => {
+                    let (row, column) = batch.cells[value];
+                    self.cells.push(DbCell {
+                        table: table.map(str::to_string),
+                        column: columns[column].clone(),
+                        key: batch.keys[row].clone(),
+                    });
+                    current = Some((value, self.cells.len() - 1));
+                    self.cells.len() - 1
+                }
+            };
+            self.matches.push((cell, start, end, pattern_idx));
+        }
+        *batch = Batch::default();
+    }
+}
+
+/// Open an SQLite database read-only, from a path, a `file:` URI or an `sqlite:///<path>` URL
+fn open(source: &str) -> Result<Connection> {
+    let path = match source.split_once("://") {
+        Some(("sqlite", path)) => path.strip_prefix('/').ok_or_else(|| {
+            invalid_input(format!(
+                "Invalid SQLite URL '{}', expected sqlite:///<path>",
+                source
+            ))
+        })?,
+        Some((scheme, _)) if !scheme.contains(['/', '\\']) => {
+            return Err(invalid_input(format!(
+                "Unsupported database URL scheme '{}', only SQLite databases are opened by \
+                 voluta, others are scanned through a connection of their driver",
+                scheme
+            )))
+        }
+        _ => source,
+    };
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+        | OpenFlags::SQLITE_OPEN_URI
+        | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let conn = Connection::open_with_flags(path, flags).map_err(sqlite_error)?;
+    // Opening is lazy, reading the schema fails on files that are not databases
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+        .map_err(sqlite_error)?;
+    Ok(conn)
+}
+
+/// Tables of the database, SQLite's own and virtual tables excepted, the content of virtual
+/// tables being stored in tables of their own
+fn list_tables(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'
+                 AND sql NOT LIKE 'CREATE VIRTUAL TABLE%'
+             ORDER BY name",
+        )
+        .map_err(sqlite_error)?;
+    stmt.query_map([], |row| row.get(0))
+        .and_then(|rows| rows.collect())
+        .map_err(sqlite_error)
+}
+
+/// Whether a column declared with this type has the text or blob affinity of SQLite
+fn holds_text(declared: &str) -> bool {
+    let declared = declared.to_ascii_uppercase();
+    !declared.contains("INT")
+        && (declared.is_empty()
+            || ["CHAR", "CLOB", "TEXT", "BLOB"]
+                .iter()
+                .any(|name| declared.contains(name)))
+}
+
+/// Quote an identifier for SQLite
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, message)
+}
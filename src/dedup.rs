@@ -0,0 +1,110 @@
+use crate::density::WindowUnit;
+use aho_corasick::PatternID;
+use std::collections::HashMap;
+
+/// Options of duplicate suppression
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DedupOptions {
+    /// Largest distance from the previous occurrence of a finding at which a repeat is folded
+    /// into it, None folding every repeat in the input
+    pub window: Option<usize>,
+    /// Unit of the window
+    pub unit: WindowUnit,
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        Self {
+            window: None,
+            unit: WindowUnit::Bytes,
+        }
+    }
+}
+
+/// A reported finding and the state of its repeats
+struct Finding {
+    start: usize,
+    end: usize,
+    pattern_idx: PatternID,
+    count: usize,
+}
+
+/// Folding of repeated identical findings, fed with matches in the order they are found
+///
+/// Findings are identical when they share their pattern and matched bytes. A repeat is folded
+/// into the finding reported before it, incrementing its count, when it lies at most `window`
+/// bytes or lines after the previous occurrence. The window slides with every occurrence, so
+/// a key repeated steadily through a log is reported once, and again only after a gap longer
+/// than the window. As with density rules, positions are measured at match ends, which never
+/// decrease for the standard and the overlapping Aho-Corasick iterators.
+pub struct DedupTracker<'a> {
+    data: &'a [u8],
+    options: DedupOptions,
+    /// Index of the finding of each key in `findings`, and the position of its last occurrence
+    last: HashMap<(PatternID, &'a [u8]), (usize, usize)>,
+    findings: Vec<Finding>,
+    /// Line counting state for line-based windows
+    line: usize,
+    line_pos: usize,
+}
+
+impl<'a> DedupTracker<'a> {
+    pub fn new(data: &'a [u8], options: DedupOptions) -> Self {
+        Self {
+            data,
+            options,
+            last: HashMap::new(),
+            findings: Vec::new(),
+            line: 0,
+            line_pos: 0,
+        }
+    }
+
+    pub fn push(&mut self, start: usize, end: usize, pattern_idx: PatternID) {
+        let position = match self.options.unit {
+            WindowUnit::Bytes => end,
+            WindowUnit::Lines => {
+                let last_byte = end.saturating_sub(1);
+                if last_byte >= self.line_pos {
+                    self.line +=
+                        memchr::memchr_iter(b'\n', &self.data[self.line_pos..last_byte]).count();
+                    self.line_pos = last_byte;
+                }
+                self.line
+            }
+        };
+
+        let key = (pattern_idx, &self.data[start..end]);
+        if let Some((idx, last_position)) = self.last.get_mut(&key) {
+            if self
+                .options
+                .window
+                .is_none_or(|window| position.saturating_sub(*last_position) <= window)
+            {
+                self.findings[*idx].count += 1;
+                *last_position = (*last_position).max(position);
+                return;
+            }
+        }
+
+        self.last.insert(key, (self.findings.len(), position));
+        self.findings.push(Finding {
+            start,
+            end,
+            pattern_idx,
+            count: 1,
+        });
+    }
+
+    /// (start, end, pattern, count) of the reported findings sorted by offset, each with the
+    /// number of occurrences folded into it, itself included
+    pub fn finish(self) -> Vec<(usize, usize, PatternID, usize)> {
+        let mut findings: Vec<_> = self
+            .findings
+            .into_iter()
+            .map(|f| (f.start, f.end, f.pattern_idx, f.count))
+            .collect();
+        findings.sort_unstable();
+        findings
+    }
+}
@@ -0,0 +1,166 @@
+use aho_corasick::PatternID;
+use std::cmp;
+use std::collections::VecDeque;
+
+/// Unit used to measure the size of a density window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowUnit {
+    Bytes,
+    Lines,
+}
+
+impl WindowUnit {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bytes" => Some(Self::Bytes),
+            "lines" => Some(Self::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// A region where at least `min_matches` matches fell inside one window
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DensityRegion {
+    pub start: usize,
+    pub end: usize,
+    pub count: usize,
+    /// (start, end, pattern) of the matches counted in the region, in the order they were
+    /// found, when the tracker keeps the evidence
+    pub matches: Vec<(usize, usize, PatternID)>,
+}
+
+/// The region being extended
+struct Region {
+    region: DensityRegion,
+    /// Sequence number of the last match counted in this region
+    last_seq: usize,
+}
+
+/// Sliding-window bookkeeping for density rules, fed with matches in the order they are found
+///
+/// Matches must arrive with non-decreasing end offsets, which holds for both the standard
+/// and the overlapping Aho-Corasick iterators. A match's position in the window is measured
+/// at its end offset (or the line containing it), and any window of `window` units holding
+/// at least `min_matches` matches is reported. Overlapping dense windows are merged into one
+/// region, along with the matches it counted when `evidence` is set.
+pub struct DensityTracker<'a> {
+    data: &'a [u8],
+    min_matches: usize,
+    window: usize,
+    unit: WindowUnit,
+    evidence: bool,
+    /// (position, start, end, sequence number, pattern) of the matches inside the current window
+    entries: VecDeque<(usize, usize, usize, usize, PatternID)>,
+    seq: usize,
+    /// Line counting state for line-based windows
+    line: usize,
+    line_pos: usize,
+    region: Option<Region>,
+    regions: Vec<DensityRegion>,
+}
+
+impl<'a> DensityTracker<'a> {
+    pub fn new(
+        data: &'a [u8],
+        min_matches: usize,
+        window: usize,
+        unit: WindowUnit,
+        evidence: bool,
+    ) -> Self {
+        Self {
+            data,
+            min_matches,
+            window,
+            unit,
+            evidence,
+            entries: VecDeque::new(),
+            seq: 0,
+            line: 0,
+            line_pos: 0,
+            region: None,
+            regions: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, start: usize, end: usize, pattern: PatternID) {
+        let position = match self.unit {
+            WindowUnit::Bytes => end,
+            WindowUnit::Lines => {
+                // The match ends at `end` exclusive, its last byte decides the line
+                let last_byte = end - 1;
+                if last_byte >= self.line_pos {
+                    self.line +=
+                        memchr::memchr_iter(b'\n', &self.data[self.line_pos..last_byte]).count();
+                    self.line_pos = last_byte;
+                }
+                self.line
+            }
+        };
+
+        self.seq += 1;
+        self.entries
+            .push_back((position, start, end, self.seq, pattern));
+
+        // Drop matches that no longer fit in a window ending at this match
+        while let Some(&(front_position, ..)) = self.entries.front() {
+            if position - front_position >= self.window {
+                self.entries.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.entries.len() < self.min_matches {
+            return;
+        }
+
+        let first_seq = self.entries.front().map(|e| e.3).unwrap_or(self.seq);
+
+        match &mut self.region {
+            // This window shares matches with the current region, so extend it
+            Some(Region { region, last_seq }) if first_seq <= *last_seq => {
+                let new = self.entries.iter().filter(|e| e.3 > *last_seq);
+                for &(_, entry_start, entry_end, _, entry_pattern) in new {
+                    region.start = cmp::min(region.start, entry_start);
+                    region.end = cmp::max(region.end, entry_end);
+                    if self.evidence {
+                        region.matches.push((entry_start, entry_end, entry_pattern));
+                    }
+                }
+                region.count += self.seq - *last_seq;
+                *last_seq = self.seq;
+            }
+            _ => {
+                self.close_region();
+                let region_start = self.entries.iter().map(|e| e.1).min().unwrap_or(start);
+                let region_end = self.entries.iter().map(|e| e.2).max().unwrap_or(end);
+                let matches = if self.evidence {
+                    self.entries.iter().map(|e| (e.1, e.2, e.4)).collect()
+                } else {
+                    Vec::new()
+                };
+                self.region = Some(Region {
+                    region: DensityRegion {
+                        start: region_start,
+                        end: region_end,
+                        count: self.entries.len(),
+                        matches,
+                    },
+                    last_seq: self.seq,
+                });
+            }
+        }
+    }
+
+    fn close_region(&mut self) {
+        if let Some(Region { region, .. }) = self.region.take() {
+            self.regions.push(region);
+        }
+    }
+
+    pub fn finish(mut self) -> Vec<DensityRegion> {
+        self.close_region();
+        self.regions
+    }
+}
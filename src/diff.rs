@@ -0,0 +1,210 @@
+//! Diff scans: only the lines a unified diff adds, reported at their file and line in the new
+//! version, e.g. the staged changes of a pre-commit secret gate
+
+use crate::git;
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use std::io::Result;
+
+/// Matches of the added lines of a diff
+pub struct DiffScan {
+    /// Path in the new version of every file the diff adds lines to, in diff order
+    pub files: Vec<String>,
+    /// (file, line, start, end, pattern) matches, the file indexing `files`, lines numbered
+    /// from 1 in the new version and offsets relative to the line, sorted by file, line then
+    /// offset
+    pub matches: Vec<(usize, usize, usize, usize, PatternID)>,
+}
+
+impl DiffScan {
+    /// Scan the lines added by a unified diff, as printed by `git diff` or `diff -u`
+    pub fn scan(matcher: &Matcher, diff: &[u8]) -> Self {
+        let added = added_lines(diff);
+
+        // Added lines are searched as one buffer, one newline apart, matches spanning two
+        // lines being dropped
+        let mut buffer = Vec::with_capacity(added.lines.iter().map(|l| l.text.len() + 1).sum());
+        let mut starts = Vec::with_capacity(added.lines.len());
+        for line in &added.lines {
+            starts.push(buffer.len());
+            buffer.extend_from_slice(line.text);
+            buffer.push(b'\n');
+        }
+        let mut matches: Vec<_> = matcher
+            .match_bytes(&buffer)
+            .into_iter()
+            .filter_map(|(start, end, pattern)| {
+                let idx = starts.partition_point(|&s| s <= start) - 1;
+                let line = &added.lines[idx];
+                let offset = starts[idx];
+                (end <= offset + line.text.len()).then(|| {
+                    (
+                        line.file,
+                        line.number,
+                        start - offset,
+                        end - offset,
+                        pattern,
+                    )
+                })
+            })
+            .collect();
+        matches.sort_unstable();
+        Self {
+            files: added.files,
+            matches,
+        }
+    }
+
+    /// Scan the lines added by the changes staged in the git repository at `repo`
+    pub fn staged(matcher: &Matcher, repo: &str) -> Result<Self> {
+        // Outside of a repository git diff would compare paths instead
+        git::run(repo, &["rev-parse", "--git-dir"])?;
+        let diff = git::run(
+            repo,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "--cached",
+                "--no-color",
+                "--no-ext-diff",
+                "--unified=0",
+            ],
+        )?;
+        Ok(Self::scan(matcher, &diff))
+    }
+}
+
+/// A line added by a diff
+struct Line<'d> {
+    file: usize,
+    number: usize,
+    text: &'d [u8],
+}
+
+struct Added<'d> {
+    files: Vec<String>,
+    lines: Vec<Line<'d>>,
+}
+
+/// Added lines of a unified diff; hunks are read up to the line counts of their header, so
+/// added lines starting with `++` are not taken for file headers
+fn added_lines(diff: &[u8]) -> Added<'_> {
+    let mut added = Added {
+        files: Vec::new(),
+        lines: Vec::new(),
+    };
+    // Path of the current hunks, None for deleted files, added to the files at its first
+    // added line so files with deletions only are not reported
+    let mut path = None;
+    let mut file = None;
+    let (mut old_left, mut new_left, mut number) = (0, 0, 0);
+    for line in diff.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if old_left > 0 || new_left > 0 {
+            match line.first() {
+                Some(b'+') => {
+                    if let Some(path) = path.take() {
+                        added.files.push(path);
+                        file = Some(added.files.len() - 1);
+                    }
+                    if let Some(file) = file {
+                        added.lines.push(Line {
+                            file,
+                            number,
+                            text: &line[1..],
+                        });
+                    }
+                    number += 1;
+                    new_left -= 1;
+                }
+                Some(b'-') => old_left -= 1,
+                // No newline at end of file
+                Some(b'\\') => {}
+                _ => {
+                    number += 1;
+                    old_left -= 1;
+                    new_left -= 1;
+                }
+            }
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix(b"+++ ") {
+            path = new_path(header);
+            file = None;
+        } else if let Some(header) = line.strip_prefix(b"@@ -") {
+            if let Some(counts) = hunk_counts(header) {
+                (old_left, number, new_left) = counts;
+            }
+        }
+    }
+    added
+}
+
+/// Path of a `+++` header, without its `b/` prefix and timestamp, None for `/dev/null`
+fn new_path(header: &[u8]) -> Option<String> {
+    let header = String::from_utf8_lossy(header);
+    let path = match header.strip_prefix('"') {
+        Some(quoted) => unquote(quoted),
+        // diff -u separates a timestamp with a tab
+        None => header.split('\t').next().unwrap_or_default().to_string(),
+    };
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix("b/").unwrap_or(&path).to_string())
+}
+
+/// Path quoted by git, C escapes and octal bytes included, up to the closing quote
+fn unquote(quoted: &str) -> String {
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.bytes();
+    while let Some(b) = chars.next() {
+        match b {
+            b'"' => break,
+            b'\\' => match chars.next() {
+                Some(b'n') => bytes.push(b'\n'),
+                Some(b't') => bytes.push(b'\t'),
+                Some(b'a') => bytes.push(0x07),
+                Some(b'b') => bytes.push(0x08),
+                Some(b'f') => bytes.push(0x0C),
+                Some(b'r') => bytes.push(b'\r'),
+                Some(b'v') => bytes.push(0x0B),
+                Some(digit @ b'0'..=b'7') => {
+                    let mut value = u32::from(digit - b'0');
+                    for _ in 0..2 {
+                        match chars.clone().next() {
+                            Some(d @ b'0'..=b'7') => {
+                                value = value * 8 + u32::from(d - b'0');
+                                chars.next();
+                            }
+                            _ => break,
+                        }
+                    }
+                    bytes.push(value as u8);
+                }
+                Some(other) => bytes.push(other),
+                None => {}
+            },
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// (old line count, new first line, new line count) of a hunk header after its `@@ -`
+fn hunk_counts(header: &[u8]) -> Option<(usize, usize, usize)> {
+    let end = header.windows(3).position(|w| w == b" @@")?;
+    let header = std::str::from_utf8(&header[..end]).ok()?;
+    let (old, new) = header.split_once(" +")?;
+    let count = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((first, count)) => Some((first.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (_, old_count) = count(old)?;
+    let (first, new_count) = count(new)?;
+    Some((old_count, first, new_count))
+}
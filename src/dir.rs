@@ -0,0 +1,422 @@
+//! Directory scans: every regular file under a directory, reported with a summary of each
+//! file alongside the matches, so operational reporting needs no bookkeeping of its own
+
+use crate::audit::{AuditRun, AuditTotals};
+use crate::events::Event;
+use crate::ignore::{IgnoreFile, Ignores};
+use crate::matcher::Matcher;
+use crate::throttle::Throttle;
+use aho_corasick::PatternID;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Options of a directory scan
+#[derive(Clone, Debug)]
+pub struct DirOptions {
+    /// Scan the subdirectories too
+    pub recursive: bool,
+    /// Follow symbolic links, each directory being scanned once; links are skipped otherwise
+    pub follow_links: bool,
+    /// Files larger than this are skipped
+    pub max_file_size: Option<u64>,
+    /// Honor the `.gitignore` and `.volutaignore` files of the scanned directories, and skip
+    /// `.git` directories; excluded paths are not visited, so not summarized
+    pub ignore_files: bool,
+    /// Keep only the first match of every pattern in each file, each scan stopping once every
+    /// pattern has been seen, for inventories of what a tree holds
+    pub first_only: bool,
+    /// Limit on the average read rate of the whole walk, for background sweeps sharing the
+    /// disk with other workloads
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl Default for DirOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            follow_links: false,
+            max_file_size: None,
+            ignore_files: true,
+            first_only: false,
+            max_bytes_per_sec: None,
+        }
+    }
+}
+
+/// Outcome of one file of a directory scan
+/// A file is scanned, skipped with a reason, or failed with an error, and only scanned files
+/// have matches
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct FileSummary {
+    pub path: String,
+    pub bytes_scanned: usize,
+    pub elapsed: Duration,
+    pub matches: usize,
+    /// Why the file was not scanned
+    pub skipped: Option<String>,
+    /// Why the file could not be scanned, a directory that cannot be listed included
+    pub error: Option<String>,
+}
+
+impl FileSummary {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            bytes_scanned: 0,
+            elapsed: Duration::ZERO,
+            matches: 0,
+            skipped: None,
+            error: None,
+        }
+    }
+
+    fn skipping(path: &Path, reason: &str) -> Self {
+        Self {
+            skipped: Some(reason.to_string()),
+            ..Self::new(path)
+        }
+    }
+
+    fn failed(path: &Path, error: std::io::Error) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            ..Self::new(path)
+        }
+    }
+}
+
+/// Matches and file summaries of a directory scan
+pub struct DirScan {
+    /// One summary per file visited, sorted by path
+    pub files: Vec<FileSummary>,
+    /// (file, start, end, pattern) matches, the file indexing `files`, sorted by file then
+    /// offset
+    pub matches: Vec<(usize, usize, usize, PatternID)>,
+}
+
+impl DirScan {
+    /// Scan the regular files under `root` in parallel, largest first; files that cannot be
+    /// read are summarized with their error instead of failing the scan, which only fails
+    /// when `root` itself cannot be listed
+    /// The scan is recorded in the matcher's audit log, if it has one
+    pub fn scan(matcher: &Matcher, root: &str, options: &DirOptions) -> Result<Self> {
+        let audit = AuditRun::start(matcher, "match_dir", &[root]);
+        let res = Self::walk_and_scan(matcher, root, options);
+        AuditRun::end(audit, res, |scan| AuditTotals {
+            files_scanned: scan
+                .files
+                .iter()
+                .filter(|f| f.skipped.is_none() && f.error.is_none())
+                .count(),
+            files_failed: scan.files.iter().filter(|f| f.error.is_some()).count(),
+            bytes_scanned: scan.files.iter().map(|f| f.bytes_scanned).sum(),
+            matches: scan.matches.len(),
+        })
+    }
+
+    fn walk_and_scan(matcher: &Matcher, root: &str, options: &DirOptions) -> Result<Self> {
+        let mut visited = HashSet::new();
+        let root = Path::new(root);
+        if options.follow_links {
+            visited.insert(fs::canonicalize(root)?);
+        }
+        let listing = fs::read_dir(root)?;
+        let mut walker = Walker {
+            matcher,
+            options,
+            visited,
+            ignores: Ignores::default(),
+            entries: Vec::new(),
+        };
+        walker.walk(root, listing);
+        let mut entries = walker.entries;
+        entries.sort_unstable_by(|a, b| a.path().cmp(b.path()));
+
+        // Largest files first, so the scan does not end waiting on a big file picked up last
+        let mut order: Vec<(u64, usize)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| match entry {
+                Entry::File(_, size, _) => Some((*size, idx)),
+                Entry::Done(_) => None,
+            })
+            .collect();
+        order.sort_unstable_by_key(|&(size, idx)| (Reverse(size), idx));
+        // One schedule for every worker, each file being accounted for once scanned
+        let throttle = options
+            .max_bytes_per_sec
+            .map(|rate| Mutex::new(Throttle::new(rate)));
+        let mut scanned: Vec<_> = order
+            .into_par_iter()
+            .map(|(_, idx)| {
+                let Entry::File(path, _, allowed) = &entries[idx] else {
+                    unreachable!("only files are scanned")
+                };
+                let (summary, matches) = scan_file(matcher, path, allowed, options.first_only);
+                if let Some(throttle) = &throttle {
+                    // Workers ahead of schedule wait their turn behind the one sleeping
+                    let mut throttle = throttle.lock().unwrap_or_else(|e| e.into_inner());
+                    throttle.consume(summary.bytes_scanned);
+                }
+                (idx, summary, matches)
+            })
+            .collect();
+        scanned.sort_unstable_by_key(|(idx, _, _)| *idx);
+
+        let mut files = Vec::with_capacity(entries.len());
+        let mut matches = Vec::new();
+        let mut scanned = scanned.into_iter();
+        for (idx, entry) in entries.into_iter().enumerate() {
+            match entry {
+                Entry::Done(summary) => files.push(summary),
+                Entry::File(..) => {
+                    let (_, summary, file_matches) = scanned.next().expect("file scanned");
+                    matches.extend(
+                        file_matches
+                            .into_iter()
+                            .map(|(start, end, pattern)| (idx, start, end, pattern)),
+                    );
+                    files.push(summary);
+                }
+            }
+        }
+        Ok(Self { files, matches })
+    }
+}
+
+/// A file found while walking a directory: to be scanned, with its size and the patterns whose
+/// matches its ignore files drop, or already summarized
+enum Entry {
+    File(PathBuf, u64, Vec<PatternID>),
+    Done(FileSummary),
+}
+
+impl Entry {
+    fn path(&self) -> &Path {
+        match self {
+            Self::File(path, ..) => path,
+            Self::Done(summary) => Path::new(&summary.path),
+        }
+    }
+}
+
+/// State of the walk of a directory tree
+struct Walker<'a> {
+    matcher: &'a Matcher,
+    options: &'a DirOptions,
+    visited: HashSet<PathBuf>,
+    // Ignore files of the directories being walked
+    ignores: Ignores,
+    entries: Vec<Entry>,
+}
+
+impl Walker<'_> {
+    fn walk(&mut self, dir: &Path, listing: fs::ReadDir) {
+        let ignore_file = self
+            .options
+            .ignore_files
+            .then(|| IgnoreFile::load(dir))
+            .flatten();
+        let pushed = ignore_file.is_some();
+        if let Some(file) = ignore_file {
+            self.ignores.push(file);
+        }
+        for item in listing {
+            match item {
+                Ok(item) => self.visit(item),
+                Err(e) => self.failed(dir, e),
+            }
+        }
+        if pushed {
+            self.ignores.pop();
+        }
+    }
+
+    fn visit(&mut self, item: fs::DirEntry) {
+        let path = item.path();
+        let metadata = match item.file_type() {
+            Ok(kind) if kind.is_symlink() && !self.options.follow_links => {
+                self.entries
+                    .push(Entry::Done(FileSummary::skipping(&path, "symbolic link")));
+                return;
+            }
+            Ok(_) => fs::metadata(&path),
+            Err(e) => Err(e),
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.failed(&path, e);
+                return;
+            }
+        };
+        if self.options.ignore_files
+            && ((metadata.is_dir() && item.file_name() == ".git")
+                || self.ignores.excluded(&path, metadata.is_dir()))
+        {
+            return;
+        }
+
+        if metadata.is_dir() {
+            if !self.options.recursive {
+                return;
+            }
+            // Directories reached twice through links are walked once
+            if self.options.follow_links {
+                match fs::canonicalize(&path) {
+                    Ok(real) => {
+                        if !self.visited.insert(real) {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        self.failed(&path, e);
+                        return;
+                    }
+                }
+            }
+            match fs::read_dir(&path) {
+                Ok(listing) => self.walk(&path, listing),
+                Err(e) => self.failed(&path, e),
+            }
+        } else if !metadata.is_file() {
+            self.entries.push(Entry::Done(FileSummary::skipping(
+                &path,
+                "not a regular file",
+            )));
+        } else if self
+            .options
+            .max_file_size
+            .is_some_and(|max| metadata.len() > max)
+        {
+            let reason = "larger than max_file_size";
+            self.matcher.emit(|| {
+                Event::limit_hit(
+                    "match_dir",
+                    Some(&path.to_string_lossy()),
+                    reason.to_string(),
+                )
+            });
+            self.entries
+                .push(Entry::Done(FileSummary::skipping(&path, reason)));
+        } else {
+            let allowed = self.allowed(&path);
+            self.entries
+                .push(Entry::File(path, metadata.len(), allowed));
+        }
+    }
+
+    /// Summarize a path that cannot be walked
+    fn failed(&mut self, path: &Path, error: std::io::Error) {
+        self.matcher
+            .emit(|| Event::failed("match_dir", Some(&path.to_string_lossy()), &error));
+        self.entries
+            .push(Entry::Done(FileSummary::failed(path, error)));
+    }
+
+    /// Patterns of the matcher whose matches the ignore files drop in the file at `path`
+    fn allowed(&self, path: &Path) -> Vec<PatternID> {
+        let names = self.ignores.allowed(path);
+        if names.is_empty() {
+            return Vec::new();
+        }
+        self.matcher
+            .patterns()
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| names.contains(&pattern.as_str()))
+            .map(|(idx, _)| PatternID::must(idx))
+            .collect()
+    }
+}
+
+fn scan_file(
+    matcher: &Matcher,
+    path: &Path,
+    allowed: &[PatternID],
+    first_only: bool,
+) -> (FileSummary, Vec<(usize, usize, PatternID)>) {
+    let started = Instant::now();
+    let path_str = path.to_string_lossy();
+    let (mmap, mut matches) = match matcher.match_mapped("match_dir", &path_str, first_only) {
+        Ok(scanned) => scanned,
+        Err(e) => return (FileSummary::failed(path, e), Vec::new()),
+    };
+    if !allowed.is_empty() {
+        matches.retain(|(_, _, pattern)| !allowed.contains(pattern));
+    }
+    matches.sort_unstable();
+    let summary = FileSummary {
+        bytes_scanned: mmap.len(),
+        elapsed: started.elapsed(),
+        matches: matches.len(),
+        ..FileSummary::new(path)
+    };
+    (summary, matches)
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FileSummary {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let status = match (&self.skipped, &self.error) {
+            (Some(reason), _) => format!("skipped={}", crate::python::quote(py, reason)?),
+            (_, Some(error)) => format!("error={}", crate::python::quote(py, error)?),
+            _ => format!(
+                "matches={}, bytes_scanned={}",
+                self.matches, self.bytes_scanned
+            ),
+        };
+        Ok(format!(
+            "FileSummary(path={}, {})",
+            crate::python::quote(py, &self.path)?,
+            status
+        ))
+    }
+
+    #[getter]
+    fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    #[getter]
+    fn bytes_scanned(&self) -> usize {
+        self.bytes_scanned
+    }
+
+    /// Seconds spent scanning the file
+    #[getter]
+    fn elapsed(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    #[getter]
+    fn matches(&self) -> usize {
+        self.matches
+    }
+
+    #[getter]
+    fn skipped(&self) -> Option<String> {
+        self.skipped.clone()
+    }
+
+    #[getter]
+    fn error(&self) -> Option<String> {
+        self.error.clone()
+    }
+
+    /// Whether the file was read and searched
+    #[getter]
+    fn scanned(&self) -> bool {
+        self.skipped.is_none() && self.error.is_none()
+    }
+}
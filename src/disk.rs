@@ -0,0 +1,99 @@
+//! Disk image scans: raw images and block devices swept in large sector-aligned chunks
+//! regardless of their filesystem, unallocated space included, and EnCase E01 images with the
+//! `ewf` feature, matches being addressed by sector
+
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use std::fs::File;
+use std::io::{Cursor, Read, Result};
+
+/// Signature starting every segment file of an E01 image
+pub(crate) const EWF_SIGNATURE: &[u8] = b"EVF\x09\x0D\x0A\xFF\x00";
+
+const DEFAULT_SECTOR_SIZE: usize = 512;
+const DEFAULT_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Options of a disk image scan
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiskOptions {
+    /// Bytes per sector, by default that recorded in an E01 image or 512
+    pub sector_size: Option<usize>,
+    /// Bytes read at a time, rounded up to whole sectors, 16MB by default
+    pub chunk_size: Option<usize>,
+    /// Threads scanning the chunks, one per core by default
+    pub n_threads: Option<usize>,
+}
+
+/// Matches of a disk image
+pub struct DiskScan {
+    pub sector_size: usize,
+    /// (start, end, pattern) matches, offsets being those of the image's media
+    pub matches: Vec<(usize, usize, PatternID)>,
+}
+
+impl DiskScan {
+    /// Scan the raw image, block device or E01 image at `path`, read sequentially in chunks
+    /// scanned in parallel
+    pub fn scan(matcher: &Matcher, path: &str, options: DiskOptions) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut signature = Vec::new();
+        (&mut file)
+            .take(EWF_SIGNATURE.len() as u64)
+            .read_to_end(&mut signature)?;
+        if signature == EWF_SIGNATURE {
+            let (reader, sector_size) = open_ewf(path)?;
+            return Self::scan_reader(
+                matcher,
+                reader,
+                options.sector_size.unwrap_or(sector_size),
+                options,
+            );
+        }
+
+        // Block devices and pipes are read as they are, without seeking back
+        let reader = Cursor::new(signature).chain(file);
+        let sector_size = options.sector_size.unwrap_or(DEFAULT_SECTOR_SIZE);
+        Self::scan_reader(matcher, reader, sector_size, options)
+    }
+
+    fn scan_reader(
+        matcher: &Matcher,
+        reader: impl Read,
+        sector_size: usize,
+        options: DiskOptions,
+    ) -> Result<Self> {
+        let sector_size = sector_size.max(1);
+        let chunk_size = options
+            .chunk_size
+            .unwrap_or(DEFAULT_CHUNK_SIZE)
+            .div_ceil(sector_size)
+            .max(1)
+            * sector_size;
+        let matches = matcher.match_reader_parallel(reader, chunk_size, options.n_threads)?;
+        Ok(Self {
+            sector_size,
+            matches,
+        })
+    }
+
+    /// Sector holding the byte at `offset`
+    pub fn sector(&self, offset: usize) -> usize {
+        offset / self.sector_size
+    }
+}
+
+/// Reader of the media of the E01 image at `path`, and its bytes per sector
+#[cfg(feature = "ewf")]
+fn open_ewf(path: &str) -> Result<(crate::ewf::EwfReader, usize)> {
+    let reader = crate::ewf::EwfReader::open(path)?;
+    let sector_size = reader.sector_size();
+    Ok((reader, sector_size))
+}
+
+#[cfg(not(feature = "ewf"))]
+fn open_ewf(_path: &str) -> Result<(std::io::Empty, usize)> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "E01 images require voluta to be built with the ewf feature",
+    ))
+}
@@ -0,0 +1,117 @@
+//! Legacy single-byte encodings of scanned text, decoded to UTF-8 before the search so
+//! patterns with accented characters hit files written by older Windows systems, with
+//! offsets mapped back to the raw bytes
+
+/// Characters of the Windows-1252 bytes 0x80 to 0x9F, the bytes Windows leaves undefined
+/// decoding to the C1 control of the same value as in Latin-1
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Bytes at the start of an input `Encoding::Auto` detects its encoding from, once for all
+/// the chunks of the input
+pub const AUTO_DETECT_LEN: usize = 1 << 16;
+
+/// Encoding of the scanned bytes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Bytes are searched as they are
+    #[default]
+    Utf8,
+    /// ISO-8859-1, every byte is the code point of the same value
+    Latin1,
+    /// Windows-1252, Latin-1 with printable characters in place of the C1 controls
+    Windows1252,
+    /// UTF-8 when the first `AUTO_DETECT_LEN` bytes of the input are valid UTF-8,
+    /// Windows-1252 otherwise; a superset of the printable Latin-1 characters, so Latin-1
+    /// text decodes the same
+    Auto,
+}
+
+impl Encoding {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            "latin-1" | "latin1" | "iso-8859-1" => Some(Self::Latin1),
+            "windows-1252" | "cp1252" => Some(Self::Windows1252),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Latin1 => "latin-1",
+            Self::Windows1252 => "windows-1252",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Encoding of an input starting with `data`, `Auto` being resolved from its first
+    /// `AUTO_DETECT_LEN` bytes
+    pub(crate) fn detect(self, data: &[u8]) -> Self {
+        match self {
+            Self::Auto if is_utf8(&data[..data.len().min(AUTO_DETECT_LEN)]) => Self::Utf8,
+            Self::Auto => Self::Windows1252,
+            encoding => encoding,
+        }
+    }
+
+    /// UTF-8 text of `data`, None when `data` is searched as it is
+    /// `Auto` detects the encoding of `data` as a whole input, chunked scans resolve it once
+    /// for all their chunks before
+    pub(crate) fn decode(self, data: &[u8]) -> Option<Decoded> {
+        let windows = match self.detect(data) {
+            Self::Utf8 => return None,
+            Self::Latin1 => false,
+            _ => true,
+        };
+        if data.is_ascii() {
+            return None;
+        }
+
+        let mut text = Vec::with_capacity(data.len() + data.len() / 4);
+        let mut offsets = Vec::with_capacity(text.capacity());
+        let mut utf8 = [0; 4];
+        for (pos, &byte) in data.iter().enumerate() {
+            let c = match byte {
+                0x80..=0x9F if windows => WINDOWS_1252_HIGH[usize::from(byte - 0x80)],
+                _ => char::from(byte),
+            };
+            let encoded = c.encode_utf8(&mut utf8);
+            text.extend_from_slice(encoded.as_bytes());
+            offsets.extend(std::iter::repeat_n(pos, encoded.len()));
+        }
+        Some(Decoded { text, offsets })
+    }
+}
+
+/// Check if `data` is UTF-8, but for a character cut by the end of the buffer, as the end of
+/// the detected prefix cuts them
+fn is_utf8(data: &[u8]) -> bool {
+    match std::str::from_utf8(data) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+/// UTF-8 text decoded from a legacy encoding, with the raw offset of every byte of the text
+pub(crate) struct Decoded {
+    text: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Decoded {
+    pub(crate) fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Raw span of the characters of `text[start..end]`
+    pub(crate) fn span(&self, start: usize, end: usize) -> std::ops::Range<usize> {
+        self.offsets[start]..self.offsets[end - 1] + 1
+    }
+}
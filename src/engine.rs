@@ -0,0 +1,871 @@
+//! Search backends of a Matcher, all reporting matches with the Aho-Corasick semantics
+
+use crate::budget::MemoryBudget;
+use crate::matcher::ConfigError;
+use crate::tags::TagFilter;
+use aho_corasick::packed::{self, MatchKind};
+use aho_corasick::{
+    AhoCorasick, AhoCorasickBuilder, AhoCorasickKind, FindIter, FindOverlappingIter, Match,
+    PatternID, Span,
+};
+use memchr::memmem;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+/// Engine used to search for the patterns of a matcher
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Pick the engine expected to be the fastest for the pattern set
+    #[default]
+    Auto,
+    /// Aho-Corasick DFA
+    Aho,
+    /// memchr::memmem for a single exact pattern, the Teddy SIMD searcher for a few patterns,
+    /// limited to `TINY_MAX_PATTERNS` patterns of up to `TINY_MAX_PATTERN_LEN` bytes
+    Memmem,
+    /// Vectorscan, a Hyperscan fork, available with the `hyperscan` feature
+    Hyperscan,
+    /// OpenCL kernel for very large haystacks, experimental, available with the `gpu` feature
+    /// Falls back to the Aho-Corasick DFA when no OpenCL device is found
+    Gpu,
+}
+
+impl Engine {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Self::Auto),
+            "aho" => Some(Self::Aho),
+            "memmem" => Some(Self::Memmem),
+            "hyperscan" => Some(Self::Hyperscan),
+            "gpu" => Some(Self::Gpu),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Aho => "aho",
+            Self::Memmem => "memmem",
+            Self::Hyperscan => "hyperscan",
+            Self::Gpu => "gpu",
+        }
+    }
+}
+
+/// Largest pattern sets searched without an automaton, where building and driving the DFA
+/// costs more than the search itself on short messages
+pub const TINY_MAX_PATTERNS: usize = 8;
+pub const TINY_MAX_PATTERN_LEN: usize = 32;
+
+/// Pattern count from which `auto` picks Vectorscan, whose literal matcher keeps scanning fast
+/// where the Aho-Corasick DFA outgrows the CPU caches
+#[cfg(feature = "hyperscan")]
+const HYPERSCAN_MIN_PATTERNS: usize = 2000;
+
+/// Backend built for the patterns of a matcher
+pub(crate) enum Backend {
+    Aho(AhoCorasick),
+    Tiny(Box<Tiny>),
+    #[cfg(feature = "hyperscan")]
+    Hyperscan(hyperscan::Database),
+    #[cfg(feature = "gpu")]
+    Gpu(Box<gpu::Gpu>),
+}
+
+impl Backend {
+    pub(crate) fn build(
+        patterns: &[String],
+        case_insensitive: bool,
+        engine: Engine,
+    ) -> Result<Self, ConfigError> {
+        Self::build_within(patterns, case_insensitive, engine, None)
+    }
+
+    /// Backend whose Aho-Corasick automaton, if it builds one, fits the memory budget
+    pub(crate) fn build_within(
+        patterns: &[String],
+        case_insensitive: bool,
+        engine: Engine,
+        budget: Option<MemoryBudget>,
+    ) -> Result<Self, ConfigError> {
+        #[cfg(feature = "hyperscan")]
+        let engine = match engine {
+            Engine::Auto if patterns.len() >= HYPERSCAN_MIN_PATTERNS => Engine::Hyperscan,
+            engine => engine,
+        };
+
+        match engine {
+            Engine::Auto => match Tiny::new(patterns, case_insensitive) {
+                Some(tiny) => Ok(Self::Tiny(Box::new(tiny))),
+                None => Self::aho(patterns, case_insensitive, AhoCorasickKind::DFA, budget),
+            },
+            Engine::Aho => Self::aho(patterns, case_insensitive, AhoCorasickKind::DFA, budget),
+            Engine::Memmem => Tiny::new(patterns, case_insensitive)
+                .map(|tiny| Self::Tiny(Box::new(tiny)))
+                .ok_or_else(|| {
+                    ConfigError::Limit(format!(
+                        "The memmem engine supports up to {} patterns of at most {} bytes",
+                        TINY_MAX_PATTERNS, TINY_MAX_PATTERN_LEN
+                    ))
+                }),
+            #[cfg(feature = "hyperscan")]
+            Engine::Hyperscan => Ok(Self::Hyperscan(hyperscan::Database::new(
+                patterns,
+                case_insensitive,
+            )?)),
+            #[cfg(not(feature = "hyperscan"))]
+            Engine::Hyperscan => Err(ConfigError::Invalid(
+                "The hyperscan engine requires voluta to be built with the hyperscan feature"
+                    .to_string(),
+            )),
+            #[cfg(feature = "gpu")]
+            Engine::Gpu => {
+                // The device keeps a DFA for the haystacks left to the CPU
+                let kind = match budget {
+                    Some(budget) => {
+                        budget.kind(patterns, case_insensitive, AhoCorasickKind::DFA)?
+                    }
+                    None => AhoCorasickKind::DFA,
+                };
+                let gpu = match kind {
+                    AhoCorasickKind::DFA => gpu::Gpu::new(patterns, case_insensitive),
+                    _ => None,
+                };
+                match gpu {
+                    Some(gpu) => Ok(Self::Gpu(Box::new(gpu))),
+                    None => Self::aho(patterns, case_insensitive, kind, None),
+                }
+            }
+            #[cfg(not(feature = "gpu"))]
+            Engine::Gpu => Err(ConfigError::Invalid(
+                "The gpu engine requires voluta to be built with the gpu feature".to_string(),
+            )),
+        }
+    }
+
+    /// Backend for patterns expanded with their fuzzy variants: thousands of near-identical
+    /// patterns whose DFA would take hundreds of megabytes, so the Aho-Corasick engine builds
+    /// its contiguous NFA instead, searching about as fast in a fraction of the memory
+    pub(crate) fn build_expanded(
+        patterns: &[String],
+        case_insensitive: bool,
+        engine: Engine,
+        budget: Option<MemoryBudget>,
+    ) -> Result<Self, ConfigError> {
+        match engine {
+            Engine::Auto | Engine::Aho => Self::aho(
+                patterns,
+                case_insensitive,
+                AhoCorasickKind::ContiguousNFA,
+                budget,
+            ),
+            engine => Self::build_within(patterns, case_insensitive, engine, budget),
+        }
+    }
+
+    /// Aho-Corasick automaton of the preferred kind, or the kind the budget leaves room for
+    fn aho(
+        patterns: &[String],
+        case_insensitive: bool,
+        kind: AhoCorasickKind,
+        budget: Option<MemoryBudget>,
+    ) -> Result<Self, ConfigError> {
+        let kind = match budget {
+            Some(budget) => budget.kind(patterns, case_insensitive, kind)?,
+            None => kind,
+        };
+        Ok(Self::Aho(
+            AhoCorasickBuilder::new()
+                .kind(Some(kind))
+                .ascii_case_insensitive(case_insensitive)
+                .build(patterns)
+                .unwrap(),
+        ))
+    }
+
+    /// Kind of the Aho-Corasick automaton searched on the CPU, None for the other backends
+    pub(crate) fn automaton(&self) -> Option<AhoCorasickKind> {
+        match self {
+            Self::Aho(ac) => Some(ac.kind()),
+            #[cfg(feature = "gpu")]
+            Self::Gpu(gpu) => Some(gpu.cpu().kind()),
+            _ => None,
+        }
+    }
+
+    /// Engine actually used, never `Auto`
+    pub(crate) fn engine(&self) -> Engine {
+        match self {
+            Self::Aho(_) => Engine::Aho,
+            Self::Tiny(_) => Engine::Memmem,
+            #[cfg(feature = "hyperscan")]
+            Self::Hyperscan(_) => Engine::Hyperscan,
+            #[cfg(feature = "gpu")]
+            Self::Gpu(_) => Engine::Gpu,
+        }
+    }
+
+    /// Heap bytes used by the backend
+    pub(crate) fn memory_usage(&self) -> usize {
+        match self {
+            Self::Aho(ac) => ac.memory_usage(),
+            Self::Tiny(tiny) => tiny.memory_usage(),
+            #[cfg(feature = "hyperscan")]
+            Self::Hyperscan(db) => db.memory_usage(),
+            #[cfg(feature = "gpu")]
+            Self::Gpu(gpu) => gpu.memory_usage(),
+        }
+    }
+
+    /// Non-overlapping matches, as found by the standard Aho-Corasick search
+    pub(crate) fn find_iter<'a, 'h>(&'a self, haystack: &'h [u8]) -> Matches<'a, 'h> {
+        match self {
+            Self::Aho(ac) => Matches::Standard(ac.find_iter(haystack)),
+            Self::Tiny(tiny) => {
+                Matches::Buffered(standard(tiny.find_overlapping(haystack)).into_iter())
+            }
+            #[cfg(feature = "hyperscan")]
+            Self::Hyperscan(db) => {
+                Matches::Buffered(standard(db.find_overlapping(haystack)).into_iter())
+            }
+            #[cfg(feature = "gpu")]
+            Self::Gpu(gpu) => match gpu.find_overlapping(haystack) {
+                Some(matches) => Matches::Buffered(standard(matches).into_iter()),
+                None => Matches::Standard(gpu.cpu().find_iter(haystack)),
+            },
+        }
+    }
+
+    /// Every occurrence of every pattern
+    pub(crate) fn find_overlapping_iter<'a, 'h>(&'a self, haystack: &'h [u8]) -> Matches<'a, 'h> {
+        match self {
+            Self::Aho(ac) => Matches::Overlapping(ac.find_overlapping_iter(haystack)),
+            Self::Tiny(tiny) => Matches::Buffered(tiny.find_overlapping(haystack).into_iter()),
+            #[cfg(feature = "hyperscan")]
+            Self::Hyperscan(db) => Matches::Buffered(db.find_overlapping(haystack).into_iter()),
+            #[cfg(feature = "gpu")]
+            Self::Gpu(gpu) => match gpu.find_overlapping(haystack) {
+                Some(matches) => Matches::Buffered(matches.into_iter()),
+                None => Matches::Overlapping(gpu.cpu().find_overlapping_iter(haystack)),
+            },
+        }
+    }
+}
+
+pub(crate) enum Matches<'a, 'h> {
+    Standard(FindIter<'a, 'h>),
+    Overlapping(FindOverlappingIter<'a, 'h>),
+    Buffered(std::vec::IntoIter<Match>),
+    /// Matches of searched pattern `i` reported as pattern `originals[i]`
+    Mapped(Box<Matches<'a, 'h>>, &'a [PatternID]),
+    /// Matches of the patterns active under a tag filter
+    Filtered(Box<Matches<'a, 'h>>, &'a TagFilter),
+}
+
+impl<'a, 'h> Matches<'a, 'h> {
+    /// Report the matches of the searched patterns as the patterns they stand for, the
+    /// searched patterns being those reported when `originals` is empty
+    pub(crate) fn originals(self, originals: &'a [PatternID]) -> Self {
+        if originals.is_empty() {
+            self
+        } else {
+            Self::Mapped(Box::new(self), originals)
+        }
+    }
+
+    /// Only report the matches of the patterns active under `filter`, if any
+    pub(crate) fn active(self, filter: Option<&'a TagFilter>) -> Self {
+        match filter {
+            Some(filter) => Self::Filtered(Box::new(self), filter),
+            None => self,
+        }
+    }
+}
+
+impl Iterator for Matches<'_, '_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        match self {
+            Self::Standard(iter) => iter.next(),
+            Self::Overlapping(iter) => iter.next(),
+            Self::Buffered(iter) => iter.next(),
+            Self::Mapped(iter, originals) => iter
+                .next()
+                .map(|m| Match::new(originals[m.pattern().as_usize()], m.span())),
+            Self::Filtered(iter, filter) => iter.find(|m| filter.is_active(m.pattern())),
+        }
+    }
+}
+
+/// Order matches like the overlapping Aho-Corasick search: by end offset, then longest first
+pub(crate) fn overlapping_order(matches: &mut [Match]) {
+    matches.sort_unstable_by_key(|m| (m.end(), Reverse(m.len()), m.pattern()));
+}
+
+/// Non-overlapping matches out of every match in overlapping order, as found by the standard
+/// Aho-Corasick search: the earliest ending match, the longest on ties, then the search
+/// restarts where it ended
+pub(crate) fn standard(matches: Vec<Match>) -> Vec<Match> {
+    let mut searched = 0;
+    matches
+        .into_iter()
+        .filter(|m| {
+            if m.start() < searched {
+                return false;
+            }
+            searched = m.end();
+            true
+        })
+        .collect()
+}
+
+/// Searcher for a handful of short patterns
+/// A single exact pattern is found with memmem, otherwise Teddy finds the candidate starts from
+/// prefixes of the patterns, in every ASCII case when case-insensitive, and each pattern is
+/// checked at these starts
+pub(crate) struct Tiny {
+    patterns: Vec<Vec<u8>>,
+    case_insensitive: bool,
+    search: TinySearch,
+}
+
+enum TinySearch {
+    Memmem(memmem::Finder<'static>),
+    Teddy(packed::Searcher),
+}
+
+/// Leading bytes Teddy fingerprints at most, it uses as many as the shortest pattern has
+const TEDDY_PREFIX_LEN: usize = 3;
+
+impl Tiny {
+    /// None if the patterns are too many or too long, or Teddy is unavailable on this CPU
+    fn new(patterns: &[String], case_insensitive: bool) -> Option<Self> {
+        if patterns.len() > TINY_MAX_PATTERNS
+            || patterns.iter().any(|p| p.len() > TINY_MAX_PATTERN_LEN)
+        {
+            return None;
+        }
+
+        let patterns: Vec<Vec<u8>> = patterns.iter().map(|p| p.as_bytes().to_vec()).collect();
+        let caseless = |p: &[u8]| case_insensitive && p.iter().any(u8::is_ascii_alphabetic);
+        let search = match patterns.as_slice() {
+            [pattern] if !caseless(pattern) => {
+                TinySearch::Memmem(memmem::Finder::new(pattern).into_owned())
+            }
+            _ => {
+                // Prefixes of the same length keep the case variants within Teddy's limits
+                let prefix_len = patterns
+                    .iter()
+                    .map(|p| p.len())
+                    .min()
+                    .unwrap_or(0)
+                    .min(TEDDY_PREFIX_LEN);
+                let mut prefixes = HashSet::new();
+                for pattern in &patterns {
+                    let prefix = &pattern[..prefix_len];
+                    let mut variants = vec![Vec::new()];
+                    for &byte in prefix {
+                        let cases = if case_insensitive && byte.is_ascii_alphabetic() {
+                            vec![byte.to_ascii_lowercase(), byte.to_ascii_uppercase()]
+                        } else {
+                            vec![byte]
+                        };
+                        variants = variants
+                            .into_iter()
+                            .flat_map(|v: Vec<u8>| {
+                                cases.iter().map(move |&b| [v.as_slice(), &[b]].concat())
+                            })
+                            .collect();
+                    }
+                    prefixes.extend(variants);
+                }
+
+                let mut builder = packed::Config::new()
+                    .match_kind(MatchKind::LeftmostFirst)
+                    .builder();
+                builder.extend(prefixes);
+                TinySearch::Teddy(builder.build()?)
+            }
+        };
+
+        Some(Self {
+            patterns,
+            case_insensitive,
+            search,
+        })
+    }
+
+    /// Every occurrence of every pattern, in overlapping order
+    fn find_overlapping(&self, haystack: &[u8]) -> Vec<Match> {
+        let mut matches = Vec::new();
+        match &self.search {
+            TinySearch::Memmem(finder) => {
+                let len = self.patterns[0].len();
+                let mut at = 0;
+                while let Some(pos) = finder.find(&haystack[at..]) {
+                    let start = at + pos;
+                    matches.push(Match::must(0, start..start + len));
+                    at = start + 1;
+                }
+            }
+            TinySearch::Teddy(searcher) => {
+                // Candidates are the leftmost prefix hits, so every start with a match is visited
+                let mut at = 0;
+                while let Some(hit) = searcher.find_in(haystack, Span::from(at..haystack.len())) {
+                    let start = hit.start();
+                    for (idx, pattern) in self.patterns.iter().enumerate() {
+                        let end = start + pattern.len();
+                        if end <= haystack.len() && self.eq(&haystack[start..end], pattern) {
+                            matches.push(Match::must(idx, start..end));
+                        }
+                    }
+                    at = start + 1;
+                }
+                overlapping_order(&mut matches);
+            }
+        }
+        matches
+    }
+
+    fn memory_usage(&self) -> usize {
+        let patterns: usize = self.patterns.iter().map(|p| p.len()).sum();
+        patterns
+            + match &self.search {
+                TinySearch::Memmem(finder) => finder.needle().len(),
+                TinySearch::Teddy(searcher) => searcher.memory_usage(),
+            }
+    }
+
+    fn eq(&self, text: &[u8], pattern: &[u8]) -> bool {
+        if self.case_insensitive {
+            text.eq_ignore_ascii_case(pattern)
+        } else {
+            text == pattern
+        }
+    }
+}
+
+#[cfg(feature = "hyperscan")]
+mod hyperscan {
+    use super::overlapping_order;
+    use crate::matcher::ConfigError;
+    use aho_corasick::Match;
+    use std::cmp;
+    use std::fmt::Write;
+    use vectorscan_rs::{BlockDatabase, Flag, Pattern, Scan};
+
+    /// Vectorscan block database of literal patterns
+    pub(crate) struct Database {
+        db: BlockDatabase,
+        pattern_lens: Vec<usize>,
+        max_pattern_len: usize,
+    }
+
+    impl Database {
+        pub(crate) fn new(
+            patterns: &[String],
+            case_insensitive: bool,
+        ) -> Result<Self, ConfigError> {
+            let flags = if case_insensitive {
+                Flag::CASELESS
+            } else {
+                Flag::default()
+            };
+            let expressions = patterns
+                .iter()
+                .enumerate()
+                .map(|(idx, p)| Pattern::new(literal(p.as_bytes()), flags, Some(idx as u32)))
+                .collect();
+            let db = BlockDatabase::new(expressions).map_err(|e| {
+                ConfigError::Invalid(format!(
+                    "Could not compile the patterns with vectorscan: {}",
+                    e
+                ))
+            })?;
+
+            let pattern_lens: Vec<usize> = patterns.iter().map(|p| p.len()).collect();
+            Ok(Self {
+                db,
+                max_pattern_len: pattern_lens.iter().copied().max().unwrap_or(0),
+                pattern_lens,
+            })
+        }
+
+        pub(crate) fn memory_usage(&self) -> usize {
+            self.db.size().unwrap_or(0) + self.pattern_lens.len() * std::mem::size_of::<usize>()
+        }
+
+        /// Every occurrence of every pattern, in overlapping order
+        pub(crate) fn find_overlapping(&self, haystack: &[u8]) -> Vec<Match> {
+            // Scratch space is allocated per scan, the database itself is shared by threads
+            let mut scanner = self
+                .db
+                .create_scanner()
+                .expect("could not allocate vectorscan scratch space");
+            let mut matches = Vec::new();
+
+            // Block scans take at most u32::MAX bytes, longer haystacks are scanned in blocks
+            // overlapping by one pattern length, each match kept by the block it starts in
+            let overlap = self.max_pattern_len.saturating_sub(1);
+            let block = u32::MAX as usize - overlap;
+            let mut offset = 0;
+            loop {
+                let block_end = cmp::min(offset + block + overlap, haystack.len());
+                let last = block_end == haystack.len();
+                scanner
+                    .scan(&haystack[offset..block_end], |id, _, to, _| {
+                        let end = offset + to as usize;
+                        let start = end - self.pattern_lens[id as usize];
+                        if last || start < offset + block {
+                            matches.push(Match::must(id as usize, start..end));
+                        }
+                        Scan::Continue
+                    })
+                    .expect("vectorscan scan failed");
+                if last {
+                    break;
+                }
+                offset += block;
+            }
+
+            overlapping_order(&mut matches);
+            matches
+        }
+    }
+
+    /// Vectorscan expression matching `pattern` literally
+    fn literal(pattern: &[u8]) -> Vec<u8> {
+        let mut expression = String::with_capacity(pattern.len() * 4);
+        for byte in pattern {
+            let _ = write!(expression, "\\x{:02x}", byte);
+        }
+        expression.into_bytes()
+    }
+}
+
+#[cfg(feature = "gpu")]
+mod gpu {
+    use super::overlapping_order;
+    use aho_corasick::{AhoCorasick, AhoCorasickBuilder, AhoCorasickKind, Match};
+    use opencl3::command_queue::{CommandQueue, CL_BLOCKING};
+    use opencl3::context::Context;
+    use opencl3::device::{get_all_devices, Device, CL_DEVICE_TYPE_GPU};
+    use opencl3::kernel::{ExecuteKernel, Kernel};
+    use opencl3::memory::{Buffer, CL_MEM_COPY_HOST_PTR, CL_MEM_READ_ONLY, CL_MEM_READ_WRITE};
+    use opencl3::program::Program;
+    use opencl3::types::{cl_uchar, cl_uint};
+    use opencl3::Result;
+    use std::sync::{Mutex, TryLockError};
+    use std::{cmp, ptr};
+
+    /// Haystacks below this size are scanned on the CPU, copying them to the device costs more
+    /// than the scan itself
+    const MIN_HAYSTACK_LEN: usize = 1024 * 1024;
+    /// Largest block copied to the device at once
+    const MAX_BLOCK_LEN: usize = 256 * 1024 * 1024;
+    /// Haystack bytes per reserved hit slot on the first scan of a block, blocks with more hits
+    /// are scanned again with room for all of them
+    const BYTES_PER_HIT: usize = 64;
+    /// Patterns of two bytes or more are bucketed by their first two bytes, single byte patterns
+    /// by their byte after them
+    const BUCKETS: usize = 65536 + 256;
+
+    /// One work item per haystack offset, checking the patterns of the buckets of the bytes
+    /// there and reporting every pattern starting at that offset
+    const KERNEL: &str = r#"
+inline uchar fold(uchar b, uint caseless) {
+    return caseless && b >= 'A' && b <= 'Z' ? (uchar)(b + 32) : b;
+}
+
+__kernel void find(__global const uchar *haystack, const uint len,
+                   __global const uchar *patterns, __global const uint *offsets,
+                   __global const uint *buckets, __global const uint *ids,
+                   const uint caseless, __global uint *count, __global uint2 *hits,
+                   const uint capacity) {
+    uint start = get_global_id(0);
+    if (start >= len) {
+        return;
+    }
+    uchar first = fold(haystack[start], caseless);
+    uint keys[2];
+    uint n = 0;
+    keys[n++] = 65536 + first;
+    if (start + 1 < len) {
+        keys[n++] = ((uint)first << 8) | fold(haystack[start + 1], caseless);
+    }
+    for (uint k = 0; k < n; k++) {
+        for (uint b = buckets[keys[k]]; b < buckets[keys[k] + 1]; b++) {
+            uint id = ids[b];
+            uint from = offsets[id];
+            uint pattern_len = offsets[id + 1] - from;
+            if (pattern_len > len - start) {
+                continue;
+            }
+            uint i = 0;
+            while (i < pattern_len && fold(haystack[start + i], caseless) == patterns[from + i]) {
+                i++;
+            }
+            if (i == pattern_len) {
+                uint slot = atomic_inc(count);
+                if (slot < capacity) {
+                    hits[slot] = (uint2)(start, id);
+                }
+            }
+        }
+    }
+}
+"#;
+
+    /// OpenCL searcher, with the Aho-Corasick DFA scanning the haystacks it does not take
+    pub(crate) struct Gpu {
+        cpu: AhoCorasick,
+        device: Mutex<DeviceScan>,
+        pattern_lens: Vec<usize>,
+        max_pattern_len: usize,
+        max_block_len: usize,
+        table_len: usize,
+    }
+
+    /// Kernel and pattern tables on the device, used by one scan at a time
+    struct DeviceScan {
+        queue: CommandQueue,
+        kernel: Kernel,
+        patterns: Buffer<cl_uchar>,
+        offsets: Buffer<cl_uint>,
+        buckets: Buffer<cl_uint>,
+        ids: Buffer<cl_uint>,
+        caseless: cl_uint,
+        // Released last, after everything created in it
+        _program: Program,
+        context: Context,
+    }
+
+    impl Gpu {
+        /// None without an OpenCL GPU, or if the kernel does not build on it
+        pub(crate) fn new(patterns: &[String], case_insensitive: bool) -> Option<Self> {
+            if patterns.iter().any(|p| p.is_empty()) {
+                return None;
+            }
+            let pattern_lens: Vec<usize> = patterns.iter().map(|p| p.len()).collect();
+            let max_pattern_len = pattern_lens.iter().copied().max().unwrap_or(0);
+
+            let (device, max_block_len, table_len) =
+                DeviceScan::new(patterns, case_insensitive).ok().flatten()?;
+            let cpu = AhoCorasickBuilder::new()
+                .kind(Some(AhoCorasickKind::DFA))
+                .ascii_case_insensitive(case_insensitive)
+                .build(patterns)
+                .unwrap();
+            Some(Self {
+                cpu,
+                device: Mutex::new(device),
+                pattern_lens,
+                max_pattern_len,
+                max_block_len: cmp::max(max_block_len, max_pattern_len * 2),
+                table_len,
+            })
+        }
+
+        /// Automaton scanning the haystacks left to the CPU
+        pub(crate) fn cpu(&self) -> &AhoCorasick {
+            &self.cpu
+        }
+
+        pub(crate) fn memory_usage(&self) -> usize {
+            self.cpu.memory_usage() + self.table_len
+        }
+
+        /// Every occurrence of every pattern, in overlapping order
+        /// None when the haystack is left to the CPU: too short, the device busy with another
+        /// scan, or the scan failed on the device
+        pub(crate) fn find_overlapping(&self, haystack: &[u8]) -> Option<Vec<Match>> {
+            if haystack.len() < MIN_HAYSTACK_LEN {
+                return None;
+            }
+            let device = match self.device.try_lock() {
+                Ok(device) => device,
+                Err(TryLockError::WouldBlock) => return None,
+                Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            };
+
+            // Blocks overlap by one pattern length, each match kept by the block it starts in
+            let overlap = self.max_pattern_len - 1;
+            let block = self.max_block_len - overlap;
+            let mut matches = Vec::new();
+            let mut offset = 0;
+            loop {
+                let block_end = cmp::min(offset + block + overlap, haystack.len());
+                let last = block_end == haystack.len();
+                let hits = device.find(&haystack[offset..block_end]).ok()?;
+                for (start, id) in hits {
+                    let start = offset + start as usize;
+                    if last || start < offset + block {
+                        let end = start + self.pattern_lens[id as usize];
+                        matches.push(Match::must(id as usize, start..end));
+                    }
+                }
+                if last {
+                    break;
+                }
+                offset += block;
+            }
+
+            overlapping_order(&mut matches);
+            Some(matches)
+        }
+    }
+
+    impl DeviceScan {
+        /// The scan, the largest block it takes and the bytes of its tables, None without a GPU
+        fn new(
+            patterns: &[String],
+            case_insensitive: bool,
+        ) -> Result<Option<(Self, usize, usize)>> {
+            let Some(&device_id) = get_all_devices(CL_DEVICE_TYPE_GPU)?.first() else {
+                return Ok(None);
+            };
+            let device = Device::new(device_id);
+            let max_block_len = cmp::min(device.max_mem_alloc_size()? as usize, MAX_BLOCK_LEN);
+            let context = Context::from_device(&device)?;
+            let Ok(program) = Program::create_and_build_from_source(&context, KERNEL, "") else {
+                return Ok(None);
+            };
+            let kernel = Kernel::create(&program, "find")?;
+            #[allow(deprecated)]
+            let queue = CommandQueue::create_default(&context, 0)?;
+
+            // Patterns are stored folded, the kernel folds the haystack the same way
+            let mut bytes: Vec<cl_uchar> = Vec::new();
+            let mut offsets: Vec<cl_uint> = vec![0];
+            for pattern in patterns {
+                if case_insensitive {
+                    bytes.extend(pattern.bytes().map(|b| b.to_ascii_lowercase()));
+                } else {
+                    bytes.extend(pattern.bytes());
+                }
+                offsets.push(bytes.len() as cl_uint);
+            }
+
+            let mut keyed: Vec<(usize, cl_uint)> = (0..patterns.len())
+                .map(|idx| {
+                    let pattern = &bytes[offsets[idx] as usize..offsets[idx + 1] as usize];
+                    let key = match pattern {
+                        [byte] => 65536 + *byte as usize,
+                        [first, second, ..] => (*first as usize) << 8 | *second as usize,
+                        [] => unreachable!("empty patterns are searched on the CPU"),
+                    };
+                    (key, idx as cl_uint)
+                })
+                .collect();
+            keyed.sort_unstable();
+            let mut ids: Vec<cl_uint> = keyed.iter().map(|&(_, idx)| idx).collect();
+            let mut buckets: Vec<cl_uint> = vec![0; BUCKETS + 1];
+            for &(key, _) in &keyed {
+                buckets[key + 1] += 1;
+            }
+            for key in 0..BUCKETS {
+                buckets[key + 1] += buckets[key];
+            }
+
+            let table_len = std::mem::size_of_val(bytes.as_slice())
+                + std::mem::size_of_val(offsets.as_slice())
+                + std::mem::size_of_val(buckets.as_slice())
+                + std::mem::size_of_val(ids.as_slice());
+            // An empty pattern set makes empty buffers, which fail to create, leaving it to the CPU
+            let scan = Self {
+                patterns: read_only(&context, &mut bytes)?,
+                offsets: read_only(&context, &mut offsets)?,
+                buckets: read_only(&context, &mut buckets)?,
+                ids: read_only(&context, &mut ids)?,
+                caseless: case_insensitive as cl_uint,
+                queue,
+                kernel,
+                _program: program,
+                context,
+            };
+            Ok(Some((scan, max_block_len, table_len)))
+        }
+
+        /// Start offset and pattern of every match in `block`, unordered
+        fn find(&self, block: &[u8]) -> Result<Vec<(cl_uint, cl_uint)>> {
+            let context = &self.context;
+            let len = block.len() as cl_uint;
+            let mut capacity = block.len() / BYTES_PER_HIT + 1024;
+            loop {
+                let mut haystack = unsafe {
+                    Buffer::<cl_uchar>::create(
+                        context,
+                        CL_MEM_READ_ONLY,
+                        block.len(),
+                        ptr::null_mut(),
+                    )?
+                };
+                let mut count = unsafe {
+                    Buffer::<cl_uint>::create(context, CL_MEM_READ_WRITE, 1, ptr::null_mut())?
+                };
+                let hits = unsafe {
+                    Buffer::<[cl_uint; 2]>::create(
+                        context,
+                        CL_MEM_READ_WRITE,
+                        capacity,
+                        ptr::null_mut(),
+                    )?
+                };
+                let capacity_arg = capacity as cl_uint;
+                unsafe {
+                    self.queue
+                        .enqueue_write_buffer(&mut haystack, CL_BLOCKING, 0, block, &[])?;
+                    self.queue
+                        .enqueue_write_buffer(&mut count, CL_BLOCKING, 0, &[0], &[])?;
+                    ExecuteKernel::new(&self.kernel)
+                        .set_arg(&haystack)
+                        .set_arg(&len)
+                        .set_arg(&self.patterns)
+                        .set_arg(&self.offsets)
+                        .set_arg(&self.buckets)
+                        .set_arg(&self.ids)
+                        .set_arg(&self.caseless)
+                        .set_arg(&count)
+                        .set_arg(&hits)
+                        .set_arg(&capacity_arg)
+                        .set_global_work_size(block.len())
+                        .enqueue_nd_range(&self.queue)?;
+                }
+                self.queue.finish()?;
+
+                let mut found: [cl_uint; 1] = [0];
+                unsafe {
+                    self.queue
+                        .enqueue_read_buffer(&count, CL_BLOCKING, 0, &mut found, &[])?;
+                }
+                let found = found[0] as usize;
+                if found > capacity {
+                    capacity = found;
+                    continue;
+                }
+                let mut out = vec![[0; 2]; found];
+                if found > 0 {
+                    unsafe {
+                        self.queue
+                            .enqueue_read_buffer(&hits, CL_BLOCKING, 0, &mut out, &[])?;
+                    }
+                }
+                return Ok(out.into_iter().map(|[start, id]| (start, id)).collect());
+            }
+        }
+    }
+
+    /// Device copy of a pattern table
+    fn read_only<T>(context: &Context, data: &mut [T]) -> Result<Buffer<T>> {
+        unsafe {
+            Buffer::create(
+                context,
+                CL_MEM_READ_ONLY | CL_MEM_COPY_HOST_PTR,
+                data.len(),
+                data.as_mut_ptr().cast(),
+            )
+        }
+    }
+}
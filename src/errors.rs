@@ -0,0 +1,148 @@
+//! Exception classes of the Python module, all deriving from VolutaError; each also derives
+//! from the builtin exception raised before it existed, so existing handlers keep catching it
+
+use crate::matcher::ConfigError;
+use pyo3::exceptions::{PyException, PyOSError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyTuple, PyType};
+use std::io::{self, ErrorKind};
+
+/// Error of a call, for the class raising it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Error {
+    /// Invalid patterns or matcher options, a ValueError
+    Pattern,
+    /// A file or readable that cannot be read, an OSError
+    ScanIo,
+    /// Scanned bytes that cannot be decoded, e.g. invalid UTF-8, a ScanIOError
+    Decode,
+    /// Patterns or options exceeding a limit of the library or of an engine, a ValueError
+    Limit,
+}
+
+struct Classes {
+    base: Py<PyType>,
+    pattern: Py<PyType>,
+    scan_io: Py<PyType>,
+    decode: Py<PyType>,
+    limit: Py<PyType>,
+}
+
+static CLASSES: GILOnceCell<Classes> = GILOnceCell::new();
+
+/// Python class named `name` deriving from `bases`, created like a class statement would
+fn class<'py>(
+    py: Python<'py>,
+    name: &str,
+    bases: &[&Bound<'py, PyType>],
+    doc: &str,
+) -> PyResult<Py<PyType>> {
+    let namespace = PyDict::new(py);
+    namespace.set_item("__module__", "voluta")?;
+    namespace.set_item("__doc__", doc)?;
+    let class = py
+        .get_type::<PyType>()
+        .call1((name, PyTuple::new(py, bases)?, namespace))?;
+    Ok(class.downcast_into::<PyType>()?.unbind())
+}
+
+fn classes(py: Python<'_>) -> PyResult<&Classes> {
+    CLASSES.get_or_try_init(py, || {
+        let base = class(
+            py,
+            "VolutaError",
+            &[&py.get_type::<PyException>()],
+            "Base class of the errors raised by voluta",
+        )?;
+        let scan_io = class(
+            py,
+            "ScanIOError",
+            &[base.bind(py), &py.get_type::<PyOSError>()],
+            "A file or readable cannot be read",
+        )?;
+        Ok(Classes {
+            pattern: class(
+                py,
+                "PatternError",
+                &[base.bind(py), &py.get_type::<PyValueError>()],
+                "Invalid patterns or matcher options",
+            )?,
+            decode: class(
+                py,
+                "DecodeError",
+                &[scan_io.bind(py)],
+                "Scanned bytes cannot be decoded",
+            )?,
+            limit: class(
+                py,
+                "LimitExceeded",
+                &[base.bind(py), &py.get_type::<PyValueError>()],
+                "Patterns or options exceed a limit of voluta or of the engine",
+            )?,
+            base,
+            scan_io,
+        })
+    })
+}
+
+impl Error {
+    /// Python exception of this class with `message`
+    pub(crate) fn new_err(self, message: String) -> PyErr {
+        Python::with_gil(|py| match classes(py) {
+            Ok(classes) => {
+                let class = match self {
+                    Self::Pattern => &classes.pattern,
+                    Self::ScanIo => &classes.scan_io,
+                    Self::Decode => &classes.decode,
+                    Self::Limit => &classes.limit,
+                };
+                PyErr::from_type(class.bind(py).clone(), message)
+            }
+            Err(e) => e,
+        })
+    }
+}
+
+impl From<ConfigError> for PyErr {
+    fn from(e: ConfigError) -> Self {
+        match e {
+            ConfigError::Invalid(message) => Error::Pattern.new_err(message),
+            ConfigError::Limit(message) => Error::Limit.new_err(message),
+            // Builds from Python raise the exception that cancelled them instead
+            ConfigError::Cancelled(message) => PyRuntimeError::new_err(message),
+        }
+    }
+}
+
+/// Convert a scan error to ScanIOError, or DecodeError for undecodable data, unless it is a
+/// Python error raised by a readable
+pub(crate) fn io_error(e: io::Error) -> PyErr {
+    if e.get_ref().is_some_and(|inner| inner.is::<PyErr>()) {
+        if let Some(Ok(e)) = e.into_inner().map(|inner| inner.downcast::<PyErr>()) {
+            return *e;
+        }
+        return Error::ScanIo.new_err("I/O error".to_string());
+    }
+    match e.kind() {
+        ErrorKind::InvalidData => Error::Decode.new_err(e.to_string()),
+        _ => Error::ScanIo.new_err(e.to_string()),
+    }
+}
+
+/// Add the exception classes to the module
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    let classes = classes(py)?;
+    for class in [
+        &classes.base,
+        &classes.pattern,
+        &classes.scan_io,
+        &classes.decode,
+        &classes.limit,
+    ] {
+        let class = class.bind(py);
+        m.add(class.name()?, class)?;
+    }
+    Ok(())
+}
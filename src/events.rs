@@ -0,0 +1,302 @@
+//! Structured events of a matcher: scans started, finished and failed, limits hit and
+//! automaton builds, emitted to a hook configured per matcher, such as Rust `tracing`
+//! subscribers with the `tracing` feature or Python's logging module
+
+use crate::audit::{AuditRun, AuditTotals};
+use crate::telemetry::ScanSpan;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// What happened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    ScanStarted,
+    ScanFinished,
+    ScanFailed,
+    /// A limit failed a build, or skipped a file of a directory scan
+    LimitHit,
+    /// An automaton was built, a matcher's or a layer of an incremental matcher's
+    Built,
+}
+
+/// Severity of an event, as mapped to log levels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ScanStarted => "scan_started",
+            Self::ScanFinished => "scan_finished",
+            Self::ScanFailed => "scan_failed",
+            Self::LimitHit => "limit_hit",
+            Self::Built => "built",
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        match self {
+            Self::ScanStarted => Level::Debug,
+            Self::ScanFinished | Self::Built => Level::Info,
+            Self::LimitHit => Level::Warning,
+            Self::ScanFailed => Level::Error,
+        }
+    }
+}
+
+/// One event, with the fields its kind has
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Method that emitted the event, e.g. "match_file_memmap", "match_dir" or "build"
+    pub operation: &'static str,
+    /// File scanned, None for scans of in-memory data and builds
+    pub path: Option<String>,
+    pub bytes: Option<usize>,
+    pub matches: Option<usize>,
+    pub elapsed: Option<Duration>,
+    /// Patterns of a build
+    pub patterns: Option<usize>,
+    /// Error of a failed scan, or the limit hit
+    pub error: Option<String>,
+}
+
+impl Event {
+    pub(crate) fn new(kind: EventKind, operation: &'static str) -> Self {
+        Self {
+            kind,
+            operation,
+            path: None,
+            bytes: None,
+            matches: None,
+            elapsed: None,
+            patterns: None,
+            error: None,
+        }
+    }
+
+    pub(crate) fn limit_hit(operation: &'static str, path: Option<&str>, error: String) -> Self {
+        Self {
+            path: path.map(str::to_string),
+            error: Some(error),
+            ..Self::new(EventKind::LimitHit, operation)
+        }
+    }
+
+    pub(crate) fn failed(operation: &'static str, path: Option<&str>, error: &io::Error) -> Self {
+        Self {
+            path: path.map(str::to_string),
+            error: Some(error.to_string()),
+            ..Self::new(EventKind::ScanFailed, operation)
+        }
+    }
+
+    pub(crate) fn built(operation: &'static str, patterns: usize, elapsed: Duration) -> Self {
+        Self {
+            patterns: Some(patterns),
+            elapsed: Some(elapsed),
+            ..Self::new(EventKind::Built, operation)
+        }
+    }
+
+    /// Human-readable summary of the event
+    pub fn message(&self) -> String {
+        let on = self
+            .path
+            .as_deref()
+            .map_or_else(String::new, |path| format!(" on {path}"));
+        let elapsed = self.elapsed.unwrap_or_default().as_secs_f64();
+        match self.kind {
+            EventKind::ScanStarted => format!(
+                "{} started{on} ({} bytes)",
+                self.operation,
+                self.bytes.unwrap_or(0)
+            ),
+            EventKind::ScanFinished => format!(
+                "{} finished{on}: {} matches in {elapsed:.3}s",
+                self.operation,
+                self.matches.unwrap_or(0)
+            ),
+            EventKind::ScanFailed | EventKind::LimitHit => format!(
+                "{} {}{on}: {}",
+                self.operation,
+                if self.kind == EventKind::ScanFailed {
+                    "failed"
+                } else {
+                    "hit a limit"
+                },
+                self.error.as_deref().unwrap_or_default()
+            ),
+            EventKind::Built => format!(
+                "{} built {} patterns in {elapsed:.3}s",
+                self.operation,
+                self.patterns.unwrap_or(0)
+            ),
+        }
+    }
+}
+
+/// Receiver of the events of a matcher, called on the thread the event happens on
+#[derive(Clone)]
+pub struct EventHook(Arc<dyn Fn(&Event) + Send + Sync>);
+
+impl EventHook {
+    pub fn new(hook: impl Fn(&Event) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Emit the events as `tracing` events of the "voluta" target, at the level of their kind
+    #[cfg(feature = "tracing")]
+    pub fn tracing() -> Self {
+        Self::new(|event| {
+            let elapsed = event.elapsed.map(|elapsed| elapsed.as_secs_f64());
+            macro_rules! emit {
+                ($level:expr) => {
+                    tracing::event!(
+                        target: "voluta",
+                        $level,
+                        event = event.kind.name(),
+                        operation = event.operation,
+                        path = event.path.as_deref(),
+                        bytes = event.bytes,
+                        matches = event.matches,
+                        elapsed,
+                        patterns = event.patterns,
+                        error = event.error.as_deref(),
+                        "{}",
+                        event.message()
+                    )
+                };
+            }
+            match event.kind.level() {
+                Level::Debug => emit!(tracing::Level::DEBUG),
+                Level::Info => emit!(tracing::Level::INFO),
+                Level::Warning => emit!(tracing::Level::WARN),
+                Level::Error => emit!(tracing::Level::ERROR),
+            }
+        })
+    }
+
+    pub fn emit(&self, event: &Event) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for EventHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventHook")
+    }
+}
+
+/// Telemetry span, events and audit of one scan, ended by `end`, `finish` or `fail`
+pub(crate) struct TracedScan {
+    span: ScanSpan,
+    events: Option<(EventHook, Event, Instant)>,
+    audit: Option<(AuditRun, usize)>,
+}
+
+impl TracedScan {
+    pub(crate) fn start(
+        hook: Option<&EventHook>,
+        operation: &'static str,
+        path: Option<&str>,
+        bytes: usize,
+        audit: Option<AuditRun>,
+    ) -> Self {
+        let span = ScanSpan::start(operation, path, bytes);
+        Self::with_events(span, hook, operation, path, bytes, audit)
+    }
+
+    pub(crate) fn file(
+        hook: Option<&EventHook>,
+        operation: &'static str,
+        path: &str,
+        audit: Option<AuditRun>,
+    ) -> Self {
+        let span = ScanSpan::file(operation, path);
+        let bytes = if hook.is_some() || audit.is_some() {
+            std::fs::metadata(path).map_or(0, |m| m.len() as usize)
+        } else {
+            0
+        };
+        Self::with_events(span, hook, operation, Some(path), bytes, audit)
+    }
+
+    fn with_events(
+        span: ScanSpan,
+        hook: Option<&EventHook>,
+        operation: &'static str,
+        path: Option<&str>,
+        bytes: usize,
+        audit: Option<AuditRun>,
+    ) -> Self {
+        let events = hook.map(|hook| {
+            let event = Event {
+                path: path.map(str::to_string),
+                bytes: Some(bytes),
+                ..Event::new(EventKind::ScanStarted, operation)
+            };
+            hook.emit(&event);
+            (hook.clone(), event, Instant::now())
+        });
+        Self {
+            span,
+            events,
+            audit: audit.map(|run| (run, bytes)),
+        }
+    }
+
+    /// End the scan with its outcome, failing it if its audit record cannot be written
+    pub(crate) fn end<T>(
+        mut self,
+        res: io::Result<T>,
+        matches: impl FnOnce(&T) -> usize,
+    ) -> io::Result<T> {
+        let (run, bytes) = self.audit.take().unzip();
+        let matches = match &res {
+            Ok(value) => matches(value),
+            Err(_) => 0,
+        };
+        match &res {
+            Ok(_) => self.finish(matches),
+            Err(e) => self.fail(e),
+        }
+        AuditRun::end(run, res, |_| AuditTotals {
+            files_scanned: 1,
+            files_failed: 0,
+            bytes_scanned: bytes.unwrap_or(0),
+            matches,
+        })
+    }
+
+    pub(crate) fn finish(self, matches: usize) {
+        self.span.finish(matches);
+        if let Some((hook, started, at)) = self.events {
+            hook.emit(&Event {
+                kind: EventKind::ScanFinished,
+                matches: Some(matches),
+                elapsed: Some(at.elapsed()),
+                ..started
+            });
+        }
+    }
+
+    pub(crate) fn fail(self, error: &io::Error) {
+        self.span.fail(error);
+        if let Some((hook, started, at)) = self.events {
+            hook.emit(&Event {
+                kind: EventKind::ScanFailed,
+                elapsed: Some(at.elapsed()),
+                error: Some(error.to_string()),
+                ..started
+            });
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! EnCase E01 images: the media stored in the chunks of one or more segment files, read in
+//! order and decompressed as they are read
+
+use crate::disk::EWF_SIGNATURE;
+use flate2::read::ZlibDecoder;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Result, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Bytes of a section descriptor
+const DESCRIPTOR_SIZE: usize = 76;
+
+/// Largest chunk, larger ones meaning a corrupt volume section
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Location of a chunk in the segment files
+#[derive(Clone, Copy)]
+struct Chunk {
+    segment: usize,
+    offset: u64,
+    compressed: bool,
+}
+
+/// Reader of the media of an E01 image
+pub(crate) struct EwfReader {
+    segments: Vec<PathBuf>,
+    chunks: Vec<Chunk>,
+    chunk_size: usize,
+    sector_size: usize,
+    media_size: u64,
+    /// Segment file being read and its index
+    file: Option<(usize, File)>,
+    /// Index of the next chunk to load
+    next: usize,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl EwfReader {
+    /// Open the image whose first segment file is at `path`, reading the chunk tables of every
+    /// segment, named like the first with the extensions following .E01: .E02 to .E99, then
+    /// .EAA to .ZZZ
+    pub(crate) fn open(path: &str) -> Result<Self> {
+        let first = PathBuf::from(path);
+        let mut segments = Vec::new();
+        let mut chunks = Vec::new();
+        let mut geometry = None;
+
+        loop {
+            let path = match segments.len() {
+                0 => first.clone(),
+                n => segment_path(&first, n + 1).ok_or_else(|| corrupt("too many segments"))?,
+            };
+            let mut file = File::open(&path).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Cannot open E01 segment {}: {e}", path.display()),
+                )
+            })?;
+            let mut header = [0; 13];
+            file.read_exact(&mut header)
+                .map_err(|_| corrupt("truncated segment header"))?;
+            if !header.starts_with(EWF_SIGNATURE) {
+                return Err(corrupt("segment without the EVF signature"));
+            }
+            let segment = segments.len();
+            segments.push(path);
+
+            let mut offset = header.len() as u64;
+            let done = loop {
+                file.seek(SeekFrom::Start(offset))?;
+                let mut descriptor = [0; DESCRIPTOR_SIZE];
+                file.read_exact(&mut descriptor)
+                    .map_err(|_| corrupt("truncated section"))?;
+                let kind = descriptor[..16]
+                    .split(|&b| b == 0)
+                    .next()
+                    .unwrap_or_default();
+                let next = le64(&descriptor, 16);
+                match kind {
+                    b"volume" | b"disk" if geometry.is_none() => {
+                        let mut volume = [0; 24];
+                        file.read_exact(&mut volume)
+                            .map_err(|_| corrupt("truncated volume section"))?;
+                        geometry = Some((le32(&volume, 8), le32(&volume, 12), le64(&volume, 16)));
+                    }
+                    b"table" => read_table(&mut file, segment, &mut chunks)?,
+                    b"next" => break false,
+                    b"done" => break true,
+                    _ => {}
+                }
+                if next <= offset {
+                    return Err(corrupt("section loop"));
+                }
+                offset = next;
+            };
+            if done {
+                break;
+            }
+        }
+
+        let (sectors_per_chunk, sector_size, sectors) =
+            geometry.ok_or_else(|| corrupt("no volume section"))?;
+        let (sector_size, chunk_size) = (
+            sector_size as usize,
+            sectors_per_chunk as usize * sector_size as usize,
+        );
+        if sector_size == 0 || chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(corrupt("invalid chunk size"));
+        }
+        Ok(Self {
+            segments,
+            chunks,
+            chunk_size,
+            sector_size,
+            media_size: sectors.saturating_mul(sector_size as u64),
+            file: None,
+            next: 0,
+            buf: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    pub(crate) fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    /// Load the next chunk, false at the end of the media
+    fn load(&mut self) -> Result<bool> {
+        let start = self.next as u64 * self.chunk_size as u64;
+        let Some(&chunk) = self.chunks.get(self.next) else {
+            return Ok(false);
+        };
+        if start >= self.media_size {
+            return Ok(false);
+        }
+        let len = (self.media_size - start).min(self.chunk_size as u64);
+
+        if self
+            .file
+            .as_ref()
+            .is_none_or(|(segment, _)| *segment != chunk.segment)
+        {
+            self.file = Some((chunk.segment, File::open(&self.segments[chunk.segment])?));
+        }
+        let (_, file) = self.file.as_mut().expect("segment file opened above");
+        file.seek(SeekFrom::Start(chunk.offset))?;
+        self.buf.clear();
+        // Uncompressed chunks are followed by a checksum, compressed ones are zlib streams
+        if chunk.compressed {
+            ZlibDecoder::new(&mut *file)
+                .take(len)
+                .read_to_end(&mut self.buf)
+                .map_err(|_| corrupt("invalid compressed chunk"))?;
+        } else {
+            file.take(len).read_to_end(&mut self.buf)?;
+        }
+        if (self.buf.len() as u64) < len {
+            return Err(corrupt("truncated chunk"));
+        }
+        self.next += 1;
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+impl Read for EwfReader {
+    fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        if self.pos == self.buf.len() && !self.load()? {
+            return Ok(0);
+        }
+        let len = out.len().min(self.buf.len() - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Add the chunks of the table section whose descriptor was just read
+fn read_table(file: &mut File, segment: usize, chunks: &mut Vec<Chunk>) -> Result<()> {
+    let mut header = [0; 24];
+    file.read_exact(&mut header)
+        .map_err(|_| corrupt("truncated table section"))?;
+    let entries = le32(&header, 0) as usize;
+    let base = le64(&header, 8);
+    // Entries are read up to their count rather than allocated from it, so a corrupt count
+    // fails on the truncated section instead of allocating
+    let mut table = Vec::new();
+    file.take(entries as u64 * 4).read_to_end(&mut table)?;
+    if table.len() < entries * 4 {
+        return Err(corrupt("truncated table section"));
+    }
+    chunks.extend(table.chunks_exact(4).map(|entry| {
+        let entry = le32(entry, 0);
+        Chunk {
+            segment,
+            offset: base + u64::from(entry & 0x7FFF_FFFF),
+            compressed: entry & 0x8000_0000 != 0,
+        }
+    }));
+    Ok(())
+}
+
+/// Path of segment `number` of the image whose first segment is `first`
+fn segment_path(first: &Path, number: usize) -> Option<PathBuf> {
+    let extension = first.extension()?.to_str()?;
+    let letter = *extension.as_bytes().first()?;
+    let extension = if number < 100 {
+        format!("{}{number:02}", letter as char)
+    } else {
+        let n = number - 100;
+        let first = letter.to_ascii_uppercase() + u8::try_from(n / (26 * 26)).ok()?;
+        if first > b'Z' {
+            return None;
+        }
+        let name = [first, b'A' + (n / 26 % 26) as u8, b'A' + (n % 26) as u8];
+        let name = String::from_utf8_lossy(&name).into_owned();
+        if letter.is_ascii_lowercase() {
+            name.to_ascii_lowercase()
+        } else {
+            name
+        }
+    };
+    Some(first.with_extension(extension))
+}
+
+fn corrupt(reason: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("Corrupt E01 image, {reason}"),
+    )
+}
+
+fn le32(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+}
+
+fn le64(data: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+}
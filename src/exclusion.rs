@@ -0,0 +1,104 @@
+//! Exclusion zones: byte ranges, or occurrences of exclusion patterns such as known test
+//! fixtures, inside which matches are suppressed during the scan
+
+use crate::engine::{Backend, Engine};
+use crate::matcher::ConfigError;
+use aho_corasick::PatternID;
+
+/// Byte ranges of a scanned input where matches are suppressed, (start, end) with end
+/// exclusive; a match overlapping any of them by at least one byte is dropped
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IgnoreSpans(Vec<(usize, usize)>);
+
+impl IgnoreSpans {
+    /// Ranges in any order, overlapping ranges are merged and empty ones ignore nothing
+    pub fn new(mut spans: Vec<(usize, usize)>) -> Result<Self, ConfigError> {
+        if let Some(&(start, end)) = spans.iter().find(|(start, end)| start > end) {
+            return Err(ConfigError::Invalid(format!(
+                "Invalid ignore span ({}, {}), start must not exceed end",
+                start, end
+            )));
+        }
+        spans.retain(|(start, end)| start < end);
+        spans.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+        for (start, end) in spans {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Ok(Self(merged))
+    }
+
+    /// Merged ranges, sorted by offset
+    pub fn spans(&self) -> &[(usize, usize)] {
+        &self.0
+    }
+
+    /// Check if `start..end` overlaps one of the ranges
+    pub fn overlaps(&self, start: usize, end: usize) -> bool {
+        // First range ending after `start`, the only one that can overlap
+        let idx = self.0.partition_point(|&(_, span_end)| span_end <= start);
+        self.0
+            .get(idx)
+            .is_some_and(|&(span_start, _)| span_start < end.max(start + 1))
+    }
+
+    /// Drop the matches overlapping one of the ranges
+    pub fn retain(&self, matches: &mut Vec<(usize, usize, PatternID)>) {
+        if !self.0.is_empty() {
+            matches.retain(|&(start, end, _)| !self.overlaps(start, end));
+        }
+    }
+}
+
+/// Exclusion patterns of a matcher: matches overlapping an occurrence of any of them are
+/// suppressed, e.g. the example keys of documentation or the fixtures of test suites
+pub(crate) struct Exclusions {
+    patterns: Vec<String>,
+    engine: Backend,
+    max_len: usize,
+}
+
+impl Exclusions {
+    /// Exclusions on the non-empty `patterns`, None when there are none
+    pub(crate) fn new(
+        patterns: Vec<String>,
+        case_insensitive: bool,
+    ) -> Result<Option<Self>, ConfigError> {
+        let patterns: Vec<String> = patterns.into_iter().filter(|p| !p.is_empty()).collect();
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            engine: Backend::build(&patterns, case_insensitive, Engine::Auto)?,
+            max_len: patterns.iter().map(|p| p.len()).max().unwrap_or(0),
+            patterns,
+        }))
+    }
+
+    pub(crate) fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Longest exclusion pattern, the context a scan needs around a match to find the
+    /// occurrences overlapping it
+    pub(crate) fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.engine.memory_usage() + self.patterns.iter().map(|p| p.capacity()).sum::<usize>()
+    }
+
+    /// Check if the match `data[start..end]` overlaps an occurrence of an exclusion pattern,
+    /// searching only the bytes it could share with one
+    pub(crate) fn overlaps(&self, data: &[u8], start: usize, end: usize) -> bool {
+        let from = start.saturating_sub(self.max_len - 1);
+        let to = (end + self.max_len - 1).min(data.len());
+        self.engine
+            .find_overlapping_iter(&data[from..to])
+            .any(|m| from + m.start() < end.max(start + 1) && from + m.end() > start)
+    }
+}
@@ -0,0 +1,91 @@
+//! Approximate matching: patterns expanded at build time into their variants within a bounded
+//! Levenshtein distance, searched by the same engine as the patterns themselves
+
+use crate::matcher::ConfigError;
+use std::collections::HashSet;
+
+/// Largest edit distance a pattern can be matched at
+pub const MAX_FUZZY_DISTANCE: usize = 2;
+/// Most variants built for a pattern set, every one of them is a pattern of the automaton
+pub const MAX_FUZZY_VARIANTS: usize = 1_000_000;
+
+/// Variants of `pattern` within `distance` substitutions, insertions and deletions of
+/// characters, the pattern itself excluded
+/// Edits leave the first and last characters in place, so a variant is never just a part of an
+/// exact match; substituted and inserted characters are ASCII letters and digits or characters
+/// of the pattern. Case-insensitive variants are lowercased, the engines fold the text likewise
+pub(crate) fn variants(
+    pattern: &str,
+    distance: usize,
+    case_insensitive: bool,
+) -> Result<Vec<String>, ConfigError> {
+    if distance > MAX_FUZZY_DISTANCE {
+        return Err(ConfigError::Limit(format!(
+            "Fuzzy distances must be at most {}, got {} for pattern '{}'",
+            MAX_FUZZY_DISTANCE, distance, pattern
+        )));
+    }
+    if distance == 0 {
+        return Ok(Vec::new());
+    }
+
+    let chars: Vec<char> = if case_insensitive {
+        pattern.to_ascii_lowercase().chars().collect()
+    } else {
+        pattern.chars().collect()
+    };
+    if chars.len() <= 2 * distance {
+        return Err(ConfigError::Invalid(format!(
+            "Pattern '{}' is too short for fuzzy distance {}, it needs at least {} characters",
+            pattern,
+            distance,
+            2 * distance + 1
+        )));
+    }
+
+    let mut alphabet: Vec<char> = ('a'..='z').chain('0'..='9').collect();
+    if !case_insensitive {
+        alphabet.extend('A'..='Z');
+    }
+    alphabet.extend(chars.iter().copied());
+    alphabet.sort_unstable();
+    alphabet.dedup();
+
+    let mut seen: HashSet<Vec<char>> = HashSet::new();
+    seen.insert(chars.clone());
+    let mut found = Vec::new();
+    let mut frontier = vec![chars];
+    for _ in 0..distance {
+        let mut next = Vec::new();
+        for variant in &frontier {
+            let len = variant.len();
+            let mut push = |edited: Vec<char>| {
+                if seen.insert(edited.clone()) {
+                    next.push(edited);
+                }
+            };
+            for i in 1..len - 1 {
+                let mut deleted = variant.clone();
+                deleted.remove(i);
+                push(deleted);
+                for &c in &alphabet {
+                    if c != variant[i] {
+                        let mut substituted = variant.clone();
+                        substituted[i] = c;
+                        push(substituted);
+                    }
+                }
+            }
+            for i in 1..len {
+                for &c in &alphabet {
+                    let mut inserted = variant.clone();
+                    inserted.insert(i, c);
+                    push(inserted);
+                }
+            }
+        }
+        found.extend(next.iter().map(|v| v.iter().collect::<String>()));
+        frontier = next;
+    }
+    Ok(found)
+}
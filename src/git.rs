@@ -0,0 +1,278 @@
+//! Git repository scans: the blobs reachable from revisions, read through a single
+//! `git cat-file --batch` process rather than one process per blob, reported with the commit
+//! and path each blob was found at
+
+use crate::matcher::Matcher;
+use crate::report::SPLIT_FILE_SIZE;
+use aho_corasick::PatternID;
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Result, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+/// Options of a repository scan
+#[derive(Clone, Debug)]
+pub struct GitOptions {
+    /// Revisions whose trees are scanned, or whose history with `history`
+    pub revisions: Vec<String>,
+    /// Scan every blob added or modified by the commits reachable from the revisions, reported
+    /// at the oldest commit introducing it, instead of the trees of the revisions only
+    pub history: bool,
+}
+
+impl Default for GitOptions {
+    fn default() -> Self {
+        Self {
+            revisions: vec!["HEAD".to_string()],
+            history: false,
+        }
+    }
+}
+
+/// Matches of a repository scan
+pub struct GitScan {
+    /// (commit, path) of every blob scanned, each distinct blob being scanned once
+    pub blobs: Vec<(String, String)>,
+    /// (blob, start, end, pattern) matches, the blob indexing `blobs`, sorted by blob then
+    /// offset
+    pub matches: Vec<(usize, usize, usize, PatternID)>,
+}
+
+impl GitScan {
+    /// Scan the blobs of the repository at `repo`, with the `git` executable of the PATH
+    /// Symbolic links and submodules are not scanned
+    pub fn scan(matcher: &Matcher, repo: &str, options: &GitOptions) -> Result<Self> {
+        if let Some(revision) = options
+            .revisions
+            .iter()
+            .find(|rev| rev.is_empty() || rev.starts_with('-'))
+        {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid revision '{}'", revision),
+            ));
+        }
+        let blobs = if options.history {
+            history_blobs(repo, &options.revisions)?
+        } else {
+            tree_blobs(repo, &options.revisions)?
+        };
+        let mut matches = scan_blobs(matcher, repo, &blobs)?;
+        matches.sort_unstable();
+        Ok(Self {
+            blobs: blobs
+                .into_iter()
+                .map(|blob| (blob.commit, blob.path))
+                .collect(),
+            matches,
+        })
+    }
+}
+
+/// A blob to scan and where it was found
+struct Blob {
+    oid: String,
+    commit: String,
+    path: String,
+}
+
+/// Output of a git command run in `repo`
+pub(crate) fn run(repo: &str, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(spawn_error)?;
+    if !output.status.success() {
+        // The command is named by its first argument that is not an option
+        let command = args
+            .iter()
+            .find(|arg| !arg.starts_with('-') && !arg.contains('='))
+            .unwrap_or(&"");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            command,
+            stderr.lines().next().unwrap_or_default().trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+fn spawn_error(e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("Could not run git: {}", e))
+}
+
+/// Regular file blobs, git modes of symbolic links and submodules being 120000 and 160000
+fn is_file_mode(mode: &str) -> bool {
+    mode.starts_with("100")
+}
+
+/// Blobs of the trees of `revisions`, at the commit of the first revision containing them
+fn tree_blobs(repo: &str, revisions: &[String]) -> Result<Vec<Blob>> {
+    let mut seen = HashSet::new();
+    let mut blobs = Vec::new();
+    for revision in revisions {
+        let commit = run(
+            repo,
+            &["rev-parse", "--verify", &format!("{}^{{commit}}", revision)],
+        )?;
+        let commit = String::from_utf8_lossy(&commit).trim().to_string();
+        let listing = run(repo, &["ls-tree", "-r", "-z", "--full-tree", &commit])?;
+        // <mode> <type> <oid>\t<path>, NUL terminated
+        for entry in listing.split(|&b| b == 0).filter(|e| !e.is_empty()) {
+            let entry = String::from_utf8_lossy(entry);
+            let Some((header, path)) = entry.split_once('\t') else {
+                continue;
+            };
+            let mut fields = header.split(' ');
+            let (Some(mode), Some("blob"), Some(oid)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if is_file_mode(mode) && seen.insert(oid.to_string()) {
+                blobs.push(Blob {
+                    oid: oid.to_string(),
+                    commit: commit.clone(),
+                    path: path.to_string(),
+                });
+            }
+        }
+    }
+    Ok(blobs)
+}
+
+/// Blobs added or modified by the commits reachable from `revisions`, oldest commit first
+fn history_blobs(repo: &str, revisions: &[String]) -> Result<Vec<Blob>> {
+    let mut args = vec![
+        "log",
+        "--reverse",
+        "--format=%x01%H",
+        "--raw",
+        "--no-abbrev",
+        "--no-renames",
+        "--root",
+        "--diff-filter=AMT",
+        "-z",
+    ];
+    args.extend(revisions.iter().map(String::as_str));
+    args.push("--");
+    let log = run(repo, &args)?;
+
+    // \x01<commit>, then for each change :<old mode> <mode> <old oid> <oid> <status> and the
+    // path, all NUL terminated
+    let mut seen = HashSet::new();
+    let mut blobs = Vec::new();
+    let mut commit = String::new();
+    let mut tokens = log.split(|&b| b == 0);
+    while let Some(token) = tokens.next() {
+        let token = token.strip_prefix(b"\n").unwrap_or(token);
+        if let Some(hash) = token.strip_prefix(b"\x01") {
+            commit = String::from_utf8_lossy(hash).into_owned();
+            continue;
+        }
+        let Some(change) = token.strip_prefix(b":") else {
+            continue;
+        };
+        let Some(path) = tokens.next() else {
+            break;
+        };
+        let change = String::from_utf8_lossy(change);
+        let fields: Vec<&str> = change.split(' ').collect();
+        let [_, mode, _, oid, _] = fields[..] else {
+            continue;
+        };
+        if is_file_mode(mode) && seen.insert(oid.to_string()) {
+            blobs.push(Blob {
+                oid: oid.to_string(),
+                commit: commit.clone(),
+                path: String::from_utf8_lossy(path).into_owned(),
+            });
+        }
+    }
+    Ok(blobs)
+}
+
+/// Read the blobs through `git cat-file --batch` and scan them in parallel as they arrive
+fn scan_blobs(
+    matcher: &Matcher,
+    repo: &str,
+    blobs: &[Blob],
+) -> Result<Vec<(usize, usize, usize, PatternID)>> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(spawn_error)?;
+    let mut stdin = BufWriter::new(child.stdin.take().expect("piped stdin"));
+    let mut stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+
+    // Blobs read ahead of the scan are bounded, so a huge history is not held in memory
+    let (sender, receiver) = mpsc::sync_channel(2 * rayon::current_num_threads());
+    let matches = std::thread::scope(|scope| {
+        scope.spawn(move || -> Result<()> {
+            for blob in blobs {
+                writeln!(stdin, "{}", blob.oid)?;
+            }
+            stdin.flush()
+        });
+        scope.spawn(move || {
+            for idx in 0..blobs.len() {
+                let blob = read_blob(&mut stdout);
+                let failed = blob.is_err();
+                if sender.send(blob.map(|data| (idx, data))).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        receiver
+            .into_iter()
+            .par_bridge()
+            .map(|blob| {
+                let (idx, data) = blob?;
+                let matches = if data.len() >= SPLIT_FILE_SIZE {
+                    matcher.match_bytes_parallel(&data)
+                } else {
+                    matcher.match_bytes(&data)
+                };
+                Ok(matches
+                    .into_iter()
+                    .map(|(start, end, pattern)| (idx, start, end, pattern))
+                    .collect::<Vec<_>>())
+            })
+            .collect::<Result<Vec<_>>>()
+    });
+    child.kill().ok();
+    child.wait()?;
+    Ok(matches?.into_iter().flatten().collect())
+}
+
+/// Content of the next blob of a `git cat-file --batch` output:
+/// <oid> blob <size>\n<content>\n
+fn read_blob(stdout: &mut impl BufRead) -> Result<Vec<u8>> {
+    let mut header = String::new();
+    if stdout.read_line(&mut header)? == 0 {
+        return Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "git cat-file exited early",
+        ));
+    }
+    let fields: Vec<&str> = header.trim_end().split(' ').collect();
+    let size = match fields[..] {
+        [_, "blob", size] => size.parse::<usize>().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| io::Error::other(format!("Unexpected git object: {}", header.trim_end())))?;
+    let mut data = vec![0; size + 1];
+    stdout.read_exact(&mut data)?;
+    data.pop();
+    Ok(data)
+}
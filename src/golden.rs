@@ -0,0 +1,260 @@
+//! Golden tests of a rule set: sample documents declared with the matches expected in them and
+//! the patterns expected not to match, checked against the current matcher so rule regressions
+//! show up before production
+
+use crate::matcher::Matcher;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// One sample document and what the matcher should find in it
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoldenCase {
+    /// Name of the case in failures, its position otherwise
+    pub name: Option<String>,
+    /// Sample text
+    pub text: Option<String>,
+    /// Sample file, relative to the spec file's directory
+    pub file: Option<String>,
+    #[serde(default)]
+    pub matches: Vec<Expected>,
+    /// Patterns that must not match
+    #[serde(default)]
+    pub no_matches: Vec<String>,
+    /// Fail on findings not listed in `matches`
+    #[serde(default)]
+    pub exact: bool,
+}
+
+/// Match expected in a sample, a pattern alone meaning at least one match of it
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Expected {
+    Pattern(String),
+    Match {
+        pattern: String,
+        /// Matched text, as it appears in the sample
+        text: Option<String>,
+        /// Byte offset of the match
+        start: Option<usize>,
+        /// Exact number of such matches
+        count: Option<usize>,
+    },
+}
+
+/// Expectation of a sample that the matcher does not meet
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenFailure {
+    pub case: String,
+    /// "missing" for expected matches not found or found a different number of times,
+    /// "unexpected" for matches of no_matches patterns or outside an exact case's matches,
+    /// "error" for samples that cannot be read
+    pub kind: &'static str,
+    pub pattern: Option<String>,
+    /// Matches expected, None for "at least one"
+    pub expected: Option<usize>,
+    pub found: usize,
+    pub message: String,
+}
+
+/// Outcome of the golden tests of a matcher
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoldenReport {
+    /// Number of cases run
+    pub cases: usize,
+    /// Failures in case order
+    pub failures: Vec<GoldenFailure>,
+}
+
+impl GoldenReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Parse a JSON list of cases
+pub fn parse_cases(json: &str) -> serde_json::Result<Vec<GoldenCase>> {
+    serde_json::from_str(json)
+}
+
+/// Check `matcher` against every case, sample files being resolved against `base`
+/// Patterns are named as in results, by their digests for matchers built with a hash_key
+pub fn run_pattern_tests(matcher: &Matcher, cases: &[GoldenCase], base: &Path) -> GoldenReport {
+    let mut failures = Vec::new();
+    for (idx, case) in cases.iter().enumerate() {
+        let name = case.name.clone().unwrap_or_else(|| format!("#{}", idx + 1));
+        let failure =
+            |kind, pattern: Option<&str>, expected, found, message: String| GoldenFailure {
+                case: name.clone(),
+                kind,
+                pattern: pattern.map(str::to_string),
+                expected,
+                found,
+                message,
+            };
+
+        let data = match (&case.text, &case.file) {
+            (Some(text), None) => text.clone().into_bytes(),
+            (None, Some(file)) => match fs::read(base.join(file)) {
+                Ok(data) => data,
+                Err(e) => {
+                    failures.push(failure("error", None, None, 0, format!("{file}: {e}")));
+                    continue;
+                }
+            },
+            _ => {
+                let message = "a case needs one of text and file".to_string();
+                failures.push(failure("error", None, None, 0, message));
+                continue;
+            }
+        };
+
+        let matches = matcher.match_bytes(&data);
+        let mut expected_spans = vec![false; matches.len()];
+        for expected in &case.matches {
+            let (pattern, text, start, count) = match expected {
+                Expected::Pattern(pattern) => (pattern, None, None, None),
+                Expected::Match {
+                    pattern,
+                    text,
+                    start,
+                    count,
+                } => (pattern, text.as_deref(), *start, *count),
+            };
+            let mut found = 0;
+            for (idx, &(s, e, pattern_idx)) in matches.iter().enumerate() {
+                if matcher.pattern(pattern_idx) == pattern
+                    && text.is_none_or(|text| &data[s..e] == text.as_bytes())
+                    && start.is_none_or(|start| start == s)
+                {
+                    found += 1;
+                    expected_spans[idx] = true;
+                }
+            }
+            if count.map_or(found == 0, |count| count != found) {
+                let mut what = format!("'{pattern}'");
+                if let Some(text) = text {
+                    what.push_str(&format!(" matching '{text}'"));
+                }
+                if let Some(start) = start {
+                    what.push_str(&format!(" at {start}"));
+                }
+                let message = match count {
+                    Some(count) => format!("expected {count} matches of {what}, found {found}"),
+                    None => format!("expected a match of {what}, found none"),
+                };
+                failures.push(failure("missing", Some(pattern), count, found, message));
+            }
+        }
+
+        for pattern in &case.no_matches {
+            let found = matches
+                .iter()
+                .filter(|m| matcher.pattern(m.2) == pattern)
+                .count();
+            if found > 0 {
+                let message = format!("expected no match of '{pattern}', found {found}");
+                failures.push(failure(
+                    "unexpected",
+                    Some(pattern),
+                    Some(0),
+                    found,
+                    message,
+                ));
+            }
+        }
+
+        if case.exact {
+            for (&(start, end, pattern_idx), _) in matches
+                .iter()
+                .zip(&expected_spans)
+                .filter(|(_, expected)| !**expected)
+            {
+                let pattern = matcher.pattern(pattern_idx);
+                let message = format!(
+                    "unexpected match of '{pattern}' at {start}..{end}: '{}'",
+                    String::from_utf8_lossy(&data[start..end])
+                );
+                failures.push(failure("unexpected", Some(pattern), None, 1, message));
+            }
+        }
+    }
+    GoldenReport {
+        cases: cases.len(),
+        failures,
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl GoldenFailure {
+    fn __repr__(&self) -> String {
+        format!("GoldenFailure(case={:?}, {})", self.case, self.message)
+    }
+
+    #[getter]
+    fn case(&self) -> String {
+        self.case.clone()
+    }
+
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    #[getter]
+    fn pattern(&self) -> Option<String> {
+        self.pattern.clone()
+    }
+
+    #[getter]
+    fn expected(&self) -> Option<usize> {
+        self.expected
+    }
+
+    #[getter]
+    fn found(&self) -> usize {
+        self.found
+    }
+
+    #[getter]
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl GoldenReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "GoldenReport(cases={}, failures={})",
+            self.cases,
+            self.failures.len()
+        )
+    }
+
+    fn __bool__(&self) -> bool {
+        self.passed()
+    }
+
+    #[getter]
+    fn cases(&self) -> usize {
+        self.cases
+    }
+
+    #[getter(passed)]
+    fn py_passed(&self) -> bool {
+        self.passed()
+    }
+
+    #[getter]
+    fn failures(&self) -> Vec<GoldenFailure> {
+        self.failures.clone()
+    }
+}
@@ -0,0 +1,230 @@
+//! Rendering of matches highlighted in the text they were found in
+
+use aho_corasick::PatternID;
+use std::fmt::Write;
+
+/// A highlighted region, the union of overlapping matches and the patterns they matched
+pub struct Region<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub patterns: Vec<&'a str>,
+}
+
+/// Merge overlapping matches into disjoint regions sorted by offset
+/// Matches past the end of `data_len` are clipped or dropped
+pub fn regions<'a>(
+    matches: &[(usize, usize, PatternID)],
+    patterns: &'a [String],
+    data_len: usize,
+) -> Vec<Region<'a>> {
+    let mut sorted: Vec<_> = matches
+        .iter()
+        .filter(|(start, end, _)| start < end && *start < data_len)
+        .map(|&(start, end, pattern_idx)| (start, end.min(data_len), pattern_idx))
+        .collect();
+    sorted.sort_unstable();
+
+    let mut regions: Vec<Region> = Vec::new();
+    for (start, end, pattern_idx) in sorted {
+        let pattern = patterns[pattern_idx.as_usize()].as_str();
+        match regions.last_mut() {
+            Some(region) if start < region.end => {
+                region.end = region.end.max(end);
+                if !region.patterns.contains(&pattern) {
+                    region.patterns.push(pattern);
+                }
+            }
+            _ => regions.push(Region {
+                start,
+                end,
+                patterns: vec![pattern],
+            }),
+        }
+    }
+    regions
+}
+
+/// Escape text for use in HTML content and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Move `pos` back to the start of the UTF-8 character it falls in
+pub(crate) fn char_start(data: &[u8], mut pos: usize) -> usize {
+    while pos > 0 && pos < data.len() && data[pos] & 0xC0 == 0x80 {
+        pos -= 1;
+    }
+    pos
+}
+
+/// Move `pos` forward to the end of the UTF-8 character it falls in
+pub(crate) fn char_end(data: &[u8], mut pos: usize) -> usize {
+    while pos < data.len() && data[pos] & 0xC0 == 0x80 {
+        pos += 1;
+    }
+    pos
+}
+
+/// Text before a match, of the match and after it, the context taking up to `context` bytes
+/// on each side within the line of the match and cut at character boundaries
+pub(crate) fn line_context(
+    data: &[u8],
+    start: usize,
+    end: usize,
+    context: usize,
+) -> (String, String, String) {
+    let line_start = memchr::memrchr(b'\n', &data[..start]).map_or(0, |pos| pos + 1);
+    let line_end = memchr::memchr(b'\n', &data[end..]).map_or(data.len(), |pos| end + pos);
+    let before = char_start(data, start.saturating_sub(context).max(line_start));
+    let after = char_end(data, (end + context).min(line_end));
+    (
+        String::from_utf8_lossy(&data[before..start]).into_owned(),
+        String::from_utf8_lossy(&data[start..end]).into_owned(),
+        String::from_utf8_lossy(&data[end..after])
+            .trim_end_matches('\r')
+            .to_string(),
+    )
+}
+
+/// Byte ranges of `data` to render: everything, or `context` bytes around each region
+/// with nearby snippets merged
+pub fn snippets(data: &[u8], regions: &[Region], context: Option<usize>) -> Vec<(usize, usize)> {
+    let Some(context) = context else {
+        return vec![(0, data.len())];
+    };
+
+    let mut snippets: Vec<(usize, usize)> = Vec::new();
+    for region in regions {
+        let start = char_start(data, region.start.saturating_sub(context));
+        let end = char_end(data, (region.end + context).min(data.len()));
+        match snippets.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => snippets.push((start, end)),
+        }
+    }
+    snippets
+}
+
+/// Render `data` as HTML with the matched regions wrapped in `<mark>` elements titled with
+/// their patterns, keeping only `context` bytes around matches when given
+pub fn to_html(
+    data: &[u8],
+    matches: &[(usize, usize, PatternID)],
+    patterns: &[String],
+    context: Option<usize>,
+) -> String {
+    let regions = regions(matches, patterns, data.len());
+    let mut html = String::from("<pre class=\"voluta-highlight\">");
+
+    let mut regions_iter = regions.iter().peekable();
+    for (i, (start, end)) in snippets(data, &regions, context).into_iter().enumerate() {
+        if i > 0 {
+            html.push_str("<span class=\"voluta-gap\">\n…\n</span>");
+        }
+
+        let mut pos = start;
+        while let Some(region) = regions_iter.next_if(|region| region.start < end) {
+            html.push_str(&escape_html(&String::from_utf8_lossy(
+                &data[pos..region.start],
+            )));
+            let _ = write!(
+                html,
+                "<mark title=\"{}\">{}</mark>",
+                escape_html(&region.patterns.join(", ")),
+                escape_html(&String::from_utf8_lossy(&data[region.start..region.end]))
+            );
+            pos = region.end;
+        }
+        html.push_str(&escape_html(&String::from_utf8_lossy(&data[pos..end])));
+    }
+
+    html.push_str("</pre>");
+    html
+}
+
+/// ANSI colors of grep-style output, GNU grep's defaults
+const COLOR_MATCH: &str = "\x1b[01;31m";
+const COLOR_PATH: &str = "\x1b[35m";
+const COLOR_NUMBER: &str = "\x1b[32m";
+const COLOR_SEPARATOR: &str = "\x1b[36m";
+const COLOR_RESET: &str = "\x1b[0m";
+
+/// Render matches grep-style, one `path:line:col: text` line per line of `data` holding a
+/// match, with 1-based line and byte column numbers of its first match
+/// `color` highlights the matches and decorates the prefix with ANSI escape codes
+pub fn to_grep(path: &str, data: &[u8], spans: &[(usize, usize)], color: bool) -> String {
+    let mut spans: Vec<(usize, usize)> = spans
+        .iter()
+        .filter(|(start, end)| start < end && *start < data.len())
+        .map(|&(start, end)| (start, end.min(data.len())))
+        .collect();
+    spans.sort_unstable();
+
+    let mut out = String::new();
+    let mut line_number = 1;
+    let mut counted = 0;
+    let mut i = 0;
+    while i < spans.len() {
+        let first = spans[i].0;
+        line_number += memchr::memchr_iter(b'\n', &data[counted..first]).count();
+        counted = first;
+
+        let line_start = memchr::memrchr(b'\n', &data[..first]).map_or(0, |pos| pos + 1);
+        let line_end = memchr::memchr(b'\n', &data[first..]).map_or(data.len(), |pos| first + pos);
+        let mut text_end = line_end;
+        if text_end > line_start && data[text_end - 1] == b'\r' {
+            text_end -= 1;
+        }
+
+        let column = first - line_start + 1;
+        if color {
+            let _ = write!(
+                out,
+                "{COLOR_PATH}{path}{COLOR_RESET}{COLOR_SEPARATOR}:{COLOR_RESET}\
+                 {COLOR_NUMBER}{line_number}{COLOR_RESET}{COLOR_SEPARATOR}:{COLOR_RESET}\
+                 {COLOR_NUMBER}{column}{COLOR_RESET}{COLOR_SEPARATOR}:{COLOR_RESET} "
+            );
+        } else {
+            let _ = write!(out, "{path}:{line_number}:{column}: ");
+        }
+
+        // Highlight the union of the matches on this line, clipped to the line
+        let mut pos = line_start;
+        while i < spans.len() && spans[i].0 < line_end {
+            let start = spans[i].0.max(pos);
+            let mut end = spans[i].1;
+            i += 1;
+            while i < spans.len() && spans[i].0 < end.min(line_end) {
+                end = end.max(spans[i].1);
+                i += 1;
+            }
+            let end = end.min(text_end);
+            if start >= end {
+                continue;
+            }
+
+            out.push_str(&String::from_utf8_lossy(&data[pos..start]));
+            let text = String::from_utf8_lossy(&data[start..end]);
+            if color {
+                let _ = write!(out, "{COLOR_MATCH}{text}{COLOR_RESET}");
+            } else {
+                out.push_str(&text);
+            }
+            pos = end;
+        }
+        out.push_str(&String::from_utf8_lossy(&data[pos.min(text_end)..text_end]));
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,309 @@
+use crate::matcher::ConfigError;
+
+/// How matches are bucketed by a histogram
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Buckets {
+    /// Byte ranges of this many bytes
+    Bytes(usize),
+    /// Periods of `interval` seconds of the timestamp at the start of the line of each match
+    Time {
+        format: TimestampFormat,
+        interval: u64,
+    },
+}
+
+/// Match counts per bucket
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// (bucket_start, bucket_end, match_count) of the buckets holding matches, in order,
+    /// byte offsets or seconds since the epoch
+    pub buckets: Vec<(i64, i64, usize)>,
+    /// Matches without a bucket, found before the first line with a timestamp
+    pub unbucketed: usize,
+}
+
+/// One directive of a timestamp format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    Literal(u8),
+    /// One or more whitespace bytes
+    Space,
+    Year,
+    Month,
+    MonthName,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    /// Fractional seconds, digits that are skipped
+    Fraction,
+    /// UTC offset as Z, +hh, +hhmm or +hh:mm
+    Zone,
+    /// Seconds since the epoch
+    Epoch,
+}
+
+/// strftime-like format of the timestamps prefixing log lines
+///
+/// Supports %Y, %m, %b (English month abbreviation), %d and %e (day, optionally space-padded),
+/// %H, %M, %S, %f (fractional seconds), %z (UTC offset or Z), %s (epoch seconds) and %%.
+/// Whitespace matches any run of whitespace, other characters match themselves. Timestamps
+/// without %z are taken as UTC, and without %Y as of 1970, as in traditional syslog lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimestampFormat {
+    items: Vec<Item>,
+}
+
+const MONTHS: [&[u8; 3]; 12] = [
+    b"jan", b"feb", b"mar", b"apr", b"may", b"jun", b"jul", b"aug", b"sep", b"oct", b"nov", b"dec",
+];
+
+impl TimestampFormat {
+    pub fn parse(format: &str) -> Result<Self, ConfigError> {
+        let mut items = Vec::new();
+        let mut bytes = format.bytes();
+        while let Some(b) = bytes.next() {
+            let item = match b {
+                b'%' => match bytes.next() {
+                    Some(b'Y') => Item::Year,
+                    Some(b'm') => Item::Month,
+                    Some(b'b') => Item::MonthName,
+                    Some(b'd' | b'e') => Item::Day,
+                    Some(b'H') => Item::Hour,
+                    Some(b'M') => Item::Minute,
+                    Some(b'S') => Item::Second,
+                    Some(b'f') => Item::Fraction,
+                    Some(b'z') => Item::Zone,
+                    Some(b's') => Item::Epoch,
+                    Some(b'%') => Item::Literal(b'%'),
+                    other => {
+                        let directive = other.map(|b| (b as char).to_string()).unwrap_or_default();
+                        return Err(ConfigError::Invalid(format!(
+                            "Unknown timestamp directive '%{directive}'"
+                        )));
+                    }
+                },
+                b if b.is_ascii_whitespace() => Item::Space,
+                b => Item::Literal(b),
+            };
+            // Consecutive whitespace in the format matches one run in the line
+            if !(item == Item::Space && items.last() == Some(&Item::Space)) {
+                items.push(item);
+            }
+        }
+        if !items
+            .iter()
+            .any(|item| !matches!(item, Item::Literal(_) | Item::Space))
+        {
+            return Err(ConfigError::Invalid(
+                "Timestamp format has no date or time directive".to_string(),
+            ));
+        }
+        Ok(Self { items })
+    }
+
+    /// Seconds since the epoch of the timestamp starting `line`, None if it does not start
+    /// with one
+    pub fn timestamp(&self, line: &[u8]) -> Option<i64> {
+        let (mut year, mut month, mut day) = (1970, 1, 1);
+        let (mut hour, mut minute, mut second, mut offset) = (0, 0, 0, 0);
+        let mut epoch = None;
+        let mut pos = 0;
+
+        for item in &self.items {
+            match *item {
+                Item::Literal(b) => {
+                    if line.get(pos) != Some(&b) {
+                        return None;
+                    }
+                    pos += 1;
+                }
+                Item::Space => {
+                    let spaces = line[pos..]
+                        .iter()
+                        .take_while(|b| b.is_ascii_whitespace())
+                        .count();
+                    if spaces == 0 {
+                        return None;
+                    }
+                    pos += spaces;
+                }
+                Item::Year => year = number(line, &mut pos, 4)?,
+                Item::Month => month = number(line, &mut pos, 2)?,
+                Item::MonthName => {
+                    let name = line.get(pos..pos + 3)?.to_ascii_lowercase();
+                    month = MONTHS.iter().position(|m| m[..] == name[..])? as i64 + 1;
+                    pos += 3;
+                }
+                Item::Day => {
+                    // Space-padded days, like the " 5" of syslog's "Jun  5"
+                    if line.get(pos) == Some(&b' ') {
+                        pos += 1;
+                    }
+                    day = number(line, &mut pos, 2)?;
+                }
+                Item::Hour => hour = number(line, &mut pos, 2)?,
+                Item::Minute => minute = number(line, &mut pos, 2)?,
+                Item::Second => second = number(line, &mut pos, 2)?,
+                Item::Fraction => {
+                    number(line, &mut pos, 9)?;
+                }
+                Item::Zone => offset = zone(line, &mut pos)?,
+                Item::Epoch => epoch = Some(number(line, &mut pos, 12)?),
+            }
+        }
+
+        if let Some(epoch) = epoch {
+            return Some(epoch);
+        }
+        if !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return None;
+        }
+        Some(
+            days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second
+                - offset,
+        )
+    }
+}
+
+/// Unsigned number of 1 to `max_digits` digits
+fn number(line: &[u8], pos: &mut usize, max_digits: usize) -> Option<i64> {
+    let digits = line[*pos..]
+        .iter()
+        .take(max_digits)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if digits == 0 {
+        return None;
+    }
+    let value = line[*pos..*pos + digits]
+        .iter()
+        .fold(0, |value, &b| value * 10 + i64::from(b - b'0'));
+    *pos += digits;
+    Some(value)
+}
+
+/// UTC offset in seconds of a Z, +hh, +hhmm or +hh:mm zone
+fn zone(line: &[u8], pos: &mut usize) -> Option<i64> {
+    let sign = match line.get(*pos)? {
+        b'Z' => {
+            *pos += 1;
+            return Some(0);
+        }
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    *pos += 1;
+    let hours = number(line, pos, 2)?;
+    if line.get(*pos) == Some(&b':') {
+        *pos += 1;
+    }
+    let minutes = number(line, pos, 2).unwrap_or(0);
+    Some(sign * (hours * 3_600 + minutes * 60))
+}
+
+/// Days since the epoch of a civil date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Bucketing of matches, fed with matches in the order they are found
+///
+/// Time buckets use the timestamp of the line holding the start of each match. Lines without
+/// one, like the continuation lines of a stack trace, take the timestamp of the closest line
+/// above them that has one.
+pub struct HistogramBuilder<'a> {
+    data: &'a [u8],
+    buckets: &'a Buckets,
+    counts: std::collections::BTreeMap<i64, usize>,
+    unbucketed: usize,
+    /// Range of the last line looked up and its timestamp, possibly inherited
+    line: Option<(usize, usize, Option<i64>)>,
+}
+
+impl<'a> HistogramBuilder<'a> {
+    pub fn new(data: &'a [u8], buckets: &'a Buckets) -> Self {
+        Self {
+            data,
+            buckets,
+            counts: Default::default(),
+            unbucketed: 0,
+            line: None,
+        }
+    }
+
+    pub fn push(&mut self, start: usize) {
+        let key = match self.buckets {
+            Buckets::Bytes(size) => Some((start - start % size) as i64),
+            Buckets::Time { format, interval } => self
+                .line_timestamp(format, start)
+                .map(|ts| ts - ts.rem_euclid(*interval as i64)),
+        };
+        match key {
+            Some(key) => *self.counts.entry(key).or_default() += 1,
+            None => self.unbucketed += 1,
+        }
+    }
+
+    /// Timestamp of the line holding `offset`, inherited from the lines above when it has none
+    fn line_timestamp(&mut self, format: &TimestampFormat, offset: usize) -> Option<i64> {
+        if let Some((line_start, line_end, ts)) = self.line {
+            if (line_start..=line_end).contains(&offset) {
+                return ts;
+            }
+        }
+        let line_start = memchr::memrchr(b'\n', &self.data[..offset]).map_or(0, |eol| eol + 1);
+        let line_end =
+            memchr::memchr(b'\n', &self.data[offset..]).map_or(self.data.len(), |eol| offset + eol);
+
+        // Walk up the lines without a timestamp, down to the last line looked up when it
+        // precedes them
+        let mut ts = None;
+        let mut pos = line_start;
+        loop {
+            if let Some((prev_start, prev_end, prev_ts)) = self.line {
+                if prev_start <= pos && pos <= prev_end {
+                    ts = prev_ts;
+                    break;
+                }
+            }
+            let end = memchr::memchr(b'\n', &self.data[pos..]).map_or(self.data.len(), |e| pos + e);
+            if let Some(found) = format.timestamp(&self.data[pos..end]) {
+                ts = Some(found);
+                break;
+            }
+            if pos == 0 {
+                break;
+            }
+            pos = memchr::memrchr(b'\n', &self.data[..pos - 1]).map_or(0, |eol| eol + 1);
+        }
+        self.line = Some((line_start, line_end, ts));
+        ts
+    }
+
+    pub fn finish(self) -> Histogram {
+        let width = match self.buckets {
+            Buckets::Bytes(size) => *size as i64,
+            Buckets::Time { interval, .. } => *interval as i64,
+        };
+        Histogram {
+            buckets: self
+                .counts
+                .into_iter()
+                .map(|(start, count)| (start, start + width, count))
+                .collect(),
+            unbucketed: self.unbucketed,
+        }
+    }
+}
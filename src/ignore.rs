@@ -0,0 +1,266 @@
+//! Ignore files of directory scans: `.gitignore` and `.volutaignore` rules in the gitignore
+//! syntax, excluding paths from the scan like ripgrep does, and in `.volutaignore` only the
+//! matches of some patterns under a path
+
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Ignore files read in every scanned directory, later ones taking precedence
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".volutaignore"];
+
+/// One line of an ignore file
+struct Rule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+    /// Patterns whose matches are dropped under the paths of the rule, which then does not
+    /// exclude them; only `.volutaignore` rules have some
+    patterns: Vec<String>,
+}
+
+impl Rule {
+    /// Rule of a line, None for blank lines, comments and globs that cannot be parsed
+    fn parse(line: &str, tags: bool) -> Option<Self> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let mut words = split_words(line, tags);
+        if words.is_empty() {
+            return None;
+        }
+        let glob = words.remove(0);
+        let patterns = words
+            .into_iter()
+            .filter_map(|word| word.strip_prefix('@').map(str::to_string))
+            .collect();
+
+        let (glob, dir_only) = match glob.strip_suffix('/') {
+            Some(glob) => (glob, true),
+            None => (glob.as_str(), false),
+        };
+        // Globs with a slash before their end are relative to the directory of the file, the
+        // others match a name at any depth below it
+        let anchored = glob.contains('/');
+        let glob = glob.strip_prefix('/').unwrap_or(glob);
+        if glob.is_empty() {
+            return None;
+        }
+        let mut regex = String::from("^");
+        if !anchored {
+            regex.push_str("(?:.*/)?");
+        }
+        glob_regex(glob, &mut regex);
+        regex.push('$');
+        Some(Self {
+            regex: Regex::new(&regex).ok()?,
+            negated,
+            dir_only,
+            patterns,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.regex.is_match(relative)
+    }
+}
+
+/// Words of a line: the whole line in `.gitignore`, its trailing spaces dropped unless
+/// escaped as in git, or in `.volutaignore` the glob and the `@pattern` exceptions, separated by
+/// whitespace that backslashes escape
+fn split_words(line: &str, tags: bool) -> Vec<String> {
+    if !tags {
+        let trimmed = line.trim_end_matches(' ');
+        let glob = if trimmed.ends_with('\\') && trimmed.len() < line.len() {
+            &line[..trimmed.len() + 1]
+        } else {
+            trimmed
+        };
+        if glob.is_empty() {
+            return Vec::new();
+        }
+        return vec![glob.to_string()];
+    }
+
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    // The glob keeps its escapes, which its regex reads
+                    if words.is_empty() {
+                        word.push('\\');
+                    }
+                    word.push(next);
+                }
+            }
+            c if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Append the regex of `glob` to `regex`: `*` and `?` stop at slashes, `**` spans
+/// directories and `[...]` is a character class
+fn glob_regex(glob: &str, regex: &mut String) {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '*' if chars.get(idx + 1) == Some(&'*')
+                && (idx == 0 || chars[idx - 1] == '/')
+                && (idx + 2 == chars.len() || chars[idx + 2] == '/') =>
+            {
+                if idx + 2 == chars.len() {
+                    regex.push_str(".*");
+                } else {
+                    // `**/` matches zero or more directories
+                    regex.push_str("(?:.*/)?");
+                    idx += 1;
+                }
+                idx += 1;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[idx + 1..].iter().skip(1).position(|&c| c == ']') {
+                Some(len) => {
+                    let class = &chars[idx + 1..idx + 2 + len];
+                    regex.push('[');
+                    for (pos, &c) in class.iter().enumerate() {
+                        match c {
+                            '!' | '^' if pos == 0 => regex.push('^'),
+                            '-' if pos > 0 && pos + 1 < class.len() => regex.push('-'),
+                            _ => regex.push_str(&regex::escape(&c.to_string())),
+                        }
+                    }
+                    regex.push(']');
+                    idx += len + 2;
+                }
+                None => regex.push_str(r"\["),
+            },
+            '\\' if idx + 1 < chars.len() => {
+                idx += 1;
+                regex.push_str(&regex::escape(&chars[idx].to_string()));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        idx += 1;
+    }
+}
+
+/// Rules of the ignore files of one directory
+pub(crate) struct IgnoreFile {
+    dir: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl IgnoreFile {
+    /// Rules of the ignore files of `dir`, None when it has none; unreadable ignore files are
+    /// treated as missing, as git does
+    pub(crate) fn load(dir: &Path) -> Option<Self> {
+        let mut rules = Vec::new();
+        for name in IGNORE_FILES {
+            if let Ok(text) = fs::read_to_string(dir.join(name)) {
+                let tags = name == ".volutaignore";
+                rules.extend(text.lines().filter_map(|line| Rule::parse(line, tags)));
+            }
+        }
+        (!rules.is_empty()).then(|| Self {
+            dir: dir.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Path of `path` relative to the directory of the file, with slash separators
+    fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.dir).ok()?;
+        let parts: Vec<_> = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect();
+        Some(parts.join("/"))
+    }
+}
+
+/// Ignore files of the directories from the scanned root down to the directory being walked
+#[derive(Default)]
+pub(crate) struct Ignores(Vec<IgnoreFile>);
+
+impl Ignores {
+    pub(crate) fn push(&mut self, file: IgnoreFile) {
+        self.0.push(file);
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.0.pop();
+    }
+
+    /// Check if `path` is excluded, the last matching rule of the deepest ignore file with one
+    /// deciding
+    pub(crate) fn excluded(&self, path: &Path, is_dir: bool) -> bool {
+        for file in self.0.iter().rev() {
+            let Some(relative) = file.relative(path) else {
+                continue;
+            };
+            let rule = file
+                .rules
+                .iter()
+                .rev()
+                .filter(|rule| rule.patterns.is_empty())
+                .find(|rule| rule.matches(&relative, is_dir));
+            if let Some(rule) = rule {
+                return !rule.negated;
+            }
+        }
+        false
+    }
+
+    /// Patterns whose matches are dropped in the file at `path`, the rules naming a pattern
+    /// applying to the paths below theirs too and a negated rule lifting the exception
+    pub(crate) fn allowed(&self, path: &Path) -> Vec<&str> {
+        let mut allowed: Vec<&str> = Vec::new();
+        for file in &self.0 {
+            let Some(relative) = file.relative(path) else {
+                continue;
+            };
+            for rule in file.rules.iter().filter(|rule| !rule.patterns.is_empty()) {
+                if !covers(rule, &relative) {
+                    continue;
+                }
+                for pattern in &rule.patterns {
+                    if rule.negated {
+                        allowed.retain(|p| p != pattern);
+                    } else if !allowed.contains(&pattern.as_str()) {
+                        allowed.push(pattern);
+                    }
+                }
+            }
+        }
+        allowed
+    }
+}
+
+/// Check if `rule` matches the file at `relative` or one of its directories
+fn covers(rule: &Rule, relative: &str) -> bool {
+    if rule.matches(relative, false) {
+        return true;
+    }
+    relative
+        .match_indices('/')
+        .any(|(pos, _)| rule.matches(&relative[..pos], true))
+}
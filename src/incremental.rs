@@ -0,0 +1,303 @@
+//! Pattern sets growing while they are in use: patterns added in small automaton layers,
+//! merged into larger ones as they accumulate, instead of rebuilding one DFA per addition
+
+use crate::engine::{overlapping_order, standard, Backend, Engine};
+use crate::events::{Event, EventHook};
+use crate::matcher::{is_word_boundary, ConfigError};
+use crate::postprocess::PostProcess;
+use aho_corasick::{Match, PatternID};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::Result;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Options of an IncrementalMatcher
+#[derive(Clone, Debug)]
+pub struct IncrementalOptions {
+    /// Report every match, including matches overlapping other matches
+    pub overlapping: bool,
+    /// Match ASCII letters regardless of case
+    pub case_insensitive: bool,
+    /// Only report matches surrounded by non-word characters
+    pub whole_word: bool,
+    /// Strategy used to resolve overlapping matches, merge or longest
+    pub postprocess: Option<PostProcess>,
+    /// Most layers kept, the newest are merged beyond it
+    pub max_layers: usize,
+    /// Receiver of an event for every rebuild of the layers
+    pub events: Option<EventHook>,
+}
+
+impl Default for IncrementalOptions {
+    fn default() -> Self {
+        Self {
+            overlapping: true,
+            case_insensitive: true,
+            whole_word: false,
+            postprocess: None,
+            max_layers: 16,
+            events: None,
+        }
+    }
+}
+
+/// Automaton over a contiguous range of the patterns
+struct Layer {
+    /// Index of the first pattern of the layer
+    first: usize,
+    patterns: Vec<String>,
+    engine: Backend,
+}
+
+impl Layer {
+    fn build(first: usize, patterns: Vec<String>, case_insensitive: bool) -> Self {
+        // Every layer is an Aho-Corasick DFA, whatever its size
+        let engine = Backend::build(&patterns, case_insensitive, Engine::Aho)
+            .expect("the aho engine accepts every pattern set");
+        Self {
+            first,
+            patterns,
+            engine,
+        }
+    }
+}
+
+/// Matcher whose pattern set can grow while other threads scan with it
+/// New patterns are built into a small layer of their own; the last layers are merged as
+/// soon as the one before holds no more patterns than the newest, so adding n patterns one
+/// by one keeps about log2(n) layers and rebuilds each pattern about log2(n) times
+/// Matches are (start, end, pattern) tuples with the pattern index into `patterns()`, which
+/// is the order the patterns were added in, and match those of a Matcher built on every
+/// pattern at once with the Aho-Corasick engine
+pub struct IncrementalMatcher {
+    layers: RwLock<Arc<Vec<Arc<Layer>>>>,
+    // Serializes additions, scans only ever take the layers lock to clone the snapshot
+    writer: Mutex<()>,
+    options: IncrementalOptions,
+    build_time: RwLock<Duration>,
+}
+
+impl IncrementalMatcher {
+    /// Build a matcher on `patterns`, which can be empty, empty patterns are dropped
+    pub fn new(
+        patterns: Vec<String>,
+        options: IncrementalOptions,
+    ) -> std::result::Result<Self, ConfigError> {
+        if let Some(PostProcess::Priority) = options.postprocess {
+            return Err(ConfigError::Invalid(
+                "IncrementalMatcher supports the 'merge' and 'longest' postprocess strategies"
+                    .to_string(),
+            ));
+        }
+        if options.max_layers == 0 {
+            return Err(ConfigError::Invalid(
+                "max_layers must be positive".to_string(),
+            ));
+        }
+
+        let matcher = Self {
+            layers: RwLock::new(Arc::new(Vec::new())),
+            writer: Mutex::new(()),
+            options,
+            build_time: RwLock::new(Duration::ZERO),
+        };
+        matcher.add_patterns(patterns);
+        Ok(matcher)
+    }
+
+    pub fn options(&self) -> &IncrementalOptions {
+        &self.options
+    }
+
+    /// Add patterns, empty patterns are dropped
+    /// Returns the index of the first added pattern
+    pub fn add_patterns(&self, patterns: Vec<String>) -> usize {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let started = Instant::now();
+        let snapshot = self.snapshot();
+        let first = snapshot.last().map_or(0, |l| l.first + l.patterns.len());
+
+        let patterns: Vec<String> = patterns.into_iter().filter(|p| !p.is_empty()).collect();
+        if patterns.is_empty() {
+            return first;
+        }
+
+        let mut layers: Vec<Arc<Layer>> = snapshot.as_ref().clone();
+        layers.push(Arc::new(Layer::build(
+            first,
+            patterns,
+            self.options.case_insensitive,
+        )));
+        while layers.len() > 1 {
+            let n = layers.len();
+            if layers.len() <= self.options.max_layers
+                && layers[n - 2].patterns.len() > layers[n - 1].patterns.len()
+            {
+                break;
+            }
+            let last = layers.pop().unwrap();
+            let previous = layers.pop().unwrap();
+            layers.push(Arc::new(self.merge(&previous, &last)));
+        }
+
+        self.publish(layers, started, "add_patterns");
+        first
+    }
+
+    /// Merge every layer into a single automaton
+    pub fn compact(&self) {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let started = Instant::now();
+        let snapshot = self.snapshot();
+        if snapshot.len() <= 1 {
+            return;
+        }
+        let patterns = snapshot
+            .iter()
+            .flat_map(|layer| layer.patterns.iter().cloned())
+            .collect();
+        let layer = Layer::build(0, patterns, self.options.case_insensitive);
+        self.publish(vec![Arc::new(layer)], started, "compact");
+    }
+
+    /// Number of automaton layers
+    pub fn layer_count(&self) -> usize {
+        self.snapshot().len()
+    }
+
+    /// Number of patterns
+    pub fn len(&self) -> usize {
+        self.snapshot()
+            .last()
+            .map_or(0, |l| l.first + l.patterns.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Patterns in the order they were added
+    pub fn patterns(&self) -> Vec<String> {
+        self.snapshot()
+            .iter()
+            .flat_map(|layer| layer.patterns.iter().cloned())
+            .collect()
+    }
+
+    /// Pattern of a match
+    pub fn pattern(&self, pattern_idx: PatternID) -> String {
+        pattern(&self.snapshot(), pattern_idx).to_string()
+    }
+
+    /// Time taken by the last addition or compaction
+    pub fn build_time(&self) -> Duration {
+        *self.build_time.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Heap bytes used by the layers and the patterns
+    pub fn memory_usage(&self) -> usize {
+        self.snapshot()
+            .iter()
+            .map(|layer| {
+                layer.engine.memory_usage()
+                    + layer.patterns.iter().map(|p| p.capacity()).sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Convert pattern indices to pattern strings
+    pub fn resolve(&self, matches: Vec<(usize, usize, PatternID)>) -> Vec<(usize, usize, String)> {
+        let snapshot = self.snapshot();
+        matches
+            .into_iter()
+            .map(|(start, end, pattern_idx)| {
+                (start, end, pattern(&snapshot, pattern_idx).to_string())
+            })
+            .collect()
+    }
+
+    /// Match provided bytes against the patterns added so far
+    pub fn match_bytes(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let snapshot = self.snapshot();
+        let matches: Vec<(usize, usize, PatternID)> = match snapshot.as_slice() {
+            // A single layer is searched directly, its pattern indices are the global ones
+            [layer] if !self.options.overlapping => layer
+                .engine
+                .find_iter(data)
+                .map(|m| (m.start(), m.end(), m.pattern()))
+                .collect(),
+            layers => {
+                let mut found: Vec<Match> = layers
+                    .iter()
+                    .flat_map(|layer| {
+                        layer
+                            .engine
+                            .find_overlapping_iter(data)
+                            .map(|m| Match::must(layer.first + m.pattern().as_usize(), m.span()))
+                    })
+                    .collect();
+                if layers.len() > 1 {
+                    overlapping_order(&mut found);
+                }
+                if !self.options.overlapping {
+                    found = standard(found);
+                }
+                found
+                    .into_iter()
+                    .map(|m| (m.start(), m.end(), m.pattern()))
+                    .collect()
+            }
+        };
+
+        let matches = matches
+            .into_iter()
+            .filter(|&(start, end, _)| {
+                !self.options.whole_word || is_word_boundary(data, start, end)
+            })
+            .collect();
+        match self.options.postprocess {
+            Some(postprocess) => postprocess.apply(matches, &[], true),
+            None => matches,
+        }
+    }
+
+    /// Match a memory-mapped file against the patterns added so far
+    pub fn match_file_memmap(&self, path: &str) -> Result<Vec<(usize, usize, PatternID)>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(self.match_bytes(&mmap))
+    }
+
+    /// Layers as of now, kept alive by the caller while additions swap in new ones
+    fn snapshot(&self) -> Arc<Vec<Arc<Layer>>> {
+        Arc::clone(&self.layers.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn merge(&self, previous: &Layer, last: &Layer) -> Layer {
+        let patterns = previous
+            .patterns
+            .iter()
+            .chain(&last.patterns)
+            .cloned()
+            .collect();
+        Layer::build(previous.first, patterns, self.options.case_insensitive)
+    }
+
+    fn publish(&self, layers: Vec<Arc<Layer>>, started: Instant, operation: &'static str) {
+        let elapsed = started.elapsed();
+        let patterns = layers.last().map_or(0, |l| l.first + l.patterns.len());
+        *self.layers.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(layers);
+        *self.build_time.write().unwrap_or_else(|e| e.into_inner()) = elapsed;
+        if let Some(events) = &self.options.events {
+            events.emit(&Event::built(operation, patterns, elapsed));
+        }
+    }
+}
+
+/// Pattern of a global pattern index, found in the layer holding it
+fn pattern(layers: &[Arc<Layer>], pattern_idx: PatternID) -> &str {
+    let idx = pattern_idx.as_usize();
+    let layer = &layers[layers.partition_point(|l| l.first <= idx) - 1];
+    &layer.patterns[idx - layer.first]
+}
@@ -0,0 +1,335 @@
+use crate::matcher::ConfigError;
+use crate::sink::{JsonFinding, MatchSink, OutputSchema, Provenance};
+use apache_avro::writer::datum::GenericDatumWriter;
+use apache_avro::Schema;
+use kafka::producer::{Producer, Record, RequiredAcks};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::io::{self, Result};
+use std::time::Duration;
+
+/// Avro schema of the findings published by Kafka sinks using Avro serialization
+pub const AVRO_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "Finding",
+    "namespace": "voluta",
+    "fields": [
+        {"name": "scan_id", "type": "string"},
+        {"name": "file", "type": ["null", "string"]},
+        {"name": "start", "type": "long"},
+        {"name": "end", "type": "long"},
+        {"name": "pattern", "type": "string"},
+        {"name": "host", "type": ["null", "string"], "default": null},
+        {"name": "fingerprint", "type": ["null", "string"], "default": null},
+        {"name": "timestamp", "type": ["null", "string"], "default": null}
+    ]
+}"#;
+
+/// Message serialization used by Kafka sinks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Serialization {
+    Json,
+    Avro,
+}
+
+/// Configuration of a sink that publishes each finding as a message to a Kafka topic
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct KafkaSink {
+    pub brokers: Vec<String>,
+    pub topic: String,
+    pub serialization: Serialization,
+    pub batch_size: usize,
+    pub ack_timeout: f64,
+    pub client_id: Option<String>,
+}
+
+impl Serialization {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "avro" => Some(Self::Avro),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Avro => "avro",
+        }
+    }
+}
+
+impl KafkaSink {
+    /// A Kafka sink publishing JSON messages with the default batch size and acknowledgement timeout
+    pub fn new(
+        brokers: Vec<String>,
+        topic: impl Into<String>,
+    ) -> std::result::Result<Self, ConfigError> {
+        if brokers.is_empty() {
+            return Err(ConfigError::Invalid(
+                "At least one Kafka broker is required".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            brokers,
+            topic: topic.into(),
+            serialization: Serialization::Json,
+            batch_size: 1000,
+            ack_timeout: 30.0,
+            client_id: None,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl KafkaSink {
+    #[classattr]
+    const AVRO_SCHEMA: &'static str = AVRO_SCHEMA;
+
+    #[new]
+    #[pyo3(signature = (brokers, topic, serialization=None, batch_size=None, ack_timeout=None, client_id=None))]
+    fn py_new(
+        brokers: Vec<String>,
+        topic: String,
+        serialization: Option<&str>,
+        batch_size: Option<usize>,
+        ack_timeout: Option<f64>,
+        client_id: Option<String>,
+    ) -> PyResult<Self> {
+        let defaults = Self::new(brokers, topic)?;
+
+        let serialization = match serialization {
+            Some(name) => Serialization::parse(name).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown serialization '{}', expected 'json' or 'avro'",
+                    name
+                ))
+            })?,
+            None => defaults.serialization,
+        };
+
+        let ack_timeout = ack_timeout.unwrap_or(defaults.ack_timeout);
+        // Rejects NaN, infinite and overflowing timeouts as well
+        if !(ack_timeout > 0.0 && Duration::try_from_secs_f64(ack_timeout).is_ok()) {
+            return Err(ConfigError::Invalid(format!(
+                "ack_timeout must be a positive number of seconds, got {}",
+                ack_timeout
+            ))
+            .into());
+        }
+
+        Ok(Self {
+            serialization,
+            batch_size: batch_size.unwrap_or(defaults.batch_size).max(1),
+            ack_timeout,
+            client_id,
+            ..defaults
+        })
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let client_id = match &self.client_id {
+            Some(client_id) => crate::python::quote(py, client_id)?,
+            None => "None".to_string(),
+        };
+        Ok(format!(
+            "KafkaSink(brokers={}, topic={}, serialization={}, batch_size={}, ack_timeout={:?}, client_id={})",
+            pyo3::types::PyList::new(py, &self.brokers)?.repr()?,
+            crate::python::quote(py, &self.topic)?,
+            crate::python::quote(py, self.serialization.name())?,
+            self.batch_size,
+            self.ack_timeout,
+            client_id
+        ))
+    }
+
+    /// Message serialization, "json" or "avro"
+    #[getter(serialization)]
+    fn serialization_name(&self) -> &'static str {
+        self.serialization.name()
+    }
+
+    #[getter]
+    fn brokers(&self) -> Vec<String> {
+        self.brokers.clone()
+    }
+
+    #[getter]
+    fn topic(&self) -> String {
+        self.topic.clone()
+    }
+
+    #[getter]
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    #[getter]
+    fn ack_timeout(&self) -> f64 {
+        self.ack_timeout
+    }
+
+    #[getter]
+    fn client_id(&self) -> Option<String> {
+        self.client_id.clone()
+    }
+}
+
+#[derive(Serialize)]
+struct Finding<'a> {
+    scan_id: &'a str,
+    file: Option<&'a str>,
+    start: usize,
+    end: usize,
+    pattern: &'a str,
+    // Provenance of the scan, null unless attached
+    host: Option<&'a str>,
+    fingerprint: Option<&'a str>,
+    timestamp: Option<&'a str>,
+}
+
+fn kafka_error(e: kafka::Error) -> io::Error {
+    io::Error::other(format!("Kafka error: {}", e))
+}
+
+/// Publishes findings in batches, each send waits for the broker acknowledgements
+/// Messages are keyed by scan id so the findings of one scan stay ordered in one partition
+pub struct KafkaWriter {
+    producer: Producer,
+    topic: String,
+    /// Parsed Avro schema, None when publishing JSON
+    schema: Option<Schema>,
+    batch_size: usize,
+    scan_id: String,
+    file: Option<String>,
+    provenance: Option<Provenance>,
+    output_schema: OutputSchema,
+    messages: Vec<Vec<u8>>,
+}
+
+impl KafkaWriter {
+    /// Connect to the brokers, `provenance` being attached to every finding when given and
+    /// JSON messages following `output_schema`
+    pub fn connect(
+        config: &KafkaSink,
+        scan_id: String,
+        file: Option<String>,
+        provenance: Option<Provenance>,
+        output_schema: OutputSchema,
+    ) -> Result<Self> {
+        // The fields are public, so the timeout is checked here too
+        let ack_timeout = Duration::try_from_secs_f64(config.ack_timeout)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut builder = Producer::from_hosts(config.brokers.clone())
+            .with_ack_timeout(ack_timeout)
+            .with_required_acks(RequiredAcks::All);
+        if let Some(client_id) = &config.client_id {
+            builder = builder.with_client_id(client_id.clone());
+        }
+        let producer = builder.create().map_err(kafka_error)?;
+
+        let schema = match config.serialization {
+            Serialization::Avro => {
+                Some(Schema::parse_str(AVRO_SCHEMA).map_err(|e| io::Error::other(e.to_string()))?)
+            }
+            Serialization::Json => None,
+        };
+
+        Ok(Self {
+            producer,
+            topic: config.topic.clone(),
+            schema,
+            batch_size: config.batch_size,
+            scan_id,
+            file,
+            provenance,
+            output_schema,
+            messages: Vec::with_capacity(config.batch_size),
+        })
+    }
+
+    fn encode(&self, start: usize, end: usize, pattern: &str) -> Result<Vec<u8>> {
+        let finding = Finding {
+            scan_id: &self.scan_id,
+            file: self.file.as_deref(),
+            start,
+            end,
+            pattern,
+            host: self.provenance.as_ref().and_then(|p| p.host.as_deref()),
+            fingerprint: self.provenance.as_ref().map(|p| p.fingerprint.as_str()),
+            timestamp: self.provenance.as_ref().map(|p| p.timestamp.as_str()),
+        };
+
+        match &self.schema {
+            Some(schema) => GenericDatumWriter::builder(schema)
+                .build()
+                .and_then(|writer| writer.write_ser_to_vec(&finding))
+                .map_err(|e| io::Error::other(e.to_string())),
+            None if self.output_schema == OutputSchema::V1 => {
+                Ok(serde_json::to_vec(&JsonFinding::new(
+                    Some(&self.scan_id),
+                    self.file.as_deref(),
+                    start,
+                    end,
+                    pattern,
+                    self.provenance.as_ref(),
+                ))?)
+            }
+            None => Ok(serde_json::to_vec(&finding)?),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.messages.is_empty() {
+            return Ok(());
+        }
+
+        let records: Vec<_> = self
+            .messages
+            .iter()
+            .map(|value| {
+                Record::from_key_value(&self.topic, self.scan_id.as_bytes(), value.as_slice())
+            })
+            .collect();
+
+        let confirms = self.producer.send_all(&records).map_err(kafka_error)?;
+        for confirm in confirms {
+            for partition in confirm.partition_confirms {
+                if let Err(code) = partition.offset {
+                    return Err(io::Error::other(format!(
+                        "Kafka rejected findings on partition {}: {:?}",
+                        partition.partition, code
+                    )));
+                }
+            }
+        }
+
+        self.messages.clear();
+        Ok(())
+    }
+}
+
+impl MatchSink for KafkaWriter {
+    fn write(&mut self, start: usize, end: usize, pattern: &str, _tags: &[String]) -> Result<()> {
+        let message = self.encode(start, end, pattern)?;
+        self.messages.push(message);
+
+        if self.messages.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        KafkaWriter::flush(self)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        KafkaWriter::flush(self)
+    }
+}
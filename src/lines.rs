@@ -0,0 +1,122 @@
+//! Newline index of a file or buffer, converting the byte offsets of matches to line and
+//! column positions, and UTF-8 character offsets of matches
+
+use aho_corasick::PatternID;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::Result;
+
+/// Indexed bytes, copied from memory or mapped from a file
+enum Source {
+    Owned(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Source {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            Self::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// Offsets of the line starts of a file or buffer, built once so each conversion is a binary
+/// search
+/// Lines are numbered from 1 like the results of `match_file`, columns from 0
+pub struct LineIndex {
+    source: Source,
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Index of `data`
+    pub fn new(data: Vec<u8>) -> Self {
+        Self::build(Source::Owned(data))
+    }
+
+    /// Index of a memory-mapped file, which stays mapped for character columns
+    pub fn from_file(path: &str) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self::build(Source::Mapped(mmap)))
+    }
+
+    fn build(source: Source) -> Self {
+        let data = source.bytes();
+        let mut starts = Vec::with_capacity(data.len() / 64 + 1);
+        starts.push(0);
+        starts.extend(memchr::memchr_iter(b'\n', data).map(|pos| pos + 1));
+        Self { source, starts }
+    }
+
+    /// Bytes indexed
+    pub fn len(&self) -> usize {
+        self.source.bytes().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of lines, a trailing newline starting an empty last line
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Byte offset of the first byte of `line`, None past the last line
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        line.checked_sub(1)
+            .and_then(|idx| self.starts.get(idx))
+            .copied()
+    }
+
+    /// (line, column) of `offset`, the column counting bytes from the start of the line, or
+    /// UTF-8 characters with `chars`; `offset` may be the length of the data, for match ends
+    /// None past the end of the data
+    pub fn position(&self, offset: usize, chars: bool) -> Option<(usize, usize)> {
+        if offset > self.len() {
+            return None;
+        }
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let start = self.starts[line - 1];
+        let column = if chars {
+            char_count(&self.source.bytes()[start..offset])
+        } else {
+            offset - start
+        };
+        Some((line, column))
+    }
+}
+
+/// UTF-8 characters in `bytes`, every byte but continuation bytes starting one, so invalid
+/// lead bytes count as one character each and stray continuation bytes as none
+fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
+
+/// (char_start, char_end) character offsets of the (start, end, pattern) matches of `data`, in
+/// the order of `matches`, counting the characters of `data` once whatever the number of
+/// matches
+pub fn char_offsets(data: &[u8], matches: &[(usize, usize, PatternID)]) -> Vec<(usize, usize)> {
+    // Offsets are visited in order, each slot being a match start (even) or end (odd)
+    let mut order: Vec<(usize, usize)> = matches
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, &(start, end, _))| [(start, 2 * idx), (end, 2 * idx + 1)])
+        .collect();
+    order.sort_unstable();
+
+    let mut offsets = vec![(0, 0); matches.len()];
+    let (mut pos, mut chars) = (0, 0);
+    for (offset, slot) in order {
+        chars += char_count(&data[pos..offset]);
+        pos = offset;
+        if slot % 2 == 0 {
+            offsets[slot / 2].0 = chars;
+        } else {
+            offsets[slot / 2].1 = chars;
+        }
+    }
+    offsets
+}
@@ -0,0 +1,2498 @@
+use crate::audit::{AuditLog, AuditRun};
+use crate::budget::{kind_name, MemoryBudget, OnMemoryLimit, TrieShape};
+use crate::chunked::ChunkedScanner;
+use crate::codecs::DEFAULT_MAX_DECODED_SIZE;
+use crate::compare::Comparison;
+use crate::dedup::{DedupOptions, DedupTracker};
+use crate::density::{DensityRegion, DensityTracker, WindowUnit};
+use crate::encoding::{Decoded, Encoding, AUTO_DETECT_LEN};
+use crate::engine::{Backend, Engine, Matches};
+use crate::events::{Event, EventHook, TracedScan};
+use crate::exclusion::Exclusions;
+use crate::fuzzy::{self, MAX_FUZZY_VARIANTS};
+use crate::histogram::{Buckets, Histogram, HistogramBuilder};
+use crate::mmap::{MappedFile, MmapOptions};
+use crate::patterndiff::PatternDiff;
+use crate::pipeline;
+use crate::postprocess::PostProcess;
+use crate::prefilter::LineFilter;
+use crate::progress::{BuildStage, BuildTracker, ProgressHook};
+use crate::report::SPLIT_FILE_SIZE;
+use crate::reverse::RevMatches;
+use crate::sampling::{self, DensityEstimate, SampleOptions};
+use crate::sink::{self, OutputSchema, Provenance, SinkSpec, SinkSummary};
+use crate::spill::{Spool, SpoolOptions};
+use crate::stats::StatsHandle;
+use crate::tags::{PatternTags, TagFilter};
+use crate::telemetry::ScanContext;
+use crate::throttle::Throttle;
+use crate::tuning::Tuning;
+use crate::unique::{UniqueOptions, UniqueValues};
+use crate::variants::CaseVariants;
+use aho_corasick::{Match, PatternID};
+use hmac::{Hmac, Mac};
+use rayon::prelude::*;
+use sha2::Sha256;
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Result};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Error raised when a matcher or one of its options is misconfigured
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Invalid patterns or options
+    Invalid(String),
+    /// Valid patterns or options exceeding a limit of the library or of an engine
+    Limit(String),
+    /// Build cancelled by its progress hook
+    Cancelled(String),
+}
+
+impl ConfigError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Invalid(message) | Self::Limit(message) | Self::Cancelled(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Metadata of a pattern, such as its owner or severity, carried along without changing the
+/// matches
+pub type PatternMetadata = BTreeMap<String, String>;
+
+/// Memory-mapped file of a multi-file scan and its matches
+pub(crate) type MappedMatches = (MappedFile, Vec<(usize, usize, PatternID)>);
+
+/// Options of a Matcher, the defaults match the Python TextMatcher defaults
+#[derive(Clone, Debug)]
+pub struct MatcherOptions {
+    /// Report every match, including matches overlapping other matches
+    pub overlapping: bool,
+    /// Match ASCII letters regardless of case
+    pub case_insensitive: bool,
+    /// Only report matches surrounded by non-word characters
+    pub whole_word: bool,
+    /// Bytes counting as word characters for whole_word
+    pub word_chars: WordChars,
+    /// Strategy used to resolve overlapping matches, if any
+    pub postprocess: Option<PostProcess>,
+    /// One priority per pattern, higher wins, implies the priority strategy
+    pub priorities: Option<Vec<i64>>,
+    /// Break priority ties in favor of the longer match
+    pub prefer_longer: bool,
+    /// Report the hex HMAC-SHA256 of the matched text under this key instead of the pattern
+    pub hash_key: Option<Vec<u8>>,
+    /// One edit distance per pattern, up to `MAX_FUZZY_DISTANCE`, matching the texts within
+    /// that many character substitutions, insertions and deletions of the pattern
+    pub fuzzy: Option<Vec<usize>>,
+    /// Ignore ASCII whitespace and punctuation in the patterns and the text, matches spanning
+    /// runs of up to `MAX_SEPARATOR_RUN` separators between the characters of their pattern
+    pub ignore_separators: bool,
+    /// One threshold per pattern, the matches of a pattern being reported only when a scanned
+    /// input holds at least that many of them
+    pub min_counts: Option<Vec<usize>>,
+    /// Texts whose occurrences are exclusion zones, matches overlapping one are dropped
+    pub exclude: Option<Vec<String>>,
+    /// Anchor literals a line must contain for the line-oriented scan to search it for the
+    /// patterns, so a large pattern set only runs on the few candidate lines
+    pub line_filter: Option<Vec<String>>,
+    /// What the line-oriented scan does with lines that are not valid UTF-8
+    pub invalid_utf8: InvalidUtf8,
+    /// Encoding of the scanned bytes, legacy encodings are decoded to UTF-8 before the search
+    /// and offsets still point into the raw bytes
+    pub encoding: Encoding,
+    /// Engine searching for the patterns
+    pub engine: Engine,
+    /// Largest estimated size in bytes of the Aho-Corasick automaton, checked before it is
+    /// built, unlimited by default
+    pub max_memory_bytes: Option<usize>,
+    /// What the build does when the automaton would exceed `max_memory_bytes`
+    pub on_memory_limit: OnMemoryLimit,
+    /// Bytes an input decompressed by a scan takes at most, a WARC record or payload, an Avro
+    /// block or an ORC stream
+    pub max_decoded_size: usize,
+    /// How memory-mapped scans map their files
+    pub mmap: MmapOptions,
+    /// Receiver of the structured events of the matcher's builds and scans
+    pub events: Option<EventHook>,
+    /// Append-only log receiving a record of every scan run
+    pub audit_log: Option<AuditLog>,
+    /// JSON schema of the findings exported by sinks and collections
+    pub output_schema: OutputSchema,
+    /// One metadata map per pattern
+    pub metadata: Option<Vec<PatternMetadata>>,
+    /// Tags of each pattern, which scans can be restricted to
+    pub tags: Option<Vec<Vec<String>>>,
+    /// Receiver of the progress of the build, which can cancel it
+    pub progress: Option<ProgressHook>,
+}
+
+impl Default for MatcherOptions {
+    fn default() -> Self {
+        Self {
+            overlapping: true,
+            case_insensitive: true,
+            whole_word: false,
+            word_chars: WordChars::default(),
+            postprocess: None,
+            priorities: None,
+            prefer_longer: true,
+            hash_key: None,
+            fuzzy: None,
+            ignore_separators: false,
+            min_counts: None,
+            exclude: None,
+            line_filter: None,
+            invalid_utf8: InvalidUtf8::default(),
+            encoding: Encoding::default(),
+            engine: Engine::Auto,
+            max_memory_bytes: None,
+            on_memory_limit: OnMemoryLimit::default(),
+            max_decoded_size: DEFAULT_MAX_DECODED_SIZE,
+            mmap: MmapOptions::default(),
+            events: None,
+            audit_log: None,
+            output_schema: OutputSchema::Legacy,
+            metadata: None,
+            tags: None,
+            progress: None,
+        }
+    }
+}
+
+/// Handling of the lines of `match_file` that are not valid UTF-8, as found in mixed-encoding
+/// log directories
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Fail the scan with an InvalidData error
+    #[default]
+    Error,
+    /// Replace invalid sequences with U+FFFD before searching the line, offsets are then
+    /// relative to the replaced line
+    Lossy,
+    /// Skip the line, which still counts in line numbers
+    Skip,
+    /// Search the raw bytes of the line, offsets are relative to the line as read
+    Binary,
+}
+
+impl InvalidUtf8 {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "lossy" => Some(Self::Lossy),
+            "skip" => Some(Self::Skip),
+            "binary" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Lossy => "lossy",
+            Self::Skip => "skip",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// The line searched for the record `buffer`, None when it is skipped
+    fn apply(self, buffer: &[u8]) -> Result<Option<Cow<'_, [u8]>>> {
+        if self == Self::Binary || std::str::from_utf8(buffer).is_ok() {
+            return Ok(Some(Cow::Borrowed(buffer)));
+        }
+        match self {
+            Self::Error => Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )),
+            Self::Lossy => Ok(Some(Cow::Owned(
+                String::from_utf8_lossy(buffer).into_owned().into_bytes(),
+            ))),
+            Self::Skip | Self::Binary => Ok(None),
+        }
+    }
+}
+
+/// Hex SHA-256 of the voluta version, the patterns and the options that change the matches,
+/// the same for matchers built alike by the same version
+pub(crate) fn fingerprint(patterns: &[String], options: &MatcherOptions) -> String {
+    use sha2::Digest;
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("voluta {}\n", env!("CARGO_PKG_VERSION")));
+    // Length-prefixed so pattern lists cannot collide by moving bytes between patterns
+    for pattern in patterns {
+        hasher.update((pattern.len() as u64).to_le_bytes());
+        hasher.update(pattern);
+    }
+    let hash_key = options
+        .hash_key
+        .as_ref()
+        .map(|key| hex(&Sha256::digest(key)));
+    hasher.update(format!(
+        "\noverlapping={:?} case_insensitive={:?} whole_word={:?} word_chars={:?} \
+         postprocess={:?} priorities={:?} prefer_longer={:?} hash_key={:?} fuzzy={:?} \
+         ignore_separators={:?} min_counts={:?} exclude={:?} line_filter={:?} invalid_utf8={:?} \
+         encoding={:?}",
+        options.overlapping,
+        options.case_insensitive,
+        options.whole_word,
+        options.word_chars,
+        options.postprocess.map(|p| p.name()),
+        options.priorities,
+        options.prefer_longer,
+        hash_key,
+        options.fuzzy,
+        options.ignore_separators,
+        options.min_counts,
+        options.exclude,
+        options.line_filter,
+        options.invalid_utf8.name(),
+        options.encoding.name(),
+    ));
+    hex(&hasher.finalize())
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Hex HMAC-SHA256 of `text` keyed with `key`
+pub fn hash_text(key: &[u8], text: &[u8]) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(text);
+    hex(&mac.finalize().into_bytes())
+}
+
+/// Set of the bytes counting as word characters for whole word matching, ASCII letters,
+/// digits and underscore by default
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordChars([u128; 2]);
+
+impl Default for WordChars {
+    fn default() -> Self {
+        DEFAULT_WORD_CHARS
+    }
+}
+
+const DEFAULT_WORD_CHARS: WordChars = {
+    let mut low = 0u128;
+    let mut c = 0u8;
+    while c < 128 {
+        if c.is_ascii_alphanumeric() || c == b'_' {
+            low |= 1 << c;
+        }
+        c += 1;
+    }
+    WordChars([low, 0])
+};
+
+impl WordChars {
+    fn from_bytes(bytes: impl IntoIterator<Item = u8>) -> Self {
+        let mut set = Self([0; 2]);
+        for byte in bytes {
+            set.0[byte as usize / 128] |= 1 << (byte % 128);
+        }
+        set
+    }
+
+    /// Parse a class like the inside of a regex bracket expression, ASCII characters and
+    /// ranges such as `a-z`, a `-` first or last standing for itself: `A-Za-z0-9_.-` adds `.`
+    /// and `-` to the defaults for hostnames, `A-Za-z_` leaves digits out
+    pub fn parse(spec: &str) -> std::result::Result<Self, ConfigError> {
+        if !spec.is_ascii() {
+            return Err(ConfigError::Invalid(format!(
+                "word_chars must only contain ASCII characters, got '{}'",
+                spec
+            )));
+        }
+        let bytes = spec.as_bytes();
+        let mut set = Self([0; 2]);
+        let mut i = 0;
+        while i < bytes.len() {
+            if i + 2 < bytes.len() && bytes[i + 1] == b'-' {
+                let (from, to) = (bytes[i], bytes[i + 2]);
+                if from > to {
+                    return Err(ConfigError::Invalid(format!(
+                        "Invalid range '{}-{}' in word_chars",
+                        from as char, to as char
+                    )));
+                }
+                set = set.union(Self::from_bytes(from..=to));
+                i += 3;
+            } else {
+                set = set.union(Self::from_bytes([bytes[i]]));
+                i += 1;
+            }
+        }
+        Ok(set)
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self([self.0[0] | other.0[0], self.0[1] | other.0[1]])
+    }
+
+    pub fn contains(&self, c: u8) -> bool {
+        self.0[c as usize / 128] & (1 << (c % 128)) != 0
+    }
+
+    /// Class of the set in the syntax of `parse`, runs of three bytes or more as ranges and
+    /// `-` last
+    pub fn spec(&self) -> String {
+        let listed = |c: usize| c < 256 && c != b'-' as usize && self.contains(c as u8);
+        let mut spec = String::new();
+        let mut c = 0;
+        while c < 256 {
+            if !listed(c) {
+                c += 1;
+                continue;
+            }
+            let mut end = c;
+            while listed(end + 1) {
+                end += 1;
+            }
+            if end - c >= 2 {
+                spec.push_str(&format!("{}-{}", c as u8 as char, end as u8 as char));
+            } else {
+                spec.extend((c..=end).map(|b| b as u8 as char));
+            }
+            c = end + 1;
+        }
+        if self.contains(b'-') {
+            spec.push('-');
+        }
+        spec
+    }
+
+    /// Check if the match `data[start..end]` is surrounded by non-word characters, the ends
+    /// of the text counting as word boundaries
+    pub fn is_boundary(&self, data: &[u8], start: usize, end: usize) -> bool {
+        let before_is_word = start > 0 && self.contains(data[start - 1]);
+        let after_is_word = end < data.len() && self.contains(data[end]);
+        !before_is_word && !after_is_word
+    }
+}
+
+/// Longest run of separators `ignore_separators` skips between two characters of a match,
+/// bounding the span of a match so chunked scans can overlap their chunks by enough
+pub const MAX_SEPARATOR_RUN: usize = 8;
+
+/// Check if a byte is a separator skipped by `ignore_separators`, ASCII whitespace or
+/// punctuation
+fn is_separator(c: u8) -> bool {
+    c.is_ascii_whitespace() || c.is_ascii_punctuation()
+}
+
+/// Check if the match `data[start..end]` is surrounded by non-word characters, the ends of
+/// the text counting as word boundaries
+pub(crate) fn is_word_boundary(data: &[u8], start: usize, end: usize) -> bool {
+    DEFAULT_WORD_CHARS.is_boundary(data, start, end)
+}
+
+/// Per-call overrides of the settings a matcher was built with, settings left unset keep
+/// their built value
+/// Only settings applied while scanning can be overridden, case folding and fuzzy variants
+/// are compiled into the automaton
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchOverrides {
+    pub overlapping: Option<bool>,
+    pub whole_word: Option<bool>,
+    pub encoding: Option<Encoding>,
+}
+
+/// Read the next record into `buffer`, up to and including `delimiter` or the end of the file
+/// Returns the bytes read, 0 at the end of the file
+fn read_record(reader: &mut impl BufRead, delimiter: &[u8], buffer: &mut Vec<u8>) -> Result<usize> {
+    let last = delimiter[delimiter.len() - 1];
+    loop {
+        if reader.read_until(last, buffer)? == 0 || buffer.ends_with(delimiter) {
+            return Ok(buffer.len());
+        }
+    }
+}
+
+/// Reader with the bytes read ahead from it put back in front
+type Prefixed<R> = io::Chain<io::Cursor<Vec<u8>>, R>;
+
+/// Multi-pattern matcher built on an Aho-Corasick DFA
+/// Matches are (start, end, pattern) tuples of byte offsets, end exclusive, and the pattern
+/// index into `patterns()`; every method applies the configured post-processing
+/// Clones share the automaton and the patterns
+#[derive(Clone)]
+pub struct Matcher {
+    patterns: Arc<Vec<String>>,
+    // Names reported for the patterns, the patterns themselves unless hash_key is set
+    labels: Arc<Vec<String>>,
+    hash_key: Option<Vec<u8>>,
+    engine: Arc<Backend>,
+    // Size limit of the automaton, kept for the matchers rebuilt from this one
+    memory_budget: Option<MemoryBudget>,
+    // Pattern of every searched pattern when fuzzy variants are searched too, empty otherwise
+    originals: Arc<Vec<PatternID>>,
+    fuzzy: Arc<Vec<usize>>,
+    ignore_separators: bool,
+    min_counts: Arc<Vec<usize>>,
+    // Whether a min count is above 1
+    thresholded: bool,
+    metadata: Arc<Vec<PatternMetadata>>,
+    tags: Arc<PatternTags>,
+    // Patterns a scan is restricted to by tag, every pattern when None
+    tag_filter: Option<Arc<TagFilter>>,
+    exclusions: Option<Arc<Exclusions>>,
+    line_filter: Option<Arc<LineFilter>>,
+    invalid_utf8: InvalidUtf8,
+    encoding: Encoding,
+    // Longest raw span of a match, for chunk overlaps
+    max_pattern_len: usize,
+    overlapping: bool,
+    case_insensitive: bool,
+    whole_word: bool,
+    word_chars: WordChars,
+    postprocess: Option<PostProcess>,
+    priorities: Arc<Vec<i64>>,
+    prefer_longer: bool,
+    build_time: Duration,
+    max_decoded_size: usize,
+    mmap: MmapOptions,
+    events: Option<EventHook>,
+    audit_log: Option<AuditLog>,
+    output_schema: OutputSchema,
+    fingerprint: Arc<String>,
+}
+
+impl Matcher {
+    pub fn new(
+        patterns: Vec<String>,
+        options: MatcherOptions,
+    ) -> std::result::Result<Self, ConfigError> {
+        let events = options.events.clone();
+        let res = Self::build(patterns, options);
+        if let Some(events) = &events {
+            match &res {
+                Ok(matcher) => events.emit(&Event::built(
+                    "build",
+                    matcher.patterns.len(),
+                    matcher.build_time,
+                )),
+                Err(ConfigError::Limit(message)) => {
+                    events.emit(&Event::limit_hit("build", None, message.clone()))
+                }
+                Err(_) => {}
+            }
+        }
+        res
+    }
+
+    fn build(
+        patterns: Vec<String>,
+        options: MatcherOptions,
+    ) -> std::result::Result<Self, ConfigError> {
+        let started = Instant::now();
+        let mut progress = BuildTracker::new(options.progress.clone());
+        let total = patterns.len();
+        progress.report(BuildStage::Prepare, 0, total)?;
+        let fingerprint = Arc::new(fingerprint(&patterns, &options));
+        let priorities_given = options.priorities.is_some();
+        if let Some(priorities) = &options.priorities {
+            if priorities.len() != patterns.len() {
+                return Err(ConfigError::Invalid(
+                    "Priorities must have one entry per pattern".to_string(),
+                ));
+            }
+        }
+
+        if let Some(fuzzy) = &options.fuzzy {
+            if fuzzy.len() != patterns.len() {
+                return Err(ConfigError::Invalid(
+                    "Fuzzy distances must have one entry per pattern".to_string(),
+                ));
+            }
+            if options.hash_key.is_some() && fuzzy.iter().any(|&d| d > 0) {
+                return Err(ConfigError::Invalid(
+                    "Fuzzy patterns cannot be combined with hash_key".to_string(),
+                ));
+            }
+        }
+        if options.hash_key.is_some() && options.ignore_separators {
+            return Err(ConfigError::Invalid(
+                "ignore_separators cannot be combined with hash_key".to_string(),
+            ));
+        }
+        if let Some(min_counts) = &options.min_counts {
+            if min_counts.len() != patterns.len() {
+                return Err(ConfigError::Invalid(
+                    "Min counts must have one entry per pattern".to_string(),
+                ));
+            }
+        }
+        if let Some(metadata) = &options.metadata {
+            if metadata.len() != patterns.len() {
+                return Err(ConfigError::Invalid(
+                    "Metadata must have one entry per pattern".to_string(),
+                ));
+            }
+        }
+        if let Some(tags) = &options.tags {
+            if tags.len() != patterns.len() {
+                return Err(ConfigError::Invalid(
+                    "Tags must have one entry per pattern".to_string(),
+                ));
+            }
+        }
+
+        // Without explicit priorities, patterns listed first take precedence
+        let priorities = options
+            .priorities
+            .unwrap_or_else(|| (0..patterns.len()).map(|i| -(i as i64)).collect());
+        let fuzzy = options.fuzzy.unwrap_or_else(|| vec![0; patterns.len()]);
+        let min_counts = options
+            .min_counts
+            .unwrap_or_else(|| vec![1; patterns.len()]);
+
+        // Filter out empty patterns, along with their priorities, fuzzy distances, min counts,
+        // metadata and tags; patterns of separators only are empty once their separators are ignored
+        let ignore_separators = options.ignore_separators;
+        let kept = |p: &str| p.bytes().any(|b| !ignore_separators || !is_separator(b));
+        let filtered_min_counts: Vec<usize> = patterns
+            .iter()
+            .zip(min_counts)
+            .filter(|(p, _)| kept(p))
+            .map(|(_, count)| count)
+            .collect();
+        let filtered_metadata: Vec<PatternMetadata> = match options.metadata {
+            Some(metadata) => patterns
+                .iter()
+                .zip(metadata)
+                .filter(|(p, _)| kept(p))
+                .map(|(_, metadata)| metadata)
+                .collect(),
+            None => vec![PatternMetadata::new(); patterns.iter().filter(|p| kept(p)).count()],
+        };
+        let filtered_tags: Vec<Vec<String>> = match options.tags {
+            Some(tags) => patterns
+                .iter()
+                .zip(tags)
+                .filter(|(p, _)| kept(p))
+                .map(|(_, tags)| tags)
+                .collect(),
+            None => vec![Vec::new(); patterns.iter().filter(|p| kept(p)).count()],
+        };
+        let (filtered_patterns, (filtered_priorities, filtered_fuzzy)): (
+            Vec<String>,
+            (Vec<i64>, Vec<usize>),
+        ) = patterns
+            .into_iter()
+            .zip(priorities.into_iter().zip(fuzzy))
+            .filter(|(p, _)| kept(p))
+            .unzip();
+        // Thresholds of 0 and 1 report every match
+        let thresholded = filtered_min_counts.iter().any(|&count| count > 1);
+
+        // Check if we have any patterns left after filtering
+        if filtered_patterns.is_empty() {
+            return Err(ConfigError::Invalid(
+                "Pattern set cannot be empty".to_string(),
+            ));
+        }
+        progress.report(BuildStage::Prepare, total, total)?;
+
+        // Patterns searched by the engine: the patterns, without their separators when ignored,
+        // then their fuzzy variants mapped back to the pattern they vary
+        let mut searched: Vec<String> = if ignore_separators {
+            filtered_patterns
+                .iter()
+                .map(|p| {
+                    p.chars()
+                        .filter(|&c| !c.is_ascii() || !is_separator(c as u8))
+                        .collect()
+                })
+                .collect()
+        } else {
+            filtered_patterns.clone()
+        };
+        let mut originals = Vec::new();
+        let fuzzy_given = filtered_fuzzy.iter().any(|&distance| distance > 0);
+        for (idx, &distance) in filtered_fuzzy.iter().enumerate() {
+            if fuzzy_given {
+                progress.report(BuildStage::Expand, idx, filtered_fuzzy.len())?;
+            }
+            let expanded = fuzzy::variants(&searched[idx], distance, options.case_insensitive)?;
+            originals.extend(std::iter::repeat_n(PatternID::must(idx), expanded.len()));
+            searched.extend(expanded);
+            if originals.len() > MAX_FUZZY_VARIANTS {
+                return Err(ConfigError::Limit(format!(
+                    "The fuzzy patterns expand to more than {} variants, lower their distances",
+                    MAX_FUZZY_VARIANTS
+                )));
+            }
+        }
+        if fuzzy_given {
+            progress.report(
+                BuildStage::Expand,
+                filtered_fuzzy.len(),
+                filtered_fuzzy.len(),
+            )?;
+        }
+        let expanded = !originals.is_empty();
+        if expanded {
+            originals.splice(0..0, (0..filtered_patterns.len()).map(PatternID::must));
+        }
+
+        // Calculate the maximum pattern length for overlap handling, matches ignoring
+        // separators are given room for the longest run of separators between each of their
+        // characters
+        let max_pattern_len = searched.iter().map(|p| p.len()).max().unwrap_or(0);
+        let max_pattern_len = if ignore_separators {
+            max_pattern_len + max_pattern_len.saturating_sub(1) * MAX_SEPARATOR_RUN
+        } else {
+            max_pattern_len
+        };
+
+        // Streamed scans keep room for the exclusion zones around a match too
+        let exclusions = match options.exclude {
+            Some(exclude) => Exclusions::new(exclude, options.case_insensitive)?,
+            None => None,
+        };
+        let max_pattern_len = max_pattern_len + exclusions.as_ref().map_or(0, |e| e.max_len());
+        let line_filter = options
+            .line_filter
+            .map(|anchors| LineFilter::new(anchors, options.case_insensitive))
+            .transpose()?;
+
+        // Explicit priorities imply priority-based overlap resolution
+        let postprocess = match (options.postprocess, priorities_given) {
+            (None, true) => Some(PostProcess::Priority),
+            (Some(PostProcess::Priority), _) | (_, false) => options.postprocess,
+            (Some(other), true) => {
+                return Err(ConfigError::Invalid(format!(
+                    "Priorities cannot be combined with the '{}' postprocess strategy",
+                    other.name()
+                )));
+            }
+        };
+
+        let budget = options.max_memory_bytes.map(|max_bytes| MemoryBudget {
+            max_bytes,
+            on_limit: options.on_memory_limit,
+        });
+        let (case_insensitive, engine) = (options.case_insensitive, options.engine);
+        let engine = progress.run(BuildStage::Automaton, move || {
+            if expanded {
+                Backend::build_expanded(&searched, case_insensitive, engine, budget)
+            } else {
+                Backend::build_within(&searched, case_insensitive, engine, budget)
+            }
+        })??;
+
+        // Case-insensitive matches are folded like the patterns, so equal texts hash equally
+        let patterns = Arc::new(filtered_patterns);
+        let labels = match &options.hash_key {
+            Some(key) => {
+                let mut labels = Vec::with_capacity(patterns.len());
+                for (idx, p) in patterns.iter().enumerate() {
+                    progress.report(BuildStage::Labels, idx, patterns.len())?;
+                    labels.push(if case_insensitive {
+                        hash_text(key, p.to_ascii_lowercase().as_bytes())
+                    } else {
+                        hash_text(key, p.as_bytes())
+                    });
+                }
+                progress.report(BuildStage::Labels, patterns.len(), patterns.len())?;
+                Arc::new(labels)
+            }
+            None => Arc::clone(&patterns),
+        };
+
+        Ok(Self {
+            patterns,
+            labels,
+            hash_key: options.hash_key,
+            engine: Arc::new(engine),
+            memory_budget: budget,
+            originals: Arc::new(originals),
+            fuzzy: Arc::new(filtered_fuzzy),
+            ignore_separators,
+            min_counts: Arc::new(filtered_min_counts),
+            metadata: Arc::new(filtered_metadata),
+            tags: Arc::new(PatternTags::new(filtered_tags)),
+            tag_filter: None,
+            thresholded,
+            exclusions: exclusions.map(Arc::new),
+            line_filter: line_filter.map(Arc::new),
+            invalid_utf8: options.invalid_utf8,
+            encoding: options.encoding,
+            max_pattern_len,
+            overlapping: options.overlapping,
+            case_insensitive: options.case_insensitive,
+            whole_word: options.whole_word,
+            word_chars: options.word_chars,
+            postprocess,
+            priorities: Arc::new(filtered_priorities),
+            prefer_longer: options.prefer_longer,
+            build_time: started.elapsed(),
+            max_decoded_size: options.max_decoded_size,
+            mmap: options.mmap,
+            events: options.events,
+            audit_log: options.audit_log,
+            output_schema: options.output_schema,
+            fingerprint,
+        })
+    }
+
+    /// The matcher with `overrides` applied, sharing the automaton, borrowed when nothing
+    /// changes
+    pub fn with_overrides(&self, overrides: MatchOverrides) -> Cow<'_, Matcher> {
+        let overlapping = overrides.overlapping.unwrap_or(self.overlapping);
+        let whole_word = overrides.whole_word.unwrap_or(self.whole_word);
+        let encoding = overrides.encoding.unwrap_or(self.encoding);
+        if overlapping == self.overlapping
+            && whole_word == self.whole_word
+            && encoding == self.encoding
+        {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(Matcher {
+            overlapping,
+            whole_word,
+            encoding,
+            ..self.clone()
+        })
+    }
+
+    /// The matcher with the `Auto` encoding resolved to the one detected from the first bytes
+    /// of an input, so every chunk of the input is decoded the same way
+    pub(crate) fn detected(&self, data: &[u8]) -> Cow<'_, Matcher> {
+        let encoding = self.encoding.detect(data);
+        if encoding == self.encoding {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(Matcher {
+            encoding,
+            ..self.clone()
+        })
+    }
+
+    /// `detected` for the input of a reader, whose first `AUTO_DETECT_LEN` bytes are read ahead
+    /// when the encoding is detected and put back in front of the returned reader
+    fn detected_reader<R: Read>(&self, mut reader: R) -> Result<(Cow<'_, Matcher>, Prefixed<R>)> {
+        let mut prefix = Vec::new();
+        if self.encoding == Encoding::Auto {
+            reader
+                .by_ref()
+                .take(AUTO_DETECT_LEN as u64)
+                .read_to_end(&mut prefix)?;
+        }
+        let matcher = self.detected(&prefix);
+        Ok((matcher, io::Cursor::new(prefix).chain(reader)))
+    }
+
+    /// Patterns of the matcher, empty patterns are dropped when building it
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Shared handle on the reported pattern names, for results that outlive a borrow of the
+    /// matcher
+    pub fn shared_patterns(&self) -> Arc<Vec<String>> {
+        Arc::clone(&self.labels)
+    }
+
+    #[cfg(feature = "python")]
+    pub(crate) fn shared_tags(&self) -> Arc<PatternTags> {
+        Arc::clone(&self.tags)
+    }
+
+    /// Name reported for a pattern: the pattern itself, or the digest of its matches when
+    /// hashing
+    pub fn pattern(&self, pattern_idx: PatternID) -> &str {
+        &self.labels[pattern_idx.as_usize()]
+    }
+
+    /// Tags of the pattern, empty for patterns given none
+    pub fn pattern_tags(&self, pattern_idx: PatternID) -> &[String] {
+        &self.tags()[pattern_idx.as_usize()]
+    }
+
+    /// Whether results report digests of the matched text instead of the patterns
+    pub fn hashed(&self) -> bool {
+        self.hash_key.is_some()
+    }
+
+    pub fn overlapping(&self) -> bool {
+        self.overlapping
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn whole_word(&self) -> bool {
+        self.whole_word
+    }
+
+    pub fn word_chars(&self) -> WordChars {
+        self.word_chars
+    }
+
+    pub fn postprocess(&self) -> Option<PostProcess> {
+        self.postprocess
+    }
+
+    /// Priority of every pattern, patterns listed first ranking higher unless given
+    pub fn priorities(&self) -> &[i64] {
+        &self.priorities
+    }
+
+    pub fn prefer_longer(&self) -> bool {
+        self.prefer_longer
+    }
+
+    /// Scan `data` with this matcher and `other` in parallel, splitting their findings
+    pub fn compare(&self, other: &Matcher, data: &[u8]) -> Comparison {
+        let span = self.span("compare", None, data.len());
+        let comparison = Comparison::scan(self, other, data);
+        span.finish(comparison.matches_a());
+        comparison
+    }
+
+    /// Memory-map a file once and scan it with this matcher and `other`, see `compare`
+    pub fn compare_file(&self, other: &Matcher, path: &str) -> Result<Comparison> {
+        let span = self.file_span("compare_file", path);
+        let res = self
+            .map_file(path)
+            .map(|mmap| Comparison::scan(self, other, &mmap));
+        Self::traced(span, res, Comparison::matches_a)
+    }
+
+    /// Estimate the matches of a memory-mapped file from a random sample of its chunks
+    pub fn estimate_density(&self, path: &str, options: &SampleOptions) -> Result<DensityEstimate> {
+        let span = self.file_span("estimate_density", path);
+        let res = sampling::estimate_file(self, path, options);
+        Self::traced(span, res, |estimate| estimate.sampled_matches)
+    }
+
+    /// Differences of the patterns and options of `other` from those of this matcher
+    pub fn diff_patterns(&self, other: &Matcher) -> PatternDiff {
+        PatternDiff::new(self, other)
+    }
+
+    /// Matcher of the patterns of this matcher followed by those of `others`, with their
+    /// priorities, fuzzy distances, min counts and metadata, so pattern `i` of a matcher
+    /// becomes pattern `i` plus the pattern counts of the matchers before it
+    /// Every matcher must have the options changing the matches of this one, whose other
+    /// options the union keeps; the engine is picked again for the combined pattern set
+    pub fn union(&self, others: &[&Matcher]) -> std::result::Result<Matcher, ConfigError> {
+        for other in others {
+            let mut differences: Vec<&str> = self
+                .diff_patterns(other)
+                .options
+                .iter()
+                .map(|change| change.option)
+                .collect();
+            if self.hashed() && other.hashed() && self.hash_key != other.hash_key {
+                differences.push("hash_key");
+            }
+            if !differences.is_empty() {
+                return Err(ConfigError::Invalid(format!(
+                    "Cannot combine matchers with different options: {}",
+                    differences.join(", ")
+                )));
+            }
+        }
+
+        let picks: Vec<(&Matcher, usize)> = std::iter::once(self)
+            .chain(others.iter().copied())
+            .flat_map(|m| (0..m.patterns.len()).map(move |idx| (m, idx)))
+            .collect();
+        self.rebuild(&picks)
+    }
+
+    /// Dedicated matcher of the patterns with any of `tags`, in order, with their per-pattern
+    /// settings, metadata and tags and the options of this matcher; smaller than the whole
+    /// dictionary, its automaton is faster than a tag filter for hot paths
+    pub fn subset(&self, tags: &[String]) -> std::result::Result<Matcher, ConfigError> {
+        let filter = self.tags.filter(tags)?;
+        let picks: Vec<(&Matcher, usize)> = (0..self.patterns.len())
+            .filter(|&idx| filter.is_active(PatternID::must(idx)))
+            .map(|idx| (self, idx))
+            .collect();
+        if picks.is_empty() {
+            return Err(ConfigError::Invalid(format!(
+                "No pattern has any of the tags {:?}",
+                tags
+            )));
+        }
+        self.rebuild(&picks)
+    }
+
+    /// Matcher of the picked (matcher, pattern) patterns, in order, with their per-pattern
+    /// settings and the options of this matcher; the engine is picked again for them
+    fn rebuild(&self, picks: &[(&Matcher, usize)]) -> std::result::Result<Matcher, ConfigError> {
+        let (patterns, options) = self.picked_options(picks);
+        Matcher::new(patterns, options)
+    }
+
+    /// Patterns and options of a matcher of the picked (matcher, pattern) patterns, see
+    /// `rebuild`
+    pub(crate) fn picked_options(
+        &self,
+        picks: &[(&Matcher, usize)],
+    ) -> (Vec<String>, MatcherOptions) {
+        let patterns = picks
+            .iter()
+            .map(|&(m, idx)| m.patterns[idx].clone())
+            .collect();
+        // Priorities only rank patterns resolving overlaps by priority, passing them otherwise
+        // would imply it
+        let priorities = (self.postprocess == Some(PostProcess::Priority))
+            .then(|| picks.iter().map(|&(m, idx)| m.priorities[idx]).collect());
+        // Defaults are left out, so the result has the fingerprint of a matcher built on its
+        // patterns
+        let fuzzy: Vec<usize> = picks.iter().map(|&(m, idx)| m.fuzzy[idx]).collect();
+        let min_counts: Vec<usize> = picks.iter().map(|&(m, idx)| m.min_counts[idx]).collect();
+        let options = MatcherOptions {
+            overlapping: self.overlapping,
+            case_insensitive: self.case_insensitive,
+            whole_word: self.whole_word,
+            word_chars: self.word_chars,
+            postprocess: self.postprocess,
+            priorities,
+            prefer_longer: self.prefer_longer,
+            hash_key: self.hash_key.clone(),
+            fuzzy: fuzzy.iter().any(|&d| d > 0).then_some(fuzzy),
+            ignore_separators: self.ignore_separators,
+            min_counts: min_counts.iter().any(|&c| c != 1).then_some(min_counts),
+            exclude: self.exclusions.as_ref().map(|e| e.patterns().to_vec()),
+            line_filter: self.line_filter().map(<[String]>::to_vec),
+            invalid_utf8: self.invalid_utf8,
+            encoding: self.encoding,
+            engine: Engine::Auto,
+            max_memory_bytes: self.memory_budget.map(|budget| budget.max_bytes),
+            on_memory_limit: self
+                .memory_budget
+                .map_or(OnMemoryLimit::default(), |budget| budget.on_limit),
+            max_decoded_size: self.max_decoded_size,
+            mmap: self.mmap,
+            events: self.events.clone(),
+            audit_log: self.audit_log.clone(),
+            output_schema: self.output_schema,
+            metadata: Some(
+                picks
+                    .iter()
+                    .map(|&(m, idx)| m.metadata[idx].clone())
+                    .collect(),
+            ),
+            tags: Some(
+                picks
+                    .iter()
+                    .map(|&(m, idx)| m.tags()[idx].clone())
+                    .collect(),
+            ),
+            progress: None,
+        };
+        (patterns, options)
+    }
+
+    /// Time taken to build the matcher
+    pub fn build_time(&self) -> Duration {
+        self.build_time
+    }
+
+    /// Hex SHA-256 of the voluta version, the patterns and the options that change the
+    /// matches, tracing results to the rule set that produced them
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Log receiving a record of every scan run
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit_log.as_ref()
+    }
+
+    /// JSON schema of the findings exported by sinks and collections
+    pub fn output_schema(&self) -> OutputSchema {
+        self.output_schema
+    }
+
+    /// Bytes an input decompressed by a scan takes at most
+    pub fn max_decoded_size(&self) -> usize {
+        self.max_decoded_size
+    }
+
+    /// Heap bytes used by the search engine and the patterns
+    pub fn memory_usage(&self) -> usize {
+        let patterns: usize = self.patterns.iter().map(|p| p.capacity()).sum();
+        let labels: usize = if self.hashed() {
+            self.labels.iter().map(|l| l.capacity()).sum()
+        } else {
+            0
+        };
+        self.engine.memory_usage()
+            + patterns
+            + labels
+            + std::mem::size_of_val(self.originals.as_slice())
+            + self.exclusions.as_ref().map_or(0, |e| e.memory_usage())
+            + self.line_filter.as_ref().map_or(0, |f| f.memory_usage())
+            + self.tags.memory_usage()
+    }
+
+    /// Chunking picked for a memory-mapped scan of `file_size` bytes on `n_threads` workers,
+    /// or on the rayon thread pool when unset
+    pub fn tune(&self, file_size: usize, n_threads: Option<usize>) -> Tuning {
+        let threads = n_threads.unwrap_or_else(rayon::current_num_threads);
+        Tuning::new(
+            file_size,
+            threads,
+            self.max_pattern_len,
+            self.engine.memory_usage(),
+        )
+    }
+
+    /// Chunking picked for a memory-mapped scan of the file at `path`
+    pub fn tune_file(&self, path: &str, n_threads: Option<usize>) -> Result<Tuning> {
+        let file_size = std::fs::metadata(path)?.len();
+        Ok(self.tune(usize::try_from(file_size).unwrap_or(usize::MAX), n_threads))
+    }
+
+    /// Number of states of the pattern trie, one per distinct pattern prefix (ASCII-folded when
+    /// case-insensitive) plus the root, which tracks automaton growth whatever the engine
+    pub fn state_count(&self) -> usize {
+        TrieShape::new(&self.patterns, self.case_insensitive).states
+    }
+
+    /// Engine searching for the patterns, `Auto` resolved to the engine it picked
+    pub fn engine(&self) -> Engine {
+        self.engine.engine()
+    }
+
+    /// Kind of Aho-Corasick automaton searching for the patterns, `dfa`, `contiguous_nfa` or
+    /// `noncontiguous_nfa`, None when the engine does not build one
+    pub fn automaton(&self) -> Option<&'static str> {
+        self.engine.automaton().map(kind_name)
+    }
+
+    /// Edit distance each pattern is matched at, 0 for exact matching
+    pub fn fuzzy(&self) -> &[usize] {
+        &self.fuzzy
+    }
+
+    /// Whether separators are ignored in the patterns and the text
+    pub fn ignore_separators(&self) -> bool {
+        self.ignore_separators
+    }
+
+    /// Matches each pattern needs in a scanned input before any of them is reported
+    pub fn min_counts(&self) -> &[usize] {
+        &self.min_counts
+    }
+
+    /// Metadata of each pattern, empty maps for patterns given none
+    pub fn metadata(&self) -> &[PatternMetadata] {
+        &self.metadata
+    }
+
+    /// Tags of each pattern, empty for patterns given none
+    pub fn tags(&self) -> &[Vec<String>] {
+        self.tags.tags()
+    }
+
+    /// The matcher restricted to the patterns with any of `tags`, sharing the automaton:
+    /// matches of the other patterns are dropped as they are found, so with non-overlapping
+    /// matching they still hide the matches of active patterns they overlap
+    pub fn with_tags(&self, tags: &[String]) -> std::result::Result<Matcher, ConfigError> {
+        Ok(Matcher {
+            tag_filter: Some(Arc::new(self.tags.filter(tags)?)),
+            ..self.clone()
+        })
+    }
+
+    /// Exclusion patterns, empty patterns are dropped when building the matcher
+    pub fn exclude(&self) -> &[String] {
+        self.exclusions.as_ref().map_or(&[], |e| e.patterns())
+    }
+
+    /// Anchors of the line prefilter of `match_file`, if any
+    pub fn line_filter(&self) -> Option<&[String]> {
+        self.line_filter.as_ref().map(|f| f.anchors())
+    }
+
+    /// Handling of the lines of `match_file` that are not valid UTF-8
+    pub fn invalid_utf8(&self) -> InvalidUtf8 {
+        self.invalid_utf8
+    }
+
+    /// Encoding of the scanned bytes
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Non-overlapping engine matches, fuzzy variants reported as their pattern, of the
+    /// patterns active under the tag filter
+    fn find_iter<'a, 'h>(&'a self, data: &'h [u8]) -> Matches<'a, 'h> {
+        self.find_unfiltered_iter(data)
+            .active(self.tag_filter.as_deref())
+    }
+
+    /// Non-overlapping engine matches of every pattern, the inactive ones included, which
+    /// still decide where the search resumes
+    pub(crate) fn find_unfiltered_iter<'a, 'h>(&'a self, data: &'h [u8]) -> Matches<'a, 'h> {
+        if let Some(decoded) = self.encoding.decode(data) {
+            Matches::Buffered(self.find_decoded(&decoded, false).into_iter())
+        } else if self.ignore_separators {
+            Matches::Buffered(self.find_without_separators(data, false).into_iter())
+        } else {
+            self.engine.find_iter(data).originals(&self.originals)
+        }
+    }
+
+    /// Whether the pattern is active under the tag filter
+    pub(crate) fn is_active(&self, pattern: PatternID) -> bool {
+        self.tag_filter
+            .as_ref()
+            .is_none_or(|filter| filter.is_active(pattern))
+    }
+
+    /// Overlapping engine matches, fuzzy variants reported as their pattern, of the patterns
+    /// active under the tag filter
+    pub(crate) fn find_overlapping_iter<'a, 'h>(&'a self, data: &'h [u8]) -> Matches<'a, 'h> {
+        let matches = if let Some(decoded) = self.encoding.decode(data) {
+            Matches::Buffered(self.find_decoded(&decoded, true).into_iter())
+        } else if self.ignore_separators {
+            Matches::Buffered(self.find_without_separators(data, true).into_iter())
+        } else {
+            self.engine
+                .find_overlapping_iter(data)
+                .originals(&self.originals)
+        };
+        matches.active(self.tag_filter.as_deref())
+    }
+
+    /// Engine matches in the UTF-8 text decoded from a legacy encoding, mapped back to the
+    /// raw bytes of the matched characters
+    fn find_decoded(&self, decoded: &Decoded, overlapping: bool) -> Vec<Match> {
+        let text = decoded.text();
+        let matches: Vec<Match> = match (self.ignore_separators, overlapping) {
+            (true, _) => self.find_without_separators(text, overlapping),
+            (false, true) => self
+                .engine
+                .find_overlapping_iter(text)
+                .originals(&self.originals)
+                .collect(),
+            (false, false) => self
+                .engine
+                .find_iter(text)
+                .originals(&self.originals)
+                .collect(),
+        };
+        matches
+            .into_iter()
+            .map(|m| Match::new(m.pattern(), decoded.span(m.start(), m.end())))
+            .collect()
+    }
+
+    /// Engine matches in a copy of `data` without its separators, mapped back to the span of
+    /// `data` from the first to the last byte matched
+    /// Runs of more than `MAX_SEPARATOR_RUN` separators split the text, no match spanning them
+    fn find_without_separators(&self, data: &[u8], overlapping: bool) -> Vec<Match> {
+        let mut text = Vec::with_capacity(data.len());
+        let mut offsets = Vec::with_capacity(data.len());
+        // Ends of the segments of `text` matches may span
+        let mut ends = Vec::new();
+        let mut run = 0;
+        for (pos, &byte) in data.iter().enumerate() {
+            if is_separator(byte) {
+                run += 1;
+                continue;
+            }
+            if run > MAX_SEPARATOR_RUN && !text.is_empty() {
+                ends.push(text.len());
+            }
+            run = 0;
+            text.push(byte);
+            offsets.push(pos);
+        }
+        ends.push(text.len());
+
+        let mut found = Vec::new();
+        let mut start = 0;
+        for end in ends {
+            let segment = &text[start..end];
+            let matches = if overlapping {
+                self.engine.find_overlapping_iter(segment)
+            } else {
+                self.engine.find_iter(segment)
+            };
+            found.extend(matches.originals(&self.originals).map(|m| {
+                let span = offsets[start + m.start()]..offsets[start + m.end() - 1] + 1;
+                Match::new(m.pattern(), span)
+            }));
+            start = end;
+        }
+        found
+    }
+
+    pub fn mmap_options(&self) -> MmapOptions {
+        self.mmap
+    }
+
+    /// Memory-map a file for a scan, applying the mmap options
+    pub(crate) fn map_file(&self, path: &str) -> Result<MappedFile> {
+        MappedFile::open(path, &self.mmap)
+    }
+
+    /// Convert pattern indices to pattern strings
+    pub fn resolve(&self, matches: Vec<(usize, usize, PatternID)>) -> Vec<(usize, usize, String)> {
+        matches
+            .into_iter()
+            .map(|(start, end, pattern_idx)| (start, end, self.pattern(pattern_idx).to_string()))
+            .collect()
+    }
+
+    /// Match provided bytes
+    pub fn match_bytes(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let span = self.span("match_bytes", None, data.len());
+        let matches = self.match_bytes_impl(data);
+        span.finish(matches.len());
+        matches
+    }
+
+    fn match_bytes_impl(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let mut matches = Vec::new();
+        self.for_each_match(data, |start, end, pattern_idx| {
+            matches.push((start, end, pattern_idx))
+        });
+        self.postprocess_matches(matches)
+    }
+
+    /// Memory-map a whole file of a multi-file scan and match it, in chunks on the rayon
+    /// thread pool from `SPLIT_FILE_SIZE` bytes, or only the first match of every pattern
+    /// with `first_only`
+    /// Without post-processing the matches are not sorted
+    pub(crate) fn match_mapped(
+        &self,
+        operation: &'static str,
+        path: &str,
+        first_only: bool,
+    ) -> Result<MappedMatches> {
+        // Audited as part of the multi-file scan
+        let span = TracedScan::file(self.events.as_ref(), operation, path, None);
+        let res = self.map_file(path).map(|mmap| {
+            let matches = if first_only {
+                self.match_first_impl(&mmap)
+            } else {
+                self.match_split(&mmap)
+            };
+            (mmap, matches)
+        });
+        Self::traced(span, res, |(_, matches)| matches.len())
+    }
+
+    /// Match provided bytes, in chunks on the rayon thread pool from `SPLIT_FILE_SIZE` bytes
+    /// Without post-processing the matches are not sorted
+    pub(crate) fn match_split(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        if data.len() >= SPLIT_FILE_SIZE {
+            self.match_bytes_parallel(data)
+        } else {
+            self.match_bytes_impl(data)
+        }
+    }
+
+    /// Match many values, such as the cells of a table or the fields of records, in parallel
+    /// on the rayon thread pool
+    /// Returns (value, start, end, pattern) matches sorted by value then offset
+    pub(crate) fn match_values<V: AsRef<[u8]> + Sync>(
+        &self,
+        values: &[V],
+    ) -> Vec<(usize, usize, usize, PatternID)> {
+        let mut matches: Vec<_> = values
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(idx, value)| {
+                self.match_split(value.as_ref())
+                    .into_iter()
+                    .map(move |(start, end, pattern_idx)| (idx, start, end, pattern_idx))
+            })
+            .collect();
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Match provided bytes in auto-tuned chunks on the rayon thread pool
+    /// Matches come chunk by chunk, by start without overlapping and by end within a chunk
+    /// otherwise
+    pub fn match_bytes_parallel(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let tuning = self.tune(data.len(), None);
+        let matches = if tuning.workers > 1 {
+            self.match_chunks_parallel(data, tuning.chunk_size)
+        } else {
+            let mut matches = Vec::new();
+            self.for_each_match(data, |start, end, pattern_idx| {
+                matches.push((start, end, pattern_idx))
+            });
+            matches
+        };
+        self.postprocess_matches(matches)
+    }
+
+    /// Match provided bytes in chunks of `chunk_size` bytes on the current rayon thread pool
+    /// Matches come chunk by chunk, by start without overlapping and by end within a chunk
+    /// otherwise, the same and in the same order as `match_file_memmap` with that chunk size
+    pub fn match_bytes_chunked(
+        &self,
+        data: &[u8],
+        chunk_size: usize,
+    ) -> Vec<(usize, usize, PatternID)> {
+        let span = self.span("match_bytes_chunked", None, data.len());
+        let matches = self.postprocess_matches(self.match_chunks_parallel(data, chunk_size));
+        span.finish(matches.len());
+        matches
+    }
+
+    /// Matches of `data` from the last to the first, searched back from its end
+    /// Every occurrence counts whatever the overlapping setting, and neither post-processing
+    /// nor min counts are applied
+    pub fn rev_matches<'h>(&self, data: &'h [u8]) -> RevMatches<'_, 'h> {
+        RevMatches::new(self, data)
+    }
+
+    /// The last `count` matches of `data`, the last one first, see `rev_matches`
+    pub fn find_last(&self, data: &[u8], count: usize) -> Vec<(usize, usize, PatternID)> {
+        let span = self.span("find_last", None, data.len());
+        let matches: Vec<_> = self.rev_matches(data).take(count).collect();
+        span.finish(matches.len());
+        matches
+    }
+
+    /// The last `count` matches of a memory-mapped file, the last one first, reading only the
+    /// end of the file when the matches are there
+    pub fn find_last_file(
+        &self,
+        path: &str,
+        count: usize,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let span = self.file_span("find_last_file", path);
+        let res = self
+            .map_file(path)
+            .map(|mmap| self.rev_matches(&mmap).take(count).collect());
+        Self::traced(span, res, |res: &Vec<_>| res.len())
+    }
+
+    /// Match a file line by line, skipping the lines without an anchor of the line filter
+    /// Returns (line_number, start, end, pattern) tuples with offsets relative to the line
+    pub fn match_file(&self, path: &str) -> Result<Vec<(usize, usize, usize, PatternID)>> {
+        let span = self.file_span("match_file", path);
+        Self::traced(span, self.match_file_impl(path, b"\n"), |res| res.len())
+    }
+
+    /// Match a memory-mapped file line by line like `match_file`, blocks of whole lines of about
+    /// `chunk_size` bytes searched in parallel
+    /// Returns the same (line_number, start, end, pattern) tuples as `match_file`, in file order
+    pub fn match_file_parallel(
+        &self,
+        path: &str,
+        chunk_size: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<(usize, usize, usize, PatternID)>> {
+        let span = self.file_span("match_file_parallel", path);
+        let res = self.match_file_parallel_impl(path, chunk_size, n_threads);
+        Self::traced(span, res, |res| res.len())
+    }
+
+    /// Match a file record by record, records ending with `delimiter` instead of a newline,
+    /// e.g. NUL for NUL-delimited exports or a blank line for multi-line log records
+    /// Returns (record_number, start, end, pattern) tuples with offsets relative to the record
+    pub fn match_file_records(
+        &self,
+        path: &str,
+        delimiter: &[u8],
+    ) -> Result<Vec<(usize, usize, usize, PatternID)>> {
+        let span = self.file_span("match_file_records", path);
+        let res = if delimiter.is_empty() {
+            Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Record delimiter cannot be empty",
+            ))
+        } else {
+            self.match_file_impl(path, delimiter)
+        };
+        Self::traced(span, res, |res| res.len())
+    }
+
+    /// Match a memory-mapped file, processed in chunks of `chunk_size` bytes
+    pub fn match_file_memmap(
+        &self,
+        path: &str,
+        chunk_size: usize,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let span = self.file_span("match_file_memmap", path);
+        let res = self
+            .match_file_memmap_impl(path, chunk_size)
+            .map(|res| self.postprocess_matches(res));
+        Self::traced(span, res, |res| res.len())
+    }
+
+    /// Match a memory-mapped file, processing chunks of `chunk_size` bytes in parallel
+    /// Matches come chunk by chunk, by start without overlapping and by end within a chunk
+    /// otherwise, the same and in the same order as `match_file_memmap` with that chunk size
+    pub fn match_file_memmap_parallel(
+        &self,
+        path: &str,
+        chunk_size: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let span = self.file_span("match_file_memmap_parallel", path);
+        let res = self
+            .match_file_memmap_parallel_impl(path, chunk_size, n_threads)
+            .map(|res| self.postprocess_matches(res));
+        Self::traced(span, res, |res| res.len())
+    }
+
+    /// Match a file read in buffers of `buffer_size` bytes, optionally throttled
+    pub fn match_file_stream(
+        &self,
+        path: &str,
+        buffer_size: usize,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let span = self.file_span("match_file_stream", path);
+        let res = self
+            .match_file_stream_impl(path, buffer_size, max_bytes_per_sec)
+            .map(|res| self.postprocess_matches(res));
+        Self::traced(span, res, |res| res.len())
+    }
+
+    /// Match provided bytes in chunks of `buffer_size` bytes
+    pub fn match_stream(
+        &self,
+        data: &[u8],
+        buffer_size: usize,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let span = self.span("match_stream", None, data.len());
+        let res = self
+            .match_stream_impl(data, buffer_size)
+            .map(|res| self.postprocess_matches(res));
+        Self::traced(span, res, |res| res.len())
+    }
+
+    /// Match a memory-mapped file, keeping at most `options.max_in_memory` matches in memory
+    /// and spilling the others to a temporary file, for result sets too large for memory
+    /// Spooled scans cannot post-process nor apply min counts, which need every match at once
+    pub fn match_file_spooled(&self, path: &str, options: SpoolOptions) -> Result<Spool> {
+        if self.postprocess.is_some() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Spooled scans cannot post-process matches",
+            ));
+        }
+        if self.thresholded {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Spooled scans cannot apply min counts",
+            ));
+        }
+        let span = self.file_span("match_file_spooled", path);
+        let res = self.map_file(path).and_then(|mmap| {
+            let mut spool = Spool::new(options);
+            self.try_for_each_match(&mmap, |start, end, pattern_idx| {
+                spool.push(start, end, pattern_idx)
+            })?;
+            Ok(spool)
+        });
+        Self::traced(span, res, |spool| spool.len())
+    }
+
+    /// Match anything implementing Read, read in chunks of `buffer_size` bytes
+    /// The bytes following the last chunk's matches are carried over, so matches spanning
+    /// chunks are found, and matches ending near the end of a chunk wait for the next one to
+    /// check the word boundary and the exclusion zones
+    pub fn match_reader(
+        &self,
+        reader: impl Read,
+        buffer_size: usize,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let matches = self.match_chunked_reader(reader, buffer_size, |_| {})?;
+        Ok(self.postprocess_matches(matches))
+    }
+
+    /// Match anything implementing Read on `n_threads` workers, or one per core when unset
+    /// This thread reads chunks of `buffer_size` bytes and hands them to the workers over a
+    /// bounded channel, so pipes, sockets and decompression streams are scanned on all cores
+    /// with at most a few chunks per worker in memory, finding the matches of `match_reader`
+    pub fn match_reader_parallel(
+        &self,
+        reader: impl Read,
+        buffer_size: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let workers = n_threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, |workers| workers.get())
+        });
+        let (matcher, reader) = self.detected_reader(reader)?;
+        let matches = pipeline::scan(&matcher, reader, buffer_size.max(1), workers)?;
+        Ok(self.postprocess_matches(matches))
+    }
+
+    /// Density rule, reports regions where at least `min_matches` matches fall inside a
+    /// sliding window of `window` bytes or lines, counting only the patterns selected by `counted`
+    /// Returns (start, end, match_count) tuples
+    pub fn match_density(
+        &self,
+        data: &[u8],
+        min_matches: usize,
+        window: usize,
+        unit: WindowUnit,
+        counted: Option<&[bool]>,
+    ) -> Vec<(usize, usize, usize)> {
+        self.match_density_impl(data, min_matches, window, unit, counted, false)
+            .into_iter()
+            .map(|region| (region.start, region.end, region.count))
+            .collect()
+    }
+
+    /// Density rule reporting every region with, when `evidence` is set, the (start, end,
+    /// pattern) matches counted in it in the order they were found, see `match_density`
+    pub fn match_density_regions(
+        &self,
+        data: &[u8],
+        min_matches: usize,
+        window: usize,
+        unit: WindowUnit,
+        counted: Option<&[bool]>,
+        evidence: bool,
+    ) -> Vec<DensityRegion> {
+        self.match_density_impl(data, min_matches, window, unit, counted, evidence)
+    }
+
+    /// Distinct matched strings per pattern, for patterns that matched at least once
+    /// Hash-only matchers report the digests of the distinct strings instead
+    pub fn extract_unique(
+        &self,
+        data: &[u8],
+        options: &UniqueOptions,
+    ) -> BTreeMap<PatternID, BTreeSet<String>> {
+        let mut values = UniqueValues::new(
+            data,
+            options,
+            self.hash_key.as_deref(),
+            self.case_insensitive,
+        );
+
+        if self.batched() {
+            for (start, end, pattern_idx) in self.match_bytes(data) {
+                values.push(start, end, pattern_idx);
+            }
+        } else {
+            self.for_each_match(data, |start, end, pattern_idx| {
+                values.push(start, end, pattern_idx)
+            });
+        }
+
+        values.finish()
+    }
+
+    /// Distinct matched strings per pattern in a memory-mapped file, see `extract_unique`
+    pub fn extract_unique_file(
+        &self,
+        path: &str,
+        options: &UniqueOptions,
+    ) -> Result<BTreeMap<PatternID, BTreeSet<String>>> {
+        let span = self.file_span("extract_unique_file", path);
+        let res = self
+            .map_file(path)
+            .map(|mmap| self.extract_unique(&mmap, options));
+        Self::traced(span, res, |res| res.values().map(BTreeSet::len).sum())
+    }
+
+    /// Count of each original-case form matched by each pattern, for patterns that matched at
+    /// least once, such as "Password", "PASSWORD" and "password" found by a case-insensitive scan
+    /// Hash-only matchers report the digests of the forms instead
+    pub fn case_variants(&self, data: &[u8]) -> BTreeMap<PatternID, BTreeMap<String, usize>> {
+        let mut variants = CaseVariants::new(data, self.hash_key.as_deref());
+
+        if self.batched() {
+            for (start, end, pattern_idx) in self.match_bytes(data) {
+                variants.push(start, end, pattern_idx);
+            }
+        } else {
+            self.for_each_match(data, |start, end, pattern_idx| {
+                variants.push(start, end, pattern_idx)
+            });
+        }
+
+        variants.finish()
+    }
+
+    /// Original-case forms per pattern in a memory-mapped file, see `case_variants`
+    pub fn case_variants_file(
+        &self,
+        path: &str,
+    ) -> Result<BTreeMap<PatternID, BTreeMap<String, usize>>> {
+        let span = self.file_span("case_variants_file", path);
+        let res = self.map_file(path).map(|mmap| self.case_variants(&mmap));
+        Self::traced(span, res, |res| {
+            res.values().flat_map(BTreeMap::values).sum::<usize>()
+        })
+    }
+
+    /// Matches with repeated identical findings folded into the first, see `DedupTracker`
+    /// Returns (start, end, pattern, count) tuples, count including the reported match
+    pub fn match_dedup(
+        &self,
+        data: &[u8],
+        options: DedupOptions,
+    ) -> Vec<(usize, usize, PatternID, usize)> {
+        let mut tracker = DedupTracker::new(data, options);
+
+        if self.batched() {
+            for (start, end, pattern_idx) in self.match_bytes(data) {
+                tracker.push(start, end, pattern_idx);
+            }
+        } else {
+            self.for_each_match(data, |start, end, pattern_idx| {
+                tracker.push(start, end, pattern_idx)
+            });
+        }
+
+        tracker.finish()
+    }
+
+    /// Deduplicated matches of a memory-mapped file, see `match_dedup`
+    pub fn match_file_dedup(
+        &self,
+        path: &str,
+        options: DedupOptions,
+    ) -> Result<Vec<(usize, usize, PatternID, usize)>> {
+        let span = self.file_span("match_file_dedup", path);
+        let res = self
+            .map_file(path)
+            .map(|mmap| self.match_dedup(&mmap, options));
+        Self::traced(span, res, |res| res.iter().map(|m| m.3).sum())
+    }
+
+    /// Match counts per byte range or per period of the timestamps prefixing the lines,
+    /// counting only the patterns selected by `counted`
+    pub fn match_histogram(
+        &self,
+        data: &[u8],
+        buckets: &Buckets,
+        counted: Option<&[bool]>,
+    ) -> Histogram {
+        let mut histogram = HistogramBuilder::new(data, buckets);
+        let mut push = |start: usize, pattern_idx: PatternID| {
+            if counted.is_none_or(|mask| mask[pattern_idx.as_usize()]) {
+                histogram.push(start);
+            }
+        };
+
+        if self.batched() {
+            for (start, _, pattern_idx) in self.match_bytes(data) {
+                push(start, pattern_idx);
+            }
+        } else {
+            self.for_each_match(data, |start, _, pattern_idx| push(start, pattern_idx));
+        }
+
+        histogram.finish()
+    }
+
+    /// Match counts per bucket of a memory-mapped file, see `match_histogram`
+    pub fn match_file_histogram(
+        &self,
+        path: &str,
+        buckets: &Buckets,
+        counted: Option<&[bool]>,
+    ) -> Result<Histogram> {
+        let span = self.file_span("match_file_histogram", path);
+        let res = self
+            .map_file(path)
+            .map(|mmap| self.match_histogram(&mmap, buckets, counted));
+        Self::traced(span, res, |res| {
+            res.unbucketed + res.buckets.iter().map(|b| b.2).sum::<usize>()
+        })
+    }
+
+    /// Build a per-pattern mask selecting the given patterns
+    pub fn pattern_mask(&self, patterns: &[String]) -> std::result::Result<Vec<bool>, ConfigError> {
+        let mut mask = vec![false; self.patterns.len()];
+        for name in patterns {
+            let mut found = false;
+            for (idx, pattern) in self.patterns.iter().enumerate() {
+                if pattern == name {
+                    mask[idx] = true;
+                    found = true;
+                }
+            }
+            if !found {
+                return Err(ConfigError::Invalid(format!("Unknown pattern '{}'", name)));
+            }
+        }
+
+        Ok(mask)
+    }
+
+    /// Whether every one of `patterns` has a match in `data` as match_bytes reports them, the
+    /// scan stopping as soon as all of them have been seen
+    /// Post-processing decides which matches are reported, so matchers with a postprocess scan
+    /// the whole of `data`
+    pub fn contains_all(
+        &self,
+        data: &[u8],
+        patterns: &[String],
+    ) -> std::result::Result<bool, ConfigError> {
+        let (slots, required) = self.name_slots(patterns)?;
+        let span = self.span("contains_all", None, data.len());
+        let mut seen = vec![false; required];
+        let mut missing = required;
+        let mut see = |pattern_idx: PatternID| {
+            if let Some(slot) = slots[pattern_idx.as_usize()] {
+                if !seen[slot] {
+                    seen[slot] = true;
+                    missing -= 1;
+                }
+            }
+            missing == 0
+        };
+        if required > 0 {
+            if self.batched() {
+                for (_, _, pattern_idx) in self.match_bytes_impl(data) {
+                    see(pattern_idx);
+                }
+            } else {
+                self.find_until(data, |_, _, pattern_idx| see(pattern_idx));
+            }
+        }
+        span.finish(required - missing);
+        Ok(missing == 0)
+    }
+
+    /// First match of every pattern in `data` as match_bytes reports them, sorted by offset,
+    /// the scan stopping as soon as every pattern has been seen
+    /// Patterns sharing a name count as one, and matchers with a postprocess scan the whole of
+    /// `data`
+    pub fn match_first(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let span = self.span("match_first", None, data.len());
+        let matches = self.match_first_impl(data);
+        span.finish(matches.len());
+        matches
+    }
+
+    /// First match of every pattern in a memory-mapped file, see `match_first`
+    pub fn match_file_first(&self, path: &str) -> Result<Vec<(usize, usize, PatternID)>> {
+        let span = self.file_span("match_file_first", path);
+        let res = self.map_file(path).map(|mmap| self.match_first_impl(&mmap));
+        Self::traced(span, res, |res| res.len())
+    }
+
+    fn match_first_impl(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let (slots, count) = self
+            .name_slots(&self.patterns)
+            .expect("the matcher has its own patterns");
+        let mut seen = vec![false; count];
+        let mut missing = count;
+        let mut first = Vec::new();
+        let mut keep = |start: usize, end: usize, pattern_idx: PatternID| {
+            if let Some(slot) = slots[pattern_idx.as_usize()] {
+                if !seen[slot] {
+                    seen[slot] = true;
+                    missing -= 1;
+                    first.push((start, end, pattern_idx));
+                }
+            }
+            missing == 0
+        };
+        if count > 0 {
+            if self.batched() {
+                for (start, end, pattern_idx) in self.match_bytes_impl(data) {
+                    keep(start, end, pattern_idx);
+                }
+            } else {
+                self.find_until(data, &mut keep);
+            }
+        }
+        first.sort_unstable();
+        first
+    }
+
+    /// Slot of every pattern among the distinct `names`, patterns of the same name sharing
+    /// one, and the number of slots
+    fn name_slots(
+        &self,
+        names: &[String],
+    ) -> std::result::Result<(Vec<Option<usize>>, usize), ConfigError> {
+        let mut index: HashMap<&str, (usize, bool)> = HashMap::new();
+        for name in names {
+            let slots = index.len();
+            index.entry(name).or_insert((slots, false));
+        }
+        let mut slots = vec![None; self.patterns.len()];
+        for (idx, pattern) in self.patterns.iter().enumerate() {
+            if let Some((slot, found)) = index.get_mut(pattern.as_str()) {
+                slots[idx] = Some(*slot);
+                *found = true;
+            }
+        }
+        if let Some(name) = names.iter().find(|name| !index[name.as_str()].1) {
+            return Err(ConfigError::Invalid(format!("Unknown pattern '{}'", name)));
+        }
+        Ok((slots, index.len()))
+    }
+
+    /// Match provided bytes and write the matches to a sink as they are found
+    /// `file` is recorded by sinks that support it, and with `provenance` every match carries
+    /// the provenance of the scan
+    pub fn match_to_sink(
+        &self,
+        data: &[u8],
+        sink: &SinkSpec,
+        scan_id: Option<String>,
+        file: Option<&str>,
+        provenance: bool,
+    ) -> Result<SinkSummary> {
+        let operation = if file.is_some() {
+            "match_file_to_sink"
+        } else {
+            "match_bytes_to_sink"
+        };
+        let span = self.span(operation, file, data.len());
+        Self::traced(
+            span,
+            self.match_to_sink_impl(data, sink, scan_id, file, provenance),
+            |summary| summary.matches_written,
+        )
+    }
+
+    /// Memory-map a file and write its matches to a sink as they are found
+    pub fn match_file_to_sink(
+        &self,
+        path: &str,
+        sink: &SinkSpec,
+        scan_id: Option<String>,
+        provenance: bool,
+    ) -> Result<SinkSummary> {
+        let mmap = self.map_file(path)?;
+        self.match_to_sink(&mmap, sink, scan_id, Some(path), provenance)
+    }
+
+    /// Start the telemetry span and the events of a scan, audited when it scans a file
+    fn span(&self, operation: &'static str, file: Option<&str>, bytes: usize) -> TracedScan {
+        let audit = file.and_then(|file| AuditRun::start(self, operation, &[file]));
+        TracedScan::start(self.events.as_ref(), operation, file, bytes, audit)
+    }
+
+    /// Start the telemetry span, the events and the audit of a scan of the file at `path`
+    fn file_span(&self, operation: &'static str, path: &str) -> TracedScan {
+        let audit = AuditRun::start(self, operation, &[path]);
+        TracedScan::file(self.events.as_ref(), operation, path, audit)
+    }
+
+    /// Emit an event to the matcher's hook, if it has one
+    pub(crate) fn emit(&self, event: impl FnOnce() -> Event) {
+        if let Some(events) = &self.events {
+            events.emit(&event());
+        }
+    }
+
+    /// End a scan span with the outcome of the scan
+    fn traced<T>(span: TracedScan, res: Result<T>, matches: impl FnOnce(&T) -> usize) -> Result<T> {
+        span.end(res, matches)
+    }
+
+    /// Bytes after a match needed to decide whether it is reported: the byte checked for the
+    /// word boundary, and the rest of any exclusion zone overlapping the match
+    pub(crate) fn lookahead(&self) -> usize {
+        self.exclusions.as_ref().map_or(1, |e| e.max_len())
+    }
+
+    /// Longest raw span of a match, exclusion patterns included
+    pub(crate) fn max_pattern_len(&self) -> usize {
+        self.max_pattern_len
+    }
+
+    /// Every reported occurrence starting in `from..to`, whatever the overlapping setting,
+    /// searching only the bytes around that range the occurrences and their checks need
+    pub(crate) fn occurrences_between(
+        &self,
+        data: &[u8],
+        from: usize,
+        to: usize,
+    ) -> Vec<(usize, usize, PatternID)> {
+        let context = self.lookahead();
+        let lo = from.saturating_sub(context);
+        let hi = (to + self.max_pattern_len + context).min(data.len());
+        let window = &data[lo..hi];
+        self.find_overlapping_iter(window)
+            .filter(|m| (from..to).contains(&(lo + m.start())))
+            .filter(|m| self.is_reported_match(window, m.start(), m.end()))
+            .map(|m| (lo + m.start(), lo + m.end(), m.pattern()))
+            .collect()
+    }
+
+    /// Number of the matches match_bytes reports starting in `from..to`, searching only the
+    /// bytes around that range
+    /// Min count thresholds hold for whole inputs and are not applied
+    pub(crate) fn count_between(&self, data: &[u8], from: usize, to: usize) -> usize {
+        let lo = from.saturating_sub(self.lookahead().max(self.max_pattern_len));
+        let hi = (to + self.max_pattern_len + self.lookahead()).min(data.len());
+        let mut matches = Vec::new();
+        self.for_each_match(&data[lo..hi], |start, end, pattern_idx| {
+            matches.push((start, end, pattern_idx))
+        });
+        self.resolve_overlaps(matches)
+            .iter()
+            .filter(|m| (from..to).contains(&(lo + m.0)))
+            .count()
+    }
+
+    /// Check if a match is at word boundaries, when whole_word is set, and outside the
+    /// exclusion zones
+    pub(crate) fn is_reported_match(&self, data: &[u8], start: usize, end: usize) -> bool {
+        (!self.whole_word || self.word_chars.is_boundary(data, start, end))
+            && !self
+                .exclusions
+                .as_ref()
+                .is_some_and(|e| e.overlaps(data, start, end))
+    }
+
+    /// Apply the configured post-processing, then the min count thresholds, to the raw
+    /// matches of a scanned input
+    fn postprocess_matches(
+        &self,
+        matches: Vec<(usize, usize, PatternID)>,
+    ) -> Vec<(usize, usize, PatternID)> {
+        self.apply_min_counts(self.resolve_overlaps(matches), |m| m.2)
+    }
+
+    /// Apply the configured post-processing to raw matches
+    fn resolve_overlaps(
+        &self,
+        matches: Vec<(usize, usize, PatternID)>,
+    ) -> Vec<(usize, usize, PatternID)> {
+        match self.postprocess {
+            Some(postprocess) => postprocess.apply(matches, &self.priorities, self.prefer_longer),
+            None => matches,
+        }
+    }
+
+    /// Drop the matches of the patterns matched fewer times than their min count
+    fn apply_min_counts<T>(
+        &self,
+        mut matches: Vec<T>,
+        pattern: impl Fn(&T) -> PatternID,
+    ) -> Vec<T> {
+        if !self.thresholded {
+            return matches;
+        }
+        let mut counts = vec![0; self.patterns.len()];
+        for m in &matches {
+            counts[pattern(m).as_usize()] += 1;
+        }
+        matches.retain(|m| {
+            let idx = pattern(m).as_usize();
+            counts[idx] >= self.min_counts[idx]
+        });
+        matches
+    }
+
+    /// Whether the matches reported depend on the other matches of the input, through
+    /// post-processing or min count thresholds, so scans need every match at once
+    fn batched(&self) -> bool {
+        self.postprocess.is_some() || self.thresholded
+    }
+
+    /// Call `f` with every (start, end, pattern) match in `data`, honoring the
+    /// overlapping and whole_word settings but not post-processing
+    pub fn for_each_match(&self, data: &[u8], mut f: impl FnMut(usize, usize, PatternID)) {
+        let _ = self.try_for_each_match(data, |start, end, pattern_idx| {
+            f(start, end, pattern_idx);
+            Ok(())
+        });
+    }
+
+    /// Like `for_each_match`, stopping at the first error returned by `f`
+    pub fn try_for_each_match(
+        &self,
+        data: &[u8],
+        mut f: impl FnMut(usize, usize, PatternID) -> Result<()>,
+    ) -> Result<()> {
+        if self.overlapping {
+            for mat in self.find_overlapping_iter(data) {
+                if self.is_reported_match(data, mat.start(), mat.end()) {
+                    f(mat.start(), mat.end(), mat.pattern())?;
+                }
+            }
+        } else {
+            for mat in self.find_iter(data) {
+                if self.is_reported_match(data, mat.start(), mat.end()) {
+                    f(mat.start(), mat.end(), mat.pattern())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `for_each_match`, stopping at the first match `f` returns true for
+    fn find_until(&self, data: &[u8], mut f: impl FnMut(usize, usize, PatternID) -> bool) {
+        let mut report = |mat: Match| {
+            self.is_reported_match(data, mat.start(), mat.end())
+                && f(mat.start(), mat.end(), mat.pattern())
+        };
+        if self.overlapping {
+            self.find_overlapping_iter(data).any(&mut report);
+        } else {
+            self.find_iter(data).any(&mut report);
+        }
+    }
+
+    fn match_to_sink_impl(
+        &self,
+        data: &[u8],
+        sink_spec: &SinkSpec,
+        scan_id: Option<String>,
+        file: Option<&str>,
+        provenance: bool,
+    ) -> Result<SinkSummary> {
+        let scan_id = scan_id.unwrap_or_else(sink::new_scan_id);
+        let provenance = provenance.then(|| Provenance::new(&scan_id, self.fingerprint(), file));
+        let mut sink = sink_spec.open(&scan_id, file, provenance, self.output_schema)?;
+        let mut matches_written = 0;
+
+        if self.batched() {
+            // Post-processing and thresholds need every match at once, so matches are resolved
+            // before writing
+            let mut matches = Vec::new();
+            self.for_each_match(data, |start, end, pattern_idx| {
+                matches.push((start, end, pattern_idx))
+            });
+            for (start, end, pattern_idx) in self.postprocess_matches(matches) {
+                sink.write(
+                    start,
+                    end,
+                    self.pattern(pattern_idx),
+                    self.pattern_tags(pattern_idx),
+                )?;
+                matches_written += 1;
+            }
+        } else {
+            self.try_for_each_match(data, |start, end, pattern_idx| {
+                sink.write(
+                    start,
+                    end,
+                    self.pattern(pattern_idx),
+                    self.pattern_tags(pattern_idx),
+                )?;
+                matches_written += 1;
+                Ok(())
+            })?;
+        }
+
+        sink.finish()?;
+
+        Ok(SinkSummary {
+            sink: sink_spec.target(),
+            format: sink_spec.format_name(),
+            scan_id,
+            fingerprint: self.fingerprint().to_string(),
+            matches_written,
+            bytes_scanned: data.len(),
+        })
+    }
+
+    fn match_density_impl(
+        &self,
+        data: &[u8],
+        min_matches: usize,
+        window: usize,
+        unit: WindowUnit,
+        counted: Option<&[bool]>,
+        evidence: bool,
+    ) -> Vec<DensityRegion> {
+        let mut tracker =
+            DensityTracker::new(data, min_matches.max(1), window.max(1), unit, evidence);
+
+        self.for_each_match(data, |start, end, pattern_idx| {
+            if counted.is_none_or(|mask| mask[pattern_idx.as_usize()]) {
+                tracker.push(start, end, pattern_idx);
+            }
+        });
+
+        tracker.finish()
+    }
+
+    fn match_file_impl(
+        &self,
+        path: &str,
+        delimiter: &[u8],
+    ) -> Result<Vec<(usize, usize, usize, PatternID)>> {
+        let f = File::open(Path::new(path))?;
+        let mut reader = BufReader::new(f);
+        let mut buffer = Vec::new();
+        let mut matches = Vec::new();
+        let mut line_number = 0;
+
+        while read_record(&mut reader, delimiter, &mut buffer)? > 0 {
+            line_number += 1;
+            self.match_record(&buffer, line_number, &mut matches)?;
+            buffer.clear();
+        }
+
+        Ok(self.apply_min_counts(matches, |m| m.3))
+    }
+
+    /// Match one line or record, its matches post-processed on their own and appended to
+    /// `matches` with the line number and offsets relative to the record
+    fn match_record(
+        &self,
+        record: &[u8],
+        line_number: usize,
+        matches: &mut Vec<(usize, usize, usize, PatternID)>,
+    ) -> Result<()> {
+        // Legacy encodings decode every byte, only UTF-8 lines can be invalid
+        let line = if self.encoding == Encoding::Utf8 {
+            self.invalid_utf8.apply(record)?
+        } else {
+            Some(Cow::Borrowed(record))
+        };
+        let Some(line) = line else {
+            return Ok(());
+        };
+
+        // Lines without an anchor of the prefilter are not searched at all
+        if let Some(filter) = &self.line_filter {
+            if !filter.accepts(&line) {
+                return Ok(());
+            }
+        }
+
+        let mut line_matches = Vec::new();
+        if self.overlapping {
+            for mat in self.find_overlapping_iter(&line) {
+                let pattern_idx = mat.pattern();
+                let start_idx = mat.start();
+                let end_idx = mat.end();
+
+                // Check word boundaries and exclusion zones
+                if self.is_reported_match(&line, start_idx, end_idx) {
+                    line_matches.push((start_idx, end_idx, pattern_idx));
+                }
+            }
+        } else {
+            for mat in self.find_iter(&line) {
+                let pattern_idx = mat.pattern();
+                let start_idx = mat.start();
+                let end_idx = mat.end();
+
+                // Check word boundaries and exclusion zones
+                if self.is_reported_match(&line, start_idx, end_idx) {
+                    line_matches.push((start_idx, end_idx, pattern_idx));
+                }
+            }
+        }
+
+        // Post-processing applies to the matches of each line independently
+        if let Some(postprocess) = self.postprocess {
+            line_matches = postprocess.apply(line_matches, &self.priorities, self.prefer_longer);
+        }
+
+        for (start_idx, end_idx, pattern_idx) in line_matches {
+            matches.push((line_number, start_idx, end_idx, pattern_idx));
+        }
+        Ok(())
+    }
+
+    /// Match a memory-mapped file line by line, blocks of whole lines of about `chunk_size`
+    /// bytes searched in parallel, each numbering its lines from 1 until the line counts of the
+    /// blocks before it are known
+    fn match_file_parallel_impl(
+        &self,
+        path: &str,
+        chunk_size: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<(usize, usize, usize, PatternID)>> {
+        // A pool of its own, the global pool can only be configured once per process
+        let pool = match n_threads {
+            Some(threads) => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(io::Error::other)?,
+            ),
+            None => None,
+        };
+
+        let mmap = self.map_file(path)?;
+        let data: &[u8] = &mmap;
+
+        // Blocks end right after a newline, so no line is split between two of them
+        let mut blocks = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let end = cmp::min(offset + chunk_size.max(1), data.len());
+            let end = match memchr::memchr(b'\n', &data[end - 1..]) {
+                Some(pos) => end + pos,
+                None => data.len(),
+            };
+            blocks.push((offset, end));
+            offset = end;
+        }
+
+        let scan = ScanContext::current();
+        let stats = StatsHandle::current();
+        let scan_blocks = || {
+            blocks
+                .par_iter()
+                .map(|&(start, end)| {
+                    let mut chunk_span = scan.chunk(start, end);
+                    stats.chunk();
+                    let mut lines = 0;
+                    let mut matches = Vec::new();
+                    for line in data[start..end].split_inclusive(|&b| b == b'\n') {
+                        lines += 1;
+                        self.match_record(line, lines, &mut matches)?;
+                    }
+                    chunk_span.matches(matches.len());
+                    Ok((lines, matches))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        let results = match &pool {
+            Some(pool) => pool.install(scan_blocks),
+            None => scan_blocks(),
+        }?;
+
+        // Blocks come back in file order, shift their line numbers past the lines before them
+        let mut matches = Vec::new();
+        let mut lines_before = 0;
+        for (lines, block_matches) in results {
+            matches.extend(
+                block_matches
+                    .into_iter()
+                    .map(|(line, start, end, pattern_idx)| {
+                        (lines_before + line, start, end, pattern_idx)
+                    }),
+            );
+            lines_before += lines;
+        }
+
+        Ok(self.apply_min_counts(matches, |m| m.3))
+    }
+
+    fn match_file_memmap_impl(
+        &self,
+        path: &str,
+        chunk_size: usize,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let mmap = self.map_file(path)?;
+        Ok(self.match_chunks(&mmap, chunk_size))
+    }
+
+    /// Match `data` in chunks of `chunk_size` bytes, one after the other, each resuming where
+    /// the search of the chunk before it stopped, not post-processed
+    fn match_chunks(&self, data: &[u8], chunk_size: usize) -> Vec<(usize, usize, PatternID)> {
+        if let Cow::Owned(matcher) = self.detected(data) {
+            return matcher.match_chunks(data, chunk_size);
+        }
+        let mut matches = Vec::new();
+        let mut scanner = ChunkedScanner::new(self, 0);
+        let scan = ScanContext::current();
+        let stats = StatsHandle::current();
+        for (start, owned_end, window_end) in self.owned_chunks(data.len(), chunk_size) {
+            let mut chunk_span = scan.chunk(start, window_end);
+            stats.chunk();
+            let found_before = matches.len();
+            scanner.scan(data, 0, window_end, owned_end, &mut matches);
+            chunk_span.matches(matches.len() - found_before);
+        }
+        matches
+    }
+
+    /// Chunks of `chunk_size` bytes of `len` bytes of data, as (start, owned_end, window_end)
+    /// ranges: a chunk owns the matches starting in `start..owned_end` and is searched up to
+    /// `window_end`, far enough past its owned range for the longest match, so every match has
+    /// exactly one owner and no duplicates need to be dropped
+    /// The last chunk is the first whose window reaches the end of the data
+    fn owned_chunks(&self, len: usize, chunk_size: usize) -> Vec<(usize, usize, usize)> {
+        let overlap = self.max_pattern_len.saturating_sub(1);
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < len {
+            let window_end = cmp::min(start + chunk_size + overlap, len);
+            let owned_end = if window_end >= len {
+                len
+            } else {
+                start + chunk_size
+            };
+            chunks.push((start, owned_end, window_end));
+            start = owned_end;
+        }
+        chunks
+    }
+
+    fn match_file_memmap_parallel_impl(
+        &self,
+        path: &str,
+        chunk_size: usize,
+        n_threads: Option<usize>,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        // Configure thread pool if specified
+        if let Some(threads) = n_threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+                .unwrap_or(());
+        }
+
+        let mmap = self.map_file(path)?;
+        Ok(self.match_chunks_parallel(&mmap, chunk_size))
+    }
+
+    /// Match `data` in chunks of `chunk_size` bytes on the rayon thread pool, not
+    /// post-processed, finding the same matches in the same order as `match_file_memmap`
+    pub(crate) fn match_chunks_parallel(
+        &self,
+        data: &[u8],
+        chunk_size: usize,
+    ) -> Vec<(usize, usize, PatternID)> {
+        if let Cow::Owned(matcher) = self.detected(data) {
+            return matcher.match_chunks_parallel(data, chunk_size);
+        }
+        let chunks = self.owned_chunks(data.len(), chunk_size);
+
+        // Every chunk is searched from its start, not knowing where the search of the chunk
+        // before it stops
+        let scan = ScanContext::current();
+        let stats = StatsHandle::current();
+        let results: Vec<_> = chunks
+            .par_iter()
+            .map(|&(start, owned_end, window_end)| {
+                let mut chunk_span = scan.chunk(start, window_end);
+                stats.chunk();
+                let mut matches = Vec::new();
+                let mut scanner = ChunkedScanner::new(self, start);
+                let first = scanner.scan(data, 0, window_end, owned_end, &mut matches);
+                chunk_span.matches(matches.len());
+                (matches, first, scanner)
+            })
+            .collect();
+
+        // A match of one chunk running into the next hides the matches of the next chunk
+        // starting under it, that chunk is then searched again from where the match ends
+        let mut all_matches = Vec::with_capacity(results.iter().map(|r| r.0.len()).sum());
+        let mut scanner = ChunkedScanner::new(self, 0);
+        for ((_, owned_end, window_end), (mut matches, first, next)) in
+            chunks.into_iter().zip(results)
+        {
+            if scanner.agrees(first) {
+                scanner.join(&next);
+            } else {
+                matches.clear();
+                scanner.scan(data, 0, window_end, owned_end, &mut matches);
+            }
+            all_matches.append(&mut matches);
+        }
+        all_matches
+    }
+
+    fn match_file_stream_impl(
+        &self,
+        path: &str,
+        buffer_size: usize,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let file = File::open(path)?;
+        let mut throttle = max_bytes_per_sec.map(Throttle::new);
+        let stats = StatsHandle::current();
+        self.match_chunked_reader(file, buffer_size, |read| {
+            stats.chunk();
+            if let Some(throttle) = &mut throttle {
+                throttle.consume(read);
+            }
+        })
+    }
+
+    /// Match anything implementing Read in chunks of `buffer_size` bytes, not post-processed
+    /// Only the bytes the next chunk needs are kept between reads, see `ChunkedScanner`
+    /// `on_read` is called with the size of every chunk read
+    fn match_chunked_reader(
+        &self,
+        reader: impl Read,
+        buffer_size: usize,
+        on_read: impl FnMut(usize),
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let (matcher, reader) = self.detected_reader(reader)?;
+        matcher.scan_chunked_reader(reader, buffer_size, on_read)
+    }
+
+    /// `match_chunked_reader` once the encoding of the input is known
+    fn scan_chunked_reader(
+        &self,
+        mut reader: impl Read,
+        buffer_size: usize,
+        mut on_read: impl FnMut(usize),
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        let mut matches = Vec::new();
+        let mut buffer = vec![0; buffer_size.max(1)];
+        let mut window: Vec<u8> = Vec::new();
+        let mut window_offset = 0;
+        let mut scanner = ChunkedScanner::new(self, 0);
+
+        loop {
+            let read = reader.read(&mut buffer)?;
+            window.extend_from_slice(&buffer[..read]);
+            let eof = read == 0;
+            if !eof {
+                on_read(read);
+            }
+
+            let end = window_offset + window.len();
+            let owned_end = scanner.settled(end, eof);
+            scanner.scan(&window, window_offset, end, owned_end, &mut matches);
+            if eof {
+                return Ok(matches);
+            }
+
+            let drop = scanner.needed_from() - window_offset;
+            window.drain(..drop);
+            window_offset += drop;
+        }
+    }
+
+    fn match_stream_impl(
+        &self,
+        data: &[u8],
+        buffer_size: usize,
+    ) -> Result<Vec<(usize, usize, PatternID)>> {
+        if let Cow::Owned(matcher) = self.detected(data) {
+            return matcher.match_stream_impl(data, buffer_size);
+        }
+        let mut matches = Vec::new();
+        let mut scanner = ChunkedScanner::new(self, 0);
+
+        // The whole data is at hand, each buffer is only searched as far as its matches need
+        let stats = StatsHandle::current();
+        let mut offset = 0;
+        for chunk in data.chunks(buffer_size.max(1)) {
+            stats.chunk();
+            offset += chunk.len();
+            let search_end = cmp::min(offset + scanner.overlap(), data.len());
+            scanner.scan(data, 0, search_end, offset, &mut matches);
+        }
+
+        Ok(matches)
+    }
+}
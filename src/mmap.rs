@@ -0,0 +1,142 @@
+//! Memory maps of scanned files with kernel access hints and background prefaulting
+
+use memmap2::Mmap;
+use std::fs::File;
+use std::hint;
+use std::io::Result;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// Bytes between two touched addresses when prefaulting, the smallest common page size
+const PAGE_SIZE: usize = 4096;
+/// Pages prefaulted between two checks of whether the scan is done
+const PAGES_PER_CHECK: usize = 256;
+
+/// Access pattern announced to the kernel with madvise
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Madvise {
+    /// No hint, the kernel default
+    #[default]
+    Normal,
+    /// Pages are read in order, the kernel reads ahead aggressively and drops pages once read
+    Sequential,
+    /// The whole file is needed soon, the kernel starts reading it in
+    WillNeed,
+}
+
+impl Madvise {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(Self::Normal),
+            "sequential" => Some(Self::Sequential),
+            "willneed" => Some(Self::WillNeed),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Sequential => "sequential",
+            Self::WillNeed => "willneed",
+        }
+    }
+}
+
+/// How memory-mapped scans map their files
+/// Hints are best effort, they are ignored on platforms or kernels without support
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MmapOptions {
+    pub madvise: Madvise,
+    /// Ask for transparent huge pages, Linux only
+    pub huge_pages: bool,
+    /// Touch every page from a background thread while the scan runs, so page faults on cold
+    /// caches and network filesystems are taken ahead of the scan
+    pub prefault: bool,
+}
+
+/// Memory-mapped file, the prefault thread if any stops when it is dropped
+pub(crate) struct MappedFile {
+    mmap: Arc<Mmap>,
+    done: Arc<AtomicBool>,
+}
+
+impl MappedFile {
+    pub(crate) fn open(path: &str, options: &MmapOptions) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+        advise(&mmap, options);
+
+        let done = Arc::new(AtomicBool::new(false));
+        if options.prefault && !mmap.is_empty() {
+            let mmap = Arc::clone(&mmap);
+            let done = Arc::clone(&done);
+            thread::spawn(move || prefault(&mmap, &done));
+        }
+        Ok(Self { mmap, done })
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.mmap
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Read a file into the page cache ahead of timing-critical scans: ask the kernel to read it
+/// in, then touch every page so it is resident when this returns
+/// Returns the size of the file
+pub fn warmup(path: &str) -> Result<usize> {
+    let file = File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    advise(
+        &mmap,
+        &MmapOptions {
+            madvise: Madvise::WillNeed,
+            ..Default::default()
+        },
+    );
+    prefault(&mmap, &AtomicBool::new(false));
+    Ok(mmap.len())
+}
+
+#[cfg(unix)]
+fn advise(mmap: &Mmap, options: &MmapOptions) {
+    use memmap2::Advice;
+
+    let advice = match options.madvise {
+        Madvise::Normal => None,
+        Madvise::Sequential => Some(Advice::Sequential),
+        Madvise::WillNeed => Some(Advice::WillNeed),
+    };
+    if let Some(advice) = advice {
+        let _ = mmap.advise(advice);
+    }
+    #[cfg(target_os = "linux")]
+    if options.huge_pages {
+        let _ = mmap.advise(Advice::HugePage);
+    }
+}
+
+#[cfg(not(unix))]
+fn advise(_mmap: &Mmap, _options: &MmapOptions) {}
+
+/// Read one byte per page until the end of the file or the end of the scan
+fn prefault(data: &[u8], done: &AtomicBool) {
+    for (page, offset) in (0..data.len()).step_by(PAGE_SIZE).enumerate() {
+        if page % PAGES_PER_CHECK == 0 && done.load(Ordering::Relaxed) {
+            return;
+        }
+        hint::black_box(data[offset]);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::budget::OnMemoryLimit;
+use crate::codecs::DEFAULT_MAX_DECODED_SIZE;
+use crate::engine::Engine;
+use crate::matcher::{ConfigError, Matcher, MatcherOptions};
+use crate::mmap::MmapOptions;
+use crate::postprocess::PostProcess;
+use crate::sink::OutputSchema;
+use aho_corasick::PatternID;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::io;
+use std::sync::Arc;
+
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::new(Status::InvalidArg, e.to_string())
+    }
+}
+
+fn io_error(e: io::Error) -> Error {
+    Error::from_reason(e.to_string())
+}
+
+/// Options of a TextMatcher, the defaults match the Python TextMatcher defaults
+#[napi(object)]
+pub struct TextMatcherOptions {
+    pub overlapping: Option<bool>,
+    pub case_insensitive: Option<bool>,
+    pub whole_word: Option<bool>,
+    /// 'merge', 'longest' or 'priority'
+    pub postprocess: Option<String>,
+    pub priorities: Option<Vec<i64>>,
+    pub prefer_longer: Option<bool>,
+}
+
+/// A match as byte offsets, `end` exclusive
+#[napi(object)]
+pub struct Match {
+    pub start: i64,
+    pub end: i64,
+    pub pattern: String,
+}
+
+/// A match of `matchFile`, with offsets relative to the line
+#[napi(object)]
+pub struct LineMatch {
+    pub line: i64,
+    pub start: i64,
+    pub end: i64,
+    pub pattern: String,
+}
+
+/// Node.js TextMatcher, sharing the core matcher with the Python bindings
+/// The matcher is immutable, so async scans run on the libuv thread pool against the same one
+#[napi]
+pub struct TextMatcher {
+    inner: Arc<Matcher>,
+}
+
+#[napi]
+impl TextMatcher {
+    #[napi(constructor)]
+    pub fn new(patterns: Vec<String>, options: Option<TextMatcherOptions>) -> Result<Self> {
+        let defaults = MatcherOptions::default();
+        let options = options.unwrap_or(TextMatcherOptions {
+            overlapping: None,
+            case_insensitive: None,
+            whole_word: None,
+            postprocess: None,
+            priorities: None,
+            prefer_longer: None,
+        });
+
+        let postprocess = match options.postprocess.as_deref() {
+            Some(name) => Some(PostProcess::parse(name).ok_or_else(|| {
+                Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Unknown postprocess strategy '{}', expected 'merge', 'longest' or 'priority'",
+                        name
+                    ),
+                )
+            })?),
+            None => None,
+        };
+
+        let options = MatcherOptions {
+            overlapping: options.overlapping.unwrap_or(defaults.overlapping),
+            case_insensitive: options
+                .case_insensitive
+                .unwrap_or(defaults.case_insensitive),
+            whole_word: options.whole_word.unwrap_or(defaults.whole_word),
+            word_chars: defaults.word_chars,
+            postprocess,
+            priorities: options.priorities,
+            prefer_longer: options.prefer_longer.unwrap_or(defaults.prefer_longer),
+            hash_key: None,
+            fuzzy: None,
+            ignore_separators: false,
+            min_counts: None,
+            exclude: None,
+            line_filter: None,
+            invalid_utf8: defaults.invalid_utf8,
+            encoding: defaults.encoding,
+            engine: Engine::Auto,
+            max_memory_bytes: None,
+            on_memory_limit: OnMemoryLimit::default(),
+            max_decoded_size: DEFAULT_MAX_DECODED_SIZE,
+            mmap: MmapOptions::default(),
+            events: None,
+            audit_log: None,
+            output_schema: OutputSchema::Legacy,
+            metadata: None,
+            tags: None,
+            progress: None,
+        };
+
+        Ok(Self {
+            inner: Arc::new(Matcher::new(patterns, options)?),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn patterns(&self) -> Vec<String> {
+        self.inner.patterns().to_vec()
+    }
+
+    #[napi(getter)]
+    pub fn overlapping(&self) -> bool {
+        self.inner.overlapping()
+    }
+
+    #[napi(getter)]
+    pub fn case_insensitive(&self) -> bool {
+        self.inner.case_insensitive()
+    }
+
+    #[napi(getter)]
+    pub fn whole_word(&self) -> bool {
+        self.inner.whole_word()
+    }
+
+    #[napi(getter)]
+    pub fn prefer_longer(&self) -> bool {
+        self.inner.prefer_longer()
+    }
+
+    /// Name of the post-processing strategy applied to results, if any
+    #[napi(getter)]
+    pub fn postprocess(&self) -> Option<&'static str> {
+        self.inner.postprocess().map(|p| p.name())
+    }
+
+    /// Match a file line by line
+    #[napi]
+    pub fn match_file(&self, path: String) -> Result<Vec<LineMatch>> {
+        let matches = self.inner.match_file(&path).map_err(io_error)?;
+        Ok(self.resolve_lines(matches))
+    }
+
+    /// Match a memory-mapped file, processed in chunks of `chunkSize` bytes
+    #[napi]
+    pub fn match_file_memmap(&self, path: String, chunk_size: Option<u32>) -> Result<Vec<Match>> {
+        let chunk_size = match chunk_size {
+            Some(size) => size as usize,
+            None => {
+                self.inner
+                    .tune_file(&path, Some(1))
+                    .map_err(io_error)?
+                    .chunk_size
+            }
+        };
+        self.inner
+            .match_file_memmap(&path, chunk_size)
+            .map(|res| resolve(&self.inner, res))
+            .map_err(io_error)
+    }
+
+    /// Match a memory-mapped file, processing chunks in parallel
+    #[napi]
+    pub fn match_file_memmap_parallel(
+        &self,
+        path: String,
+        chunk_size: Option<u32>,
+        n_threads: Option<u32>,
+    ) -> Result<Vec<Match>> {
+        let n_threads = n_threads.map(|n| n as usize);
+        let chunk_size = match chunk_size {
+            Some(size) => size as usize,
+            None => {
+                self.inner
+                    .tune_file(&path, n_threads)
+                    .map_err(io_error)?
+                    .chunk_size
+            }
+        };
+        self.inner
+            .match_file_memmap_parallel(&path, chunk_size, n_threads)
+            .map(|res| resolve(&self.inner, res))
+            .map_err(io_error)
+    }
+
+    /// Match a Buffer or Uint8Array
+    #[napi]
+    pub fn match_bytes(&self, data: &[u8]) -> Vec<Match> {
+        resolve(&self.inner, self.inner.match_bytes(data))
+    }
+
+    /// Match a file read in buffers of `bufferSize` bytes, optionally throttled
+    #[napi]
+    pub fn match_file_stream(
+        &self,
+        path: String,
+        buffer_size: Option<u32>,
+        max_bytes_per_sec: Option<i64>,
+    ) -> Result<Vec<Match>> {
+        let buffer_size = buffer_size.map_or(DEFAULT_CHUNK_SIZE, |size| size as usize);
+        let max_bytes_per_sec = max_bytes_per_sec.map(|rate| rate.max(0) as u64);
+        self.inner
+            .match_file_stream(&path, buffer_size, max_bytes_per_sec)
+            .map(|res| resolve(&self.inner, res))
+            .map_err(io_error)
+    }
+
+    /// Match a Buffer processed in buffers of `bufferSize` bytes
+    #[napi]
+    pub fn match_stream(&self, data: &[u8], buffer_size: Option<u32>) -> Result<Vec<Match>> {
+        let buffer_size = buffer_size.map_or(DEFAULT_CHUNK_SIZE, |size| size as usize);
+        self.inner
+            .match_stream(data, buffer_size)
+            .map(|res| resolve(&self.inner, res))
+            .map_err(io_error)
+    }
+
+    /// Asynchronous `matchFileMemmapParallel`, resolving to the matches sorted by offset
+    #[napi(ts_return_type = "Promise<Array<Match>>")]
+    pub fn match_file_async(&self, path: String) -> AsyncTask<ScanTask> {
+        AsyncTask::new(ScanTask {
+            matcher: Arc::clone(&self.inner),
+            input: ScanInput::File(path),
+        })
+    }
+
+    /// Asynchronous `matchBytes`, the data is copied and scanned on the libuv thread pool
+    #[napi(ts_return_type = "Promise<Array<Match>>")]
+    pub fn match_bytes_async(&self, data: &[u8]) -> AsyncTask<ScanTask> {
+        AsyncTask::new(ScanTask {
+            matcher: Arc::clone(&self.inner),
+            input: ScanInput::Bytes(data.to_vec()),
+        })
+    }
+}
+
+impl TextMatcher {
+    fn resolve_lines(&self, matches: Vec<(usize, usize, usize, PatternID)>) -> Vec<LineMatch> {
+        matches
+            .into_iter()
+            .map(|(line, start, end, pattern_idx)| LineMatch {
+                line: line as i64,
+                start: start as i64,
+                end: end as i64,
+                pattern: self.inner.pattern(pattern_idx).to_string(),
+            })
+            .collect()
+    }
+}
+
+fn resolve(matcher: &Matcher, matches: Vec<(usize, usize, PatternID)>) -> Vec<Match> {
+    matches
+        .into_iter()
+        .map(|(start, end, pattern_idx)| Match {
+            start: start as i64,
+            end: end as i64,
+            pattern: matcher.pattern(pattern_idx).to_string(),
+        })
+        .collect()
+}
+
+pub enum ScanInput {
+    Bytes(Vec<u8>),
+    File(String),
+}
+
+/// Scan run off the main thread by the async methods
+pub struct ScanTask {
+    matcher: Arc<Matcher>,
+    input: ScanInput,
+}
+
+impl Task for ScanTask {
+    type Output = Vec<(usize, usize, PatternID)>;
+    type JsValue = Vec<Match>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        match &self.input {
+            ScanInput::Bytes(data) => Ok(self.matcher.match_bytes(data)),
+            ScanInput::File(path) => {
+                let tuning = self.matcher.tune_file(path, None).map_err(io_error)?;
+                let mut matches = self
+                    .matcher
+                    .match_file_memmap_parallel(path, tuning.chunk_size, None)
+                    .map_err(io_error)?;
+                matches.sort_unstable();
+                Ok(matches)
+            }
+        }
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(resolve(&self.matcher, output))
+    }
+}
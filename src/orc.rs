@@ -0,0 +1,876 @@
+//! ORC scans: the string, varchar, char and binary columns of the stripes of ORC files,
+//! decompressed and decoded natively, and reported with the index of their row and their
+//! field path
+//!
+//! Only the streams of those columns and of the structs, lists, maps and unions holding them
+//! are decoded, the other columns are skipped.
+
+use crate::codecs;
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use flate2::read::DeflateDecoder;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Result};
+
+/// First bytes of ORC files, also closing their postscript
+const MAGIC: &[u8] = b"ORC";
+/// Values a byte of a stream encodes at most, a run of 130 PRESENT bytes holding 1040
+/// booleans in 2 bytes, so counts read from the metadata can be checked against the streams
+const MAX_VALUES_PER_BYTE: usize = 520;
+
+/// Column types, as numbered by the format
+const STRING: u64 = 7;
+const BINARY: u64 = 8;
+const LIST: u64 = 10;
+const MAP: u64 = 11;
+const STRUCT: u64 = 12;
+const UNION: u64 = 13;
+const VARCHAR: u64 = 16;
+const CHAR: u64 = 17;
+
+/// Stream kinds
+const PRESENT: u64 = 0;
+const DATA: u64 = 1;
+const LENGTH: u64 = 2;
+const DICTIONARY_DATA: u64 = 3;
+
+/// Column encodings, the version 1 ones using run-length encoding version 1
+const DIRECT: u64 = 0;
+const DICTIONARY: u64 = 1;
+
+/// Matches of the rows of an ORC file
+pub struct OrcScan {
+    /// Path of every field with matches, like "address.city", "emails[1]" for the items of
+    /// lists or "labels[env]" for the values of maps
+    pub fields: Vec<String>,
+    /// (row, field, start, end, pattern) matches, the row counting from 0 in file order and
+    /// the field indexing `fields`, sorted by row, then column, then offset
+    pub matches: Vec<(u64, usize, usize, usize, PatternID)>,
+}
+
+impl OrcScan {
+    /// Scan the string, varchar, char and binary columns of the ORC file at `path`,
+    /// uncompressed or compressed with zlib, snappy or lz4, stripe by stripe
+    /// Map keys are not scanned
+    pub fn scan(matcher: &Matcher, path: &str) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let orc = OrcFile::open(&mmap, matcher.max_decoded_size())?;
+
+        let mut scan = Self {
+            fields: Vec::new(),
+            matches: Vec::new(),
+        };
+        let mut fields: HashMap<String, usize> = HashMap::new();
+        let mut first_row = 0u64;
+        for stripe in &orc.stripes {
+            let next_row = first_row
+                .checked_add(stripe.rows)
+                .ok_or_else(|| invalid(format!("Invalid ORC row count {}", stripe.rows)))?;
+            let stripe_scan = orc.scan_stripe(matcher, stripe, first_row)?;
+            for (row, path, start, end, pattern_idx) in stripe_scan {
+                let field = match fields.get(&path) {
+                    Some(&field) => field,
+                    None => {
+                        scan.fields.push(path.clone());
+                        fields.insert(path, scan.fields.len() - 1);
+                        scan.fields.len() - 1
+                    }
+                };
+                scan.matches.push((row, field, start, end, pattern_idx));
+            }
+            first_row = next_row;
+        }
+        Ok(scan)
+    }
+}
+
+/// (row, field path, start, end, pattern) match of a stripe
+type StripeMatch = (u64, String, usize, usize, PatternID);
+
+/// Location of a stripe in the file
+struct Stripe {
+    offset: usize,
+    index_length: usize,
+    data_length: usize,
+    footer_length: usize,
+    rows: u64,
+}
+
+/// A column type: its kind, and its children with the field names of structs
+#[derive(Default)]
+struct Type {
+    kind: u64,
+    children: Vec<usize>,
+    names: Vec<String>,
+}
+
+/// Entry of a column: the entry of the parent column it belongs to, the row for the root
+/// column, and its position in the parent list or map
+#[derive(Clone, Copy)]
+struct Owner {
+    parent: usize,
+    pos: usize,
+}
+
+/// Decoded column of a stripe
+#[derive(Default)]
+struct Column<'a> {
+    owners: Vec<Owner>,
+    /// Values of the entries of string and binary columns, None for nulls
+    values: Vec<Option<&'a [u8]>>,
+}
+
+/// Tail of a file: its compression, stripes and types
+struct OrcFile<'a> {
+    data: &'a [u8],
+    compression: u64,
+    block_size: usize,
+    /// Bytes a stream or the footer decompresses to at most
+    max_decoded_size: usize,
+    stripes: Vec<Stripe>,
+    types: Vec<Type>,
+    /// Parent of every column, None for the root
+    parents: Vec<Option<usize>>,
+}
+
+impl<'a> OrcFile<'a> {
+    fn open(data: &'a [u8], max_decoded_size: usize) -> Result<Self> {
+        if !data.starts_with(MAGIC) || data.len() < MAGIC.len() + 1 {
+            return Err(invalid("Not an ORC file".to_string()));
+        }
+        let ps_len = data[data.len() - 1] as usize;
+        let ps_start = (data.len() - 1).checked_sub(ps_len).ok_or_else(truncated)?;
+        let mut footer_len = 0;
+        let mut compression = 0;
+        let mut block_size = 256 * 1024;
+        for field in Proto::new(&data[ps_start..data.len() - 1]) {
+            match field? {
+                (1, Field::Varint(n)) => footer_len = n as usize,
+                (2, Field::Varint(n)) => compression = n,
+                (3, Field::Varint(n)) => block_size = n as usize,
+                _ => {}
+            }
+        }
+        let footer_start = ps_start.checked_sub(footer_len).ok_or_else(truncated)?;
+
+        let mut orc = Self {
+            data,
+            compression,
+            block_size,
+            max_decoded_size,
+            stripes: Vec::new(),
+            types: Vec::new(),
+            parents: Vec::new(),
+        };
+        let footer = orc.decompress(&data[footer_start..ps_start])?;
+        for field in Proto::new(&footer) {
+            match field? {
+                (3, Field::Bytes(stripe)) => orc.stripes.push(parse_stripe(stripe)?),
+                (4, Field::Bytes(column)) => orc.types.push(parse_type(column)?),
+                _ => {}
+            }
+        }
+
+        // Children come after their parent, so the type tree has no cycles
+        orc.parents = vec![None; orc.types.len()];
+        for (column, column_type) in orc.types.iter().enumerate() {
+            for &child in &column_type.children {
+                if child <= column || child >= orc.types.len() || orc.parents[child].is_some() {
+                    return Err(invalid(format!(
+                        "Invalid ORC type tree at column {}",
+                        column
+                    )));
+                }
+                orc.parents[child] = Some(column);
+            }
+        }
+        Ok(orc)
+    }
+
+    /// Decompress a stream or the footer, made of chunks compressed with the codec of the file
+    /// or stored as they are
+    fn decompress(&self, data: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        if self.compression == 0 {
+            return Ok(Cow::Borrowed(data));
+        }
+        let mut out = Vec::new();
+        let mut pos = 0;
+        while pos < data.len() {
+            let header = data.get(pos..pos + 3).ok_or_else(truncated)?;
+            let header =
+                header[0] as usize | (header[1] as usize) << 8 | (header[2] as usize) << 16;
+            pos += 3;
+            let chunk = data.get(pos..pos + (header >> 1)).ok_or_else(truncated)?;
+            pos += header >> 1;
+            // Chunks decompress to a block at most, and the stream to max_decoded_size
+            let max_len = self.block_size.max(chunk.len());
+            let room = self.max_decoded_size.saturating_sub(out.len());
+            if header & 1 == 1 {
+                out.extend_from_slice(chunk);
+            } else {
+                match self.compression {
+                    1 => codecs::decode(
+                        DeflateDecoder::new(chunk).take(max_len as u64),
+                        &mut out,
+                        self.max_decoded_size,
+                        "ORC zlib",
+                    )?,
+                    2 => out.extend(codecs::snappy(chunk, max_len.min(room))?),
+                    4 => out.extend(codecs::lz4(chunk, max_len.min(room))?),
+                    other => {
+                        let name = match other {
+                            3 => "lzo",
+                            5 => "zstd",
+                            6 => "brotli",
+                            _ => "unknown",
+                        };
+                        return Err(invalid(format!(
+                            "Unsupported ORC compression '{}', expected none, zlib, snappy or lz4",
+                            name
+                        )));
+                    }
+                }
+            }
+            if out.len() > self.max_decoded_size {
+                return Err(codecs::too_large("ORC stream", self.max_decoded_size));
+            }
+        }
+        Ok(Cow::Owned(out))
+    }
+
+    /// `length` bytes of the file from `offset`
+    fn slice(&self, offset: usize, length: usize) -> Result<&'a [u8]> {
+        offset
+            .checked_add(length)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or_else(truncated)
+    }
+
+    /// Whether the column or one of its descendants holds strings
+    fn holds_strings(&self, column: usize) -> bool {
+        let column_type = &self.types[column];
+        is_string(column_type.kind)
+            || column_type
+                .children
+                .iter()
+                .any(|&child| self.holds_strings(child))
+    }
+
+    /// Scan the string columns of a stripe
+    /// Returns (row, field path, start, end, pattern) matches sorted by row, then column,
+    /// then offset
+    fn scan_stripe(
+        &self,
+        matcher: &Matcher,
+        stripe: &Stripe,
+        first_row: u64,
+    ) -> Result<Vec<StripeMatch>> {
+        if self.types.is_empty() || stripe.rows == 0 || !self.holds_strings(0) {
+            return Ok(Vec::new());
+        }
+        let footer_start = stripe
+            .offset
+            .checked_add(stripe.index_length)
+            .and_then(|offset| offset.checked_add(stripe.data_length))
+            .ok_or_else(truncated)?;
+        let footer = self.decompress(self.slice(footer_start, stripe.footer_length)?)?;
+
+        // Streams are stored one after another in the order the footer lists them
+        let mut streams: HashMap<(usize, u64), &[u8]> = HashMap::new();
+        let mut encodings: Vec<(u64, usize)> = Vec::new();
+        let mut offset = stripe.offset;
+        for field in Proto::new(&footer) {
+            match field? {
+                (1, Field::Bytes(stream)) => {
+                    let (mut kind, mut column, mut length) = (0, 0, 0);
+                    for field in Proto::new(stream) {
+                        match field? {
+                            (1, Field::Varint(n)) => kind = n,
+                            (2, Field::Varint(n)) => column = n as usize,
+                            (3, Field::Varint(n)) => length = n as usize,
+                            _ => {}
+                        }
+                    }
+                    let bytes = self.slice(offset, length)?;
+                    streams.insert((column, kind), bytes);
+                    offset += bytes.len();
+                }
+                (2, Field::Bytes(encoding)) => {
+                    let (mut kind, mut dictionary_size) = (DIRECT, 0);
+                    for field in Proto::new(encoding) {
+                        match field? {
+                            (1, Field::Varint(n)) => kind = n,
+                            (2, Field::Varint(n)) => dictionary_size = n as usize,
+                            _ => {}
+                        }
+                    }
+                    encodings.push((kind, dictionary_size));
+                }
+                _ => {}
+            }
+        }
+
+        // Decompressed streams are kept until the values borrowing them are scanned
+        let mut buffers: Vec<Cow<'_, [u8]>> = Vec::new();
+        let mut stream_ids: HashMap<(usize, u64), usize> = HashMap::new();
+        for (&key, &bytes) in &streams {
+            if key.0 < self.types.len() && self.holds_strings(key.0) {
+                stream_ids.insert(key, buffers.len());
+                buffers.push(self.decompress(bytes)?);
+            }
+        }
+        let stream = |column: usize, kind: u64| -> &[u8] {
+            stream_ids
+                .get(&(column, kind))
+                .map_or(&[], |&id| buffers[id].as_ref())
+        };
+
+        // Every entry of a column is encoded in the streams of the column or of its
+        // descendants, so no column has more entries than the streams can encode
+        let max_entries = buffers
+            .iter()
+            .map(|buffer| buffer.len())
+            .sum::<usize>()
+            .saturating_mul(MAX_VALUES_PER_BYTE);
+        let rows = usize::try_from(stripe.rows)
+            .ok()
+            .filter(|&rows| rows <= max_entries)
+            .ok_or_else(|| invalid(format!("Invalid ORC row count {}", stripe.rows)))?;
+
+        let mut columns: Vec<Column<'_>> =
+            (0..self.types.len()).map(|_| Column::default()).collect();
+        columns[0].owners = (0..rows)
+            .map(|row| Owner {
+                parent: row,
+                pos: 0,
+            })
+            .collect();
+        for column in 0..self.types.len() {
+            if !self.holds_strings(column) {
+                continue;
+            }
+            let owners = std::mem::take(&mut columns[column].owners);
+            let present = match stream_ids.get(&(column, PRESENT)) {
+                Some(_) => booleans(stream(column, PRESENT), owners.len())?,
+                None => vec![true; owners.len()],
+            };
+            let non_null: Vec<usize> = (0..owners.len()).filter(|&entry| present[entry]).collect();
+            let (encoding, dictionary_size) = encodings.get(column).copied().unwrap_or((DIRECT, 0));
+            let v2 = encoding != DIRECT && encoding != DICTIONARY;
+
+            let column_type = &self.types[column];
+            match column_type.kind {
+                STRUCT => {
+                    for &child in &column_type.children {
+                        columns[child].owners = non_null
+                            .iter()
+                            .map(|&entry| Owner {
+                                parent: entry,
+                                pos: 0,
+                            })
+                            .collect();
+                    }
+                }
+                LIST | MAP => {
+                    let lengths = unsigned_rle(stream(column, LENGTH), non_null.len(), v2)?;
+                    lengths
+                        .iter()
+                        .try_fold(0usize, |total, &length| {
+                            total.checked_add(usize::try_from(length).ok()?)
+                        })
+                        .filter(|&total| total <= max_entries)
+                        .ok_or_else(|| {
+                            invalid(format!("Invalid ORC lengths of column {}", column))
+                        })?;
+                    let mut owners = Vec::new();
+                    for (&entry, &length) in non_null.iter().zip(&lengths) {
+                        owners.extend((0..length as usize).map(|pos| Owner { parent: entry, pos }));
+                    }
+                    for &child in &column_type.children {
+                        columns[child].owners = owners.clone();
+                    }
+                }
+                UNION => {
+                    let tags = byte_rle(stream(column, DATA), non_null.len())?;
+                    for (&entry, &tag) in non_null.iter().zip(&tags) {
+                        let child = *column_type
+                            .children
+                            .get(tag as usize)
+                            .ok_or_else(|| invalid(format!("Invalid ORC union tag {}", tag)))?;
+                        columns[child].owners.push(Owner {
+                            parent: entry,
+                            pos: 0,
+                        });
+                    }
+                }
+                kind if is_string(kind) => {
+                    let values: Vec<&[u8]> = if encoding == DICTIONARY || encoding == 3 {
+                        let lengths = unsigned_rle(stream(column, LENGTH), dictionary_size, v2)?;
+                        let dictionary = split(stream(column, DICTIONARY_DATA), &lengths)?;
+                        unsigned_rle(stream(column, DATA), non_null.len(), v2)?
+                            .into_iter()
+                            .map(|id| {
+                                dictionary.get(id as usize).copied().ok_or_else(|| {
+                                    invalid(format!("Invalid ORC dictionary entry {}", id))
+                                })
+                            })
+                            .collect::<Result<_>>()?
+                    } else {
+                        let lengths = unsigned_rle(stream(column, LENGTH), non_null.len(), v2)?;
+                        split(stream(column, DATA), &lengths)?
+                    };
+                    let mut column_values = vec![None; owners.len()];
+                    for (&entry, value) in non_null.iter().zip(values) {
+                        column_values[entry] = Some(value);
+                    }
+                    columns[column].values = column_values;
+                }
+                _ => {}
+            }
+            columns[column].owners = owners;
+        }
+
+        // Map keys only name the values of their map
+        let mut scanned: Vec<(usize, usize, &[u8])> = Vec::new();
+        for (column, decoded) in columns.iter().enumerate() {
+            let is_key = self.parents[column].is_some_and(|parent| {
+                self.types[parent].kind == MAP && self.types[parent].children[0] == column
+            });
+            if is_key {
+                continue;
+            }
+            for (entry, value) in decoded.values.iter().enumerate() {
+                if let Some(value) = value {
+                    scanned.push((column, entry, value));
+                }
+            }
+        }
+        let values: Vec<&[u8]> = scanned.iter().map(|&(_, _, value)| value).collect();
+        let mut matches = Vec::new();
+        for (value, start, end, pattern_idx) in matcher.match_values(&values) {
+            let (column, entry, _) = scanned[value];
+            let (row, path) = self.locate(&columns, column, entry);
+            matches.push((
+                first_row + row as u64,
+                column,
+                path,
+                start,
+                end,
+                pattern_idx,
+            ));
+        }
+        matches.sort_by_key(|&(row, column, ..)| (row, column));
+        Ok(matches
+            .into_iter()
+            .map(|(row, _, path, start, end, pattern_idx)| (row, path, start, end, pattern_idx))
+            .collect())
+    }
+
+    /// Row and field path of an entry of a column
+    fn locate(
+        &self,
+        columns: &[Column<'_>],
+        mut column: usize,
+        mut entry: usize,
+    ) -> (usize, String) {
+        let mut parts: Vec<String> = Vec::new();
+        while let Some(parent) = self.parents[column] {
+            let owner = columns[column].owners[entry];
+            let parent_type = &self.types[parent];
+            match parent_type.kind {
+                STRUCT => {
+                    let idx = parent_type
+                        .children
+                        .iter()
+                        .position(|&child| child == column)
+                        .unwrap_or_default();
+                    let name = parent_type.names.get(idx).map_or("", String::as_str);
+                    parts.push(format!(".{}", name));
+                }
+                LIST => parts.push(format!("[{}]", owner.pos)),
+                MAP => {
+                    let key = parent_type.children[0];
+                    match columns[key].values.get(entry) {
+                        Some(Some(key)) => {
+                            parts.push(format!("[{}]", String::from_utf8_lossy(key)))
+                        }
+                        _ => parts.push(format!("[{}]", owner.pos)),
+                    }
+                }
+                _ => {}
+            }
+            column = parent;
+            entry = owner.parent;
+        }
+        let row = columns[0].owners[entry].parent;
+        let path: String = parts.iter().rev().map(String::as_str).collect();
+        let path = path.strip_prefix('.').unwrap_or(&path).to_string();
+        (row, path)
+    }
+}
+
+fn is_string(kind: u64) -> bool {
+    matches!(kind, STRING | BINARY | VARCHAR | CHAR)
+}
+
+fn parse_stripe(data: &[u8]) -> Result<Stripe> {
+    let mut stripe = Stripe {
+        offset: 0,
+        index_length: 0,
+        data_length: 0,
+        footer_length: 0,
+        rows: 0,
+    };
+    for field in Proto::new(data) {
+        match field? {
+            (1, Field::Varint(n)) => stripe.offset = n as usize,
+            (2, Field::Varint(n)) => stripe.index_length = n as usize,
+            (3, Field::Varint(n)) => stripe.data_length = n as usize,
+            (4, Field::Varint(n)) => stripe.footer_length = n as usize,
+            (5, Field::Varint(n)) => stripe.rows = n,
+            _ => {}
+        }
+    }
+    Ok(stripe)
+}
+
+fn parse_type(data: &[u8]) -> Result<Type> {
+    let mut column_type = Type::default();
+    for field in Proto::new(data) {
+        match field? {
+            (1, Field::Varint(n)) => column_type.kind = n,
+            // Subtypes are packed, or repeated one by one by some writers
+            (2, Field::Varint(n)) => column_type.children.push(n as usize),
+            (2, Field::Bytes(packed)) => {
+                let mut pos = 0;
+                while pos < packed.len() {
+                    column_type
+                        .children
+                        .push(varint(packed, &mut pos)? as usize);
+                }
+            }
+            (3, Field::Bytes(name)) => column_type
+                .names
+                .push(String::from_utf8_lossy(name).into_owned()),
+            _ => {}
+        }
+    }
+    Ok(column_type)
+}
+
+/// Field of a protobuf message
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Reader of the fields of a protobuf message
+struct Proto<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Proto<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn field(&mut self) -> Result<(u32, Field<'a>)> {
+        let key = varint(self.buf, &mut self.pos)?;
+        let number = (key >> 3) as u32;
+        let field = match key & 7 {
+            0 => Field::Varint(varint(self.buf, &mut self.pos)?),
+            1 | 5 => {
+                self.pos += if key & 7 == 1 { 8 } else { 4 };
+                Field::Fixed
+            }
+            2 => {
+                let len = varint(self.buf, &mut self.pos)? as usize;
+                let bytes = self
+                    .buf
+                    .get(self.pos..self.pos.saturating_add(len))
+                    .ok_or_else(truncated)?;
+                self.pos += len;
+                Field::Bytes(bytes)
+            }
+            wire => return Err(invalid(format!("Invalid ORC metadata wire type {}", wire))),
+        };
+        Ok((number, field))
+    }
+}
+
+impl<'a> Iterator for Proto<'a> {
+    type Item = Result<(u32, Field<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.buf.len() {
+            return None;
+        }
+        let field = self.field();
+        if field.is_err() {
+            self.pos = self.buf.len();
+        }
+        Some(field)
+    }
+}
+
+/// Unsigned base-128 varint
+fn varint(buf: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..70).step_by(7) {
+        let byte = *buf.get(*pos).ok_or_else(truncated)?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("Invalid ORC varint".to_string()))
+}
+
+/// Split the bytes of a stream into values of the given lengths
+fn split<'a>(data: &'a [u8], lengths: &[u64]) -> Result<Vec<&'a [u8]>> {
+    let mut pos = 0usize;
+    lengths
+        .iter()
+        .map(|&len| {
+            let value = data
+                .get(pos..pos.saturating_add(len as usize))
+                .ok_or_else(truncated)?;
+            pos += len as usize;
+            Ok(value)
+        })
+        .collect()
+}
+
+/// Decode `count` bytes of a byte run-length encoded stream
+fn byte_rle(data: &[u8], count: usize) -> Result<Vec<u8>> {
+    check_count(data, count)?;
+    let mut out = Vec::with_capacity(count.min(data.len()));
+    let mut pos = 0;
+    while out.len() < count {
+        let header = *data.get(pos).ok_or_else(truncated)? as i8;
+        pos += 1;
+        if header >= 0 {
+            let value = *data.get(pos).ok_or_else(truncated)?;
+            pos += 1;
+            out.extend(std::iter::repeat_n(value, header as usize + 3));
+        } else {
+            let len = header.unsigned_abs() as usize;
+            out.extend_from_slice(data.get(pos..pos + len).ok_or_else(truncated)?);
+            pos += len;
+        }
+    }
+    out.truncate(count);
+    Ok(out)
+}
+
+/// Decode `count` booleans of a PRESENT stream, bits of byte run-length encoded bytes
+fn booleans(data: &[u8], count: usize) -> Result<Vec<bool>> {
+    let bytes = byte_rle(data, count.div_ceil(8))?;
+    Ok((0..count)
+        .map(|idx| bytes[idx / 8] & (0x80 >> (idx % 8)) != 0)
+        .collect())
+}
+
+/// Decode `count` unsigned integers of a run-length encoded stream, version 1 or 2
+fn unsigned_rle(data: &[u8], count: usize, v2: bool) -> Result<Vec<u64>> {
+    check_count(data, count)?;
+    let mut out = Vec::with_capacity(count.min(data.len()));
+    let mut pos = 0;
+    while out.len() < count {
+        if v2 {
+            rle_v2_run(data, &mut pos, &mut out)?;
+        } else {
+            let header = *data.get(pos).ok_or_else(truncated)?;
+            pos += 1;
+            if header < 128 {
+                let delta = *data.get(pos).ok_or_else(truncated)? as i8 as i64;
+                pos += 1;
+                let base = varint(data, &mut pos)?;
+                out.extend(
+                    (0..header as i64 + 3).map(|idx| base.wrapping_add((idx * delta) as u64)),
+                );
+            } else {
+                for _ in 0..256 - header as usize {
+                    out.push(varint(data, &mut pos)?);
+                }
+            }
+        }
+    }
+    out.truncate(count);
+    Ok(out)
+}
+
+/// Fail unless the stream is long enough to encode `count` values
+fn check_count(data: &[u8], count: usize) -> Result<()> {
+    if count > data.len().saturating_mul(MAX_VALUES_PER_BYTE) {
+        return Err(invalid(format!(
+            "ORC stream of {} bytes too short for {} values",
+            data.len(),
+            count
+        )));
+    }
+    Ok(())
+}
+
+/// Decode one run of version 2 run-length encoding
+fn rle_v2_run(data: &[u8], pos: &mut usize, out: &mut Vec<u64>) -> Result<()> {
+    let mut byte = || -> Result<u8> {
+        let value = *data.get(*pos).ok_or_else(truncated)?;
+        *pos += 1;
+        Ok(value)
+    };
+    let header = byte()?;
+    match header >> 6 {
+        // Short repeat: a value of 1 to 8 bytes repeated 3 to 10 times
+        0 => {
+            let width = ((header >> 3) & 7) as usize + 1;
+            let mut value = 0u64;
+            for _ in 0..width {
+                value = value << 8 | byte()? as u64;
+            }
+            out.extend(std::iter::repeat_n(value, (header & 7) as usize + 3));
+        }
+        // Direct: bit-packed values
+        1 => {
+            let width = decode_width((header >> 1) & 0x1F);
+            let len = ((header as usize & 1) << 8 | byte()? as usize) + 1;
+            out.extend(unpack(data, pos, len, width)?);
+        }
+        // Patched base: bit-packed offsets from a base, their outliers patched with high bits
+        2 => {
+            let width = decode_width((header >> 1) & 0x1F);
+            let len = ((header as usize & 1) << 8 | byte()? as usize) + 1;
+            let third = byte()?;
+            let base_width = ((third >> 5) & 7) as usize + 1;
+            let patch_width = decode_width(third & 0x1F);
+            let fourth = byte()?;
+            let gap_width = ((fourth >> 5) & 7) as usize + 1;
+            let patches = (fourth & 0x1F) as usize;
+            if gap_width + patch_width > 64 {
+                return Err(invalid(format!("Invalid ORC patch width {}", patch_width)));
+            }
+            let mut base = 0u64;
+            for _ in 0..base_width {
+                base = base << 8 | byte()? as u64;
+            }
+            // The most significant bit of the base is its sign
+            let sign = 1u64 << (base_width * 8 - 1);
+            let base = if base & sign != 0 {
+                -((base & !sign) as i64)
+            } else {
+                base as i64
+            };
+            let mut values = unpack(data, pos, len, width)?;
+            let entries = unpack(data, pos, patches, closest_width(gap_width + patch_width))?;
+            let mut idx = 0;
+            for entry in entries {
+                idx += (entry >> patch_width) as usize;
+                let patch = entry & ((1u64 << patch_width) - 1);
+                if let Some(value) = values.get_mut(idx) {
+                    *value |= patch.checked_shl(width as u32).unwrap_or(0);
+                }
+            }
+            out.extend(
+                values
+                    .into_iter()
+                    .map(|value| base.wrapping_add(value as i64) as u64),
+            );
+        }
+        // Delta: a base and a delta, then bit-packed deltas of the same sign
+        _ => {
+            let code = (header >> 1) & 0x1F;
+            let len = ((header as usize & 1) << 8 | byte()? as usize) + 1;
+            let base = varint(data, pos)?;
+            let delta = varint(data, pos)?;
+            let delta = (delta >> 1) as i64 ^ -((delta & 1) as i64);
+            out.push(base);
+            if code == 0 {
+                let mut value = base;
+                for _ in 1..len {
+                    value = value.wrapping_add(delta as u64);
+                    out.push(value);
+                }
+            } else if len > 1 {
+                let mut value = base.wrapping_add(delta as u64);
+                out.push(value);
+                for step in unpack(data, pos, len - 2, decode_width(code))? {
+                    value = if delta < 0 {
+                        value.wrapping_sub(step)
+                    } else {
+                        value.wrapping_add(step)
+                    };
+                    out.push(value);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bit width of a 5-bit width code
+fn decode_width(code: u8) -> usize {
+    match code {
+        0..=23 => code as usize + 1,
+        24 => 26,
+        25 => 28,
+        26 => 30,
+        27 => 32,
+        28 => 40,
+        29 => 48,
+        30 => 56,
+        _ => 64,
+    }
+}
+
+/// Smallest encodable bit width holding `width` bits
+fn closest_width(width: usize) -> usize {
+    match width {
+        0 => 1,
+        1..=24 => width,
+        25..=26 => 26,
+        27..=28 => 28,
+        29..=30 => 30,
+        31..=32 => 32,
+        33..=40 => 40,
+        41..=48 => 48,
+        49..=56 => 56,
+        _ => 64,
+    }
+}
+
+/// Read `count` big-endian bit-packed values of `width` bits, padded to a whole byte
+fn unpack(data: &[u8], pos: &mut usize, count: usize, width: usize) -> Result<Vec<u64>> {
+    let bytes = (count * width).div_ceil(8);
+    let packed = data.get(*pos..*pos + bytes).ok_or_else(truncated)?;
+    *pos += bytes;
+    // At least a bit per value, so the count is bounded by the packed bytes
+    let mut values = Vec::with_capacity(count);
+    let mut acc = 0u128;
+    let mut bits = 0;
+    let mut packed = packed.iter();
+    for _ in 0..count {
+        while bits < width {
+            acc = acc << 8 | *packed.next().ok_or_else(truncated)? as u128;
+            bits += 8;
+        }
+        bits -= width;
+        values.push((acc >> bits) as u64 & (u64::MAX >> (64 - width)));
+        acc &= (1u128 << bits) - 1;
+    }
+    Ok(values)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn truncated() -> io::Error {
+    invalid("Truncated ORC file".to_string())
+}
@@ -0,0 +1,264 @@
+//! Differences between the pattern sets of two matchers, e.g. a machine-readable policy diff
+//! between two releases of a rule set
+
+use crate::matcher::Matcher;
+use crate::postprocess::PostProcess;
+use aho_corasick::PatternID;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Value of a matcher option
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum OptionValue {
+    Bool(bool),
+    Text(String),
+    List(Vec<String>),
+    None,
+}
+
+/// Per-pattern setting changed between two matchers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PatternChange {
+    pub pattern: String,
+    /// "priority", "fuzzy" or "min_count"
+    pub field: &'static str,
+    pub old: i64,
+    pub new: i64,
+}
+
+/// Option changed between two matchers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OptionChange {
+    pub option: &'static str,
+    pub old: OptionValue,
+    pub new: OptionValue,
+}
+
+/// Differences from an old matcher to a new one
+/// Patterns are named as in results, by their digests for matchers built with a hash_key, so
+/// the patterns of hashed matchers only compare equal under the same key
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PatternDiff {
+    /// Patterns of the new matcher only, in its order
+    pub added: Vec<String>,
+    /// Patterns of the old matcher only, in its order
+    pub removed: Vec<String>,
+    /// Patterns of both matchers whose priority or fuzzy distance changed, in the old order
+    /// Priorities are only compared between matchers resolving overlaps by priority
+    pub changed: Vec<PatternChange>,
+    /// Options changing the matches, engines and memory-mapping hints left out
+    pub options: Vec<OptionChange>,
+}
+
+impl PatternDiff {
+    pub fn new(old: &Matcher, new: &Matcher) -> Self {
+        let old_names = names(old);
+        let new_names = names(new);
+        let added = new_names
+            .order
+            .iter()
+            .filter(|(name, _)| !old_names.index.contains_key(*name))
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        let priorities = old.postprocess() == Some(PostProcess::Priority)
+            && new.postprocess() == Some(PostProcess::Priority);
+        let mut removed = Vec::new();
+        let mut changed = Vec::new();
+        for &(name, old_idx) in &old_names.order {
+            let Some(&new_idx) = new_names.index.get(name) else {
+                removed.push(name.to_string());
+                continue;
+            };
+            let mut change = |field, old: i64, new: i64| {
+                if old != new {
+                    changed.push(PatternChange {
+                        pattern: name.to_string(),
+                        field,
+                        old,
+                        new,
+                    });
+                }
+            };
+            if priorities {
+                change(
+                    "priority",
+                    old.priorities()[old_idx],
+                    new.priorities()[new_idx],
+                );
+            }
+            change(
+                "fuzzy",
+                old.fuzzy()[old_idx] as i64,
+                new.fuzzy()[new_idx] as i64,
+            );
+            // Min counts of 0 and 1 both report every match
+            change(
+                "min_count",
+                old.min_counts()[old_idx].max(1) as i64,
+                new.min_counts()[new_idx].max(1) as i64,
+            );
+        }
+
+        let options = options(old)
+            .into_iter()
+            .zip(options(new))
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((option, old), (_, new))| OptionChange { option, old, new })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            changed,
+            options,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.options.is_empty()
+    }
+}
+
+/// Distinct pattern names of a matcher in order, with the index of their first occurrence
+struct Names<'a> {
+    order: Vec<(&'a str, usize)>,
+    index: HashMap<&'a str, usize>,
+}
+
+fn names(matcher: &Matcher) -> Names<'_> {
+    let mut order = Vec::new();
+    let mut index = HashMap::new();
+    for idx in 0..matcher.patterns().len() {
+        let name = matcher.pattern(PatternID::must(idx));
+        if !index.contains_key(name) {
+            index.insert(name, idx);
+            order.push((name, idx));
+        }
+    }
+    Names { order, index }
+}
+
+fn options(matcher: &Matcher) -> Vec<(&'static str, OptionValue)> {
+    let text = |value: &str| OptionValue::Text(value.to_string());
+    vec![
+        ("overlapping", OptionValue::Bool(matcher.overlapping())),
+        (
+            "case_insensitive",
+            OptionValue::Bool(matcher.case_insensitive()),
+        ),
+        ("whole_word", OptionValue::Bool(matcher.whole_word())),
+        ("word_chars", OptionValue::Text(matcher.word_chars().spec())),
+        (
+            "postprocess",
+            matcher
+                .postprocess()
+                .map_or(OptionValue::None, |p| text(p.name())),
+        ),
+        ("prefer_longer", OptionValue::Bool(matcher.prefer_longer())),
+        ("hashed", OptionValue::Bool(matcher.hashed())),
+        (
+            "ignore_separators",
+            OptionValue::Bool(matcher.ignore_separators()),
+        ),
+        ("exclude", OptionValue::List(matcher.exclude().to_vec())),
+        (
+            "line_filter",
+            matcher.line_filter().map_or(OptionValue::None, |anchors| {
+                OptionValue::List(anchors.to_vec())
+            }),
+        ),
+        ("invalid_utf8", text(matcher.invalid_utf8().name())),
+        ("encoding", text(matcher.encoding().name())),
+    ]
+}
+
+#[cfg(feature = "python")]
+impl<'py> IntoPyObject<'py> for &OptionValue {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(match self {
+            OptionValue::Bool(value) => value.into_pyobject(py)?.to_owned().into_any(),
+            OptionValue::Text(value) => value.into_pyobject(py)?.into_any(),
+            OptionValue::List(values) => values.into_pyobject(py)?,
+            OptionValue::None => py.None().into_bound(py),
+        })
+    }
+}
+
+/// Per-pattern change as returned to Python: (pattern, field, old, new)
+#[cfg(feature = "python")]
+type PyPatternChange = (String, &'static str, i64, i64);
+
+/// Option change as returned to Python: (option, old, new)
+#[cfg(feature = "python")]
+type PyOptionChange<'py> = (&'static str, Bound<'py, PyAny>, Bound<'py, PyAny>);
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PatternDiff {
+    fn __repr__(&self) -> String {
+        format!(
+            "PatternDiff(added={}, removed={}, changed={}, options={})",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len(),
+            self.options.len()
+        )
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.is_empty()
+    }
+
+    #[getter]
+    fn added(&self) -> Vec<String> {
+        self.added.clone()
+    }
+
+    #[getter]
+    fn removed(&self) -> Vec<String> {
+        self.removed.clone()
+    }
+
+    /// (pattern, field, old, new) tuples
+    #[getter]
+    fn changed(&self) -> Vec<PyPatternChange> {
+        self.changed
+            .iter()
+            .map(|c| (c.pattern.clone(), c.field, c.old, c.new))
+            .collect()
+    }
+
+    /// (option, old, new) tuples
+    #[getter]
+    fn options<'py>(&self, py: Python<'py>) -> PyResult<Vec<PyOptionChange<'py>>> {
+        self.options
+            .iter()
+            .map(|c| {
+                Ok((
+                    c.option,
+                    (&c.old).into_pyobject(py)?,
+                    (&c.new).into_pyobject(py)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Serialize the diff as a JSON object with "added", "removed", "changed" and "options"
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(self).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
@@ -0,0 +1,606 @@
+//! Packet capture scans: the TCP and UDP payloads of pcap and pcapng files, TCP streams
+//! reassembled best effort, reported with the 5-tuple of their flow
+
+use crate::matcher::Matcher;
+use crate::report::SPLIT_FILE_SIZE;
+use aho_corasick::PatternID;
+use flate2::read::MultiGzDecoder;
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::mpsc;
+
+/// Payload bytes kept per flow, the rest being ignored, so one huge transfer cannot exhaust
+/// memory
+const MAX_STREAM: usize = 256 * 1024 * 1024;
+
+/// Out-of-order bytes a TCP stream buffers before the segments they wait for are given up as
+/// lost and the gap is skipped
+const MAX_PENDING: usize = 16 * 1024 * 1024;
+
+/// Length of a packet or block above which the capture is considered corrupt
+const MAX_RECORD: u32 = 64 * 1024 * 1024;
+
+/// Transport protocol of a flow
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transport {
+    Tcp,
+    Udp,
+}
+
+impl Transport {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+        }
+    }
+}
+
+/// One direction of a TCP connection or UDP exchange
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Flow {
+    pub transport: Transport,
+    pub src: IpAddr,
+    pub src_port: u16,
+    pub dst: IpAddr,
+    pub dst_port: u16,
+}
+
+/// Matches of the payloads of a packet capture
+pub struct PcapScan {
+    /// Every flow carrying a payload, in the order of their first payload
+    pub flows: Vec<Flow>,
+    /// (flow, start, end, pattern) matches, the flow indexing `flows` and the offsets being
+    /// those of its reassembled payload, sorted by flow then offset
+    pub matches: Vec<(usize, usize, usize, PatternID)>,
+}
+
+impl PcapScan {
+    /// Scan the TCP and UDP payloads of the pcap or pcapng file at `path`, gzipped or not
+    ///
+    /// TCP streams are reassembled and scanned as soon as they are closed by a FIN or RST, the
+    /// others once the capture is read, in parallel. The bytes of segments never captured are
+    /// skipped, and UDP datagrams scanned one at a time, so no match spans a gap or two
+    /// datagrams. A capture cut short is scanned up to its last complete packet.
+    pub fn scan(matcher: &Matcher, path: &str) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let gzipped = file.fill_buf()?.starts_with(&[0x1F, 0x8B]);
+        let reader: Box<dyn BufRead + Send> = if gzipped {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            Box::new(file)
+        };
+        let mut capture = Capture::open(reader)?;
+
+        // Payloads waiting for the scan are bounded, so a large capture is streamed
+        let (sender, receiver) = mpsc::sync_channel(2 * rayon::current_num_threads());
+        let (flows, mut matches) = std::thread::scope(|scope| {
+            let reader = scope.spawn(move || -> Result<Vec<Flow>> {
+                let mut streams = Streams::default();
+                while let Some((link, packet)) = capture.next_packet()? {
+                    let Some(segment) = parse_packet(link, packet) else {
+                        continue;
+                    };
+                    for payload in streams.add(segment) {
+                        if sender.send(payload).is_err() {
+                            return Ok(streams.flows);
+                        }
+                    }
+                }
+                for payload in streams.finish() {
+                    if sender.send(payload).is_err() {
+                        break;
+                    }
+                }
+                Ok(streams.flows)
+            });
+            let matches: Vec<_> = receiver
+                .into_iter()
+                .par_bridge()
+                .flat_map_iter(|payload| scan_payload(matcher, payload))
+                .collect();
+            (reader.join().expect("pcap reader panicked"), matches)
+        });
+        matches.sort_unstable();
+        Ok(Self {
+            flows: flows?,
+            matches,
+        })
+    }
+}
+
+/// Reassembled payload of a flow
+struct Payload {
+    flow: usize,
+    data: Vec<u8>,
+    /// Ends of the pieces of `data` scanned separately, empty when it is scanned whole
+    breaks: Vec<usize>,
+}
+
+fn scan_payload(matcher: &Matcher, payload: Payload) -> Vec<(usize, usize, usize, PatternID)> {
+    let flow = payload.flow;
+    let mut matches = Vec::new();
+    let mut offset = 0;
+    for end in payload.breaks.into_iter().chain([payload.data.len()]) {
+        let piece = &payload.data[offset..end];
+        let piece_matches = if piece.len() >= SPLIT_FILE_SIZE {
+            matcher.match_bytes_parallel(piece)
+        } else {
+            matcher.match_bytes(piece)
+        };
+        matches.extend(
+            piece_matches
+                .into_iter()
+                .map(|(start, end, pattern)| (flow, offset + start, offset + end, pattern)),
+        );
+        offset = end;
+    }
+    matches
+}
+
+/// Packets of a pcap or pcapng file
+struct Capture<R> {
+    reader: R,
+    /// Whether the file, or its current pcapng section, is big-endian
+    big_endian: bool,
+    /// Link type of the pcap file, or of every interface of the current pcapng section
+    links: Vec<u32>,
+    pcapng: bool,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Capture<R> {
+    fn open(mut reader: R) -> Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic).map_err(|_| not_a_capture())?;
+        let mut capture = Self {
+            reader,
+            big_endian: false,
+            links: Vec::new(),
+            pcapng: false,
+            buf: Vec::new(),
+        };
+        match magic {
+            // Microsecond and nanosecond timestamps
+            [0xD4, 0xC3, 0xB2, 0xA1] | [0x4D, 0x3C, 0xB2, 0xA1] => {}
+            [0xA1, 0xB2, 0xC3, 0xD4] | [0xA1, 0xB2, 0x3C, 0x4D] => capture.big_endian = true,
+            [0x0A, 0x0D, 0x0D, 0x0A] => {
+                capture.pcapng = true;
+                capture.section()?;
+                return Ok(capture);
+            }
+            _ => return Err(not_a_capture()),
+        }
+        if !capture.read(20)? {
+            return Err(not_a_capture());
+        }
+        // The upper bits of the link type hold FCS flags
+        let link = capture.u32_at(16) & 0xFFFF;
+        capture.links.push(link);
+        Ok(capture)
+    }
+
+    /// Next packet and the link type of its interface, None at the end of the capture
+    fn next_packet(&mut self) -> Result<Option<(u32, &[u8])>> {
+        if !self.pcapng {
+            if !self.read(16)? {
+                return Ok(None);
+            }
+            let len = self.u32_at(8);
+            if len > MAX_RECORD {
+                return Err(corrupt());
+            }
+            if !self.read(len as usize)? {
+                return Ok(None);
+            }
+            return Ok(Some((self.links[0], &self.buf)));
+        }
+
+        loop {
+            if !self.read(4)? {
+                return Ok(None);
+            }
+            let kind = self.u32_at(0);
+            if kind == 0x0A0D0D0A {
+                self.section()?;
+                continue;
+            }
+            if !self.read(4)? {
+                return Ok(None);
+            }
+            let len = self.u32_at(0);
+            if !(12..=MAX_RECORD).contains(&len) || !len.is_multiple_of(4) {
+                return Err(corrupt());
+            }
+            // The block body, followed by its repeated length
+            if !self.read(len as usize - 8)? {
+                return Ok(None);
+            }
+            let body_len = self.buf.len() - 4;
+            let (interface, start, captured) = match kind {
+                // Interface description
+                1 if body_len >= 2 => {
+                    let link = u32::from(self.u16_at(0));
+                    self.links.push(link);
+                    continue;
+                }
+                // Enhanced packet
+                6 if body_len >= 20 => (self.u32_at(0), 20, self.u32_at(12)),
+                // Simple packet, of the first interface, its length being the original one
+                3 if body_len >= 4 => (0, 4, self.u32_at(0)),
+                // Obsolete packet block
+                2 if body_len >= 20 => (u32::from(self.u16_at(0)), 20, self.u32_at(12)),
+                _ => continue,
+            };
+            let Some(&link) = self.links.get(interface as usize) else {
+                continue;
+            };
+            let end = body_len.min(start + captured as usize);
+            return Ok(Some((link, &self.buf[start..end])));
+        }
+    }
+
+    /// Start a pcapng section after its block type: its byte order and a new set of interfaces
+    fn section(&mut self) -> Result<()> {
+        if !self.read(8)? {
+            return Err(not_a_capture());
+        }
+        self.big_endian = match self.buf[4..8] {
+            [0x1A, 0x2B, 0x3C, 0x4D] => true,
+            [0x4D, 0x3C, 0x2B, 0x1A] => false,
+            _ => return Err(not_a_capture()),
+        };
+        let len = self.u32_at(0);
+        if !(28..=MAX_RECORD).contains(&len) || !len.is_multiple_of(4) {
+            return Err(corrupt());
+        }
+        self.links.clear();
+        if !self.read(len as usize - 12)? {
+            return Err(corrupt());
+        }
+        Ok(())
+    }
+
+    /// Read `len` bytes into the buffer, false when the capture ends before them
+    fn read(&mut self, len: usize) -> Result<bool> {
+        self.buf.clear();
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut self.buf)?;
+        Ok(self.buf.len() == len)
+    }
+
+    fn u32_at(&self, pos: usize) -> u32 {
+        let bytes = self.buf[pos..pos + 4].try_into().unwrap();
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    fn u16_at(&self, pos: usize) -> u16 {
+        let bytes = self.buf[pos..pos + 2].try_into().unwrap();
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+}
+
+fn not_a_capture() -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, "Not a pcap or pcapng file")
+}
+
+fn corrupt() -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        "Corrupt capture, invalid record length",
+    )
+}
+
+/// TCP segment or UDP datagram of a packet
+struct Segment<'a> {
+    flow: Flow,
+    /// Sequence number of a TCP segment
+    seq: u32,
+    syn: bool,
+    /// Whether a TCP segment closes its direction of the connection, with a FIN or RST
+    close: bool,
+    payload: &'a [u8],
+}
+
+/// TCP or UDP segment of a captured frame, None for other protocols, IP fragments but the
+/// first, and link types that are not understood
+fn parse_packet(link: u32, frame: &[u8]) -> Option<Segment<'_>> {
+    let ip = match link {
+        // BSD loopback, a 4-byte address family
+        0 | 108 => frame.get(4..)?,
+        // Ethernet, possibly VLAN-tagged
+        1 => {
+            let mut ethertype = be16(frame, 12)?;
+            let mut rest = frame.get(14..)?;
+            while matches!(ethertype, 0x8100 | 0x88A8 | 0x9100) {
+                ethertype = be16(rest, 2)?;
+                rest = rest.get(4..)?;
+            }
+            ip_ethertype(ethertype, rest)?
+        }
+        // Raw IP
+        12 | 14 | 101 | 228 | 229 => frame,
+        // Linux cooked captures, v1 and v2
+        113 => ip_ethertype(be16(frame, 14)?, frame.get(16..)?)?,
+        276 => ip_ethertype(be16(frame, 0)?, frame.get(20..)?)?,
+        _ => return None,
+    };
+
+    let (src, dst, protocol, payload) = match ip.first()? >> 4 {
+        4 => {
+            let header = usize::from(ip[0] & 0x0F) * 4;
+            // Fragments after the first have no transport header
+            if be16(ip, 6)? & 0x1FFF != 0 {
+                return None;
+            }
+            let src: [u8; 4] = ip.get(12..16)?.try_into().ok()?;
+            let dst: [u8; 4] = ip.get(16..20)?.try_into().ok()?;
+            // Frames are padded, and offloaded segments captured with a zero length
+            let total = usize::from(be16(ip, 2)?);
+            let end = if total == 0 {
+                ip.len()
+            } else {
+                total.min(ip.len())
+            };
+            (
+                IpAddr::V4(Ipv4Addr::from(src)),
+                IpAddr::V4(Ipv4Addr::from(dst)),
+                ip[9],
+                ip.get(header..end)?,
+            )
+        }
+        6 => {
+            let src: [u8; 16] = ip.get(8..24)?.try_into().ok()?;
+            let dst: [u8; 16] = ip.get(24..40)?.try_into().ok()?;
+            let len = usize::from(be16(ip, 4)?);
+            let end = if len == 0 {
+                ip.len()
+            } else {
+                (40 + len).min(ip.len())
+            };
+            let mut next = ip[6];
+            let mut rest = ip.get(40..end)?;
+            loop {
+                match next {
+                    // Hop-by-hop, routing and destination options
+                    0 | 43 | 60 => {
+                        next = *rest.first()?;
+                        rest = rest.get((usize::from(*rest.get(1)?) + 1) * 8..)?;
+                    }
+                    44 => {
+                        if be16(rest, 2)? & 0xFFF8 != 0 {
+                            return None;
+                        }
+                        next = *rest.first()?;
+                        rest = rest.get(8..)?;
+                    }
+                    _ => break,
+                }
+            }
+            (
+                IpAddr::V6(Ipv6Addr::from(src)),
+                IpAddr::V6(Ipv6Addr::from(dst)),
+                next,
+                rest,
+            )
+        }
+        _ => return None,
+    };
+
+    let flow = |transport| {
+        Some(Flow {
+            transport,
+            src,
+            src_port: be16(payload, 0)?,
+            dst,
+            dst_port: be16(payload, 2)?,
+        })
+    };
+    match protocol {
+        6 => {
+            let header = usize::from(*payload.get(12)? >> 4) * 4;
+            let flags = *payload.get(13)?;
+            Some(Segment {
+                flow: flow(Transport::Tcp)?,
+                seq: be32(payload, 4)?,
+                syn: flags & 0x02 != 0,
+                close: flags & 0x05 != 0,
+                payload: payload.get(header..)?,
+            })
+        }
+        17 => {
+            let len = usize::from(be16(payload, 4)?);
+            let end = if len < 8 {
+                payload.len()
+            } else {
+                len.min(payload.len())
+            };
+            Some(Segment {
+                flow: flow(Transport::Udp)?,
+                seq: 0,
+                syn: false,
+                close: false,
+                payload: payload.get(8..end)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// IP packet of a frame of the given ethertype, None for other protocols
+fn ip_ethertype(ethertype: u16, rest: &[u8]) -> Option<&[u8]> {
+    matches!(ethertype, 0x0800 | 0x86DD).then_some(rest)
+}
+
+fn be16(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn be32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// Flows being reassembled
+#[derive(Default)]
+struct Streams {
+    open: HashMap<Flow, Stream>,
+    /// TCP flows closed by a FIN or RST, whose late retransmissions are ignored until a new SYN
+    closed: HashSet<Flow>,
+    flows: Vec<Flow>,
+}
+
+/// Payload of a flow being reassembled
+#[derive(Default)]
+struct Stream {
+    /// Index of the flow, assigned with its first packet carrying a payload
+    flow: Option<usize>,
+    data: Vec<u8>,
+    /// Ends of the datagrams of a UDP flow, or of the bytes before the gaps of a TCP stream,
+    /// so no match spans two datagrams or a lost segment
+    breaks: Vec<usize>,
+    /// Sequence number of the first byte of a TCP stream
+    base: Option<u32>,
+    /// Offset from `base` of the next byte expected
+    next: u64,
+    /// Segments received ahead of `next`, by offset
+    pending: BTreeMap<u64, Vec<u8>>,
+    pending_len: usize,
+}
+
+impl Streams {
+    /// Add a segment, returning the payloads of the flows it completes
+    fn add(&mut self, segment: Segment<'_>) -> Vec<Payload> {
+        let mut done = Vec::new();
+        let key = segment.flow;
+        let mut seq = segment.seq;
+        if segment.syn {
+            // A new connection reusing the ports of an earlier one
+            self.closed.remove(&key);
+            done.extend(self.open.remove(&key).and_then(Stream::finish));
+            seq = seq.wrapping_add(1);
+            self.open.entry(key).or_default().base = Some(seq);
+        }
+        if self.closed.contains(&key) {
+            return done;
+        }
+
+        let stream = self.open.entry(key).or_default();
+        if !segment.payload.is_empty() && stream.flow.is_none() {
+            stream.flow = Some(self.flows.len());
+            self.flows.push(key);
+        }
+        if key.transport == Transport::Udp {
+            if !segment.payload.is_empty() {
+                stream.append(segment.payload);
+                stream.breaks.push(stream.data.len());
+            }
+            return done;
+        }
+        // Connections already open when the capture started begin at their first segment
+        let base = *stream.base.get_or_insert(seq);
+        let offset = seq.wrapping_sub(base);
+        // Offsets past 2^31 are retransmissions of bytes before the base
+        if !segment.payload.is_empty() && offset < 1 << 31 {
+            stream.insert(u64::from(offset), segment.payload);
+        }
+        if segment.close {
+            self.closed.insert(key);
+            done.extend(self.open.remove(&key).and_then(Stream::finish));
+        }
+        done
+    }
+
+    /// Payloads of the flows still open at the end of the capture, in flow order
+    fn finish(&mut self) -> Vec<Payload> {
+        let mut payloads: Vec<_> = self
+            .open
+            .drain()
+            .filter_map(|(_, stream)| stream.finish())
+            .collect();
+        payloads.sort_unstable_by_key(|payload| payload.flow);
+        payloads
+    }
+}
+
+impl Stream {
+    /// Add the bytes of a TCP segment at `offset` from the stream base
+    fn insert(&mut self, offset: u64, bytes: &[u8]) {
+        let end = offset + bytes.len() as u64;
+        if end <= self.next {
+            return;
+        }
+        if offset > self.next {
+            let pending = self.pending.entry(offset).or_default();
+            if bytes.len() > pending.len() {
+                self.pending_len += bytes.len() - pending.len();
+                *pending = bytes.to_vec();
+            }
+            while self.pending_len > MAX_PENDING {
+                self.skip_gap();
+            }
+            return;
+        }
+        self.append(&bytes[(self.next - offset) as usize..]);
+        self.next = end;
+        self.drain();
+    }
+
+    /// Append the pending segments that have become contiguous
+    fn drain(&mut self) {
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() > self.next {
+                break;
+            }
+            let (offset, bytes) = entry.remove_entry();
+            self.pending_len -= bytes.len();
+            let end = offset + bytes.len() as u64;
+            if end > self.next {
+                self.append(&bytes[(self.next - offset) as usize..]);
+                self.next = end;
+            }
+        }
+    }
+
+    /// Give up on the bytes missing before the first pending segment
+    fn skip_gap(&mut self) {
+        if let Some(&offset) = self.pending.keys().next() {
+            if self.breaks.last() != Some(&self.data.len()) {
+                self.breaks.push(self.data.len());
+            }
+            self.next = offset;
+            self.drain();
+        }
+    }
+
+    fn append(&mut self, bytes: &[u8]) {
+        let len = bytes.len().min(MAX_STREAM - self.data.len());
+        self.data.extend_from_slice(&bytes[..len]);
+    }
+
+    /// Payload of the stream, pending segments appended past their gaps, None when empty
+    fn finish(mut self) -> Option<Payload> {
+        while !self.pending.is_empty() {
+            self.skip_gap();
+        }
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(Payload {
+            flow: self.flow?,
+            data: self.data,
+            breaks: self.breaks,
+        })
+    }
+}
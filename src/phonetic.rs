@@ -0,0 +1,204 @@
+//! Phonetic codes of name tokens, so names spelled differently but pronounced alike match
+
+/// Phonetic algorithm encoding the tokens of a TokenMatcher
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phonetic {
+    /// American Soundex: the first letter and three digits for the consonants after it
+    Soundex,
+    /// Original Metaphone by Lawrence Philips, finer than Soundex on English names
+    Metaphone,
+}
+
+impl Phonetic {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "soundex" => Some(Self::Soundex),
+            "metaphone" => Some(Self::Metaphone),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Soundex => "soundex",
+            Self::Metaphone => "metaphone",
+        }
+    }
+
+    /// Append the code of `token` to `out`, from its ASCII letters only; nothing is appended
+    /// for tokens without any
+    pub fn encode(&self, token: &[u8], out: &mut Vec<u8>) {
+        let letters: Vec<u8> = token
+            .iter()
+            .filter(|b| b.is_ascii_alphabetic())
+            .map(u8::to_ascii_uppercase)
+            .collect();
+        if letters.is_empty() {
+            return;
+        }
+        match self {
+            Self::Soundex => soundex(&letters, out),
+            Self::Metaphone => metaphone(&letters, out),
+        }
+    }
+}
+
+/// Soundex digit of a letter, 0 for vowels and the letters without one
+fn soundex_digit(letter: u8) -> u8 {
+    match letter {
+        b'B' | b'F' | b'P' | b'V' => b'1',
+        b'C' | b'G' | b'J' | b'K' | b'Q' | b'S' | b'X' | b'Z' => b'2',
+        b'D' | b'T' => b'3',
+        b'L' => b'4',
+        b'M' | b'N' => b'5',
+        b'R' => b'6',
+        _ => 0,
+    }
+}
+
+/// Soundex code of uppercase `letters`, which are not empty
+fn soundex(letters: &[u8], out: &mut Vec<u8>) {
+    let start = out.len();
+    out.push(letters[0]);
+    let mut last = soundex_digit(letters[0]);
+    for &letter in &letters[1..] {
+        // H and W do not separate consonants of the same digit, vowels do
+        if letter == b'H' || letter == b'W' {
+            continue;
+        }
+        let digit = soundex_digit(letter);
+        if digit != 0 && digit != last {
+            out.push(digit);
+            if out.len() - start == 4 {
+                return;
+            }
+        }
+        last = digit;
+    }
+    out.resize(start + 4, b'0');
+}
+
+fn is_vowel(letter: u8) -> bool {
+    matches!(letter, b'A' | b'E' | b'I' | b'O' | b'U')
+}
+
+/// Metaphone code of uppercase `letters`, which are not empty, '0' standing for "th"
+fn metaphone(letters: &[u8], out: &mut Vec<u8>) {
+    let at = |i: usize| letters.get(i).copied().unwrap_or(0);
+    let len = letters.len();
+
+    // Initial letter exceptions
+    let mut i = match (letters[0], at(1)) {
+        (b'A', b'E') | (b'G', b'N') | (b'K', b'N') | (b'P', b'N') | (b'W', b'R') => 1,
+        (b'X', _) => {
+            out.push(b'S');
+            1
+        }
+        (b'W', b'H') => {
+            out.push(b'W');
+            2
+        }
+        _ => 0,
+    };
+
+    while i < len {
+        let letter = letters[i];
+        let prev = if i > 0 { letters[i - 1] } else { 0 };
+        let next = at(i + 1);
+        let after = at(i + 2);
+        let mut step = 1;
+
+        // Doubled letters are encoded once, except C
+        if letter == prev && letter != b'C' {
+            i += 1;
+            continue;
+        }
+
+        match letter {
+            b'A' | b'E' | b'I' | b'O' | b'U' => {
+                if i == 0 {
+                    out.push(letter);
+                }
+            }
+            // Silent in a trailing "mb"
+            b'B' => {
+                if !(prev == b'M' && i + 1 == len) {
+                    out.push(b'B');
+                }
+            }
+            b'C' => {
+                if (next == b'I' && after == b'A') || (next == b'H' && prev != b'S') {
+                    out.push(b'X');
+                } else if matches!(next, b'I' | b'E' | b'Y') {
+                    if prev != b'S' {
+                        out.push(b'S');
+                    }
+                } else {
+                    out.push(b'K');
+                }
+            }
+            b'D' => {
+                if next == b'G' && matches!(after, b'E' | b'I' | b'Y') {
+                    out.push(b'J');
+                    step = 2;
+                } else {
+                    out.push(b'T');
+                }
+            }
+            b'G' => {
+                let silent_gh = next == b'H' && i + 2 < len && !is_vowel(after);
+                let silent_gn = next == b'N'
+                    && (i + 2 == len || (after == b'E' && at(i + 3) == b'D' && i + 4 == len));
+                if !silent_gh && !silent_gn {
+                    out.push(if matches!(next, b'I' | b'E' | b'Y') {
+                        b'J'
+                    } else {
+                        b'K'
+                    });
+                }
+            }
+            b'H' => {
+                // Silent after the consonants it combines with, and between a vowel and a
+                // consonant
+                let combined = matches!(prev, b'C' | b'S' | b'P' | b'T' | b'G');
+                let silent = combined || (is_vowel(prev) && !is_vowel(next));
+                if !silent {
+                    out.push(b'H');
+                }
+            }
+            b'K' => {
+                if prev != b'C' {
+                    out.push(b'K');
+                }
+            }
+            b'P' => out.push(if next == b'H' { b'F' } else { b'P' }),
+            b'Q' => out.push(b'K'),
+            b'S' => {
+                if next == b'H' || (next == b'I' && matches!(after, b'O' | b'A')) {
+                    out.push(b'X');
+                } else {
+                    out.push(b'S');
+                }
+            }
+            b'T' => {
+                if next == b'I' && matches!(after, b'O' | b'A') {
+                    out.push(b'X');
+                } else if next == b'H' {
+                    out.push(b'0');
+                } else if !(next == b'C' && after == b'H') {
+                    out.push(b'T');
+                }
+            }
+            b'V' => out.push(b'F'),
+            b'W' | b'Y' => {
+                if is_vowel(next) {
+                    out.push(letter);
+                }
+            }
+            b'X' => out.extend_from_slice(b"KS"),
+            b'Z' => out.push(b'S'),
+            other => out.push(other),
+        }
+        i += step;
+    }
+}
@@ -0,0 +1,202 @@
+//! Phrases matched word by word: one automaton finds the words of every phrase, and a gap
+//! checker chains the hits of consecutive words separated by whitespace only, line breaks
+//! included
+
+use crate::engine::{Backend, Engine};
+use crate::matcher::{is_word_boundary, ConfigError};
+use aho_corasick::PatternID;
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Result;
+use std::sync::Arc;
+
+/// Options of a PhraseMatcher
+#[derive(Clone, Debug)]
+pub struct PhraseOptions {
+    /// Match ASCII letters regardless of case
+    pub case_insensitive: bool,
+    /// Most whitespace bytes between two words of a phrase, any run of whitespace if unset
+    pub max_gap: Option<usize>,
+}
+
+impl Default for PhraseOptions {
+    fn default() -> Self {
+        Self {
+            case_insensitive: true,
+            max_gap: None,
+        }
+    }
+}
+
+/// Matcher of phrases whose words appear in order, separated by runs of ASCII whitespace
+/// such as line wraps; words match whole words only
+/// Matches are (start, end, phrase) tuples of byte offsets from the first byte of the first word
+/// to the last byte of the last word, end exclusive, and the phrase index into `patterns()`,
+/// sorted by start, end then phrase
+pub struct PhraseMatcher {
+    patterns: Arc<Vec<String>>,
+    // Word indices of every phrase, into the patterns of `words`
+    phrases: Vec<Vec<usize>>,
+    // Phrases starting with each word
+    by_first_word: Vec<Vec<PatternID>>,
+    words: Backend,
+    word_count: usize,
+    case_insensitive: bool,
+    max_gap: Option<usize>,
+}
+
+impl PhraseMatcher {
+    /// Build a matcher on `phrases`, phrases without any word are dropped
+    pub fn new(
+        phrases: Vec<String>,
+        options: PhraseOptions,
+    ) -> std::result::Result<Self, ConfigError> {
+        if options.max_gap == Some(0) {
+            return Err(ConfigError::Invalid("max_gap must be positive".to_string()));
+        }
+        let patterns: Vec<String> = phrases
+            .into_iter()
+            .filter(|phrase| phrase.split_ascii_whitespace().next().is_some())
+            .collect();
+        if patterns.is_empty() {
+            return Err(ConfigError::Invalid(
+                "Phrase set cannot be empty".to_string(),
+            ));
+        }
+
+        // Words equal once folded share one pattern of the automaton
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut words = Vec::new();
+        let mut phrases = Vec::with_capacity(patterns.len());
+        for phrase in &patterns {
+            let word_ids: Vec<usize> = phrase
+                .split_ascii_whitespace()
+                .map(|word| {
+                    let key = if options.case_insensitive {
+                        word.to_ascii_lowercase()
+                    } else {
+                        word.to_string()
+                    };
+                    *ids.entry(key).or_insert_with(|| {
+                        words.push(word.to_string());
+                        words.len() - 1
+                    })
+                })
+                .collect();
+            phrases.push(word_ids);
+        }
+
+        let mut by_first_word = vec![Vec::new(); words.len()];
+        for (idx, word_ids) in phrases.iter().enumerate() {
+            by_first_word[word_ids[0]].push(PatternID::must(idx));
+        }
+
+        Ok(Self {
+            words: Backend::build(&words, options.case_insensitive, Engine::Auto)?,
+            word_count: words.len(),
+            patterns: Arc::new(patterns),
+            phrases,
+            by_first_word,
+            case_insensitive: options.case_insensitive,
+            max_gap: options.max_gap,
+        })
+    }
+
+    /// Phrases of the matcher, phrases without any word are dropped when building it
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Number of distinct words searched by the automaton
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn max_gap(&self) -> Option<usize> {
+        self.max_gap
+    }
+
+    /// Heap bytes used by the word automaton and the phrases
+    pub fn memory_usage(&self) -> usize {
+        let phrases: usize = self
+            .phrases
+            .iter()
+            .map(|p| std::mem::size_of_val(p.as_slice()))
+            .sum();
+        self.words.memory_usage()
+            + self.patterns.iter().map(|p| p.capacity()).sum::<usize>()
+            + phrases
+    }
+
+    /// Convert phrase indices to phrases
+    pub fn resolve(&self, matches: Vec<(usize, usize, PatternID)>) -> Vec<(usize, usize, String)> {
+        matches
+            .into_iter()
+            .map(|(start, end, idx)| (start, end, self.patterns[idx.as_usize()].clone()))
+            .collect()
+    }
+
+    /// Match provided bytes
+    pub fn match_bytes(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        // Whole word hits of every word, sorted by start so the word after a gap is looked up
+        // by its offset
+        let mut hits: Vec<(usize, usize, usize)> = self
+            .words
+            .find_overlapping_iter(data)
+            .filter(|m| is_word_boundary(data, m.start(), m.end()))
+            .map(|m| (m.start(), m.end(), m.pattern().as_usize()))
+            .collect();
+        hits.sort_unstable();
+
+        let mut matches = Vec::new();
+        for &(start, end, word) in &hits {
+            for &phrase in &self.by_first_word[word] {
+                if let Some(phrase_end) = self.chain(data, &hits, end, &self.phrases[phrase][1..]) {
+                    matches.push((start, phrase_end, phrase));
+                }
+            }
+        }
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Match a memory-mapped file
+    pub fn match_file_memmap(&self, path: &str) -> Result<Vec<(usize, usize, PatternID)>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(self.match_bytes(&mmap))
+    }
+
+    /// End of the last of `words` when they follow `end` in order, each after a gap of
+    /// whitespace
+    fn chain(
+        &self,
+        data: &[u8],
+        hits: &[(usize, usize, usize)],
+        mut end: usize,
+        words: &[usize],
+    ) -> Option<usize> {
+        for &word in words {
+            let gap = data[end..]
+                .iter()
+                .take_while(|b| b.is_ascii_whitespace())
+                .count();
+            if gap == 0 || self.max_gap.is_some_and(|max_gap| gap > max_gap) {
+                return None;
+            }
+            let next = end + gap;
+            let first = hits.partition_point(|&(start, _, _)| start < next);
+            end = hits[first..]
+                .iter()
+                .take_while(|&&(start, _, _)| start == next)
+                .find(|&&(_, _, hit_word)| hit_word == word)
+                .map(|&(_, hit_end, _)| hit_end)?;
+        }
+        Some(end)
+    }
+}
@@ -0,0 +1,203 @@
+//! Parallel scanning of sources that cannot be memory-mapped: a reader thread cuts the source
+//! into chunks handed to a pool of workers over bounded channels, and the results are joined
+//! in source order as `Matcher::match_chunks_parallel` joins those of the chunks of a file
+
+use crate::chunked::ChunkedScanner;
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use std::cmp;
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read, Result};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::thread;
+
+/// Chunks read and not yet joined, per worker: waiting for a worker, being scanned or waiting
+/// for the chunks before them, bounding the memory held by a fast reader
+const CHUNKS_IN_FLIGHT_PER_WORKER: usize = 3;
+
+/// Bytes of the source handed to a worker, see `ChunkedScanner::scan`
+struct Chunk {
+    /// Position of the chunk in the source, results are joined in this order
+    seq: usize,
+    /// Source offset of `data[0]`, the bytes before `start` are there for the checks
+    offset: usize,
+    /// The chunk owns the matches starting in `start..owned_end`
+    start: usize,
+    owned_end: usize,
+    /// End of the search, far enough past `owned_end` for the longest match, `data` reaching
+    /// past it for the checks
+    search_end: usize,
+    data: Vec<u8>,
+}
+
+/// Chunk searched from its own start
+struct Scanned<'m> {
+    chunk: Chunk,
+    matches: Vec<(usize, usize, PatternID)>,
+    first: Option<usize>,
+    scanner: ChunkedScanner<'m>,
+}
+
+/// Scan `reader` with `matcher` in chunks of `buffer_size` bytes on `workers` threads
+/// Returns the matches in the order of a single search of the whole source
+pub(crate) fn scan(
+    matcher: &Matcher,
+    reader: impl Read,
+    buffer_size: usize,
+    workers: usize,
+) -> Result<Vec<(usize, usize, PatternID)>> {
+    let workers = workers.max(1);
+    let in_flight = workers * CHUNKS_IN_FLIGHT_PER_WORKER;
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel(in_flight);
+    let chunk_rx = Mutex::new(chunk_rx);
+    let (result_tx, result_rx) = mpsc::channel();
+    // The reader takes a credit per chunk and gets it back once the chunk is joined
+    let (credit_tx, credit_rx) = mpsc::sync_channel(in_flight);
+    for _ in 0..in_flight {
+        let _ = credit_tx.send(());
+    }
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let chunk_rx = &chunk_rx;
+            let result_tx = result_tx.clone();
+            scope.spawn(move || loop {
+                let chunk: Chunk = match chunk_rx.lock().map(|rx| rx.recv()) {
+                    Ok(Ok(chunk)) => chunk,
+                    _ => break,
+                };
+                let mut matches = Vec::new();
+                let mut scanner = ChunkedScanner::new(matcher, chunk.start);
+                let first = scanner.scan(
+                    &chunk.data,
+                    chunk.offset,
+                    chunk.search_end,
+                    chunk.owned_end,
+                    &mut matches,
+                );
+                let scanned = Scanned {
+                    chunk,
+                    matches,
+                    first,
+                    scanner,
+                };
+                if result_tx.send(scanned).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(result_tx);
+
+        // A match of one chunk running into the next hides the matches of the next chunk
+        // starting under it, that chunk is then searched again from where the match ends
+        let joiner = scope.spawn(move || {
+            let mut all_matches = Vec::new();
+            let mut scanner = ChunkedScanner::new(matcher, 0);
+            let mut waiting = BTreeMap::new();
+            let mut next_seq = 0;
+            for scanned in result_rx {
+                waiting.insert(scanned.chunk.seq, scanned);
+                while let Some(mut scanned) = waiting.remove(&next_seq) {
+                    let chunk = &scanned.chunk;
+                    if scanner.agrees(scanned.first) {
+                        scanner.join(&scanned.scanner);
+                    } else {
+                        scanned.matches.clear();
+                        scanner.scan(
+                            &chunk.data,
+                            chunk.offset,
+                            chunk.search_end,
+                            chunk.owned_end,
+                            &mut scanned.matches,
+                        );
+                    }
+                    all_matches.append(&mut scanned.matches);
+                    next_seq += 1;
+                    let _ = credit_tx.send(());
+                }
+            }
+            // Dropping the credits stops a reader waiting for chunks a dead worker held
+            all_matches
+        });
+
+        // This thread is the reader, dropping the sender once done lets the workers finish
+        let scanner = ChunkedScanner::new(matcher, 0);
+        send_chunks(reader, buffer_size, &scanner, chunk_tx, credit_rx)?;
+        Ok(joiner
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e)))
+    })
+}
+
+/// Read the source ahead of each chunk by the bytes its search and checks need past it, and
+/// keep the bytes before it its checks need
+fn send_chunks(
+    mut reader: impl Read,
+    buffer_size: usize,
+    scanner: &ChunkedScanner,
+    chunk_tx: SyncSender<Chunk>,
+    credit_rx: Receiver<()>,
+) -> Result<()> {
+    let (overlap, context) = (scanner.overlap(), scanner.context());
+    let mut window: Vec<u8> = Vec::new();
+    let mut window_offset = 0;
+    let mut start = 0;
+    let mut eof = false;
+
+    for seq in 0.. {
+        while !eof && window_offset + window.len() < start + buffer_size + overlap + context {
+            let read = read_full(&mut reader, buffer_size)?;
+            eof = read.len() < buffer_size;
+            window.extend_from_slice(&read);
+        }
+        let end = window_offset + window.len();
+        if start >= end {
+            break;
+        }
+
+        // As in `Matcher::owned_chunks`, the last chunk is the first searched to the end
+        let search_end = cmp::min(start + buffer_size + overlap, end);
+        let owned_end = if eof && search_end == end {
+            end
+        } else {
+            start + buffer_size
+        };
+        let offset = start.saturating_sub(context);
+        let data_end = cmp::min(search_end + context, end);
+        let chunk = Chunk {
+            seq,
+            offset,
+            start,
+            owned_end,
+            search_end,
+            data: window[offset - window_offset..data_end - window_offset].to_vec(),
+        };
+        if credit_rx.recv().is_err() || chunk_tx.send(chunk).is_err() {
+            // Nobody is left to scan or join the rest
+            break;
+        }
+
+        start = owned_end;
+        let drop = start.saturating_sub(context) - window_offset;
+        window.drain(..drop);
+        window_offset += drop;
+    }
+    Ok(())
+}
+
+/// Read up to `size` bytes, fewer only at the end of the source
+fn read_full(reader: &mut impl Read, size: usize) -> Result<Vec<u8>> {
+    let mut buffer = vec![0; size];
+    let mut filled = 0;
+    while filled < size {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    buffer.truncate(filled);
+    Ok(buffer)
+}
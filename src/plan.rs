@@ -0,0 +1,273 @@
+//! Two-pass scan plans: a cheap matcher over everything, then the deep pass only on the
+//! regions around its hits, both on a single mapping of each file
+
+use crate::confirm::ConfirmMatcher;
+use crate::highlight::line_context;
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::cmp;
+use std::sync::Arc;
+
+/// Options of a scan plan
+#[derive(Clone, Debug)]
+pub struct PlanOptions {
+    /// Bytes around each hit of the first pass scanned by the second, on each side
+    /// None scans the whole file once the first pass has a hit in it
+    pub region: Option<usize>,
+    /// Bytes of context kept on each side of a finding, within its line
+    pub context: usize,
+}
+
+impl Default for PlanOptions {
+    fn default() -> Self {
+        Self {
+            region: Some(4096),
+            context: 40,
+        }
+    }
+}
+
+/// A confirmed match of the second pass with the text around it on its line
+#[derive(Clone, Debug)]
+pub struct PlanFinding {
+    pub start: usize,
+    pub end: usize,
+    pub rule: PatternID,
+    pub before: String,
+    pub text: String,
+    pub after: String,
+}
+
+/// Outcome of a scan plan on one file or buffer
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct PlanResult {
+    /// None for in-memory data
+    pub path: Option<String>,
+    pub bytes_scanned: usize,
+    /// Matches of the first pass, sorted by offset
+    pub hits: Vec<(usize, usize, PatternID)>,
+    /// Byte ranges scanned by the second pass
+    pub regions: Vec<(usize, usize)>,
+    /// Matches of the second pass, sorted by offset
+    pub findings: Vec<PlanFinding>,
+    /// Why the file could not be scanned
+    pub error: Option<String>,
+    patterns: Arc<Vec<String>>,
+    rules: Arc<Vec<String>>,
+}
+
+/// Scan plan running `coarse` over everything and `deep` around its hits
+/// Deep matches must fit inside a region to be found, like regex matches in the window of a
+/// ConfirmMatcher
+pub struct ScanPlan<'a> {
+    coarse: &'a Matcher,
+    deep: &'a ConfirmMatcher,
+    options: PlanOptions,
+}
+
+impl<'a> ScanPlan<'a> {
+    pub fn new(coarse: &'a Matcher, deep: &'a ConfirmMatcher, options: PlanOptions) -> Self {
+        Self {
+            coarse,
+            deep,
+            options,
+        }
+    }
+
+    pub fn options(&self) -> &PlanOptions {
+        &self.options
+    }
+
+    /// Run both passes on provided bytes
+    pub fn scan_bytes(&self, data: &[u8]) -> PlanResult {
+        self.scan(None, data)
+    }
+
+    /// Run both passes on a memory-mapped file, mapped once with the options of the coarse
+    /// matcher
+    pub fn scan_file(&self, path: &str) -> std::io::Result<PlanResult> {
+        let mmap = self.coarse.map_file(path)?;
+        Ok(self.scan(Some(path.to_string()), &mmap))
+    }
+
+    /// Run both passes on the files at `paths` in parallel, files that cannot be read are
+    /// reported with their error instead of failing the scan
+    pub fn scan_files(&self, paths: &[String]) -> Vec<PlanResult> {
+        paths
+            .par_iter()
+            .map(|path| {
+                self.scan_file(path).unwrap_or_else(|e| PlanResult {
+                    path: Some(path.clone()),
+                    bytes_scanned: 0,
+                    hits: Vec::new(),
+                    regions: Vec::new(),
+                    findings: Vec::new(),
+                    error: Some(e.to_string()),
+                    patterns: self.coarse.shared_patterns(),
+                    rules: self.deep.shared_rules(),
+                })
+            })
+            .collect()
+    }
+
+    fn scan(&self, path: Option<String>, data: &[u8]) -> PlanResult {
+        let mut hits = self.coarse.match_bytes(data);
+        hits.sort_unstable();
+        let regions = self.regions(data.len(), &hits);
+
+        let mut matches: Vec<(usize, usize, PatternID)> = regions
+            .par_iter()
+            .flat_map_iter(|&(from, to)| {
+                self.deep
+                    .match_bytes(&data[from..to])
+                    .into_iter()
+                    .map(move |(start, end, rule)| (from + start, from + end, rule))
+            })
+            .collect();
+        matches.sort_unstable();
+
+        let findings = matches
+            .into_iter()
+            .map(|(start, end, rule)| {
+                let (before, text, after) = line_context(data, start, end, self.options.context);
+                PlanFinding {
+                    start,
+                    end,
+                    rule,
+                    before,
+                    text,
+                    after,
+                }
+            })
+            .collect();
+
+        PlanResult {
+            path,
+            bytes_scanned: data.len(),
+            hits,
+            regions,
+            findings,
+            error: None,
+            patterns: self.coarse.shared_patterns(),
+            rules: self.deep.shared_rules(),
+        }
+    }
+
+    /// Disjoint ranges around the sorted `hits`, nearby ranges merged
+    fn regions(&self, len: usize, hits: &[(usize, usize, PatternID)]) -> Vec<(usize, usize)> {
+        let Some(region) = self.options.region else {
+            return if hits.is_empty() {
+                Vec::new()
+            } else {
+                vec![(0, len)]
+            };
+        };
+
+        let mut regions: Vec<(usize, usize)> = Vec::new();
+        for &(start, end, _) in hits {
+            let from = start.saturating_sub(region);
+            let to = cmp::min(end + region, len);
+            match regions.last_mut() {
+                Some(last) if from <= last.1 => last.1 = cmp::max(last.1, to),
+                _ => regions.push((from, to)),
+            }
+        }
+        regions
+    }
+}
+
+impl PlanResult {
+    /// Name of the coarse pattern of a first pass hit
+    pub fn pattern(&self, pattern_idx: PatternID) -> &str {
+        &self.patterns[pattern_idx.as_usize()]
+    }
+
+    /// Name of the rule of a finding
+    pub fn rule(&self, rule_idx: PatternID) -> &str {
+        &self.rules[rule_idx.as_usize()]
+    }
+
+    /// Bytes scanned by the second pass
+    pub fn deep_bytes(&self) -> usize {
+        self.regions.iter().map(|(from, to)| to - from).sum()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PlanResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "PlanResult(path={}, hits={}, findings={}, deep_bytes={})",
+            match &self.path {
+                Some(path) => format!("{:?}", path),
+                None => "None".to_string(),
+            },
+            self.hits.len(),
+            self.findings.len(),
+            self.deep_bytes()
+        )
+    }
+
+    /// Number of findings
+    fn __len__(&self) -> usize {
+        self.findings.len()
+    }
+
+    #[getter]
+    fn path(&self) -> Option<String> {
+        self.path.clone()
+    }
+
+    #[getter]
+    fn bytes_scanned(&self) -> usize {
+        self.bytes_scanned
+    }
+
+    #[getter(deep_bytes)]
+    fn py_deep_bytes(&self) -> usize {
+        self.deep_bytes()
+    }
+
+    #[getter]
+    fn error(&self) -> Option<String> {
+        self.error.clone()
+    }
+
+    /// (start_index, end_index, matched_pattern) tuples of the first pass
+    #[getter]
+    fn hits(&self) -> Vec<(usize, usize, String)> {
+        self.hits
+            .iter()
+            .map(|&(start, end, idx)| (start, end, self.pattern(idx).to_string()))
+            .collect()
+    }
+
+    /// (start_index, end_index) ranges scanned by the second pass
+    #[getter]
+    fn regions(&self) -> Vec<(usize, usize)> {
+        self.regions.clone()
+    }
+
+    /// (start_index, end_index, rule_name, before, text, after) tuples of the second pass
+    #[getter]
+    fn findings(&self) -> Vec<(usize, usize, String, String, String, String)> {
+        self.findings
+            .iter()
+            .map(|finding| {
+                (
+                    finding.start,
+                    finding.end,
+                    self.rule(finding.rule).to_string(),
+                    finding.before.clone(),
+                    finding.text.clone(),
+                    finding.after.clone(),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,117 @@
+use aho_corasick::PatternID;
+use std::cmp::{self, Reverse};
+use std::collections::BTreeMap;
+
+/// Strategy used to reduce overlapping match spans before results are returned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostProcess {
+    /// Merge overlapping and adjacent spans into a single span
+    Merge,
+    /// Keep the longest match wherever matches overlap
+    Longest,
+    /// Keep the highest-priority pattern wherever matches overlap
+    Priority,
+}
+
+impl PostProcess {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "merge" => Some(Self::Merge),
+            "longest" => Some(Self::Longest),
+            "priority" => Some(Self::Priority),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Longest => "longest",
+            Self::Priority => "priority",
+        }
+    }
+
+    /// Apply this strategy to a list of (start, end, pattern) matches
+    /// `priorities` is indexed by pattern and only used by the priority strategy,
+    /// where a higher value wins and `prefer_longer` decides ties by span length
+    /// The result is always sorted by start offset
+    pub fn apply(
+        &self,
+        matches: Vec<(usize, usize, PatternID)>,
+        priorities: &[i64],
+        prefer_longer: bool,
+    ) -> Vec<(usize, usize, PatternID)> {
+        match self {
+            Self::Merge => merge_spans(matches),
+            Self::Longest => select_non_overlapping(matches, |&(start, end, pattern)| {
+                (Reverse(end - start), start, pattern)
+            }),
+            Self::Priority => select_non_overlapping(matches, |&(start, end, pattern)| {
+                let len = (end - start) as i64;
+                let len_key = if prefer_longer { -len } else { len };
+                (Reverse(priorities[pattern.as_usize()]), len_key, start)
+            }),
+        }
+    }
+}
+
+/// Merge overlapping or touching spans, reporting the longest contributing pattern for each
+fn merge_spans(mut matches: Vec<(usize, usize, PatternID)>) -> Vec<(usize, usize, PatternID)> {
+    matches.sort_unstable();
+
+    let mut merged: Vec<(usize, usize, PatternID)> = Vec::with_capacity(matches.len());
+    // Length of the longest match that contributed to the last merged span
+    let mut longest = 0;
+
+    for (start, end, pattern) in matches {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => {
+                last.1 = cmp::max(last.1, end);
+                if end - start > longest {
+                    longest = end - start;
+                    last.2 = pattern;
+                }
+            }
+            _ => {
+                longest = end - start;
+                merged.push((start, end, pattern));
+            }
+        }
+    }
+
+    merged
+}
+
+/// Greedily keep the best-ranked matches that do not overlap an already kept match
+/// Lower keys rank better
+fn select_non_overlapping<K: Ord>(
+    mut matches: Vec<(usize, usize, PatternID)>,
+    rank: impl Fn(&(usize, usize, PatternID)) -> K,
+) -> Vec<(usize, usize, PatternID)> {
+    matches.sort_unstable_by_key(|m| rank(m));
+
+    // Kept spans keyed by start offset, which never overlap each other
+    let mut kept: BTreeMap<usize, (usize, PatternID)> = BTreeMap::new();
+
+    for (start, end, pattern) in matches {
+        // The closest kept span starting at or before this one must end before it starts
+        let overlaps_before = kept
+            .range(..=start)
+            .next_back()
+            .is_some_and(|(_, (kept_end, _))| *kept_end > start);
+
+        // The closest kept span starting after this one must start after it ends
+        let overlaps_after = kept
+            .range(start + 1..)
+            .next()
+            .is_some_and(|(kept_start, _)| *kept_start < end);
+
+        if !overlaps_before && !overlaps_after {
+            kept.insert(start, (end, pattern));
+        }
+    }
+
+    kept.into_iter()
+        .map(|(start, (end, pattern))| (start, end, pattern))
+        .collect()
+}
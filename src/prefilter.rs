@@ -0,0 +1,40 @@
+//! Line prefilters: cheap anchor literals a line must contain before the line-oriented scan
+//! runs the full pattern set on it
+
+use crate::engine::{Backend, Engine};
+use crate::matcher::ConfigError;
+
+/// Anchor literals of a line prefilter, folded like the patterns of the matcher
+pub(crate) struct LineFilter {
+    anchors: Vec<String>,
+    engine: Backend,
+}
+
+impl LineFilter {
+    /// Prefilter on the non-empty `anchors`
+    pub(crate) fn new(anchors: Vec<String>, case_insensitive: bool) -> Result<Self, ConfigError> {
+        let anchors: Vec<String> = anchors.into_iter().filter(|a| !a.is_empty()).collect();
+        if anchors.is_empty() {
+            return Err(ConfigError::Invalid(
+                "line_filter needs at least one non-empty anchor".to_string(),
+            ));
+        }
+        Ok(Self {
+            engine: Backend::build(&anchors, case_insensitive, Engine::Auto)?,
+            anchors,
+        })
+    }
+
+    pub(crate) fn anchors(&self) -> &[String] {
+        &self.anchors
+    }
+
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.engine.memory_usage() + self.anchors.iter().map(|a| a.capacity()).sum::<usize>()
+    }
+
+    /// Check if `line` contains one of the anchors
+    pub(crate) fn accepts(&self, line: &[u8]) -> bool {
+        self.engine.find_iter(line).next().is_some()
+    }
+}
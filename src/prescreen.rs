@@ -0,0 +1,201 @@
+//! Pre-screen of a matcher: a Bloom filter of one n-gram of every pattern, telling documents
+//! that cannot match apart from those that might with a hash probe per byte, to route
+//! documents before full scans in high-throughput pipelines
+
+use crate::encoding::Encoding;
+use crate::matcher::{ConfigError, Matcher};
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+
+/// Longest n-gram indexed, so n-grams fit the rolling 64-bit window
+const MAX_NGRAM: usize = 8;
+
+/// Bits set per n-gram, all in the same 64-bit word, so probes cost one memory access
+const BITS_PER_NGRAM: u32 = 8;
+
+/// Header of serialized pre-screens
+const MAGIC: &[u8; 4] = b"VPS1";
+
+/// Blocked Bloom filter of an n-gram of every pattern of a matcher, a 64-bit word per pattern
+/// keeping the false positives of every position of a document around 10^-7
+/// Every document a matcher finds a match in passes `might_contain`, and most documents
+/// without matches do not
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreScreen {
+    ngram: usize,
+    case_insensitive: bool,
+    // log2 of the number of words
+    shift: u32,
+    bits: Vec<u64>,
+}
+
+impl PreScreen {
+    /// Build the pre-screen of a matcher, indexing the first n-gram of every pattern, n being
+    /// the length of the shortest pattern up to 8 bytes
+    /// Matchers searching fuzzy variants, ignoring separators or transcoding their patterns
+    /// match text not containing those n-grams, and are rejected
+    pub fn new(matcher: &Matcher) -> Result<Self, ConfigError> {
+        if matcher.fuzzy().iter().any(|&d| d > 0) {
+            return Err(ConfigError::Invalid(
+                "Pre-screens do not support fuzzy matching".to_string(),
+            ));
+        }
+        if matcher.ignore_separators() {
+            return Err(ConfigError::Invalid(
+                "Pre-screens do not support ignore_separators".to_string(),
+            ));
+        }
+        if matcher.encoding() != Encoding::Utf8 {
+            return Err(ConfigError::Invalid(
+                "Pre-screens only support the utf-8 encoding".to_string(),
+            ));
+        }
+
+        let patterns = matcher.patterns();
+        let ngram = patterns
+            .iter()
+            .map(|p| p.len())
+            .min()
+            .unwrap_or(1)
+            .clamp(1, MAX_NGRAM);
+        let shift = patterns.len().next_power_of_two().max(8).trailing_zeros();
+        let mut screen = Self {
+            ngram,
+            case_insensitive: matcher.case_insensitive(),
+            shift,
+            bits: vec![0; 1 << shift],
+        };
+        for pattern in patterns {
+            let (word, mask) = screen.probe(screen.key(&pattern.as_bytes()[..ngram]));
+            screen.bits[word] |= mask;
+        }
+        Ok(screen)
+    }
+
+    /// Length of the indexed n-grams
+    pub fn ngram(&self) -> usize {
+        self.ngram
+    }
+
+    /// Bytes of the filter
+    pub fn size(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// Whether `data` holds an indexed n-gram, false meaning it cannot match
+    pub fn might_contain(&self, data: &[u8]) -> bool {
+        if data.len() < self.ngram {
+            return false;
+        }
+        let mask = if self.ngram == MAX_NGRAM {
+            u64::MAX
+        } else {
+            (1 << (8 * self.ngram)) - 1
+        };
+        let mut window = self.key(&data[..self.ngram - 1]);
+        data[self.ngram - 1..].iter().any(|&byte| {
+            window = ((window << 8) | u64::from(self.fold(byte))) & mask;
+            let (word, mask) = self.probe(window);
+            self.bits[word] & mask == mask
+        })
+    }
+
+    /// Serialize the pre-screen, e.g. to ship it to the routing tier
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.size());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.ngram as u8);
+        bytes.push(u8::from(self.case_insensitive));
+        bytes.push(self.shift as u8);
+        bytes.push(0);
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Read a pre-screen serialized by `to_bytes`, None if the bytes are not one
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (header, body) = bytes.split_at_checked(8)?;
+        let [m0, m1, m2, m3, ngram, case_insensitive, shift, _] = header.try_into().ok()?;
+        if [m0, m1, m2, m3] != *MAGIC
+            || !(1..=MAX_NGRAM).contains(&usize::from(ngram))
+            || case_insensitive > 1
+            || !(3..=34).contains(&shift)
+            || body.len() != 8usize << shift
+        {
+            return None;
+        }
+        Some(Self {
+            ngram: usize::from(ngram),
+            case_insensitive: case_insensitive == 1,
+            shift: u32::from(shift),
+            bits: body
+                .chunks_exact(8)
+                .map(|word| u64::from_le_bytes(word.try_into().expect("8-byte chunk")))
+                .collect(),
+        })
+    }
+
+    fn fold(&self, byte: u8) -> u8 {
+        if self.case_insensitive {
+            byte.to_ascii_lowercase()
+        } else {
+            byte
+        }
+    }
+
+    fn key(&self, ngram: &[u8]) -> u64 {
+        ngram
+            .iter()
+            .fold(0, |key, &byte| (key << 8) | u64::from(self.fold(byte)))
+    }
+
+    /// The word of an n-gram and its bits in that word, from a multiplicative hash
+    fn probe(&self, key: u64) -> (usize, u64) {
+        let hash = (key ^ (key >> 29)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let word = (hash >> (64 - self.shift)) as usize;
+        let mask = (0..BITS_PER_NGRAM).fold(0, |mask, i| mask | 1 << ((hash >> (6 * i)) & 63));
+        (word, mask)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PreScreen {
+    /// Read a pre-screen serialized by to_bytes
+    #[staticmethod]
+    #[pyo3(name = "from_bytes")]
+    fn py_from_bytes(data: &[u8]) -> PyResult<Self> {
+        Self::from_bytes(data).ok_or_else(|| PyValueError::new_err("Invalid pre-screen data"))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PreScreen(ngram={}, size={})", self.ngram, self.size())
+    }
+
+    #[getter(ngram)]
+    fn py_ngram(&self) -> usize {
+        self.ngram
+    }
+
+    #[getter(size)]
+    fn py_size(&self) -> usize {
+        self.size()
+    }
+
+    #[pyo3(name = "might_contain")]
+    fn py_might_contain(&self, py: Python<'_>, data: &[u8]) -> bool {
+        py.allow_threads(|| self.might_contain(data))
+    }
+
+    #[pyo3(name = "to_bytes")]
+    fn py_to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.to_bytes())
+    }
+}
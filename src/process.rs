@@ -0,0 +1,134 @@
+//! Process memory scans on Linux: the readable regions of a process listed by
+//! /proc/<pid>/maps, read from /proc/<pid>/mem and reported with their base address
+
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+use std::io::Result;
+
+/// Bytes read from process memory at a time
+#[cfg(target_os = "linux")]
+const READ_SIZE: usize = 8 * 1024 * 1024;
+
+/// A mapped region of a process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub start: u64,
+    pub end: u64,
+    /// Mapped file, or pseudo-path like `[heap]` and `[stack]`, empty for anonymous memory
+    pub name: String,
+}
+
+impl Region {
+    /// Whether the region maps a file rather than anonymous memory
+    pub fn is_file(&self) -> bool {
+        self.name.starts_with('/')
+    }
+}
+
+/// Matches of the memory of a process
+pub struct ProcessScan {
+    /// Readable regions scanned, in address order
+    pub regions: Vec<Region>,
+    /// (region, start, end, pattern) matches, the region indexing `regions` and the offsets
+    /// being relative to its start, sorted by region then offset
+    pub matches: Vec<(usize, usize, usize, PatternID)>,
+}
+
+impl ProcessScan {
+    /// Scan the readable regions of process `pid`, in parallel, skipping file mappings unless
+    /// `include_files`
+    /// Pages the kernel refuses to read, like those of `[vvar]` or unmapped while the scan
+    /// runs, end the scan of their region
+    #[cfg(target_os = "linux")]
+    pub fn scan(matcher: &Matcher, pid: u32, include_files: bool) -> Result<Self> {
+        use crate::report::SPLIT_FILE_SIZE;
+        use rayon::prelude::*;
+        use std::fs::{self, File};
+
+        let maps = fs::read_to_string(format!("/proc/{pid}/maps"))?;
+        let regions: Vec<Region> = maps
+            .lines()
+            .filter_map(parse_region)
+            .filter(|region| include_files || !region.is_file())
+            .collect();
+        let mem = File::open(format!("/proc/{pid}/mem"))?;
+
+        let mut matches: Vec<_> = regions
+            .par_iter()
+            .enumerate()
+            .map(|(idx, region)| {
+                let reader = RegionReader {
+                    mem: &mem,
+                    pos: region.start,
+                    end: region.end,
+                };
+                let matches = if region.end - region.start >= SPLIT_FILE_SIZE as u64 {
+                    matcher.match_reader_parallel(reader, READ_SIZE, None)?
+                } else {
+                    matcher.match_reader(reader, READ_SIZE)?
+                };
+                Ok(matches
+                    .into_iter()
+                    .map(|(start, end, pattern)| (idx, start, end, pattern))
+                    .collect::<Vec<_>>())
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        matches.sort_unstable();
+        Ok(Self { regions, matches })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn scan(_matcher: &Matcher, _pid: u32, _include_files: bool) -> Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Process memory scans are only supported on Linux",
+        ))
+    }
+}
+
+/// Readable region of a line of /proc/<pid>/maps, None for regions without read access
+#[cfg(target_os = "linux")]
+fn parse_region(line: &str) -> Option<Region> {
+    let mut fields = line.split_whitespace();
+    let (start, end) = fields.next()?.split_once('-')?;
+    let perms = fields.next()?;
+    if !perms.starts_with('r') {
+        return None;
+    }
+    // Offset, device and inode precede the name
+    let name = fields.skip(3).collect::<Vec<_>>().join(" ");
+    Some(Region {
+        start: u64::from_str_radix(start, 16).ok()?,
+        end: u64::from_str_radix(end, 16).ok()?,
+        name,
+    })
+}
+
+/// Bytes of a region read from /proc/<pid>/mem, ending at the first page that cannot be read
+#[cfg(target_os = "linux")]
+struct RegionReader<'a> {
+    mem: &'a std::fs::File,
+    pos: u64,
+    end: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl std::io::Read for RegionReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        use std::os::unix::fs::FileExt;
+
+        let len = buf.len().min((self.end - self.pos) as usize);
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = self.mem.read_at(&mut buf[..len], self.pos).unwrap_or(0);
+        if read == 0 {
+            self.pos = self.end;
+        }
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
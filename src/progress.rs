@@ -0,0 +1,131 @@
+//! Progress of matcher builds, reported stage by stage to a hook that can cancel them, for
+//! dictionaries whose automaton takes minutes to build
+
+use crate::matcher::ConfigError;
+use std::fmt;
+use std::panic;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest time between two reports of a stage, which is also how long a cancelled build may
+/// take to return
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Step of a build, in the order they run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildStage {
+    /// Checking the options and dropping empty patterns
+    Prepare,
+    /// Expanding fuzzy patterns to their variants, only with fuzzy distances
+    Expand,
+    /// Building the automaton, or whichever structure the engine searches with
+    Automaton,
+    /// Hashing the patterns reported in place of the matched text, only with a hash key
+    Labels,
+}
+
+impl BuildStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Prepare => "prepare",
+            Self::Expand => "expand",
+            Self::Automaton => "automaton",
+            Self::Labels => "labels",
+        }
+    }
+}
+
+/// Receiver of the progress of a build, called with the stage, the patterns done and the
+/// total on the thread building the matcher; returning false cancels the build
+///
+/// The automaton stage cannot be measured and reports 0 of 1 until the automaton is built. It
+/// runs on a thread of its own meanwhile, so a cancelled build returns within
+/// `REPORT_INTERVAL` and the automaton is dropped once that thread finishes it.
+#[derive(Clone)]
+pub struct ProgressHook(Arc<dyn Fn(BuildStage, usize, usize) -> bool + Send + Sync>);
+
+impl ProgressHook {
+    pub fn new(hook: impl Fn(BuildStage, usize, usize) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// Progress of one build, reporting the start and end of each stage and at most every
+/// `REPORT_INTERVAL` in between
+pub(crate) struct BuildTracker {
+    hook: Option<ProgressHook>,
+    last: Instant,
+}
+
+impl BuildTracker {
+    pub(crate) fn new(hook: Option<ProgressHook>) -> Self {
+        Self {
+            hook,
+            last: Instant::now(),
+        }
+    }
+
+    /// Report `done` of `total` patterns of the stage, failing if the hook cancels the build
+    pub(crate) fn report(
+        &mut self,
+        stage: BuildStage,
+        done: usize,
+        total: usize,
+    ) -> Result<(), ConfigError> {
+        let Some(hook) = &self.hook else {
+            return Ok(());
+        };
+        if done != 0 && done < total && self.last.elapsed() < REPORT_INTERVAL {
+            return Ok(());
+        }
+        self.last = Instant::now();
+        if (hook.0)(stage, done, total) {
+            Ok(())
+        } else {
+            Err(ConfigError::Cancelled(format!(
+                "The build was cancelled during the {} stage",
+                stage.name()
+            )))
+        }
+    }
+
+    /// Run `work` as the stage, on a thread of its own when the build reports its progress so
+    /// the hook keeps being called, and can cancel it, while the work cannot report
+    pub(crate) fn run<T: Send + 'static>(
+        &mut self,
+        stage: BuildStage,
+        work: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, ConfigError> {
+        if self.hook.is_none() {
+            return Ok(work());
+        }
+
+        self.report(stage, 0, 1)?;
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            // The build is abandoned once cancelled, with nobody left to receive its result
+            let _ = sender.send(work());
+        });
+        loop {
+            match receiver.recv_timeout(REPORT_INTERVAL) {
+                Ok(value) => {
+                    self.report(stage, 1, 1)?;
+                    return Ok(value);
+                }
+                Err(RecvTimeoutError::Timeout) => self.report(stage, 0, 1)?,
+                Err(RecvTimeoutError::Disconnected) => match worker.join() {
+                    Err(payload) => panic::resume_unwind(payload),
+                    Ok(()) => unreachable!("the build thread sends its result before exiting"),
+                },
+            }
+        }
+    }
+}
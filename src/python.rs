@@ -0,0 +1,4126 @@
+use crate::audit::AuditLog;
+use crate::avro::AvroScan;
+use crate::bench::{self, BenchConfig, BenchResult};
+use crate::budget::OnMemoryLimit;
+use crate::builder::{self, MatcherBuilder, PatternNormalizer, PatternValidator};
+use crate::bundle::{self, Bundle, BundleInfo};
+use crate::collection::MatchCollection;
+use crate::compare::Comparison;
+use crate::confirm::{ConfirmMatcher as Confirmer, ConfirmOptions, Rule};
+use crate::db::{DbOptions, DbScan, DbValue};
+use crate::dedup::DedupOptions;
+use crate::density::{DensityRegion, WindowUnit};
+use crate::diff::DiffScan;
+use crate::dir::{DirOptions, DirScan, FileSummary};
+use crate::disk::{DiskOptions, DiskScan};
+use crate::encoding::Encoding;
+use crate::engine::Engine;
+use crate::errors::io_error;
+use crate::events::{EventHook, Level as EventLevel};
+use crate::exclusion::IgnoreSpans;
+use crate::git::{GitOptions, GitScan};
+use crate::golden::{self, GoldenFailure, GoldenReport};
+use crate::highlight;
+use crate::histogram::{Buckets, TimestampFormat};
+use crate::incremental::{IncrementalMatcher as Incremental, IncrementalOptions};
+use crate::lines::{self, LineIndex as Lines};
+use crate::matcher::{
+    self, ConfigError, InvalidUtf8, MatchOverrides, Matcher, MatcherOptions, PatternMetadata,
+    WordChars,
+};
+use crate::mmap::{self, Madvise, MmapOptions};
+use crate::orc::OrcScan;
+use crate::patterndiff::PatternDiff;
+use crate::pcap::PcapScan;
+use crate::phonetic::Phonetic;
+use crate::phrase::{PhraseMatcher as Phrases, PhraseOptions};
+use crate::plan::{PlanOptions, PlanResult, ScanPlan as Plan};
+use crate::postprocess::PostProcess;
+use crate::prescreen::PreScreen;
+use crate::process::ProcessScan;
+use crate::progress::ProgressHook;
+use crate::report::{ReportOptions, ScanReport};
+use crate::sampling::{DensityEstimate, SampleOptions};
+use crate::sink::{OutputSchema, SinkFormat, SinkSpec, SinkSummary};
+use crate::source::{LogSource, SourceOptions};
+use crate::spill::{Spool, SpoolIter, SpoolOptions};
+use crate::stats::ScanStats;
+use crate::token::{TokenMatcher as Tokens, TokenOptions};
+use crate::unique::{Normalize, UniqueOptions};
+use crate::warc::{WarcOptions, WarcScan};
+use crate::webhook::{self, WebhookSink};
+use aho_corasick::PatternID;
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyMemoryView, PyString, PyTuple};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Python repr of a string, for the reprs of our classes
+pub(crate) fn quote(py: Python<'_>, value: &str) -> PyResult<String> {
+    Ok(PyString::new(py, value).repr()?.to_string())
+}
+
+/// Python repr of a bool
+pub(crate) fn py_bool(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+/// Python binary file-like object read through its read(size) method
+struct PyReader(Py<PyAny>);
+
+impl Read for PyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Python::with_gil(|py| {
+            let chunk = self.0.bind(py).call_method1("read", (buf.len(),))?;
+            let chunk = chunk.downcast::<PyBytes>()?.as_bytes();
+            let len = chunk.len().min(buf.len());
+            buf[..len].copy_from_slice(&chunk[..len]);
+            Ok(len)
+        })
+        .map_err(|e: PyErr| io::Error::other(e))
+    }
+}
+
+/// (start_index, end_index, matched_pattern) tuple returned to Python
+pub(crate) type PyMatch = (usize, usize, Py<PyString>);
+
+/// (line_number, start_index, end_index, matched_pattern) tuple returned to Python, or
+/// (sector, start_index, end_index, matched_pattern) match of a disk image
+type PyLineMatch = (usize, usize, usize, Py<PyString>);
+
+/// (line, column, end_line, end_column, pattern) position of a match returned to Python
+type PyPosition = (usize, usize, usize, usize, Py<PyAny>);
+
+/// (start, end, pattern, char_start, char_end) match returned to Python, with UTF-8
+/// character offsets next to the byte offsets
+type PyCharMatch = (usize, usize, Py<PyString>, usize, usize);
+
+/// (path, start, end, pattern) match of a directory scan returned to Python, or
+/// (uri, start, end, pattern) match of a web archive
+type PyDirMatch = (Py<PyString>, usize, usize, Py<PyString>);
+
+/// (path, line, start, end, pattern) match of the added lines of a diff returned to Python
+type PyDiffMatch = (Py<PyString>, usize, usize, usize, Py<PyString>);
+
+/// (commit, path, start, end, pattern) match of a repository scan returned to Python
+type PyGitMatch = (Py<PyString>, Py<PyString>, usize, usize, Py<PyString>);
+
+/// (base_address, region, start, end, pattern) match of the memory of a process returned to
+/// Python
+type PyProcessMatch = (u64, Py<PyString>, usize, usize, Py<PyString>);
+
+/// ((transport, src, src_port, dst, dst_port), start, end, pattern) match of a packet capture
+/// returned to Python
+type PyPcapMatch = (Py<PyTuple>, usize, usize, Py<PyString>);
+
+/// (record, field, start, end, pattern) match of an Avro or ORC file returned to Python
+type PyRecordMatch = (u64, Py<PyString>, usize, usize, Py<PyString>);
+
+/// (table, column, key, start, end, pattern) match of a database scan returned to Python
+type PyDbMatch = (
+    Option<Py<PyString>>,
+    Py<PyString>,
+    Py<PyAny>,
+    usize,
+    usize,
+    Py<PyString>,
+);
+
+/// (start, end, pattern, count) finding with the number of identical occurrences folded into it
+type PyCountedMatch = (usize, usize, Py<PyString>, usize);
+
+/// ([(bucket_start, bucket_end, match_count)], unbucketed) histogram returned to Python
+type PyHistogram = (Vec<(i64, i64, usize)>, usize);
+
+/// Python strings of the names reported by a matcher, created on first use and shared by every
+/// result of a pattern, so results with many hits do not allocate a string per hit
+#[derive(Default)]
+pub(crate) struct PyNames(GILOnceCell<Vec<Py<PyString>>>);
+
+impl PyNames {
+    pub(crate) fn get(&self, py: Python<'_>, names: &[String]) -> &[Py<PyString>] {
+        self.0.get_or_init(py, || {
+            names
+                .iter()
+                .map(|name| PyString::new(py, name).unbind())
+                .collect()
+        })
+    }
+
+    pub(crate) fn resolve(
+        &self,
+        py: Python<'_>,
+        names: &[String],
+        matches: impl IntoIterator<Item = (usize, usize, PatternID)>,
+    ) -> Vec<PyMatch> {
+        let names = self.get(py, names);
+        matches
+            .into_iter()
+            .map(|(start, end, idx)| (start, end, names[idx.as_usize()].clone_ref(py)))
+            .collect()
+    }
+}
+
+/// TextMatcher is immutable once built and the match methods release the GIL while scanning,
+/// so one matcher can be shared by Python threads scanning in parallel
+#[pyclass(frozen)]
+pub struct TextMatcher {
+    inner: Matcher,
+    names: PyNames,
+}
+
+impl TextMatcher {
+    fn with_options(
+        py: Python<'_>,
+        patterns: Vec<String>,
+        options: TextMatcherOptions<'_>,
+    ) -> PyResult<Self> {
+        let defaults = MatcherOptions::default();
+        let fuzzy = options
+            .fuzzy
+            .as_ref()
+            .map(|fuzzy| extract_fuzzy(fuzzy, patterns.len()))
+            .transpose()?;
+        let min_counts = options
+            .min_count
+            .as_ref()
+            .map(|min_count| extract_min_counts(min_count, &patterns))
+            .transpose()?;
+
+        let postprocess = match options.postprocess.as_deref() {
+            Some(name) => Some(PostProcess::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown postprocess strategy '{}', expected 'merge', 'longest' or 'priority'",
+                    name
+                ))
+            })?),
+            None => None,
+        };
+
+        let engine = match options.engine.as_deref() {
+            Some(name) => parse_engine(name)?,
+            None => defaults.engine,
+        };
+
+        let madvise = match options.madvise.as_deref() {
+            Some(name) => Madvise::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown madvise hint '{}', expected 'normal', 'sequential' or 'willneed'",
+                    name
+                ))
+            })?,
+            None => defaults.mmap.madvise,
+        };
+
+        let invalid_utf8 = match options.invalid_utf8.as_deref() {
+            Some(name) => InvalidUtf8::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown invalid_utf8 policy '{}', expected 'error', 'lossy', 'skip' or 'binary'",
+                    name
+                ))
+            })?,
+            None => defaults.invalid_utf8,
+        };
+
+        let output_schema = match options.schema_version {
+            Some(version) => OutputSchema::from_version(version).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown schema version {}, expected 0 (legacy) to {}",
+                    version,
+                    OutputSchema::LATEST
+                ))
+            })?,
+            None => defaults.output_schema,
+        };
+
+        let on_memory_limit = match options.on_memory_limit.as_deref() {
+            Some(name) => OnMemoryLimit::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown on_memory_limit policy '{}', expected 'error' or 'downgrade'",
+                    name
+                ))
+            })?,
+            None => defaults.on_memory_limit,
+        };
+
+        let matcher_options = MatcherOptions {
+            overlapping: options.overlapping.unwrap_or(defaults.overlapping),
+            case_insensitive: options
+                .case_insensitive
+                .unwrap_or(defaults.case_insensitive),
+            whole_word: options.whole_word.unwrap_or(defaults.whole_word),
+            word_chars: match options.word_chars.as_deref() {
+                Some(spec) => WordChars::parse(spec)?,
+                None => defaults.word_chars,
+            },
+            postprocess,
+            priorities: options.priorities,
+            prefer_longer: options.prefer_longer.unwrap_or(defaults.prefer_longer),
+            hash_key: options.hash_key.as_ref().map(extract_key).transpose()?,
+            fuzzy,
+            ignore_separators: options
+                .ignore_separators
+                .unwrap_or(defaults.ignore_separators),
+            min_counts,
+            exclude: options.exclude,
+            line_filter: options.line_filter,
+            invalid_utf8,
+            encoding: extract_encoding(options.encoding.as_deref())?.unwrap_or(defaults.encoding),
+            engine,
+            max_memory_bytes: options.max_memory_bytes,
+            on_memory_limit,
+            max_decoded_size: options
+                .max_decoded_size
+                .unwrap_or(defaults.max_decoded_size),
+            mmap: MmapOptions {
+                madvise,
+                huge_pages: options.huge_pages.unwrap_or(defaults.mmap.huge_pages),
+                prefault: options.prefault.unwrap_or(defaults.mmap.prefault),
+            },
+            events: options.events.as_ref().map(extract_events).transpose()?,
+            audit_log: options.audit_log.map(AuditLog::new),
+            output_schema,
+            metadata: options.metadata,
+            tags: options.tags,
+            progress: None,
+        };
+
+        let inner = build_matcher(py, options.progress.as_ref(), |hook| {
+            Matcher::new(
+                patterns,
+                MatcherOptions {
+                    progress: Some(hook),
+                    ..matcher_options
+                },
+            )
+        })?;
+        Ok(Self {
+            inner,
+            names: PyNames::default(),
+        })
+    }
+}
+
+/// Options of a TextMatcher: the arguments of its constructor, and the options added since
+/// that only TextMatcherBuilder sets
+#[derive(Default)]
+struct TextMatcherOptions<'py> {
+    overlapping: Option<bool>,
+    case_insensitive: Option<bool>,
+    whole_word: Option<bool>,
+    postprocess: Option<String>,
+    priorities: Option<Vec<i64>>,
+    prefer_longer: Option<bool>,
+    hash_key: Option<Bound<'py, PyAny>>,
+    engine: Option<String>,
+    madvise: Option<String>,
+    huge_pages: Option<bool>,
+    prefault: Option<bool>,
+    fuzzy: Option<Bound<'py, PyAny>>,
+    ignore_separators: Option<bool>,
+    word_chars: Option<String>,
+    exclude: Option<Vec<String>>,
+    line_filter: Option<Vec<String>>,
+    invalid_utf8: Option<String>,
+    encoding: Option<String>,
+    events: Option<Bound<'py, PyAny>>,
+    audit_log: Option<String>,
+    min_count: Option<Bound<'py, PyAny>>,
+    schema_version: Option<u32>,
+    metadata: Option<Vec<PatternMetadata>>,
+    tags: Option<Vec<Vec<String>>>,
+    max_memory_bytes: Option<usize>,
+    on_memory_limit: Option<String>,
+    max_decoded_size: Option<usize>,
+    progress: Option<Bound<'py, PyAny>>,
+}
+
+impl<'py> TextMatcherOptions<'py> {
+    /// Take the options out of a dict of keyword arguments, raising TypeError for any other
+    fn from_dict(options: &Bound<'py, PyDict>) -> PyResult<Self> {
+        let parsed = Self {
+            overlapping: take_option(options, "overlapping")?,
+            case_insensitive: take_option(options, "case_insensitive")?,
+            whole_word: take_option(options, "whole_word")?,
+            postprocess: take_option(options, "postprocess")?,
+            priorities: take_option(options, "priorities")?,
+            prefer_longer: take_option(options, "prefer_longer")?,
+            hash_key: take_option(options, "hash_key")?,
+            engine: take_option(options, "engine")?,
+            madvise: take_option(options, "madvise")?,
+            huge_pages: take_option(options, "huge_pages")?,
+            prefault: take_option(options, "prefault")?,
+            fuzzy: take_option(options, "fuzzy")?,
+            ignore_separators: take_option(options, "ignore_separators")?,
+            word_chars: take_option(options, "word_chars")?,
+            exclude: take_option(options, "exclude")?,
+            line_filter: take_option(options, "line_filter")?,
+            invalid_utf8: take_option(options, "invalid_utf8")?,
+            encoding: take_option(options, "encoding")?,
+            events: take_option(options, "events")?,
+            audit_log: take_option(options, "audit_log")?,
+            min_count: take_option(options, "min_count")?,
+            schema_version: take_option(options, "schema_version")?,
+            metadata: take_option(options, "metadata")?,
+            tags: take_option(options, "tags")?,
+            max_memory_bytes: take_option(options, "max_memory_bytes")?,
+            on_memory_limit: take_option(options, "on_memory_limit")?,
+            max_decoded_size: take_option(options, "max_decoded_size")?,
+            progress: take_option(options, "progress")?,
+        };
+        if let Some((key, _)) = options.iter().next() {
+            return Err(PyTypeError::new_err(format!(
+                "Unknown TextMatcher option '{}'",
+                key
+            )));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Remove `key` from `options` and extract its value, None when missing
+fn take_option<'py, T: FromPyObject<'py>>(
+    options: &Bound<'py, PyDict>,
+    key: &str,
+) -> PyResult<Option<T>> {
+    let Some(value) = options.get_item(key)? else {
+        return Ok(None);
+    };
+    options.del_item(key)?;
+    value.extract().map_err(|e| {
+        if e.is_instance_of::<PyTypeError>(options.py()) {
+            PyTypeError::new_err(format!("option '{}': {}", key, e.value(options.py())))
+        } else {
+            e
+        }
+    })
+}
+
+#[pymethods]
+impl TextMatcher {
+    /// The constructor arguments are frozen, options added since are set through
+    /// TextMatcherBuilder
+    #[new]
+    #[pyo3(signature = (patterns, overlapping=None, case_insensitive=None, whole_word=None, postprocess=None, priorities=None, prefer_longer=None, hash_key=None, engine=None, madvise=None, huge_pages=None, prefault=None, fuzzy=None, ignore_separators=None, word_chars=None, exclude=None, line_filter=None, invalid_utf8=None, encoding=None, events=None, audit_log=None, min_count=None, schema_version=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<'py>(
+        py: Python<'py>,
+        patterns: Vec<String>,
+        overlapping: Option<bool>,
+        case_insensitive: Option<bool>,
+        whole_word: Option<bool>,
+        postprocess: Option<String>,
+        priorities: Option<Vec<i64>>,
+        prefer_longer: Option<bool>,
+        hash_key: Option<Bound<'py, PyAny>>,
+        engine: Option<String>,
+        madvise: Option<String>,
+        huge_pages: Option<bool>,
+        prefault: Option<bool>,
+        fuzzy: Option<Bound<'py, PyAny>>,
+        ignore_separators: Option<bool>,
+        word_chars: Option<String>,
+        exclude: Option<Vec<String>>,
+        line_filter: Option<Vec<String>>,
+        invalid_utf8: Option<String>,
+        encoding: Option<String>,
+        events: Option<Bound<'py, PyAny>>,
+        audit_log: Option<String>,
+        min_count: Option<Bound<'py, PyAny>>,
+        schema_version: Option<u32>,
+    ) -> PyResult<Self> {
+        let options = TextMatcherOptions {
+            overlapping,
+            case_insensitive,
+            whole_word,
+            postprocess,
+            priorities,
+            prefer_longer,
+            hash_key,
+            engine,
+            madvise,
+            huge_pages,
+            prefault,
+            fuzzy,
+            ignore_separators,
+            word_chars,
+            exclude,
+            line_filter,
+            invalid_utf8,
+            encoding,
+            events,
+            audit_log,
+            min_count,
+            schema_version,
+            ..Default::default()
+        };
+        Self::with_options(py, patterns, options)
+    }
+
+    /// Load a matcher bundle written by save_bundle or compile_bundle, building its automaton
+    /// without the GIL
+    /// With `expected_hash`, the content hash pinned when the bundle was compiled, bundles
+    /// rewritten since are rejected even with a matching recorded hash
+    /// With `verify_key`, bundles not signed with its signing key are rejected
+    #[staticmethod]
+    #[pyo3(signature = (path, progress=None, expected_hash=None, verify_key=None))]
+    pub fn load_bundle(
+        py: Python<'_>,
+        path: PathBuf,
+        progress: Option<&Bound<'_, PyAny>>,
+        expected_hash: Option<&str>,
+        verify_key: Option<&[u8]>,
+    ) -> PyResult<Self> {
+        if let Some(key) = verify_key {
+            bundle::check_key_len("verify_key", key)?;
+        }
+        let bundle = py
+            .allow_threads(|| {
+                let bundle = Bundle::load(&path)?;
+                if let Some(expected) = expected_hash {
+                    bundle.check_hash(expected)?;
+                }
+                if let Some(key) = verify_key {
+                    bundle.verify(key)?;
+                }
+                Ok(bundle)
+            })
+            .map_err(io_error)?;
+        let inner = build_matcher(py, progress, |hook| bundle.matcher(Some(hook)))?;
+        Ok(Self {
+            inner,
+            names: PyNames::default(),
+        })
+    }
+
+    /// Write the matcher to a bundle file that load_bundle builds it back from, signed with
+    /// the 32-byte Ed25519 seed `signing_key` when given
+    #[pyo3(signature = (path, signing_key=None))]
+    pub fn save_bundle(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        signing_key: Option<&[u8]>,
+    ) -> PyResult<BundleInfo> {
+        let mut bundle = Bundle::new(&self.inner)?;
+        if let Some(key) = signing_key {
+            bundle.sign(key)?;
+        }
+        py.allow_threads(|| bundle.save(&path)).map_err(io_error)?;
+        Ok(bundle.info().clone())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let postprocess = match self.inner.postprocess() {
+            Some(postprocess) => quote(py, postprocess.name())?,
+            None => "None".to_string(),
+        };
+        Ok(format!(
+            "TextMatcher(patterns={}, overlapping={}, case_insensitive={}, whole_word={}, postprocess={}{})",
+            self.inner.patterns().len(),
+            py_bool(self.inner.overlapping()),
+            py_bool(self.inner.case_insensitive()),
+            py_bool(self.inner.whole_word()),
+            postprocess,
+            if self.inner.hashed() { ", hashed=True" } else { "" }
+        ))
+    }
+
+    /// Whether results report HMAC-SHA256 digests of the matched text instead of the patterns
+    #[getter]
+    pub fn hashed(&self) -> bool {
+        self.inner.hashed()
+    }
+
+    /// Engine searching for the patterns, the one picked when built with 'auto'
+    #[getter]
+    pub fn engine(&self) -> &'static str {
+        self.inner.engine().name()
+    }
+
+    /// Kind of Aho-Corasick automaton searching for the patterns, 'dfa', 'contiguous_nfa' or
+    /// 'noncontiguous_nfa', None when the engine does not build one
+    #[getter]
+    pub fn automaton(&self) -> Option<&'static str> {
+        self.inner.automaton()
+    }
+
+    /// Hex SHA-256 of the voluta version, the patterns and the options that change the matches
+    #[getter]
+    pub fn fingerprint(&self) -> &str {
+        self.inner.fingerprint()
+    }
+
+    /// Build a Bloom filter pre-screen of the patterns, telling documents that cannot match
+    /// apart from those that might far faster than a scan
+    pub fn prescreen(&self) -> PyResult<PreScreen> {
+        Ok(PreScreen::new(&self.inner)?)
+    }
+
+    /// Estimate the matches of a memory-mapped file from a random sample of its chunks, with
+    /// 95% confidence bounds, to prioritize which files to scan in full
+    #[pyo3(signature = (path, sample_fraction=0.01, chunk_size=65536, seed=None))]
+    pub fn estimate_density(
+        &self,
+        py: Python<'_>,
+        path: String,
+        sample_fraction: f64,
+        chunk_size: usize,
+        seed: Option<u64>,
+    ) -> PyResult<DensityEstimate> {
+        if !(sample_fraction > 0.0 && sample_fraction <= 1.0) {
+            return Err(PyValueError::new_err("sample_fraction must be in (0, 1]"));
+        }
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err("chunk_size must be positive"));
+        }
+        let options = SampleOptions {
+            fraction: sample_fraction,
+            chunk_size,
+            seed,
+        };
+        py.allow_threads(|| self.inner.estimate_density(&path, &options))
+            .map_err(io_error)
+    }
+
+    /// First match of every pattern in bytes, or in a file given by its path and
+    /// memory-mapped, the scan stopping as soon as every pattern has been seen
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples sorted by offset
+    pub fn match_first(
+        &self,
+        py: Python<'_>,
+        data_or_path: &Bound<'_, PyAny>,
+    ) -> PyResult<Vec<PyMatch>> {
+        let matches = if let Ok(data) = data_or_path.downcast::<PyBytes>() {
+            let data = data.as_bytes();
+            py.allow_threads(|| self.inner.match_first(data))
+        } else {
+            let path: String = data_or_path
+                .extract()
+                .map_err(|_| PyTypeError::new_err("data_or_path must be bytes or a path"))?;
+            py.allow_threads(|| self.inner.match_file_first(&path))
+                .map_err(io_error)?
+        };
+        Ok(self.resolve(py, matches))
+    }
+
+    /// Scan bytes, or a file given by its path and memory-mapped once, with this matcher (A)
+    /// and `other` (B) in parallel
+    /// Returns a Comparison of the findings of A only, of B only and of both
+    pub fn compare_scan(
+        &self,
+        py: Python<'_>,
+        other: &TextMatcher,
+        data_or_path: &Bound<'_, PyAny>,
+    ) -> PyResult<Comparison> {
+        if let Ok(data) = data_or_path.downcast::<PyBytes>() {
+            let data = data.as_bytes();
+            Ok(py.allow_threads(|| self.inner.compare(&other.inner, data)))
+        } else {
+            let path: String = data_or_path
+                .extract()
+                .map_err(|_| PyTypeError::new_err("data_or_path must be bytes or a path"))?;
+            py.allow_threads(|| self.inner.compare_file(&other.inner, &path))
+                .map_err(io_error)
+        }
+    }
+
+    /// Patterns added, removed and changed and options changed from this matcher to `other`
+    pub fn diff_patterns(&self, other: &TextMatcher) -> PatternDiff {
+        self.inner.diff_patterns(&other.inner)
+    }
+
+    /// Matcher of the patterns of this matcher followed by those of `others`, keeping their
+    /// per-pattern settings and metadata, see `Matcher::union`
+    pub fn union(&self, py: Python<'_>, others: Vec<PyRef<'_, TextMatcher>>) -> PyResult<Self> {
+        let others: Vec<&Matcher> = others.iter().map(|other| &other.inner).collect();
+        let inner = py.allow_threads(|| self.inner.union(&others))?;
+        Ok(Self {
+            inner,
+            names: PyNames::default(),
+        })
+    }
+
+    /// Dedicated matcher of the patterns with any of `tags`, see `Matcher::subset`
+    pub fn subset(&self, py: Python<'_>, tags: Vec<String>) -> PyResult<Self> {
+        let inner = py.allow_threads(|| self.inner.subset(&tags))?;
+        Ok(Self {
+            inner,
+            names: PyNames::default(),
+        })
+    }
+
+    /// Path of the JSON Lines file every scan run is recorded in, if any
+    #[getter]
+    pub fn audit_log(&self) -> Option<String> {
+        self.inner
+            .audit_log()
+            .map(|log| log.path().to_string_lossy().into_owned())
+    }
+
+    /// Metadata of each pattern, empty dicts for patterns given none
+    #[getter]
+    pub fn metadata(&self) -> Vec<PatternMetadata> {
+        self.inner.metadata().to_vec()
+    }
+
+    /// Tags of each pattern, empty lists for patterns given none
+    #[getter]
+    pub fn tags(&self) -> Vec<Vec<String>> {
+        self.inner.tags().to_vec()
+    }
+
+    /// Version of the JSON schema of exported findings, 0 for the legacy shapes
+    #[getter]
+    pub fn schema_version(&self) -> u32 {
+        self.inner.output_schema().version()
+    }
+
+    /// Edit distance each pattern is matched at, 0 for exact matching
+    #[getter]
+    pub fn fuzzy(&self) -> Vec<usize> {
+        self.inner.fuzzy().to_vec()
+    }
+
+    /// Whether ASCII whitespace and punctuation are ignored in the patterns and the text
+    #[getter]
+    pub fn ignore_separators(&self) -> bool {
+        self.inner.ignore_separators()
+    }
+
+    /// Exclusion patterns, matches overlapping one of their occurrences are dropped
+    #[getter]
+    pub fn exclude(&self) -> Vec<String> {
+        self.inner.exclude().to_vec()
+    }
+
+    /// Anchors a line must contain for match_file to search it, if any
+    #[getter]
+    pub fn line_filter(&self) -> Option<Vec<String>> {
+        self.inner.line_filter().map(<[String]>::to_vec)
+    }
+
+    /// Handling of the lines of match_file that are not valid UTF-8
+    #[getter]
+    pub fn invalid_utf8(&self) -> &'static str {
+        self.inner.invalid_utf8().name()
+    }
+
+    /// Encoding of the scanned bytes, legacy encodings are decoded to UTF-8 before the search
+    #[getter]
+    pub fn encoding(&self) -> &'static str {
+        self.inner.encoding().name()
+    }
+
+    /// madvise hint applied to memory-mapped files
+    #[getter]
+    pub fn madvise(&self) -> &'static str {
+        self.inner.mmap_options().madvise.name()
+    }
+
+    #[getter]
+    pub fn huge_pages(&self) -> bool {
+        self.inner.mmap_options().huge_pages
+    }
+
+    #[getter]
+    pub fn prefault(&self) -> bool {
+        self.inner.mmap_options().prefault
+    }
+
+    /// Number of patterns, empty patterns are dropped when building the matcher
+    #[getter]
+    pub fn pattern_count(&self) -> usize {
+        self.inner.patterns().len()
+    }
+
+    /// Number of states of the pattern trie, whatever the engine
+    #[getter]
+    pub fn state_count(&self, py: Python<'_>) -> usize {
+        py.allow_threads(|| self.inner.state_count())
+    }
+
+    /// Milliseconds taken to build the matcher
+    #[getter]
+    pub fn build_time_ms(&self) -> f64 {
+        self.inner.build_time().as_secs_f64() * 1000.0
+    }
+
+    /// Heap bytes used by the search engine and the patterns
+    pub fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
+    }
+
+    /// Number of patterns, empty patterns are dropped when building the matcher
+    fn __len__(&self) -> usize {
+        self.inner.patterns().len()
+    }
+
+    #[getter]
+    pub fn patterns(&self) -> Vec<String> {
+        self.inner.patterns().to_vec()
+    }
+
+    #[getter]
+    pub fn overlapping(&self) -> bool {
+        self.inner.overlapping()
+    }
+
+    #[getter]
+    pub fn case_insensitive(&self) -> bool {
+        self.inner.case_insensitive()
+    }
+
+    #[getter]
+    pub fn whole_word(&self) -> bool {
+        self.inner.whole_word()
+    }
+
+    /// Class of the bytes counting as word characters for whole_word
+    #[getter]
+    pub fn word_chars(&self) -> String {
+        self.inner.word_chars().spec()
+    }
+
+    #[getter]
+    pub fn prefer_longer(&self) -> bool {
+        self.inner.prefer_longer()
+    }
+
+    /// Name of the post-processing strategy applied to results, if any
+    #[getter]
+    pub fn postprocess(&self) -> Option<&'static str> {
+        self.inner.postprocess().map(|p| p.name())
+    }
+
+    #[pyo3(signature = (path, overlapping=None, whole_word=None, delimiter=None, encoding=None, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_file(
+        &self,
+        py: Python<'_>,
+        path: String,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        delimiter: Option<&Bound<'_, PyAny>>,
+        encoding: Option<&str>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Vec<PyLineMatch>> {
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let delimiter = match delimiter {
+            Some(delimiter) => extract_delimiter(delimiter)?,
+            None => b"\n".to_vec(),
+        };
+        let matches = py
+            .allow_threads(|| matcher.match_file_records(&path, &delimiter))
+            .map_err(io_error)?;
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(matches
+            .into_iter()
+            .map(|(line, start, end, pattern_idx)| {
+                (
+                    line,
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Parallel line-based matching of large files with memory mapping
+    /// Returns the same (line_number, start_index, end_index, matched_pattern) tuples as
+    /// match_file, in the same order, blocks of whole lines being searched in parallel
+    /// chunk_size and n_threads default to "auto", sized from the file, the available cores
+    /// and the pattern set
+    #[pyo3(signature = (path, chunk_size=None, n_threads=None, overlapping=None, whole_word=None, encoding=None, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_file_parallel(
+        &self,
+        py: Python<'_>,
+        path: String,
+        chunk_size: Option<&Bound<'_, PyAny>>,
+        n_threads: Option<&Bound<'_, PyAny>>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        encoding: Option<&str>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Vec<PyLineMatch>> {
+        let chunk_size = extract_auto(chunk_size, "chunk_size")?;
+        let n_threads = extract_auto(n_threads, "n_threads")?;
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let matches = py.allow_threads(|| {
+            let chunk_size = self.chunk_size(&path, chunk_size, n_threads)?;
+            matcher
+                .match_file_parallel(&path, chunk_size, n_threads)
+                .map_err(io_error)
+        })?;
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(matches
+            .into_iter()
+            .map(|(line, start, end, pattern_idx)| {
+                (
+                    line,
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Faster file matching using memory mapping for large files
+    /// Returns a list of (byte_offset, start_index, end_index, matched_pattern) tuples
+    /// chunk_size defaults to "auto", sized from the file and the pattern set
+    /// With `stats`, returns a (matches, ScanStats) tuple
+    #[pyo3(signature = (path, chunk_size=None, overlapping=None, whole_word=None, ignore_spans=None, encoding=None, stats=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_file_memmap(
+        &self,
+        py: Python<'_>,
+        path: String,
+        chunk_size: Option<&Bound<'_, PyAny>>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        ignore_spans: Option<Vec<(usize, usize)>>,
+        encoding: Option<&str>,
+        stats: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let chunk_size = extract_auto(chunk_size, "chunk_size")?;
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let ignore_spans = ignore_spans.map(IgnoreSpans::new).transpose()?;
+        let (matches, stats) = py.allow_threads(|| {
+            recorded(
+                stats,
+                || file_size(&path),
+                || {
+                    let chunk_size = self.chunk_size(&path, chunk_size, Some(1))?;
+                    matcher
+                        .match_file_memmap(&path, chunk_size)
+                        .map(|matches| without_spans(matches, &ignore_spans))
+                        .map_err(io_error)
+                },
+            )
+        });
+        self.resolve_with_stats(py, matches?, stats)
+    }
+
+    /// Parallel matching of large files with memory mapping
+    /// Splits the file into chunks and processes them in parallel
+    /// chunk_size and n_threads default to "auto", sized from the file, the available cores
+    /// and the pattern set
+    /// With `stats`, returns a (matches, ScanStats) tuple
+    #[pyo3(signature = (path, chunk_size=None, n_threads=None, overlapping=None, whole_word=None, ignore_spans=None, encoding=None, stats=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_file_memmap_parallel(
+        &self,
+        py: Python<'_>,
+        path: String,
+        chunk_size: Option<&Bound<'_, PyAny>>,
+        n_threads: Option<&Bound<'_, PyAny>>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        ignore_spans: Option<Vec<(usize, usize)>>,
+        encoding: Option<&str>,
+        stats: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let chunk_size = extract_auto(chunk_size, "chunk_size")?;
+        let n_threads = extract_auto(n_threads, "n_threads")?;
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let ignore_spans = ignore_spans.map(IgnoreSpans::new).transpose()?;
+        let (matches, stats) = py.allow_threads(|| {
+            recorded(
+                stats,
+                || file_size(&path),
+                || {
+                    let chunk_size = self.chunk_size(&path, chunk_size, n_threads)?;
+                    matcher
+                        .match_file_memmap_parallel(&path, chunk_size, n_threads)
+                        .map(|matches| without_spans(matches, &ignore_spans))
+                        .map_err(io_error)
+                },
+            )
+        });
+        self.resolve_with_stats(py, matches?, stats)
+    }
+
+    /// Chunking picked by chunk_size="auto" for a file, as (chunk_size, n_threads)
+    #[pyo3(signature = (path, n_threads=None))]
+    pub fn tune(&self, path: String, n_threads: Option<usize>) -> PyResult<(usize, usize)> {
+        let tuning = self.inner.tune_file(&path, n_threads).map_err(io_error)?;
+        Ok((tuning.chunk_size, tuning.workers))
+    }
+
+    /// Raw byte matching on provided byte data
+    /// This allows for maximum performance by avoiding file I/O overhead
+    /// The caller is responsible for loading the bytes
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples, or with `stats` a
+    /// (matches, ScanStats) tuple
+    #[pyo3(signature = (data, overlapping=None, whole_word=None, ignore_spans=None, encoding=None, stats=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_bytes(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        ignore_spans: Option<Vec<(usize, usize)>>,
+        encoding: Option<&str>,
+        stats: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let ignore_spans = ignore_spans.map(IgnoreSpans::new).transpose()?;
+        let (matches, stats) = py.allow_threads(|| {
+            recorded(
+                stats,
+                || data.len(),
+                || without_spans(matcher.match_bytes(data), &ignore_spans),
+            )
+        });
+        self.resolve_with_stats(py, matches, stats)
+    }
+
+    /// Stream-based file matching that processes the file in chunks
+    /// Useful for very large files or when memory efficiency is important
+    /// `max_bytes_per_sec` throttles reads so background scans don't starve other disk users
+    /// Returns a list of (byte_offset, start_index, end_index, matched_pattern) tuples, or
+    /// with `stats` a (matches, ScanStats) tuple
+    #[pyo3(signature = (path, buffer_size=None, max_bytes_per_sec=None, overlapping=None, whole_word=None, encoding=None, stats=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_file_stream(
+        &self,
+        py: Python<'_>,
+        path: String,
+        buffer_size: Option<usize>,
+        max_bytes_per_sec: Option<u64>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        encoding: Option<&str>,
+        stats: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let (matches, stats) = py.allow_threads(|| {
+            recorded(
+                stats,
+                || file_size(&path),
+                || {
+                    let buffer_size = buffer_size.unwrap_or(8 * 1024 * 1024);
+                    matcher
+                        .match_file_stream(&path, buffer_size, max_bytes_per_sec)
+                        .map_err(io_error)
+                },
+            )
+        });
+        self.resolve_with_stats(py, matches?, stats)
+    }
+
+    /// Stream-based matching from any Read implementer (files, network streams, etc.)
+    /// Returns a list of (byte_offset, start_index, end_index, matched_pattern) tuples
+    #[pyo3(signature = (stream, buffer_size=None, overlapping=None, whole_word=None, ignore_spans=None, encoding=None, stats=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_stream(
+        &self,
+        py: Python<'_>,
+        stream: &[u8],
+        buffer_size: Option<usize>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        ignore_spans: Option<Vec<(usize, usize)>>,
+        encoding: Option<&str>,
+        stats: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let ignore_spans = ignore_spans.map(IgnoreSpans::new).transpose()?;
+        let (matches, stats) = py.allow_threads(|| {
+            recorded(
+                stats,
+                || stream.len(),
+                || {
+                    matcher
+                        .match_stream(stream, buffer_size.unwrap_or(8 * 1024 * 1024))
+                        .map(|matches| without_spans(matches, &ignore_spans))
+                        .map_err(io_error)
+                },
+            )
+        });
+        self.resolve_with_stats(py, matches?, stats)
+    }
+
+    /// Parallel matching of a readable binary file-like object, e.g. a pipe, a socket file or
+    /// gzip.open(path), which cannot be memory-mapped
+    /// The object is read in chunks of `buffer_size` bytes that n_threads workers scan
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples
+    #[pyo3(signature = (readable, buffer_size=None, n_threads=None, overlapping=None, whole_word=None, encoding=None, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_readable_parallel(
+        &self,
+        py: Python<'_>,
+        readable: Py<PyAny>,
+        buffer_size: Option<usize>,
+        n_threads: Option<usize>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        encoding: Option<&str>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Vec<PyMatch>> {
+        let matcher =
+            self.overridden(overlapping, whole_word, extract_encoding(encoding)?, tags)?;
+        let matches = py.allow_threads(|| {
+            let buffer_size = buffer_size.unwrap_or(8 * 1024 * 1024);
+            matcher
+                .match_reader_parallel(PyReader(readable), buffer_size, n_threads)
+                .map_err(io_error)
+        })?;
+        Ok(self.resolve(py, matches))
+    }
+
+    /// Density rule on provided byte data
+    /// Reports regions where at least `min_matches` matches fall inside a sliding window
+    /// of `window` bytes (or lines), optionally counting only the given patterns, or the
+    /// patterns with one of the given tags
+    /// Returns a list of (start_index, end_index, match_count) tuples, or with `evidence`
+    /// (start_index, end_index, match_count, matches) tuples listing the matches counted
+    #[pyo3(signature = (data, min_matches, window, unit=None, patterns=None, evidence=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_density(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        min_matches: usize,
+        window: usize,
+        unit: Option<&str>,
+        patterns: Option<Vec<String>>,
+        evidence: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let unit = parse_window_unit(unit)?;
+        let counted = self.pattern_mask(patterns)?;
+        let matcher = self.overridden(None, None, None, tags)?;
+        let regions = py.allow_threads(|| {
+            matcher.match_density_regions(
+                data,
+                min_matches,
+                window,
+                unit,
+                counted.as_deref(),
+                evidence,
+            )
+        });
+        self.density_regions(py, regions, evidence)
+    }
+
+    /// Density rule on a memory-mapped file
+    /// Returns a list of (start_index, end_index, match_count) tuples, or with `evidence`
+    /// (start_index, end_index, match_count, matches) tuples
+    #[pyo3(signature = (path, min_matches, window, unit=None, patterns=None, evidence=false, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_file_density(
+        &self,
+        py: Python<'_>,
+        path: String,
+        min_matches: usize,
+        window: usize,
+        unit: Option<&str>,
+        patterns: Option<Vec<String>>,
+        evidence: bool,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let unit = parse_window_unit(unit)?;
+        let counted = self.pattern_mask(patterns)?;
+        let matcher = self.overridden(None, None, None, tags)?;
+
+        let regions = py.allow_threads(|| {
+            let mmap = map_file(&path)?;
+
+            PyResult::Ok(matcher.match_density_regions(
+                &mmap,
+                min_matches,
+                window,
+                unit,
+                counted.as_deref(),
+                evidence,
+            ))
+        })?;
+        self.density_regions(py, regions, evidence)
+    }
+
+    /// Whether every one of the given patterns has a match in the provided bytes, the scan
+    /// stopping as soon as all of them have been seen
+    pub fn contains_all(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        patterns: Vec<String>,
+    ) -> PyResult<bool> {
+        Ok(py.allow_threads(|| self.inner.contains_all(data, &patterns))?)
+    }
+
+    /// Match provided bytes, folding repeats of identical findings (same pattern and matched
+    /// text) into the first instead of reporting each of them
+    /// A repeat is folded when it lies at most `window` bytes (or lines) after the previous
+    /// occurrence, or anywhere in the data when `window` is None
+    /// Returns a list of (start_index, end_index, matched_pattern, count) tuples
+    #[pyo3(signature = (data, window=None, unit=None))]
+    pub fn match_dedup(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        window: Option<usize>,
+        unit: Option<&str>,
+    ) -> PyResult<Vec<PyCountedMatch>> {
+        let options = DedupOptions {
+            window,
+            unit: parse_window_unit(unit)?,
+        };
+        let findings = py.allow_threads(|| self.inner.match_dedup(data, options));
+        Ok(self.resolve_counted(py, findings))
+    }
+
+    /// Match a memory-mapped file, folding repeats of identical findings, see match_dedup
+    /// Returns a list of (start_index, end_index, matched_pattern, count) tuples
+    #[pyo3(signature = (path, window=None, unit=None))]
+    pub fn match_file_dedup(
+        &self,
+        py: Python<'_>,
+        path: &str,
+        window: Option<usize>,
+        unit: Option<&str>,
+    ) -> PyResult<Vec<PyCountedMatch>> {
+        let options = DedupOptions {
+            window,
+            unit: parse_window_unit(unit)?,
+        };
+        let findings = py
+            .allow_threads(|| self.inner.match_file_dedup(path, options))
+            .map_err(io_error)?;
+        Ok(self.resolve_counted(py, findings))
+    }
+
+    /// Match counts per bucket of provided bytes, without retrieving the matches
+    /// Buckets are ranges of `bucket_size` bytes, or periods of `interval` seconds of the
+    /// timestamp parsed with `timestamp_format` at the start of the line of each match
+    /// Returns ([(bucket_start, bucket_end, match_count)], unbucketed) with the buckets holding
+    /// matches in order, unbucketed counting the matches before the first timestamped line
+    #[pyo3(signature = (data, bucket_size=None, timestamp_format=None, interval=3600, patterns=None))]
+    pub fn match_histogram(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        bucket_size: Option<usize>,
+        timestamp_format: Option<&str>,
+        interval: u64,
+        patterns: Option<Vec<String>>,
+    ) -> PyResult<PyHistogram> {
+        let buckets = parse_buckets(bucket_size, timestamp_format, interval)?;
+        let counted = self.pattern_mask(patterns)?;
+        let histogram = py.allow_threads(|| {
+            self.inner
+                .match_histogram(data, &buckets, counted.as_deref())
+        });
+        Ok((histogram.buckets, histogram.unbucketed))
+    }
+
+    /// Match counts per bucket of a memory-mapped file, see match_histogram
+    #[pyo3(signature = (path, bucket_size=None, timestamp_format=None, interval=3600, patterns=None))]
+    pub fn match_file_histogram(
+        &self,
+        py: Python<'_>,
+        path: &str,
+        bucket_size: Option<usize>,
+        timestamp_format: Option<&str>,
+        interval: u64,
+        patterns: Option<Vec<String>>,
+    ) -> PyResult<PyHistogram> {
+        let buckets = parse_buckets(bucket_size, timestamp_format, interval)?;
+        let counted = self.pattern_mask(patterns)?;
+        let histogram = py
+            .allow_threads(|| {
+                self.inner
+                    .match_file_histogram(path, &buckets, counted.as_deref())
+            })
+            .map_err(io_error)?;
+        Ok((histogram.buckets, histogram.unbucketed))
+    }
+
+    /// Distinct matched strings per pattern, in bytes or in the memory-mapped file at a path
+    /// Returns a dict mapping each pattern that matched to the set of matched strings, with at
+    /// most `max_values` strings per pattern, optionally lowercased or uppercased first
+    #[pyo3(signature = (data_or_path, max_values=None, normalize=None))]
+    pub fn extract_unique(
+        &self,
+        py: Python<'_>,
+        data_or_path: &Bound<'_, PyAny>,
+        max_values: Option<usize>,
+        normalize: Option<&str>,
+    ) -> PyResult<HashMap<String, HashSet<String>>> {
+        let options = UniqueOptions {
+            max_values,
+            normalize: normalize
+                .map(|name| {
+                    Normalize::parse(name).ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Unknown normalization '{}', expected 'lower' or 'upper'",
+                            name
+                        ))
+                    })
+                })
+                .transpose()?,
+        };
+
+        let values = if let Ok(data) = data_or_path.downcast::<PyBytes>() {
+            let data = data.as_bytes();
+            py.allow_threads(|| self.inner.extract_unique(data, &options))
+        } else {
+            let path: String = data_or_path
+                .extract()
+                .map_err(|_| PyTypeError::new_err("data_or_path must be bytes or a path"))?;
+            py.allow_threads(|| self.inner.extract_unique_file(&path, &options))
+                .map_err(io_error)?
+        };
+
+        // Patterns listed twice share their strings
+        let mut unique: HashMap<String, HashSet<String>> = HashMap::new();
+        for (pattern_idx, strings) in values {
+            unique
+                .entry(self.inner.pattern(pattern_idx).to_string())
+                .or_default()
+                .extend(strings);
+        }
+        Ok(unique)
+    }
+
+    /// Original-case forms matched by each pattern and their counts, in bytes or in the
+    /// memory-mapped file at a path
+    /// Returns a dict mapping each pattern that matched to a dict of the forms found, as they
+    /// appear in the data, and how many times each was found
+    pub fn case_variants(
+        &self,
+        py: Python<'_>,
+        data_or_path: &Bound<'_, PyAny>,
+    ) -> PyResult<HashMap<String, HashMap<String, usize>>> {
+        let found = if let Ok(data) = data_or_path.downcast::<PyBytes>() {
+            let data = data.as_bytes();
+            py.allow_threads(|| self.inner.case_variants(data))
+        } else {
+            let path: String = data_or_path
+                .extract()
+                .map_err(|_| PyTypeError::new_err("data_or_path must be bytes or a path"))?;
+            py.allow_threads(|| self.inner.case_variants_file(&path))
+                .map_err(io_error)?
+        };
+
+        // Patterns listed twice add up their counts
+        let mut variants: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        for (pattern_idx, forms) in found {
+            let counts = variants
+                .entry(self.inner.pattern(pattern_idx).to_string())
+                .or_default();
+            for (form, count) in forms {
+                *counts.entry(form).or_default() += count;
+            }
+        }
+        Ok(variants)
+    }
+
+    /// The last `count` matches of the data, the last one first, found by searching back from
+    /// the end instead of scanning forward and discarding
+    /// Every occurrence counts whatever the overlapping setting, post-processing is not applied
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples
+    #[pyo3(signature = (data, count=1))]
+    pub fn find_last(&self, py: Python<'_>, data: &[u8], count: usize) -> PyResult<Vec<PyMatch>> {
+        check_count(count)?;
+        let matches = py.allow_threads(|| self.inner.find_last(data, count));
+        Ok(self.resolve(py, matches))
+    }
+
+    /// The last `count` matches of a memory-mapped file, the last one first, see find_last
+    #[pyo3(signature = (path, count=1))]
+    pub fn find_last_file(
+        &self,
+        py: Python<'_>,
+        path: String,
+        count: usize,
+    ) -> PyResult<Vec<PyMatch>> {
+        check_count(count)?;
+        let matches = py
+            .allow_threads(|| self.inner.find_last_file(&path, count))
+            .map_err(io_error)?;
+        Ok(self.resolve(py, matches))
+    }
+
+    /// Raw byte matching returning a MatchCollection instead of a list of tuples
+    pub fn match_bytes_collection(&self, py: Python<'_>, data: &[u8]) -> MatchCollection {
+        py.allow_threads(|| {
+            MatchCollection::new(self.inner.shared_patterns(), self.inner.match_bytes(data))
+                .with_schema(self.inner.output_schema())
+                .with_tags(self.inner.shared_tags())
+        })
+    }
+
+    /// Parallel memory-mapped file matching returning a MatchCollection sorted by offset
+    #[pyo3(signature = (path, chunk_size=None, n_threads=None))]
+    pub fn match_file_collection(
+        &self,
+        py: Python<'_>,
+        path: String,
+        chunk_size: Option<&Bound<'_, PyAny>>,
+        n_threads: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<MatchCollection> {
+        let chunk_size = extract_auto(chunk_size, "chunk_size")?;
+        let n_threads = extract_auto(n_threads, "n_threads")?;
+        py.allow_threads(|| {
+            let chunk_size = self.chunk_size(&path, chunk_size, n_threads)?;
+            let mut res = self
+                .inner
+                .match_file_memmap_parallel(&path, chunk_size, n_threads)
+                .map_err(io_error)?;
+            res.sort_unstable();
+            Ok(MatchCollection::new(self.inner.shared_patterns(), res)
+                .with_schema(self.inner.output_schema())
+                .with_tags(self.inner.shared_tags()))
+        })
+    }
+
+    /// Raw byte matching returning a NumPy structured array of (start, end, pattern_id)
+    /// records, pattern_id indexing `patterns`, filled in Rust without a Python object per match
+    pub fn match_bytes_np<'py>(&self, py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+        let numpy = py.import("numpy")?;
+        let records = py.allow_threads(|| {
+            let matches = self.inner.match_bytes(data);
+            let mut records = Vec::with_capacity(20 * matches.len());
+            for (start, end, pattern_idx) in matches {
+                records.extend_from_slice(&(start as u64).to_le_bytes());
+                records.extend_from_slice(&(end as u64).to_le_bytes());
+                records.extend_from_slice(&pattern_idx.as_u32().to_le_bytes());
+            }
+            records
+        });
+        let dtype = numpy.call_method1(
+            "dtype",
+            (vec![
+                ("start", "<u8"),
+                ("end", "<u8"),
+                ("pattern_id", "<u4"),
+            ],),
+        )?;
+        // An array over a bytearray is writable
+        numpy.call_method1("frombuffer", (PyByteArray::new(py, &records), dtype))
+    }
+
+    /// Raw byte matching reporting the UTF-8 character offsets of the matches too
+    /// Returns a list of (start_index, end_index, matched_pattern, char_start, char_end) tuples
+    pub fn match_bytes_chars(&self, py: Python<'_>, data: &[u8]) -> Vec<PyCharMatch> {
+        let (matches, chars) = py.allow_threads(|| {
+            let matches = self.inner.match_bytes(data);
+            let chars = lines::char_offsets(data, &matches);
+            (matches, chars)
+        });
+        self.resolve_chars(py, matches, chars)
+    }
+
+    /// Parallel memory-mapped file matching reporting the UTF-8 character offsets of the
+    /// matches too, sorted by offset
+    #[pyo3(signature = (path, chunk_size=None, n_threads=None))]
+    pub fn match_file_chars(
+        &self,
+        py: Python<'_>,
+        path: String,
+        chunk_size: Option<&Bound<'_, PyAny>>,
+        n_threads: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Vec<PyCharMatch>> {
+        let chunk_size = extract_auto(chunk_size, "chunk_size")?;
+        let n_threads = extract_auto(n_threads, "n_threads")?;
+        let (matches, chars) = py.allow_threads(|| {
+            let chunk_size = self.chunk_size(&path, chunk_size, n_threads)?;
+            let mmap = self.inner.map_file(&path).map_err(io_error)?;
+            let mut matches = self
+                .inner
+                .match_file_memmap_parallel(&path, chunk_size, n_threads)
+                .map_err(io_error)?;
+            matches.sort_unstable();
+            let chars = lines::char_offsets(&mmap, &matches);
+            Ok::<_, PyErr>((matches, chars))
+        })?;
+        Ok(self.resolve_chars(py, matches, chars))
+    }
+
+    /// Memory-mapped file matching for result sets too large for memory: at most
+    /// `max_in_memory` matches are kept in memory, the others are spilled to a temporary file
+    /// in `spill_dir`, and the matches are read back in order while iterating
+    #[pyo3(signature = (path, max_in_memory=None, spill_dir=None))]
+    pub fn match_file_spooled(
+        &self,
+        py: Python<'_>,
+        path: String,
+        max_in_memory: Option<usize>,
+        spill_dir: Option<PathBuf>,
+    ) -> PyResult<SpooledMatches> {
+        if self.inner.postprocess().is_some() {
+            return Err(PyValueError::new_err(
+                "Spooled scans cannot post-process matches, build the matcher without postprocess",
+            ));
+        }
+        if self.inner.min_counts().iter().any(|&count| count > 1) {
+            return Err(PyValueError::new_err(
+                "Spooled scans cannot apply min counts, build the matcher without min_count",
+            ));
+        }
+        let defaults = SpoolOptions::default();
+        let options = SpoolOptions {
+            max_in_memory: max_in_memory.unwrap_or(defaults.max_in_memory),
+            dir: spill_dir,
+        };
+        let spool = py
+            .allow_threads(|| self.inner.match_file_spooled(&path, options))
+            .map_err(io_error)?;
+        SpooledMatches::new(spool, self.inner.shared_patterns())
+    }
+
+    /// Match a memory-mapped file and write the matches to a sink as they are found
+    /// Only a summary is returned, so the matches never need to fit in memory
+    /// `sink` is a file path (NDJSON, CSV or SQLite), an http(s) URL or a WebhookSink
+    /// The file format is inferred from the path extension unless given explicitly
+    /// With `provenance` every match carries the scan id, host, matcher fingerprint, source
+    /// path and start time of the scan
+    #[pyo3(signature = (path, sink, format=None, scan_id=None, provenance=false))]
+    pub fn match_file_to_sink(
+        &self,
+        py: Python<'_>,
+        path: String,
+        sink: &Bound<'_, PyAny>,
+        format: Option<&str>,
+        scan_id: Option<String>,
+        provenance: bool,
+    ) -> PyResult<SinkSummary> {
+        let sink = parse_sink(sink, format)?;
+
+        // Sinks may block on disk or network I/O, so other Python threads keep running meanwhile
+        py.allow_threads(|| {
+            self.inner
+                .match_file_to_sink(&path, &sink, scan_id, provenance)
+                .map_err(io_error)
+        })
+    }
+
+    /// Match provided bytes and write the matches to a sink as they are found
+    #[pyo3(signature = (data, sink, format=None, scan_id=None, provenance=false))]
+    pub fn match_bytes_to_sink(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        sink: &Bound<'_, PyAny>,
+        format: Option<&str>,
+        scan_id: Option<String>,
+        provenance: bool,
+    ) -> PyResult<SinkSummary> {
+        let sink = parse_sink(sink, format)?;
+
+        py.allow_threads(|| {
+            self.inner
+                .match_to_sink(data, &sink, scan_id, None, provenance)
+                .map_err(io_error)
+        })
+    }
+
+    /// Scan a memory-mapped file and format the matches grep-style, see format_grep
+    #[pyo3(signature = (path, color=false))]
+    pub fn grep(&self, py: Python<'_>, path: String, color: bool) -> PyResult<String> {
+        if self.inner.hashed() {
+            return Err(PyValueError::new_err(
+                "grep output shows the matched lines, which hash_key is meant to keep out of results",
+            ));
+        }
+        py.allow_threads(|| {
+            let mmap = map_file(&path)?;
+            let spans: Vec<(usize, usize)> = self
+                .inner
+                .match_bytes(&mmap)
+                .into_iter()
+                .map(|(start, end, _)| (start, end))
+                .collect();
+            Ok(highlight::to_grep(&path, &mmap, &spans, color))
+        })
+    }
+
+    /// Scan files in parallel and build a report of the run, rendered with to_html or to_markdown
+    /// Files that cannot be read are listed in the report instead of raising, the scan only
+    /// failing when it cannot be recorded in the audit log
+    #[pyo3(signature = (paths, context=40, max_findings=Some(100), title=None, scan_id=None))]
+    pub fn scan_report(
+        &self,
+        py: Python<'_>,
+        paths: Vec<String>,
+        context: usize,
+        max_findings: Option<usize>,
+        title: Option<String>,
+        scan_id: Option<String>,
+    ) -> PyResult<ScanReport> {
+        let options = ReportOptions {
+            title,
+            scan_id,
+            context,
+            max_findings,
+        };
+        py.allow_threads(|| ScanReport::scan(&self.inner, &paths, &options))
+            .map_err(io_error)
+    }
+
+    /// Scan every regular file under a directory in parallel, largest files first
+    /// Returns (matches, summaries): (path, start_index, end_index, matched_pattern) tuples
+    /// sorted by path then offset, and a FileSummary of every file visited, sorted by path,
+    /// unreadable files being summarized with their error instead of failing the scan
+    /// `.gitignore` and `.volutaignore` files are honored unless `ignore_files` is False
+    /// With `first_only`, only the first match of every pattern in each file is kept
+    /// `max_bytes_per_sec` throttles the reads of the whole walk, not of each file
+    #[pyo3(signature = (path, recursive=None, follow_links=None, max_file_size=None, ignore_files=None, first_only=false, max_bytes_per_sec=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_dir(
+        &self,
+        py: Python<'_>,
+        path: String,
+        recursive: Option<bool>,
+        follow_links: Option<bool>,
+        max_file_size: Option<u64>,
+        ignore_files: Option<bool>,
+        first_only: bool,
+        max_bytes_per_sec: Option<u64>,
+    ) -> PyResult<(Vec<PyDirMatch>, Vec<FileSummary>)> {
+        let defaults = DirOptions::default();
+        let options = DirOptions {
+            recursive: recursive.unwrap_or(defaults.recursive),
+            follow_links: follow_links.unwrap_or(defaults.follow_links),
+            max_file_size: max_file_size.or(defaults.max_file_size),
+            ignore_files: ignore_files.unwrap_or(defaults.ignore_files),
+            first_only,
+            max_bytes_per_sec: max_bytes_per_sec.or(defaults.max_bytes_per_sec),
+        };
+        let scan = py
+            .allow_threads(|| DirScan::scan(&self.inner, &path, &options))
+            .map_err(io_error)?;
+
+        // Matches of a file share the Python string of its path
+        let paths: Vec<Py<PyString>> = scan
+            .files
+            .iter()
+            .map(|file| PyString::new(py, &file.path).unbind())
+            .collect();
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        let matches = scan
+            .matches
+            .into_iter()
+            .map(|(file, start, end, pattern_idx)| {
+                (
+                    paths[file].clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect();
+        Ok((matches, scan.files))
+    }
+
+    /// Scan the blobs of a git repository: the trees of `revisions`, HEAD by default, or with
+    /// `history` every blob added or modified by the commits reachable from them
+    /// Returns (commit, path, start_index, end_index, matched_pattern) tuples, each distinct
+    /// blob being scanned once and reported at the first commit and path it was found at
+    #[pyo3(signature = (path, revisions=None, history=None))]
+    pub fn match_git_repo(
+        &self,
+        py: Python<'_>,
+        path: String,
+        revisions: Option<Vec<String>>,
+        history: Option<bool>,
+    ) -> PyResult<Vec<PyGitMatch>> {
+        let defaults = GitOptions::default();
+        let options = GitOptions {
+            revisions: revisions.unwrap_or(defaults.revisions),
+            history: history.unwrap_or(defaults.history),
+        };
+        let scan = py
+            .allow_threads(|| GitScan::scan(&self.inner, &path, &options))
+            .map_err(io_error)?;
+
+        // Matches of a blob share the Python strings of its commit and path
+        let mut commits: HashMap<&str, Py<PyString>> = HashMap::new();
+        let blobs: Vec<(Py<PyString>, Py<PyString>)> = scan
+            .blobs
+            .iter()
+            .map(|(commit, path)| {
+                let commit = commits
+                    .entry(commit)
+                    .or_insert_with(|| PyString::new(py, commit).unbind())
+                    .clone_ref(py);
+                (commit, PyString::new(py, path).unbind())
+            })
+            .collect();
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(scan
+            .matches
+            .into_iter()
+            .map(|(blob, start, end, pattern_idx)| {
+                (
+                    blobs[blob].0.clone_ref(py),
+                    blobs[blob].1.clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Scan the payloads of the HTTP responses of a WARC file, plain or gzipped, decoded from
+    /// their chunked transfer encoding and gzip or deflate content encoding
+    /// Returns (target_uri, start_index, end_index, matched_pattern) tuples sorted by record
+    /// then offset, offsets being those of the decoded payload
+    /// Records larger than `max_record_size` are skipped without being read into memory
+    #[pyo3(signature = (path, max_record_size=None))]
+    pub fn match_warc(
+        &self,
+        py: Python<'_>,
+        path: &str,
+        max_record_size: Option<u64>,
+    ) -> PyResult<Vec<PyDirMatch>> {
+        let options = WarcOptions { max_record_size };
+        let scan = py
+            .allow_threads(|| WarcScan::scan(&self.inner, path, &options))
+            .map_err(io_error)?;
+
+        // Matches of a record share the Python string of its URI
+        let uris: Vec<Py<PyString>> = scan
+            .records
+            .iter()
+            .map(|uri| PyString::new(py, uri).unbind())
+            .collect();
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(scan
+            .matches
+            .into_iter()
+            .map(|(record, start, end, pattern_idx)| {
+                (
+                    uris[record].clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Scan a raw disk image or block device in large sector-aligned chunks, whatever its
+    /// filesystem, or an E01 image when built with the ewf feature
+    /// Returns (sector, start_index, end_index, matched_pattern) tuples, the sector being the
+    /// one holding the first byte of the match
+    #[pyo3(signature = (path, sector_size=None, chunk_size=None, n_threads=None))]
+    pub fn match_disk_image(
+        &self,
+        py: Python<'_>,
+        path: &str,
+        sector_size: Option<usize>,
+        chunk_size: Option<usize>,
+        n_threads: Option<usize>,
+    ) -> PyResult<Vec<PyLineMatch>> {
+        for (name, value) in [
+            ("sector_size", sector_size),
+            ("chunk_size", chunk_size),
+            ("n_threads", n_threads),
+        ] {
+            if value == Some(0) {
+                return Err(PyValueError::new_err(format!("{} must be positive", name)));
+            }
+        }
+        let options = DiskOptions {
+            sector_size,
+            chunk_size,
+            n_threads,
+        };
+        let scan = py
+            .allow_threads(|| DiskScan::scan(&self.inner, path, options))
+            .map_err(io_error)?;
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(scan
+            .matches
+            .iter()
+            .map(|&(start, end, pattern_idx)| {
+                (
+                    scan.sector(start),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Scan the TCP and UDP payloads of a pcap or pcapng file, plain or gzipped, TCP streams
+    /// being reassembled best effort
+    /// Returns ((transport, src, src_port, dst, dst_port), start_index, end_index,
+    /// matched_pattern) tuples sorted by flow then offset, offsets being those of the flow's
+    /// reassembled payload
+    pub fn match_pcap(&self, py: Python<'_>, path: &str) -> PyResult<Vec<PyPcapMatch>> {
+        let scan = py
+            .allow_threads(|| PcapScan::scan(&self.inner, path))
+            .map_err(io_error)?;
+
+        // Matches of a flow share the Python tuple of its 5-tuple
+        let flows = scan
+            .flows
+            .iter()
+            .map(|flow| {
+                let tuple = (
+                    flow.transport.as_str(),
+                    flow.src.to_string(),
+                    flow.src_port,
+                    flow.dst.to_string(),
+                    flow.dst_port,
+                );
+                Ok(tuple.into_pyobject(py)?.unbind())
+            })
+            .collect::<PyResult<Vec<Py<PyTuple>>>>()?;
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(scan
+            .matches
+            .into_iter()
+            .map(|(flow, start, end, pattern_idx)| {
+                (
+                    flows[flow].clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Scan the readable memory of a running process, Linux only, regions being listed by
+    /// /proc/<pid>/maps and read from /proc/<pid>/mem
+    /// Returns (base_address, region, start_index, end_index, matched_pattern) tuples sorted
+    /// by address, offsets being relative to the base address of the region
+    #[pyo3(signature = (pid, include_files=true))]
+    pub fn match_process(
+        &self,
+        py: Python<'_>,
+        pid: u32,
+        include_files: bool,
+    ) -> PyResult<Vec<PyProcessMatch>> {
+        let scan = py
+            .allow_threads(|| ProcessScan::scan(&self.inner, pid, include_files))
+            .map_err(io_error)?;
+
+        // Matches of a region share the Python string of its name
+        let regions: Vec<Py<PyString>> = scan
+            .regions
+            .iter()
+            .map(|region| PyString::new(py, &region.name).unbind())
+            .collect();
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(scan
+            .matches
+            .into_iter()
+            .map(|(region, start, end, pattern_idx)| {
+                (
+                    scan.regions[region].start,
+                    regions[region].clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Scan the text and binary values of a database, streamed row by row: the tables of an
+    /// SQLite database, given by path, `file:` URI or `sqlite:///<path>` URL, or the rows of a
+    /// DB-API connection or cursor of any other database
+    /// Returns (table, column, key, start_index, end_index, matched_pattern) tuples sorted by
+    /// row then column, table being None for the rows of a query or cursor and key the value
+    /// of the key column, a tuple of them for several, or the row number
+    #[pyo3(signature = (source, tables=None, columns=None, query=None, key=None, batch_size=1000))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_db(
+        &self,
+        py: Python<'_>,
+        source: &Bound<'_, PyAny>,
+        tables: Option<Vec<String>>,
+        columns: Option<Vec<String>>,
+        query: Option<String>,
+        key: Option<Vec<String>>,
+        batch_size: usize,
+    ) -> PyResult<Vec<PyDbMatch>> {
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch_size must be positive"));
+        }
+        if query.is_some() && tables.is_some() {
+            return Err(PyValueError::new_err(
+                "tables and query cannot be combined, the query selects the rows scanned",
+            ));
+        }
+        let options = DbOptions {
+            tables,
+            columns,
+            query,
+            key,
+        };
+        if !source.hasattr("fetchmany")? && !source.hasattr("cursor")? {
+            let path: PathBuf = source.extract()?;
+            let source = path.to_string_lossy();
+            let scan = py
+                .allow_threads(|| DbScan::scan(&self.inner, &source, &options))
+                .map_err(io_error)?;
+            return self.db_matches(py, scan);
+        }
+
+        let (cursor, owned) = if source.hasattr("fetchmany")? {
+            (source.clone(), false)
+        } else {
+            (source.call_method0("cursor")?, true)
+        };
+        let res = self.match_db_cursor(py, &cursor, owned, &options, batch_size);
+        if owned {
+            cursor.call_method0("close")?;
+        }
+        res
+    }
+
+    /// Scan the string and bytes fields of the records of an Avro object container file,
+    /// uncompressed or compressed with deflate or snappy, decoded with the schema it embeds
+    /// Returns (record, field, start_index, end_index, matched_pattern) tuples sorted by
+    /// record then field, record counting from 0 and field being a path like "user.emails[0]"
+    pub fn match_avro(&self, py: Python<'_>, path: &str) -> PyResult<Vec<PyRecordMatch>> {
+        let scan = py
+            .allow_threads(|| AvroScan::scan(&self.inner, path))
+            .map_err(io_error)?;
+        Ok(self.record_matches(py, &scan.fields, scan.matches))
+    }
+
+    /// Scan the string, varchar, char and binary columns of the rows of an ORC file,
+    /// uncompressed or compressed with zlib, snappy or lz4, stripe by stripe
+    /// Returns (row, field, start_index, end_index, matched_pattern) tuples sorted by row
+    /// then column, row counting from 0 and field being a path like "user.emails[0]"
+    pub fn match_orc(&self, py: Python<'_>, path: &str) -> PyResult<Vec<PyRecordMatch>> {
+        let scan = py
+            .allow_threads(|| OrcScan::scan(&self.inner, path))
+            .map_err(io_error)?;
+        Ok(self.record_matches(py, &scan.fields, scan.matches))
+    }
+
+    /// Scan the MESSAGE field of journald entries as they are logged, writing the matches to
+    /// a sink, entries being read from `journalctl --output=export` run with `args`
+    /// Runs until the journal ends (when not following it), `max_records` entries were
+    /// scanned, no entry arrived for `idle_timeout` seconds, or KeyboardInterrupt
+    /// Offsets are those of a log holding the messages one per line
+    #[pyo3(signature = (sink, format=None, scan_id=None, follow=true, args=None, max_records=None, idle_timeout=None, journalctl="journalctl".to_string(), provenance=false))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_journald_to_sink(
+        &self,
+        py: Python<'_>,
+        sink: &Bound<'_, PyAny>,
+        format: Option<&str>,
+        scan_id: Option<String>,
+        follow: bool,
+        args: Option<Vec<String>>,
+        max_records: Option<u64>,
+        idle_timeout: Option<f64>,
+        journalctl: String,
+        provenance: bool,
+    ) -> PyResult<SinkSummary> {
+        let source = LogSource::Journald {
+            journalctl,
+            args: args.unwrap_or_default(),
+            follow,
+        };
+        let options = source_options(max_records, idle_timeout)?;
+        self.match_source_to_sink(py, source, sink, format, scan_id, provenance, options)
+    }
+
+    /// Scan syslog messages as they are received, writing the matches to a sink
+    /// `address` is `udp://host:port`, `tcp://host:port` (newline-delimited or octet-counted
+    /// messages) or `unix:///path` (a datagram socket created at the path, like /dev/log)
+    /// Runs until `max_records` messages were scanned, no message arrived for `idle_timeout`
+    /// seconds, or KeyboardInterrupt
+    #[pyo3(signature = (address, sink, format=None, scan_id=None, max_records=None, idle_timeout=None, provenance=false))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn match_syslog_to_sink(
+        &self,
+        py: Python<'_>,
+        address: &str,
+        sink: &Bound<'_, PyAny>,
+        format: Option<&str>,
+        scan_id: Option<String>,
+        max_records: Option<u64>,
+        idle_timeout: Option<f64>,
+        provenance: bool,
+    ) -> PyResult<SinkSummary> {
+        let source = LogSource::syslog(address)?;
+        let options = source_options(max_records, idle_timeout)?;
+        self.match_source_to_sink(py, source, sink, format, scan_id, provenance, options)
+    }
+
+    /// Scan only the lines added by a unified diff, as printed by `git diff` or `diff -u`
+    /// Returns (path, line_number, start_index, end_index, matched_pattern) tuples, lines
+    /// numbered in the new version of the file and offsets relative to the line
+    pub fn match_diff(&self, py: Python<'_>, diff: &[u8]) -> Vec<PyDiffMatch> {
+        let scan = py.allow_threads(|| DiffScan::scan(&self.inner, diff));
+        self.resolve_diff(py, scan)
+    }
+
+    /// Scan only the lines added by the changes staged in a git repository, for pre-commit
+    /// gates; returns the tuples of match_diff
+    #[pyo3(signature = (path="."))]
+    pub fn match_staged(&self, py: Python<'_>, path: &str) -> PyResult<Vec<PyDiffMatch>> {
+        let scan = py
+            .allow_threads(|| DiffScan::staged(&self.inner, path))
+            .map_err(io_error)?;
+        Ok(self.resolve_diff(py, scan))
+    }
+
+    /// Asynchronous match_file, the file is scanned on a worker thread
+    /// Returns an awaitable resolving to (line_number, start_index, end_index, matched_pattern) tuples
+    #[pyo3(signature = (path, overlapping=None, whole_word=None, delimiter=None, encoding=None, tags=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn amatch_file<'py>(
+        slf: Py<Self>,
+        py: Python<'py>,
+        path: String,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        delimiter: Option<Py<PyAny>>,
+        encoding: Option<String>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        run_async(py, move || {
+            Python::with_gil(|py| {
+                let delimiter = delimiter.as_ref().map(|d| d.bind(py));
+                slf.get().match_file(
+                    py,
+                    path,
+                    overlapping,
+                    whole_word,
+                    delimiter,
+                    encoding.as_deref(),
+                    tags,
+                )
+            })
+        })
+    }
+
+    /// Asynchronous match_bytes, the data is copied and scanned on a worker thread
+    /// Returns an awaitable resolving to (start_index, end_index, matched_pattern) tuples
+    #[pyo3(signature = (data, overlapping=None, whole_word=None, encoding=None, tags=None))]
+    pub fn amatch_bytes<'py>(
+        slf: Py<Self>,
+        py: Python<'py>,
+        data: Vec<u8>,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        encoding: Option<&str>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let encoding = extract_encoding(encoding)?;
+        run_async(py, move || {
+            let matcher = slf
+                .get()
+                .overridden(overlapping, whole_word, encoding, tags)?;
+            Ok(matcher.resolve(matcher.match_bytes(&data)))
+        })
+    }
+
+    /// Asynchronous matching of a readable binary file-like object, e.g. io.BytesIO or open(path, "rb")
+    /// The object is read in chunks of `buffer_size` bytes on a worker thread
+    /// Returns an awaitable resolving to (start_index, end_index, matched_pattern) tuples
+    #[pyo3(signature = (readable, buffer_size=None))]
+    pub fn amatch_readable<'py>(
+        slf: Py<Self>,
+        py: Python<'py>,
+        readable: Py<PyAny>,
+        buffer_size: Option<usize>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let buffer_size = buffer_size.unwrap_or(8 * 1024 * 1024);
+        run_async(py, move || {
+            let matcher = &slf.get().inner;
+            let matches = matcher
+                .match_reader(PyReader(readable), buffer_size)
+                .map_err(io_error)?;
+            Ok(matcher.resolve(matches))
+        })
+    }
+}
+
+impl TextMatcher {
+    /// The matcher with the per-call overlapping, whole_word and encoding overrides applied,
+    /// restricted to the patterns with one of `tags` if given
+    fn overridden(
+        &self,
+        overlapping: Option<bool>,
+        whole_word: Option<bool>,
+        encoding: Option<Encoding>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Cow<'_, Matcher>> {
+        let matcher = self.inner.with_overrides(MatchOverrides {
+            overlapping,
+            whole_word,
+            encoding,
+        });
+        Ok(match tags {
+            Some(tags) => Cow::Owned(matcher.with_tags(&tags)?),
+            None => matcher,
+        })
+    }
+
+    /// Resolve pattern indices to the shared Python strings of the reported names
+    fn resolve(&self, py: Python<'_>, matches: Vec<(usize, usize, PatternID)>) -> Vec<PyMatch> {
+        self.names
+            .resolve(py, &self.inner.shared_patterns(), matches)
+    }
+
+    /// Resolve pattern indices of matches and pair them with their character offsets
+    fn resolve_chars(
+        &self,
+        py: Python<'_>,
+        matches: Vec<(usize, usize, PatternID)>,
+        chars: Vec<(usize, usize)>,
+    ) -> Vec<PyCharMatch> {
+        self.resolve(py, matches)
+            .into_iter()
+            .zip(chars)
+            .map(|((start, end, name), (char_start, char_end))| {
+                (start, end, name, char_start, char_end)
+            })
+            .collect()
+    }
+
+    /// Scan the records of a log source until it ends or stops, checking for signals meanwhile
+    #[allow(clippy::too_many_arguments)]
+    fn match_source_to_sink(
+        &self,
+        py: Python<'_>,
+        source: LogSource,
+        sink: &Bound<'_, PyAny>,
+        format: Option<&str>,
+        scan_id: Option<String>,
+        provenance: bool,
+        options: SourceOptions,
+    ) -> PyResult<SinkSummary> {
+        let sink = parse_sink(sink, format)?;
+
+        // The scan runs without the GIL, taking it back periodically so Ctrl-C stops it
+        py.allow_threads(|| {
+            source.scan(&self.inner, &sink, scan_id, provenance, options, || {
+                Python::with_gil(|py| py.check_signals()).map_err(io::Error::other)
+            })
+        })
+        .map_err(io_error)
+    }
+
+    /// Resolved matches, paired with the statistics of their scan when recorded
+    fn resolve_with_stats(
+        &self,
+        py: Python<'_>,
+        matches: Vec<(usize, usize, PatternID)>,
+        stats: Option<ScanStats>,
+    ) -> PyResult<Py<PyAny>> {
+        match stats {
+            Some(mut stats) => {
+                stats.count_matches(&matches, &self.inner.shared_patterns());
+                Ok((self.resolve(py, matches), stats)
+                    .into_pyobject(py)?
+                    .into_any()
+                    .unbind())
+            }
+            None => Ok(self.resolve(py, matches).into_pyobject(py)?.unbind()),
+        }
+    }
+
+    /// Density regions as tuples, with their resolved matches when the evidence was kept
+    fn density_regions(
+        &self,
+        py: Python<'_>,
+        regions: Vec<DensityRegion>,
+        evidence: bool,
+    ) -> PyResult<Py<PyAny>> {
+        if !evidence {
+            let regions: Vec<_> = regions
+                .into_iter()
+                .map(|region| (region.start, region.end, region.count))
+                .collect();
+            return Ok(regions.into_pyobject(py)?.unbind());
+        }
+        let regions: Vec<_> = regions
+            .into_iter()
+            .map(|region| {
+                let matches = self.resolve(py, region.matches);
+                (region.start, region.end, region.count, matches)
+            })
+            .collect();
+        Ok(regions.into_pyobject(py)?.unbind())
+    }
+
+    /// Resolve pattern indices of deduplicated findings, keeping their counts
+    fn resolve_counted(
+        &self,
+        py: Python<'_>,
+        findings: Vec<(usize, usize, PatternID, usize)>,
+    ) -> Vec<PyCountedMatch> {
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        findings
+            .into_iter()
+            .map(|(start, end, pattern_idx, count)| {
+                (
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                    count,
+                )
+            })
+            .collect()
+    }
+
+    /// Resolve the file and pattern indices of the matches of a diff, matches of a file sharing
+    /// the Python string of its path
+    fn resolve_diff(&self, py: Python<'_>, scan: DiffScan) -> Vec<PyDiffMatch> {
+        let paths: Vec<Py<PyString>> = scan
+            .files
+            .iter()
+            .map(|path| PyString::new(py, path).unbind())
+            .collect();
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        scan.matches
+            .into_iter()
+            .map(|(file, line, start, end, pattern_idx)| {
+                (
+                    paths[file].clone_ref(py),
+                    line,
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect()
+    }
+
+    /// Build a per-pattern mask selecting the given patterns, None selects every pattern
+    fn pattern_mask(&self, patterns: Option<Vec<String>>) -> PyResult<Option<Vec<bool>>> {
+        match patterns {
+            Some(patterns) => Ok(Some(self.inner.pattern_mask(&patterns)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The given chunk size, or the one picked for the file when chunk_size is "auto"
+    fn chunk_size(
+        &self,
+        path: &str,
+        chunk_size: Option<usize>,
+        n_threads: Option<usize>,
+    ) -> PyResult<usize> {
+        match chunk_size {
+            Some(size) => Ok(size),
+            None => Ok(self
+                .inner
+                .tune_file(path, n_threads)
+                .map_err(io_error)?
+                .chunk_size),
+        }
+    }
+}
+
+/// Matcher confirming hits of cheap literal anchors with a regex run on a small window around
+/// each hit, so regexes only ever scan the neighbourhood of candidates
+/// `rules` is a list of (name, anchors, regex) tuples, matches report the rule name
+#[pyclass(frozen)]
+pub struct ConfirmMatcher {
+    inner: Confirmer,
+    names: PyNames,
+}
+
+#[pymethods]
+impl ConfirmMatcher {
+    #[new]
+    #[pyo3(signature = (rules, window=None, case_insensitive=None))]
+    pub fn new(
+        rules: Vec<(String, Vec<String>, String)>,
+        window: Option<usize>,
+        case_insensitive: Option<bool>,
+    ) -> PyResult<Self> {
+        let defaults = ConfirmOptions::default();
+        let rules = rules
+            .into_iter()
+            .map(|(name, anchors, regex)| Rule {
+                name,
+                anchors,
+                regex,
+            })
+            .collect();
+        let options = ConfirmOptions {
+            window: window.unwrap_or(defaults.window),
+            case_insensitive: case_insensitive.unwrap_or(defaults.case_insensitive),
+        };
+
+        Ok(Self {
+            inner: Confirmer::new(rules, options)?,
+            names: PyNames::default(),
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConfirmMatcher(rules={}, window={}, case_insensitive={})",
+            self.inner.rules().len(),
+            self.inner.window(),
+            py_bool(self.inner.case_insensitive())
+        )
+    }
+
+    /// Number of rules
+    fn __len__(&self) -> usize {
+        self.inner.rules().len()
+    }
+
+    /// Names of the rules
+    #[getter]
+    pub fn rules(&self) -> Vec<String> {
+        self.inner.rules().to_vec()
+    }
+
+    #[getter]
+    pub fn window(&self) -> usize {
+        self.inner.window()
+    }
+
+    #[getter]
+    pub fn case_insensitive(&self) -> bool {
+        self.inner.case_insensitive()
+    }
+
+    /// Match provided bytes
+    /// Returns a list of (start_index, end_index, rule_name) tuples sorted by offset
+    pub fn match_bytes(&self, py: Python<'_>, data: &[u8]) -> Vec<PyMatch> {
+        let matches = py.allow_threads(|| self.inner.match_bytes_parallel(data));
+        self.resolve(py, matches)
+    }
+
+    /// Match a memory-mapped file
+    /// Returns a list of (start_index, end_index, rule_name) tuples sorted by offset
+    pub fn match_file_memmap(&self, py: Python<'_>, path: String) -> PyResult<Vec<PyMatch>> {
+        let matches = py.allow_threads(|| self.inner.match_file_memmap(&path).map_err(io_error))?;
+        Ok(self.resolve(py, matches))
+    }
+
+    /// Raw byte matching returning a MatchCollection grouped by rule name
+    pub fn match_bytes_collection(&self, py: Python<'_>, data: &[u8]) -> MatchCollection {
+        py.allow_threads(|| {
+            MatchCollection::new(
+                self.inner.shared_rules(),
+                self.inner.match_bytes_parallel(data),
+            )
+        })
+    }
+
+    /// Memory-mapped file matching returning a MatchCollection grouped by rule name
+    pub fn match_file_collection(&self, py: Python<'_>, path: String) -> PyResult<MatchCollection> {
+        py.allow_threads(|| {
+            let res = self.inner.match_file_memmap(&path).map_err(io_error)?;
+            Ok(MatchCollection::new(self.inner.shared_rules(), res))
+        })
+    }
+}
+
+impl ConfirmMatcher {
+    /// Resolve rule indices to the shared Python strings of the rule names
+    fn resolve(&self, py: Python<'_>, matches: Vec<(usize, usize, PatternID)>) -> Vec<PyMatch> {
+        self.names.resolve(py, self.inner.rules(), matches)
+    }
+}
+
+/// Matcher of whole tokens against huge term lists, such as customer names, stored in a
+/// finite state transducer far smaller than the DFA of a TextMatcher over the same terms
+#[pyclass(frozen)]
+pub struct TokenMatcher {
+    inner: Tokens,
+    names: PyNames,
+}
+
+#[pymethods]
+impl TokenMatcher {
+    #[new]
+    #[pyo3(signature = (terms, overlapping=None, case_insensitive=None, phonetic=None))]
+    pub fn new(
+        py: Python<'_>,
+        terms: Vec<String>,
+        overlapping: Option<bool>,
+        case_insensitive: Option<bool>,
+        phonetic: Option<&str>,
+    ) -> PyResult<Self> {
+        let defaults = TokenOptions::default();
+        let phonetic = match phonetic {
+            Some(name) => Some(Phonetic::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown phonetic algorithm '{}', expected 'soundex' or 'metaphone'",
+                    name
+                ))
+            })?),
+            None => defaults.phonetic,
+        };
+        let options = TokenOptions {
+            overlapping: overlapping.unwrap_or(defaults.overlapping),
+            case_insensitive: case_insensitive.unwrap_or(defaults.case_insensitive),
+            phonetic,
+        };
+        Ok(Self {
+            inner: py.allow_threads(|| Tokens::new(terms, options))?,
+            names: PyNames::default(),
+        })
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let phonetic = match self.inner.phonetic() {
+            Some(phonetic) => format!(", phonetic={}", quote(py, phonetic.name())?),
+            None => String::new(),
+        };
+        Ok(format!(
+            "TokenMatcher(terms={}, overlapping={}, case_insensitive={}{})",
+            self.inner.patterns().len(),
+            py_bool(self.inner.overlapping()),
+            py_bool(self.inner.case_insensitive()),
+            phonetic
+        ))
+    }
+
+    /// Number of terms
+    fn __len__(&self) -> usize {
+        self.inner.patterns().len()
+    }
+
+    #[getter]
+    pub fn pattern_count(&self) -> usize {
+        self.inner.patterns().len()
+    }
+
+    #[getter]
+    pub fn overlapping(&self) -> bool {
+        self.inner.overlapping()
+    }
+
+    #[getter]
+    pub fn case_insensitive(&self) -> bool {
+        self.inner.case_insensitive()
+    }
+
+    /// Phonetic algorithm encoding the tokens, if any
+    #[getter]
+    pub fn phonetic(&self) -> Option<&'static str> {
+        self.inner.phonetic().map(|p| p.name())
+    }
+
+    /// Bytes of the transducer and the terms
+    pub fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
+    }
+
+    /// Match provided bytes
+    /// Returns a list of (start_index, end_index, matched_term) tuples sorted by offset
+    pub fn match_bytes(&self, py: Python<'_>, data: &[u8]) -> Vec<PyMatch> {
+        let matches = py.allow_threads(|| self.inner.match_bytes(data));
+        self.resolve(py, matches)
+    }
+
+    /// Match a memory-mapped file, scanning chunks cut at line breaks in parallel
+    /// Returns a list of (start_index, end_index, matched_term) tuples sorted by offset
+    pub fn match_file_memmap(&self, py: Python<'_>, path: String) -> PyResult<Vec<PyMatch>> {
+        let matches = py.allow_threads(|| self.inner.match_file_memmap(&path).map_err(io_error))?;
+        Ok(self.resolve(py, matches))
+    }
+}
+
+impl TokenMatcher {
+    /// Resolve term indices to the shared Python strings of the terms
+    fn resolve(&self, py: Python<'_>, matches: Vec<(usize, usize, PatternID)>) -> Vec<PyMatch> {
+        self.names.resolve(py, self.inner.patterns(), matches)
+    }
+}
+
+/// Matcher of multi-word phrases whose words appear in order separated by any run of
+/// whitespace, line wraps included
+#[pyclass(frozen)]
+pub struct PhraseMatcher {
+    inner: Phrases,
+    names: PyNames,
+}
+
+#[pymethods]
+impl PhraseMatcher {
+    #[new]
+    #[pyo3(signature = (phrases, case_insensitive=None, max_gap=None))]
+    pub fn new(
+        py: Python<'_>,
+        phrases: Vec<String>,
+        case_insensitive: Option<bool>,
+        max_gap: Option<usize>,
+    ) -> PyResult<Self> {
+        let defaults = PhraseOptions::default();
+        let options = PhraseOptions {
+            case_insensitive: case_insensitive.unwrap_or(defaults.case_insensitive),
+            max_gap: max_gap.or(defaults.max_gap),
+        };
+        Ok(Self {
+            inner: py.allow_threads(|| Phrases::new(phrases, options))?,
+            names: PyNames::default(),
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PhraseMatcher(phrases={}, words={}, case_insensitive={})",
+            self.inner.patterns().len(),
+            self.inner.word_count(),
+            py_bool(self.inner.case_insensitive())
+        )
+    }
+
+    /// Number of phrases
+    fn __len__(&self) -> usize {
+        self.inner.patterns().len()
+    }
+
+    #[getter]
+    pub fn pattern_count(&self) -> usize {
+        self.inner.patterns().len()
+    }
+
+    #[getter]
+    pub fn patterns(&self) -> Vec<String> {
+        self.inner.patterns().to_vec()
+    }
+
+    /// Number of distinct words searched by the automaton
+    #[getter]
+    pub fn word_count(&self) -> usize {
+        self.inner.word_count()
+    }
+
+    #[getter]
+    pub fn case_insensitive(&self) -> bool {
+        self.inner.case_insensitive()
+    }
+
+    /// Most whitespace bytes between two words of a phrase, None for any run
+    #[getter]
+    pub fn max_gap(&self) -> Option<usize> {
+        self.inner.max_gap()
+    }
+
+    /// Heap bytes used by the word automaton and the phrases
+    pub fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
+    }
+
+    /// Match provided bytes
+    /// Returns a list of (start_index, end_index, matched_phrase) tuples sorted by offset
+    pub fn match_bytes(&self, py: Python<'_>, data: &[u8]) -> Vec<PyMatch> {
+        let matches = py.allow_threads(|| self.inner.match_bytes(data));
+        self.resolve(py, matches)
+    }
+
+    /// Match a memory-mapped file
+    /// Returns a list of (start_index, end_index, matched_phrase) tuples sorted by offset
+    pub fn match_file_memmap(&self, py: Python<'_>, path: String) -> PyResult<Vec<PyMatch>> {
+        let matches = py.allow_threads(|| self.inner.match_file_memmap(&path).map_err(io_error))?;
+        Ok(self.resolve(py, matches))
+    }
+}
+
+impl PhraseMatcher {
+    /// Resolve phrase indices to the shared Python strings of the phrases
+    fn resolve(&self, py: Python<'_>, matches: Vec<(usize, usize, PatternID)>) -> Vec<PyMatch> {
+        self.names.resolve(py, self.inner.patterns(), matches)
+    }
+}
+
+/// Newline index of a file or buffer built once, converting the byte offsets of matches to
+/// (line, column) positions with a binary search each
+#[pyclass(frozen)]
+pub struct LineIndex {
+    inner: Lines,
+}
+
+#[pymethods]
+impl LineIndex {
+    /// Index bytes, or a file given by its path which stays memory-mapped
+    #[new]
+    pub fn new(py: Python<'_>, data_or_path: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let inner = if let Ok(data) = data_or_path.downcast::<PyBytes>() {
+            let data = data.as_bytes().to_vec();
+            py.allow_threads(|| Lines::new(data))
+        } else {
+            let path: String = data_or_path
+                .extract()
+                .map_err(|_| PyTypeError::new_err("data_or_path must be bytes or a path"))?;
+            py.allow_threads(|| Lines::from_file(&path))
+                .map_err(io_error)?
+        };
+        Ok(Self { inner })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LineIndex(lines={}, bytes={})",
+            self.inner.line_count(),
+            self.inner.len()
+        )
+    }
+
+    /// Number of lines, a trailing newline starting an empty last line
+    #[getter]
+    pub fn line_count(&self) -> usize {
+        self.inner.line_count()
+    }
+
+    /// Number of bytes indexed
+    #[getter]
+    pub fn byte_count(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Byte offset of the first byte of a line, numbered from 1, None past the last line
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.inner.line_start(line)
+    }
+
+    /// (line, column) of a byte offset, lines numbered from 1 and columns from 0
+    /// Columns count bytes, or UTF-8 characters with chars=True
+    #[pyo3(signature = (offset, chars=false))]
+    pub fn position(&self, offset: usize, chars: bool) -> PyResult<(usize, usize)> {
+        self.checked_position(offset, chars)
+    }
+
+    /// (line, column) of every byte offset
+    #[pyo3(signature = (offsets, chars=false))]
+    pub fn positions(
+        &self,
+        py: Python<'_>,
+        offsets: Vec<usize>,
+        chars: bool,
+    ) -> PyResult<Vec<(usize, usize)>> {
+        py.allow_threads(|| {
+            offsets
+                .into_iter()
+                .map(|offset| self.checked_position(offset, chars))
+                .collect()
+        })
+    }
+
+    /// Positions of (start, end, pattern) matches, as (line, column, end_line, end_column,
+    /// pattern) tuples
+    #[pyo3(signature = (matches, chars=false))]
+    pub fn locate(
+        &self,
+        matches: Vec<(usize, usize, Py<PyAny>)>,
+        chars: bool,
+    ) -> PyResult<Vec<PyPosition>> {
+        matches
+            .into_iter()
+            .map(|(start, end, pattern)| {
+                let (line, column) = self.checked_position(start, chars)?;
+                let (end_line, end_column) = self.checked_position(end, chars)?;
+                Ok((line, column, end_line, end_column, pattern))
+            })
+            .collect()
+    }
+}
+
+impl LineIndex {
+    fn checked_position(&self, offset: usize, chars: bool) -> PyResult<(usize, usize)> {
+        self.inner.position(offset, chars).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Offset {} is past the end of the data ({} bytes)",
+                offset,
+                self.inner.len()
+            ))
+        })
+    }
+}
+
+/// Chained construction of a TextMatcher: patterns added from several sources with their
+/// metadata, normalizers and validators, and TextMatcher options, all checked by build()
+#[pyclass]
+pub struct TextMatcherBuilder {
+    inner: MatcherBuilder,
+    /// Options of the TextMatcher, the patterns, metadata and tags excepted
+    options: Py<PyDict>,
+    /// Python callables accepting or rejecting each normalized pattern
+    validators: Vec<PyObject>,
+}
+
+#[pymethods]
+impl TextMatcherBuilder {
+    #[new]
+    #[pyo3(signature = (patterns=None))]
+    pub fn new(py: Python<'_>, patterns: Option<Vec<String>>) -> Self {
+        let inner =
+            MatcherBuilder::new().patterns(patterns.unwrap_or_default(), &PatternMetadata::new());
+        Self {
+            inner,
+            options: PyDict::new(py).unbind(),
+            validators: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TextMatcherBuilder(patterns={}, normalizers={}, validators={})",
+            self.inner.len(),
+            self.inner.normalizers().len(),
+            self.inner.validators().len() + self.validators.len()
+        )
+    }
+
+    /// Number of patterns added so far
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Add a pattern, with an optional dict of metadata and list of tags
+    #[pyo3(signature = (pattern, metadata=None, tags=None))]
+    pub fn add_pattern(
+        mut slf: PyRefMut<'_, Self>,
+        pattern: String,
+        metadata: Option<PatternMetadata>,
+        tags: Option<Vec<String>>,
+    ) -> PyRefMut<'_, Self> {
+        let inner = std::mem::take(&mut slf.inner);
+        slf.inner = inner.tagged_pattern(
+            pattern,
+            metadata.unwrap_or_default(),
+            tags.unwrap_or_default(),
+        );
+        slf
+    }
+
+    /// Add patterns, with a dict of metadata shared by all of them or a list of one dict per
+    /// pattern, and a list of tags shared by all of them or a list of one list per pattern
+    #[pyo3(signature = (patterns, metadata=None, tags=None))]
+    pub fn add_patterns<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        patterns: Vec<String>,
+        metadata: Option<&Bound<'py, PyAny>>,
+        tags: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let metadata = match metadata {
+            Some(metadata) if metadata.is_instance_of::<PyDict>() => {
+                vec![metadata.extract::<PatternMetadata>()?; patterns.len()]
+            }
+            Some(metadata) => {
+                let metadata: Vec<PatternMetadata> = metadata.extract()?;
+                if metadata.len() != patterns.len() {
+                    return Err(PyValueError::new_err(
+                        "metadata must be a dict or have one entry per pattern",
+                    ));
+                }
+                metadata
+            }
+            None => vec![PatternMetadata::new(); patterns.len()],
+        };
+        let tags = match tags {
+            Some(tags) => match tags.extract::<Vec<String>>() {
+                Ok(shared) => vec![shared; patterns.len()],
+                Err(_) => {
+                    let tags: Vec<Vec<String>> = tags.extract()?;
+                    if tags.len() != patterns.len() {
+                        return Err(PyValueError::new_err(
+                            "tags must be a list of tags or have one list per pattern",
+                        ));
+                    }
+                    tags
+                }
+            },
+            None => vec![Vec::new(); patterns.len()],
+        };
+        let mut inner = std::mem::take(&mut slf.inner);
+        for ((pattern, metadata), tags) in patterns.into_iter().zip(metadata).zip(tags) {
+            inner = inner.tagged_pattern(pattern, metadata, tags);
+        }
+        slf.inner = inner;
+        Ok(slf)
+    }
+
+    /// Add one pattern per line of a UTF-8 file, skipping blank lines and lines starting with
+    /// '#', with an optional dict of metadata and list of tags shared by all of them
+    #[pyo3(signature = (path, metadata=None, tags=None))]
+    pub fn add_patterns_from_file(
+        mut slf: PyRefMut<'_, Self>,
+        path: PathBuf,
+        metadata: Option<PatternMetadata>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let patterns = builder::read_patterns(io::BufReader::new(std::fs::File::open(path)?))?;
+        let inner = std::mem::take(&mut slf.inner);
+        slf.inner = inner.tagged_patterns(
+            patterns,
+            &metadata.unwrap_or_default(),
+            &tags.unwrap_or_default(),
+        );
+        Ok(slf)
+    }
+
+    /// Engine searching for the patterns: 'auto', 'aho', 'memmem', 'hyperscan' or 'gpu'
+    pub fn engine<'py>(slf: PyRefMut<'py, Self>, name: &str) -> PyResult<PyRefMut<'py, Self>> {
+        parse_engine(name)?;
+        slf.options.bind(slf.py()).set_item("engine", name)?;
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (enabled=true))]
+    pub fn overlapping(slf: PyRefMut<'_, Self>, enabled: bool) -> PyResult<PyRefMut<'_, Self>> {
+        slf.options
+            .bind(slf.py())
+            .set_item("overlapping", enabled)?;
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (enabled=true))]
+    pub fn case_insensitive(
+        slf: PyRefMut<'_, Self>,
+        enabled: bool,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        slf.options
+            .bind(slf.py())
+            .set_item("case_insensitive", enabled)?;
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (enabled=true))]
+    pub fn whole_word(slf: PyRefMut<'_, Self>, enabled: bool) -> PyResult<PyRefMut<'_, Self>> {
+        slf.options.bind(slf.py()).set_item("whole_word", enabled)?;
+        Ok(slf)
+    }
+
+    /// Any other TextMatcher option, including those the TextMatcher constructor does not
+    /// take, checked along with the rest by build()
+    #[pyo3(signature = (**options))]
+    pub fn options<'py>(
+        slf: PyRefMut<'py, Self>,
+        options: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        if let Some(options) = options {
+            for key in ["patterns", "metadata", "tags"] {
+                if options.contains(key)? {
+                    return Err(PyValueError::new_err(format!(
+                        "'{}' cannot be set as an option, add the patterns instead",
+                        key
+                    )));
+                }
+            }
+            slf.options.bind(slf.py()).update(options.as_mapping())?;
+        }
+        Ok(slf)
+    }
+
+    /// Rewrite every pattern before it is validated: 'trim', 'lower' or 'collapse_whitespace',
+    /// applied in the order they are added
+    pub fn normalize<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        name: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let normalizer = PatternNormalizer::parse(name).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown normalizer '{}', expected 'trim', 'lower' or 'collapse_whitespace'",
+                name
+            ))
+        })?;
+        let inner = std::mem::take(&mut slf.inner);
+        slf.inner = inner.normalize(normalizer);
+        Ok(slf)
+    }
+
+    /// Check every normalized pattern with a built-in validator, 'non_empty', 'ascii',
+    /// 'unique', 'min_length' or 'max_length' with the length as argument, or with a callable
+    /// returning whether it accepts the pattern
+    #[pyo3(signature = (validator, arg=None))]
+    pub fn validate<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        validator: &Bound<'py, PyAny>,
+        arg: Option<usize>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        if validator.is_callable() {
+            slf.validators.push(validator.clone().unbind());
+            return Ok(slf);
+        }
+        let name: &str = validator.extract()?;
+        let validator = PatternValidator::parse(name, arg).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown validator '{}', expected 'non_empty', 'ascii', 'unique', \
+                 'min_length' or 'max_length', the length limits with an argument",
+                name
+            ))
+        })?;
+        let inner = std::mem::take(&mut slf.inner);
+        slf.inner = inner.validate(validator);
+        Ok(slf)
+    }
+
+    /// Normalize and validate the patterns, then build the TextMatcher
+    /// Raises PatternError listing every rejected pattern, or for invalid options
+    pub fn build<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, TextMatcher>> {
+        let (patterns, mut failures) = self.inner.normalized();
+        for validator in &self.validators {
+            for (idx, pattern) in patterns.iter().enumerate() {
+                if !validator.call1(py, (pattern,))?.is_truthy(py)? {
+                    let name = validator.bind(py).getattr("__name__")?;
+                    let reason = format!("is rejected by {}", name);
+                    failures.push(builder::failure(idx, pattern, &reason));
+                }
+            }
+        }
+        if !failures.is_empty() {
+            return Err(builder::invalid_patterns(&failures).into());
+        }
+
+        let mut options = TextMatcherOptions::from_dict(&self.options.bind(py).copy()?)?;
+        options.metadata = Some(self.inner.metadata().to_vec());
+        options.tags = Some(self.inner.tags().to_vec());
+        Bound::new(py, TextMatcher::with_options(py, patterns, options)?)
+    }
+}
+
+/// Matcher whose pattern set grows while it is in use: added patterns go to small automaton
+/// layers merged as they accumulate, so additions never rebuild the whole dictionary and
+/// scans running meanwhile keep using the layers they started with
+#[pyclass(frozen)]
+pub struct IncrementalMatcher {
+    inner: Incremental,
+}
+
+#[pymethods]
+impl IncrementalMatcher {
+    #[new]
+    #[pyo3(signature = (patterns=None, overlapping=None, case_insensitive=None, whole_word=None, postprocess=None, max_layers=None, events=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        py: Python<'_>,
+        patterns: Option<Vec<String>>,
+        overlapping: Option<bool>,
+        case_insensitive: Option<bool>,
+        whole_word: Option<bool>,
+        postprocess: Option<&str>,
+        max_layers: Option<usize>,
+        events: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let defaults = IncrementalOptions::default();
+        let postprocess = match postprocess {
+            Some(name) => Some(PostProcess::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown postprocess strategy '{}', expected 'merge' or 'longest'",
+                    name
+                ))
+            })?),
+            None => None,
+        };
+        let options = IncrementalOptions {
+            overlapping: overlapping.unwrap_or(defaults.overlapping),
+            case_insensitive: case_insensitive.unwrap_or(defaults.case_insensitive),
+            whole_word: whole_word.unwrap_or(defaults.whole_word),
+            postprocess,
+            max_layers: max_layers.unwrap_or(defaults.max_layers),
+            events: events.map(extract_events).transpose()?,
+        };
+        let patterns = patterns.unwrap_or_default();
+        let inner = py.allow_threads(|| Incremental::new(patterns, options))?;
+        Ok(Self { inner })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "IncrementalMatcher(patterns={}, layers={})",
+            self.inner.len(),
+            self.inner.layer_count()
+        )
+    }
+
+    /// Number of patterns
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Patterns in the order they were added
+    #[getter]
+    pub fn patterns(&self) -> Vec<String> {
+        self.inner.patterns()
+    }
+
+    #[getter]
+    pub fn pattern_count(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Number of automaton layers searched by each scan
+    #[getter]
+    pub fn layer_count(&self) -> usize {
+        self.inner.layer_count()
+    }
+
+    /// Time taken by the last addition or compaction in milliseconds
+    #[getter]
+    pub fn build_time_ms(&self) -> f64 {
+        self.inner.build_time().as_secs_f64() * 1000.0
+    }
+
+    /// Heap bytes used by the layers and the patterns
+    pub fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
+    }
+
+    #[getter]
+    pub fn overlapping(&self) -> bool {
+        self.inner.options().overlapping
+    }
+
+    #[getter]
+    pub fn case_insensitive(&self) -> bool {
+        self.inner.options().case_insensitive
+    }
+
+    #[getter]
+    pub fn whole_word(&self) -> bool {
+        self.inner.options().whole_word
+    }
+
+    #[getter]
+    pub fn postprocess(&self) -> Option<&'static str> {
+        self.inner.options().postprocess.map(|p| p.name())
+    }
+
+    #[getter]
+    pub fn max_layers(&self) -> usize {
+        self.inner.options().max_layers
+    }
+
+    /// Add patterns, visible to scans started once this returns
+    /// Returns the index of the first added pattern
+    pub fn add_patterns(&self, py: Python<'_>, patterns: Vec<String>) -> usize {
+        py.allow_threads(|| self.inner.add_patterns(patterns))
+    }
+
+    /// Merge every layer into a single automaton
+    pub fn compact(&self, py: Python<'_>) {
+        py.allow_threads(|| self.inner.compact())
+    }
+
+    /// Match provided bytes
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples
+    pub fn match_bytes(&self, py: Python<'_>, data: &[u8]) -> Vec<(usize, usize, String)> {
+        py.allow_threads(|| self.inner.resolve(self.inner.match_bytes(data)))
+    }
+
+    /// Match a memory-mapped file
+    /// Returns a list of (start_index, end_index, matched_pattern) tuples
+    pub fn match_file_memmap(
+        &self,
+        py: Python<'_>,
+        path: String,
+    ) -> PyResult<Vec<(usize, usize, String)>> {
+        py.allow_threads(|| {
+            let matches = self.inner.match_file_memmap(&path).map_err(io_error)?;
+            Ok(self.inner.resolve(matches))
+        })
+    }
+}
+
+/// Two-pass scan: the `coarse` TextMatcher runs over everything and the `deep`
+/// ConfirmMatcher only on the regions around its hits, each file being mapped once for both
+#[pyclass(frozen)]
+pub struct ScanPlan {
+    coarse: Py<TextMatcher>,
+    deep: Py<ConfirmMatcher>,
+    options: PlanOptions,
+}
+
+#[pymethods]
+impl ScanPlan {
+    #[new]
+    #[pyo3(signature = (coarse, deep, region=None, context=None))]
+    pub fn new(
+        coarse: Py<TextMatcher>,
+        deep: Py<ConfirmMatcher>,
+        region: Option<&Bound<'_, PyAny>>,
+        context: Option<usize>,
+    ) -> PyResult<Self> {
+        let defaults = PlanOptions::default();
+        let region = match region {
+            None => defaults.region,
+            Some(value) => match value.extract::<String>() {
+                Ok(name) if name == "file" => None,
+                Ok(name) => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown region '{}', expected a number of bytes or 'file'",
+                        name
+                    )))
+                }
+                Err(_) => Some(value.extract::<usize>().map_err(|_| {
+                    PyTypeError::new_err("region must be a number of bytes or 'file'")
+                })?),
+            },
+        };
+        Ok(Self {
+            coarse,
+            deep,
+            options: PlanOptions {
+                region,
+                context: context.unwrap_or(defaults.context),
+            },
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanPlan(patterns={}, rules={}, region={})",
+            self.coarse.get().inner.patterns().len(),
+            self.deep.get().inner.rules().len(),
+            match self.options.region {
+                Some(region) => region.to_string(),
+                None => "'file'".to_string(),
+            }
+        )
+    }
+
+    #[getter]
+    pub fn coarse(&self, py: Python<'_>) -> Py<TextMatcher> {
+        self.coarse.clone_ref(py)
+    }
+
+    #[getter]
+    pub fn deep(&self, py: Python<'_>) -> Py<ConfirmMatcher> {
+        self.deep.clone_ref(py)
+    }
+
+    /// Bytes scanned by the second pass around each hit, None when it scans whole files
+    #[getter]
+    pub fn region(&self) -> Option<usize> {
+        self.options.region
+    }
+
+    #[getter]
+    pub fn context(&self) -> usize {
+        self.options.context
+    }
+
+    /// Run both passes on provided bytes
+    pub fn scan_bytes(&self, py: Python<'_>, data: &[u8]) -> PlanResult {
+        py.allow_threads(|| self.plan().scan_bytes(data))
+    }
+
+    /// Run both passes on a memory-mapped file
+    pub fn scan_file(&self, py: Python<'_>, path: String) -> PyResult<PlanResult> {
+        py.allow_threads(|| self.plan().scan_file(&path).map_err(io_error))
+    }
+
+    /// Run both passes on files in parallel, unreadable files are reported with their error
+    pub fn scan_files(&self, py: Python<'_>, paths: Vec<String>) -> Vec<PlanResult> {
+        py.allow_threads(|| self.plan().scan_files(&paths))
+    }
+}
+
+impl ScanPlan {
+    fn plan(&self) -> Plan<'_> {
+        Plan::new(
+            &self.coarse.get().inner,
+            &self.deep.get().inner,
+            self.options.clone(),
+        )
+    }
+}
+
+/// Iterator over the matches of a spooled scan, yielding (start_index, end_index,
+/// matched_pattern) tuples in order; its temporary file is removed once it is exhausted or
+/// dropped
+#[pyclass]
+pub struct SpooledMatches {
+    matches: Option<SpoolIter>,
+    patterns: Arc<Vec<String>>,
+    names: PyNames,
+    len: usize,
+    spilled: usize,
+}
+
+impl SpooledMatches {
+    fn new(spool: Spool, patterns: Arc<Vec<String>>) -> PyResult<Self> {
+        let (len, spilled) = (spool.len(), spool.spilled());
+        Ok(Self {
+            matches: Some(spool.into_matches().map_err(io_error)?),
+            patterns,
+            names: PyNames::default(),
+            len,
+            spilled,
+        })
+    }
+}
+
+#[pymethods]
+impl SpooledMatches {
+    /// Number of matches of the scan
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    /// Number of matches that did not fit in memory and were written to the temporary file
+    #[getter]
+    fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyMatch>> {
+        let Some(matches) = &mut self.matches else {
+            return Ok(None);
+        };
+        match matches.next() {
+            Some(Ok((start, end, pattern_idx))) => {
+                let name = &self.names.get(py, &self.patterns)[pattern_idx.as_usize()];
+                Ok(Some((start, end, name.clone_ref(py))))
+            }
+            Some(Err(e)) => {
+                self.matches = None;
+                Err(io_error(e))
+            }
+            None => {
+                self.matches = None;
+                Ok(None)
+            }
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SpooledMatches(len={}, spilled={})", self.len, self.spilled)
+    }
+}
+
+/// Run a blocking scan on the tokio blocking pool and return a Python awaitable for its result
+fn run_async<'py, T, F>(py: Python<'py>, scan: F) -> PyResult<Bound<'py, PyAny>>
+where
+    F: FnOnce() -> PyResult<T> + Send + 'static,
+    T: for<'a> IntoPyObject<'a> + Send + 'static,
+{
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        tokio::task::spawn_blocking(scan)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?
+    })
+}
+
+/// A size given as a positive integer, or as "auto" (the default) to let voluta pick it
+fn extract_auto(value: Option<&Bound<'_, PyAny>>, name: &str) -> PyResult<Option<usize>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    if let Ok(size) = value.extract::<usize>() {
+        if size == 0 {
+            return Err(PyValueError::new_err(format!("{} must be positive", name)));
+        }
+        return Ok(Some(size));
+    }
+    match value.extract::<String>() {
+        Ok(mode) if mode == "auto" => Ok(None),
+        Ok(mode) => Err(PyValueError::new_err(format!(
+            "Unknown {} '{}', expected a positive integer or 'auto'",
+            name, mode
+        ))),
+        Err(_) => Err(PyTypeError::new_err(format!(
+            "{} must be a positive integer or 'auto'",
+            name
+        ))),
+    }
+}
+
+/// Event hook of a matcher: "tracing" for Rust `tracing` subscribers, or a `logging.Logger`,
+/// or "logging" for the "voluta" logger, receiving each event as a record whose `extra`
+/// fields hold the event's fields
+/// Build a matcher without the GIL, calling `progress` with the stage, the patterns done and
+/// the total as it goes; an exception raised by `progress`, or by a signal handler such as
+/// KeyboardInterrupt, cancels the build and is raised in its place
+fn build_matcher(
+    py: Python<'_>,
+    progress: Option<&Bound<'_, PyAny>>,
+    build: impl FnOnce(ProgressHook) -> Result<Matcher, ConfigError> + Send,
+) -> PyResult<Matcher> {
+    let callback = match progress {
+        Some(progress) if progress.is_callable() => Some(progress.clone().unbind()),
+        Some(_) => return Err(PyTypeError::new_err("progress must be callable")),
+        None => None,
+    };
+    let raised: Arc<Mutex<Option<PyErr>>> = Arc::default();
+    let hook_raised = Arc::clone(&raised);
+    let hook = ProgressHook::new(move |stage, done, total| {
+        Python::with_gil(|py| {
+            let res = match &callback {
+                Some(callback) => callback.call1(py, (stage.name(), done, total)).map(drop),
+                None => Ok(()),
+            };
+            match res.and_then(|()| py.check_signals()) {
+                Ok(()) => true,
+                Err(e) => {
+                    *hook_raised.lock().unwrap() = Some(e);
+                    false
+                }
+            }
+        })
+    });
+
+    match py.allow_threads(|| build(hook)) {
+        Ok(matcher) => Ok(matcher),
+        Err(e) => Err(raised.lock().unwrap().take().unwrap_or_else(|| e.into())),
+    }
+}
+
+fn extract_events(events: &Bound<'_, PyAny>) -> PyResult<EventHook> {
+    let py = events.py();
+    let logger = match events.extract::<String>() {
+        Ok(name) if name == "tracing" => {
+            #[cfg(feature = "tracing")]
+            return Ok(EventHook::tracing());
+            #[cfg(not(feature = "tracing"))]
+            return Err(PyValueError::new_err(
+                "events='tracing' needs voluta built with the tracing feature",
+            ));
+        }
+        Ok(name) if name == "logging" => py
+            .import("logging")?
+            .call_method1("getLogger", ("voluta",))?,
+        Ok(name) => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown events '{}', expected 'logging', 'tracing' or a logging.Logger",
+                name
+            )))
+        }
+        Err(_) if events.hasattr("log")? => events.clone(),
+        Err(_) => {
+            return Err(PyTypeError::new_err(
+                "events must be 'logging', 'tracing' or a logging.Logger",
+            ))
+        }
+    };
+    let logger = logger.unbind();
+    Ok(EventHook::new(move |event| {
+        Python::with_gil(|py| {
+            let level = match event.kind.level() {
+                EventLevel::Debug => 10,
+                EventLevel::Info => 20,
+                EventLevel::Warning => 30,
+                EventLevel::Error => 40,
+            };
+            let log = || -> PyResult<()> {
+                let extra = PyDict::new(py);
+                extra.set_item("event", event.kind.name())?;
+                extra.set_item("operation", event.operation)?;
+                extra.set_item("path", &event.path)?;
+                extra.set_item("bytes", event.bytes)?;
+                extra.set_item("matches", event.matches)?;
+                extra.set_item("elapsed", event.elapsed.map(|e| e.as_secs_f64()))?;
+                extra.set_item("patterns", event.patterns)?;
+                extra.set_item("error", &event.error)?;
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("extra", extra)?;
+                logger.call_method(py, "log", (level, event.message()), Some(&kwargs))?;
+                Ok(())
+            };
+            if let Err(e) = log() {
+                e.write_unraisable(py, Some(logger.bind(py)));
+            }
+        })
+    }))
+}
+
+fn parse_engine(name: &str) -> PyResult<Engine> {
+    Engine::parse(name).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Unknown engine '{}', expected 'auto', 'aho', 'memmem', 'hyperscan' or 'gpu'",
+            name
+        ))
+    })
+}
+
+/// Configurations of a benchmark, a list of dicts or a dict of lists of values expanded to
+/// every combination, with "engine", "chunk_size" and "n_threads" keys
+fn extract_bench_configs(configs: &Bound<'_, PyAny>) -> PyResult<Vec<BenchConfig>> {
+    if let Ok(matrix) = configs.downcast::<PyDict>() {
+        let mut configs = vec![BenchConfig::default()];
+        for (key, values) in matrix.iter() {
+            let key: String = key.extract()?;
+            let values: Vec<Bound<'_, PyAny>> = values.extract().map_err(|_| {
+                PyTypeError::new_err(format!("configs['{}'] must be a list of values", key))
+            })?;
+            let mut expanded = Vec::with_capacity(configs.len() * values.len());
+            for config in &configs {
+                for value in &values {
+                    let mut config = *config;
+                    set_bench_option(&mut config, &key, value)?;
+                    expanded.push(config);
+                }
+            }
+            configs = expanded;
+        }
+        return Ok(configs);
+    }
+
+    let configs: Vec<Bound<'_, PyDict>> = configs
+        .extract()
+        .map_err(|_| PyTypeError::new_err("configs must be a list of dicts or a dict of lists"))?;
+    configs
+        .iter()
+        .map(|options| {
+            let mut config = BenchConfig::default();
+            for (key, value) in options.iter() {
+                set_bench_option(&mut config, &key.extract::<String>()?, &value)?;
+            }
+            Ok(config)
+        })
+        .collect()
+}
+
+fn set_bench_option(config: &mut BenchConfig, key: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+    let value = (!value.is_none()).then_some(value);
+    match key {
+        "engine" => {
+            config.engine = value.map_or(Ok(Engine::Auto), |v| parse_engine(v.extract()?))?
+        }
+        "chunk_size" => config.chunk_size = extract_auto(value, "chunk_size")?,
+        "n_threads" => config.n_threads = extract_auto(value, "n_threads")?,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown benchmark option '{}', expected 'engine', 'chunk_size' or 'n_threads'",
+                other
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn parse_window_unit(unit: Option<&str>) -> PyResult<WindowUnit> {
+    match unit {
+        None => Ok(WindowUnit::Bytes),
+        Some(name) => WindowUnit::parse(name).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown window unit '{}', expected 'bytes' or 'lines'",
+                name
+            ))
+        }),
+    }
+}
+
+/// Run a scan, recording its statistics when `record`
+fn recorded<T>(
+    record: bool,
+    bytes_scanned: impl FnOnce() -> usize,
+    scan: impl FnOnce() -> T,
+) -> (T, Option<ScanStats>) {
+    if !record {
+        return (scan(), None);
+    }
+    let (res, stats) = ScanStats::record(bytes_scanned(), scan);
+    (res, Some(stats))
+}
+
+/// Size of the file at a path, 0 when it cannot be read
+fn file_size(path: &str) -> usize {
+    std::fs::metadata(path).map_or(0, |metadata| metadata.len() as usize)
+}
+
+fn parse_buckets(
+    bucket_size: Option<usize>,
+    timestamp_format: Option<&str>,
+    interval: u64,
+) -> PyResult<Buckets> {
+    match (bucket_size, timestamp_format) {
+        (Some(0), _) => Err(PyValueError::new_err("bucket_size must be positive")),
+        (Some(size), None) => Ok(Buckets::Bytes(size)),
+        (None, Some(_)) if interval == 0 => Err(PyValueError::new_err("interval must be positive")),
+        (None, Some(format)) => Ok(Buckets::Time {
+            format: TimestampFormat::parse(format)?,
+            interval,
+        }),
+        _ => Err(PyValueError::new_err(
+            "Pass either bucket_size or timestamp_format",
+        )),
+    }
+}
+
+/// Limits of a log source scan, validated
+fn source_options(max_records: Option<u64>, idle_timeout: Option<f64>) -> PyResult<SourceOptions> {
+    if max_records == Some(0) {
+        return Err(PyValueError::new_err("max_records must be positive"));
+    }
+    let idle_timeout = match idle_timeout {
+        Some(secs) if !(secs > 0.0 && secs.is_finite()) => {
+            return Err(PyValueError::new_err("idle_timeout must be positive"));
+        }
+        secs => secs.map(Duration::from_secs_f64),
+    };
+    Ok(SourceOptions {
+        max_records,
+        idle_timeout,
+    })
+}
+
+fn parse_sink(sink: &Bound<'_, PyAny>, format: Option<&str>) -> PyResult<SinkSpec> {
+    if let Ok(config) = sink.downcast::<WebhookSink>() {
+        return Ok(SinkSpec::Webhook(config.get().clone()));
+    }
+
+    #[cfg(feature = "kafka")]
+    if let Ok(config) = sink.downcast::<crate::kafka::KafkaSink>() {
+        return Ok(SinkSpec::Kafka(config.get().clone()));
+    }
+
+    let path: String = sink
+        .extract()
+        .map_err(|_| PyTypeError::new_err("sink must be a path, a URL or a WebhookSink"))?;
+
+    if webhook::is_webhook_url(&path) {
+        return Ok(SinkSpec::Webhook(WebhookSink::new(path)?));
+    }
+
+    let format = match format {
+        Some(name) => SinkFormat::parse(name).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown sink format '{}', expected 'ndjson', 'csv' or 'sqlite'",
+                name
+            ))
+        })?,
+        None => SinkFormat::from_path(&path).ok_or_else(|| {
+            PyValueError::new_err(
+                "Cannot infer the sink format from the path, pass format='ndjson', 'csv' or 'sqlite'",
+            )
+        })?,
+    };
+
+    Ok(SinkSpec::File { path, format })
+}
+
+/// Fuzzy distances given as one distance for every pattern, or as a list of one per pattern
+fn extract_fuzzy(fuzzy: &Bound<'_, PyAny>, pattern_count: usize) -> PyResult<Vec<usize>> {
+    if let Ok(distance) = fuzzy.extract::<usize>() {
+        return Ok(vec![distance; pattern_count]);
+    }
+    fuzzy.extract::<Vec<usize>>().map_err(|_| {
+        PyTypeError::new_err("fuzzy must be a distance or a list of one distance per pattern")
+    })
+}
+
+/// Min counts of a matcher from a count for every pattern, a list of one count per pattern or
+/// a dict of counts by pattern, the patterns left out of the dict reporting every match
+fn extract_min_counts(min_count: &Bound<'_, PyAny>, patterns: &[String]) -> PyResult<Vec<usize>> {
+    if let Ok(count) = min_count.extract::<usize>() {
+        return Ok(vec![count; patterns.len()]);
+    }
+    if let Ok(counts) = min_count.downcast::<PyDict>() {
+        let mut min_counts = vec![1; patterns.len()];
+        for (name, count) in counts.iter() {
+            let name: String = name.extract()?;
+            let count: usize = count.extract()?;
+            let mut found = false;
+            for (idx, pattern) in patterns.iter().enumerate() {
+                if *pattern == name {
+                    min_counts[idx] = count;
+                    found = true;
+                }
+            }
+            if !found {
+                return Err(ConfigError::Invalid(format!("Unknown pattern '{}'", name)).into());
+            }
+        }
+        return Ok(min_counts);
+    }
+    min_count.extract::<Vec<usize>>().map_err(|_| {
+        PyTypeError::new_err(
+            "min_count must be a count, a list of one count per pattern or a dict of counts by pattern",
+        )
+    })
+}
+
+/// Matches outside the per-call ignore spans, if any
+fn without_spans(
+    mut matches: Vec<(usize, usize, PatternID)>,
+    spans: &Option<IgnoreSpans>,
+) -> Vec<(usize, usize, PatternID)> {
+    if let Some(spans) = spans {
+        spans.retain(&mut matches);
+    }
+    matches
+}
+
+/// Number of matches asked for from the end of the data
+fn check_count(count: usize) -> PyResult<()> {
+    if count == 0 {
+        return Err(PyValueError::new_err("count must be positive"));
+    }
+    Ok(())
+}
+
+/// Encoding of a scan, by name
+fn extract_encoding(encoding: Option<&str>) -> PyResult<Option<Encoding>> {
+    encoding
+        .map(|name| {
+            Encoding::parse(name).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Unknown encoding '{}', expected 'utf-8', 'latin-1', 'windows-1252' or 'auto'",
+                    name
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Record delimiter of match_file, as bytes or a UTF-8 string
+fn extract_delimiter(delimiter: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    let delimiter = extract_key(delimiter)
+        .map_err(|_| PyTypeError::new_err("delimiter must be bytes or str"))?;
+    if delimiter.is_empty() {
+        return Err(PyValueError::new_err("delimiter cannot be empty"));
+    }
+    Ok(delimiter)
+}
+
+/// Key of hash-only results, as bytes or a UTF-8 string
+fn extract_key(key: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    if let Ok(bytes) = key.downcast::<PyBytes>() {
+        return Ok(bytes.as_bytes().to_vec());
+    }
+    key.extract::<String>()
+        .map(String::into_bytes)
+        .map_err(|_| PyTypeError::new_err("hash_key must be bytes or str"))
+}
+
+/// Hex HMAC-SHA256 of `text` under `key`, as reported by matchers built with the same hash_key
+/// Pass `case_insensitive=True` to fold ASCII case like a case-insensitive matcher does
+#[pyfunction]
+#[pyo3(signature = (key, text, case_insensitive=false))]
+fn hash_text(
+    key: &Bound<'_, PyAny>,
+    text: &Bound<'_, PyAny>,
+    case_insensitive: bool,
+) -> PyResult<String> {
+    let mut text =
+        extract_key(text).map_err(|_| PyTypeError::new_err("text must be bytes or str"))?;
+    if case_insensitive {
+        text.make_ascii_lowercase();
+    }
+    Ok(matcher::hash_text(&extract_key(key)?, &text))
+}
+
+fn map_file(path: &str) -> PyResult<memmap2::Mmap> {
+    std::fs::File::open(path)
+        .and_then(|file| unsafe { memmap2::Mmap::map(&file) })
+        .map_err(io_error)
+}
+
+/// Format the matches found in the file at `path` grep-style, one `path:line:col: text` line
+/// per matching line, with the matches colorized using ANSI escape codes when `color` is set
+/// `matches` is a MatchCollection or a list of (start_index, end_index, matched_pattern) tuples
+#[pyfunction]
+#[pyo3(signature = (path, matches, color=false))]
+fn format_grep(
+    py: Python<'_>,
+    path: String,
+    matches: &Bound<'_, PyAny>,
+    color: bool,
+) -> PyResult<String> {
+    let spans = match matches.downcast::<MatchCollection>() {
+        Ok(collection) => collection.get().spans(),
+        Err(_) => {
+            let matches: Vec<(usize, usize, Bound<'_, PyAny>)> =
+                matches.extract().map_err(|_| {
+                    PyTypeError::new_err(
+                    "matches must be a MatchCollection or a list of (start, end, pattern) tuples",
+                )
+                })?;
+            matches
+                .into_iter()
+                .map(|(start, end, _)| (start, end))
+                .collect()
+        }
+    };
+
+    py.allow_threads(|| {
+        let mmap = map_file(&path)?;
+        Ok(highlight::to_grep(&path, &mmap, &spans, color))
+    })
+}
+
+/// Read the file at `path` into the page cache and touch all its pages, so timing-critical
+/// and repeated scans of it do not pay for page faults, returns its size in bytes
+#[pyfunction]
+fn warmup(py: Python<'_>, path: String) -> PyResult<usize> {
+    py.allow_threads(|| mmap::warmup(&path).map_err(io_error))
+}
+
+/// Check `matcher` against golden test cases: a YAML or JSON file of cases, or the list of
+/// cases itself, each a dict with "text" or "file", "matches", "no_matches" and "exact" keys
+/// YAML files are read with PyYAML, and sample files are relative to the directory of the file
+#[pyfunction]
+fn run_pattern_tests(
+    py: Python<'_>,
+    matcher: &TextMatcher,
+    spec: &Bound<'_, PyAny>,
+) -> PyResult<GoldenReport> {
+    let (json, base) = if let Ok(path) = spec.extract::<String>() {
+        let text = std::fs::read_to_string(&path).map_err(io_error)?;
+        let json = if path.ends_with(".json") {
+            text
+        } else {
+            let cases = py.import("yaml")?.call_method1("safe_load", (text,))?;
+            py.import("json")?
+                .call_method1("dumps", (cases,))?
+                .extract()?
+        };
+        let base = Path::new(&path).parent().map(Path::to_path_buf);
+        (json, base.unwrap_or_default())
+    } else {
+        let json = py
+            .import("json")?
+            .call_method1("dumps", (spec,))?
+            .extract()?;
+        (json, PathBuf::new())
+    };
+    let cases = golden::parse_cases(&json)
+        .map_err(|e| PyValueError::new_err(format!("Invalid pattern tests: {e}")))?;
+    Ok(py.allow_threads(|| golden::run_pattern_tests(&matcher.inner, &cases, &base)))
+}
+
+impl TextMatcher {
+    /// Matches of an Avro or ORC scan, matches of a field sharing the Python string of its path
+    fn record_matches(
+        &self,
+        py: Python<'_>,
+        fields: &[String],
+        matches: Vec<(u64, usize, usize, usize, PatternID)>,
+    ) -> Vec<PyRecordMatch> {
+        let fields: Vec<Py<PyString>> = fields
+            .iter()
+            .map(|field| PyString::new(py, field).unbind())
+            .collect();
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        matches
+            .into_iter()
+            .map(|(record, field, start, end, pattern_idx)| {
+                (
+                    record,
+                    fields[field].clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect()
+    }
+
+    /// Matches of an SQLite scan, cells of a table and column sharing their Python strings
+    fn db_matches(&self, py: Python<'_>, scan: DbScan) -> PyResult<Vec<PyDbMatch>> {
+        let mut strings: HashMap<String, Py<PyString>> = HashMap::new();
+        let mut string = |value: &str| {
+            strings
+                .entry(value.to_string())
+                .or_insert_with(|| PyString::new(py, value).unbind())
+                .clone_ref(py)
+        };
+        let cells = scan
+            .cells
+            .iter()
+            .map(|cell| {
+                let values = cell
+                    .key
+                    .iter()
+                    .map(|value| db_value(py, value))
+                    .collect::<PyResult<Vec<_>>>()?;
+                Ok((
+                    cell.table.as_deref().map(&mut string),
+                    string(&cell.column),
+                    db_key(py, values)?,
+                ))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        Ok(scan
+            .matches
+            .into_iter()
+            .map(|(cell, start, end, pattern_idx)| {
+                let (table, column, key) = &cells[cell];
+                (
+                    table.as_ref().map(|table| table.clone_ref(py)),
+                    column.clone_ref(py),
+                    key.clone_ref(py),
+                    start,
+                    end,
+                    names[pattern_idx.as_usize()].clone_ref(py),
+                )
+            })
+            .collect())
+    }
+
+    /// Scan the result sets of a DB-API cursor: those of the query or of the tables when given,
+    /// executed on it, or its pending results otherwise, fetched `batch_size` rows at a time and
+    /// scanned batch by batch without the GIL
+    fn match_db_cursor(
+        &self,
+        py: Python<'_>,
+        cursor: &Bound<'_, PyAny>,
+        owned: bool,
+        options: &DbOptions,
+        batch_size: usize,
+    ) -> PyResult<Vec<PyDbMatch>> {
+        let result_sets: Vec<(Option<&str>, Option<String>)> =
+            match (&options.query, &options.tables) {
+                (Some(query), _) => vec![(None, Some(query.clone()))],
+                // Double quotes are the standard SQL quotes of identifiers
+                (None, Some(tables)) => tables
+                    .iter()
+                    .map(|table| {
+                        let quoted = format!("\"{}\"", table.replace('"', "\"\""));
+                        (
+                            Some(table.as_str()),
+                            Some(format!("SELECT * FROM {}", quoted)),
+                        )
+                    })
+                    .collect(),
+                (None, None) if owned => {
+                    return Err(PyValueError::new_err(
+                        "query or tables is required to scan a DB-API connection",
+                    ))
+                }
+                (None, None) => vec![(None, None)],
+            };
+
+        let names = self.names.get(py, &self.inner.shared_patterns());
+        let mut matches = Vec::new();
+        for (table, query) in result_sets {
+            if let Some(query) = query {
+                cursor.call_method1("execute", (query,))?;
+            }
+            let description = cursor.getattr("description")?;
+            if description.is_none() {
+                return Err(PyValueError::new_err(
+                    "The cursor has no results to scan, execute a query on it first",
+                ));
+            }
+            let column_names = description
+                .try_iter()?
+                .map(|column| column?.get_item(0)?.extract::<String>())
+                .collect::<PyResult<Vec<_>>>()?;
+            let position = |wanted: &str, role: &str| {
+                column_names
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(wanted))
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "{} column '{}' is not in the results",
+                            role, wanted
+                        ))
+                    })
+            };
+            let key_idx = match &options.key {
+                Some(key) => Some(
+                    key.iter()
+                        .map(|k| position(k, "Key"))
+                        .collect::<PyResult<Vec<_>>>()?,
+                ),
+                None => None,
+            };
+            // Tables without a wanted column are scanned for the others
+            let scanned_idx: Vec<usize> = match (&options.columns, table) {
+                (Some(columns), Some(_)) => (0..column_names.len())
+                    .filter(|&idx| {
+                        columns
+                            .iter()
+                            .any(|c| c.eq_ignore_ascii_case(&column_names[idx]))
+                    })
+                    .collect(),
+                (Some(columns), None) => columns
+                    .iter()
+                    .map(|c| position(c, "Scanned"))
+                    .collect::<PyResult<Vec<_>>>()?,
+                (None, _) => (0..column_names.len()).collect(),
+            };
+            let table = table.map(|table| PyString::new(py, table).unbind());
+            let columns: Vec<Py<PyString>> = column_names
+                .iter()
+                .map(|name| PyString::new(py, name).unbind())
+                .collect();
+
+            let mut row_number: usize = 0;
+            loop {
+                let rows = cursor.call_method1("fetchmany", (batch_size,))?;
+                let mut keys: Vec<Py<PyAny>> = Vec::new();
+                let mut cells: Vec<(usize, usize)> = Vec::new();
+                let mut values: Vec<Vec<u8>> = Vec::new();
+                let mut fetched = 0;
+                for row in rows.try_iter()? {
+                    let row = row?;
+                    fetched += 1;
+                    let mut added = false;
+                    for &idx in &scanned_idx {
+                        if let Some(value) = db_bytes(&row.get_item(idx)?)? {
+                            cells.push((keys.len(), idx));
+                            values.push(value);
+                            added = true;
+                        }
+                    }
+                    if added {
+                        let key = match &key_idx {
+                            Some(key_idx) => db_key(
+                                py,
+                                key_idx
+                                    .iter()
+                                    .map(|&idx| Ok(row.get_item(idx)?.unbind()))
+                                    .collect::<PyResult<Vec<_>>>()?,
+                            )?,
+                            None => row_number.into_pyobject(py)?.into_any().unbind(),
+                        };
+                        keys.push(key);
+                    }
+                    row_number += 1;
+                }
+                if fetched == 0 {
+                    break;
+                }
+                let found = py.allow_threads(|| DbScan::scan_values(&self.inner, &values));
+                matches.extend(found.into_iter().map(|(value, start, end, pattern_idx)| {
+                    let (row, column) = cells[value];
+                    (
+                        table.as_ref().map(|table| table.clone_ref(py)),
+                        columns[column].clone_ref(py),
+                        keys[row].clone_ref(py),
+                        start,
+                        end,
+                        names[pattern_idx.as_usize()].clone_ref(py),
+                    )
+                }));
+            }
+        }
+        Ok(matches)
+    }
+}
+
+/// Python value of a key column of an SQLite row
+fn db_value(py: Python<'_>, value: &DbValue) -> PyResult<Py<PyAny>> {
+    Ok(match value {
+        DbValue::Null => py.None(),
+        DbValue::Integer(n) => n.into_pyobject(py)?.into_any().unbind(),
+        DbValue::Real(x) => x.into_pyobject(py)?.into_any().unbind(),
+        DbValue::Text(text) => PyString::new(py, text).into_any().unbind(),
+        DbValue::Blob(blob) => PyBytes::new(py, blob).into_any().unbind(),
+    })
+}
+
+/// Key of a row: the value of its key column, or a tuple of the values of several
+fn db_key(py: Python<'_>, mut values: Vec<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+    if values.len() == 1 {
+        Ok(values.remove(0))
+    } else {
+        Ok(PyTuple::new(py, values)?.into_any().unbind())
+    }
+}
+
+/// Bytes scanned of a value of a DB-API row: UTF-8 of text, bytes of binary values (bytes,
+/// bytearray or memoryview, the bytea of some drivers), None for values of other types
+fn db_bytes(value: &Bound<'_, PyAny>) -> PyResult<Option<Vec<u8>>> {
+    if let Ok(text) = value.downcast::<PyString>() {
+        Ok(Some(text.to_cow()?.as_bytes().to_vec()))
+    } else if let Ok(bytes) = value.downcast::<PyBytes>() {
+        Ok(Some(bytes.as_bytes().to_vec()))
+    } else if let Ok(bytes) = value.downcast::<PyByteArray>() {
+        Ok(Some(bytes.to_vec()))
+    } else if value.is_instance_of::<PyMemoryView>() {
+        Ok(Some(value.call_method0("tobytes")?.extract()?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Compile pattern files into a matcher bundle, as a CI step: the patterns of every file,
+/// with their file as "source" metadata, are checked by the validators, each a validator of
+/// TextMatcherBuilder.validate or a tuple of its arguments, built with the
+/// TextMatcher keyword arguments and checked against the golden tests, if any, before the
+/// bundle is written, signed with `signing_key` when given
+#[pyfunction]
+#[pyo3(signature = (pattern_files, output, golden=None, validators=None, signing_key=None, **options))]
+fn compile_bundle(
+    py: Python<'_>,
+    pattern_files: Vec<String>,
+    output: PathBuf,
+    golden: Option<&Bound<'_, PyAny>>,
+    validators: Option<Vec<Bound<'_, PyAny>>>,
+    signing_key: Option<&[u8]>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<BundleInfo> {
+    let builder = Bound::new(py, TextMatcherBuilder::new(py, None))?;
+    for path in &pattern_files {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(
+            "metadata",
+            [("source", path)].into_iter().collect::<HashMap<_, _>>(),
+        )?;
+        builder.call_method("add_patterns_from_file", (path,), Some(&kwargs))?;
+    }
+    for validator in validators.unwrap_or_default() {
+        match validator.downcast::<PyTuple>() {
+            Ok(args) => builder.call_method1("validate", args)?,
+            Err(_) => builder.call_method1("validate", (validator,))?,
+        };
+    }
+    builder.call_method("options", (), options)?;
+    let matcher = builder
+        .call_method0("build")?
+        .downcast_into::<TextMatcher>()?;
+
+    if let Some(golden) = golden {
+        let report = run_pattern_tests(py, matcher.get(), golden)?;
+        if !report.passed() {
+            let failures: Vec<String> = report
+                .failures
+                .iter()
+                .map(|failure| format!("{}: {}", failure.case, failure.message))
+                .collect();
+            return Err(ConfigError::Invalid(format!(
+                "Pattern tests failed ({}): {}",
+                failures.len(),
+                failures.join(", ")
+            ))
+            .into());
+        }
+    }
+    matcher.get().save_bundle(py, output, signing_key)
+}
+
+/// Ed25519 verify key of the 32-byte seed `signing_key`, for load_bundle to check the
+/// bundles it signs
+#[pyfunction]
+fn bundle_verify_key<'py>(py: Python<'py>, signing_key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    Ok(PyBytes::new(py, &Bundle::verify_key(signing_key)?))
+}
+
+/// What the bundle at `path` holds, read without building its matcher
+#[pyfunction]
+fn bundle_info(py: Python<'_>, path: PathBuf) -> PyResult<BundleInfo> {
+    py.allow_threads(|| Bundle::load(&path))
+        .map(|bundle| bundle.info().clone())
+        .map_err(io_error)
+}
+
+/// Time scans of `data` for `patterns` under every configuration of `configs`, `repeat`
+/// times each after a warmup run, to pick an engine, chunk size and thread count empirically
+/// `configs` is a list of dicts, or a dict of lists expanded to every combination, with any of
+/// the "engine", "chunk_size" and "n_threads" keys. Without chunk_size and n_threads, the data
+/// is scanned in a single pass
+/// Returns a list of BenchResult, in the order of the configurations
+#[pyfunction]
+#[pyo3(signature = (patterns, data, configs, repeat=3, overlapping=None, case_insensitive=None, whole_word=None))]
+#[allow(clippy::too_many_arguments)]
+fn benchmark(
+    py: Python<'_>,
+    patterns: Vec<String>,
+    data: &[u8],
+    configs: &Bound<'_, PyAny>,
+    repeat: usize,
+    overlapping: Option<bool>,
+    case_insensitive: Option<bool>,
+    whole_word: Option<bool>,
+) -> PyResult<Vec<BenchResult>> {
+    if repeat == 0 {
+        return Err(PyValueError::new_err("repeat must be positive"));
+    }
+    let configs = extract_bench_configs(configs)?;
+    let defaults = MatcherOptions::default();
+    let options = MatcherOptions {
+        overlapping: overlapping.unwrap_or(defaults.overlapping),
+        case_insensitive: case_insensitive.unwrap_or(defaults.case_insensitive),
+        whole_word: whole_word.unwrap_or(defaults.whole_word),
+        ..defaults
+    };
+    Ok(py.allow_threads(|| bench::benchmark(&patterns, &options, data, &configs, repeat))?)
+}
+
+/// Start exporting scan spans and metrics over OTLP/HTTP
+/// `endpoint` is the collector base URL, e.g. http://localhost:4318, and defaults to the
+/// standard OTEL_EXPORTER_OTLP_* environment variables
+#[cfg(feature = "otel")]
+#[pyfunction]
+#[pyo3(signature = (endpoint=None, service_name=None))]
+fn enable_telemetry(endpoint: Option<&str>, service_name: Option<&str>) -> PyResult<()> {
+    crate::telemetry::enable(endpoint, service_name)
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+/// Flush pending spans and metrics and stop exporting them
+#[cfg(feature = "otel")]
+#[pyfunction]
+fn shutdown_telemetry(py: Python<'_>) -> PyResult<()> {
+    py.allow_threads(crate::telemetry::shutdown)
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+#[pymodule(gil_used = false)]
+fn voluta(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    crate::errors::register(m)?;
+    m.add_class::<TextMatcher>()?;
+    m.add_class::<TextMatcherBuilder>()?;
+    m.add_class::<BundleInfo>()?;
+    m.add_class::<ConfirmMatcher>()?;
+    m.add_class::<IncrementalMatcher>()?;
+    m.add_class::<TokenMatcher>()?;
+    m.add_class::<PhraseMatcher>()?;
+    m.add_class::<LineIndex>()?;
+    m.add_class::<MatchCollection>()?;
+    m.add_class::<SpooledMatches>()?;
+    m.add_class::<SinkSummary>()?;
+    m.add_class::<ScanStats>()?;
+    m.add_class::<BenchResult>()?;
+    m.add_class::<ScanReport>()?;
+    m.add_class::<FileSummary>()?;
+    m.add_class::<ScanPlan>()?;
+    m.add_class::<PlanResult>()?;
+    m.add_class::<PatternDiff>()?;
+    m.add_class::<Comparison>()?;
+    m.add_class::<GoldenReport>()?;
+    m.add_class::<GoldenFailure>()?;
+    m.add_class::<DensityEstimate>()?;
+    m.add_class::<PreScreen>()?;
+    m.add_class::<WebhookSink>()?;
+    m.add_function(wrap_pyfunction!(benchmark, m)?)?;
+    m.add_function(wrap_pyfunction!(bundle_info, m)?)?;
+    m.add_function(wrap_pyfunction!(bundle_verify_key, m)?)?;
+    m.add_function(wrap_pyfunction!(compile_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(format_grep, m)?)?;
+    m.add_function(wrap_pyfunction!(hash_text, m)?)?;
+    m.add_function(wrap_pyfunction!(run_pattern_tests, m)?)?;
+    m.add_function(wrap_pyfunction!(warmup, m)?)?;
+    #[cfg(feature = "kafka")]
+    m.add_class::<crate::kafka::KafkaSink>()?;
+    #[cfg(feature = "otel")]
+    {
+        m.add_function(wrap_pyfunction!(enable_telemetry, m)?)?;
+        m.add_function(wrap_pyfunction!(shutdown_telemetry, m)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,578 @@
+//! Standalone HTML and Markdown reports of a scan run, e.g. as compliance evidence
+
+use crate::audit::{AuditRun, AuditTotals};
+use crate::highlight::{escape_html, line_context};
+use crate::matcher::Matcher;
+use crate::sink::new_scan_id;
+use aho_corasick::PatternID;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::fs;
+use std::io::Result;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Files from this size on are scanned in parallel chunks rather than by a single worker
+pub(crate) const SPLIT_FILE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Options of a scan report
+#[derive(Clone, Debug)]
+pub struct ReportOptions {
+    /// Title of the report
+    pub title: Option<String>,
+    /// Identifier of the scan, generated if not given
+    pub scan_id: Option<String>,
+    /// Bytes of context shown on each side of a finding, within its line
+    pub context: usize,
+    /// Findings listed per file, the tallies always count every match
+    pub max_findings: Option<usize>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            title: None,
+            scan_id: None,
+            context: 40,
+            max_findings: Some(100),
+        }
+    }
+}
+
+/// A match with its position and the text around it on its line
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub start: usize,
+    pub end: usize,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based byte column
+    pub column: usize,
+    pub pattern: PatternID,
+    pub before: String,
+    pub text: String,
+    pub after: String,
+}
+
+/// Findings of one file of a scan report
+#[derive(Clone, Debug)]
+pub struct FileReport {
+    pub path: String,
+    pub bytes_scanned: usize,
+    /// Number of matches, including those not listed in `findings`
+    pub matches: usize,
+    /// Number of matches per pattern index
+    pub pattern_counts: Vec<usize>,
+    pub findings: Vec<Finding>,
+    /// Why the file could not be scanned
+    pub error: Option<String>,
+}
+
+/// Report of a scan run over a set of files
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct ScanReport {
+    pub title: String,
+    pub scan_id: String,
+    /// See `Matcher::fingerprint`
+    pub fingerprint: String,
+    /// When the scan started, as an RFC 3339 UTC timestamp
+    pub started_at: String,
+    /// Duration of the scan in seconds
+    pub duration: f64,
+    pub patterns: Arc<Vec<String>>,
+    pub files: Vec<FileReport>,
+    /// Findings carry no text as the matcher reports digests instead of patterns
+    pub redacted: bool,
+}
+
+impl ScanReport {
+    /// Scan the files at `paths` in parallel, largest first; files that cannot be read are
+    /// reported with their error instead of failing the scan, which only fails when it cannot
+    /// be recorded in the matcher's audit log
+    pub fn scan(matcher: &Matcher, paths: &[String], options: &ReportOptions) -> Result<Self> {
+        let scan_id = options.scan_id.clone().unwrap_or_else(new_scan_id);
+        let roots: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let audit = AuditRun::start(matcher, "scan_report", &roots)
+            .map(|run| run.with_scan_id(scan_id.clone()));
+        let report = Self::scan_files(matcher, paths, options, scan_id);
+        AuditRun::end(audit, Ok(report), |report| AuditTotals {
+            files_scanned: report.files.iter().filter(|f| f.error.is_none()).count(),
+            files_failed: report.files.iter().filter(|f| f.error.is_some()).count(),
+            bytes_scanned: report.bytes_scanned(),
+            matches: report.total_matches(),
+        })
+    }
+
+    fn scan_files(
+        matcher: &Matcher,
+        paths: &[String],
+        options: &ReportOptions,
+        scan_id: String,
+    ) -> Self {
+        let started_at = format_timestamp(SystemTime::now());
+        let started = Instant::now();
+
+        // Largest files first, so the scan does not end waiting on a big file picked up last,
+        // and files above SPLIT_FILE_SIZE are themselves split into chunks whose tasks idle
+        // workers steal
+        let mut order: Vec<(u64, usize)> = paths
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| (fs::metadata(path).map_or(0, |m| m.len()), idx))
+            .collect();
+        order.sort_unstable_by_key(|&(size, idx)| (Reverse(size), idx));
+
+        let mut files: Vec<(usize, FileReport)> = order
+            .into_par_iter()
+            .map(|(_, idx)| (idx, scan_file(matcher, &paths[idx], options)))
+            .collect();
+        files.sort_unstable_by_key(|(idx, _)| *idx);
+        let files = files.into_iter().map(|(_, file)| file).collect();
+
+        Self {
+            title: options
+                .title
+                .clone()
+                .unwrap_or_else(|| "Voluta scan report".to_string()),
+            scan_id,
+            fingerprint: matcher.fingerprint().to_string(),
+            started_at,
+            duration: started.elapsed().as_secs_f64(),
+            patterns: matcher.shared_patterns(),
+            files,
+            redacted: matcher.hashed(),
+        }
+    }
+
+    pub fn bytes_scanned(&self) -> usize {
+        self.files.iter().map(|file| file.bytes_scanned).sum()
+    }
+
+    pub fn total_matches(&self) -> usize {
+        self.files.iter().map(|file| file.matches).sum()
+    }
+
+    /// Number of files holding at least one match
+    pub fn files_with_matches(&self) -> usize {
+        self.files.iter().filter(|file| file.matches > 0).count()
+    }
+
+    /// (pattern, matches, files) tallies in pattern order, including patterns never matched
+    pub fn pattern_tallies(&self) -> Vec<(&str, usize, usize)> {
+        let mut matches = vec![0; self.patterns.len()];
+        let mut files = vec![0; self.patterns.len()];
+        for file in &self.files {
+            for (idx, &count) in file.pattern_counts.iter().enumerate() {
+                matches[idx] += count;
+                files[idx] += usize::from(count > 0);
+            }
+        }
+        self.patterns
+            .iter()
+            .enumerate()
+            .map(|(idx, pattern)| (pattern.as_str(), matches[idx], files[idx]))
+            .collect()
+    }
+
+    fn summary(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Scan id", self.scan_id.clone()),
+            ("Matcher fingerprint", self.fingerprint.clone()),
+            ("Started", self.started_at.clone()),
+            ("Duration", format!("{:.3} s", self.duration)),
+            ("Patterns", self.patterns.len().to_string()),
+            ("Files scanned", self.files.len().to_string()),
+            ("Files with matches", self.files_with_matches().to_string()),
+            (
+                "Unreadable files",
+                self.files
+                    .iter()
+                    .filter(|file| file.error.is_some())
+                    .count()
+                    .to_string(),
+            ),
+            ("Bytes scanned", self.bytes_scanned().to_string()),
+            ("Matches", self.total_matches().to_string()),
+        ]
+    }
+
+    /// Render the report as a standalone HTML document
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{REPORT_CSS}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        );
+
+        html.push_str("<h2>Summary</h2>\n<table class=\"summary\">\n");
+        for (name, value) in self.summary() {
+            let _ = writeln!(
+                html,
+                "<tr><th>{name}</th><td>{}</td></tr>",
+                escape_html(&value)
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str(
+            "<h2>Patterns</h2>\n<table>\n<tr><th>Pattern</th><th>Matches</th><th>Files</th></tr>\n",
+        );
+        for (pattern, matches, files) in self.pattern_tallies() {
+            let _ = writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td>{matches}</td><td>{files}</td></tr>",
+                escape_html(pattern)
+            );
+        }
+        html.push_str("</table>\n<h2>Files</h2>\n");
+
+        for file in &self.files {
+            let _ = writeln!(
+                html,
+                "<h3><code>{}</code></h3>\n<p>{}</p>",
+                escape_html(&file.path),
+                escape_html(&file.status())
+            );
+            if file.findings.is_empty() {
+                continue;
+            }
+            html.push_str(
+                "<table>\n<tr><th>Line</th><th>Column</th><th>Offset</th><th>Pattern</th>",
+            );
+            if !self.redacted {
+                html.push_str("<th>Context</th>");
+            }
+            html.push_str("</tr>\n");
+            for finding in &file.findings {
+                let _ = write!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td><code>{}</code></td>",
+                    finding.line,
+                    finding.column,
+                    finding.start,
+                    escape_html(&self.patterns[finding.pattern.as_usize()])
+                );
+                if !self.redacted {
+                    let _ = write!(
+                        html,
+                        "<td><code>{}<mark>{}</mark>{}</code></td>",
+                        escape_html(&finding.before),
+                        escape_html(&finding.text),
+                        escape_html(&finding.after)
+                    );
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Render the report as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!(
+            "# {}\n\n## Summary\n\n| | |\n|---|---|\n",
+            escape_markdown(&self.title)
+        );
+        for (name, value) in self.summary() {
+            let _ = writeln!(md, "| {name} | {} |", escape_markdown(&value));
+        }
+
+        md.push_str("\n## Patterns\n\n| Pattern | Matches | Files |\n|---|---:|---:|\n");
+        for (pattern, matches, files) in self.pattern_tallies() {
+            let _ = writeln!(md, "| {} | {matches} | {files} |", escape_markdown(pattern));
+        }
+        md.push_str("\n## Files\n");
+
+        for file in &self.files {
+            let _ = write!(
+                md,
+                "\n### {}\n\n{}\n",
+                escape_markdown(&file.path),
+                escape_markdown(&file.status())
+            );
+            if file.findings.is_empty() {
+                continue;
+            }
+            if self.redacted {
+                md.push_str("\n| Line | Column | Offset | Pattern |\n|---:|---:|---:|---|\n");
+            } else {
+                md.push_str(
+                    "\n| Line | Column | Offset | Pattern | Context |\n|---:|---:|---:|---|---|\n",
+                );
+            }
+            for finding in &file.findings {
+                let _ = write!(
+                    md,
+                    "| {} | {} | {} | {} |",
+                    finding.line,
+                    finding.column,
+                    finding.start,
+                    escape_markdown(&self.patterns[finding.pattern.as_usize()])
+                );
+                if !self.redacted {
+                    let _ = write!(
+                        md,
+                        " {}**{}**{} |",
+                        escape_markdown(&finding.before),
+                        escape_markdown(&finding.text),
+                        escape_markdown(&finding.after)
+                    );
+                }
+                md.push('\n');
+            }
+        }
+        md
+    }
+}
+
+impl FileReport {
+    fn status(&self) -> String {
+        match &self.error {
+            Some(error) => format!("Could not be scanned: {error}"),
+            None if self.matches > self.findings.len() => format!(
+                "{} matches in {} bytes, showing the first {}",
+                self.matches,
+                self.bytes_scanned,
+                self.findings.len()
+            ),
+            None => format!("{} matches in {} bytes", self.matches, self.bytes_scanned),
+        }
+    }
+}
+
+const REPORT_CSS: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1em}\
+th,td{border:1px solid #ccc;padding:.3em .6em;text-align:left;vertical-align:top}\
+th{background:#f4f4f4}code{white-space:pre-wrap;word-break:break-all}\
+mark{background:#ffd54f}";
+
+fn scan_file(matcher: &Matcher, path: &str, options: &ReportOptions) -> FileReport {
+    let (mmap, mut matches) = match matcher.match_mapped("scan_report", path, false) {
+        Ok(scanned) => scanned,
+        Err(e) => {
+            return FileReport {
+                path: path.to_string(),
+                bytes_scanned: 0,
+                matches: 0,
+                pattern_counts: vec![0; matcher.patterns().len()],
+                findings: Vec::new(),
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    matches.sort_unstable();
+    let listed = options
+        .max_findings
+        .unwrap_or(usize::MAX)
+        .min(matches.len());
+    let mut pattern_counts = vec![0; matcher.patterns().len()];
+    for &(_, _, pattern) in &matches {
+        pattern_counts[pattern.as_usize()] += 1;
+    }
+
+    // Count lines incrementally, the matches are sorted by offset
+    let mut findings = Vec::with_capacity(listed);
+    let mut line = 1;
+    let mut counted = 0;
+    for &(start, end, pattern) in &matches[..listed] {
+        line += memchr::memchr_iter(b'\n', &mmap[counted..start]).count();
+        counted = start;
+        let line_start = memchr::memrchr(b'\n', &mmap[..start]).map_or(0, |pos| pos + 1);
+
+        let mut finding = Finding {
+            start,
+            end,
+            line,
+            column: start - line_start + 1,
+            pattern,
+            before: String::new(),
+            text: String::new(),
+            after: String::new(),
+        };
+
+        // Hash-only matchers keep the matched text and its surroundings out of reports
+        if !matcher.hashed() {
+            (finding.before, finding.text, finding.after) =
+                line_context(&mmap, start, end, options.context);
+        }
+        findings.push(finding);
+    }
+
+    FileReport {
+        path: path.to_string(),
+        bytes_scanned: mmap.len(),
+        matches: matches.len(),
+        pattern_counts,
+        findings,
+        error: None,
+    }
+}
+
+/// Escape text for Markdown, including table cell separators and line breaks
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' | '~' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with second precision
+pub(crate) fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ScanReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanReport(files_scanned={}, matches={}, bytes_scanned={})",
+            self.files.len(),
+            self.total_matches(),
+            self.bytes_scanned()
+        )
+    }
+
+    #[getter]
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    #[getter]
+    fn scan_id(&self) -> String {
+        self.scan_id.clone()
+    }
+
+    #[getter]
+    fn fingerprint(&self) -> String {
+        self.fingerprint.clone()
+    }
+
+    #[getter]
+    fn started_at(&self) -> String {
+        self.started_at.clone()
+    }
+
+    #[getter]
+    fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    #[getter]
+    fn files_scanned(&self) -> usize {
+        self.files.len()
+    }
+
+    #[getter(bytes_scanned)]
+    fn py_bytes_scanned(&self) -> usize {
+        self.bytes_scanned()
+    }
+
+    #[getter(total_matches)]
+    fn py_total_matches(&self) -> usize {
+        self.total_matches()
+    }
+
+    /// Number of matches per file path
+    #[getter]
+    fn file_matches(&self) -> Vec<(String, usize)> {
+        self.files
+            .iter()
+            .map(|file| (file.path.clone(), file.matches))
+            .collect()
+    }
+
+    /// (pattern, matches, files) tallies for every pattern
+    #[getter(pattern_tallies)]
+    fn py_pattern_tallies(&self) -> Vec<(String, usize, usize)> {
+        self.pattern_tallies()
+            .into_iter()
+            .map(|(pattern, matches, files)| (pattern.to_string(), matches, files))
+            .collect()
+    }
+
+    /// (path, error) of the files that could not be scanned
+    #[getter]
+    fn errors(&self) -> Vec<(String, String)> {
+        self.files
+            .iter()
+            .filter_map(|file| Some((file.path.clone(), file.error.clone()?)))
+            .collect()
+    }
+
+    #[pyo3(name = "to_html")]
+    fn py_to_html(&self) -> String {
+        self.to_html()
+    }
+
+    #[pyo3(name = "to_markdown")]
+    fn py_to_markdown(&self) -> String {
+        self.to_markdown()
+    }
+
+    /// Write the report to `path`, as HTML or Markdown depending on `format` or the extension
+    #[pyo3(signature = (path, format=None))]
+    fn save(&self, path: &str, format: Option<&str>) -> PyResult<()> {
+        let format = match format {
+            Some(format) => format.to_ascii_lowercase(),
+            None => match path.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()) {
+                Some(ext) if ext == "html" || ext == "htm" => "html".to_string(),
+                Some(ext) if ext == "md" || ext == "markdown" => "markdown".to_string(),
+                _ => {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "Cannot infer the report format from the path, pass format='html' or 'markdown'",
+                    ))
+                }
+            },
+        };
+        let content = match format.as_str() {
+            "html" => self.to_html(),
+            "markdown" | "md" => self.to_markdown(),
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown report format '{}', expected 'html' or 'markdown'",
+                    other
+                )))
+            }
+        };
+        std::fs::write(path, content).map_err(crate::errors::io_error)
+    }
+}
@@ -0,0 +1,57 @@
+//! Reverse scans: the last matches of a buffer, found by searching windows of growing size
+//! back from its end instead of scanning it from the start and discarding
+
+use crate::matcher::Matcher;
+use aho_corasick::PatternID;
+
+/// Bytes of the last window searched first, the windows before it doubling up to
+/// `MAX_WINDOW`, so a match near the end of a huge append-only log costs one small search
+const FIRST_WINDOW: usize = 64 * 1024;
+const MAX_WINDOW: usize = 16 * 1024 * 1024;
+
+/// Matches of a buffer from the last to the first, every occurrence counting whatever the
+/// overlapping setting and without post-processing, ordered by start, end then pattern
+/// Whole word matching and exclusion zones apply as in forward scans
+pub struct RevMatches<'m, 'h> {
+    matcher: &'m Matcher,
+    data: &'h [u8],
+    // Start of the part of `data` already searched
+    searched: usize,
+    window: usize,
+    // Matches of the last window searched not yet returned, sorted in forward order
+    pending: Vec<(usize, usize, PatternID)>,
+}
+
+impl<'m, 'h> RevMatches<'m, 'h> {
+    pub(crate) fn new(matcher: &'m Matcher, data: &'h [u8]) -> Self {
+        Self {
+            matcher,
+            data,
+            searched: data.len(),
+            window: FIRST_WINDOW,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Iterator for RevMatches<'_, '_> {
+    type Item = (usize, usize, PatternID);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(m) = self.pending.pop() {
+                return Some(m);
+            }
+            if self.searched == 0 {
+                return None;
+            }
+            let from = self.searched.saturating_sub(self.window);
+            self.pending = self
+                .matcher
+                .occurrences_between(self.data, from, self.searched);
+            self.pending.sort_unstable();
+            self.searched = from;
+            self.window = (self.window * 2).min(MAX_WINDOW);
+        }
+    }
+}
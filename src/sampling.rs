@@ -0,0 +1,227 @@
+//! Match density estimated from a random sample of the chunks of a file, extrapolated with
+//! confidence bounds, to rank archives by how much a full scan would find
+
+use crate::matcher::Matcher;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::io::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Normal quantile of the two-sided 95% confidence bounds
+const Z_95: f64 = 1.959_964;
+
+/// Options of a density estimate
+#[derive(Clone, Copy, Debug)]
+pub struct SampleOptions {
+    /// Share of the chunks scanned, in (0, 1]; at least one chunk is
+    pub fraction: f64,
+    pub chunk_size: usize,
+    /// Seed of the chunk draw, drawn from the clock when None
+    pub seed: Option<u64>,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self {
+            fraction: 0.01,
+            chunk_size: 64 * 1024,
+            seed: None,
+        }
+    }
+}
+
+/// Matches of a file extrapolated from a sample of its chunks
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityEstimate {
+    pub bytes: usize,
+    pub chunks: usize,
+    pub sampled_chunks: usize,
+    pub sampled_bytes: usize,
+    /// Matches found in the sampled chunks
+    pub sampled_matches: usize,
+    /// Matches expected in the whole file
+    pub estimated_matches: f64,
+    /// Bounds of the 95% confidence interval of `estimated_matches`, the upper one infinite
+    /// when a single chunk of several was sampled
+    pub lower: f64,
+    pub upper: f64,
+    pub seed: u64,
+}
+
+impl DensityEstimate {
+    /// Matches expected per MB (10^6 bytes)
+    pub fn matches_per_mb(&self) -> f64 {
+        if self.bytes == 0 {
+            0.0
+        } else {
+            self.estimated_matches / self.bytes as f64 * 1e6
+        }
+    }
+}
+
+/// Estimate the matches of `data` from a random sample of its chunks
+/// Matches are counted as match_bytes reports them, those starting in a sampled chunk being
+/// found with the context around it; the matches of the chunks are extrapolated with a ratio
+/// estimator, and the bounds assume their normal approximation
+pub(crate) fn estimate(matcher: &Matcher, data: &[u8], options: &SampleOptions) -> DensityEstimate {
+    let seed = options.seed.unwrap_or_else(|| {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        nanos ^ (u64::from(std::process::id()) << 32)
+    });
+    let chunk_size = options.chunk_size.max(1);
+    let chunks = data.len().div_ceil(chunk_size);
+    let sampled = ((chunks as f64 * options.fraction).ceil() as usize).clamp(1, chunks.max(1));
+    let picked = if chunks == 0 {
+        Vec::new()
+    } else {
+        sample_indices(chunks, sampled, seed)
+    };
+
+    // (bytes, matches) of every sampled chunk
+    let counts: Vec<(usize, usize)> = picked
+        .par_iter()
+        .map(|&idx| {
+            let from = idx * chunk_size;
+            let to = (from + chunk_size).min(data.len());
+            (to - from, matcher.count_between(data, from, to))
+        })
+        .collect();
+
+    let sampled_bytes: usize = counts.iter().map(|c| c.0).sum();
+    let sampled_matches: usize = counts.iter().map(|c| c.1).sum();
+    let ratio = if sampled_bytes == 0 {
+        0.0
+    } else {
+        sampled_matches as f64 / sampled_bytes as f64
+    };
+    let estimated_matches = ratio * data.len() as f64;
+
+    let k = counts.len();
+    let (lower, upper) = if k == chunks {
+        (estimated_matches, estimated_matches)
+    } else if k < 2 {
+        (sampled_matches as f64, f64::INFINITY)
+    } else {
+        // Variance of the ratio estimator, with the finite population correction
+        let residuals: f64 = counts
+            .iter()
+            .map(|&(bytes, matches)| (matches as f64 - ratio * bytes as f64).powi(2))
+            .sum::<f64>()
+            / (k - 1) as f64;
+        let n = chunks as f64;
+        let variance = n * n * (1.0 - k as f64 / n) * residuals / k as f64;
+        let margin = Z_95 * variance.sqrt();
+        (
+            (estimated_matches - margin).max(sampled_matches as f64),
+            estimated_matches + margin,
+        )
+    };
+
+    DensityEstimate {
+        bytes: data.len(),
+        chunks,
+        sampled_chunks: k,
+        sampled_bytes,
+        sampled_matches,
+        estimated_matches,
+        lower,
+        upper,
+        seed,
+    }
+}
+
+/// Estimate the matches of the file at `path`, memory-mapped, see `estimate`
+pub(crate) fn estimate_file(
+    matcher: &Matcher,
+    path: &str,
+    options: &SampleOptions,
+) -> Result<DensityEstimate> {
+    let mmap = matcher.map_file(path)?;
+    Ok(estimate(matcher, &mmap, options))
+}
+
+/// `k` distinct indices below `n`, drawn by a partial Fisher-Yates shuffle and sorted
+fn sample_indices(n: usize, k: usize, seed: u64) -> Vec<usize> {
+    let mut state = seed;
+    let mut next = move || {
+        // SplitMix64
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    let mut indices: Vec<usize> = (0..n).collect();
+    for i in 0..k {
+        let j = i + (next() % (n - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(k);
+    indices.sort_unstable();
+    indices
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl DensityEstimate {
+    fn __repr__(&self) -> String {
+        format!(
+            "DensityEstimate(estimated_matches={:.1}, lower={:.1}, upper={:.1}, sampled_chunks={}/{})",
+            self.estimated_matches, self.lower, self.upper, self.sampled_chunks, self.chunks
+        )
+    }
+
+    #[getter]
+    fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    #[getter]
+    fn chunks(&self) -> usize {
+        self.chunks
+    }
+
+    #[getter]
+    fn sampled_chunks(&self) -> usize {
+        self.sampled_chunks
+    }
+
+    #[getter]
+    fn sampled_bytes(&self) -> usize {
+        self.sampled_bytes
+    }
+
+    #[getter]
+    fn sampled_matches(&self) -> usize {
+        self.sampled_matches
+    }
+
+    #[getter]
+    fn estimated_matches(&self) -> f64 {
+        self.estimated_matches
+    }
+
+    #[getter]
+    fn lower(&self) -> f64 {
+        self.lower
+    }
+
+    #[getter]
+    fn upper(&self) -> f64 {
+        self.upper
+    }
+
+    #[getter(matches_per_mb)]
+    fn py_matches_per_mb(&self) -> f64 {
+        self.matches_per_mb()
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+}
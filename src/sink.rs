@@ -0,0 +1,541 @@
+use crate::audit::hostname;
+#[cfg(feature = "kafka")]
+use crate::kafka::{KafkaSink, KafkaWriter};
+use crate::report::format_timestamp;
+use crate::webhook::{WebhookSink, WebhookWriter};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Result, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the JSON schema of exported findings
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputSchema {
+    /// Unversioned shapes of the releases before schema versions, which differ between
+    /// exports, kept so existing parsers do not break
+    #[default]
+    Legacy,
+    /// Version 1, every finding being a `JsonFinding`
+    V1,
+}
+
+impl OutputSchema {
+    /// Latest schema version
+    pub const LATEST: u32 = 1;
+
+    /// Schema of a version number, 0 being the legacy shapes
+    pub fn from_version(version: u32) -> Option<Self> {
+        match version {
+            0 => Some(Self::Legacy),
+            1 => Some(Self::V1),
+            _ => None,
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        match self {
+            Self::Legacy => 0,
+            Self::V1 => 1,
+        }
+    }
+}
+
+/// A finding as exported in JSON by version 1 of the output schema, the same object in
+/// NDJSON lines, webhook batches, Kafka messages and collections
+#[derive(Serialize)]
+pub struct JsonFinding<'a> {
+    pub schema_version: u32,
+    /// Null outside sinks
+    pub scan_id: Option<&'a str>,
+    /// Scanned path or log source, null for buffers
+    pub file: Option<&'a str>,
+    pub start: usize,
+    pub end: usize,
+    pub pattern: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<&'a Provenance>,
+}
+
+impl<'a> JsonFinding<'a> {
+    pub fn new(
+        scan_id: Option<&'a str>,
+        file: Option<&'a str>,
+        start: usize,
+        end: usize,
+        pattern: &'a str,
+        provenance: Option<&'a Provenance>,
+    ) -> Self {
+        Self {
+            schema_version: 1,
+            scan_id,
+            file,
+            start,
+            end,
+            pattern,
+            provenance,
+        }
+    }
+}
+
+/// Envelope attributing findings to the scan that emitted them, attached to every finding by
+/// sinks opened with one, so findings aggregated from many hosts stay attributable
+#[derive(Clone, Debug, Serialize)]
+pub struct Provenance {
+    pub scan_id: String,
+    pub host: Option<String>,
+    /// See `Matcher::fingerprint`
+    pub fingerprint: String,
+    /// Path or URI of the scanned data, None for buffers
+    pub source: Option<String>,
+    /// When the scan started, as an RFC 3339 UTC timestamp
+    pub timestamp: String,
+}
+
+impl Provenance {
+    /// Provenance of a scan starting now on this host
+    pub fn new(scan_id: &str, fingerprint: &str, source: Option<&str>) -> Self {
+        Self {
+            scan_id: scan_id.to_string(),
+            host: hostname(),
+            fingerprint: fingerprint.to_string(),
+            source: source.map(str::to_string),
+            timestamp: format_timestamp(SystemTime::now()),
+        }
+    }
+}
+
+/// Output format of a file sink
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkFormat {
+    Ndjson,
+    Csv,
+    Sqlite,
+}
+
+impl SinkFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "csv" => Some(Self::Csv),
+            "sqlite" | "sqlite3" | "db" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the extension of the sink path
+    pub fn from_path(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| Self::parse(&ext.to_ascii_lowercase()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+            Self::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// Destination for matches written as they are found
+pub trait MatchSink {
+    /// Write a match of `pattern`, whose tags are `tags`
+    fn write(&mut self, start: usize, end: usize, pattern: &str, tags: &[String]) -> Result<()>;
+
+    /// Make the matches written so far visible, called periodically by scans that run
+    /// continuously
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Flush any buffered output, called once after the scan
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// Line of an NDJSON sink, a match and the provenance of its scan if attached
+#[derive(Serialize)]
+struct NdjsonRecord<'a> {
+    start: usize,
+    end: usize,
+    pattern: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<&'a Provenance>,
+}
+
+/// Writes one JSON object per line
+pub struct NdjsonSink {
+    writer: BufWriter<File>,
+    scan_id: String,
+    file: Option<String>,
+    provenance: Option<Provenance>,
+    schema: OutputSchema,
+}
+
+impl NdjsonSink {
+    pub fn create(
+        path: &str,
+        scan_id: String,
+        file: Option<String>,
+        provenance: Option<Provenance>,
+        schema: OutputSchema,
+    ) -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            scan_id,
+            file,
+            provenance,
+            schema,
+        })
+    }
+}
+
+impl MatchSink for NdjsonSink {
+    fn write(&mut self, start: usize, end: usize, pattern: &str, _tags: &[String]) -> Result<()> {
+        match self.schema {
+            OutputSchema::Legacy => serde_json::to_writer(
+                &mut self.writer,
+                &NdjsonRecord {
+                    start,
+                    end,
+                    pattern,
+                    provenance: self.provenance.as_ref(),
+                },
+            )?,
+            OutputSchema::V1 => serde_json::to_writer(
+                &mut self.writer,
+                &JsonFinding::new(
+                    Some(&self.scan_id),
+                    self.file.as_deref(),
+                    start,
+                    end,
+                    pattern,
+                    self.provenance.as_ref(),
+                ),
+            )?,
+        }
+        self.writer.write_all(b"\n")
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writes a CSV file with a start,end,pattern header, followed by
+/// scan_id,host,fingerprint,source,timestamp columns when provenance is attached
+pub struct CsvSink {
+    writer: BufWriter<File>,
+    // Provenance columns, the same on every row
+    suffix: String,
+}
+
+impl CsvSink {
+    pub fn create(path: &str, provenance: Option<Provenance>) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(b"start,end,pattern")?;
+        let mut suffix = String::new();
+        if let Some(provenance) = provenance {
+            writer.write_all(b",scan_id,host,fingerprint,source,timestamp")?;
+            for field in [
+                Some(provenance.scan_id),
+                provenance.host,
+                Some(provenance.fingerprint),
+                provenance.source,
+                Some(provenance.timestamp),
+            ] {
+                suffix.push(',');
+                suffix.push_str(&csv_field(field.as_deref().unwrap_or_default()));
+            }
+        }
+        writer.write_all(b"\n")?;
+        Ok(Self { writer, suffix })
+    }
+}
+
+/// Quote fields containing separators, quotes or line breaks as per RFC 4180
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+impl MatchSink for CsvSink {
+    fn write(&mut self, start: usize, end: usize, pattern: &str, _tags: &[String]) -> Result<()> {
+        writeln!(
+            self.writer,
+            "{},{},{}{}",
+            start,
+            end,
+            csv_field(pattern),
+            self.suffix
+        )
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+}
+
+const SQLITE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS findings (
+        id INTEGER PRIMARY KEY,
+        scan_id TEXT NOT NULL,
+        file TEXT,
+        start_offset INTEGER NOT NULL,
+        end_offset INTEGER NOT NULL,
+        pattern TEXT NOT NULL,
+        tag TEXT,
+        found_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS findings_scan_id ON findings (scan_id);
+    CREATE INDEX IF NOT EXISTS findings_file ON findings (file);
+    CREATE INDEX IF NOT EXISTS findings_pattern ON findings (pattern);
+    CREATE TABLE IF NOT EXISTS scans (
+        scan_id TEXT PRIMARY KEY,
+        host TEXT,
+        fingerprint TEXT NOT NULL,
+        source TEXT,
+        started_at TEXT NOT NULL
+    );
+";
+
+pub(crate) fn sqlite_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Appends findings to a `findings` table in an SQLite database, creating it if needed
+/// All findings of one scan are written in a single transaction, committed in parts only by
+/// scans that run continuously
+/// Provenance is recorded once per scan in a `scans` table, joined to findings by scan id
+pub struct SqliteSink {
+    conn: Connection,
+    scan_id: String,
+    file: Option<String>,
+}
+
+impl SqliteSink {
+    pub fn open(
+        path: &str,
+        scan_id: String,
+        file: Option<String>,
+        provenance: Option<Provenance>,
+    ) -> Result<Self> {
+        let conn = Connection::open(path).map_err(sqlite_error)?;
+        conn.execute_batch(SQLITE_SCHEMA).map_err(sqlite_error)?;
+        conn.execute_batch("BEGIN").map_err(sqlite_error)?;
+        if let Some(provenance) = provenance {
+            conn.execute(
+                "INSERT OR IGNORE INTO scans (scan_id, host, fingerprint, source, started_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    provenance.scan_id,
+                    provenance.host,
+                    provenance.fingerprint,
+                    provenance.source,
+                    provenance.timestamp
+                ],
+            )
+            .map_err(sqlite_error)?;
+        }
+        Ok(Self {
+            conn,
+            scan_id,
+            file,
+        })
+    }
+}
+
+impl MatchSink for SqliteSink {
+    fn write(&mut self, start: usize, end: usize, pattern: &str, tags: &[String]) -> Result<()> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "INSERT INTO findings
+                     (scan_id, file, start_offset, end_offset, pattern, tag, found_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))",
+            )
+            .map_err(sqlite_error)?;
+        // Comma-joined, NULL for patterns without tags
+        let tag = (!tags.is_empty()).then(|| tags.join(","));
+        stmt.execute(rusqlite::params![
+            self.scan_id,
+            self.file,
+            start as i64,
+            end as i64,
+            pattern,
+            tag
+        ])
+        .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.conn
+            .execute_batch("COMMIT; BEGIN")
+            .map_err(sqlite_error)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.conn.execute_batch("COMMIT").map_err(sqlite_error)
+    }
+}
+
+/// Generate an identifier for a scan from the current time and process id
+pub fn new_scan_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}-{:x}", nanos, std::process::id())
+}
+
+/// Where and how the matches of a scan are written
+pub enum SinkSpec {
+    File {
+        path: String,
+        format: SinkFormat,
+    },
+    Webhook(WebhookSink),
+    #[cfg(feature = "kafka")]
+    Kafka(KafkaSink),
+}
+
+impl SinkSpec {
+    /// Path or URL of the sink
+    pub fn target(&self) -> String {
+        match self {
+            Self::File { path, .. } => path.clone(),
+            Self::Webhook(config) => config.url.clone(),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(config) => format!("kafka://{}/{}", config.brokers.join(","), config.topic),
+        }
+    }
+
+    pub fn format_name(&self) -> &'static str {
+        match self {
+            Self::File { format, .. } => format.name(),
+            Self::Webhook(_) => "webhook",
+            #[cfg(feature = "kafka")]
+            Self::Kafka(_) => "kafka",
+        }
+    }
+
+    /// Open the sink, `scan_id` and `file` are recorded by sinks that support them,
+    /// `provenance` is attached to every finding when given, and JSON findings follow `schema`
+    pub fn open(
+        &self,
+        scan_id: &str,
+        file: Option<&str>,
+        provenance: Option<Provenance>,
+        schema: OutputSchema,
+    ) -> Result<Box<dyn MatchSink>> {
+        Ok(match self {
+            Self::File { path, format } => match format {
+                SinkFormat::Ndjson => Box::new(NdjsonSink::create(
+                    path,
+                    scan_id.to_string(),
+                    file.map(str::to_string),
+                    provenance,
+                    schema,
+                )?),
+                SinkFormat::Csv => Box::new(CsvSink::create(path, provenance)?),
+                SinkFormat::Sqlite => Box::new(SqliteSink::open(
+                    path,
+                    scan_id.to_string(),
+                    file.map(str::to_string),
+                    provenance,
+                )?),
+            },
+            Self::Webhook(config) => Box::new(WebhookWriter::start(
+                config,
+                scan_id.to_string(),
+                file.map(str::to_string),
+                provenance,
+                schema,
+            )),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(config) => Box::new(KafkaWriter::connect(
+                config,
+                scan_id.to_string(),
+                file.map(str::to_string),
+                provenance,
+                schema,
+            )?),
+        })
+    }
+}
+
+/// Summary of a scan whose matches were written to a sink
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct SinkSummary {
+    pub sink: String,
+    pub format: &'static str,
+    pub scan_id: String,
+    /// Fingerprint of the matcher that found the matches, see `Matcher::fingerprint`
+    pub fingerprint: String,
+    pub matches_written: usize,
+    pub bytes_scanned: usize,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SinkSummary {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "SinkSummary(sink={}, format={}, scan_id={}, matches_written={}, bytes_scanned={})",
+            crate::python::quote(py, &self.sink)?,
+            crate::python::quote(py, self.format)?,
+            crate::python::quote(py, &self.scan_id)?,
+            self.matches_written,
+            self.bytes_scanned
+        ))
+    }
+
+    #[getter]
+    fn sink(&self) -> String {
+        self.sink.clone()
+    }
+
+    #[getter]
+    fn format(&self) -> &'static str {
+        self.format
+    }
+
+    #[getter]
+    fn scan_id(&self) -> String {
+        self.scan_id.clone()
+    }
+
+    #[getter]
+    fn fingerprint(&self) -> String {
+        self.fingerprint.clone()
+    }
+
+    #[getter]
+    fn matches_written(&self) -> usize {
+        self.matches_written
+    }
+
+    #[getter]
+    fn bytes_scanned(&self) -> usize {
+        self.bytes_scanned
+    }
+}
@@ -0,0 +1,449 @@
+//! Log source adapters: journald entries and syslog messages pulled continuously and scanned
+//! record by record, findings being written to a sink as they are found
+
+use crate::matcher::{ConfigError, Matcher};
+use crate::sink::{self, MatchSink, Provenance, SinkSpec, SinkSummary};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Result};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::{self, Scope};
+use std::time::{Duration, Instant};
+
+/// How often a scan waiting for records flushes its sink and checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Records received ahead of the scan
+const QUEUED_RECORDS: usize = 1024;
+
+/// Largest syslog message over a stream, longer ones being cut, as datagrams are
+const MAX_MESSAGE: usize = 64 * 1024;
+
+/// A continuous source of log records
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogSource {
+    /// The MESSAGE field of the entries of the systemd journal, read from the export format
+    /// printed by `journalctl --output=export`, followed for new entries when `follow`
+    Journald {
+        journalctl: String,
+        args: Vec<String>,
+        follow: bool,
+    },
+    /// Syslog messages received on a UDP socket
+    SyslogUdp(String),
+    /// Syslog messages received over TCP, newline-delimited or octet-counted as per RFC 6587
+    SyslogTcp(String),
+    /// Syslog messages received on a Unix datagram socket created at the path
+    SyslogUnix(String),
+}
+
+/// When a scan of a log source stops, besides the end of the source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceOptions {
+    /// Stop after this many records
+    pub max_records: Option<u64>,
+    /// Stop once no record arrived for this long
+    pub idle_timeout: Option<Duration>,
+}
+
+impl LogSource {
+    /// Syslog source listening on `udp://host:port`, `tcp://host:port` or `unix:///path`
+    pub fn syslog(address: &str) -> std::result::Result<Self, ConfigError> {
+        let invalid = || {
+            ConfigError::Invalid(format!(
+                "Invalid syslog address '{address}', expected udp://host:port, tcp://host:port or unix:///path"
+            ))
+        };
+        let (scheme, rest) = address.split_once("://").ok_or_else(invalid)?;
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        match scheme {
+            "udp" => Ok(Self::SyslogUdp(rest.to_string())),
+            "tcp" => Ok(Self::SyslogTcp(rest.to_string())),
+            "unix" if cfg!(unix) => Ok(Self::SyslogUnix(rest.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Name of the source, recorded as the file of its findings by sinks that support it
+    pub fn name(&self) -> String {
+        match self {
+            Self::Journald { .. } => "journald".to_string(),
+            Self::SyslogUdp(address) => format!("udp://{address}"),
+            Self::SyslogTcp(address) => format!("tcp://{address}"),
+            Self::SyslogUnix(path) => format!("unix://{path}"),
+        }
+    }
+
+    /// Scan the records of the source as they arrive, writing findings to `sink` until the
+    /// source ends, a limit of `options` is reached, or `interrupted` fails, which it is
+    /// called periodically to check
+    /// With `provenance` every finding carries the provenance of the scan, with the name of
+    /// the source as its source
+    /// Offsets are those of a log holding the records one per line, each record being
+    /// scanned on its own
+    pub fn scan(
+        &self,
+        matcher: &Matcher,
+        sink_spec: &SinkSpec,
+        scan_id: Option<String>,
+        provenance: bool,
+        options: SourceOptions,
+        interrupted: impl FnMut() -> Result<()>,
+    ) -> Result<SinkSummary> {
+        let scan_id = scan_id.unwrap_or_else(sink::new_scan_id);
+        let name = self.name();
+        let provenance =
+            provenance.then(|| Provenance::new(&scan_id, matcher.fingerprint(), Some(&name)));
+        let mut sink =
+            sink_spec.open(&scan_id, Some(&name), provenance, matcher.output_schema())?;
+        let stop = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::sync_channel(QUEUED_RECORDS);
+
+        let res = thread::scope(|scope| {
+            let mut child = self.start(scope, sender, &stop)?;
+            let res = consume(matcher, receiver, sink.as_mut(), options, interrupted);
+            stop.store(true, Ordering::Relaxed);
+            if let Some(path) = self.socket_path() {
+                let _ = std::fs::remove_file(path);
+            }
+            match &mut child {
+                // The journal ended by itself, so journalctl exited or is exiting
+                Some(child) if res.as_ref().is_ok_and(|(_, _, ended)| *ended) => check_exit(child)?,
+                Some(child) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                None => {}
+            }
+            res
+        });
+
+        // Findings written before an error are kept
+        let finished = sink.finish();
+        let (bytes_scanned, matches_written, _) = res?;
+        finished?;
+        Ok(SinkSummary {
+            sink: sink_spec.target(),
+            format: sink_spec.format_name(),
+            scan_id,
+            fingerprint: matcher.fingerprint().to_string(),
+            matches_written,
+            bytes_scanned,
+        })
+    }
+
+    /// Start the threads sending the records of the source, and the journalctl process
+    fn start<'scope>(
+        &self,
+        scope: &'scope Scope<'scope, '_>,
+        sender: SyncSender<Result<Vec<u8>>>,
+        stop: &'scope AtomicBool,
+    ) -> Result<Option<Child>> {
+        match self {
+            Self::Journald {
+                journalctl,
+                args,
+                follow,
+            } => {
+                let mut command = Command::new(journalctl);
+                command.arg("--output=export");
+                if *follow {
+                    command.arg("--follow");
+                }
+                let mut child = command
+                    .args(args)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| {
+                        io::Error::new(e.kind(), format!("Cannot run {journalctl}: {e}"))
+                    })?;
+                let stdout = child.stdout.take().expect("stdout is piped");
+                scope.spawn(move || read_export(BufReader::new(stdout), &sender));
+                Ok(Some(child))
+            }
+            Self::SyslogUdp(address) => {
+                let socket = UdpSocket::bind(address)?;
+                socket.set_read_timeout(Some(POLL_INTERVAL))?;
+                scope.spawn(move || receive_datagrams(|buf| socket.recv(buf), &sender, stop));
+                Ok(None)
+            }
+            #[cfg(unix)]
+            Self::SyslogUnix(path) => {
+                let socket = std::os::unix::net::UnixDatagram::bind(path)?;
+                socket.set_read_timeout(Some(POLL_INTERVAL))?;
+                scope.spawn(move || receive_datagrams(|buf| socket.recv(buf), &sender, stop));
+                Ok(None)
+            }
+            #[cfg(not(unix))]
+            Self::SyslogUnix(_) => Err(io::Error::new(
+                ErrorKind::Unsupported,
+                "Unix sockets are not supported on this platform",
+            )),
+            Self::SyslogTcp(address) => {
+                let listener = TcpListener::bind(address)?;
+                listener.set_nonblocking(true)?;
+                scope.spawn(move || accept_streams(scope, listener, sender, stop));
+                Ok(None)
+            }
+        }
+    }
+
+    fn socket_path(&self) -> Option<&str> {
+        match self {
+            Self::SyslogUnix(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// Scan the records until the source ends or the scan stops
+/// Returns the bytes scanned, the matches written and whether the source ended
+fn consume(
+    matcher: &Matcher,
+    receiver: Receiver<Result<Vec<u8>>>,
+    sink: &mut dyn MatchSink,
+    options: SourceOptions,
+    mut interrupted: impl FnMut() -> Result<()>,
+) -> Result<(usize, usize, bool)> {
+    let (mut offset, mut bytes_scanned, mut matches_written, mut records) = (0, 0, 0, 0);
+    let mut last_record = Instant::now();
+    let mut last_poll = Instant::now();
+    loop {
+        if options.max_records.is_some_and(|max| records >= max) {
+            return Ok((bytes_scanned, matches_written, false));
+        }
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(record) => {
+                let record = record?;
+                for (start, end, pattern_idx) in matcher.match_bytes(&record) {
+                    sink.write(
+                        offset + start,
+                        offset + end,
+                        matcher.pattern(pattern_idx),
+                        matcher.pattern_tags(pattern_idx),
+                    )?;
+                    matches_written += 1;
+                }
+                offset += record.len() + 1;
+                bytes_scanned += record.len();
+                records += 1;
+                last_record = Instant::now();
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if options
+                    .idle_timeout
+                    .is_some_and(|timeout| last_record.elapsed() >= timeout)
+                {
+                    return Ok((bytes_scanned, matches_written, false));
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Ok((bytes_scanned, matches_written, true))
+            }
+        }
+        if last_poll.elapsed() >= POLL_INTERVAL {
+            sink.flush()?;
+            interrupted()?;
+            last_poll = Instant::now();
+        }
+    }
+}
+
+/// Fail with the error output of journalctl when it exited unsuccessfully
+fn check_exit(child: &mut Child) -> Result<()> {
+    let status = child.wait()?;
+    if status.success() {
+        return Ok(());
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    Err(io::Error::other(format!(
+        "journalctl failed with {status}: {}",
+        stderr.trim()
+    )))
+}
+
+/// Send the MESSAGE field of every entry of the journal export format
+fn read_export(mut reader: impl BufRead, sender: &SyncSender<Result<Vec<u8>>>) {
+    let mut message = None;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = match reader.read_until(b'\n', &mut line) {
+            Ok(read) => read,
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+        // Entries end with a blank line, the last one possibly with the output
+        if read == 0 || line == b"\n" {
+            if let Some(message) = message.take() {
+                if sender.send(Ok(message)).is_err() {
+                    return;
+                }
+            }
+            if read == 0 {
+                return;
+            }
+            continue;
+        }
+        if line.ends_with(b"\n") {
+            line.pop();
+        }
+        let value = match line.iter().position(|&b| b == b'=') {
+            Some(eq) => line[eq + 1..].to_vec(),
+            // Binary fields are the field name, a 64-bit little-endian length and the data
+            None => match read_binary_field(&mut reader) {
+                Ok(value) => value,
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            },
+        };
+        if line.starts_with(b"MESSAGE=") || line == b"MESSAGE" {
+            message = Some(value);
+        }
+    }
+}
+
+fn read_binary_field(reader: &mut impl BufRead) -> Result<Vec<u8>> {
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    // Read up to the length rather than allocated from it, a corrupt length failing on the
+    // end of the output instead of allocating
+    let mut value = Vec::new();
+    reader.take(len).read_to_end(&mut value)?;
+    let mut newline = [0; 1];
+    if (value.len() as u64) < len || reader.read_exact(&mut newline).is_err() {
+        return Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "Truncated binary field in the journal export",
+        ));
+    }
+    Ok(value)
+}
+
+/// Send every datagram received until the scan stops, trailing newlines and NULs removed
+fn receive_datagrams(
+    recv: impl Fn(&mut [u8]) -> Result<usize>,
+    sender: &SyncSender<Result<Vec<u8>>>,
+    stop: &AtomicBool,
+) {
+    let mut buf = vec![0; MAX_MESSAGE];
+    while !stop.load(Ordering::Relaxed) {
+        match recv(&mut buf) {
+            Ok(len) => {
+                let message = buf[..len].trim_ascii_end();
+                let message = message.strip_suffix(b"\0").unwrap_or(message);
+                if sender.send(Ok(message.to_vec())).is_err() {
+                    return;
+                }
+            }
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        }
+    }
+}
+
+/// Accept syslog connections until the scan stops, each read by its own thread
+fn accept_streams<'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    listener: TcpListener,
+    sender: SyncSender<Result<Vec<u8>>>,
+    stop: &'scope AtomicBool,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let sender = sender.clone();
+                scope.spawn(move || read_stream(stream, &sender, stop));
+            }
+            Err(e) if is_timeout(&e) => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        }
+    }
+}
+
+/// Send the messages of a syslog connection until it closes or the scan stops
+fn read_stream(mut stream: TcpStream, sender: &SyncSender<Result<Vec<u8>>>, stop: &AtomicBool) {
+    if stream.set_nonblocking(false).is_err()
+        || stream.set_read_timeout(Some(POLL_INTERVAL)).is_err()
+    {
+        return;
+    }
+    let mut pending = Vec::new();
+    let mut buf = vec![0; MAX_MESSAGE];
+    while !stop.load(Ordering::Relaxed) {
+        let eof = match stream.read(&mut buf) {
+            Ok(0) => true,
+            Ok(len) => {
+                pending.extend_from_slice(&buf[..len]);
+                false
+            }
+            Err(e) if is_timeout(&e) => continue,
+            // A connection reset by the peer only ends that connection
+            Err(_) => true,
+        };
+        while let Some(message) = next_frame(&mut pending, eof) {
+            // Blank lines between messages are not messages
+            if message.is_empty() {
+                continue;
+            }
+            if sender.send(Ok(message)).is_err() {
+                return;
+            }
+        }
+        if eof {
+            return;
+        }
+    }
+}
+
+/// Next message of a syslog stream, octet-counted when it starts with its length, newline
+/// delimited otherwise; at the end of the stream, the bytes left are the last message
+fn next_frame(pending: &mut Vec<u8>, eof: bool) -> Option<Vec<u8>> {
+    let digits = pending.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits > 0 && pending.get(digits) == Some(&b' ') {
+        let len: usize = std::str::from_utf8(&pending[..digits]).ok()?.parse().ok()?;
+        let end = digits + 1 + len.min(MAX_MESSAGE);
+        if pending.len() >= end {
+            let message = pending[digits + 1..end].to_vec();
+            pending.drain(..(digits + 1 + len).min(pending.len()));
+            return Some(message);
+        }
+    } else if let Some(eol) = memchr::memchr(b'\n', pending) {
+        let message = pending[..eol.min(MAX_MESSAGE)].trim_ascii_end().to_vec();
+        pending.drain(..=eol);
+        return Some(message);
+    }
+    if eof && !pending.is_empty() {
+        return Some(std::mem::take(pending).trim_ascii_end().to_vec());
+    }
+    if pending.len() > MAX_MESSAGE + 32 {
+        // A message without its delimiter, cut rather than buffered without bounds
+        let message = pending[..MAX_MESSAGE].to_vec();
+        pending.drain(..MAX_MESSAGE);
+        return Some(message);
+    }
+    None
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
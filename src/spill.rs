@@ -0,0 +1,171 @@
+//! Result sets too large for memory, spilled to a temporary file and read back in order
+
+use crate::sink::new_scan_id;
+use aho_corasick::PatternID;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::vec;
+
+/// Bytes of a spilled match: start and end as u64, pattern as u32, little-endian
+const RECORD_SIZE: usize = 20;
+
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Options of a spooled scan
+#[derive(Clone, Debug)]
+pub struct SpoolOptions {
+    /// Matches held in memory, further matches are written to a temporary file
+    pub max_in_memory: usize,
+    /// Directory of the temporary file, the system temporary directory by default
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for SpoolOptions {
+    fn default() -> Self {
+        Self {
+            max_in_memory: 1_000_000,
+            dir: None,
+        }
+    }
+}
+
+/// Matches collected in memory up to a cap, then spilled to a temporary file
+/// The file is removed when the spool is dropped
+pub struct Spool {
+    options: SpoolOptions,
+    buffer: Vec<(usize, usize, PatternID)>,
+    file: Option<SpillFile>,
+    spilled: usize,
+    len: usize,
+}
+
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Spool {
+    pub fn new(options: SpoolOptions) -> Self {
+        Self {
+            buffer: Vec::with_capacity(options.max_in_memory.min(1024)),
+            options,
+            file: None,
+            spilled: 0,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, start: usize, end: usize, pattern_idx: PatternID) -> Result<()> {
+        if self.buffer.len() >= self.options.max_in_memory {
+            self.spill()?;
+        }
+        self.buffer.push((start, end, pattern_idx));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Number of matches
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of matches written to the temporary file
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    /// Iterate over the matches in the order they were pushed, spilled matches first
+    pub fn into_matches(mut self) -> Result<SpoolIter> {
+        let spilled = match self.file.take() {
+            Some(mut file) => {
+                file.writer.flush()?;
+                let mut reader = file.writer.get_ref().try_clone()?;
+                reader.seek(SeekFrom::Start(0))?;
+                Some((BufReader::new(reader), file))
+            }
+            None => None,
+        };
+        Ok(SpoolIter {
+            spilled,
+            buffer: std::mem::take(&mut self.buffer).into_iter(),
+        })
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(self.create_file()?),
+        };
+        for &(start, end, pattern_idx) in &self.buffer {
+            let mut record = [0; RECORD_SIZE];
+            record[..8].copy_from_slice(&(start as u64).to_le_bytes());
+            record[8..16].copy_from_slice(&(end as u64).to_le_bytes());
+            record[16..].copy_from_slice(&pattern_idx.as_u32().to_le_bytes());
+            file.writer.write_all(&record)?;
+        }
+        self.spilled += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+
+    fn create_file(&self) -> Result<SpillFile> {
+        let dir = self.options.dir.clone().unwrap_or_else(std::env::temp_dir);
+        let path = dir.join(format!(
+            "voluta-spill-{}-{}.bin",
+            new_scan_id(),
+            SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(SpillFile {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+/// Matches of a spool, read back from the temporary file then from memory
+pub struct SpoolIter {
+    // The spill file is kept so it is removed once the iteration is dropped
+    spilled: Option<(BufReader<File>, SpillFile)>,
+    buffer: vec::IntoIter<(usize, usize, PatternID)>,
+}
+
+impl Iterator for SpoolIter {
+    type Item = Result<(usize, usize, PatternID)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((reader, _)) = &mut self.spilled {
+            let mut record = [0; RECORD_SIZE];
+            match reader.read_exact(&mut record) {
+                Ok(()) => {
+                    let start = u64::from_le_bytes(record[..8].try_into().unwrap()) as usize;
+                    let end = u64::from_le_bytes(record[8..16].try_into().unwrap()) as usize;
+                    let pattern = u32::from_le_bytes(record[16..].try_into().unwrap());
+                    return Some(Ok((start, end, PatternID::must(pattern as usize))));
+                }
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => self.spilled = None,
+                Err(e) => {
+                    self.spilled = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.buffer.next().map(Ok)
+    }
+}
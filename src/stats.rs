@@ -0,0 +1,173 @@
+//! Per-scan statistics, collected on request by the chunked scans running on the calling thread
+//! and its rayon workers, and returned alongside the matches
+
+use aho_corasick::PatternID;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyDeprecationWarning;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Counters of the scan being recorded
+#[derive(Default)]
+struct Counters {
+    chunks: AtomicUsize,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Counters>>> = const { RefCell::new(None) };
+}
+
+/// Handle on the counters of the scan running on the current thread, a no-op when its
+/// statistics are not recorded
+/// Chunked scans take it before spreading chunks over rayon workers, as for telemetry spans
+#[derive(Clone, Default)]
+pub(crate) struct StatsHandle(Option<Arc<Counters>>);
+
+impl StatsHandle {
+    pub(crate) fn current() -> Self {
+        Self(CURRENT.with(|current| current.borrow().clone()))
+    }
+
+    /// Count a chunk scanned
+    pub(crate) fn chunk(&self) {
+        if let Some(counters) = &self.0 {
+            counters.chunks.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Statistics of one scan
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScanStats {
+    pub bytes_scanned: usize,
+    pub elapsed: Duration,
+    /// Chunks or buffers the input was scanned in, 1 for scans of the input at once
+    pub chunks: usize,
+    /// Deprecated, always 0: matches found twice where chunks overlap, which cannot happen
+    /// now that every match belongs to the one chunk it starts in
+    pub dedup_collisions: usize,
+    /// (pattern, matches) of the patterns that matched, in pattern order
+    pub matches_per_pattern: Vec<(String, usize)>,
+}
+
+impl ScanStats {
+    /// Run a scan of `bytes_scanned` bytes, recording its wall time and chunks
+    /// Matches per pattern are counted afterwards, from the final matches, by `count_matches`
+    pub fn record<T>(bytes_scanned: usize, scan: impl FnOnce() -> T) -> (T, Self) {
+        let counters = Arc::new(Counters::default());
+        let outer = CURRENT.with(|current| current.replace(Some(counters.clone())));
+        let started = Instant::now();
+        let res = scan();
+        let elapsed = started.elapsed();
+        CURRENT.with(|current| current.replace(outer));
+
+        let stats = Self {
+            bytes_scanned,
+            elapsed,
+            chunks: counters.chunks.load(Ordering::Relaxed).max(1),
+            dedup_collisions: 0,
+            matches_per_pattern: Vec::new(),
+        };
+        (res, stats)
+    }
+
+    /// Count the matches of every pattern, `patterns` being indexed by pattern ID
+    pub fn count_matches(&mut self, matches: &[(usize, usize, PatternID)], patterns: &[String]) {
+        let mut counts = vec![0; patterns.len()];
+        for &(_, _, pattern_idx) in matches {
+            counts[pattern_idx.as_usize()] += 1;
+        }
+        self.matches_per_pattern = patterns
+            .iter()
+            .zip(counts)
+            .filter(|&(_, count)| count > 0)
+            .map(|(pattern, count)| (pattern.clone(), count))
+            .collect();
+    }
+
+    /// Bytes scanned per second of wall time
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_scanned as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    pub fn total_matches(&self) -> usize {
+        self.matches_per_pattern
+            .iter()
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ScanStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanStats(bytes_scanned={}, elapsed={:.6}, throughput={:.0}, chunks={}, total_matches={})",
+            self.bytes_scanned,
+            self.elapsed.as_secs_f64(),
+            self.throughput(),
+            self.chunks,
+            self.total_matches()
+        )
+    }
+
+    #[getter]
+    fn bytes_scanned(&self) -> usize {
+        self.bytes_scanned
+    }
+
+    /// Wall time of the scan in seconds
+    #[getter]
+    fn elapsed(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    /// Bytes scanned per second of wall time
+    #[getter(throughput)]
+    fn py_throughput(&self) -> f64 {
+        self.throughput()
+    }
+
+    #[getter]
+    fn chunks(&self) -> usize {
+        self.chunks
+    }
+
+    /// Deprecated, always 0, warns with DeprecationWarning
+    #[getter]
+    fn dedup_collisions(&self, py: Python<'_>) -> PyResult<usize> {
+        PyErr::warn(
+            py,
+            &py.get_type::<PyDeprecationWarning>(),
+            c"ScanStats.dedup_collisions is deprecated and always 0, chunked scans never find a match twice",
+            1,
+        )?;
+        Ok(self.dedup_collisions)
+    }
+
+    /// Dict mapping each pattern that matched to its number of matches
+    #[getter]
+    fn matches_per_pattern(&self) -> std::collections::HashMap<String, usize> {
+        let mut counts = std::collections::HashMap::new();
+        for (pattern, count) in &self.matches_per_pattern {
+            *counts.entry(pattern.clone()).or_default() += count;
+        }
+        counts
+    }
+
+    #[getter(total_matches)]
+    fn py_total_matches(&self) -> usize {
+        self.total_matches()
+    }
+}
@@ -0,0 +1,95 @@
+//! Tags of the patterns of a matcher, such as "pci" or "pii", kept as one bitset per pattern
+//! so a scan can restrict the active patterns without rebuilding the automaton
+
+use crate::matcher::ConfigError;
+use aho_corasick::PatternID;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Tags of every pattern of a matcher
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PatternTags {
+    /// Tags of each pattern, as given
+    tags: Vec<Vec<String>>,
+    /// Distinct tags, in order of first appearance, and their bit
+    index: HashMap<String, usize>,
+    /// 64-bit words of a bitset
+    words: usize,
+    /// Bitset of the tags of each pattern, `words` words per pattern
+    bits: Vec<u64>,
+}
+
+impl PatternTags {
+    pub(crate) fn new(tags: Vec<Vec<String>>) -> Self {
+        let mut index = HashMap::new();
+        for tag in tags.iter().flatten() {
+            let bit = index.len();
+            index.entry(tag.clone()).or_insert(bit);
+        }
+        let words = index.len().div_ceil(64);
+        let mut bits = vec![0; tags.len() * words];
+        for (idx, pattern_tags) in tags.iter().enumerate() {
+            for tag in pattern_tags {
+                let bit = index[tag];
+                bits[idx * words + bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Self {
+            tags,
+            index,
+            words,
+            bits,
+        }
+    }
+
+    /// Tags of each pattern
+    pub(crate) fn tags(&self) -> &[Vec<String>] {
+        &self.tags
+    }
+
+    /// Filter keeping the matches of the patterns with any of `tags`
+    pub(crate) fn filter(self: &Arc<Self>, tags: &[String]) -> Result<TagFilter, ConfigError> {
+        let mut mask = vec![0u64; self.words];
+        for tag in tags {
+            let bit = *self
+                .index
+                .get(tag)
+                .ok_or_else(|| ConfigError::Invalid(format!("Unknown tag '{}'", tag)))?;
+            mask[bit / 64] |= 1 << (bit % 64);
+        }
+        Ok(TagFilter {
+            tags: Arc::clone(self),
+            mask,
+        })
+    }
+
+    /// Heap bytes used by the tags and their bitsets
+    pub(crate) fn memory_usage(&self) -> usize {
+        let tags: usize = self
+            .tags
+            .iter()
+            .map(|tags| tags.iter().map(|t| t.capacity()).sum::<usize>())
+            .sum();
+        tags + self.index.keys().map(|t| t.capacity()).sum::<usize>() + self.bits.len() * 8
+    }
+}
+
+/// Patterns active in a scan restricted to some tags
+#[derive(Clone, Debug)]
+pub(crate) struct TagFilter {
+    tags: Arc<PatternTags>,
+    /// Bitset of the active tags
+    mask: Vec<u64>,
+}
+
+impl TagFilter {
+    /// Whether the pattern has one of the active tags, a word of its bitset at a time
+    pub(crate) fn is_active(&self, pattern: PatternID) -> bool {
+        let words = self.tags.words;
+        let idx = pattern.as_usize();
+        self.tags.bits[idx * words..(idx + 1) * words]
+            .iter()
+            .zip(&self.mask)
+            .any(|(bits, mask)| bits & mask != 0)
+    }
+}
@@ -0,0 +1,285 @@
+//! Optional OpenTelemetry instrumentation of scans
+//! With the `otel` feature every scan records a span, chunked scans record one child span per
+//! chunk, and bytes scanned, matches and durations are recorded as metrics exported over OTLP.
+//! Without the feature the same guards compile to no-ops.
+
+#[cfg(feature = "otel")]
+pub use otel::*;
+
+#[cfg(not(feature = "otel"))]
+pub use noop::*;
+
+#[cfg(not(feature = "otel"))]
+mod noop {
+    use std::io;
+
+    /// Span of one scan, ended by `finish` or `fail`
+    pub struct ScanSpan;
+
+    impl ScanSpan {
+        pub fn start(_operation: &'static str, _file: Option<&str>, _bytes: usize) -> Self {
+            Self
+        }
+
+        pub fn file(_operation: &'static str, _path: &str) -> Self {
+            Self
+        }
+
+        pub fn finish(self, _matches: usize) {}
+
+        pub fn fail(self, _error: &io::Error) {}
+    }
+
+    /// Handle on the scan running on the current thread, used to parent chunk spans
+    #[derive(Clone)]
+    pub struct ScanContext;
+
+    impl ScanContext {
+        pub fn current() -> Self {
+            Self
+        }
+
+        pub fn chunk(&self, _start: usize, _end: usize) -> ChunkSpan {
+            ChunkSpan
+        }
+    }
+
+    /// Span of one chunk of a scan, ended when dropped
+    pub struct ChunkSpan;
+
+    impl ChunkSpan {
+        pub fn matches(&mut self, _matches: usize) {}
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::metrics::{Counter, Histogram, MeterProvider};
+    use opentelemetry::trace::{Span, Status, TraceContextExt, Tracer, TracerProvider as _};
+    use opentelemetry::{Context, ContextGuard, KeyValue};
+    use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+    use opentelemetry_sdk::Resource;
+    use std::io;
+    use std::sync::{Arc, RwLock};
+    use std::time::Instant;
+
+    struct Telemetry {
+        tracer_provider: SdkTracerProvider,
+        meter_provider: SdkMeterProvider,
+        tracer: SdkTracer,
+        bytes_scanned: Counter<u64>,
+        matches: Counter<u64>,
+        duration: Histogram<f64>,
+    }
+
+    static TELEMETRY: RwLock<Option<Arc<Telemetry>>> = RwLock::new(None);
+
+    fn active() -> Option<Arc<Telemetry>> {
+        TELEMETRY.read().ok().and_then(|t| t.clone())
+    }
+
+    fn otel_error(e: impl std::fmt::Display) -> io::Error {
+        io::Error::other(format!("OpenTelemetry error: {}", e))
+    }
+
+    /// Start exporting scan spans and metrics over OTLP/HTTP
+    /// `endpoint` is the collector base URL, e.g. http://localhost:4318, and defaults to the
+    /// standard OTEL_EXPORTER_OTLP_* environment variables
+    pub fn enable(endpoint: Option<&str>, service_name: Option<&str>) -> io::Result<()> {
+        let resource = Resource::builder()
+            .with_service_name(service_name.unwrap_or("voluta").to_string())
+            .build();
+
+        let mut spans = SpanExporter::builder().with_http();
+        let mut metrics = MetricExporter::builder().with_http();
+        if let Some(endpoint) = endpoint {
+            let base = endpoint.trim_end_matches('/');
+            spans = spans.with_endpoint(format!("{}/v1/traces", base));
+            metrics = metrics.with_endpoint(format!("{}/v1/metrics", base));
+        }
+
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(spans.build().map_err(otel_error)?)
+            .with_resource(resource.clone())
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metrics.build().map_err(otel_error)?)
+            .with_resource(resource)
+            .build();
+
+        let meter = meter_provider.meter("voluta");
+        let telemetry = Telemetry {
+            tracer: tracer_provider.tracer("voluta"),
+            bytes_scanned: meter
+                .u64_counter("voluta.bytes_scanned")
+                .with_unit("By")
+                .with_description("Bytes scanned")
+                .build(),
+            matches: meter
+                .u64_counter("voluta.matches")
+                .with_description("Matches found")
+                .build(),
+            duration: meter
+                .f64_histogram("voluta.scan.duration")
+                .with_unit("s")
+                .with_description("Duration of scans")
+                .build(),
+            tracer_provider,
+            meter_provider,
+        };
+
+        let previous = TELEMETRY
+            .write()
+            .map_err(otel_error)?
+            .replace(Arc::new(telemetry));
+        if let Some(previous) = previous {
+            previous.shutdown().map_err(otel_error)?;
+        }
+        Ok(())
+    }
+
+    /// Flush pending spans and metrics and stop exporting them
+    pub fn shutdown() -> io::Result<()> {
+        let telemetry = TELEMETRY.write().map_err(otel_error)?.take();
+        match telemetry {
+            Some(telemetry) => telemetry.shutdown().map_err(otel_error),
+            None => Ok(()),
+        }
+    }
+
+    impl Telemetry {
+        fn shutdown(&self) -> Result<(), String> {
+            let traces = self.tracer_provider.shutdown();
+            let metrics = self.meter_provider.shutdown();
+            traces.and(metrics).map_err(|e| e.to_string())
+        }
+    }
+
+    struct ActiveScan {
+        telemetry: Arc<Telemetry>,
+        operation: &'static str,
+        bytes: usize,
+        started: Instant,
+        _guard: ContextGuard,
+    }
+
+    /// Span of one scan, ended by `finish` or `fail`
+    /// The span is the current context of the thread until then, so chunk spans nest under it
+    pub struct ScanSpan {
+        active: Option<ActiveScan>,
+    }
+
+    impl ScanSpan {
+        pub fn start(operation: &'static str, file: Option<&str>, bytes: usize) -> Self {
+            let Some(telemetry) = active() else {
+                return Self { active: None };
+            };
+
+            let mut attributes = vec![
+                KeyValue::new("voluta.operation", operation),
+                KeyValue::new("voluta.bytes", bytes as i64),
+            ];
+            if let Some(file) = file {
+                attributes.push(KeyValue::new("voluta.file", file.to_string()));
+            }
+
+            let span = telemetry
+                .tracer
+                .span_builder(operation)
+                .with_attributes(attributes)
+                .start(&telemetry.tracer);
+            let guard = Context::current_with_span(span).attach();
+
+            Self {
+                active: Some(ActiveScan {
+                    telemetry,
+                    operation,
+                    bytes,
+                    started: Instant::now(),
+                    _guard: guard,
+                }),
+            }
+        }
+
+        /// Start the span of a file scan, the file size is only read when telemetry is enabled
+        pub fn file(operation: &'static str, path: &str) -> Self {
+            if TELEMETRY.read().map(|t| t.is_none()).unwrap_or(true) {
+                return Self { active: None };
+            }
+            let bytes = std::fs::metadata(path)
+                .map(|m| m.len() as usize)
+                .unwrap_or(0);
+            Self::start(operation, Some(path), bytes)
+        }
+
+        pub fn finish(self, matches: usize) {
+            let Some(scan) = self.active else { return };
+
+            let attributes = [KeyValue::new("voluta.operation", scan.operation)];
+            let telemetry = &scan.telemetry;
+            telemetry.bytes_scanned.add(scan.bytes as u64, &attributes);
+            telemetry.matches.add(matches as u64, &attributes);
+            telemetry
+                .duration
+                .record(scan.started.elapsed().as_secs_f64(), &attributes);
+
+            let cx = Context::current();
+            let span = cx.span();
+            span.set_attribute(KeyValue::new("voluta.matches", matches as i64));
+            span.end();
+        }
+
+        pub fn fail(self, error: &io::Error) {
+            let Some(_scan) = self.active else { return };
+
+            let cx = Context::current();
+            let span = cx.span();
+            span.set_status(Status::error(error.to_string()));
+            span.end();
+        }
+    }
+
+    /// Handle on the scan running on the current thread, used to parent chunk spans
+    /// It can be sent to worker threads, unlike the current context itself
+    #[derive(Clone)]
+    pub struct ScanContext {
+        parent: Option<(Arc<Telemetry>, Context)>,
+    }
+
+    impl ScanContext {
+        pub fn current() -> Self {
+            Self {
+                parent: active().map(|telemetry| (telemetry, Context::current())),
+            }
+        }
+
+        pub fn chunk(&self, start: usize, end: usize) -> ChunkSpan {
+            let span = self.parent.as_ref().map(|(telemetry, cx)| {
+                telemetry
+                    .tracer
+                    .span_builder("voluta.chunk")
+                    .with_attributes([
+                        KeyValue::new("voluta.chunk.start", start as i64),
+                        KeyValue::new("voluta.chunk.end", end as i64),
+                    ])
+                    .start_with_context(&telemetry.tracer, cx)
+            });
+            ChunkSpan { span }
+        }
+    }
+
+    /// Span of one chunk of a scan, ended when dropped
+    pub struct ChunkSpan {
+        span: Option<opentelemetry_sdk::trace::Span>,
+    }
+
+    impl ChunkSpan {
+        pub fn matches(&mut self, matches: usize) {
+            if let Some(span) = &mut self.span {
+                span.set_attribute(KeyValue::new("voluta.matches", matches as i64));
+            }
+        }
+    }
+}
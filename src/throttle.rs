@@ -0,0 +1,29 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Limits the average read rate of a scan by sleeping whenever it gets ahead of schedule
+pub struct Throttle {
+    bytes_per_sec: f64,
+    started: Instant,
+    consumed: u64,
+}
+
+impl Throttle {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1) as f64,
+            started: Instant::now(),
+            consumed: 0,
+        }
+    }
+
+    /// Account for `bytes` just read, sleeping until the average rate is back under the limit
+    pub fn consume(&mut self, bytes: usize) {
+        self.consumed += bytes as u64;
+        let due = Duration::from_secs_f64(self.consumed as f64 / self.bytes_per_sec);
+        let elapsed = self.started.elapsed();
+        if due > elapsed {
+            thread::sleep(due - elapsed);
+        }
+    }
+}
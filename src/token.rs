@@ -0,0 +1,324 @@
+//! Whole-token matching against huge term lists stored in a finite state transducer, far
+//! smaller than a DFA over the same terms
+
+use crate::matcher::ConfigError;
+use crate::phonetic::Phonetic;
+use aho_corasick::PatternID;
+use fst::raw::{Fst, Node, Output};
+use fst::Map;
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::Result;
+use std::sync::Arc;
+
+/// Bytes per chunk of a parallel file scan, chunks end at line breaks
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Options of a TokenMatcher
+#[derive(Clone, Debug)]
+pub struct TokenOptions {
+    /// Report every term, including terms overlapping other terms
+    /// Otherwise the longest term starting at the leftmost token wins and the scan resumes
+    /// after it
+    pub overlapping: bool,
+    /// Match ASCII letters regardless of case
+    pub case_insensitive: bool,
+    /// Match tokens by their phonetic code instead of their spelling, if set
+    pub phonetic: Option<Phonetic>,
+}
+
+impl Default for TokenOptions {
+    fn default() -> Self {
+        Self {
+            overlapping: true,
+            case_insensitive: true,
+            phonetic: None,
+        }
+    }
+}
+
+/// Matcher of whole tokens against a term set stored in an FST
+/// Tokens are runs of ASCII letters, digits, underscores and non-ASCII bytes; a term of
+/// several tokens matches them separated by any non-word characters other than line breaks
+/// With a phonetic algorithm, tokens match when their codes do: every term sharing the codes of
+/// the matched tokens is reported, over the span of the tokens in the input
+/// Matches are (start, end, term) tuples of byte offsets, end exclusive, and the term index
+/// into `patterns()`, sorted by start then end
+pub struct TokenMatcher {
+    patterns: Arc<Vec<String>>,
+    // Terms with their tokens joined by single spaces, mapped to their index in `patterns`, or
+    // to their index in `groups` when phonetic
+    map: Map<Vec<u8>>,
+    // Terms sharing each phonetic key, in term order
+    groups: Vec<Vec<PatternID>>,
+    overlapping: bool,
+    case_insensitive: bool,
+    phonetic: Option<Phonetic>,
+}
+
+impl TokenMatcher {
+    /// Build a matcher on `terms`, terms without any token are dropped; terms equal once
+    /// normalized report the first of them, unless phonetic where they are all reported
+    pub fn new(
+        terms: Vec<String>,
+        options: TokenOptions,
+    ) -> std::result::Result<Self, ConfigError> {
+        let patterns: Vec<String> = terms
+            .into_iter()
+            .filter(|term| !tokens(term.as_bytes()).is_empty())
+            .collect();
+        if patterns.is_empty() {
+            return Err(ConfigError::Invalid("Term set cannot be empty".to_string()));
+        }
+
+        let mut keys: Vec<(Vec<u8>, u64)> = patterns
+            .iter()
+            .enumerate()
+            .map(|(idx, term)| {
+                let key = normalize(term, options.case_insensitive, options.phonetic);
+                (key, idx as u64)
+            })
+            .collect();
+        keys.sort_unstable();
+        // Phonetic keys map to the group of every term sharing them
+        let mut groups: Vec<Vec<PatternID>> = Vec::new();
+        if options.phonetic.is_some() {
+            for i in 0..keys.len() {
+                let term = PatternID::must(keys[i].1 as usize);
+                if i > 0 && keys[i].0 == keys[i - 1].0 {
+                    groups.last_mut().unwrap().push(term);
+                } else {
+                    groups.push(vec![term]);
+                }
+                keys[i].1 = groups.len() as u64 - 1;
+            }
+        }
+        keys.dedup_by(|next, first| next.0 == first.0);
+        let map = Map::from_iter(keys)
+            .map_err(|e| ConfigError::Invalid(format!("Could not build the term set: {}", e)))?;
+
+        Ok(Self {
+            patterns: Arc::new(patterns),
+            map,
+            groups,
+            overlapping: options.overlapping,
+            case_insensitive: options.case_insensitive,
+            phonetic: options.phonetic,
+        })
+    }
+
+    /// Terms of the matcher, terms without any token are dropped when building it
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Shared handle on the terms, for results that outlive a borrow of the matcher
+    pub fn shared_patterns(&self) -> Arc<Vec<String>> {
+        Arc::clone(&self.patterns)
+    }
+
+    pub fn overlapping(&self) -> bool {
+        self.overlapping
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn phonetic(&self) -> Option<Phonetic> {
+        self.phonetic
+    }
+
+    /// Bytes of the FST, plus the terms kept to report matches
+    pub fn memory_usage(&self) -> usize {
+        let groups: usize = self
+            .groups
+            .iter()
+            .map(|g| std::mem::size_of_val(g.as_slice()))
+            .sum();
+        self.map.as_fst().size()
+            + self.patterns.iter().map(|p| p.capacity()).sum::<usize>()
+            + groups
+    }
+
+    /// Convert term indices to terms
+    pub fn resolve(&self, matches: Vec<(usize, usize, PatternID)>) -> Vec<(usize, usize, String)> {
+        matches
+            .into_iter()
+            .map(|(start, end, idx)| (start, end, self.patterns[idx.as_usize()].clone()))
+            .collect()
+    }
+
+    /// Match provided bytes
+    pub fn match_bytes(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let mut matches = Vec::new();
+        let tokens = tokens(data);
+        let fst = self.map.as_fst();
+        let mut code = Vec::new();
+
+        let mut i = 0;
+        while i < tokens.len() {
+            let before = matches.len();
+            // Start of the matches of the longest term found so far
+            let mut longest = before;
+            let mut node = fst.root();
+            let mut out = Output::zero();
+            for j in i..tokens.len() {
+                if j > i {
+                    let gap = &data[tokens[j - 1].1..tokens[j].0];
+                    if memchr::memchr2(b'\n', b'\r', gap).is_some()
+                        || !self.step(fst, &mut node, &mut out, b' ')
+                    {
+                        break;
+                    }
+                }
+                let (start, end) = tokens[j];
+                let key = token_key(&data[start..end], self.phonetic, &mut code);
+                if !key
+                    .iter()
+                    .all(|&byte| self.step(fst, &mut node, &mut out, byte))
+                {
+                    break;
+                }
+                if node.is_final() {
+                    let value = out.cat(node.final_output()).value() as usize;
+                    longest = matches.len();
+                    match self.phonetic {
+                        Some(_) => matches.extend(
+                            self.groups[value]
+                                .iter()
+                                .map(|&term| (tokens[i].0, end, term)),
+                        ),
+                        None => matches.push((tokens[i].0, end, PatternID::must(value))),
+                    }
+                }
+            }
+
+            // Without overlaps the longest term found wins and the scan resumes after it
+            if !self.overlapping && matches.len() > before {
+                matches.drain(before..longest);
+                let end = matches[before].1;
+                i = tokens.partition_point(|&(start, _)| start < end);
+            } else {
+                i += 1;
+            }
+        }
+        matches
+    }
+
+    /// Match a memory-mapped file, scanning chunks cut at line breaks in parallel
+    pub fn match_file_memmap(&self, path: &str) -> Result<Vec<(usize, usize, PatternID)>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(self.match_bytes_parallel(&mmap))
+    }
+
+    /// Match provided bytes, scanning chunks cut at line breaks on the rayon thread pool
+    /// Terms never span line breaks, so results are those of `match_bytes`
+    pub fn match_bytes_parallel(&self, data: &[u8]) -> Vec<(usize, usize, PatternID)> {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < data.len() {
+            let end = match data.get(start + CHUNK_SIZE..) {
+                Some(rest) => memchr::memchr(b'\n', rest)
+                    .map_or(data.len(), |pos| start + CHUNK_SIZE + pos + 1),
+                None => data.len(),
+            };
+            chunks.push((start, end));
+            start = end;
+        }
+
+        chunks
+            .into_par_iter()
+            .flat_map_iter(|(start, end)| {
+                self.match_bytes(&data[start..end])
+                    .into_iter()
+                    .map(move |(s, e, idx)| (start + s, start + e, idx))
+            })
+            .collect()
+    }
+
+    /// Follow the transition of `byte`, folded like the terms
+    fn step<'f>(
+        &self,
+        fst: &'f Fst<Vec<u8>>,
+        node: &mut Node<'f>,
+        out: &mut Output,
+        byte: u8,
+    ) -> bool {
+        let byte = if self.case_insensitive {
+            byte.to_ascii_lowercase()
+        } else {
+            byte
+        };
+        match node.find_input(byte) {
+            Some(i) => {
+                let transition = node.transition(i);
+                *out = out.cat(transition.out);
+                *node = fst.node(transition.addr);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn is_token_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii()
+}
+
+/// (start, end) spans of the tokens of `data`
+fn tokens(data: &[u8]) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (pos, &byte) in data.iter().enumerate() {
+        match (is_token_byte(byte), start) {
+            (true, None) => start = Some(pos),
+            (false, Some(from)) => {
+                tokens.push((from, pos));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        tokens.push((from, data.len()));
+    }
+    tokens
+}
+
+/// Bytes of a token in keys: its phonetic code when phonetic and it has one, the token itself
+/// otherwise
+fn token_key<'t>(token: &'t [u8], phonetic: Option<Phonetic>, code: &'t mut Vec<u8>) -> &'t [u8] {
+    let Some(phonetic) = phonetic else {
+        return token;
+    };
+    code.clear();
+    phonetic.encode(token, code);
+    if code.is_empty() {
+        token
+    } else {
+        code
+    }
+}
+
+/// Key of a term in the FST: the keys of its tokens, ASCII lowercased when case-insensitive,
+/// joined by single spaces
+fn normalize(term: &str, case_insensitive: bool, phonetic: Option<Phonetic>) -> Vec<u8> {
+    let bytes = term.as_bytes();
+    let mut key = Vec::with_capacity(bytes.len());
+    let mut code = Vec::new();
+    for (start, end) in tokens(bytes) {
+        if !key.is_empty() {
+            key.push(b' ');
+        }
+        let token = token_key(&bytes[start..end], phonetic, &mut code);
+        if case_insensitive {
+            key.extend(token.iter().map(u8::to_ascii_lowercase));
+        } else {
+            key.extend_from_slice(token);
+        }
+    }
+    key
+}
@@ -0,0 +1,61 @@
+//! Chunk size and worker count picked from the file size, the thread pool and the pattern set
+
+use std::cmp;
+
+/// Smallest chunk worth its own task, below it the per-chunk deduplication set and task
+/// overhead outweigh the scan
+const MIN_CHUNK_SIZE: usize = 1024 * 1024;
+/// Smallest chunk for automata too large for the CPU caches, their scans are slower per byte
+/// so splitting pays off sooner
+const MIN_CHUNK_SIZE_LARGE_AUTOMATON: usize = 256 * 1024;
+/// Automata above this size rarely stay in L2
+const LARGE_AUTOMATON: usize = 2 * 1024 * 1024;
+/// Largest chunk, bounding the matches a single task holds before they are merged
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+/// Chunks per worker, so that a slow chunk (dense matches) does not stall the whole scan
+const CHUNKS_PER_WORKER: usize = 4;
+/// Chunks are at least this many times the overlap rescanned across their boundaries
+const MIN_CHUNKS_PER_OVERLAP: usize = 256;
+
+/// Chunking of a memory-mapped scan
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tuning {
+    /// Bytes per chunk, excluding the overlap with the next chunk
+    pub chunk_size: usize,
+    /// Chunks scanned at the same time
+    pub workers: usize,
+}
+
+impl Tuning {
+    /// Pick the chunking of a `file_size` bytes scan on up to `threads` workers, for a pattern
+    /// set whose longest pattern is `max_pattern_len` bytes and whose automaton takes
+    /// `memory_usage` bytes
+    pub fn new(
+        file_size: usize,
+        threads: usize,
+        max_pattern_len: usize,
+        memory_usage: usize,
+    ) -> Self {
+        let threads = cmp::max(threads, 1);
+        let min_chunk = if memory_usage > LARGE_AUTOMATON {
+            MIN_CHUNK_SIZE_LARGE_AUTOMATON
+        } else {
+            MIN_CHUNK_SIZE
+        };
+        let min_chunk = cmp::max(min_chunk, max_pattern_len * MIN_CHUNKS_PER_OVERLAP);
+        let max_chunk = cmp::max(min_chunk, MAX_CHUNK_SIZE);
+
+        let chunk_size = if threads == 1 {
+            file_size
+        } else {
+            file_size.div_ceil(threads * CHUNKS_PER_WORKER)
+        };
+        let chunk_size = chunk_size.clamp(min_chunk, max_chunk);
+        let chunks = cmp::max(file_size.div_ceil(chunk_size), 1);
+
+        Self {
+            chunk_size,
+            workers: cmp::min(threads, chunks),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::matcher::hash_text;
+use aho_corasick::PatternID;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Normalization applied to matched text before distinct values are collected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalize {
+    Lower,
+    Upper,
+}
+
+impl Normalize {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "lower" => Some(Self::Lower),
+            "upper" => Some(Self::Upper),
+            _ => None,
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Self::Lower => text.to_lowercase(),
+            Self::Upper => text.to_uppercase(),
+        }
+    }
+}
+
+/// Options of unique-value extraction
+#[derive(Clone, Debug, Default)]
+pub struct UniqueOptions {
+    /// Keep at most this many distinct values per pattern, later new values are dropped
+    pub max_values: Option<usize>,
+    /// Normalization applied to the matched text, if any
+    pub normalize: Option<Normalize>,
+}
+
+/// Distinct matched values per pattern, fed with matches as they are found
+///
+/// Matched bytes are decoded as UTF-8, invalid sequences replaced, and normalized. With a
+/// hash key the values are the digests of the normalized text instead, ASCII-folded when the
+/// matcher is case-insensitive, so they can be compared with the reported pattern digests.
+pub struct UniqueValues<'a> {
+    data: &'a [u8],
+    options: &'a UniqueOptions,
+    hash_key: Option<&'a [u8]>,
+    case_insensitive: bool,
+    values: BTreeMap<PatternID, BTreeSet<String>>,
+}
+
+impl<'a> UniqueValues<'a> {
+    pub fn new(
+        data: &'a [u8],
+        options: &'a UniqueOptions,
+        hash_key: Option<&'a [u8]>,
+        case_insensitive: bool,
+    ) -> Self {
+        Self {
+            data,
+            options,
+            hash_key,
+            case_insensitive,
+            values: BTreeMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, start: usize, end: usize, pattern_idx: PatternID) {
+        let values = self.values.entry(pattern_idx).or_default();
+        let max_values = self.options.max_values.unwrap_or(usize::MAX);
+
+        let text = String::from_utf8_lossy(&self.data[start..end]);
+        let text = match self.options.normalize {
+            Some(normalize) => normalize.apply(&text),
+            None => text.into_owned(),
+        };
+        let value = match self.hash_key {
+            Some(key) if self.case_insensitive => {
+                hash_text(key, text.to_ascii_lowercase().as_bytes())
+            }
+            Some(key) => hash_text(key, text.as_bytes()),
+            None => text,
+        };
+
+        if values.len() < max_values || values.contains(&value) {
+            values.insert(value);
+        }
+    }
+
+    /// Distinct values of every pattern that matched at least once
+    pub fn finish(self) -> BTreeMap<PatternID, BTreeSet<String>> {
+        self.values
+    }
+}
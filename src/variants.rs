@@ -0,0 +1,44 @@
+use crate::matcher::hash_text;
+use aho_corasick::PatternID;
+use std::collections::BTreeMap;
+
+/// Original-case forms matched by each pattern and how often each was found, fed with matches
+/// as they are found
+///
+/// Matched bytes are decoded as UTF-8, invalid sequences replaced, and kept as they appear in
+/// the data. With a hash key the forms are the digests of the matched text, not folded, so
+/// distinct forms stay distinct without revealing the text.
+pub struct CaseVariants<'a> {
+    data: &'a [u8],
+    hash_key: Option<&'a [u8]>,
+    variants: BTreeMap<PatternID, BTreeMap<String, usize>>,
+}
+
+impl<'a> CaseVariants<'a> {
+    pub fn new(data: &'a [u8], hash_key: Option<&'a [u8]>) -> Self {
+        Self {
+            data,
+            hash_key,
+            variants: BTreeMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, start: usize, end: usize, pattern_idx: PatternID) {
+        let text = &self.data[start..end];
+        let form = match self.hash_key {
+            Some(key) => hash_text(key, text),
+            None => String::from_utf8_lossy(text).into_owned(),
+        };
+        *self
+            .variants
+            .entry(pattern_idx)
+            .or_default()
+            .entry(form)
+            .or_default() += 1;
+    }
+
+    /// Count of each form of every pattern that matched at least once
+    pub fn finish(self) -> BTreeMap<PatternID, BTreeMap<String, usize>> {
+        self.variants
+    }
+}
@@ -0,0 +1,297 @@
+//! WARC scans: the HTTP responses of a web archive, read record by record from plain or
+//! gzipped WARC files, their payloads decoded from the transfer and content encodings before
+//! the search and reported with the target URI
+
+use crate::events::Event;
+use crate::matcher::Matcher;
+use crate::report::SPLIT_FILE_SIZE;
+use aho_corasick::PatternID;
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Result};
+use std::sync::mpsc;
+
+/// Options of a WARC scan
+#[derive(Clone, Debug, Default)]
+pub struct WarcOptions {
+    /// Records whose block is larger than this are skipped without being read into memory
+    pub max_record_size: Option<u64>,
+}
+
+/// Matches of the response records of a WARC file
+pub struct WarcScan {
+    /// Target URI of every response record, in file order
+    pub records: Vec<String>,
+    /// (record, start, end, pattern) matches, the record indexing `records` and the offsets
+    /// being those of the decoded payload, sorted by record then offset
+    pub matches: Vec<(usize, usize, usize, PatternID)>,
+}
+
+impl WarcScan {
+    /// Scan the payloads of the response records of the WARC file at `path`, gzipped or not,
+    /// records being read one at a time and scanned in parallel as they are read
+    /// Records of gzipped files are held to the matcher's max_decoded_size like
+    /// `max_record_size`, and payloads decoding to more than it are truncated
+    pub fn scan(matcher: &Matcher, path: &str, options: &WarcOptions) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let gzipped = file.fill_buf()?.starts_with(&[0x1F, 0x8B]);
+        let mut reader: Box<dyn BufRead + Send> = if gzipped {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            Box::new(file)
+        };
+        let max_decoded_size = matcher.max_decoded_size();
+        let (max_record_size, limit) = match options.max_record_size {
+            Some(max) if !gzipped || max <= max_decoded_size as u64 => {
+                (Some(max), "max_record_size")
+            }
+            _ if gzipped => (Some(max_decoded_size as u64), "max_decoded_size"),
+            _ => (None, "max_record_size"),
+        };
+
+        // Records read ahead of the scan are bounded, so a multi-TB archive is streamed
+        let (sender, receiver) = mpsc::sync_channel(2 * rayon::current_num_threads());
+        let (records, mut matches) = std::thread::scope(|scope| {
+            let reader = scope.spawn(move || -> Result<Vec<String>> {
+                let mut records = Vec::new();
+                while let Some(record) = read_record(&mut reader, max_record_size)? {
+                    if !record.kind.eq_ignore_ascii_case("response") {
+                        continue;
+                    }
+                    let Some(block) = record.block else {
+                        matcher.emit(|| {
+                            Event::limit_hit(
+                                "match_warc",
+                                Some(path),
+                                format!("record of {} larger than {}", record.uri, limit),
+                            )
+                        });
+                        continue;
+                    };
+                    if sender
+                        .send((records.len(), record.uri.clone(), block))
+                        .is_err()
+                    {
+                        break;
+                    }
+                    records.push(record.uri);
+                }
+                Ok(records)
+            });
+            let matches: Vec<_> = receiver
+                .into_iter()
+                .par_bridge()
+                .flat_map_iter(|(idx, uri, block)| {
+                    let (payload, truncated) = http_payload(&block, max_decoded_size);
+                    if truncated {
+                        matcher.emit(|| {
+                            Event::limit_hit(
+                                "match_warc",
+                                Some(path),
+                                format!("payload of {} truncated to max_decoded_size", uri),
+                            )
+                        });
+                    }
+                    let matches = if payload.len() >= SPLIT_FILE_SIZE {
+                        matcher.match_bytes_parallel(&payload)
+                    } else {
+                        matcher.match_bytes(&payload)
+                    };
+                    matches
+                        .into_iter()
+                        .map(move |(start, end, pattern)| (idx, start, end, pattern))
+                })
+                .collect();
+            (reader.join().expect("WARC reader panicked"), matches)
+        });
+        matches.sort_unstable();
+        Ok(Self {
+            records: records?,
+            matches,
+        })
+    }
+}
+
+/// A WARC record: its type, target URI and content block, None when over the size limit
+struct Record {
+    kind: String,
+    uri: String,
+    block: Option<Vec<u8>>,
+}
+
+/// Next record of a WARC file, None at its end, its block skipped unread when larger than
+/// `max_size`
+fn read_record(reader: &mut impl BufRead, max_size: Option<u64>) -> Result<Option<Record>> {
+    // Records are separated by blank lines
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim_ascii().is_empty() {
+            break;
+        }
+    }
+    if !line.starts_with(b"WARC/") {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "Not a WARC file, a record does not start with a WARC version line",
+        ));
+    }
+
+    let (mut kind, mut uri, mut length) = (String::new(), String::new(), None);
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Err(truncated());
+        }
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = split_header(line) else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("WARC-Type") {
+            kind = value;
+        } else if name.eq_ignore_ascii_case("WARC-Target-URI") {
+            // WARC 1.0 allowed the URI in angle brackets
+            uri = value.trim_matches(|c| c == '<' || c == '>').to_string();
+        } else if name.eq_ignore_ascii_case("Content-Length") {
+            length = value.parse::<u64>().ok();
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            "WARC record without a valid Content-Length",
+        )
+    })?;
+
+    if max_size.is_some_and(|max| length > max) {
+        if io::copy(&mut reader.take(length), &mut io::sink())? < length {
+            return Err(truncated());
+        }
+        return Ok(Some(Record {
+            kind,
+            uri,
+            block: None,
+        }));
+    }
+    // The block is read up to its length rather than allocated from it, so a corrupt length
+    // fails on the truncated file instead of allocating
+    let mut block = Vec::new();
+    reader.take(length).read_to_end(&mut block)?;
+    if (block.len() as u64) < length {
+        return Err(truncated());
+    }
+    Ok(Some(Record {
+        kind,
+        uri,
+        block: Some(block),
+    }))
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(ErrorKind::UnexpectedEof, "Truncated WARC record")
+}
+
+/// (name, value) of a `Name: value` header line
+fn split_header(line: &[u8]) -> Option<(String, String)> {
+    let colon = line.iter().position(|&b| b == b':')?;
+    let name = String::from_utf8_lossy(line[..colon].trim_ascii()).into_owned();
+    let value = String::from_utf8_lossy(line[colon + 1..].trim_ascii()).into_owned();
+    Some((name, value))
+}
+
+/// Body of the HTTP response of a block, decoded from its chunked transfer encoding and gzip or
+/// deflate content encoding; bodies that cannot be decoded are scanned as they are
+/// Returns the body and whether its decoding was truncated to `max_len` bytes
+fn http_payload(block: &[u8], max_len: usize) -> (Vec<u8>, bool) {
+    let Some((head, body)) = split_message(block) else {
+        return (block.to_vec(), false);
+    };
+    let (mut chunked, mut encoding) = (false, String::new());
+    for line in head.split(|&b| b == b'\n').skip(1) {
+        let Some((name, value)) = split_header(line.trim_ascii()) else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("Transfer-Encoding") {
+            chunked = value.to_ascii_lowercase().contains("chunked");
+        } else if name.eq_ignore_ascii_case("Content-Encoding") {
+            encoding = value.to_ascii_lowercase();
+        }
+    }
+
+    let body = if chunked {
+        dechunk(body)
+    } else {
+        body.to_vec()
+    };
+    let decoded = match encoding.as_str() {
+        "gzip" | "x-gzip" => decode(MultiGzDecoder::new(&body[..]), max_len),
+        // Servers send deflate with or without the zlib wrapper
+        "deflate" => decode(ZlibDecoder::new(&body[..]), max_len)
+            .or_else(|| decode(DeflateDecoder::new(&body[..]), max_len)),
+        _ => None,
+    };
+    match decoded {
+        Some(mut data) => {
+            let truncated = data.len() > max_len;
+            data.truncate(max_len);
+            (data, truncated)
+        }
+        None => (body, false),
+    }
+}
+
+/// (head, body) of an HTTP message, split at the first blank line
+fn split_message(block: &[u8]) -> Option<(&[u8], &[u8])> {
+    let crlf = memchr::memmem::find(block, b"\r\n\r\n").map(|pos| (pos, pos + 4));
+    let lf = memchr::memmem::find(block, b"\n\n").map(|pos| (pos, pos + 2));
+    let (end, body) = match (crlf, lf) {
+        (Some(crlf), Some(lf)) => crlf.min(lf),
+        (crlf, lf) => crlf.or(lf)?,
+    };
+    block
+        .starts_with(b"HTTP/")
+        .then(|| (&block[..end], &block[body..]))
+}
+
+/// Data of a chunked body, up to its last chunk or the first malformed one
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(body.len());
+    while let Some(eol) = memchr::memchr(b'\n', body) {
+        let size = String::from_utf8_lossy(&body[..eol]);
+        // Chunk extensions follow a semicolon
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size, 16) else {
+            break;
+        };
+        body = &body[eol + 1..];
+        if size == 0 {
+            break;
+        }
+        let size = size.min(body.len());
+        data.extend_from_slice(&body[..size]);
+        body = &body[size..];
+        body = body
+            .strip_prefix(b"\r\n")
+            .or_else(|| body.strip_prefix(b"\n"))
+            .unwrap_or(body);
+    }
+    data
+}
+
+/// Output of a decoder, one byte over `max_len` when it decodes to more, None when the data
+/// is corrupt
+fn decode(decoder: impl Read, max_len: usize) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    decoder
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut data)
+        .ok()?;
+    Some(data)
+}
@@ -0,0 +1,313 @@
+use crate::matcher::ConfigError;
+use crate::sink::{JsonFinding, MatchSink, OutputSchema, Provenance};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::io::{self, Result};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Configuration of a sink that POSTs batches of findings as JSON to an HTTP endpoint
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Clone, Debug)]
+pub struct WebhookSink {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub batch_size: usize,
+    pub max_retries: u32,
+    pub timeout: f64,
+    pub queue_size: usize,
+}
+
+impl WebhookSink {
+    /// A webhook sink with the default batching, retry and timeout settings
+    pub fn new(url: impl Into<String>) -> std::result::Result<Self, ConfigError> {
+        let url = url.into();
+        if !is_webhook_url(&url) {
+            return Err(ConfigError::Invalid(
+                "Webhook URL must start with http:// or https://".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            url,
+            headers: Vec::new(),
+            batch_size: 1000,
+            max_retries: 3,
+            timeout: 30.0,
+            queue_size: 4,
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl WebhookSink {
+    #[new]
+    #[pyo3(signature = (url, headers=None, batch_size=None, max_retries=None, timeout=None, queue_size=None))]
+    fn py_new(
+        url: String,
+        headers: Option<Vec<(String, String)>>,
+        batch_size: Option<usize>,
+        max_retries: Option<u32>,
+        timeout: Option<f64>,
+        queue_size: Option<usize>,
+    ) -> PyResult<Self> {
+        let defaults = Self::new(url)?;
+        let timeout = timeout.unwrap_or(defaults.timeout);
+        // Rejects NaN, infinite and overflowing timeouts as well
+        if !(timeout > 0.0 && Duration::try_from_secs_f64(timeout).is_ok()) {
+            return Err(ConfigError::Invalid(format!(
+                "timeout must be a positive number of seconds, got {}",
+                timeout
+            ))
+            .into());
+        }
+
+        Ok(Self {
+            headers: headers.unwrap_or_default(),
+            batch_size: batch_size.unwrap_or(defaults.batch_size).max(1),
+            max_retries: max_retries.unwrap_or(defaults.max_retries),
+            timeout,
+            queue_size: queue_size.unwrap_or(defaults.queue_size).max(1),
+            ..defaults
+        })
+    }
+
+    /// Header values are left out as they usually carry credentials
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let header_names: Vec<&str> = self.headers.iter().map(|(name, _)| name.as_str()).collect();
+        Ok(format!(
+            "WebhookSink(url={}, headers={}, batch_size={}, max_retries={}, timeout={:?}, queue_size={})",
+            crate::python::quote(py, &self.url)?,
+            pyo3::types::PyList::new(py, header_names)?.repr()?,
+            self.batch_size,
+            self.max_retries,
+            self.timeout,
+            self.queue_size
+        ))
+    }
+
+    #[getter]
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    #[getter]
+    fn headers(&self) -> Vec<(String, String)> {
+        self.headers.clone()
+    }
+
+    #[getter]
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    #[getter]
+    fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    #[getter]
+    fn timeout(&self) -> f64 {
+        self.timeout
+    }
+
+    #[getter]
+    fn queue_size(&self) -> usize {
+        self.queue_size
+    }
+}
+
+pub fn is_webhook_url(sink: &str) -> bool {
+    sink.starts_with("http://") || sink.starts_with("https://")
+}
+
+#[derive(Serialize)]
+struct Finding {
+    start: usize,
+    end: usize,
+    pattern: String,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    scan_id: &'a str,
+    file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<&'a Provenance>,
+    findings: &'a [Finding],
+}
+
+#[derive(Serialize)]
+struct PayloadV1<'a> {
+    schema_version: u32,
+    findings: Vec<JsonFinding<'a>>,
+}
+
+/// Batches findings and hands them to a background thread that POSTs them
+/// The queue between the scan and the sender thread is bounded, so a slow endpoint
+/// slows the scan down instead of letting batches pile up in memory
+pub struct WebhookWriter {
+    batch: Vec<Finding>,
+    batch_size: usize,
+    sender: Option<SyncSender<Vec<Finding>>>,
+    worker: Option<JoinHandle<Result<()>>>,
+}
+
+impl WebhookWriter {
+    /// Start the sender thread, `provenance` being attached to every batch when given and
+    /// bodies following `schema`
+    pub fn start(
+        config: &WebhookSink,
+        scan_id: String,
+        file: Option<String>,
+        provenance: Option<Provenance>,
+        schema: OutputSchema,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Vec<Finding>>(config.queue_size);
+        let config = config.clone();
+        let batch_size = config.batch_size;
+
+        let worker = thread::spawn(move || {
+            // The fields are public, so the timeout is checked here too
+            let timeout = Duration::try_from_secs_f64(config.timeout)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let agent: ureq::Agent = ureq::Agent::config_builder()
+                .timeout_global(Some(timeout))
+                .http_status_as_error(false)
+                .build()
+                .into();
+
+            for findings in receiver {
+                let body = match schema {
+                    OutputSchema::Legacy => serde_json::to_string(&Payload {
+                        scan_id: &scan_id,
+                        file: file.as_deref(),
+                        provenance: provenance.as_ref(),
+                        findings: &findings,
+                    })?,
+                    OutputSchema::V1 => serde_json::to_string(&PayloadV1 {
+                        schema_version: schema.version(),
+                        findings: findings
+                            .iter()
+                            .map(|f| {
+                                JsonFinding::new(
+                                    Some(&scan_id),
+                                    file.as_deref(),
+                                    f.start,
+                                    f.end,
+                                    &f.pattern,
+                                    provenance.as_ref(),
+                                )
+                            })
+                            .collect(),
+                    })?,
+                };
+                post_with_retries(&agent, &config, &body)?;
+            }
+            Ok(())
+        });
+
+        Self {
+            batch: Vec::with_capacity(batch_size),
+            batch_size,
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    fn send_batch(&mut self) -> Result<()> {
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size));
+        let sent = match &self.sender {
+            Some(sender) => sender.send(batch).is_ok(),
+            None => false,
+        };
+
+        if sent {
+            Ok(())
+        } else {
+            // The sender thread only hangs up after failing, report its error
+            self.join()
+        }
+    }
+
+    fn join(&mut self) -> Result<()> {
+        self.sender = None;
+        match self.worker.take() {
+            Some(worker) => worker
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("Webhook sender thread panicked"))),
+            None => Err(io::Error::other("Webhook sink is already closed")),
+        }
+    }
+}
+
+impl MatchSink for WebhookWriter {
+    fn write(&mut self, start: usize, end: usize, pattern: &str, _tags: &[String]) -> Result<()> {
+        self.batch.push(Finding {
+            start,
+            end,
+            pattern: pattern.to_string(),
+        });
+
+        if self.batch.len() >= self.batch_size {
+            self.send_batch()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if !self.batch.is_empty() {
+            self.send_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.batch.is_empty() {
+            self.send_batch()?;
+        }
+        self.join()
+    }
+}
+
+/// POST a body, retrying transport errors, 429 and 5xx responses with exponential backoff
+fn post_with_retries(agent: &ureq::Agent, config: &WebhookSink, body: &str) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        let mut request = agent.post(&config.url);
+        for (name, value) in &config.headers {
+            request = request.header(name, value);
+        }
+
+        let error = match request.content_type("application/json").send(body) {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status().as_u16();
+                if status != 429 && status < 500 {
+                    return Err(io::Error::other(format!(
+                        "Webhook rejected findings with HTTP status {}",
+                        status
+                    )));
+                }
+                format!("HTTP status {}", status)
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= config.max_retries {
+            return Err(io::Error::other(format!(
+                "Webhook delivery failed after {} attempts: {}",
+                attempt + 1,
+                error
+            )));
+        }
+
+        thread::sleep(Duration::from_millis(
+            (200u64 << attempt.min(6)).min(10_000),
+        ));
+        attempt += 1;
+    }
+}
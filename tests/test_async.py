@@ -0,0 +1,67 @@
+import asyncio
+import io
+
+import pytest
+from voluta import TextMatcher
+
+
+def await_scan(method, *args):
+    """Call an async scan method inside a running event loop and wait for its result."""
+
+    async def scan():
+        return await method(*args)
+
+    return asyncio.run(scan())
+
+
+@pytest.fixture
+def log_file(tmp_path):
+    """Fixture that provides a small log file."""
+    path = tmp_path / "app.log"
+    path.write_text("error: disk full\nwarning: cpu hot\nerror again\n")
+    return str(path)
+
+
+def test_amatch_file(log_file):
+    """Test that amatch_file resolves to the same matches as match_file."""
+    matcher = TextMatcher(["error", "warning"])
+    assert await_scan(matcher.amatch_file, log_file) == matcher.match_file(log_file)
+
+
+def test_amatch_bytes():
+    """Test that amatch_bytes resolves to the same matches as match_bytes."""
+    matcher = TextMatcher(["error", "warning"])
+    data = b"error warning error"
+    assert await_scan(matcher.amatch_bytes, data) == matcher.match_bytes(data)
+
+
+@pytest.mark.parametrize("buffer_size", [1, 3, 7, 1024])
+def test_amatch_readable(buffer_size):
+    """Test that matches spanning chunk boundaries of a readable are found once."""
+    matcher = TextMatcher(["error", "warning", "err"], whole_word=True)
+    data = b"error warning errors err error_x err"
+
+    result = await_scan(matcher.amatch_readable, io.BytesIO(data), buffer_size)
+    assert sorted(result) == sorted(matcher.match_bytes(data))
+
+
+def test_concurrent_scans(log_file):
+    """Test that several scans can be awaited concurrently."""
+    matcher = TextMatcher(["error"])
+
+    async def scan_all():
+        return await asyncio.gather(*(matcher.amatch_file(log_file) for _ in range(8)))
+
+    results = asyncio.run(scan_all())
+    assert all(result == matcher.match_file(log_file) for result in results)
+
+
+def test_async_errors():
+    """Test that scan errors are raised when awaiting."""
+    matcher = TextMatcher(["error"])
+
+    with pytest.raises(IOError):
+        await_scan(matcher.amatch_file, "nonexistent_file.txt")
+
+    with pytest.raises(TypeError):
+        await_scan(matcher.amatch_readable, io.StringIO("error"))
@@ -0,0 +1,140 @@
+import json
+import os
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+
+def records(path):
+    with open(path) as f:
+        return [json.loads(line) for line in f]
+
+
+def test_file_scan_record(tmp_path):
+    """Test that file scans append a record of who scanned what with which matcher."""
+    log = tmp_path / "audit.jsonl"
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret and another secret\n")
+    matcher = TextMatcher(["secret", "token"], whole_word=True, audit_log=str(log))
+    assert matcher.audit_log == str(log)
+
+    matcher.match_file_memmap(str(path))
+    (record,) = records(log)
+    assert record["operation"] == "match_file_memmap"
+    assert record["roots"] == [str(path)]
+    assert (record["files_scanned"], record["files_failed"]) == (1, 0)
+    assert (record["bytes_scanned"], record["matches"]) == (26, 2)
+    assert record["error"] is None
+    assert record["pid"] == os.getpid()
+    assert record["started_at"].endswith("Z")
+    assert record["duration"] >= 0
+    assert record["matcher"]["patterns"] == 2
+    assert record["matcher"]["whole_word"] is True
+    assert len(record["matcher"]["fingerprint"]) == 64
+
+
+def test_file_helpers_are_recorded(tmp_path):
+    """Test that the scans of files by the unique, case variant, dedup and histogram helpers
+    are recorded like match_file."""
+    log = tmp_path / "audit.jsonl"
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret SECRET secret\n")
+    matcher = TextMatcher(["secret"], case_insensitive=True, audit_log=str(log))
+
+    matcher.extract_unique(str(path))
+    matcher.case_variants(str(path))
+    matcher.match_file_dedup(str(path))
+    matcher.match_file_histogram(str(path), bucket_size=8)
+    scans = records(log)
+    assert [r["operation"] for r in scans] == [
+        "extract_unique_file",
+        "case_variants_file",
+        "match_file_dedup",
+        "match_file_histogram",
+    ]
+    assert all(r["roots"] == [str(path)] and r["files_scanned"] == 1 for r in scans)
+    assert [r["matches"] for r in scans] == [2, 3, 3, 3]
+
+    with pytest.raises(ScanIOError):
+        matcher.match_file_dedup(str(tmp_path / "missing.log"))
+    assert "No such file" in records(log)[-1]["error"]
+
+
+def test_records_are_appended(tmp_path):
+    """Test that each scan run appends one line, in-memory scans going unrecorded."""
+    log = tmp_path / "audit.jsonl"
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret\n")
+    matcher = TextMatcher(["secret"], audit_log=str(log))
+
+    matcher.match_bytes(b"secret")
+    assert not log.exists()
+    matcher.match_file(str(path))
+    matcher.match_file(str(path))
+    scans = records(log)
+    assert [r["roots"] for r in scans] == [[str(path)], [str(path)]]
+    assert scans[0]["scan_id"] != scans[1]["scan_id"]
+
+
+def test_fingerprint_follows_matches(tmp_path):
+    """Test that fingerprints change with the patterns and the options changing the matches."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret\n")
+
+    def fingerprint(patterns, **options):
+        log = tmp_path / "audit.jsonl"
+        TextMatcher(patterns, audit_log=str(log), **options).match_file(str(path))
+        return records(log)[-1]["matcher"]["fingerprint"]
+
+    same = fingerprint(["secret"])
+    assert fingerprint(["secret"], madvise="sequential") == same
+    assert fingerprint(["secret", "token"]) != same
+    assert fingerprint(["secret"], case_insensitive=False) != same
+    assert fingerprint(["sec", "ret"]) != fingerprint(["se", "cret"])
+
+
+def test_failed_scan_record(tmp_path):
+    """Test that failed scans are recorded with their error."""
+    log = tmp_path / "audit.jsonl"
+    missing = tmp_path / "missing.log"
+    with pytest.raises(ScanIOError):
+        TextMatcher(["secret"], audit_log=str(log)).match_file(str(missing))
+    (record,) = records(log)
+    assert record["roots"] == [str(missing)]
+    assert record["files_scanned"] == 0
+    assert "No such file" in record["error"]
+
+
+def test_dir_and_report_records(tmp_path):
+    """Test that directory scans and reports are recorded as one run with their totals."""
+    root = tmp_path / "share"
+    root.mkdir()
+    (root / "a.log").write_bytes(b"secret")
+    (root / "b.log").write_bytes(b"secret secret")
+    log = tmp_path / "audit.jsonl"
+    matcher = TextMatcher(["secret"], audit_log=str(log))
+
+    matcher.match_dir(str(root))
+    paths = [str(root / "a.log"), str(root / "missing.log")]
+    report = matcher.scan_report(paths, scan_id="sweep-1")
+    dir_scan, report_scan = records(log)
+    assert dir_scan["operation"] == "match_dir"
+    assert dir_scan["roots"] == [str(root)]
+    assert (dir_scan["files_scanned"], dir_scan["bytes_scanned"], dir_scan["matches"]) == (2, 19, 3)
+
+    assert report_scan["operation"] == "scan_report"
+    assert report_scan["scan_id"] == report.scan_id == "sweep-1"
+    assert report_scan["roots"] == paths
+    assert (report_scan["files_scanned"], report_scan["files_failed"]) == (1, 1)
+    assert report_scan["matches"] == 1
+
+
+def test_unwritable_log_fails_scan(tmp_path):
+    """Test that scans whose record cannot be written raise instead of going unevidenced."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret\n")
+    matcher = TextMatcher(["secret"], audit_log=str(tmp_path / "missing" / "audit.jsonl"))
+    with pytest.raises(ScanIOError):
+        matcher.match_file(str(path))
+    with pytest.raises(ScanIOError):
+        matcher.match_dir(str(tmp_path))
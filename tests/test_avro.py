@@ -0,0 +1,291 @@
+import json
+import struct
+import zlib
+
+import pytest
+from voluta import DecodeError, ScanIOError, TextMatcher, TextMatcherBuilder
+
+MATCHER = TextMatcher(["secret", "4111"], case_insensitive=False)
+
+SYNC = bytes(range(16))
+
+USER = {
+    "type": "record",
+    "name": "User",
+    "namespace": "app",
+    "fields": [
+        {"name": "id", "type": "long"},
+        {"name": "name", "type": "string"},
+        {"name": "token", "type": "bytes"},
+        {"name": "note", "type": ["null", "string"]},
+        {"name": "emails", "type": {"type": "array", "items": "string"}},
+        {"name": "labels", "type": {"type": "map", "values": "string"}},
+        {
+            "name": "card",
+            "type": {
+                "type": "record",
+                "name": "Card",
+                "fields": [
+                    {"name": "number", "type": "string"},
+                    {"name": "balance", "type": "double"},
+                    {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["secret"]}},
+                ],
+            },
+        },
+        {"name": "backup", "type": ["null", "app.Card"]},
+    ],
+}
+
+
+def long(n):
+    """A zigzag varint."""
+    n = (n << 1) ^ (n >> 63)
+    out = b""
+    while n >= 0x80:
+        out += bytes([n & 0x7F | 0x80])
+        n >>= 7
+    return out + bytes([n])
+
+
+def string(value):
+    value = value.encode() if isinstance(value, str) else value
+    return long(len(value)) + value
+
+
+def encode(schema, value, named):
+    """The binary encoding of a value, named types being looked up in `named`."""
+    if isinstance(schema, str):
+        if schema in named:
+            return encode(named[schema], value, named)
+        return {
+            "null": lambda: b"",
+            "boolean": lambda: bytes([value]),
+            "long": lambda: long(value),
+            "int": lambda: long(value),
+            "double": lambda: struct.pack("<d", value),
+            "string": lambda: string(value),
+            "bytes": lambda: string(value),
+        }[schema]()
+    if isinstance(schema, list):
+        branch, value = value
+        return long(branch) + encode(schema[branch], value, named)
+    kind = schema["type"]
+    if kind == "record":
+        named[schema["name"]] = named["app." + schema["name"]] = schema
+        return b"".join(encode(field["type"], value[field["name"]], named) for field in schema["fields"])
+    if kind == "enum":
+        named[schema["name"]] = named["app." + schema["name"]] = schema
+        return long(schema["symbols"].index(value))
+    if kind == "array":
+        items = b"".join(encode(schema["items"], item, named) for item in value)
+        return (long(len(value)) + items if value else b"") + long(0)
+    if kind == "map":
+        entries = b"".join(string(key) + encode(schema["values"], item, named) for key, item in value.items())
+        return (long(len(value)) + entries if value else b"") + long(0)
+    return encode(kind, value, named)
+
+
+def snappy(data):
+    """A raw Snappy block made of literals only."""
+    out = bytearray()
+    n = len(data)
+    while n >= 0x80:
+        out.append(n & 0x7F | 0x80)
+        n >>= 7
+    out.append(n)
+    for i in range(0, len(data), 256):
+        chunk = data[i : i + 256]
+        if len(chunk) <= 60:
+            out.append((len(chunk) - 1) << 2)
+        else:
+            out += bytes([60 << 2, len(chunk) - 1])
+        out += chunk
+    return bytes(out) + struct.pack(">I", zlib.crc32(data))
+
+
+def avro(schema, blocks, codec="null"):
+    """An object container file of blocks of records."""
+    out = b"Obj\x01" + long(2)
+    out += string("avro.schema") + string(json.dumps(schema))
+    out += string("avro.codec") + string(codec) + long(0) + SYNC
+    for records in blocks:
+        named = {}
+        data = b"".join(encode(schema, record, named) for record in records)
+        if codec == "deflate":
+            compressor = zlib.compressobj(wbits=-15)
+            data = compressor.compress(data) + compressor.flush()
+        elif codec == "snappy":
+            data = snappy(data)
+        out += long(len(records)) + long(len(data)) + data + SYNC
+    return out
+
+
+def user(i, **fields):
+    record = {
+        "id": i,
+        "name": f"user{i}",
+        "token": b"",
+        "note": (0, None),
+        "emails": [],
+        "labels": {},
+        "card": {"number": "0000", "balance": 1.5, "kind": "secret"},
+        "backup": (0, None),
+    }
+    record.update(fields)
+    return record
+
+
+USERS = [
+    [
+        user(0, token=b"\x00secret\xff", note=(1, "a secret note")),
+        user(1),
+        user(2, emails=["a@example.com", "secret@example.com"]),
+    ],
+    [
+        user(3, labels={"secret": "none", "env": "secret"}, card={"number": "4111 1111", "balance": 0.0, "kind": "secret"}),
+        user(4, backup=(1, {"number": "4111", "balance": 2.0, "kind": "secret"})),
+    ],
+]
+
+EXPECTED = [
+    (0, "token", 1, 7, "secret"),
+    (0, "note", 2, 8, "secret"),
+    (2, "emails[1]", 0, 6, "secret"),
+    (3, "labels[env]", 0, 6, "secret"),
+    (3, "card.number", 0, 4, "4111"),
+    (4, "backup.number", 0, 4, "4111"),
+]
+
+
+@pytest.mark.parametrize("codec", ["null", "deflate", "snappy"])
+def test_codecs(tmp_path, codec):
+    """Test that records are reported by index and field path whatever the codec."""
+    path = tmp_path / "users.avro"
+    path.write_bytes(avro(USER, USERS, codec))
+    assert MATCHER.match_avro(str(path)) == EXPECTED
+
+
+def test_recursive_schema(tmp_path):
+    """Test that recursive named types are decoded to the depth of the values."""
+    schema = {
+        "type": "record",
+        "name": "Node",
+        "fields": [
+            {"name": "value", "type": "string"},
+            {"name": "next", "type": ["null", "Node"]},
+        ],
+    }
+    record = {"value": "root", "next": (1, {"value": "child", "next": (1, {"value": "secret", "next": (0, None)})})}
+    path = tmp_path / "list.avro"
+    path.write_bytes(avro(schema, [[record]]))
+    assert MATCHER.match_avro(str(path)) == [(0, "next.next.value", 0, 6, "secret")]
+
+
+def test_top_level_string(tmp_path):
+    """Test that files of plain strings report an empty field path."""
+    path = tmp_path / "strings.avro"
+    path.write_bytes(avro("string", [["public", "a secret"]]))
+    assert MATCHER.match_avro(str(path)) == [(1, "", 2, 8, "secret")]
+
+
+def test_errors(tmp_path):
+    """Test that missing, foreign, truncated and corrupt files are reported."""
+    with pytest.raises(ScanIOError):
+        MATCHER.match_avro(str(tmp_path / "missing.avro"))
+
+    path = tmp_path / "text.avro"
+    path.write_text("not an avro file")
+    with pytest.raises(DecodeError, match="Not an Avro"):
+        MATCHER.match_avro(str(path))
+
+    data = avro(USER, USERS)
+    path.write_bytes(data[:-20])
+    with pytest.raises(DecodeError):
+        MATCHER.match_avro(str(path))
+
+    path.write_bytes(data[:-16] + b"\xff" * 16)
+    with pytest.raises(DecodeError):
+        MATCHER.match_avro(str(path))
+
+    path.write_bytes(avro(USER, USERS).replace(b"\x08null", b"\x08zstd", 1))
+    with pytest.raises(DecodeError, match="zstd"):
+        MATCHER.match_avro(str(path))
+
+    data = bytearray(avro(USER, USERS, "snappy"))
+    data[-17] ^= 0xFF
+    path.write_bytes(bytes(data))
+    with pytest.raises(DecodeError, match="CRC"):
+        MATCHER.match_avro(str(path))
+
+
+def test_fastavro(tmp_path):
+    """Test files written by fastavro, when it is installed."""
+    fastavro = pytest.importorskip("fastavro")
+    for codec in ("null", "deflate", "snappy"):
+        path = tmp_path / f"users-{codec}.avro"
+        with open(path, "wb") as f:
+            records = [
+                {**record, "note": record["note"][1], "backup": record["backup"][1]}
+                for block in USERS
+                for record in block
+            ]
+            try:
+                fastavro.writer(f, fastavro.parse_schema(USER), records, codec=codec)
+            except ValueError:
+                # The snappy codec needs the python-snappy package
+                continue
+        assert MATCHER.match_avro(str(path)) == EXPECTED
+
+
+def test_inflated_counts(tmp_path):
+    """Test that counts larger than the values present neither hang nor exhaust memory."""
+    path = tmp_path / "counts.avro"
+    header = b"Obj\x01" + long(1) + string("avro.schema")
+
+    # Blocks and arrays of values taking no bytes
+    path.write_bytes(header + string('"null"') + long(0) + SYNC + long(1 << 62) + long(0) + SYNC)
+    assert MATCHER.match_avro(str(path)) == []
+    schema = {
+        "type": "record",
+        "name": "Nulls",
+        "fields": [{"name": "nulls", "type": {"type": "array", "items": "null"}}, {"name": "name", "type": "string"}],
+    }
+    data = long(1 << 62) + long(0) + string("secret")
+    data += long(-(1 << 62)) + long(0) + long(0) + string("secret")
+    path.write_bytes(header + string(json.dumps(schema)) + long(0) + SYNC + long(2) + long(len(data)) + data + SYNC)
+    assert MATCHER.match_avro(str(path)) == [(0, "name", 0, 6, "secret"), (1, "name", 0, 6, "secret")]
+
+    # Counts and sizes past the bytes of the block or the file
+    for block in (long(1 << 62) + long(7) + string("secret"), long(-1) + long(0), long(1) + long(200 << 20)):
+        path.write_bytes(header + string('"string"') + long(0) + SYNC + block + SYNC)
+        with pytest.raises(DecodeError):
+            MATCHER.match_avro(str(path))
+    path.write_bytes(header + long(1 << 62))
+    with pytest.raises(DecodeError):
+        MATCHER.match_avro(str(path))
+
+
+@pytest.mark.parametrize("codec", ["null", "deflate", "snappy"])
+def test_corrupt_bytes(tmp_path, codec):
+    """Test that truncated files and files with any byte overwritten decode or fail cleanly."""
+    path = tmp_path / "users.avro"
+    data = avro(USER, USERS, codec)
+    corrupted = [data[:end] for end in range(len(data))]
+    corrupted += [data[:i] + bytes([byte]) + data[i + 1 :] for i in range(len(data)) for byte in (0x00, 0x7F, 0xFF)]
+    for corrupt in corrupted:
+        path.write_bytes(corrupt)
+        try:
+            MATCHER.match_avro(str(path))
+        except DecodeError:
+            pass
+
+
+@pytest.mark.parametrize("codec", ["deflate", "snappy"])
+def test_max_decoded_size(tmp_path, codec):
+    """Test that blocks decompressing past max_decoded_size fail instead of exhausting memory."""
+    path = tmp_path / "large.avro"
+    path.write_bytes(avro("string", [["x" * 100_000 + "secret"]], codec))
+    assert MATCHER.match_avro(str(path)) == [(0, "", 100_000, 100_006, "secret")]
+    matcher = TextMatcherBuilder(["secret"]).options(max_decoded_size=64 * 1024).build()
+    with pytest.raises(DecodeError, match="max_decoded_size"):
+        matcher.match_avro(str(path))
@@ -0,0 +1,83 @@
+import pytest
+import voluta
+from voluta import BenchResult, LimitExceeded
+
+PATTERNS = ["password", "token"]
+
+
+@pytest.fixture
+def data():
+    """Fixture that provides sample data with matches of both patterns."""
+    return b"password=x token=y " * 5000
+
+
+def test_config_list(data):
+    """Test that every configuration of a list is timed, in order."""
+    results = voluta.benchmark(
+        PATTERNS,
+        data,
+        [{"engine": "aho"}, {"engine": "auto", "chunk_size": 4096, "n_threads": 2}],
+        repeat=2,
+    )
+    assert [type(r) for r in results] == [BenchResult, BenchResult]
+
+    single, chunked = results
+    assert (single.engine, single.chunk_size, single.n_threads) == ("aho", None, None)
+    assert (chunked.engine, chunked.chunk_size, chunked.n_threads) == ("auto", 4096, 2)
+    assert chunked.resolved_engine == "memmem"
+    for result in results:
+        assert result.matches == 10000
+        assert len(result.times) == 2
+        assert result.best == min(result.times)
+        assert result.best <= result.mean
+        assert result.throughput > 0
+        assert result.build_time >= 0
+    assert "BenchResult(engine='aho'" in repr(single)
+
+
+def test_config_matrix(data):
+    """Test that a dict of lists is expanded to every combination."""
+    results = voluta.benchmark(
+        PATTERNS,
+        data,
+        {"engine": ["aho", "memmem"], "chunk_size": [None, 1000], "n_threads": [1, 2]},
+        repeat=1,
+    )
+    assert [(r.engine, r.n_threads) for r in results] == [
+        ("aho", 1),
+        ("aho", 2),
+        ("aho", 1),
+        ("aho", 2),
+        ("memmem", 1),
+        ("memmem", 2),
+        ("memmem", 1),
+        ("memmem", 2),
+    ]
+    # n_threads alone scans in tuned chunks
+    assert all(r.chunk_size for r in results)
+    assert {r.matches for r in results} == {10000}
+
+
+def test_matcher_options():
+    """Test that the matcher options apply to every configuration."""
+    results = voluta.benchmark(
+        ["Token"], b"token TOKEN tokens", [{}], case_insensitive=True, whole_word=True
+    )
+    assert results[0].matches == 2
+    assert results[0].chunk_size is None
+
+
+def test_invalid_configs(data):
+    """Test that invalid configurations are rejected."""
+    with pytest.raises(ValueError, match="Unknown benchmark option 'threads'"):
+        voluta.benchmark(PATTERNS, data, [{"threads": 2}])
+    with pytest.raises(ValueError, match="Unknown engine"):
+        voluta.benchmark(PATTERNS, data, [{"engine": "regex"}])
+    with pytest.raises(ValueError, match="chunk_size must be positive"):
+        voluta.benchmark(PATTERNS, data, {"chunk_size": [0]})
+    with pytest.raises(ValueError, match="repeat must be positive"):
+        voluta.benchmark(PATTERNS, data, [{}], repeat=0)
+    with pytest.raises(TypeError, match="configs"):
+        voluta.benchmark(PATTERNS, data, "aho")
+    with pytest.raises(LimitExceeded, match="memmem"):
+        voluta.benchmark(["x" * 100], data, [{"engine": "memmem"}])
@@ -0,0 +1,120 @@
+import threading
+import time
+
+import pytest
+from voluta import TextMatcher, TextMatcherBuilder
+
+CUSTOMERS = [f"customer{i:06d}" for i in range(50_000)]
+
+
+class Cancelled(Exception):
+    pass
+
+
+def test_stages():
+    """Test that the start and end of every stage are reported, in order."""
+    reports = []
+    (
+        TextMatcherBuilder(["alpha", "", "beta"])
+        .options(progress=lambda *report: reports.append(report))
+        .build()
+    )
+    assert reports[:2] == [("prepare", 0, 3), ("prepare", 3, 3)]
+    assert reports[2] == ("automaton", 0, 1)
+    assert reports[-1] == ("automaton", 1, 1)
+    assert {stage for stage, _, _ in reports} == {"prepare", "automaton"}
+
+
+def test_optional_stages():
+    """Test that fuzzy expansion and hashing are reported when they run."""
+    reports = []
+    (
+        TextMatcherBuilder(["password", "secret"])
+        .options(fuzzy=1, progress=lambda *r: reports.append(r))
+        .build()
+    )
+    assert ("expand", 0, 2) in reports and ("expand", 2, 2) in reports
+
+    reports = []
+    (
+        TextMatcherBuilder(["password", "secret"])
+        .options(hash_key=b"k", progress=lambda *r: reports.append(r))
+        .build()
+    )
+    assert reports[-3:] == [("automaton", 1, 1), ("labels", 0, 2), ("labels", 2, 2)]
+
+
+def test_cancel():
+    """Test that an exception raised by the callback cancels the build."""
+
+    def progress(stage, done, total):
+        if stage == "automaton":
+            raise Cancelled()
+
+    with pytest.raises(Cancelled):
+        TextMatcherBuilder(["alpha", "beta"]).options(progress=progress).build()
+
+
+def test_cancel_automaton():
+    """Test that a build cancelled from another thread returns before the automaton is built."""
+    started = time.monotonic()
+    TextMatcher(CUSTOMERS, engine="aho")
+    build_time = time.monotonic() - started
+    if build_time < 0.5:
+        pytest.skip("the automaton builds too fast to be cancelled midway")
+
+    stop = threading.Event()
+
+    def progress(stage, done, total):
+        if stop.is_set():
+            raise Cancelled()
+
+    timer = threading.Timer(build_time / 4, stop.set)
+    timer.start()
+    started = time.monotonic()
+    try:
+        with pytest.raises(Cancelled):
+            TextMatcherBuilder(CUSTOMERS).options(engine="aho", progress=progress).build()
+    finally:
+        timer.cancel()
+    assert time.monotonic() - started < build_time
+
+
+def test_build_releases_gil():
+    """Test that other threads run while a matcher is built."""
+    ticks = []
+    done = threading.Event()
+
+    def tick():
+        while not done.is_set():
+            ticks.append(time.monotonic())
+            time.sleep(0.001)
+
+    ticker = threading.Thread(target=tick)
+    ticker.start()
+    try:
+        started = time.monotonic()
+        TextMatcher(CUSTOMERS, engine="aho")
+        finished = time.monotonic()
+    finally:
+        done.set()
+        ticker.join()
+    assert len([t for t in ticks if started < t < finished]) > 1
+
+
+def test_builder_progress():
+    """Test that the builder passes the callback on to the build."""
+    reports = []
+    matcher = (
+        TextMatcherBuilder(["alpha", "beta"])
+        .options(progress=lambda *report: reports.append(report))
+        .build()
+    )
+    assert len(matcher) == 2
+    assert reports[0] == ("prepare", 0, 2)
+
+
+def test_progress_not_callable():
+    """Test that a progress that cannot be called is rejected."""
+    with pytest.raises(TypeError, match="progress"):
+        TextMatcherBuilder(["alpha"]).options(progress="log").build()
@@ -0,0 +1,171 @@
+import pytest
+from voluta import PatternError, TextMatcher, TextMatcherBuilder
+
+
+def test_builder_matches_constructor():
+    """Test that a built matcher finds what the equivalent TextMatcher finds."""
+    data = b"Alice met Bob, alice and BOB again"
+    built = (
+        TextMatcherBuilder(["alice"])
+        .add_pattern("bob")
+        .case_insensitive(False)
+        .overlapping(False)
+        .whole_word()
+        .build()
+    )
+    direct = TextMatcher(["alice", "bob"], case_insensitive=False, overlapping=False, whole_word=True)
+    assert isinstance(built, TextMatcher)
+    assert built.match_bytes(data) == direct.match_bytes(data) == [(15, 20, "alice")]
+    assert built.fingerprint == direct.fingerprint
+
+
+def test_builder_metadata():
+    """Test that metadata follows its patterns from every source."""
+    builder = (
+        TextMatcherBuilder()
+        .add_patterns(["alpha", "beta"], metadata={"team": "red"})
+        .add_patterns(["gamma", "delta"], metadata=[{"severity": "high"}, {}])
+        .add_pattern("epsilon", metadata={"owner": "ops"})
+        .add_pattern("zeta")
+    )
+    assert len(builder) == 6
+    matcher = builder.build()
+    assert matcher.patterns == ["alpha", "beta", "gamma", "delta", "epsilon", "zeta"]
+    assert matcher.metadata == [
+        {"team": "red"},
+        {"team": "red"},
+        {"severity": "high"},
+        {},
+        {"owner": "ops"},
+        {},
+    ]
+
+    with pytest.raises(ValueError, match="one entry per pattern"):
+        builder.add_patterns(["eta", "theta"], metadata=[{}])
+    assert len(builder) == 6
+
+
+def test_metadata_dropped_with_empty_patterns():
+    """Test that the metadata of empty patterns is dropped along with them."""
+    metadata = [{"kind": "pci"}, {"kind": "none"}, {}]
+    matcher = TextMatcherBuilder().add_patterns(["card", "", "name"], metadata=metadata).build()
+    assert matcher.patterns == ["card", "name"]
+    assert matcher.metadata == [{"kind": "pci"}, {}]
+    assert TextMatcher(["card"]).metadata == [{}]
+
+    # The constructor arguments are frozen, metadata is only added through the builder
+    with pytest.raises(TypeError):
+        TextMatcher(["card", "name"], metadata=[{}, {}])
+
+
+def test_builder_patterns_from_file(tmp_path):
+    """Test that pattern files skip blank lines and comments."""
+    path = tmp_path / "rules.txt"
+    path.write_bytes(b"# secrets\r\nAKIA\r\n\r\n  ghp_  \n#ignored\n")
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns_from_file(str(path), metadata={"source": "rules.txt"})
+        .normalize("trim")
+        .build()
+    )
+    assert matcher.patterns == ["AKIA", "ghp_"]
+    assert matcher.metadata == [{"source": "rules.txt"}] * 2
+
+    with pytest.raises(IOError):
+        TextMatcherBuilder().add_patterns_from_file(str(tmp_path / "missing.txt"))
+
+
+def test_builder_normalizers():
+    """Test that normalizers apply in the order they were added."""
+    matcher = (
+        TextMatcherBuilder(["  Credit \t Card  "])
+        .normalize("trim")
+        .normalize("collapse_whitespace")
+        .normalize("lower")
+        .case_insensitive(False)
+        .build()
+    )
+    assert matcher.patterns == ["credit card"]
+
+    with pytest.raises(ValueError, match="Unknown normalizer 'upper'"):
+        TextMatcherBuilder().normalize("upper")
+
+
+def test_builder_validators_report_every_failure():
+    """Test that build() lists every rejected pattern at once."""
+    builder = (
+        TextMatcherBuilder(["key", "token", "key", "café", "pw"])
+        .validate("unique")
+        .validate("ascii")
+        .validate("min_length", 3)
+    )
+    with pytest.raises(PatternError) as excinfo:
+        builder.build()
+    message = str(excinfo.value)
+    assert "Patterns failed validation (3)" in message
+    assert "pattern 2 'key' is a duplicate" in message
+    assert "pattern 3 'café' is not ASCII" in message
+    assert "pattern 4 'pw' is shorter than 3 bytes" in message
+
+    # Many failures are counted rather than listed
+    builder = TextMatcherBuilder([str(i) for i in range(25)]).validate("max_length", 0)
+    with pytest.raises(PatternError, match=r"\(25\).* and 15 more$"):
+        builder.build()
+
+
+def test_builder_callable_validator():
+    """Test that Python callables validate the normalized patterns."""
+
+    def no_test_patterns(pattern):
+        return not pattern.startswith("test")
+
+    builder = TextMatcherBuilder(["password", " TestKey"]).normalize("trim").normalize("lower")
+    assert builder.validate(no_test_patterns) is builder
+    with pytest.raises(PatternError, match="pattern 1 'testkey' is rejected by no_test_patterns"):
+        builder.build()
+
+    def failing(pattern):
+        raise RuntimeError("validator failed")
+
+    with pytest.raises(RuntimeError, match="validator failed"):
+        TextMatcherBuilder(["password"]).validate(failing).build()
+
+
+def test_builder_invalid_arguments():
+    """Test that unknown names are rejected as they are given."""
+    with pytest.raises(ValueError, match="Unknown validator 'short'"):
+        TextMatcherBuilder().validate("short")
+    with pytest.raises(ValueError, match="Unknown validator 'min_length'"):
+        TextMatcherBuilder().validate("min_length")
+    with pytest.raises(ValueError, match="Unknown engine 'fast'"):
+        TextMatcherBuilder().engine("fast")
+    with pytest.raises(ValueError, match="'patterns' cannot be set as an option"):
+        TextMatcherBuilder().options(patterns=["a"])
+
+
+def test_builder_options_checked_at_build():
+    """Test that invalid option combinations surface at build()."""
+    builder = TextMatcherBuilder(["card", "name"]).options(postprocess="merge", priorities=[1, 2])
+    with pytest.raises(PatternError, match="Priorities cannot be combined"):
+        builder.build()
+
+    with pytest.raises(PatternError, match="Pattern set cannot be empty"):
+        TextMatcherBuilder().build()
+
+    with pytest.raises(TypeError):
+        TextMatcherBuilder(["card"]).options(colour="red").build()
+
+    with pytest.raises(ValueError, match="supports up to 8 patterns"):
+        TextMatcherBuilder([f"p{i}" for i in range(9)]).engine("memmem").build()
+
+
+def test_builder_reuse():
+    """Test that a builder keeps working after build()."""
+    builder = TextMatcherBuilder(["card"]).options(exclude=["card 0000"])
+    first = builder.build()
+    second = builder.add_pattern("name").build()
+    assert first.patterns == ["card"]
+    assert second.patterns == ["card", "name"]
+    assert second.exclude == ["card 0000"]
+    assert second.match_bytes(b"card 0000 card name") == [(10, 14, "card"), (15, 19, "name")]
+    assert repr(builder) == "TextMatcherBuilder(patterns=2, normalizers=0, validators=0)"
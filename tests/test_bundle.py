@@ -0,0 +1,274 @@
+import gzip
+import json
+import subprocess
+import sys
+
+import pytest
+from voluta import (
+    DecodeError,
+    PatternError,
+    TextMatcher,
+    TextMatcherBuilder,
+    bundle_info,
+    bundle_verify_key,
+    compile_bundle,
+)
+
+TEXT = b"Customer ACME Corp paid invoice 1234, Initech did not"
+
+
+@pytest.fixture
+def rules(tmp_path):
+    (tmp_path / "customers.txt").write_text("# customers\nacme corp\ninitech\n")
+    (tmp_path / "terms.txt").write_text("invoice\n\npaid\n")
+    return tmp_path
+
+
+def test_round_trip(tmp_path):
+    """Test that a loaded bundle has the fingerprint and matches of the saved matcher."""
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(
+            ["acme corp", "invoice", "initech"],
+            metadata=[{"team": "sales"}, {}, {}],
+            tags=[["customer"], [], ["customer"]],
+        )
+        .case_insensitive(False)
+        .whole_word()
+        .build()
+    )
+    info = matcher.save_bundle(str(tmp_path / "rules.vb"))
+    loaded = TextMatcher.load_bundle(str(tmp_path / "rules.vb"))
+
+    assert info.fingerprint == loaded.fingerprint == matcher.fingerprint
+    assert (info.pattern_count, info.engine) == (3, matcher.engine)
+    assert loaded.match_bytes(TEXT) == matcher.match_bytes(TEXT)
+    assert loaded.metadata == matcher.metadata
+    assert loaded.tags == matcher.tags
+    assert (loaded.case_insensitive, loaded.whole_word) == (False, True)
+
+
+def test_compile_bundle(rules):
+    """Test that the patterns of every file are bundled with their file as source."""
+    output = rules / "rules.vb"
+    info = compile_bundle(
+        [str(rules / "customers.txt"), str(rules / "terms.txt")],
+        str(output),
+        validators=["unique", ("min_length", 4)],
+        overlapping=False,
+    )
+    assert info.pattern_count == 4
+    assert bundle_info(str(output)).to_json() == info.to_json()
+
+    matcher = TextMatcher.load_bundle(str(output))
+    assert matcher.overlapping is False
+    assert [m[2] for m in matcher.match_bytes(TEXT)] == ["acme corp", "paid", "invoice", "initech"]
+    assert matcher.metadata[0] == {"source": str(rules / "customers.txt")}
+    assert matcher.metadata[3] == {"source": str(rules / "terms.txt")}
+
+
+def test_compile_validation(rules):
+    """Test that a pattern rejected by a validator fails the compilation."""
+    output = rules / "rules.vb"
+    with pytest.raises(PatternError, match="shorter than 5 bytes"):
+        compile_bundle([str(rules / "terms.txt")], str(output), validators=[("min_length", 5)])
+    assert not output.exists()
+
+
+def test_compile_golden(rules):
+    """Test that a failing golden test fails the compilation without writing the bundle."""
+    output = rules / "rules.vb"
+    passing = [{"text": "ACME Corp invoice", "matches": ["acme corp", "invoice"]}]
+    compile_bundle([str(rules / "customers.txt"), str(rules / "terms.txt")], str(output), golden=passing)
+    assert output.exists()
+    output.unlink()
+
+    failing = [{"name": "globex", "text": "Globex", "matches": ["globex"]}]
+    with pytest.raises(PatternError, match="Pattern tests failed \\(1\\): globex"):
+        compile_bundle([str(rules / "customers.txt")], str(output), golden=failing)
+    assert sorted(path.name for path in rules.iterdir()) == ["customers.txt", "terms.txt"]
+
+
+def test_hashed_rejected(tmp_path):
+    """Test that matchers built with a hash_key are not bundled."""
+    matcher = TextMatcher(["secret"], hash_key=b"key")
+    with pytest.raises(PatternError, match="hash_key"):
+        matcher.save_bundle(str(tmp_path / "rules.vb"))
+    assert not (tmp_path / "rules.vb").exists()
+
+
+def test_invalid_bundle(tmp_path):
+    """Test that files that are not bundles are rejected."""
+    path = tmp_path / "rules.vb"
+    path.write_bytes(b"not a bundle")
+    with pytest.raises(DecodeError, match="Invalid matcher bundle"):
+        TextMatcher.load_bundle(str(path))
+    with pytest.raises(DecodeError):
+        bundle_info(str(path))
+
+
+def test_bundle_info(tmp_path):
+    """Test that the info of a bundle is read back as written."""
+    matcher = TextMatcher([f"customer{i:04d}" for i in range(1000)], engine="aho")
+    info = matcher.save_bundle(str(tmp_path / "rules.vb"))
+    read = bundle_info(str(tmp_path / "rules.vb"))
+    assert read.to_json() == info.to_json()
+    fields = json.loads(read.to_json())
+    assert fields["automaton"] == matcher.automaton == "dfa"
+    assert fields["state_count"] == matcher.state_count
+    assert fields["fingerprint"] == matcher.fingerprint
+    assert fields["memory_usage"] > 0
+
+
+def test_cli(rules):
+    """Test that the command line compiles and inspects bundles."""
+    output = rules / "rules.vb"
+
+    def voluta(*args):
+        return subprocess.run(
+            [sys.executable, "-m", "voluta", *args], capture_output=True, text=True
+        )
+
+    compiled = voluta(
+        "compile",
+        str(rules / "customers.txt"),
+        str(rules / "terms.txt"),
+        "-o",
+        str(output),
+        "--validate",
+        "min_length=4",
+        "--option",
+        "whole_word=true",
+    )
+    assert compiled.returncode == 0, compiled.stderr
+    info = json.loads(compiled.stdout)
+    assert info["pattern_count"] == 4
+    assert TextMatcher.load_bundle(str(output)).whole_word is True
+
+    inspected = voluta("inspect", str(output))
+    assert inspected.returncode == 0, inspected.stderr
+    assert json.loads(inspected.stdout) == info
+
+    default = voluta("compile", str(rules / "customers.txt"), "-o", str(output))
+    assert default.returncode == 0, default.stderr
+    loaded = TextMatcher.load_bundle(str(output))
+    assert (loaded.case_insensitive, loaded.overlapping) == (True, True)
+    assert [m[2] for m in loaded.match_bytes(b"ACME CORP")] == ["acme corp"]
+
+    strict = voluta(
+        "compile", str(rules / "customers.txt"), "-o", str(output), "--no-case-insensitive", "--no-overlapping"
+    )
+    assert strict.returncode == 0, strict.stderr
+    loaded = TextMatcher.load_bundle(str(output))
+    assert (loaded.case_insensitive, loaded.overlapping) == (False, False)
+    assert loaded.match_bytes(b"ACME CORP") == []
+    assert [m[2] for m in loaded.match_bytes(b"acme corp")] == ["acme corp"]
+
+    failed = voluta("compile", str(rules / "terms.txt"), "-o", str(output), "--validate", "min_length=5")
+    assert failed.returncode == 1
+    assert "shorter than 5 bytes" in failed.stderr
+
+
+def rewrite(path, edit):
+    """Apply `edit` to the JSON of the bundle at `path`."""
+    fields = json.loads(gzip.decompress(path.read_bytes()))
+    edit(fields)
+    path.write_bytes(gzip.compress(json.dumps(fields).encode()))
+
+
+def test_content_hash(tmp_path):
+    """Test that bundles edited after they were written are rejected."""
+    path = tmp_path / "rules.vb"
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(["acme corp", "invoice"], metadata=[{"team": "sales"}, {}])
+        .build()
+    )
+    info = matcher.save_bundle(str(path))
+    assert len(info.content_hash) == 64
+    assert bundle_info(str(path)).content_hash == info.content_hash
+    rewrite(path, lambda fields: None)
+    assert TextMatcher.load_bundle(str(path)).metadata == matcher.metadata
+
+    for edit in (
+        lambda fields: fields["patterns"].append("globex"),
+        lambda fields: fields["metadata"][0].update(team="support"),
+        lambda fields: fields["tags"][1].append("customer"),
+        lambda fields: fields["options"].update(prefault=not fields["options"]["prefault"]),
+        lambda fields: fields["info"].update(content_hash="0" * 64),
+    ):
+        matcher.save_bundle(str(path))
+        rewrite(path, edit)
+        with pytest.raises(DecodeError, match="modified after it was written"):
+            TextMatcher.load_bundle(str(path))
+        with pytest.raises(DecodeError):
+            bundle_info(str(path))
+
+
+def test_expected_hash(tmp_path):
+    """Test that a pinned hash rejects bundles rewritten with a matching recorded hash."""
+    path = tmp_path / "rules.vb"
+    info = TextMatcher(["acme corp", "invoice"]).save_bundle(str(path))
+    loaded = TextMatcher.load_bundle(str(path), expected_hash=info.content_hash)
+    assert loaded.fingerprint == info.fingerprint
+    assert TextMatcher.load_bundle(str(path), expected_hash=info.content_hash.upper())
+
+    TextMatcher(["acme corp", "globex"]).save_bundle(str(path))
+    assert TextMatcher.load_bundle(str(path))
+    with pytest.raises(DecodeError, match=f"expected {info.content_hash}"):
+        TextMatcher.load_bundle(str(path), expected_hash=info.content_hash)
+
+
+def test_signed_bundle(tmp_path):
+    """Test that a verify key only accepts the bundles signed with its signing key."""
+    path = tmp_path / "rules.vb"
+    signing_key = bytes(range(32))
+    verify_key = bundle_verify_key(signing_key)
+    assert len(verify_key) == 32
+
+    info = TextMatcher(["acme corp", "invoice"]).save_bundle(str(path), signing_key=signing_key)
+    assert info.signed and bundle_info(str(path)).signed
+    loaded = TextMatcher.load_bundle(str(path), verify_key=verify_key)
+    assert loaded.fingerprint == info.fingerprint
+    # The signature is only checked when asked for
+    assert TextMatcher.load_bundle(str(path)).fingerprint == info.fingerprint
+
+    with pytest.raises(DecodeError, match="does not match the verify key"):
+        TextMatcher.load_bundle(str(path), verify_key=bundle_verify_key(bytes(32)))
+
+    # A signature moved to another bundle does not cover its content hash
+    signature = json.loads(gzip.decompress(path.read_bytes()))["signature"]
+    other = tmp_path / "other.vb"
+    assert not TextMatcher(["acme corp", "globex"]).save_bundle(str(other)).signed
+    with pytest.raises(DecodeError, match="not signed"):
+        TextMatcher.load_bundle(str(other), verify_key=verify_key)
+    rewrite(other, lambda fields: fields.update(signature=signature))
+    with pytest.raises(DecodeError, match="does not match the verify key"):
+        TextMatcher.load_bundle(str(other), verify_key=verify_key)
+
+    with pytest.raises(PatternError, match="signing_key must be 32 bytes"):
+        TextMatcher(["acme corp"]).save_bundle(str(path), signing_key=b"short")
+    with pytest.raises(PatternError, match="verify_key must be 32 bytes"):
+        TextMatcher.load_bundle(str(path), verify_key=verify_key[:16])
+
+
+def test_compile_signed(rules):
+    """Test that compile_bundle and the command line sign bundles."""
+    output = rules / "rules.vb"
+    signing_key = bytes(range(32, 64))
+    info = compile_bundle([str(rules / "customers.txt")], str(output), signing_key=signing_key)
+    assert info.signed
+    TextMatcher.load_bundle(str(output), verify_key=bundle_verify_key(signing_key))
+
+    key_file = rules / "signing.key"
+    key_file.write_bytes(signing_key)
+    compiled = subprocess.run(
+        [sys.executable, "-m", "voluta", "compile", str(rules / "terms.txt"), "-o", str(output)]
+        + ["--signing-key", str(key_file)],
+        capture_output=True,
+        text=True,
+    )
+    assert compiled.returncode == 0, compiled.stderr
+    assert json.loads(compiled.stdout)["signed"] is True
+    loaded = TextMatcher.load_bundle(str(output), verify_key=bundle_verify_key(signing_key))
+    assert loaded.patterns == ["invoice", "paid"]
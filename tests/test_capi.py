@@ -0,0 +1,151 @@
+import ctypes
+
+import pytest
+import voluta.voluta
+
+
+class VolutaOptions(ctypes.Structure):
+    _fields_ = [
+        ("overlapping", ctypes.c_bool),
+        ("case_insensitive", ctypes.c_bool),
+        ("whole_word", ctypes.c_bool),
+        ("postprocess", ctypes.c_int),
+        ("prefer_longer", ctypes.c_bool),
+    ]
+
+
+class VolutaMatch(ctypes.Structure):
+    _fields_ = [
+        ("start", ctypes.c_size_t),
+        ("end", ctypes.c_size_t),
+        ("pattern", ctypes.c_size_t),
+    ]
+
+
+@pytest.fixture
+def capi():
+    """Fixture that provides the C interface of the extension module."""
+    lib = ctypes.CDLL(voluta.voluta.__file__)
+    if not hasattr(lib, "voluta_matcher_new"):
+        pytest.skip("voluta was built without the capi feature")
+
+    lib.voluta_version.restype = ctypes.c_char_p
+    lib.voluta_options_default.restype = VolutaOptions
+    lib.voluta_matcher_new.restype = ctypes.c_void_p
+    lib.voluta_matcher_new.argtypes = [
+        ctypes.POINTER(ctypes.c_char_p),
+        ctypes.c_size_t,
+        ctypes.POINTER(VolutaOptions),
+        ctypes.POINTER(ctypes.c_void_p),
+    ]
+    lib.voluta_matcher_free.argtypes = [ctypes.c_void_p]
+    lib.voluta_matcher_pattern_count.restype = ctypes.c_size_t
+    lib.voluta_matcher_pattern_count.argtypes = [ctypes.c_void_p]
+    lib.voluta_matcher_pattern.restype = ctypes.c_char_p
+    lib.voluta_matcher_pattern.argtypes = [ctypes.c_void_p, ctypes.c_size_t]
+    lib.voluta_match_bytes.restype = ctypes.c_void_p
+    lib.voluta_match_bytes.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_size_t]
+    lib.voluta_match_file.restype = ctypes.c_void_p
+    lib.voluta_match_file.argtypes = [
+        ctypes.c_void_p,
+        ctypes.c_char_p,
+        ctypes.POINTER(ctypes.c_void_p),
+    ]
+    lib.voluta_matches_len.restype = ctypes.c_size_t
+    lib.voluta_matches_len.argtypes = [ctypes.c_void_p]
+    lib.voluta_matches_get.restype = ctypes.c_bool
+    lib.voluta_matches_get.argtypes = [
+        ctypes.c_void_p,
+        ctypes.c_size_t,
+        ctypes.POINTER(VolutaMatch),
+    ]
+    lib.voluta_matches_free.argtypes = [ctypes.c_void_p]
+    lib.voluta_string_free.argtypes = [ctypes.c_void_p]
+    return lib
+
+
+def new_matcher(capi, patterns, options=None):
+    """Build a matcher through the C interface, returning (matcher, error message)."""
+    array = (ctypes.c_char_p * len(patterns))(*(p.encode() for p in patterns))
+    error = ctypes.c_void_p()
+    options = ctypes.byref(options) if options is not None else None
+    matcher = capi.voluta_matcher_new(array, len(patterns), options, ctypes.byref(error))
+    if matcher:
+        return matcher, None
+    message = ctypes.cast(error, ctypes.c_char_p).value.decode()
+    capi.voluta_string_free(error)
+    return None, message
+
+
+def collect(capi, matcher, matches):
+    """Read and release a list of matches."""
+    result = []
+    mat = VolutaMatch()
+    for i in range(capi.voluta_matches_len(matches)):
+        assert capi.voluta_matches_get(matches, i, ctypes.byref(mat))
+        pattern = capi.voluta_matcher_pattern(matcher, mat.pattern).decode()
+        result.append((mat.start, mat.end, pattern))
+    assert not capi.voluta_matches_get(matches, len(result), ctypes.byref(mat))
+    capi.voluta_matches_free(matches)
+    return result
+
+
+def test_capi_match_bytes(capi):
+    """Test that scanning a buffer through the C interface matches match_bytes."""
+    patterns = ["error", "warning", "err"]
+    data = b"error: disk full, warning: ERR"
+    matcher, error = new_matcher(capi, patterns)
+    assert error is None
+
+    try:
+        assert capi.voluta_version().decode() == "0.3.0"
+        assert capi.voluta_matcher_pattern_count(matcher) == 3
+        assert capi.voluta_matcher_pattern(matcher, 3) is None
+
+        result = collect(capi, matcher, capi.voluta_match_bytes(matcher, data, len(data)))
+        assert result == voluta.TextMatcher(patterns).match_bytes(data)
+    finally:
+        capi.voluta_matcher_free(matcher)
+
+
+def test_capi_options(capi):
+    """Test that options passed through the C interface are honored."""
+    options = capi.voluta_options_default()
+    assert options.overlapping and options.case_insensitive and not options.whole_word
+
+    options.whole_word = True
+    options.postprocess = 2  # VolutaPostProcessLongest
+    matcher, _ = new_matcher(capi, ["new", "new york"], options)
+    data = b"new york renewal"
+
+    try:
+        result = collect(capi, matcher, capi.voluta_match_bytes(matcher, data, len(data)))
+        assert result == [(0, 8, "new york")]
+    finally:
+        capi.voluta_matcher_free(matcher)
+
+
+def test_capi_match_file(capi, tmp_path):
+    """Test that scanning a file through the C interface matches match_file_memmap."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"error: disk full\nwarning: cpu hot\nerror again\n")
+    matcher, _ = new_matcher(capi, ["error", "warning"])
+
+    try:
+        result = collect(capi, matcher, capi.voluta_match_file(matcher, str(path).encode(), None))
+        expected = voluta.TextMatcher(["error", "warning"]).match_file_memmap(str(path))
+        assert result == sorted(expected)
+
+        error = ctypes.c_void_p()
+        assert not capi.voluta_match_file(matcher, b"nonexistent_file.txt", ctypes.byref(error))
+        assert error.value
+        capi.voluta_string_free(error)
+    finally:
+        capi.voluta_matcher_free(matcher)
+
+
+def test_capi_errors(capi):
+    """Test that configuration errors are reported through the error out parameter."""
+    matcher, error = new_matcher(capi, [""])
+    assert matcher is None
+    assert error == "Pattern set cannot be empty"
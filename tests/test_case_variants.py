@@ -0,0 +1,53 @@
+import pytest
+from voluta import TextMatcher, hash_text
+
+
+def test_case_variants_bytes():
+    """Test that each form is counted as it appears in the data."""
+    matcher = TextMatcher(["password", "token"])
+    data = b"Password=1 password=2 PASSWORD=3 password=4 TOKEN"
+    assert matcher.case_variants(data) == {
+        "password": {"Password": 1, "password": 2, "PASSWORD": 1},
+        "token": {"TOKEN": 1},
+    }
+    assert matcher.case_variants(b"nothing here") == {}
+
+
+def test_case_variants_case_sensitive():
+    """Test that a case-sensitive matcher only reports the pattern's own form."""
+    matcher = TextMatcher(["Secret"], case_insensitive=False)
+    assert matcher.case_variants(b"Secret secret SECRET Secret") == {"Secret": {"Secret": 2}}
+
+
+def test_case_variants_duplicate_patterns():
+    """Test that a pattern listed twice adds up its counts."""
+    matcher = TextMatcher(["key", "key"], overlapping=True)
+    assert matcher.case_variants(b"Key key") == {"key": {"Key": 2, "key": 2}}
+
+
+def test_case_variants_file(tmp_path):
+    """Test counting in a file path, and errors for missing files and invalid input."""
+    path = tmp_path / "config.txt"
+    path.write_bytes(b"ApiKey=1\napikey=2\nAPIKEY=3\napikey=4\n")
+    matcher = TextMatcher(["apikey"])
+    assert matcher.case_variants(str(path)) == {"apikey": {"ApiKey": 1, "apikey": 2, "APIKEY": 1}}
+
+    with pytest.raises(IOError):
+        matcher.case_variants(str(tmp_path / "missing.txt"))
+
+    with pytest.raises(TypeError):
+        matcher.case_variants(42)
+
+
+def test_case_variants_hash_key():
+    """Test that hash-only matchers count the digests of the forms."""
+    key = b"k" * 32
+    matcher = TextMatcher(["secret"], hash_key=key)
+    result = matcher.case_variants(b"Secret secret SECRET secret")
+    assert list(result.values()) == [
+        {
+            hash_text(key, b"Secret"): 1,
+            hash_text(key, b"secret"): 2,
+            hash_text(key, b"SECRET"): 1,
+        }
+    ]
@@ -0,0 +1,79 @@
+import pytest
+from voluta import LineIndex, TextMatcher
+
+TEXT = "naïve café: secret 秘密 secret\n🔑 key=secret"
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher for a few patterns."""
+    return TextMatcher(["secret", "秘密", "key"])
+
+
+def expected(matcher, text):
+    """Character offsets computed in Python from the byte offsets of match_bytes."""
+    data = text.encode()
+    return [
+        (start, end, name, len(data[:start].decode()), len(data[:end].decode()))
+        for start, end, name in matcher.match_bytes(data)
+    ]
+
+
+def test_match_bytes_chars(matcher):
+    """Test that character offsets index the decoded text."""
+    found = matcher.match_bytes_chars(TEXT.encode())
+    assert found == expected(matcher, TEXT)
+    assert [TEXT[char_start:char_end] for _, _, _, char_start, char_end in found] == [
+        "secret",
+        "秘密",
+        "secret",
+        "key",
+        "secret",
+    ]
+    assert matcher.match_bytes_chars(b"") == []
+
+
+def test_ascii_offsets_are_equal(matcher):
+    """Test that character offsets equal byte offsets on ASCII input."""
+    for start, end, _, char_start, char_end in matcher.match_bytes_chars(b"a secret key"):
+        assert (start, end) == (char_start, char_end)
+
+
+def test_overlapping_matches():
+    """Test character offsets of overlapping and nested matches."""
+    matcher = TextMatcher(["éé", "ééé", "é"], overlapping=True)
+    text = "xéééx"
+    found = matcher.match_bytes_chars(text.encode())
+    assert sorted(found) == sorted(expected(matcher, text))
+    assert all(text[cs:ce] in ("é", "éé", "ééé") for _, _, _, cs, ce in found)
+
+
+def test_invalid_bytes(matcher):
+    """Test that invalid lead bytes count as one character, stray continuation bytes as none."""
+    assert matcher.match_bytes_chars(b"\xff\xfe key") == [(3, 6, "key", 3, 6)]
+    assert matcher.match_bytes_chars(b"\x80\x80key") == [(2, 5, "key", 0, 3)]
+
+
+def test_match_file_chars(matcher, tmp_path):
+    """Test that file scans on several threads report the offsets of match_bytes_chars."""
+    data = (TEXT + "\n").encode() * 5000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    found = matcher.match_file_chars(str(path), chunk_size=4096, n_threads=4)
+    assert found == sorted(matcher.match_bytes_chars(data))
+    assert len(found) == 25000
+    assert found == matcher.match_file_chars(str(path))
+    with pytest.raises(IOError):
+        matcher.match_file_chars(str(tmp_path / "missing.txt"))
+    with pytest.raises(ValueError, match="chunk_size"):
+        matcher.match_file_chars(str(path), chunk_size=0)
+
+
+def test_line_index_agrees(matcher):
+    """Test that character offsets agree with the character columns of a LineIndex."""
+    data = TEXT.encode()
+    index = LineIndex(data)
+    for start, _, _, char_start, _ in matcher.match_bytes_chars(data):
+        line, column = index.position(start, chars=True)
+        line_start = index.line_start(line)
+        assert char_start == len(data[:line_start].decode()) + column
@@ -1,4 +1,5 @@
 import os
+import random
 import pytest
 import tempfile
 import voluta
@@ -82,3 +83,62 @@ def test_chunk_boundary_handling(boundary_test_files):
         assert long_pattern in mmap_patterns, (
             f"Test {i + 1}: Memory-mapped should find the pattern {long_pattern}"
         )
+
+
+def write(tmp_path, data):
+    path = tmp_path / "data.bin"
+    path.write_bytes(data)
+    return str(path)
+
+
+def test_repeats_in_overlap_not_duplicated(tmp_path):
+    """Test that runs of a repeated pattern are matched as a single search would."""
+    data = b"a" * 101
+    path = write(tmp_path, data)
+    for overlapping in [False, True]:
+        matcher = voluta.TextMatcher(["aaa"], overlapping=overlapping)
+        expected = matcher.match_bytes(data)
+        assert len(expected) == (33 if not overlapping else 99)
+        for chunk_size in [1, 2, 3, 4, 10, 50]:
+            assert matcher.match_file_memmap(path, chunk_size) == expected
+            assert sorted(matcher.match_file_memmap_parallel(path, chunk_size, 4)) == sorted(expected)
+
+
+@pytest.mark.parametrize("overlapping", [False, True])
+@pytest.mark.parametrize("whole_word", [False, True])
+def test_every_chunk_size_matches_single_search(tmp_path, overlapping, whole_word):
+    """Test that no chunk size adds, drops or reorders matches, on random data."""
+    rng = random.Random(20261015)
+    data = bytes(rng.choice(b"ab c") for _ in range(600))
+    path = write(tmp_path, data)
+    matcher = voluta.TextMatcher(
+        ["ab", "aba", "bab", "b c", "cab", "abab"],
+        overlapping=overlapping,
+        whole_word=whole_word,
+    )
+    expected = matcher.match_bytes(data)
+    assert expected
+    for chunk_size in list(range(1, 12)) + [37, 128, 599, 600, 4096]:
+        mmap = matcher.match_file_memmap(path, chunk_size)
+        parallel = matcher.match_file_memmap_parallel(path, chunk_size, 4)
+        assert parallel == mmap
+        if overlapping:
+            assert sorted(mmap) == sorted(expected)
+        else:
+            assert mmap == expected
+
+
+def test_inactive_match_across_boundary(tmp_path):
+    """Test that a match filtered out by tag still hides the matches starting under it."""
+    data = b"xxxxsecretkey" * 20
+    path = write(tmp_path, data)
+    matcher = (
+        voluta.TextMatcherBuilder()
+        .add_patterns(["secretkey", "key"], tags=[["noise"], ["pci"]])
+        .overlapping(False)
+        .build()
+    )
+    assert matcher.match_bytes(data, tags=["pci"]) == []
+    for chunk_size in range(1, 15):
+        assert matcher.match_file_memmap(path, chunk_size, tags=["pci"]) == []
+        assert matcher.match_file_memmap_parallel(path, chunk_size, 2, tags=["pci"]) == []
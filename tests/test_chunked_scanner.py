@@ -0,0 +1,129 @@
+import asyncio
+import io
+import random
+
+import pytest
+from voluta import TextMatcher
+
+# Property tests of the offset model shared by the chunked scans: whatever the chunk or buffer
+# size, every chunked method finds exactly the matches of a single search of the whole input
+
+ALPHABET = b"ab c-"
+SEEDS = range(25)
+
+
+def random_case(seed):
+    """A random input, pattern set and options, reproducible from the seed."""
+    rng = random.Random(seed)
+    data = bytes(rng.choice(ALPHABET) for _ in range(rng.randint(0, 400)))
+    patterns = sorted(
+        {
+            "".join(rng.choice("ab c") for _ in range(rng.randint(1, 5))).strip() or "a"
+            for _ in range(rng.randint(1, 6))
+        }
+    )
+    options = {
+        "overlapping": rng.random() < 0.5,
+        "whole_word": rng.random() < 0.3,
+        "case_insensitive": rng.random() < 0.5,
+    }
+    if rng.random() < 0.3:
+        options["exclude"] = [rng.choice(["a-b", "c a", "bb"])]
+    sizes = sorted({rng.randint(1, 12) for _ in range(3)} | {rng.randint(13, 500), 1})
+    return data, patterns, options, sizes
+
+
+def same(actual, expected, overlapping):
+    """Without overlapping, matches come in the order of a single search."""
+    return actual == expected if not overlapping else sorted(actual) == sorted(expected)
+
+
+@pytest.mark.parametrize("seed", SEEDS)
+def test_stream_matches_single_search(seed):
+    """Test that match_stream finds the matches of match_bytes for any buffer size."""
+    data, patterns, options, sizes = random_case(seed)
+    matcher = TextMatcher(patterns, **options)
+    expected = matcher.match_bytes(data)
+    for size in sizes:
+        assert same(matcher.match_stream(data, size), expected, options["overlapping"]), size
+
+
+@pytest.mark.parametrize("seed", SEEDS)
+def test_file_scans_match_single_search(tmp_path, seed):
+    """Test that the file methods find the matches of match_bytes for any chunk size."""
+    data, patterns, options, sizes = random_case(seed)
+    path = tmp_path / "data.bin"
+    path.write_bytes(data)
+    matcher = TextMatcher(patterns, **options)
+    expected = matcher.match_bytes(data)
+    for size in sizes:
+        stream = matcher.match_file_stream(str(path), size)
+        mmap = matcher.match_file_memmap(str(path), size)
+        parallel = matcher.match_file_memmap_parallel(str(path), size, 3)
+        assert same(stream, expected, options["overlapping"]), size
+        assert same(mmap, expected, options["overlapping"]), size
+        assert parallel == mmap, size
+
+
+@pytest.mark.parametrize("seed", range(0, 25, 5))
+def test_readable_matches_single_search(seed):
+    """Test that readers returning short reads find the matches of match_bytes."""
+    data, patterns, options, sizes = random_case(seed)
+    matcher = TextMatcher(patterns, **options)
+    expected = matcher.match_bytes(data)
+
+    async def scan(size):
+        return await matcher.amatch_readable(io.BytesIO(data), size)
+
+    for size in sizes:
+        assert same(asyncio.run(scan(size)), expected, options["overlapping"]), size
+
+
+@pytest.mark.parametrize("seed", SEEDS)
+def test_readable_parallel_matches_single_search(seed):
+    """Test that parallel reader scans find the matches of match_bytes for any buffer size."""
+    data, patterns, options, sizes = random_case(seed)
+    matcher = TextMatcher(patterns, **options)
+    expected = matcher.match_bytes(data)
+    for size in sizes:
+        for n_threads in (1, 3):
+            found = matcher.match_readable_parallel(io.BytesIO(data), size, n_threads)
+            assert same(found, expected, options["overlapping"]), (size, n_threads)
+
+
+def test_readable_parallel_across_boundaries():
+    """Test that non-overlapping matches competing across a chunk boundary resolve as one search."""
+    data = b"abcd bcde abcd"
+    matcher = TextMatcher(["abcd", "bcde", "cd b"], overlapping=False)
+    expected = [(0, 4, "abcd"), (5, 9, "bcde"), (10, 14, "abcd")]
+    assert matcher.match_bytes(data) == expected
+    for size in range(1, len(data) + 1):
+        for n_threads in (1, 2, 4):
+            assert matcher.match_readable_parallel(io.BytesIO(data), size, n_threads) == expected, size
+
+
+def test_stream_offsets_with_small_buffers(tmp_path):
+    """Test that offsets stay absolute when buffers are shorter than the longest pattern."""
+    data = b"xx password yy password zz"
+    path = tmp_path / "data.bin"
+    path.write_bytes(data)
+    matcher = TextMatcher(["password", "yy"])
+    expected = [(3, 11, "password"), (12, 14, "yy"), (15, 23, "password")]
+    assert matcher.match_bytes(data) == expected
+    for size in range(1, 12):
+        assert matcher.match_stream(data, size) == expected
+        assert matcher.match_file_stream(str(path), size) == expected
+
+
+def test_stream_checks_see_neighbouring_bytes():
+    """Test that word boundaries and exclusion zones read the bytes of the next buffer."""
+    data = b"token tokens xtoken token"
+    matcher = TextMatcher(["token"], whole_word=True)
+    assert matcher.match_bytes(data) == [(0, 5, "token"), (20, 25, "token")]
+    for size in range(1, 8):
+        assert matcher.match_stream(data, size) == [(0, 5, "token"), (20, 25, "token")]
+
+    data = b"key KEY_EXAMPLE key"
+    matcher = TextMatcher(["key"], exclude=["key_example"])
+    for size in range(1, 8):
+        assert matcher.match_stream(data, size) == [(0, 3, "key"), (16, 19, "key")]
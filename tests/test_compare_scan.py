@@ -0,0 +1,57 @@
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+DATA = b"acme corp and initech, then acme again"
+
+
+def test_compare_bytes():
+    """Test that findings are split into those of A only, of B only and of both."""
+    production = TextMatcher(["acme", "initech"])
+    candidate = TextMatcher(["acme", "acme corp"])
+    result = production.compare_scan(candidate, DATA)
+    assert result.only_a == [(14, 21, "initech")]
+    assert result.only_b == [(0, 9, "acme corp")]
+    assert result.common == [(0, 4, "acme"), (28, 32, "acme")]
+
+
+def test_compare_options():
+    """Test that options of either matcher shape its findings."""
+    plain = TextMatcher(["acme"])
+    sensitive = TextMatcher(["acme"], case_insensitive=False)
+    result = plain.compare_scan(sensitive, b"ACME acme")
+    assert result.only_a == [(0, 4, "acme")]
+    assert result.only_b == []
+    assert result.common == [(5, 9, "acme")]
+
+
+def test_compare_file(tmp_path):
+    """Test that files scanned by both matchers give the findings of their bytes."""
+    path = tmp_path / "corpus.log"
+    path.write_bytes(DATA)
+    production = TextMatcher(["acme", "initech"])
+    candidate = TextMatcher(["initech", "then"])
+    from_file = production.compare_scan(candidate, str(path))
+    from_bytes = production.compare_scan(candidate, DATA)
+    assert (from_file.only_a, from_file.only_b, from_file.common) == (
+        from_bytes.only_a,
+        from_bytes.only_b,
+        from_bytes.common,
+    )
+    assert from_file.common == [(14, 21, "initech")]
+
+
+def test_compare_identical():
+    """Test that a matcher compared with itself only has common findings."""
+    matcher = TextMatcher(["acme", "corp"])
+    result = matcher.compare_scan(matcher, DATA)
+    assert (result.only_a, result.only_b) == ([], [])
+    assert len(result.common) == 3
+
+
+def test_compare_errors(tmp_path):
+    """Test that unreadable files and invalid data are rejected."""
+    matcher = TextMatcher(["acme"])
+    with pytest.raises(ScanIOError):
+        matcher.compare_scan(matcher, str(tmp_path / "missing.log"))
+    with pytest.raises(TypeError, match="data_or_path must be bytes or a path"):
+        matcher.compare_scan(matcher, 42)
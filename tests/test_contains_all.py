@@ -0,0 +1,57 @@
+import pytest
+from voluta import PatternError, TextMatcher
+
+MATCHER = TextMatcher(["falcon", "acquisition", "confidential", "lunch"])
+
+
+def test_all_present():
+    """Test that documents mentioning every pattern satisfy the query."""
+    doc = b"CONFIDENTIAL: the Falcon acquisition closes Friday"
+    assert MATCHER.contains_all(doc, ["falcon", "acquisition", "confidential"])
+    assert MATCHER.contains_all(doc, ["falcon"])
+
+
+def test_some_missing():
+    """Test that a single missing pattern fails the query."""
+    doc = b"the falcon acquisition closes friday"
+    assert not MATCHER.contains_all(doc, ["falcon", "acquisition", "confidential"])
+    assert not MATCHER.contains_all(b"", ["lunch"])
+
+
+def test_empty_and_repeated_patterns():
+    """Test that an empty list always matches and repeated patterns count once."""
+    assert MATCHER.contains_all(b"nothing here", [])
+    assert MATCHER.contains_all(b"lunch and falcon", ["lunch", "falcon", "lunch"])
+
+
+def test_unknown_pattern():
+    """Test that patterns the matcher does not have are rejected."""
+    with pytest.raises(PatternError, match="Unknown pattern 'eagle'"):
+        MATCHER.contains_all(b"falcon", ["falcon", "eagle"])
+
+
+def test_follows_match_bytes():
+    """Test that occurrences are those match_bytes reports."""
+    matcher = TextMatcher(["cat", "concat"], whole_word=True)
+    assert not matcher.contains_all(b"concatenate", ["cat"])
+    assert matcher.contains_all(b"concat cat", ["cat", "concat"])
+
+    # Non-overlapping scans report the match starting first
+    standard = TextMatcher(["abc", "bcd"], overlapping=False)
+    assert not standard.contains_all(b"abcd", ["abc", "bcd"])
+    overlapping = TextMatcher(["abc", "bcd"], overlapping=True)
+    assert overlapping.contains_all(b"abcd", ["abc", "bcd"])
+
+
+def test_postprocess():
+    """Test that matches dropped by post-processing do not count."""
+    matcher = TextMatcher(["new", "new york"], overlapping=True, postprocess="longest")
+    assert matcher.match_bytes(b"new york") == [(0, 8, "new york")]
+    assert not matcher.contains_all(b"new york", ["new", "new york"])
+    assert matcher.contains_all(b"new york, new jersey", ["new", "new york"])
+
+
+def test_early_exit_on_large_input():
+    """Test that the query is answered on inputs far larger than where the patterns occur."""
+    doc = b"falcon acquisition " + b"x" * 10_000_000
+    assert MATCHER.contains_all(doc, ["acquisition", "falcon"])
@@ -0,0 +1,120 @@
+import sqlite3
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+MATCHER = TextMatcher(["secret", "4111"], case_insensitive=False)
+
+
+@pytest.fixture
+def db(tmp_path):
+    path = tmp_path / "app.db"
+    conn = sqlite3.connect(path)
+    conn.executescript(
+        """
+        CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, note VARCHAR(200), age INTEGER);
+        CREATE TABLE cards (user_id INTEGER, seq INTEGER, number TEXT, raw BLOB,
+                            PRIMARY KEY (user_id, seq));
+        CREATE TABLE events (message TEXT);
+        """
+    )
+    conn.executemany(
+        "INSERT INTO users VALUES (?, ?, ?, ?)",
+        [(1, "alice", "no secret here, a secret", 30), (7, "bob", None, 4111), (9, "carol", "ok", 5)],
+    )
+    conn.executemany(
+        "INSERT INTO cards VALUES (?, ?, ?, ?)",
+        [(1, 1, "4111 1111", b"\x00secret\xff"), (1, 2, "5500", None)],
+    )
+    conn.execute("INSERT INTO events VALUES ('leaked secret')")
+    conn.commit()
+    yield path, conn
+    conn.close()
+
+
+def test_tables(db):
+    """Test that the text and blob columns of every table are reported with their keys."""
+    path, _ = db
+    assert MATCHER.match_db(str(path)) == [
+        ("cards", "number", (1, 1), 0, 4, "4111"),
+        ("cards", "raw", (1, 1), 1, 7, "secret"),
+        ("events", "message", 1, 7, 13, "secret"),
+        ("users", "note", 1, 3, 9, "secret"),
+        ("users", "note", 1, 18, 24, "secret"),
+    ]
+
+
+def test_connection_string(db):
+    """Test that sqlite:/// URLs are opened and other schemes rejected."""
+    path, _ = db
+    matches = MATCHER.match_db(f"sqlite:///{path}", tables=["users"])
+    assert [m[:3] for m in matches] == [("users", "note", 1)] * 2
+    with pytest.raises(ScanIOError, match="postgresql"):
+        MATCHER.match_db("postgresql://db.internal/app")
+
+
+def test_columns_and_key(db):
+    """Test that wanted columns are scanned whatever their type, under the given key."""
+    path, _ = db
+    matches = MATCHER.match_db(str(path), tables=["users"], columns=["age"], key=["name"])
+    # Integers are not scanned, only text and blob values
+    assert matches == []
+    matches = MATCHER.match_db(str(path), tables=["users"], columns=["note"], key=["name"])
+    assert {m[2] for m in matches} == {"alice"}
+
+
+def test_query(db):
+    """Test that the rows of a query are reported by row number or key columns."""
+    path, _ = db
+    query = "SELECT id, name || ' ' || coalesce(note, '') AS profile FROM users ORDER BY id"
+    assert MATCHER.match_db(str(path), query=query) == [
+        (None, "profile", 0, 9, 15, "secret"),
+        (None, "profile", 0, 24, 30, "secret"),
+    ]
+    assert {m[2] for m in MATCHER.match_db(str(path), query=query, key=["id"])} == {1}
+
+
+def test_dbapi(db):
+    """Test that DB-API connections and cursors are scanned like SQLite databases."""
+    path, conn = db
+    assert MATCHER.match_db(conn, tables=["cards", "events"]) == [
+        ("cards", "number", 0, 0, 4, "4111"),
+        ("cards", "raw", 0, 1, 7, "secret"),
+        ("events", "message", 0, 7, 13, "secret"),
+    ]
+    assert MATCHER.match_db(conn, tables=["cards"], key=["user_id", "seq"])[0][2] == (1, 1)
+
+    cursor = conn.execute("SELECT id, note FROM users ORDER BY id")
+    assert MATCHER.match_db(cursor, key=["id"], batch_size=1) == [
+        (None, "note", 1, 3, 9, "secret"),
+        (None, "note", 1, 18, 24, "secret"),
+    ]
+
+
+def test_dbapi_same_as_sqlite(db):
+    """Test that a query finds the same matches through both paths."""
+    path, conn = db
+    query = "SELECT * FROM cards"
+    assert MATCHER.match_db(conn, query=query, key=["user_id", "seq"]) == MATCHER.match_db(
+        str(path), query=query, key=["user_id", "seq"]
+    )
+
+
+def test_errors(db, tmp_path):
+    """Test that unknown tables, key columns and missing results are reported."""
+    path, conn = db
+    with pytest.raises(ScanIOError, match="No table 'missing'"):
+        MATCHER.match_db(str(path), tables=["missing"])
+    with pytest.raises(ScanIOError, match="Key column 'uid'"):
+        MATCHER.match_db(str(path), tables=["users"], key=["uid"])
+    with pytest.raises(ValueError, match="combined"):
+        MATCHER.match_db(str(path), tables=["users"], query="SELECT 1")
+    with pytest.raises(ValueError, match="query or tables"):
+        MATCHER.match_db(conn)
+    with pytest.raises(ValueError, match="Key column 'uid'"):
+        MATCHER.match_db(conn, query="SELECT * FROM users", key=["uid"])
+    with pytest.raises(ScanIOError):
+        MATCHER.match_db(str(tmp_path / "missing.db"))
+    (tmp_path / "text.db").write_text("not a database, " * 100)
+    with pytest.raises(ScanIOError):
+        MATCHER.match_db(str(tmp_path / "text.db"))
@@ -0,0 +1,58 @@
+import pytest
+from voluta import TextMatcher
+
+
+def test_dedup_whole_input():
+    """Test that repeats of a finding anywhere in the data are folded into the first."""
+    matcher = TextMatcher(["AKIA1234", "secret"])
+    data = b"key AKIA1234\n" * 1000 + b"secret\nkey AKIA1234\n"
+
+    findings = matcher.match_dedup(data)
+    assert findings == [(4, 12, "AKIA1234", 1001), (13000, 13006, "secret", 1)]
+
+
+def test_dedup_distinct_text():
+    """Test that findings of one pattern with different matched text are kept apart."""
+    matcher = TextMatcher(["token"], case_insensitive=True)
+    data = b"token TOKEN token Token"
+
+    findings = matcher.match_dedup(data)
+    assert findings == [
+        (0, 5, "token", 2),
+        (6, 11, "token", 1),
+        (18, 23, "token", 1),
+    ]
+
+
+def test_dedup_sliding_window():
+    """Test that the window slides with every repeat and restarts after a gap."""
+    matcher = TextMatcher(["key"])
+    # Repeats every 10 bytes, then a gap of 100 bytes
+    data = b"key......." * 5 + b"." * 100 + b"key"
+
+    findings = matcher.match_dedup(data, window=20)
+    assert findings == [(0, 3, "key", 5), (150, 153, "key", 1)]
+    separate = [(i * 10, i * 10 + 3, "key", 1) for i in range(5)] + [(150, 153, "key", 1)]
+    assert matcher.match_dedup(data, window=5) == separate
+
+
+def test_dedup_lines():
+    """Test windows measured in lines."""
+    matcher = TextMatcher(["key"])
+    lines = [b"key", b"", b"key", b"", b"", b"", b"key"]
+    data = b"\n".join(lines)
+
+    findings = matcher.match_dedup(data, window=2, unit="lines")
+    assert findings == [(0, 3, "key", 2), (12, 15, "key", 1)]
+    assert matcher.match_dedup(data, window=4, unit="lines") == [(0, 3, "key", 3)]
+
+
+def test_dedup_file(tmp_path):
+    """Test deduplicating the matches of a file, and invalid units."""
+    matcher = TextMatcher(["password"])
+    path = tmp_path / "app.log"
+    path.write_bytes(b"password=hunter2\n" * 100)
+
+    assert matcher.match_file_dedup(str(path)) == [(0, 8, "password", 100)]
+    with pytest.raises(ValueError, match="window unit"):
+        matcher.match_file_dedup(str(path), window=1, unit="pages")
@@ -0,0 +1,165 @@
+import os
+import tempfile
+
+import pytest
+from voluta import PatternError, TextMatcher, TextMatcherBuilder
+
+
+def test_density_bytes_window():
+    """Test that only dense clusters of matches are reported."""
+    matcher = TextMatcher(["card"])
+
+    sparse = b"card" + b"." * 100 + b"card" + b"." * 100 + b"card"
+    dense = b"card card card"
+    data = sparse + b"." * 100 + dense
+
+    regions = matcher.match_density(data, 3, 20)
+    assert len(regions) == 1
+
+    start, end, count = regions[0]
+    assert count == 3
+    assert data[start:end] == dense
+
+
+def test_density_merges_overlapping_windows():
+    """Test that overlapping dense windows are merged into one region."""
+    matcher = TextMatcher(["x"])
+
+    # 10 matches spaced 5 bytes apart, any 3 consecutive fit in a 15 byte window
+    data = b"x    " * 10
+    regions = matcher.match_density(data, 3, 15)
+    assert regions == [(0, 46, 10)]
+
+    # No window of 6 bytes holds 3 matches
+    assert matcher.match_density(data, 3, 6) == []
+
+
+def test_density_separate_regions():
+    """Test that distant clusters are reported separately."""
+    matcher = TextMatcher(["key"])
+
+    data = b"key key key" + b" " * 500 + b"key key key"
+    regions = matcher.match_density(data, 3, 50)
+    assert len(regions) == 2
+    assert all(count == 3 for _, _, count in regions)
+    assert regions[0][1] < regions[1][0]
+
+
+def test_density_lines_window():
+    """Test that windows can be measured in lines."""
+    matcher = TextMatcher(["error"])
+
+    data = b"error\nerror error\nok\nok\nok\nerror\n"
+
+    # 3 errors within 2 consecutive lines
+    regions = matcher.match_density(data, 3, 2, unit="lines")
+    assert regions == [(0, 17, 3)]
+
+    # The last error is 5 lines below the first one
+    assert matcher.match_density(data, 4, 5, unit="lines") == []
+    assert matcher.match_density(data, 4, 6, unit="lines") == [(0, 32, 4)]
+
+
+def test_density_pattern_filter():
+    """Test that only the selected patterns are counted."""
+    matcher = TextMatcher(["card", "name"])
+
+    data = b"card name name card"
+    assert matcher.match_density(data, 3, 100) == [(0, 19, 4)]
+    assert matcher.match_density(data, 3, 100, patterns=["card"]) == []
+    assert matcher.match_density(data, 2, 100, patterns=["card"]) == [(0, 19, 2)]
+
+
+def test_density_tag_filter(tmp_path):
+    """Test that only the patterns with one of the given tags are counted."""
+    tags = [["pci"], ["pci", "banking"], ["pii"]]
+    matcher = TextMatcherBuilder().add_patterns(["card", "iban", "name"], tags=tags).build()
+
+    data = b"card name iban name card"
+    assert matcher.match_density(data, 5, 100) == [(0, 24, 5)]
+    assert matcher.match_density(data, 3, 100, tags=["pci"]) == [(0, 24, 3)]
+    assert matcher.match_density(data, 2, 100, tags=["banking"]) == []
+    assert matcher.match_density(data, 2, 100, tags=["pii"], evidence=True) == [
+        (5, 19, 2, [(5, 9, "name"), (15, 19, "name")])
+    ]
+    path = tmp_path / "data.txt"
+    path.write_bytes(data)
+    assert matcher.match_file_density(str(path), 3, 100, tags=["pci"]) == [(0, 24, 3)]
+
+    with pytest.raises(PatternError, match="Unknown tag 'phi'"):
+        matcher.match_density(data, 1, 100, tags=["phi"])
+
+
+def test_density_whole_word():
+    """Test that density rules honor whole word matching."""
+    matcher = TextMatcher(["id"], whole_word=True)
+    assert matcher.match_density(b"id idea ids id", 2, 100) == [(0, 14, 2)]
+
+
+def test_density_invalid_arguments():
+    """Test that unknown units and patterns are rejected."""
+    matcher = TextMatcher(["card"])
+
+    with pytest.raises(ValueError) as excinfo:
+        matcher.match_density(b"card", 1, 10, unit="words")
+    assert "Unknown window unit" in str(excinfo.value)
+
+    with pytest.raises(ValueError) as excinfo:
+        matcher.match_density(b"card", 1, 10, patterns=["name"])
+    assert "Unknown pattern" in str(excinfo.value)
+
+
+def test_file_density_matches_bytes():
+    """Test that the file variant agrees with the bytes variant."""
+    matcher = TextMatcher(["fox", "dog"])
+    content = (("fox " * 5) + ("." * 200) + "\n") * 50 + ("dog\n" * 10)
+
+    with tempfile.NamedTemporaryFile(mode="w", delete=False) as f:
+        f.write(content)
+        temp_path = f.name
+
+    try:
+        for unit, window in [("bytes", 64), ("lines", 3)]:
+            expected = matcher.match_density(content.encode(), 5, window, unit=unit)
+            assert expected
+            assert matcher.match_file_density(temp_path, 5, window, unit=unit) == expected
+    finally:
+        os.unlink(temp_path)
+
+    with pytest.raises(IOError):
+        matcher.match_file_density("/nonexistent/file.txt", 5, 10)
+
+
+def test_density_evidence():
+    """Test that regions list the matches they counted with evidence=True."""
+    matcher = TextMatcher(["card", "name"])
+
+    data = b"card name" + b"." * 500 + b"x    " + b"card"
+    regions = matcher.match_density(data, 2, 100, evidence=True)
+    assert regions == [(0, 9, 2, [(0, 4, "card"), (5, 9, "name")])]
+
+    # Merged windows keep every match counted, and the pattern filter applies
+    data = b"x    " * 10
+    regions = TextMatcher(["x"]).match_density(data, 3, 15, evidence=True)
+    assert len(regions) == 1
+    start, end, count, matches = regions[0]
+    assert (start, end, count) == (0, 46, 10)
+    assert matches == [(i, i + 1, "x") for i in range(0, 50, 5)]
+
+    data = b"card name name card"
+    regions = matcher.match_density(data, 2, 100, patterns=["card"], evidence=True)
+    assert regions == [(0, 19, 2, [(0, 4, "card"), (15, 19, "card")])]
+
+
+def test_file_density_evidence(tmp_path):
+    """Test that the file variant keeps the same evidence as the bytes variant."""
+    matcher = TextMatcher(["fox", "dog"])
+    content = (b"fox dog " + b"." * 200 + b"\n") * 20
+    path = tmp_path / "animals.txt"
+    path.write_bytes(content)
+    for unit, window in [("bytes", 16), ("lines", 2)]:
+        expected = matcher.match_density(content, 2, window, unit=unit, evidence=True)
+        assert expected
+        assert all(len(region[3]) == region[2] for region in expected)
+        assert matcher.match_file_density(str(path), 2, window, unit=unit, evidence=True) == expected
+        assert [region[:3] for region in expected] == matcher.match_density(content, 2, window, unit=unit)
@@ -0,0 +1,109 @@
+import shutil
+import subprocess
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+DIFF = b"""diff --git a/app.py b/app.py
+index 83db48f..bf269f4 100644
+--- a/app.py
++++ b/app.py
+@@ -1,4 +1,5 @@
+ import os
+-TOKEN = "old token"
++TOKEN = "new token"
++++token = 1
+ 
+ def main():
+@@ -10,2 +11,2 @@ def main():
+     print("token")
+-    return
++    return "token"
+\\ No newline at end of file
+diff --git a/removed.txt b/removed.txt
+deleted file mode 100644
+--- a/removed.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-token
+diff --git a/only_deletions.txt b/only_deletions.txt
+--- a/only_deletions.txt
++++ b/only_deletions.txt
+@@ -3,1 +2,0 @@
+-token
+diff --git "a/caf\\303\\251 token.txt" "b/caf\\303\\251 token.txt"
+new file mode 100644
+--- /dev/null
++++ "b/caf\\303\\251 token.txt"
+@@ -0,0 +1,2 @@
++first
++a token
+"""
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["token"])
+
+
+def test_added_lines(matcher):
+    """Test that only added lines are scanned, at their line in the new version."""
+    assert matcher.match_diff(DIFF) == [
+        ("app.py", 2, 0, 5, "token"),
+        ("app.py", 2, 13, 18, "token"),
+        ("app.py", 3, 2, 7, "token"),
+        ("app.py", 12, 12, 17, "token"),
+        ("café token.txt", 2, 2, 7, "token"),
+    ]
+
+
+def test_plain_diff(matcher):
+    """Test that diff -u output with timestamps is parsed too."""
+    diff = (
+        b"--- old/a.txt\t2024-01-01 00:00:00\n"
+        b"+++ new/a.txt\t2024-01-02 00:00:00\n"
+        b"@@ -1 +1 @@\n-x\n+a token\n"
+    )
+    assert matcher.match_diff(diff) == [("new/a.txt", 1, 2, 7, "token")]
+
+
+def test_empty(matcher):
+    """Test that a diff without additions has no matches."""
+    assert matcher.match_diff(b"") == []
+    assert matcher.match_diff(b"not a diff\n+token\n") == []
+
+
+@pytest.fixture
+def repo(tmp_path):
+    """Fixture that provides a repository with a committed file."""
+    if shutil.which("git") is None:
+        pytest.skip("git is not installed")
+
+    def git(*args):
+        subprocess.run(["git", "-C", str(tmp_path), *args], check=True, capture_output=True)
+
+    git("init", "-q")
+    git("config", "user.email", "dev@example.com")
+    git("config", "user.name", "Dev")
+    (tmp_path / "a.txt").write_text("token\nkeep\n")
+    git("add", "-A")
+    git("commit", "-q", "-m", "init")
+    return tmp_path, git
+
+
+def test_staged(matcher, repo):
+    """Test that only staged additions are scanned."""
+    path, git = repo
+    (path / "a.txt").write_text("keep\nnew token\n")
+    (path / "b.txt").write_text("unstaged token\n")
+    git("add", "a.txt")
+    assert matcher.match_staged(str(path)) == [("a.txt", 2, 4, 9, "token")]
+
+
+def test_staged_not_a_repo(matcher, tmp_path):
+    """Test that scanning the staged changes of a plain directory raises ScanIOError."""
+    if shutil.which("git") is None:
+        pytest.skip("git is not installed")
+    with pytest.raises(ScanIOError, match="not a git repository"):
+        matcher.match_staged(str(tmp_path))
@@ -0,0 +1,66 @@
+import json
+
+from voluta import TextMatcher
+
+
+def test_identical_matchers():
+    """Test that matchers with the same patterns and options do not differ."""
+    old = TextMatcher(["acme", "initech"])
+    diff = old.diff_patterns(TextMatcher(["acme", "initech"], engine="aho", madvise="sequential"))
+    assert not diff
+    assert (diff.added, diff.removed, diff.changed, diff.options) == ([], [], [], [])
+
+
+def test_added_and_removed():
+    """Test that added and removed patterns are listed in the order of their matcher."""
+    old = TextMatcher(["acme", "initech", "globex", "acme"])
+    new = TextMatcher(["umbrella", "globex", "acme", "hooli"])
+    diff = old.diff_patterns(new)
+    assert diff
+    assert diff.added == ["umbrella", "hooli"]
+    assert diff.removed == ["initech"]
+    assert diff.changed == []
+    assert new.diff_patterns(old).added == ["initech"]
+
+
+def test_changed_patterns():
+    """Test that fuzzy distances and priorities are compared per pattern."""
+    old = TextMatcher(["acme", "globex"], priorities=[1, 2], fuzzy=[0, 1])
+    new = TextMatcher(["globex", "acme"], priorities=[2, 5], fuzzy=[2, 0])
+    assert old.diff_patterns(new).changed == [("acme", "priority", 1, 5), ("globex", "fuzzy", 1, 2)]
+
+    # Without postprocess="priority", priorities follow the order and are not compared
+    reordered = TextMatcher(["globex", "acme"]).diff_patterns(TextMatcher(["acme", "globex"]))
+    assert not reordered
+
+
+def test_changed_options():
+    """Test that options changing the matches are compared."""
+    old = TextMatcher(["acme"])
+    new = TextMatcher(["acme"], whole_word=True, postprocess="longest", exclude=["acme inc"])
+    assert old.diff_patterns(new).options == [
+        ("whole_word", False, True),
+        ("postprocess", None, "longest"),
+        ("exclude", [], ["acme inc"]),
+    ]
+
+
+def test_hashed_patterns():
+    """Test that the patterns of hashed matchers are compared by digest."""
+    old = TextMatcher(["acme"], hash_key=b"key")
+    new = TextMatcher(["acme", "globex"], hash_key=b"key")
+    diff = old.diff_patterns(new)
+    assert len(diff.added) == 1
+    assert diff.added[0] != "globex"
+    assert diff.removed == []
+
+
+def test_to_json():
+    """Test the JSON form of a diff."""
+    diff = TextMatcher(["acme"], fuzzy=1).diff_patterns(TextMatcher(["acme", "hooli"], overlapping=False))
+    assert json.loads(diff.to_json()) == {
+        "added": ["hooli"],
+        "removed": [],
+        "changed": [{"pattern": "acme", "field": "fuzzy", "old": 1, "new": 0}],
+        "options": [{"option": "overlapping", "old": True, "new": False}],
+    }
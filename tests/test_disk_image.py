@@ -0,0 +1,134 @@
+import struct
+import zlib
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+SECTOR = 512
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["password", "BEGIN RSA PRIVATE KEY"])
+
+
+@pytest.fixture
+def image():
+    """Fixture that provides the media of a small disk, secrets at known offsets."""
+    media = bytearray(64 * SECTOR)
+    media[3 * SECTOR + 10 : 3 * SECTOR + 18] = b"password"
+    # Spans sectors 16 and 17, and the chunks of 8KB scans
+    key = b"BEGIN RSA PRIVATE KEY"
+    media[16 * SECTOR - 5 : 16 * SECTOR - 5 + len(key)] = key
+    media[63 * SECTOR + 500 : 63 * SECTOR + 508] = b"password"
+    return bytes(media)
+
+
+EXPECTED = [
+    (3, 3 * SECTOR + 10, 3 * SECTOR + 18, "password"),
+    (15, 16 * SECTOR - 5, 16 * SECTOR + 16, "BEGIN RSA PRIVATE KEY"),
+    (63, 63 * SECTOR + 500, 63 * SECTOR + 508, "password"),
+]
+
+
+def test_raw_image(matcher, image, tmp_path):
+    """Test that matches of a raw image are addressed by sector."""
+    path = tmp_path / "disk.dd"
+    path.write_bytes(image)
+    assert matcher.match_disk_image(str(path)) == EXPECTED
+
+
+@pytest.mark.parametrize("chunk_size", [1, 1000, 8192])
+def test_chunk_boundaries(matcher, image, tmp_path, chunk_size):
+    """Test that matches spanning chunks are found once whatever the chunk size."""
+    path = tmp_path / "disk.dd"
+    path.write_bytes(image)
+    assert matcher.match_disk_image(str(path), chunk_size=chunk_size, n_threads=3) == EXPECTED
+
+
+def test_sector_size(matcher, image, tmp_path):
+    """Test that sectors are counted in the given sector size."""
+    path = tmp_path / "disk.dd"
+    path.write_bytes(image)
+    assert [m[0] for m in matcher.match_disk_image(str(path), sector_size=4096)] == [0, 1, 7]
+
+
+def test_invalid_arguments(matcher, tmp_path):
+    """Test that sizes must be positive and missing images raise ScanIOError."""
+    path = tmp_path / "disk.dd"
+    path.write_bytes(b"password")
+    for name in ["sector_size", "chunk_size", "n_threads"]:
+        with pytest.raises(ValueError, match=name):
+            matcher.match_disk_image(str(path), **{name: 0})
+    with pytest.raises(ScanIOError):
+        matcher.match_disk_image(str(tmp_path / "missing.dd"))
+
+
+def section(kind, offset, data, last=False):
+    """A section of an E01 segment file at the given offset."""
+    size = 76 + len(data)
+    following = offset if last else offset + size
+    return kind.ljust(16, b"\x00") + struct.pack("<QQ", following, size) + bytes(44) + data
+
+
+def segment(number, chunks, volume=None, last=True):
+    """An E01 segment file holding the given (data, compressed) chunks."""
+    out = b"EVF\x09\x0d\x0a\xff\x00\x01" + struct.pack("<H", number) + b"\x00\x00"
+    if volume:
+        out += section(b"volume", len(out), volume)
+    stored, offsets = b"", []
+    start = len(out) + 76
+    for data, compressed in chunks:
+        offsets.append((start + len(stored)) | (1 << 31 if compressed else 0))
+        stored += zlib.compress(data) if compressed else data + struct.pack("<I", zlib.adler32(data))
+    out += section(b"sectors", len(out), stored)
+    table = struct.pack("<IIQII", len(offsets), 0, 0, 0, 0) + struct.pack(f"<{len(offsets)}I", *offsets)
+    out += section(b"table", len(out), table + bytes(4))
+    return out + section(b"done" if last else b"next", len(out), b"", last=True)
+
+
+def e01(image, tmp_path, chunk_sectors=4):
+    """Write the image as a two-segment E01 image, returning the path of its first segment."""
+    chunk = chunk_sectors * SECTOR
+    chunks = [(image[i : i + chunk], i // chunk % 2 == 0) for i in range(0, len(image), chunk)]
+    sectors = len(image) // SECTOR
+    volume = struct.pack("<B3xIIIQ", 1, len(chunks), chunk_sectors, SECTOR, sectors).ljust(1052, b"\x00")
+    half = len(chunks) // 2
+    (tmp_path / "disk.E01").write_bytes(segment(1, chunks[:half], volume, last=False))
+    (tmp_path / "disk.E02").write_bytes(segment(2, chunks[half:]))
+    return tmp_path / "disk.E01"
+
+
+def ewf_available(matcher, tmp_path):
+    """Check if voluta was built with the ewf feature."""
+    path = tmp_path / "probe.E01"
+    path.write_bytes(segment(1, [(bytes(SECTOR), False)], struct.pack("<B3xIIIQ", 1, 1, 1, SECTOR, 1)))
+    try:
+        matcher.match_disk_image(str(path))
+        return True
+    except ScanIOError as e:
+        assert "ewf feature" in str(e)
+        return False
+
+
+def test_e01_image(matcher, image, tmp_path):
+    """Test that E01 images are read across segments and compressed chunks."""
+    if not ewf_available(matcher, tmp_path):
+        pytest.skip("voluta was built without the ewf feature")
+    path = e01(image, tmp_path)
+    assert matcher.match_disk_image(str(path)) == EXPECTED
+    assert matcher.match_disk_image(str(path), chunk_size=1000) == EXPECTED
+
+
+def test_e01_corrupt(matcher, image, tmp_path):
+    """Test that missing segments and truncated chunks are reported."""
+    if not ewf_available(matcher, tmp_path):
+        pytest.skip("voluta was built without the ewf feature")
+    path = e01(image, tmp_path)
+    (tmp_path / "disk.E02").unlink()
+    with pytest.raises(ScanIOError, match="disk.E02"):
+        matcher.match_disk_image(str(path))
+    path.write_bytes(path.read_bytes()[:13])
+    with pytest.raises(ScanIOError, match="Corrupt E01 image"):
+        matcher.match_disk_image(str(path))
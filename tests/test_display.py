@@ -0,0 +1,92 @@
+import os
+import tempfile
+
+from voluta import TextMatcher, WebhookSink
+
+
+def test_matcher_repr():
+    """Test the repr, length and patterns of a matcher."""
+    matcher = TextMatcher(["error", "", "warning"], whole_word=True, postprocess="longest")
+    assert len(matcher) == 2
+    assert matcher.patterns == ["error", "warning"]
+    assert repr(matcher) == (
+        "TextMatcher(patterns=2, overlapping=True, case_insensitive=True, "
+        "whole_word=True, postprocess='longest')"
+    )
+    assert "postprocess=None" in repr(TextMatcher(["error"]))
+
+
+def test_collection_repr():
+    """Test that the repr of a collection previews its first matches."""
+    matcher = TextMatcher(["fox", "dog"])
+    collection = matcher.match_bytes_collection(b"the fox and the dog")
+    assert repr(collection) == "MatchCollection([(4, 7, 'fox'), (16, 19, 'dog')], len=2)"
+
+    many = matcher.match_bytes_collection(b"fox " * 50)
+    assert repr(many).endswith("(76, 79, 'fox'), ...], len=50)")
+
+
+def test_collection_repr_html():
+    """Test the notebook table of a collection."""
+    matcher = TextMatcher(["<b>", "fox"])
+    html = matcher.match_bytes_collection(b"<b>fox</b> " * 30)._repr_html_()
+    assert html.startswith('<table class="voluta-matches">')
+    assert "MatchCollection of 60 matches" in html
+    assert "<td>&lt;b&gt;</td>" in html
+    assert html.count("<tr><td>") == 20
+    assert "… 40 more" in html
+
+
+def test_collection_to_html():
+    """Test highlighting matches in the scanned data."""
+    data = b"the quick fox & the <dog>"
+    matcher = TextMatcher(["fox", "dog"])
+    html = matcher.match_bytes_collection(data).to_html(data)
+    assert html == (
+        '<pre class="voluta-highlight">the quick <mark title="fox">fox</mark> &amp; the '
+        '&lt;<mark title="dog">dog</mark>&gt;</pre>'
+    )
+
+
+def test_collection_to_html_overlapping():
+    """Test that overlapping matches are highlighted as one region listing each pattern."""
+    data = b"new york city"
+    matcher = TextMatcher(["new york", "york city"])
+    html = matcher.match_bytes_collection(data).to_html(data)
+    assert html == (
+        '<pre class="voluta-highlight"><mark title="new york, york city">new york city</mark></pre>'
+    )
+
+
+def test_collection_to_html_context():
+    """Test that only the context around matches is kept when requested."""
+    data = b"fox" + b"." * 100 + b"dog" + b"." * 100 + b"fox cat"
+    matcher = TextMatcher(["fox", "dog", "cat"])
+    html = matcher.match_bytes_collection(data).to_html(data, context=2)
+    assert html == (
+        '<pre class="voluta-highlight"><mark title="fox">fox</mark>..'
+        '<span class="voluta-gap">\n…\n</span>..<mark title="dog">dog</mark>..'
+        '<span class="voluta-gap">\n…\n</span>..<mark title="fox">fox</mark> '
+        '<mark title="cat">cat</mark></pre>'
+    )
+
+
+def test_sink_reprs():
+    """Test the reprs of sinks and sink summaries."""
+    webhook = WebhookSink(
+        "https://example.com/hook", headers=[("Authorization", "Bearer secret")], batch_size=10
+    )
+    assert repr(webhook) == (
+        "WebhookSink(url='https://example.com/hook', headers=['Authorization'], "
+        "batch_size=10, max_retries=3, timeout=30.0, queue_size=4)"
+    )
+    assert "secret" not in repr(webhook)
+
+    matcher = TextMatcher(["error"])
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        sink_path = os.path.join(tmp_dir, "findings.ndjson")
+        summary = matcher.match_bytes_to_sink(b"error error", sink_path, scan_id="scan-1")
+        assert repr(summary) == (
+            f"SinkSummary(sink={sink_path!r}, format='ndjson', scan_id='scan-1', "
+            "matches_written=2, bytes_scanned=11)"
+        )
@@ -0,0 +1,135 @@
+import asyncio
+import io
+
+import pytest
+from voluta import TextMatcher
+
+PATTERNS = ["café", "naïve", "€5", "Straße"]
+TEXT = "un café naïve à €5, Straße"
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher for patterns with non-ASCII characters."""
+    return TextMatcher(PATTERNS)
+
+
+def raw_spans(text, encoding, word):
+    """Byte span of the first occurrence of word in text encoded with encoding."""
+    start = len(text[: text.index(word)].encode(encoding))
+    return (start, start + len(word.encode(encoding)))
+
+
+def test_default_is_utf8(matcher):
+    """Test that legacy bytes do not match by default."""
+    assert matcher.encoding == "utf-8"
+    assert matcher.match_bytes(TEXT.encode("cp1252")) == []
+    assert len(matcher.match_bytes(TEXT.encode())) == 4
+
+
+def test_windows_1252(matcher):
+    """Test that Windows-1252 text matches, offsets pointing into the raw bytes."""
+    data = TEXT.encode("cp1252")
+    found = matcher.match_bytes(data, encoding="windows-1252")
+    assert found == [(*raw_spans(TEXT, "cp1252", p), p) for p in PATTERNS]
+    assert [data[start:end].decode("cp1252") for start, end, _ in found] == PATTERNS
+
+
+def test_latin_1(matcher):
+    """Test that Latin-1 decodes the C1 bytes as controls, so the euro sign does not match."""
+    text = TEXT.replace("€", "¤")
+    data = text.encode("latin-1")
+    found = matcher.match_bytes(data, encoding="latin-1")
+    assert [name for _, _, name in found] == ["café", "naïve", "Straße"]
+    assert matcher.match_bytes(TEXT.encode("cp1252"), encoding="iso-8859-1") == [
+        (*raw_spans(TEXT, "cp1252", p), p) for p in ["café", "naïve", "Straße"]
+    ]
+
+
+def test_auto(matcher):
+    """Test that auto keeps UTF-8 input as it is and decodes the rest as Windows-1252."""
+    assert matcher.match_bytes(TEXT.encode(), encoding="auto") == matcher.match_bytes(TEXT.encode())
+    data = TEXT.encode("cp1252")
+    assert matcher.match_bytes(data, encoding="auto") == matcher.match_bytes(data, encoding="cp1252")
+
+
+def test_matcher_default_and_overrides(matcher):
+    """Test that the matcher encoding is the default of every call and can be overridden."""
+    legacy = TextMatcher(PATTERNS, encoding="cp1252", whole_word=True)
+    assert legacy.encoding == "windows-1252"
+    data = TEXT.encode("cp1252")
+    assert legacy.match_bytes(data) == matcher.match_bytes(data, encoding="windows-1252")
+    assert legacy.match_bytes(data, encoding="utf-8") == []
+    assert legacy.match_bytes("cafés".encode("cp1252")) == []
+
+
+def test_line_scan(matcher, tmp_path):
+    """Test that match_file decodes each line with auto, ignoring the invalid_utf8 policy."""
+    path = tmp_path / "mixed.log"
+    path.write_bytes("café utf-8\n".encode() + "café legacy\n".encode("cp1252"))
+    with pytest.raises(IOError):
+        matcher.match_file(str(path))
+    assert matcher.match_file(str(path), encoding="auto") == [(1, 0, 5, "café"), (2, 0, 4, "café")]
+
+    async def scan():
+        return await matcher.amatch_file(str(path), encoding="auto")
+
+    assert asyncio.run(scan()) == matcher.match_file(str(path), encoding="auto")
+
+
+def test_chunked_scans(matcher, tmp_path):
+    """Test that chunked, parallel and streamed scans find the matches of match_bytes."""
+    data = (TEXT + "\n").encode("cp1252") * 5000
+    path = tmp_path / "legacy.txt"
+    path.write_bytes(data)
+    expected = matcher.match_bytes(data, encoding="windows-1252")
+    assert len(expected) == 20000
+
+    mapped = matcher.match_file_memmap(str(path), chunk_size=4096, encoding="windows-1252")
+    assert sorted(mapped) == expected
+    parallel = matcher.match_file_memmap_parallel(
+        str(path), chunk_size=4096, n_threads=4, encoding="auto"
+    )
+    assert sorted(parallel) == expected
+    streamed = matcher.match_stream(data, buffer_size=4096, encoding="windows-1252")
+    assert sorted(streamed) == expected
+    readable = matcher.match_readable_parallel(
+        io.BytesIO(data), buffer_size=4096, encoding="windows-1252"
+    )
+    assert sorted(readable) == expected
+
+
+@pytest.mark.parametrize(
+    "data",
+    [
+        # Windows-1252 byte first, UTF-8 text after it: Windows-1252 for the whole input
+        b"\xe9" + ("x" * 40 + " café naïve").encode() * 3,
+        # UTF-8 first: UTF-8 for the whole input, stray bytes later on included
+        ("café " * 10).encode() + b"caf\xe9 " * 10,
+        # A stray byte past the detected prefix does not change the encoding
+        ("café ".encode() * 20000) + b"\xe9",
+    ],
+)
+@pytest.mark.parametrize("chunk_size", [16, 64, 4096])
+def test_auto_same_across_chunk_sizes(matcher, tmp_path, data, chunk_size):
+    """Test that auto detects the encoding once per input, not once per chunk."""
+    path = tmp_path / "mixed.txt"
+    path.write_bytes(data)
+    expected = matcher.match_bytes(data, encoding="auto")
+
+    kwargs = {"encoding": "auto"}
+    assert matcher.match_file_memmap(str(path), chunk_size=chunk_size, **kwargs) == expected
+    parallel = matcher.match_file_memmap_parallel(str(path), chunk_size=chunk_size, n_threads=4, **kwargs)
+    assert sorted(parallel) == expected
+    assert matcher.match_file_stream(str(path), buffer_size=chunk_size, **kwargs) == expected
+    assert sorted(matcher.match_stream(data, buffer_size=chunk_size, **kwargs)) == expected
+    readable = matcher.match_readable_parallel(io.BytesIO(data), buffer_size=chunk_size, **kwargs)
+    assert sorted(readable) == expected
+
+
+def test_unknown_encoding(matcher):
+    """Test that unknown encodings are rejected."""
+    with pytest.raises(ValueError, match="Unknown encoding 'utf-16'"):
+        matcher.match_bytes(b"data", encoding="utf-16")
+    with pytest.raises(ValueError, match="Unknown encoding 'ebcdic'"):
+        TextMatcher(PATTERNS, encoding="ebcdic")
@@ -0,0 +1,165 @@
+import random
+
+import pytest
+from voluta import TextMatcher
+
+
+def hyperscan_available():
+    """Check if voluta was built with the hyperscan feature."""
+    try:
+        TextMatcher(["x"], engine="hyperscan")
+    except ValueError:
+        return False
+    return True
+
+
+def gpu_available():
+    """Check if voluta was built with the gpu feature."""
+    try:
+        TextMatcher(["x"], engine="gpu")
+    except ValueError:
+        return False
+    return True
+
+
+PATTERNS = ["he", "she", "his", "hers", "her", "pass", "password"]
+DATA = b"ushers said HIS password is hers; she_he pass\nhe her"
+
+
+def test_default_engine():
+    """Test that auto picks the memmem engine for tiny pattern sets and Aho-Corasick otherwise."""
+    assert TextMatcher(PATTERNS).engine == "memmem"
+    assert TextMatcher(PATTERNS, engine="auto").engine == "memmem"
+    assert TextMatcher(PATTERNS, engine="aho").engine == "aho"
+    assert TextMatcher([f"pattern{i}" for i in range(9)]).engine == "aho"
+    assert TextMatcher(["x" * 33]).engine == "aho"
+
+
+def test_memmem_limits():
+    """Test that the memmem engine refuses pattern sets it cannot search."""
+    with pytest.raises(ValueError, match="memmem engine"):
+        TextMatcher([f"pattern{i}" for i in range(9)], engine="memmem")
+    with pytest.raises(ValueError, match="memmem engine"):
+        TextMatcher(["x" * 33], engine="memmem")
+
+
+@pytest.mark.parametrize(
+    "options",
+    [
+        {},
+        {"overlapping": False},
+        {"case_insensitive": False},
+        {"overlapping": False, "case_insensitive": False},
+        {"whole_word": True},
+        {"postprocess": "longest"},
+    ],
+)
+def test_memmem_matches_aho(options, tmp_path):
+    """Test that the memmem engine returns the same results as the Aho-Corasick engine."""
+    rng = random.Random(7)
+    path = tmp_path / "input.txt"
+    for _ in range(300):
+        patterns = [
+            "".join(rng.choice("aAb1 ") for _ in range(rng.randint(1, 4)))
+            for _ in range(rng.randint(1, 8))
+        ]
+        data = "".join(rng.choice("aAbB1 \n") for _ in range(rng.randint(0, 60))).encode()
+        aho = TextMatcher(patterns, engine="aho", **options)
+        memmem = TextMatcher(patterns, engine="memmem", **options)
+
+        assert memmem.match_bytes(data) == aho.match_bytes(data), (patterns, data)
+        path.write_bytes(data)
+        assert memmem.match_file(str(path)) == aho.match_file(str(path))
+        assert memmem.match_stream(data, 16) == aho.match_stream(data, 16)
+
+
+def test_unknown_engine():
+    """Test that an unknown engine is rejected."""
+    with pytest.raises(ValueError, match="Unknown engine"):
+        TextMatcher(PATTERNS, engine="re2")
+
+
+def test_hyperscan_unavailable():
+    """Test that the hyperscan engine needs the hyperscan feature."""
+    if hyperscan_available():
+        pytest.skip("voluta was built with the hyperscan feature")
+    with pytest.raises(ValueError, match="hyperscan feature"):
+        TextMatcher(PATTERNS, engine="hyperscan")
+
+
+@pytest.mark.parametrize(
+    "options",
+    [
+        {},
+        {"overlapping": False},
+        {"case_insensitive": False},
+        {"whole_word": True},
+        {"overlapping": False, "whole_word": True},
+        {"postprocess": "longest"},
+    ],
+)
+def test_hyperscan_matches_aho(options, tmp_path):
+    """Test that the hyperscan engine returns the same results as the Aho-Corasick engine."""
+    if not hyperscan_available():
+        pytest.skip("voluta was built without the hyperscan feature")
+    aho = TextMatcher(PATTERNS, engine="aho", **options)
+    hyperscan = TextMatcher(PATTERNS, engine="hyperscan", **options)
+    assert hyperscan.engine == "hyperscan"
+
+    path = tmp_path / "input.txt"
+    path.write_bytes(DATA * 100)
+    assert hyperscan.match_bytes(DATA) == aho.match_bytes(DATA)
+    assert hyperscan.match_file(str(path)) == aho.match_file(str(path))
+    assert sorted(hyperscan.match_file_memmap_parallel(str(path), 64)) == sorted(
+        aho.match_file_memmap_parallel(str(path), 64)
+    )
+
+
+def test_hyperscan_auto_large_sets():
+    """Test that auto picks the hyperscan engine for large pattern sets."""
+    if not hyperscan_available():
+        pytest.skip("voluta was built without the hyperscan feature")
+    patterns = [f"token{i:05}" for i in range(5000)]
+    matcher = TextMatcher(patterns)
+    assert matcher.engine == "hyperscan"
+    assert matcher.match_bytes(b"x token00042 token04999") == [
+        (2, 12, "token00042"),
+        (13, 23, "token04999"),
+    ]
+
+
+def test_gpu_unavailable():
+    """Test that the gpu engine needs the gpu feature."""
+    if gpu_available():
+        pytest.skip("voluta was built with the gpu feature")
+    with pytest.raises(ValueError, match="gpu feature"):
+        TextMatcher(PATTERNS, engine="gpu")
+
+
+@pytest.mark.parametrize(
+    "options",
+    [
+        {},
+        {"overlapping": False},
+        {"case_insensitive": False},
+        {"whole_word": True},
+        {"postprocess": "longest"},
+    ],
+)
+def test_gpu_matches_aho(options, tmp_path):
+    """Test that the gpu engine, or its CPU fallback, returns the same results as Aho-Corasick."""
+    if not gpu_available():
+        pytest.skip("voluta was built without the gpu feature")
+    aho = TextMatcher(PATTERNS, engine="aho", **options)
+    gpu = TextMatcher(PATTERNS, engine="gpu", **options)
+    assert gpu.engine in ("gpu", "aho")
+
+    # Large enough for the device, small haystacks are scanned on the CPU
+    data = DATA * 40000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    assert gpu.match_bytes(DATA) == aho.match_bytes(DATA)
+    assert gpu.match_bytes(data) == aho.match_bytes(data)
+    assert sorted(gpu.match_file_memmap_parallel(str(path))) == sorted(
+        aho.match_file_memmap_parallel(str(path))
+    )
@@ -0,0 +1,91 @@
+import io
+
+import pytest
+from voluta import (
+    DecodeError,
+    LimitExceeded,
+    PatternError,
+    ScanIOError,
+    TextMatcher,
+    VolutaError,
+)
+
+
+def test_hierarchy():
+    """Test that every error derives from VolutaError and its former builtin class."""
+    assert issubclass(PatternError, VolutaError)
+    assert issubclass(PatternError, ValueError)
+    assert issubclass(LimitExceeded, VolutaError)
+    assert issubclass(LimitExceeded, ValueError)
+    assert issubclass(ScanIOError, VolutaError)
+    assert issubclass(ScanIOError, OSError)
+    assert issubclass(DecodeError, ScanIOError)
+    assert PatternError.__module__ == "voluta"
+
+
+def test_pattern_error():
+    """Test that invalid patterns and options raise PatternError."""
+    with pytest.raises(PatternError, match="Pattern set cannot be empty"):
+        TextMatcher([])
+    with pytest.raises(PatternError, match="Unknown pattern 'nope'"):
+        TextMatcher(["secret"]).match_density(b"secret", 1, 10, patterns=["nope"])
+
+
+def test_limit_exceeded():
+    """Test that patterns exceeding a limit raise LimitExceeded."""
+    with pytest.raises(LimitExceeded, match="at most 2"):
+        TextMatcher(["password"], fuzzy=3)
+    with pytest.raises(LimitExceeded, match="memmem engine"):
+        TextMatcher([f"pattern{i}" for i in range(100)], engine="memmem")
+    with pytest.raises(ValueError):
+        TextMatcher(["password"], fuzzy=3)
+
+
+def test_scan_io_error():
+    """Test that unreadable files raise ScanIOError, still an IOError."""
+    matcher = TextMatcher(["secret"])
+    with pytest.raises(ScanIOError):
+        matcher.match_file("nonexistent_file.txt")
+    with pytest.raises(IOError):
+        matcher.match_file_memmap("nonexistent_file.txt")
+    with pytest.raises(ScanIOError):
+        matcher.match_dir("nonexistent_dir")
+
+
+def test_decode_error(tmp_path):
+    """Test that invalid UTF-8 raises DecodeError."""
+    path = tmp_path / "mixed.log"
+    path.write_bytes(b"secret\n\xff secret\n")
+    with pytest.raises(DecodeError, match="valid UTF-8"):
+        TextMatcher(["secret"]).match_file(str(path))
+
+
+def test_readable_errors_propagate():
+    """Test that errors raised by a readable are not wrapped."""
+
+    class Failing(io.RawIOBase):
+        def readable(self):
+            return True
+
+        def read(self, size=-1):
+            raise KeyError("boom")
+
+    with pytest.raises(KeyError):
+        TextMatcher(["secret"]).match_readable_parallel(Failing())
+
+
+def test_partial_results(tmp_path):
+    """Test that multi-file scans report unreadable files instead of raising."""
+    (tmp_path / "a.txt").write_bytes(b"a secret")
+    (tmp_path / "b.txt").symlink_to(tmp_path / "missing.txt")
+    matcher = TextMatcher(["secret"])
+
+    matches, files = matcher.match_dir(str(tmp_path), follow_links=True)
+    assert [(s, e) for _, s, e, _ in matches] == [(2, 8)]
+    assert files[0].scanned
+    assert not files[1].scanned
+    assert "No such file" in files[1].error
+
+    report = matcher.scan_report([str(tmp_path / "a.txt"), str(tmp_path / "missing.txt")])
+    assert report.total_matches == 1
+    assert [path for path, _ in report.errors] == [str(tmp_path / "missing.txt")]
@@ -0,0 +1,77 @@
+import math
+import random
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+MATCHER = TextMatcher(["secret", "token"])
+
+
+@pytest.fixture
+def corpus(tmp_path):
+    """Fixture that provides a 1MB file with a match every 100 bytes."""
+    path = tmp_path / "corpus.log"
+    path.write_bytes((b"x" * 94 + b"secret") * 10_000)
+    return str(path)
+
+
+def test_full_sample_is_exact(corpus):
+    """Test that sampling every chunk counts the matches exactly, across chunk boundaries."""
+    estimate = MATCHER.estimate_density(corpus, sample_fraction=1.0, chunk_size=1000)
+    assert (estimate.bytes, estimate.chunks, estimate.sampled_chunks) == (1_000_000, 1000, 1000)
+    assert estimate.sampled_matches == len(MATCHER.match_file_memmap(corpus)) == 10_000
+    assert estimate.estimated_matches == estimate.lower == estimate.upper == 10_000
+    assert estimate.matches_per_mb == pytest.approx(10_000)
+
+
+def test_sampled_estimate(tmp_path):
+    """Test that estimates from a sample bound the actual count."""
+    rng = random.Random(1)
+    path = tmp_path / "uneven.log"
+    path.write_bytes(b"".join(b"y" * rng.randrange(1000) + b" secret" for _ in range(5000)))
+    actual = len(MATCHER.match_file_memmap(str(path)))
+    estimate = MATCHER.estimate_density(str(path), sample_fraction=0.2, chunk_size=4096, seed=7)
+    assert estimate.sampled_chunks == math.ceil(estimate.chunks * 0.2)
+    assert estimate.sampled_bytes <= estimate.sampled_chunks * 4096
+    assert estimate.sampled_matches <= estimate.lower <= actual <= estimate.upper
+    assert estimate.estimated_matches == pytest.approx(actual, rel=0.1)
+    assert estimate.seed == 7
+
+
+def test_seed_reproduces_sample(tmp_path):
+    """Test that a seed draws the same chunks again."""
+    path = tmp_path / "uneven.log"
+    path.write_bytes(b"".join(b"token " * (i % 7) + b"y" * 1000 for i in range(500)))
+    first = MATCHER.estimate_density(str(path), sample_fraction=0.1, chunk_size=1000)
+    again = MATCHER.estimate_density(str(path), sample_fraction=0.1, chunk_size=1000, seed=first.seed)
+    assert again.sampled_matches == first.sampled_matches
+    assert again.estimated_matches == first.estimated_matches
+
+
+def test_single_chunk_sample(corpus):
+    """Test that a single sampled chunk of several leaves the upper bound open."""
+    estimate = MATCHER.estimate_density(corpus, sample_fraction=1e-9)
+    assert estimate.sampled_chunks == 1
+    assert estimate.lower == estimate.sampled_matches
+    assert estimate.upper == math.inf
+
+
+def test_empty_file(tmp_path):
+    """Test estimates of empty files."""
+    path = tmp_path / "empty.log"
+    path.write_bytes(b"")
+    estimate = MATCHER.estimate_density(str(path))
+    assert (estimate.chunks, estimate.sampled_chunks, estimate.estimated_matches) == (0, 0, 0)
+    assert estimate.matches_per_mb == 0
+
+
+def test_invalid_arguments(corpus, tmp_path):
+    """Test that invalid fractions, chunk sizes and paths are rejected."""
+    with pytest.raises(ValueError, match="sample_fraction must be in"):
+        MATCHER.estimate_density(corpus, sample_fraction=0)
+    with pytest.raises(ValueError, match="sample_fraction must be in"):
+        MATCHER.estimate_density(corpus, sample_fraction=1.5)
+    with pytest.raises(ValueError, match="chunk_size must be positive"):
+        MATCHER.estimate_density(corpus, chunk_size=0)
+    with pytest.raises(ScanIOError):
+        MATCHER.estimate_density(str(tmp_path / "missing.log"))
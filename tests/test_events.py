@@ -0,0 +1,125 @@
+import logging
+
+import pytest
+from voluta import IncrementalMatcher, LimitExceeded, ScanIOError, TextMatcher
+
+
+class Records(logging.Handler):
+    """Handler keeping the records it receives."""
+
+    def __init__(self):
+        super().__init__(logging.DEBUG)
+        self.records = []
+
+    def emit(self, record):
+        self.records.append(record)
+
+    def events(self):
+        return [record.event for record in self.records]
+
+
+@pytest.fixture
+def logger():
+    """Fixture that provides a logger recording every record."""
+    logger = logging.getLogger("voluta.test_events")
+    logger.setLevel(logging.DEBUG)
+    logger.propagate = False
+    logger.handlers = [Records()]
+    return logger
+
+
+def test_scan_events(logger):
+    """Test that scans log their start and end with structured fields."""
+    matcher = TextMatcher(["secret"], events=logger)
+    handler = logger.handlers[0]
+    assert handler.events() == ["built"]
+    assert handler.records[0].patterns == 1
+
+    matcher.match_bytes(b"a secret, another secret")
+    started, finished = handler.records[1:]
+    assert (started.event, started.levelno) == ("scan_started", logging.DEBUG)
+    assert (started.operation, started.path, started.bytes) == ("match_bytes", None, 24)
+    assert (finished.event, finished.levelno) == ("scan_finished", logging.INFO)
+    assert finished.matches == 2
+    assert finished.elapsed >= 0
+    assert finished.getMessage().startswith("match_bytes finished: 2 matches in ")
+
+
+def test_file_events(logger, tmp_path):
+    """Test events of file scans, failures included."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret\n")
+    matcher = TextMatcher(["secret"], events=logger)
+    handler = logger.handlers[0]
+
+    matcher.match_file_memmap(str(path))
+    assert handler.records[-1].event == "scan_finished"
+    assert handler.records[-1].operation == "match_file_memmap"
+    assert handler.records[-1].path == str(path)
+    assert handler.records[-1].bytes == 7
+
+    with pytest.raises(ScanIOError):
+        matcher.match_file(str(tmp_path / "missing.log"))
+    failed = handler.records[-1]
+    assert (failed.event, failed.levelno) == ("scan_failed", logging.ERROR)
+    assert failed.path == str(tmp_path / "missing.log")
+    assert "No such file" in failed.error
+
+
+def test_dir_events(logger, tmp_path):
+    """Test that directory scans log every file and the files skipped by limits."""
+    (tmp_path / "small.log").write_bytes(b"secret")
+    (tmp_path / "large.log").write_bytes(b"secret" * 100)
+    matcher = TextMatcher(["secret"], events=logger)
+    handler = logger.handlers[0]
+
+    matcher.match_dir(str(tmp_path), max_file_size=100)
+    limit = [r for r in handler.records if r.event == "limit_hit"]
+    assert [(r.operation, r.path, r.levelno) for r in limit] == [
+        ("match_dir", str(tmp_path / "large.log"), logging.WARNING)
+    ]
+    finished = [r for r in handler.records if r.event == "scan_finished"]
+    assert [(r.operation, r.path) for r in finished] == [("match_dir", str(tmp_path / "small.log"))]
+
+
+def test_build_limit_events(logger):
+    """Test that limits failing a build are logged."""
+    with pytest.raises(LimitExceeded):
+        TextMatcher(["x" * 100], engine="memmem", events=logger)
+    assert logger.handlers[0].events() == ["limit_hit"]
+
+
+def test_incremental_events(logger):
+    """Test that every rebuild of an incremental matcher is logged."""
+    matcher = IncrementalMatcher(["a"], events=logger)
+    matcher.add_patterns(["b"])
+    matcher.compact()
+    records = logger.handlers[0].records
+    assert [(r.event, r.operation, r.patterns) for r in records] == [
+        ("built", "add_patterns", 1),
+        ("built", "add_patterns", 2),
+    ]
+
+
+def test_voluta_logger():
+    """Test that events="logging" logs to the "voluta" logger."""
+    handler = Records()
+    voluta_logger = logging.getLogger("voluta")
+    voluta_logger.addHandler(handler)
+    level = voluta_logger.level
+    voluta_logger.setLevel(logging.INFO)
+    try:
+        TextMatcher(["secret"], events="logging").match_bytes(b"secret")
+    finally:
+        voluta_logger.removeHandler(handler)
+        voluta_logger.setLevel(level)
+    # Scan starts are logged at DEBUG, below the logger's level
+    assert handler.events() == ["built", "scan_finished"]
+
+
+def test_invalid_events():
+    """Test that unknown event hooks are rejected."""
+    with pytest.raises(ValueError, match="Unknown events 'stdout'"):
+        TextMatcher(["x"], events="stdout")
+    with pytest.raises(TypeError, match="events must be"):
+        TextMatcher(["x"], events=42)
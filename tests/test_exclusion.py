@@ -0,0 +1,75 @@
+import asyncio
+import io
+
+import pytest
+from voluta import TextMatcher
+
+DATA = b"key=AKIA1234 example: AKIA1234EXAMPLE other AKIA1234"
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher excluding a documented example key."""
+    return TextMatcher(["AKIA1234"], exclude=["AKIA1234EXAMPLE"])
+
+
+def test_exclude_patterns(matcher):
+    """Test that matches overlapping an occurrence of an exclusion pattern are dropped."""
+    assert matcher.exclude == ["AKIA1234EXAMPLE"]
+    assert matcher.match_bytes(DATA) == [(4, 12, "AKIA1234"), (44, 52, "AKIA1234")]
+    assert TextMatcher(["AKIA1234"]).exclude == []
+    assert len(TextMatcher(["AKIA1234"]).match_bytes(DATA)) == 3
+
+
+def test_exclude_partial_overlap():
+    """Test that an exclusion zone only overlapping part of a match suppresses it."""
+    matcher = TextMatcher(["secret"], exclude=["# test", "fixture sec"])
+    data = b"secret # test: secret_value fixture secret"
+    assert matcher.match_bytes(data) == [(0, 6, "secret"), (15, 21, "secret")]
+
+
+def test_exclude_case_follows_matcher():
+    """Test that exclusion patterns fold case like the patterns."""
+    data = b"token TOKEN_EXAMPLE"
+    assert TextMatcher(["token"], exclude=["token_example"]).match_bytes(data) == [
+        (0, 5, "token")
+    ]
+    strict = TextMatcher(["TOKEN"], case_insensitive=False, exclude=["token_example"])
+    assert strict.match_bytes(data) == [(6, 11, "TOKEN")]
+
+
+def test_exclude_chunked_scans(matcher, tmp_path):
+    """Test that chunked and parallel scans find the exclusion zones across chunk boundaries."""
+    data = DATA + b"\n" + b"xx AKIA1234EXAMPLE AKIA1234\n" * 20000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    expected = matcher.match_bytes(data)
+    assert len(expected) == 20002
+
+    assert matcher.match_file_memmap(str(path), chunk_size=4099) == expected
+    found = matcher.match_file_memmap_parallel(str(path), chunk_size=4099, n_threads=4)
+    assert sorted(found) == expected
+    assert matcher.match_stream(data, buffer_size=4099) == expected
+    with open(path, "rb") as f:
+        assert sorted(matcher.match_readable_parallel(f, buffer_size=4099)) == expected
+
+    async def scan():
+        return await matcher.amatch_readable(io.BytesIO(data), buffer_size=7)
+
+    assert asyncio.run(scan()) == expected
+
+
+def test_ignore_spans(matcher, tmp_path):
+    """Test that per-call ignore spans drop the matches overlapping them."""
+    assert matcher.match_bytes(DATA, ignore_spans=[(0, 10)]) == [(44, 52, "AKIA1234")]
+    assert matcher.match_bytes(DATA, ignore_spans=[(50, 60), (0, 2)]) == [(4, 12, "AKIA1234")]
+    assert matcher.match_bytes(DATA, ignore_spans=[(12, 12), (12, 44)]) == matcher.match_bytes(DATA)
+
+    path = tmp_path / "input.txt"
+    path.write_bytes(DATA)
+    for method in (matcher.match_file_memmap, matcher.match_file_memmap_parallel):
+        assert method(str(path), ignore_spans=[(3, 5)]) == [(44, 52, "AKIA1234")]
+    assert matcher.match_stream(DATA, ignore_spans=[(40, 45)]) == [(4, 12, "AKIA1234")]
+
+    with pytest.raises(ValueError, match=r"Invalid ignore span \(5, 3\)"):
+        matcher.match_bytes(DATA, ignore_spans=[(5, 3)])
@@ -0,0 +1,68 @@
+import pytest
+from voluta import TextMatcher, hash_text
+
+
+def test_extract_unique_bytes():
+    """Test that each pattern maps to the distinct strings it matched."""
+    matcher = TextMatcher(["akia", "secret"])
+    data = b"AKIA1 akia2 Akia3 AKIA4 secret SECRET secret"
+    assert matcher.extract_unique(data) == {
+        "akia": {"AKIA", "akia", "Akia"},
+        "secret": {"secret", "SECRET"},
+    }
+
+
+def test_extract_unique_unmatched():
+    """Test that patterns without matches are left out."""
+    matcher = TextMatcher(["error", "warning"])
+    assert matcher.extract_unique(b"error, error") == {"error": {"error"}}
+    assert matcher.extract_unique(b"all good") == {}
+
+
+def test_extract_unique_normalize():
+    """Test that strings are normalized before being deduplicated."""
+    matcher = TextMatcher(["secret"])
+    data = b"Secret SECRET secret"
+    assert matcher.extract_unique(data, normalize="lower") == {"secret": {"secret"}}
+    assert matcher.extract_unique(data, normalize="upper") == {"secret": {"SECRET"}}
+
+    with pytest.raises(ValueError):
+        matcher.extract_unique(data, normalize="title")
+
+
+def test_extract_unique_max_values():
+    """Test that the first distinct strings found are kept up to the cap."""
+    matcher = TextMatcher(["key"], overlapping=False)
+    data = b"KEY key KEY Key kEY"
+    assert matcher.extract_unique(data, max_values=2) == {"key": {"KEY", "key"}}
+    assert len(matcher.extract_unique(data)["key"]) == 4
+
+
+def test_extract_unique_file(tmp_path):
+    """Test extraction from a file path, and errors for missing files and invalid input."""
+    path = tmp_path / "leak.txt"
+    path.write_bytes(b"token=Ghp_a\ntoken=ghp_b\n")
+    matcher = TextMatcher(["ghp_"])
+    assert matcher.extract_unique(str(path)) == {"ghp_": {"Ghp_", "ghp_"}}
+
+    with pytest.raises(IOError):
+        matcher.extract_unique("nonexistent_file.txt")
+
+    with pytest.raises(TypeError):
+        matcher.extract_unique(42)
+
+
+def test_extract_unique_postprocess():
+    """Test that strings are taken from post-processed matches."""
+    matcher = TextMatcher(["new york", "york city"], postprocess="merge")
+    result = matcher.extract_unique(b"new york city")
+    assert set().union(*result.values()) == {"new york city"}
+
+
+def test_extract_unique_hashed():
+    """Test that hash-only matchers report digests of the distinct strings."""
+    key = b"findings-salt"
+    matcher = TextMatcher(["secret"], case_insensitive=False, hash_key=key)
+    assert matcher.extract_unique(b"secret secret") == {
+        hash_text(key, "secret"): {hash_text(key, "secret")}
+    }
@@ -0,0 +1,66 @@
+import pytest
+from voluta import TextMatcher
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher with overlapping patterns."""
+    return TextMatcher(["secret", "cret", "token"])
+
+
+def test_last_match(matcher):
+    """Test that the last match is returned first."""
+    data = b"token secret token"
+    assert matcher.find_last(data) == [(13, 18, "token")]
+    assert matcher.find_last(data, count=3) == [
+        (13, 18, "token"),
+        (8, 12, "cret"),
+        (6, 12, "secret"),
+    ]
+    assert matcher.find_last(data, count=99) == sorted(matcher.match_bytes(data), reverse=True)
+    assert matcher.find_last(b"nothing here") == []
+    assert matcher.find_last(b"") == []
+
+
+def test_across_windows(matcher):
+    """Test that matches straddling the windows searched back from the end are all found."""
+    data = b"x secret token " * 50000
+    expected = sorted(matcher.match_bytes(data), reverse=True)
+    assert len(expected) == 150000
+    assert matcher.find_last(data, count=len(expected) + 1) == expected
+    assert matcher.find_last(data, count=1000) == expected[:1000]
+
+
+def test_every_occurrence_counts():
+    """Test that overlapping occurrences count whatever the matcher settings."""
+    matcher = TextMatcher(["secret", "cret"], overlapping=False, postprocess="longest")
+    assert matcher.match_bytes(b"a secret") == [(2, 8, "secret")]
+    assert matcher.find_last(b"a secret", count=2) == [(4, 8, "cret"), (2, 8, "secret")]
+
+
+def test_filters_apply():
+    """Test that whole words and exclusion zones filter the matches found from the end."""
+    matcher = TextMatcher(["key"], whole_word=True, exclude=["key=example"])
+    data = b"key=1 " + b"." * 100000 + b" key=example keys"
+    assert matcher.find_last(data) == [(0, 3, "key")]
+    data = b"key=1 " + b"." * 200000 + b"key"
+    assert matcher.find_last(data) == [(200006, 200009, "key")]
+
+
+def test_file(matcher, tmp_path):
+    """Test that files are searched from the end too."""
+    data = b"token\n" + b"." * 300000 + b"\nsecret at the end\n"
+    path = tmp_path / "app.log"
+    path.write_bytes(data)
+    assert matcher.find_last_file(str(path)) == [(300009, 300013, "cret")]
+    assert matcher.find_last_file(str(path), count=10) == matcher.find_last(data, count=10)
+    with pytest.raises(IOError):
+        matcher.find_last_file(str(tmp_path / "missing.log"))
+
+
+def test_count_validation(matcher):
+    """Test that the count of matches must be positive."""
+    with pytest.raises(ValueError, match="count must be positive"):
+        matcher.find_last(b"secret", count=0)
+    with pytest.raises(ValueError, match="count must be positive"):
+        matcher.find_last_file("missing.log", count=0)
@@ -0,0 +1,48 @@
+from voluta import TextMatcher
+
+
+def test_fingerprint_is_stable():
+    """Test that fingerprints only depend on the patterns and the options changing the matches."""
+    fingerprint = TextMatcher(["secret", "token"]).fingerprint
+    assert len(fingerprint) == 64
+    assert int(fingerprint, 16) >= 0
+    assert TextMatcher(["secret", "token"]).fingerprint == fingerprint
+    assert TextMatcher(["secret", "token"], engine="aho").fingerprint == fingerprint
+    assert TextMatcher(["secret", "token"], madvise="sequential", prefault=True).fingerprint == fingerprint
+
+
+def test_fingerprint_changes():
+    """Test that fingerprints change with the patterns and the options changing the matches."""
+    fingerprint = TextMatcher(["secret", "token"]).fingerprint
+    others = [
+        TextMatcher(["token", "secret"]),
+        TextMatcher(["secret", "token", "key"]),
+        TextMatcher(["secrettoken"]),
+        TextMatcher(["secret", "token"], overlapping=False),
+        TextMatcher(["secret", "token"], case_insensitive=False),
+        TextMatcher(["secret", "token"], whole_word=True),
+        TextMatcher(["secret", "token"], postprocess="longest"),
+        TextMatcher(["secret", "token"], exclude=["secret token"]),
+        TextMatcher(["secret", "token"], hash_key=b"key"),
+        TextMatcher(["secret", "token"], hash_key=b"other key"),
+    ]
+    fingerprints = {fingerprint} | {matcher.fingerprint for matcher in others}
+    assert len(fingerprints) == len(others) + 1
+
+
+def test_report_fingerprint(tmp_path):
+    """Test that scan reports carry the fingerprint of their matcher."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"secret\n")
+    matcher = TextMatcher(["secret"])
+    report = matcher.scan_report([str(path)])
+    assert report.fingerprint == matcher.fingerprint
+    assert matcher.fingerprint in report.to_html()
+    assert matcher.fingerprint in report.to_markdown()
+
+
+def test_sink_fingerprint(tmp_path):
+    """Test that the summaries of sink scans carry the fingerprint of their matcher."""
+    matcher = TextMatcher(["secret"])
+    summary = matcher.match_bytes_to_sink(b"a secret", str(tmp_path / "out.ndjson"))
+    assert summary.fingerprint == matcher.fingerprint
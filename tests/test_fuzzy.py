@@ -0,0 +1,83 @@
+import pytest
+from voluta import TextMatcher
+
+
+def test_misspellings_match():
+    """Test that texts one edit away report the pattern they are a misspelling of."""
+    matcher = TextMatcher(["password", "secret"], fuzzy=1)
+    data = b"my pasword, passw0rd, password and PASSWORDS secert sekret"
+    assert matcher.match_bytes(data) == [
+        (3, 10, "password"),
+        (12, 20, "password"),
+        (22, 30, "password"),
+        (35, 43, "password"),
+        (52, 58, "secret"),
+    ]
+    assert matcher.fuzzy == [1, 1]
+
+
+def test_distance_two():
+    """Test that a distance of two matches swapped characters and two missing ones."""
+    matcher = TextMatcher(["password"], fuzzy=2)
+    assert matcher.match_bytes(b"passwrod pwd psswrd") == [(0, 8, "password"), (13, 19, "password")]
+
+
+def test_edits_keep_ends():
+    """Test that edits never delete or replace the first and last characters."""
+    matcher = TextMatcher(["password"], fuzzy=1)
+    assert matcher.match_bytes(b"assword passwor") == []
+    assert matcher.match_bytes(b"Xassword") == []
+
+
+def test_per_pattern_distances():
+    """Test that each pattern is matched at its own distance."""
+    matcher = TextMatcher(["password", "secret"], fuzzy=[1, 0], overlapping=False)
+    assert matcher.fuzzy == [1, 0]
+    assert matcher.match_bytes(b"pasword sekret password secret") == [
+        (0, 7, "password"),
+        (15, 23, "password"),
+        (24, 30, "secret"),
+    ]
+
+
+def test_case_sensitive():
+    """Test that case-sensitive fuzzy patterns still match the case of the pattern only."""
+    matcher = TextMatcher(["Token"], fuzzy=1, case_insensitive=False)
+    assert matcher.match_bytes(b"Tokn TOKN tokn ToKen") == [(0, 4, "Token"), (15, 20, "Token")]
+
+
+def test_whole_word():
+    """Test that fuzzy matches honor whole word matching."""
+    matcher = TextMatcher(["password"], fuzzy=1, whole_word=True)
+    assert matcher.match_bytes(b"pasword paswords") == [(0, 7, "password")]
+
+
+def test_file_scans_match_bytes(tmp_path):
+    """Test that chunked and parallel file scans find the fuzzy matches of match_bytes."""
+    data = b"the pasword is hunter2, the passw0rd was reset\n" * 50000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    matcher = TextMatcher(["password"], fuzzy=1)
+    expected = matcher.match_bytes(data)
+    assert len(expected) == 100000
+    found = matcher.match_file_memmap_parallel(str(path), chunk_size=4096, n_threads=4)
+    assert sorted(found) == expected
+
+
+def test_options_validated():
+    """Test the fuzzy option validation."""
+    with pytest.raises(ValueError, match="at most 2"):
+        TextMatcher(["password"], fuzzy=3)
+    with pytest.raises(ValueError, match="needs at least 5 characters"):
+        TextMatcher(["pass"], fuzzy=2)
+    with pytest.raises(ValueError, match="one entry per pattern"):
+        TextMatcher(["password", "secret"], fuzzy=[1])
+    with pytest.raises(ValueError, match="hash_key"):
+        TextMatcher(["password"], fuzzy=1, hash_key=b"key")
+    with pytest.raises(ValueError, match="more than 1000000 variants"):
+        TextMatcher([f"credential{i}" for i in range(20)], fuzzy=2)
+    with pytest.raises(TypeError):
+        TextMatcher(["password"], fuzzy="1")
+
+    assert TextMatcher(["ab", "password"], fuzzy=[0, 1]).fuzzy == [0, 1]
+    assert TextMatcher(["password"]).fuzzy == [0]
@@ -0,0 +1,103 @@
+import os
+import shutil
+import subprocess
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+
+def git(repo, *args):
+    """Run git in repo and return its output."""
+    return subprocess.run(
+        ["git", "-C", str(repo), *args], check=True, capture_output=True, text=True
+    ).stdout.strip()
+
+
+def commit(repo, files, message):
+    """Write files, commit them and return the commit hash."""
+    for name, content in files.items():
+        path = repo / name
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_bytes(content)
+    git(repo, "add", "-A")
+    git(repo, "commit", "-q", "-m", message)
+    return git(repo, "rev-parse", "HEAD")
+
+
+@pytest.fixture
+def repo(tmp_path):
+    """Fixture that provides a repository with a secret added then removed."""
+    if shutil.which("git") is None:
+        pytest.skip("git is not installed")
+    git(tmp_path, "init", "-q")
+    git(tmp_path, "config", "user.email", "dev@example.com")
+    git(tmp_path, "config", "user.name", "Dev")
+    first = commit(tmp_path, {"config.ini": b"password=hunter2\n", "README": b"docs"}, "add")
+    second = commit(tmp_path, {"config.ini": b"password=\n", "src/app.py": b"token"}, "fix")
+    return tmp_path, first, second
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a secret matcher."""
+    return TextMatcher(["hunter2", "token", "password="])
+
+
+def test_head_tree(matcher, repo):
+    """Test that the tree of HEAD is scanned by default."""
+    path, _, second = repo
+    assert matcher.match_git_repo(str(path)) == [
+        (second, "config.ini", 0, 9, "password="),
+        (second, "src/app.py", 0, 5, "token"),
+    ]
+
+
+def test_revisions(matcher, repo):
+    """Test that the trees of several revisions are scanned, each blob once."""
+    path, first, second = repo
+    matches = matcher.match_git_repo(str(path), revisions=["HEAD~1", "HEAD"])
+    assert matches == [
+        (first, "config.ini", 0, 9, "password="),
+        (first, "config.ini", 9, 16, "hunter2"),
+        (second, "config.ini", 0, 9, "password="),
+        (second, "src/app.py", 0, 5, "token"),
+    ]
+
+
+def test_history(matcher, repo):
+    """Test that history mode finds secrets removed since, at the commit adding them."""
+    path, first, second = repo
+    matches = matcher.match_git_repo(str(path), history=True)
+    assert (first, "config.ini", 9, 16, "hunter2") in matches
+    assert (second, "src/app.py", 0, 5, "token") in matches
+    assert len(matches) == 4
+
+
+def test_many_blobs(matcher, repo):
+    """Test that blobs streamed through one process are all scanned."""
+    path, _, _ = repo
+    head = commit(path, {f"gen/{i:03}.txt": f"{i} token".encode() for i in range(300)}, "gen")
+    matches = [m for m in matcher.match_git_repo(str(path)) if m[1].startswith("gen/")]
+    assert len(matches) == 300
+    assert matches[42] == (head, "gen/042.txt", 3, 8, "token")
+
+
+def test_symlinks_skipped(matcher, repo):
+    """Test that symbolic links are not scanned."""
+    path, _, _ = repo
+    os.symlink("token", path / "link")
+    commit(path, {}, "link")
+    assert all(m[1] != "link" for m in matcher.match_git_repo(str(path)))
+
+
+def test_errors(matcher, repo):
+    """Test that bad revisions and non-repositories raise ScanIOError."""
+    path, _, _ = repo
+    with pytest.raises(ScanIOError, match="Needed a single revision"):
+        matcher.match_git_repo(str(path), revisions=["nonexistent"])
+    with pytest.raises(ScanIOError, match="Invalid revision"):
+        matcher.match_git_repo(str(path), revisions=["--all"])
+    with pytest.raises(ScanIOError, match="not a git repository"):
+        plain = path.parent / f"{path.name}-plain"
+        plain.mkdir()
+        matcher.match_git_repo(str(plain))
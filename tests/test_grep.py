@@ -0,0 +1,70 @@
+import pytest
+from voluta import TextMatcher, format_grep
+
+
+@pytest.fixture
+def log_file(tmp_path):
+    """Fixture that provides a small log file with CRLF and LF line endings."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"boot ok\r\nerror: disk full, error again\r\nfine\nwarning: cpu hot")
+    return str(path)
+
+
+def test_format_grep(log_file):
+    """Test plain grep-style output with one line per matching line."""
+    matcher = TextMatcher(["error", "warning"])
+    output = format_grep(log_file, matcher.match_file_memmap(log_file))
+    assert output == (
+        f"{log_file}:2:1: error: disk full, error again\n"
+        f"{log_file}:4:1: warning: cpu hot\n"
+    )
+
+
+def test_format_grep_column(log_file):
+    """Test that the column is the 1-based byte column of the first match on the line."""
+    matcher = TextMatcher(["disk", "again"])
+    assert format_grep(log_file, matcher.match_file_memmap(log_file)) == (
+        f"{log_file}:2:8: error: disk full, error again\n"
+    )
+
+
+def test_format_grep_color(log_file):
+    """Test that matches and the prefix are colorized with ANSI escape codes."""
+    matcher = TextMatcher(["cpu"])
+    output = format_grep(log_file, matcher.match_file_memmap(log_file), color=True)
+    assert output == (
+        f"\x1b[35m{log_file}\x1b[0m\x1b[36m:\x1b[0m\x1b[32m4\x1b[0m\x1b[36m:\x1b[0m"
+        "\x1b[32m10\x1b[0m\x1b[36m:\x1b[0m warning: \x1b[01;31mcpu\x1b[0m hot\n"
+    )
+
+
+def test_format_grep_overlapping(tmp_path):
+    """Test that overlapping matches are highlighted as one region."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(b"new york city\n")
+    matcher = TextMatcher(["new york", "york city"])
+    output = format_grep(str(path), matcher.match_file_memmap(str(path)), color=True)
+    assert output.endswith(" \x1b[01;31mnew york city\x1b[0m\n")
+
+
+def test_format_grep_collection(log_file):
+    """Test that a MatchCollection is accepted, as well as a list of tuples."""
+    matcher = TextMatcher(["error", "warning"])
+    collection = matcher.match_file_collection(log_file)
+    assert format_grep(log_file, collection) == format_grep(log_file, collection.to_list())
+
+
+def test_matcher_grep(log_file):
+    """Test that TextMatcher.grep scans and formats a file."""
+    matcher = TextMatcher(["error", "warning"])
+    assert matcher.grep(log_file) == format_grep(log_file, matcher.match_file_memmap(log_file))
+    assert matcher.grep(log_file, color=True).count("\x1b[01;31m") == 3
+
+
+def test_format_grep_errors(log_file):
+    """Test errors for missing files and invalid matches."""
+    with pytest.raises(IOError):
+        format_grep("nonexistent_file.txt", [])
+
+    with pytest.raises(TypeError):
+        format_grep(log_file, "error")
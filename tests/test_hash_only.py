@@ -0,0 +1,92 @@
+import hashlib
+import hmac
+import json
+import os
+import tempfile
+
+import pytest
+from voluta import TextMatcher, hash_text
+
+KEY = b"findings-salt"
+
+
+def digest(text):
+    """Reference HMAC-SHA256 of a matched text."""
+    return hmac.new(KEY, text, hashlib.sha256).hexdigest()
+
+
+def test_hashed_results():
+    """Test that results carry the digest of the matched text instead of the pattern."""
+    matcher = TextMatcher(["4111-1111", "secret"], case_insensitive=False, hash_key=KEY)
+    assert matcher.hashed
+
+    result = matcher.match_bytes(b"card 4111-1111 and secret")
+    assert result == [(5, 14, digest(b"4111-1111")), (19, 25, digest(b"secret"))]
+    assert all("secret" not in pattern for _, _, pattern in result)
+    assert "hashed=True" in repr(matcher)
+
+
+def test_hash_case_insensitive():
+    """Test that case-insensitive matches are folded so equal texts get equal digests."""
+    matcher = TextMatcher(["Secret"], hash_key=KEY)
+    result = matcher.match_bytes(b"SECRET secret")
+    assert [pattern for _, _, pattern in result] == [digest(b"secret")] * 2
+
+
+def test_hash_key_str():
+    """Test that the key may be given as a string."""
+    matcher = TextMatcher(["secret"], hash_key=KEY.decode())
+    assert matcher.match_bytes(b"secret")[0][2] == digest(b"secret")
+
+    with pytest.raises(TypeError):
+        TextMatcher(["secret"], hash_key=42)
+
+
+def test_hash_text():
+    """Test that hash_text computes the digests reported by matchers."""
+    assert hash_text(KEY, b"secret") == digest(b"secret")
+    assert hash_text(KEY, "secret") == digest(b"secret")
+    assert hash_text(KEY, "SeCrEt", case_insensitive=True) == digest(b"secret")
+
+    matcher = TextMatcher(["SeCrEt"], hash_key=KEY)
+    assert matcher.match_bytes(b"secret")[0][2] == hash_text(KEY, "SeCrEt", case_insensitive=True)
+
+
+def test_hashed_file_and_collection(tmp_path):
+    """Test that file scans and collections report digests as well."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(b"token=secret\n")
+    matcher = TextMatcher(["secret"], hash_key=KEY)
+
+    assert matcher.match_file(str(path)) == [(1, 6, 12, digest(b"secret"))]
+    assert matcher.match_file_memmap_parallel(str(path)) == [(6, 12, digest(b"secret"))]
+    collection = matcher.match_file_collection(str(path))
+    assert list(collection.group_by_pattern()) == [digest(b"secret")]
+
+
+def test_hashed_sink():
+    """Test that sinks store digests instead of the matched text."""
+    matcher = TextMatcher(["secret"], hash_key=KEY)
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        sink_path = os.path.join(tmp_dir, "findings.ndjson")
+        matcher.match_bytes_to_sink(b"secret and secret", sink_path)
+        with open(sink_path) as f:
+            content = f.read()
+
+    assert "secret" not in content
+    assert [json.loads(line)["pattern"] for line in content.splitlines()] == [digest(b"secret")] * 2
+
+
+def test_hashed_report(tmp_path):
+    """Test that reports leave out the matched text and its context."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(b"password: hunter2\n")
+    report = TextMatcher(["hunter2"], hash_key=KEY).scan_report([str(path)])
+
+    for rendered in (report.to_html(), report.to_markdown()):
+        assert "hunter2" not in rendered
+        assert "password" not in rendered
+        assert digest(b"hunter2") in rendered
+
+    with pytest.raises(ValueError):
+        TextMatcher(["hunter2"], hash_key=KEY).grep(str(path))
@@ -0,0 +1,97 @@
+from datetime import datetime, timezone
+
+import pytest
+from voluta import PatternError, TextMatcher
+
+
+def epoch(*args):
+    """Seconds since the epoch of a UTC date."""
+    return int(datetime(*args, tzinfo=timezone.utc).timestamp())
+
+
+def test_byte_buckets():
+    """Test that matches are counted per byte range, empty ranges omitted."""
+    matcher = TextMatcher(["key", "token"])
+    data = b"key key".ljust(100, b".") + b"." * 200 + b"token key"
+
+    buckets, unbucketed = matcher.match_histogram(data, bucket_size=100)
+    assert buckets == [(0, 100, 2), (300, 400, 2)]
+    assert unbucketed == 0
+
+    buckets, _ = matcher.match_histogram(data, bucket_size=100, patterns=["token"])
+    assert buckets == [(300, 400, 1)]
+
+
+def test_time_buckets():
+    """Test hourly buckets of ISO 8601 timestamps, with offsets and continuation lines."""
+    matcher = TextMatcher(["secret"])
+    log = b"\n".join(
+        [
+            b"secret before any timestamp",
+            b"2025-06-01T10:15:00Z started",
+            b"2025-06-01T10:59:59Z leaked secret",
+            b"2025-06-01T13:30:00+02:00 leaked secret again",
+            b"Traceback: secret in a stack trace",
+            b"    more secret",
+            b"2025-06-01T12:00:00.123Z secret",
+        ]
+    )
+    buckets, unbucketed = matcher.match_histogram(
+        log, timestamp_format="%Y-%m-%dT%H:%M:%S%z", interval=3600
+    )
+    assert unbucketed == 1
+    hour = 3600
+    assert buckets == [
+        (epoch(2025, 6, 1, 10), epoch(2025, 6, 1, 10) + hour, 1),
+        (epoch(2025, 6, 1, 11), epoch(2025, 6, 1, 11) + hour, 4),
+    ]
+
+    # Only the last line has fractional seconds, the lines above it are left unbucketed
+    buckets, unbucketed = matcher.match_histogram(
+        log, timestamp_format="%Y-%m-%dT%H:%M:%S.%f%z", interval=60
+    )
+    assert buckets == [(epoch(2025, 6, 1, 12), epoch(2025, 6, 1, 12, 1), 1)]
+    assert unbucketed == 5
+
+
+def test_syslog_timestamps(tmp_path):
+    """Test traditional syslog timestamps and daily buckets of a file."""
+    matcher = TextMatcher(["password"])
+    path = tmp_path / "messages"
+    path.write_bytes(
+        b"Jun  5 23:59:01 host sshd: password accepted\n"
+        b"Jun  6 00:00:01 host sshd: password accepted\n"
+        b"Jun 16 08:00:00 host sshd: password accepted\n"
+    )
+    buckets, _ = matcher.match_file_histogram(
+        str(path), timestamp_format="%b %e %H:%M:%S", interval=86400
+    )
+    assert [(start, count) for start, _, count in buckets] == [
+        (epoch(1970, 6, 5), 1),
+        (epoch(1970, 6, 6), 1),
+        (epoch(1970, 6, 16), 1),
+    ]
+
+
+def test_epoch_timestamps():
+    """Test timestamps in seconds since the epoch."""
+    matcher = TextMatcher(["x"])
+    data = b"[1700000000] x\n[1700000100] x x"
+    buckets, _ = matcher.match_histogram(data, timestamp_format="[%s]")
+    start = 1700000000 - 1700000000 % 3600
+    assert buckets == [(start, start + 3600, 3)]
+
+
+def test_invalid_buckets():
+    """Test that buckets must be configured one way."""
+    matcher = TextMatcher(["x"])
+    with pytest.raises(ValueError, match="either"):
+        matcher.match_histogram(b"x")
+    with pytest.raises(ValueError, match="either"):
+        matcher.match_histogram(b"x", bucket_size=10, timestamp_format="%s")
+    with pytest.raises(ValueError, match="bucket_size"):
+        matcher.match_histogram(b"x", bucket_size=0)
+    with pytest.raises(ValueError, match="interval"):
+        matcher.match_histogram(b"x", timestamp_format="%s", interval=0)
+    with pytest.raises(PatternError, match="%q"):
+        matcher.match_histogram(b"x", timestamp_format="%Y %q")
@@ -0,0 +1,106 @@
+import os
+
+import pytest
+from voluta import TextMatcher
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["secret", "api key"])
+
+
+def scanned(matcher, root, **kwargs):
+    """Relative paths of the files scanned under root."""
+    _, files = matcher.match_dir(str(root), **kwargs)
+    return [os.path.relpath(f.path, root) for f in files]
+
+
+def test_gitignore(matcher, tmp_path):
+    """Test that .gitignore globs exclude files and directories."""
+    (tmp_path / ".gitignore").write_text("# generated\nbuild/\n*.min.js\n!keep.min.js\n/top.log\n")
+    (tmp_path / "build").mkdir()
+    (tmp_path / "build" / "out.txt").write_text("secret")
+    (tmp_path / "app.min.js").write_text("secret")
+    (tmp_path / "keep.min.js").write_text("secret")
+    (tmp_path / "top.log").write_text("secret")
+    (tmp_path / "sub").mkdir()
+    (tmp_path / "sub" / "top.log").write_text("secret")
+    (tmp_path / "sub" / "lib.min.js").write_text("secret")
+    assert scanned(matcher, tmp_path) == [".gitignore", "keep.min.js", "sub/top.log"]
+    assert len(scanned(matcher, tmp_path, ignore_files=False)) == 7
+
+
+def test_nested_gitignore(matcher, tmp_path):
+    """Test that deeper ignore files are relative to their directory and take precedence."""
+    (tmp_path / ".gitignore").write_text("*.log\n")
+    (tmp_path / "logs").mkdir()
+    (tmp_path / "logs" / ".gitignore").write_text("!app.log\ndebug/*.txt\n")
+    (tmp_path / "logs" / "app.log").write_text("secret")
+    (tmp_path / "logs" / "other.log").write_text("secret")
+    (tmp_path / "logs" / "debug").mkdir()
+    (tmp_path / "logs" / "debug" / "a.txt").write_text("secret")
+    (tmp_path / "logs" / "debug" / "b.md").write_text("secret")
+    (tmp_path / "debug").mkdir()
+    (tmp_path / "debug" / "a.txt").write_text("secret")
+    assert scanned(matcher, tmp_path) == [
+        ".gitignore",
+        "debug/a.txt",
+        "logs/.gitignore",
+        "logs/app.log",
+        "logs/debug/b.md",
+    ]
+
+
+def test_double_star(matcher, tmp_path):
+    """Test that ** spans any number of directories."""
+    (tmp_path / ".gitignore").write_text("**/fixtures/**/*.json\n")
+    deep = tmp_path / "a" / "fixtures" / "b" / "c"
+    deep.mkdir(parents=True)
+    (deep / "x.json").write_text("secret")
+    (tmp_path / "a" / "fixtures" / "y.json").write_text("secret")
+    (tmp_path / "a" / "z.json").write_text("secret")
+    assert scanned(matcher, tmp_path) == [".gitignore", "a/z.json"]
+
+
+def test_git_directory_skipped(matcher, tmp_path):
+    """Test that .git directories are skipped when honoring ignore files."""
+    (tmp_path / ".git").mkdir()
+    (tmp_path / ".git" / "config").write_text("secret")
+    (tmp_path / "a.txt").write_text("secret")
+    assert scanned(matcher, tmp_path) == ["a.txt"]
+    assert scanned(matcher, tmp_path, ignore_files=False) == [".git/config", "a.txt"]
+
+
+def test_volutaignore_pattern_exceptions(matcher, tmp_path):
+    """Test that .volutaignore drops the matches of the listed patterns under a path."""
+    (tmp_path / ".volutaignore").write_text(
+        "vendor/\ndocs/** @secret\ndocs/api/** @api\\ key\n!docs/api/real.md @secret\n"
+    )
+    (tmp_path / "vendor").mkdir()
+    (tmp_path / "vendor" / "lib.txt").write_text("secret")
+    (tmp_path / "docs" / "api").mkdir(parents=True)
+    (tmp_path / "docs" / "guide.md").write_text("secret api key")
+    (tmp_path / "docs" / "api" / "ref.md").write_text("secret api key")
+    (tmp_path / "docs" / "api" / "real.md").write_text("secret api key")
+    (tmp_path / "main.txt").write_text("secret api key")
+    matches, files = matcher.match_dir(str(tmp_path))
+    assert sorted((os.path.relpath(p, tmp_path), n) for p, _, _, n in matches) == [
+        (".volutaignore", "secret"),
+        (".volutaignore", "secret"),
+        ("docs/api/real.md", "secret"),
+        ("docs/guide.md", "api key"),
+        ("main.txt", "api key"),
+        ("main.txt", "secret"),
+    ]
+    assert "vendor/lib.txt" not in [os.path.relpath(f.path, tmp_path) for f in files]
+    assert all(f.scanned for f in files)
+
+
+def test_volutaignore_overrides_gitignore(matcher, tmp_path):
+    """Test that .volutaignore takes precedence over .gitignore in the same directory."""
+    (tmp_path / ".gitignore").write_text("*.env\n")
+    (tmp_path / ".volutaignore").write_text("!prod.env\n")
+    (tmp_path / "dev.env").write_text("secret")
+    (tmp_path / "prod.env").write_text("secret")
+    assert scanned(matcher, tmp_path) == [".gitignore", ".volutaignore", "prod.env"]
@@ -0,0 +1,135 @@
+import random
+import threading
+
+import pytest
+from voluta import IncrementalMatcher, TextMatcher
+
+DATA = b"ushers said HIS password is hers; she_he pass\nhe her"
+
+
+@pytest.mark.parametrize(
+    "options",
+    [
+        {},
+        {"overlapping": False},
+        {"case_insensitive": False},
+        {"whole_word": True},
+        {"overlapping": False, "whole_word": True},
+        {"postprocess": "longest"},
+        {"postprocess": "merge"},
+    ],
+)
+def test_matches_full_rebuild(options):
+    """Test that patterns added in batches match like a matcher built on all of them."""
+    rng = random.Random(11)
+    for _ in range(50):
+        matcher = IncrementalMatcher(**options)
+        patterns = []
+        for _ in range(rng.randint(1, 12)):
+            batch = [
+                "".join(rng.choice("aAb ") for _ in range(rng.randint(1, 4)))
+                for _ in range(rng.randint(1, 5))
+            ]
+            assert matcher.add_patterns(batch) == len(patterns)
+            patterns.extend(batch)
+        data = "".join(rng.choice("aAbB \n") for _ in range(rng.randint(0, 80))).encode()
+        expected = TextMatcher(patterns, engine="aho", **options).match_bytes(data)
+        assert matcher.match_bytes(data) == expected, (patterns, data)
+
+
+def test_layers_merge():
+    """Test that layers are merged as patterns accumulate and compact leaves a single one."""
+    matcher = IncrementalMatcher()
+    assert len(matcher) == 0
+    assert matcher.layer_count == 0
+    assert matcher.match_bytes(DATA) == []
+
+    for i in range(1000):
+        matcher.add_patterns([f"token{i}"])
+    assert len(matcher) == 1000
+    assert matcher.layer_count == bin(1000).count("1")
+    assert matcher.patterns[:3] == ["token0", "token1", "token2"]
+
+    matcher.compact()
+    assert matcher.layer_count == 1
+    assert matcher.match_bytes(b"x token999 token12") == [
+        (2, 8, "token9"),
+        (2, 9, "token99"),
+        (2, 10, "token999"),
+        (11, 17, "token1"),
+        (11, 18, "token12"),
+    ]
+
+
+def test_max_layers():
+    """Test that the newest layers are merged beyond max_layers."""
+    matcher = IncrementalMatcher(["a" * 10, "b" * 10, "c" * 10, "d" * 10], max_layers=2)
+    for pattern in ["x", "y", "z"]:
+        matcher.add_patterns([pattern])
+        assert matcher.layer_count <= 2
+    assert matcher.match_bytes(b"zyx") == [(0, 1, "z"), (1, 2, "y"), (2, 3, "x")]
+
+
+def test_empty_patterns_dropped():
+    """Test that empty patterns are dropped and do not take an index."""
+    matcher = IncrementalMatcher(["he", ""])
+    assert matcher.add_patterns(["", "she"]) == 1
+    assert matcher.patterns == ["he", "she"]
+    assert matcher.add_patterns([]) == 2
+
+
+def test_file(tmp_path):
+    """Test memory-mapped file matching."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(DATA)
+    matcher = IncrementalMatcher(["he"])
+    matcher.add_patterns(["she", "hers"])
+    assert matcher.match_file_memmap(str(path)) == IncrementalMatcher(
+        ["he", "she", "hers"]
+    ).match_bytes(DATA)
+    with pytest.raises(IOError):
+        matcher.match_file_memmap(str(tmp_path / "missing.txt"))
+
+
+def test_options():
+    """Test the options, their defaults and validation."""
+    matcher = IncrementalMatcher(["he"], overlapping=False, postprocess="merge")
+    assert matcher.overlapping is False
+    assert matcher.case_insensitive is True
+    assert matcher.whole_word is False
+    assert matcher.postprocess == "merge"
+    assert matcher.max_layers == 16
+    assert matcher.pattern_count == 1
+    assert matcher.build_time_ms >= 0
+    assert matcher.memory_usage() > 0
+    assert repr(matcher) == "IncrementalMatcher(patterns=1, layers=1)"
+    with pytest.raises(ValueError, match="'merge' and 'longest'"):
+        IncrementalMatcher(["he"], postprocess="priority")
+    with pytest.raises(ValueError, match="Unknown postprocess"):
+        IncrementalMatcher(["he"], postprocess="first")
+    with pytest.raises(ValueError, match="max_layers"):
+        IncrementalMatcher(["he"], max_layers=0)
+
+
+def test_add_while_scanning():
+    """Test that scans running in other threads see a consistent pattern set."""
+    matcher = IncrementalMatcher(["token0"])
+    data = b" ".join(f"token{i}".encode() for i in range(200))
+    errors = []
+
+    def scan():
+        for _ in range(50):
+            found = {pattern for _, _, pattern in matcher.match_bytes(data)}
+            if "token0" not in found:
+                errors.append(found)
+
+    threads = [threading.Thread(target=scan) for _ in range(4)]
+    for thread in threads:
+        thread.start()
+    for i in range(1, 200):
+        matcher.add_patterns([f"token{i}"])
+    for thread in threads:
+        thread.join()
+
+    assert errors == []
+    assert len(matcher.match_bytes(data)) == len(TextMatcher(matcher.patterns).match_bytes(data))
@@ -0,0 +1,44 @@
+from voluta import TextMatcher
+
+
+def test_pattern_count():
+    """Test that the pattern count leaves out empty patterns."""
+    matcher = TextMatcher(["error", "", "warning"])
+    assert matcher.pattern_count == 2 == len(matcher)
+
+
+def test_state_count():
+    """Test that states are counted once per distinct prefix, plus the root."""
+    # he, she, his, hers: h, he, her, hers, s, sh, she, hi, his
+    assert TextMatcher(["he", "she", "his", "hers"]).state_count == 10
+    assert TextMatcher(["abc", "abc"]).state_count == 4
+
+
+def test_state_count_case():
+    """Test that case-insensitive matchers share the states of case variants."""
+    patterns = ["Error", "ERROR", "warn"]
+    assert TextMatcher(patterns).state_count == 10
+    assert TextMatcher(patterns, case_insensitive=False).state_count == 14
+
+
+def test_state_count_same_across_engines():
+    """Test that state counts do not depend on the engine."""
+    patterns = ["alpha", "alphabet", "beta"]
+    assert (
+        TextMatcher(patterns, engine="aho").state_count
+        == TextMatcher(patterns, engine="memmem").state_count
+    )
+
+
+def test_memory_usage_grows():
+    """Test that memory usage tracks the size of the dictionary."""
+    small = TextMatcher([f"token{i}" for i in range(10)], engine="aho")
+    large = TextMatcher([f"token{i}" for i in range(10_000)], engine="aho")
+    assert 0 < small.memory_usage() < large.memory_usage()
+    assert TextMatcher(["token"], engine="memmem").memory_usage() > 0
+
+
+def test_build_time():
+    """Test that the build time is recorded."""
+    matcher = TextMatcher([f"token{i}" for i in range(10_000)])
+    assert matcher.build_time_ms > 0
@@ -0,0 +1,86 @@
+import asyncio
+
+import pytest
+from voluta import TextMatcher
+
+DATA = b"token ok\ncaf\xe9 token\n\xff\xfe\nlast token\n"
+
+
+@pytest.fixture
+def path(tmp_path):
+    """Fixture that provides a file with lines that are not valid UTF-8."""
+    path = tmp_path / "mixed.log"
+    path.write_bytes(DATA)
+    return str(path)
+
+
+def test_default_is_error(path):
+    """Test that invalid lines fail the scan by default."""
+    matcher = TextMatcher(["token"])
+    assert matcher.invalid_utf8 == "error"
+    with pytest.raises(IOError, match="valid UTF-8"):
+        matcher.match_file(path)
+
+
+def test_lossy(path):
+    """Test that invalid sequences are replaced, offsets pointing into the replaced line."""
+    matcher = TextMatcher(["token", "�"], invalid_utf8="lossy")
+    assert matcher.invalid_utf8 == "lossy"
+    assert matcher.match_file(path) == [
+        (1, 0, 5, "token"),
+        (2, 3, 6, "�"),
+        (2, 7, 12, "token"),
+        (3, 0, 3, "�"),
+        (3, 3, 6, "�"),
+        (4, 5, 10, "token"),
+    ]
+
+
+def test_skip(path):
+    """Test that invalid lines are skipped but still counted."""
+    matcher = TextMatcher(["token"], invalid_utf8="skip")
+    assert matcher.match_file(path) == [(1, 0, 5, "token"), (4, 5, 10, "token")]
+
+
+def test_binary(path):
+    """Test that invalid lines are searched as raw bytes."""
+    matcher = TextMatcher(["token"], invalid_utf8="binary")
+    assert matcher.match_file(path) == [
+        (1, 0, 5, "token"),
+        (2, 5, 10, "token"),
+        (4, 5, 10, "token"),
+    ]
+
+
+def test_records_and_async(path, tmp_path):
+    """Test that the policy applies to records and to amatch_file."""
+    matcher = TextMatcher(["token"], invalid_utf8="skip")
+    records = tmp_path / "export.bin"
+    records.write_bytes(b"token\x00caf\xe9 token\x00last token")
+    assert matcher.match_file(str(records), delimiter=b"\x00") == [
+        (1, 0, 5, "token"),
+        (3, 5, 10, "token"),
+    ]
+
+    async def scan():
+        return await matcher.amatch_file(path)
+
+    assert asyncio.run(scan()) == matcher.match_file(path)
+
+
+def test_valid_files_unchanged(tmp_path):
+    """Test that every policy reports the same matches on valid UTF-8."""
+    path = tmp_path / "valid.log"
+    path.write_text("café token\n秘密 token\n")
+    results = [
+        TextMatcher(["token"], invalid_utf8=policy).match_file(str(path))
+        for policy in ("error", "lossy", "skip", "binary")
+    ]
+    assert all(res == results[0] for res in results)
+    assert results[0] == [(1, 6, 11, "token"), (2, 7, 12, "token")]
+
+
+def test_unknown_policy():
+    """Test that unknown policies are rejected."""
+    with pytest.raises(ValueError, match="Unknown invalid_utf8 policy 'latin1'"):
+        TextMatcher(["token"], invalid_utf8="latin1")
@@ -0,0 +1,50 @@
+import pytest
+from voluta import TextMatcher
+
+LOG = (
+    "user=alice password=hunter2 token=abc\n"
+    "user=bob token=abc\n"
+    "PASSWORD reset for carol, token=xyz\n"
+)
+
+
+@pytest.fixture
+def log_file(tmp_path):
+    """Fixture that provides a small log file."""
+    path = tmp_path / "app.log"
+    path.write_text(LOG)
+    return str(path)
+
+
+def test_only_anchored_lines_scanned(log_file):
+    """Test that match_file only searches the lines containing an anchor."""
+    matcher = TextMatcher(["token", "user"], line_filter=["password"])
+    assert matcher.line_filter == ["password"]
+    assert matcher.match_file(log_file) == [
+        (1, 0, 4, "user"),
+        (1, 28, 33, "token"),
+        (3, 26, 31, "token"),
+    ]
+    assert len(TextMatcher(["token", "user"]).match_file(log_file)) == 5
+
+
+def test_anchor_case_follows_matcher(log_file):
+    """Test that anchors fold case like the patterns."""
+    matcher = TextMatcher(["token"], case_insensitive=False, line_filter=["password"])
+    assert matcher.match_file(log_file) == [(1, 28, 33, "token")]
+
+
+def test_several_anchors(log_file):
+    """Test that a line containing any of the anchors is searched."""
+    matcher = TextMatcher(["token"], line_filter=["bob", "carol"])
+    assert [line for line, _, _, _ in matcher.match_file(log_file)] == [2, 3]
+
+
+def test_line_filter_options():
+    """Test the default and the anchor validation."""
+    assert TextMatcher(["token"]).line_filter is None
+    assert TextMatcher(["token"], line_filter=["", "pass"]).line_filter == ["pass"]
+    with pytest.raises(ValueError, match="at least one non-empty anchor"):
+        TextMatcher(["token"], line_filter=[""])
+    with pytest.raises(ValueError, match="at least one non-empty anchor"):
+        TextMatcher(["token"], line_filter=[])
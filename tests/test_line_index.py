@@ -0,0 +1,60 @@
+import pytest
+from voluta import LineIndex, TextMatcher
+
+DATA = "first line\nsécurité: secret\n\nlast secret".encode()
+
+
+@pytest.fixture
+def index():
+    """Fixture that provides the index of a few lines with accented characters."""
+    return LineIndex(DATA)
+
+
+def test_byte_positions(index):
+    """Test that offsets convert to 1-based lines and 0-based byte columns."""
+    assert index.line_count == 4
+    assert index.byte_count == len(DATA)
+    assert index.position(0) == (1, 0)
+    assert index.position(10) == (1, 10)
+    assert index.position(11) == (2, 0)
+    assert index.position(DATA.index(b"secret")) == (2, 12)
+    assert index.position(len(DATA)) == (4, 11)
+    assert index.line_start(3) == DATA.index(b"\n\n") + 1
+    assert index.line_start(0) is None
+    assert index.line_start(5) is None
+
+
+def test_char_columns(index):
+    """Test that character columns count UTF-8 code points."""
+    offset = DATA.index(b"secret")
+    assert index.position(offset, chars=True) == (2, 10)
+    assert index.positions([0, offset], chars=True) == [(1, 0), (2, 10)]
+
+
+def test_locate_matches(index, tmp_path):
+    """Test converting match_bytes and memmap results, with an index built from a file."""
+    matcher = TextMatcher(["secret"])
+    assert index.locate(matcher.match_bytes(DATA)) == [
+        (2, 12, 2, 18, "secret"),
+        (4, 5, 4, 11, "secret"),
+    ]
+    path = tmp_path / "input.txt"
+    path.write_bytes(DATA)
+    from_file = LineIndex(str(path))
+    assert repr(from_file) == f"LineIndex(lines=4, bytes={len(DATA)})"
+    matches = matcher.match_file_memmap(str(path))
+    assert from_file.locate(matches, chars=True) == [
+        (2, 10, 2, 16, "secret"),
+        (4, 5, 4, 11, "secret"),
+    ]
+
+
+def test_errors(index, tmp_path):
+    """Test offsets past the end, missing files and bad inputs."""
+    with pytest.raises(ValueError, match="past the end"):
+        index.position(len(DATA) + 1)
+    with pytest.raises(IOError):
+        LineIndex(str(tmp_path / "missing.txt"))
+    with pytest.raises(TypeError):
+        LineIndex(42)
+    assert LineIndex(b"").position(0) == (1, 0)
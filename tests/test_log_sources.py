@@ -0,0 +1,218 @@
+import json
+import os
+import socket
+import stat
+import struct
+import sys
+import threading
+import time
+
+import pytest
+from voluta import PatternError, ScanIOError, TextMatcher
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["password", "token"])
+
+
+def read_ndjson(path):
+    """Records of an NDJSON sink."""
+    with open(path) as f:
+        return [json.loads(line) for line in f]
+
+
+def free_port(kind):
+    """A local port free for sockets of the given kind."""
+    with socket.socket(socket.AF_INET, kind) as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+def fake_journalctl(tmp_path, export, exit_code=0):
+    """Write a journalctl stand-in printing the export and its arguments, returning its path."""
+    (tmp_path / "export.bin").write_bytes(export)
+    script = tmp_path / "journalctl"
+    script.write_text(
+        f"#!{sys.executable}\n"
+        "import sys\n"
+        f"open({str(tmp_path / 'args.json')!r}, 'w').write(repr(sys.argv[1:]))\n"
+        f"sys.stdout.buffer.write(open({str(tmp_path / 'export.bin')!r}, 'rb').read())\n"
+        "sys.stdout.flush()\n"
+        f"if {exit_code}:\n"
+        "    sys.stderr.write('Failed to open journal')\n"
+        f"sys.exit({exit_code})\n"
+    )
+    script.chmod(script.stat().st_mode | stat.S_IEXEC)
+    return str(script)
+
+
+def entry(message, binary=False):
+    """An entry of the journal export format."""
+    fields = b"__CURSOR=s=1\nPRIORITY=6\n"
+    if binary:
+        fields += b"MESSAGE\n" + struct.pack("<Q", len(message)) + message + b"\n"
+    else:
+        fields += b"MESSAGE=" + message + b"\n"
+    return fields + b"_SYSTEMD_UNIT=api.service\n\n"
+
+
+def test_journald(matcher, tmp_path):
+    """Test that the messages of journal entries are scanned, binary ones included."""
+    if sys.platform == "win32":
+        pytest.skip("journald is Linux only")
+    first = b"login with password=hunter2"
+    second = b"binary\ntoken\x00"
+    export = entry(first) + entry(b"nothing here") + entry(second, binary=True)
+    journalctl = fake_journalctl(tmp_path, export)
+    sink = str(tmp_path / "findings.ndjson")
+
+    summary = matcher.match_journald_to_sink(
+        sink, follow=False, args=["--since=now"], journalctl=journalctl
+    )
+    assert summary.matches_written == 2
+    assert summary.bytes_scanned == len(first) + len(b"nothing here") + len(second)
+    third = len(first) + 1 + len(b"nothing here") + 1
+    assert read_ndjson(sink) == [
+        {"start": 11, "end": 19, "pattern": "password"},
+        {"start": third + 7, "end": third + 12, "pattern": "token"},
+    ]
+    assert eval((tmp_path / "args.json").read_text()) == ["--output=export", "--since=now"]
+
+
+def test_journald_limits(matcher, tmp_path):
+    """Test that max_records stops the scan of a followed journal."""
+    if sys.platform == "win32":
+        pytest.skip("journald is Linux only")
+    journalctl = fake_journalctl(tmp_path, entry(b"password") * 3)
+    sink = str(tmp_path / "findings.ndjson")
+    summary = matcher.match_journald_to_sink(sink, max_records=2, journalctl=journalctl)
+    assert summary.matches_written == 2
+    assert "--follow" in eval((tmp_path / "args.json").read_text())
+
+
+def test_journald_errors(matcher, tmp_path):
+    """Test that journalctl failures are raised with their error output."""
+    if sys.platform == "win32":
+        pytest.skip("journald is Linux only")
+    sink = str(tmp_path / "findings.ndjson")
+    journalctl = fake_journalctl(tmp_path, entry(b"password"), exit_code=1)
+    with pytest.raises(ScanIOError, match="Failed to open journal"):
+        matcher.match_journald_to_sink(sink, follow=False, journalctl=journalctl)
+    # Findings before the failure are kept
+    assert len(read_ndjson(sink)) == 1
+    with pytest.raises(ScanIOError, match="Cannot run"):
+        matcher.match_journald_to_sink(sink, journalctl=str(tmp_path / "missing"))
+
+
+def send_tcp(port, payload):
+    """Connect once the scan listens and send the payload."""
+    for _ in range(100):
+        try:
+            with socket.create_connection(("127.0.0.1", port)) as conn:
+                conn.sendall(payload)
+                return
+        except ConnectionRefusedError:
+            time.sleep(0.05)
+
+
+def test_syslog_tcp(matcher, tmp_path):
+    """Test that newline-delimited and octet-counted messages are scanned."""
+    port = free_port(socket.SOCK_STREAM)
+    messages = b"<34>1 host app - - - token one\n" + b"27 <34>1 host app - - password"
+    sender = threading.Thread(target=send_tcp, args=(port, messages + b"<34>no secret\n"))
+    sender.start()
+    sink = str(tmp_path / "findings.ndjson")
+    summary = matcher.match_syslog_to_sink(f"tcp://127.0.0.1:{port}", sink, max_records=3)
+    sender.join()
+
+    assert summary.matches_written == 2
+    first = b"<34>1 host app - - - token one"
+    assert read_ndjson(sink) == [
+        {"start": 21, "end": 26, "pattern": "token"},
+        {"start": len(first) + 1 + 19, "end": len(first) + 1 + 27, "pattern": "password"},
+    ]
+
+
+def test_syslog_udp(matcher, tmp_path):
+    """Test that datagrams are scanned until max_records."""
+    port = free_port(socket.SOCK_DGRAM)
+    done = threading.Event()
+
+    def send():
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as s:
+            while not done.is_set():
+                s.sendto(b"<13>user password\n", ("127.0.0.1", port))
+                time.sleep(0.02)
+
+    sender = threading.Thread(target=send)
+    sender.start()
+    try:
+        sink = str(tmp_path / "findings.csv")
+        summary = matcher.match_syslog_to_sink(f"udp://127.0.0.1:{port}", sink, max_records=3)
+    finally:
+        done.set()
+        sender.join()
+    assert summary.matches_written == 3
+    assert summary.bytes_scanned == 3 * len(b"<13>user password")
+
+
+def test_syslog_unix(matcher, tmp_path):
+    """Test that messages of a Unix datagram socket are scanned and the socket removed."""
+    if not hasattr(socket, "AF_UNIX"):
+        pytest.skip("Unix sockets are not supported on this platform")
+    path = str(tmp_path / "log.sock")
+    done = threading.Event()
+
+    def send():
+        with socket.socket(socket.AF_UNIX, socket.SOCK_DGRAM) as s:
+            while not done.is_set():
+                try:
+                    s.sendto(b"<13>token\x00", path)
+                except OSError:
+                    pass
+                time.sleep(0.02)
+
+    sender = threading.Thread(target=send)
+    sender.start()
+    try:
+        summary = matcher.match_syslog_to_sink(
+            f"unix://{path}", str(tmp_path / "findings.ndjson"), max_records=2
+        )
+    finally:
+        done.set()
+        sender.join()
+    assert summary.matches_written == 2
+    assert read_ndjson(tmp_path / "findings.ndjson")[1] == {
+        "start": 14,
+        "end": 19,
+        "pattern": "token",
+    }
+    assert not os.path.exists(path)
+
+
+def test_idle_timeout(matcher, tmp_path):
+    """Test that a source without records stops after the idle timeout."""
+    port = free_port(socket.SOCK_DGRAM)
+    start = time.monotonic()
+    summary = matcher.match_syslog_to_sink(
+        f"udp://127.0.0.1:{port}", str(tmp_path / "findings.ndjson"), idle_timeout=0.3
+    )
+    assert 0.3 <= time.monotonic() - start < 5
+    assert summary.matches_written == 0
+    assert summary.bytes_scanned == 0
+
+
+def test_invalid_arguments(matcher, tmp_path):
+    """Test that invalid addresses and limits are rejected."""
+    sink = str(tmp_path / "findings.ndjson")
+    for address in ["localhost:514", "http://localhost:514", "udp://"]:
+        with pytest.raises(PatternError, match="syslog address"):
+            matcher.match_syslog_to_sink(address, sink)
+    with pytest.raises(ValueError, match="max_records"):
+        matcher.match_syslog_to_sink("udp://127.0.0.1:0", sink, max_records=0)
+    with pytest.raises(ValueError, match="idle_timeout"):
+        matcher.match_syslog_to_sink("udp://127.0.0.1:0", sink, idle_timeout=-1)
+    with pytest.raises(ScanIOError):
+        matcher.match_syslog_to_sink("udp://256.0.0.1:514", sink, idle_timeout=0.1)
@@ -0,0 +1,184 @@
+import json
+import os
+import tempfile
+
+import pytest
+from voluta import MatchCollection, PatternError, TextMatcher, TextMatcherBuilder
+
+
+@pytest.fixture
+def collection():
+    """Fixture that provides a collection of matches over a small text."""
+    matcher = TextMatcher(["fox", "dog", "quick"])
+    return matcher.match_bytes_collection(b"the quick fox and the dog and the fox")
+
+
+def test_collection_sequence(collection):
+    """Test len, indexing and iteration of a collection."""
+    assert isinstance(collection, MatchCollection)
+    assert len(collection) == 4
+
+    assert collection[0] == (4, 9, "quick")
+    assert collection[-1] == (34, 37, "fox")
+    with pytest.raises(IndexError):
+        collection[4]
+
+    assert list(collection) == collection.to_list()
+
+
+def test_collection_matches_match_bytes():
+    """Test that a collection holds the same matches as match_bytes."""
+    matcher = TextMatcher(["abc", "bcd", "cde"])
+    data = b"abcde abcde"
+    assert matcher.match_bytes_collection(data).to_list() == matcher.match_bytes(data)
+
+
+def test_collection_filter(collection):
+    """Test filtering by pattern and by range."""
+    assert collection.filter(pattern="fox").to_list() == [(10, 13, "fox"), (34, 37, "fox")]
+    assert len(collection.filter(pattern=["fox", "dog"])) == 3
+    assert len(collection.filter(pattern="cat")) == 0
+
+    assert collection.filter(range=(0, 13)).to_list() == [(4, 9, "quick"), (10, 13, "fox")]
+    # Matches crossing the range boundary are excluded
+    assert collection.filter(range=(0, 12)).to_list() == [(4, 9, "quick")]
+
+    assert collection.filter(pattern="fox", range=(20, 40)).to_list() == [(34, 37, "fox")]
+
+
+def test_collection_filter_tags():
+    """Test filtering by the tags of the patterns."""
+    tags = [["animal", "wild"], ["animal"], []]
+    matcher = TextMatcherBuilder().add_patterns(["fox", "dog", "quick"], tags=tags).build()
+    collection = matcher.match_bytes_collection(b"the quick fox and the dog and the fox")
+    assert collection.filter(tag="wild").to_list() == [(10, 13, "fox"), (34, 37, "fox")]
+    assert len(collection.filter(tag=["wild", "animal"])) == 3
+    assert collection.filter(tag="animal", range=(0, 30)).to_list() == [(10, 13, "fox"), (22, 25, "dog")]
+    # Derived collections keep the tags
+    assert len(collection.filter(range=(0, 20)).filter(tag="animal")) == 1
+
+    with pytest.raises(PatternError, match="Unknown tag 'pet'"):
+        collection.filter(tag="pet")
+    with pytest.raises(ValueError, match="tag must be a string"):
+        collection.filter(tag=1)
+    with pytest.raises(PatternError, match="Unknown tag"):
+        TextMatcher(["fox"]).match_bytes_collection(b"fox").filter(tag="animal")
+
+
+def test_collection_group_by_pattern(collection):
+    """Test grouping matches by pattern."""
+    groups = collection.group_by_pattern()
+    assert set(groups) == {"fox", "dog", "quick"}
+    assert len(groups["fox"]) == 2
+    assert groups["dog"].to_list() == [(22, 25, "dog")]
+
+
+def test_collection_exports(collection):
+    """Test dict and JSON exports."""
+    dicts = collection.to_dicts()
+    assert dicts[0] == {"start": 4, "end": 9, "pattern": "quick"}
+    assert len(dicts) == len(collection)
+
+    assert json.loads(collection.to_json()) == dicts
+
+
+def test_collection_json_escaping():
+    """Test that special characters in patterns are escaped in JSON."""
+    patterns = ['say "hi"', "back\\slash", "tab\tchar"]
+    matcher = TextMatcher(patterns)
+    data = 'say "hi" back\\slash tab\tchar'.encode()
+
+    records = json.loads(matcher.match_bytes_collection(data).to_json())
+    assert [r["pattern"] for r in records] == patterns
+
+
+def test_collection_json_schema_v1():
+    """Test that collections of version 1 matchers export version 1 findings."""
+    matcher = TextMatcher(["fox", "dog"], schema_version=1)
+    collection = matcher.match_bytes_collection(b"fox dog")
+
+    findings = json.loads(collection.to_json())
+    assert findings[0] == {
+        "schema_version": 1, "scan_id": None, "file": None, "start": 0, "end": 3, "pattern": "fox"
+    }
+    assert json.loads(collection.filter(pattern="dog").to_json()) == findings[1:]
+
+    # to_dicts keeps its shape
+    assert collection.to_dicts()[0] == {"start": 0, "end": 3, "pattern": "fox"}
+
+
+def test_collection_to_pandas(collection):
+    """Test building a DataFrame from a collection."""
+    pd = pytest.importorskip("pandas")
+
+    df = collection.to_pandas()
+    assert isinstance(df, pd.DataFrame)
+    assert list(df.columns) == ["start", "end", "pattern"]
+    assert len(df) == len(collection)
+
+
+def test_collection_to_pandas_columns(collection):
+    """Test that DataFrame columns are typed arrays with a categorical pattern."""
+    pd = pytest.importorskip("pandas")
+
+    df = collection.to_pandas()
+    assert str(df["start"].dtype) == "uint64"
+    assert str(df["end"].dtype) == "uint64"
+    assert isinstance(df["pattern"].dtype, pd.CategoricalDtype)
+    assert list(df["pattern"].cat.categories) == ["fox", "dog", "quick"]
+    assert list(df.itertuples(index=False, name=None)) == collection.to_list()
+
+    # Columns are writable
+    df.loc[0, "start"] = 0
+
+    empty = TextMatcher(["fox"]).match_bytes_collection(b"").to_pandas()
+    assert list(empty.columns) == ["start", "end", "pattern"]
+    assert len(empty) == 0
+
+
+def test_collection_to_arrow(collection):
+    """Test building a pyarrow Table with a dictionary-encoded pattern column."""
+    pa = pytest.importorskip("pyarrow")
+
+    table = collection.to_arrow()
+    assert isinstance(table, pa.Table)
+    assert table.column_names == ["start", "end", "pattern"]
+    assert table.schema.field("start").type == pa.uint64()
+    assert table.schema.field("end").type == pa.uint64()
+    assert pa.types.is_dictionary(table.schema.field("pattern").type)
+
+    rows = zip(*(table.column(name).to_pylist() for name in table.column_names))
+    assert list(rows) == collection.to_list()
+
+
+def test_collection_to_arrow_shared_names():
+    """Test that patterns sharing a name share a dictionary entry."""
+    pa = pytest.importorskip("pyarrow")
+
+    matcher = TextMatcher(["fox", "dog", "fox"])
+    collection = matcher.match_bytes_collection(b"fox dog fox")
+    pattern = collection.to_arrow().column("pattern").combine_chunks()
+    assert pattern.dictionary.to_pylist() == ["fox", "dog"]
+    assert pattern.to_pylist() == [m[2] for m in collection.to_list()]
+
+    empty = TextMatcher(["fox"]).match_bytes_collection(b"").to_arrow()
+    assert empty.num_rows == 0
+
+
+def test_file_collection():
+    """Test that file collections are sorted and agree with memmap matching."""
+    matcher = TextMatcher(["error", "warning"])
+    content = "error warning info\n" * 10000
+
+    with tempfile.NamedTemporaryFile(mode="w", delete=False) as f:
+        f.write(content)
+        temp_path = f.name
+
+    try:
+        collection = matcher.match_file_collection(temp_path, 4096)
+        assert collection.to_list() == matcher.match_file_memmap(temp_path)
+    finally:
+        os.unlink(temp_path)
+
+    with pytest.raises(IOError):
+        matcher.match_file_collection("/nonexistent/file.txt")
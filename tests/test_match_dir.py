@@ -0,0 +1,125 @@
+import os
+import time
+
+import pytest
+from voluta import FileSummary, TextMatcher
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["secret", "token"])
+
+
+@pytest.fixture
+def tree(tmp_path):
+    """Fixture that provides a small directory tree."""
+    (tmp_path / "a.txt").write_bytes(b"a secret")
+    (tmp_path / "empty.txt").write_bytes(b"")
+    (tmp_path / "sub").mkdir()
+    (tmp_path / "sub" / "b.log").write_bytes(b"token and secret")
+    (tmp_path / "sub" / "big.bin").write_bytes(b"secret " * 1000)
+    return tmp_path
+
+
+def test_matches_and_summaries(matcher, tree):
+    """Test that matches carry their file and every file is summarized."""
+    matches, files = matcher.match_dir(str(tree))
+    assert [(os.path.relpath(p, tree), s, e, n) for p, s, e, n in matches][:4] == [
+        ("a.txt", 2, 8, "secret"),
+        ("sub/b.log", 0, 5, "token"),
+        ("sub/b.log", 10, 16, "secret"),
+        ("sub/big.bin", 0, 6, "secret"),
+    ]
+    assert len(matches) == 1003
+    assert all(isinstance(f, FileSummary) for f in files)
+    assert [os.path.relpath(f.path, tree) for f in files] == [
+        "a.txt",
+        "empty.txt",
+        "sub/b.log",
+        "sub/big.bin",
+    ]
+    assert [(f.matches, f.bytes_scanned) for f in files] == [(1, 8), (0, 0), (2, 16), (1000, 7000)]
+    assert all(f.scanned and f.skipped is None and f.error is None for f in files)
+    assert all(f.elapsed >= 0 for f in files)
+    assert repr(files[0]) == f"FileSummary(path={str(tree / 'a.txt')!r}, matches=1, bytes_scanned=8)"
+
+
+def test_options(matcher, tree):
+    """Test the recursive and max_file_size options."""
+    matches, files = matcher.match_dir(str(tree), recursive=False)
+    assert [os.path.basename(f.path) for f in files] == ["a.txt", "empty.txt"]
+    assert len(matches) == 1
+
+    matches, files = matcher.match_dir(str(tree), max_file_size=100)
+    big = files[-1]
+    assert big.skipped == "larger than max_file_size"
+    assert not big.scanned and big.matches == 0 and big.bytes_scanned == 0
+    assert len(matches) == 3
+
+
+def test_links(matcher, tree):
+    """Test that links are skipped unless followed, linked directories being walked once."""
+    os.symlink(tree / "a.txt", tree / "link.txt")
+    os.symlink(tree / "sub", tree / "sub" / "loop")
+    _, files = matcher.match_dir(str(tree))
+    skipped = {os.path.relpath(f.path, tree): f.skipped for f in files if f.skipped}
+    assert skipped == {"link.txt": "symbolic link", "sub/loop": "symbolic link"}
+
+    matches, files = matcher.match_dir(str(tree), follow_links=True)
+    assert [os.path.relpath(f.path, tree) for f in files if f.scanned] == [
+        "a.txt",
+        "empty.txt",
+        "link.txt",
+        "sub/b.log",
+        "sub/big.bin",
+    ]
+    assert len(matches) == 1004
+
+
+def test_dangling_link(matcher, tree):
+    """Test that a followed link to nothing is reported with its error."""
+    os.symlink(tree / "gone.txt", tree / "dangling.txt")
+    matches, files = matcher.match_dir(str(tree), follow_links=True)
+    failed = [f for f in files if f.error]
+    assert [os.path.basename(f.path) for f in failed] == ["dangling.txt"]
+    assert not failed[0].scanned
+    assert len(matches) == 1003
+
+
+def test_unreadable_files(matcher, tree):
+    """Test that unreadable files are reported with their error instead of failing the scan."""
+    if os.geteuid() == 0:
+        pytest.skip("root can read every file")
+    locked = tree / "locked.txt"
+    locked.write_bytes(b"secret")
+    locked.chmod(0)
+    try:
+        matches, files = matcher.match_dir(str(tree))
+    finally:
+        locked.chmod(0o644)
+    failed = [f for f in files if f.error]
+    assert [os.path.basename(f.path) for f in failed] == ["locked.txt"]
+    assert not failed[0].scanned
+    assert len(matches) == 1003
+
+
+def test_missing_root(matcher, tmp_path):
+    """Test that a root that cannot be listed fails the scan."""
+    with pytest.raises(IOError):
+        matcher.match_dir(str(tmp_path / "missing"))
+
+
+def test_throttle(matcher, tmp_path):
+    """Test that the read rate limit holds for the whole walk, not for each file."""
+    for i in range(8):
+        (tmp_path / f"{i}.txt").write_bytes(b"secret " * 70 + b"......")
+    expected = matcher.match_dir(str(tmp_path))
+
+    started = time.monotonic()
+    throttled = matcher.match_dir(str(tmp_path), max_bytes_per_sec=10_000)
+    elapsed = time.monotonic() - started
+
+    assert throttled[0] == expected[0]
+    # 4000 bytes at 10000 bytes per second, whatever the number of threads
+    assert elapsed >= 0.35
@@ -0,0 +1,89 @@
+import pytest
+from voluta import PatternError, TextMatcher, TextMatcherBuilder
+
+
+@pytest.fixture
+def log_path(tmp_path):
+    """Fixture that provides a log file of a few thousand lines of varying length."""
+    lines = []
+    for i in range(3000):
+        if i % 7 == 0:
+            lines.append(f"{i} ERROR disk full, password=hunter{i}")
+        elif i % 11 == 0:
+            lines.append(f"{i} warning: slow request " + "x" * (i % 50))
+        else:
+            lines.append(f"{i} info ok")
+    path = tmp_path / "app.log"
+    path.write_bytes("\n".join(lines).encode())
+    return str(path)
+
+
+def test_same_matches_as_match_file(log_path):
+    """Test that every block size gives the matches of match_file, in the same order."""
+    matcher = TextMatcher(["error", "warning", "password", "ok"])
+    expected = matcher.match_file(log_path)
+    assert len(expected) > 3000
+    for chunk_size in [1, 7, 100, 4096, 1 << 20]:
+        assert matcher.match_file_parallel(log_path, chunk_size=chunk_size) == expected
+    assert matcher.match_file_parallel(log_path) == expected
+    assert matcher.match_file_parallel(log_path, chunk_size=64, n_threads=4) == expected
+
+
+def test_line_numbers_across_blocks(tmp_path):
+    """Test that line numbers continue across blocks, blank and unterminated lines included."""
+    path = tmp_path / "lines.txt"
+    path.write_bytes(b"token\n\n\nno\ntoken token\r\n\ntoken")
+    matcher = TextMatcher(["token"])
+    expected = [
+        (1, 0, 5, "token"),
+        (5, 0, 5, "token"),
+        (5, 6, 11, "token"),
+        (7, 0, 5, "token"),
+    ]
+    assert matcher.match_file(str(path)) == expected
+    for chunk_size in [1, 2, 5, 6, 13, 100]:
+        assert matcher.match_file_parallel(str(path), chunk_size=chunk_size) == expected
+
+
+def test_options_apply_per_line(log_path):
+    """Test that line prefilters, post-processing and min counts behave as in match_file."""
+    for options in [
+        {"line_filter": ["error"]},
+        {"postprocess": "longest"},
+        {"min_count": {"warning": 1000}},
+        {"whole_word": True, "overlapping": False},
+    ]:
+        matcher = TextMatcher(["error", "error disk", "warning", "password"], **options)
+        assert matcher.match_file_parallel(log_path, chunk_size=500) == matcher.match_file(log_path)
+
+
+def test_invalid_utf8(tmp_path):
+    """Test that invalid lines are handled as configured."""
+    path = tmp_path / "mixed.log"
+    path.write_bytes(b"token ok\ncaf\xe9 token\n\xff\xfe\nlast token\n")
+    matcher = TextMatcher(["token"], invalid_utf8="skip")
+    assert matcher.match_file_parallel(str(path), chunk_size=4) == [
+        (1, 0, 5, "token"),
+        (4, 5, 10, "token"),
+    ]
+    with pytest.raises(IOError, match="valid UTF-8"):
+        TextMatcher(["token"]).match_file_parallel(str(path), chunk_size=4)
+
+
+def test_overrides_and_errors(tmp_path, log_path):
+    """Test per-call overrides, empty files and invalid arguments."""
+    matcher = TextMatcherBuilder().add_patterns(["error", "info"], tags=[["alert"], ["noise"]]).build()
+    assert matcher.match_file_parallel(log_path, tags=["alert"]) == [
+        m for m in matcher.match_file(log_path) if m[3] == "error"
+    ]
+    with pytest.raises(PatternError, match="Unknown tag"):
+        matcher.match_file_parallel(log_path, tags=["pci"])
+
+    empty = tmp_path / "empty.log"
+    empty.write_bytes(b"")
+    assert matcher.match_file_parallel(str(empty)) == []
+
+    with pytest.raises(IOError):
+        matcher.match_file_parallel(str(tmp_path / "missing.log"))
+    with pytest.raises(ValueError):
+        matcher.match_file_parallel(log_path, chunk_size=0)
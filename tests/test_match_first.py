@@ -0,0 +1,64 @@
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+MATCHER = TextMatcher(["secret", "token", "password"])
+
+
+def test_first_of_each_pattern():
+    """Test that only the first match of every pattern is reported, sorted by offset."""
+    data = b"token=1 secret token secret password token"
+    assert MATCHER.match_first(data) == [(0, 5, "token"), (8, 14, "secret"), (28, 36, "password")]
+
+
+def test_missing_patterns():
+    """Test that patterns without matches are left out."""
+    assert MATCHER.match_first(b"a secret and another secret") == [(2, 8, "secret")]
+    assert MATCHER.match_first(b"") == []
+
+
+def test_matches_match_bytes(tmp_path):
+    """Test that first matches are the first ones match_bytes reports, from bytes or a file."""
+    data = b"".join(b"line %d: %s\n" % (i, [b"secret", b"token", b"nothing"][i % 3]) for i in range(1000))
+    expected = {}
+    for start, end, pattern in MATCHER.match_bytes(data):
+        expected.setdefault(pattern, (start, end, pattern))
+    first = MATCHER.match_first(data)
+    assert first == sorted(expected.values())
+
+    path = tmp_path / "data.txt"
+    path.write_bytes(data)
+    assert MATCHER.match_first(str(path)) == first
+
+
+def test_whole_word_and_duplicates():
+    """Test that unreported occurrences are skipped and duplicate patterns count once."""
+    matcher = TextMatcher(["cat", "cat"], whole_word=True)
+    assert matcher.match_first(b"concat cat cat") == [(7, 10, "cat")]
+
+
+def test_postprocess():
+    """Test that matches dropped by post-processing are not first matches."""
+    matcher = TextMatcher(["new", "new york"], overlapping=True, postprocess="longest")
+    assert matcher.match_first(b"new york, new jersey") == [(0, 8, "new york"), (10, 13, "new")]
+
+
+def test_match_dir_first_only(tmp_path):
+    """Test that directory scans keep the first match of every pattern in each file."""
+    (tmp_path / "a.txt").write_bytes(b"secret secret token")
+    (tmp_path / "b.txt").write_bytes(b"password secret password")
+    matches, files = MATCHER.match_dir(str(tmp_path), first_only=True)
+    assert [(m[0].rsplit("/", 1)[1], m[1], m[3]) for m in matches] == [
+        ("a.txt", 0, "secret"),
+        ("a.txt", 14, "token"),
+        ("b.txt", 0, "password"),
+        ("b.txt", 9, "secret"),
+    ]
+    assert [file.matches for file in files] == [2, 2]
+
+
+def test_errors():
+    """Test that unreadable files and other arguments are rejected."""
+    with pytest.raises(ScanIOError):
+        MATCHER.match_first("/nonexistent/file")
+    with pytest.raises(TypeError, match="data_or_path must be bytes or a path"):
+        MATCHER.match_first(42)
@@ -0,0 +1,42 @@
+import pytest
+from voluta import TextMatcher
+
+np = pytest.importorskip("numpy")
+
+
+def test_structured_array():
+    """Test that the array holds the matches of match_bytes with pattern indices."""
+    matcher = TextMatcher(["fox", "dog", "quick"])
+    data = b"the quick fox and the dog and the fox"
+
+    matches = matcher.match_bytes_np(data)
+    assert isinstance(matches, np.ndarray)
+    assert matches.dtype.names == ("start", "end", "pattern_id")
+    assert matches.dtype["start"] == np.uint64
+    assert matches.dtype["end"] == np.uint64
+    assert matches.dtype["pattern_id"] == np.uint32
+
+    records = [(int(m["start"]), int(m["end"]), matcher.patterns[m["pattern_id"]]) for m in matches]
+    assert records == matcher.match_bytes(data)
+
+
+def test_vectorized_filtering():
+    """Test filtering and counting the matches with array operations."""
+    matcher = TextMatcher(["error", "warn"])
+    data = b"error warn error info error\n" * 1000
+
+    matches = matcher.match_bytes_np(data)
+    assert len(matches) == 4000
+    counts = np.bincount(matches["pattern_id"], minlength=len(matcher.patterns))
+    assert counts.tolist() == [3000, 1000]
+    assert (matches[matches["pattern_id"] == 1]["end"] - matches[matches["pattern_id"] == 1]["start"] == 4).all()
+
+    # The array is writable
+    matches["start"][0] = 1
+
+
+def test_empty():
+    """Test that inputs without matches give an empty array of the same dtype."""
+    matches = TextMatcher(["fox"]).match_bytes_np(b"nothing here")
+    assert len(matches) == 0
+    assert matches.dtype.names == ("start", "end", "pattern_id")
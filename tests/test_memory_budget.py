@@ -0,0 +1,69 @@
+import pytest
+from voluta import LimitExceeded, TextMatcher, TextMatcherBuilder
+
+CUSTOMERS = [f"customer{i:05d}" for i in range(20_000)]
+
+
+def test_budget_fits():
+    """Test that a DFA within the budget is built as usual."""
+    unlimited = TextMatcher(CUSTOMERS, engine="aho")
+    limited = (
+        TextMatcherBuilder(CUSTOMERS)
+        .options(engine="aho", max_memory_bytes=unlimited.memory_usage())
+        .build()
+    )
+    assert unlimited.automaton == limited.automaton == "dfa"
+
+
+def test_budget_error():
+    """Test that an oversized DFA fails the build before it is built."""
+    dfa = TextMatcher(CUSTOMERS, engine="aho").memory_usage()
+    with pytest.raises(LimitExceeded, match="max_memory_bytes=.*on_memory_limit='downgrade'"):
+        TextMatcherBuilder(CUSTOMERS).options(max_memory_bytes=dfa // 2).build()
+
+
+def test_budget_estimate():
+    """Test that the build is refused just under the actual size of the DFA."""
+    dfa = TextMatcher(CUSTOMERS, engine="aho").memory_usage()
+    with pytest.raises(LimitExceeded):
+        TextMatcherBuilder(CUSTOMERS).options(max_memory_bytes=int(dfa * 0.8)).build()
+
+
+def test_budget_downgrade():
+    """Test that downgrading builds a smaller contiguous NFA finding the same matches."""
+    dfa = TextMatcher(CUSTOMERS, engine="aho")
+    nfa = (
+        TextMatcherBuilder(CUSTOMERS)
+        .options(max_memory_bytes=dfa.memory_usage() // 2, on_memory_limit="downgrade")
+        .build()
+    )
+    assert nfa.automaton == "contiguous_nfa"
+    assert nfa.memory_usage() < dfa.memory_usage() // 2
+    text = b"CUSTOMER00042 called about customer19999 and customer7"
+    assert nfa.match_bytes(text) == dfa.match_bytes(text)
+    assert [m[2] for m in nfa.match_bytes(text)] == ["customer00042", "customer19999"]
+
+
+def test_budget_downgrade_too_small():
+    """Test that a budget too small for the contiguous NFA fails the build too."""
+    with pytest.raises(LimitExceeded, match="contiguous NFA"):
+        TextMatcherBuilder(CUSTOMERS).options(max_memory_bytes=1000, on_memory_limit="downgrade").build()
+
+
+def test_budget_fuzzy():
+    """Test that the contiguous NFA of fuzzy variants is held to the budget."""
+    with pytest.raises(LimitExceeded):
+        TextMatcherBuilder(["password"]).options(fuzzy=1, max_memory_bytes=1000).build()
+
+
+def test_budget_without_automaton():
+    """Test that engines without an Aho-Corasick automaton ignore the budget."""
+    matcher = TextMatcherBuilder(["password", "secret"]).options(max_memory_bytes=1).build()
+    assert matcher.engine == "memmem"
+    assert matcher.automaton is None
+
+
+def test_unknown_policy():
+    """Test that unknown policies are rejected."""
+    with pytest.raises(ValueError, match="on_memory_limit"):
+        TextMatcherBuilder(CUSTOMERS).options(max_memory_bytes=1000, on_memory_limit="ignore").build()
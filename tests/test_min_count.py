@@ -0,0 +1,95 @@
+import pytest
+from voluta import PatternError, TextMatcher
+
+DATA = b"falcon " * 4 + b"secret falcon"
+
+
+def test_threshold_by_pattern():
+    """Test that a pattern below its min count is not reported while the others are."""
+    matcher = TextMatcher(["falcon", "secret"], min_count={"falcon": 5})
+    assert [m[2] for m in matcher.match_bytes(DATA)] == ["falcon"] * 4 + ["secret", "falcon"]
+    matcher = TextMatcher(["falcon", "secret"], min_count={"falcon": 6})
+    assert matcher.match_bytes(DATA) == [(28, 34, "secret")]
+
+
+def test_threshold_forms():
+    """Test that min counts are one count, one per pattern or a dict."""
+    assert TextMatcher(["falcon", "secret"], min_count=2).match_bytes(DATA) == [
+        m for m in TextMatcher(["falcon"]).match_bytes(DATA)
+    ]
+    matcher = TextMatcher(["falcon", "secret"], min_count=[6, 1])
+    assert matcher.match_bytes(DATA) == [(28, 34, "secret")]
+    assert TextMatcher(["falcon"], min_count=0).match_bytes(b"falcon") == [(0, 6, "falcon")]
+
+
+def test_every_scan_method(tmp_path):
+    """Test that thresholds apply to the whole of file scans, whatever the method."""
+    data = b"\n".join([b"falcon"] * 3 + [b"x" * 100_000] + [b"falcon secret"] * 2)
+    path = tmp_path / "notes.txt"
+    path.write_bytes(data)
+    matcher = TextMatcher(["falcon", "secret"], min_count={"falcon": 5, "secret": 3})
+    expected = [(m[0], m[1], m[2]) for m in TextMatcher(["falcon"]).match_bytes(data)]
+    assert len(expected) == 5
+
+    assert matcher.match_bytes(data) == expected
+    assert matcher.match_file_memmap(str(path), chunk_size=4096) == expected
+    assert sorted(matcher.match_file_memmap_parallel(str(path), chunk_size=4096)) == expected
+    assert matcher.match_file_stream(str(path)) == expected
+    assert [m[3] for m in matcher.match_file(str(path))] == ["falcon"] * 5
+    assert len(matcher.match_dedup(data)) == 1
+
+    matches, files = matcher.match_dir(str(tmp_path))
+    assert [m[3] for m in matches] == ["falcon"] * 5
+    assert files[0].matches == 5
+
+
+def test_counted_per_file(tmp_path):
+    """Test that directory scans count the matches of each file separately."""
+    (tmp_path / "a.txt").write_bytes(b"falcon falcon")
+    (tmp_path / "b.txt").write_bytes(b"falcon")
+    matcher = TextMatcher(["falcon"], min_count=2)
+    matches, _ = matcher.match_dir(str(tmp_path))
+    assert [m[0].rsplit("/", 1)[1] for m in matches] == ["a.txt", "a.txt"]
+
+
+def test_after_postprocess():
+    """Test that matches dropped by post-processing do not count."""
+    matcher = TextMatcher(["new", "new york"], postprocess="longest", min_count={"new": 2})
+    assert matcher.match_bytes(b"new york, new jersey") == [(0, 8, "new york")]
+    assert matcher.match_bytes(b"new jersey, new york, new") == [
+        (0, 3, "new"),
+        (12, 20, "new york"),
+        (22, 25, "new"),
+    ]
+
+
+def test_first_match_and_contains_all():
+    """Test that first matches and required patterns only see reported matches."""
+    matcher = TextMatcher(["falcon", "secret"], min_count={"falcon": 5})
+    assert matcher.match_first(DATA) == [(0, 6, "falcon"), (28, 34, "secret")]
+    assert not matcher.match_first(b"falcon secret").count((0, 6, "falcon"))
+    assert not matcher.contains_all(b"falcon secret", ["falcon", "secret"])
+    assert matcher.contains_all(DATA, ["falcon", "secret"])
+
+
+def test_diff_and_fingerprint():
+    """Test that min counts show in pattern diffs and fingerprints."""
+    old = TextMatcher(["falcon", "secret"])
+    new = TextMatcher(["falcon", "secret"], min_count={"falcon": 5})
+    assert old.diff_patterns(new).changed == [("falcon", "min_count", 1, 5)]
+    assert old.fingerprint != new.fingerprint
+
+
+def test_invalid_min_counts(tmp_path):
+    """Test that malformed min counts and spooled scans are rejected."""
+    with pytest.raises(PatternError, match="Unknown pattern 'eagle'"):
+        TextMatcher(["falcon"], min_count={"eagle": 2})
+    with pytest.raises(PatternError, match="one entry per pattern"):
+        TextMatcher(["falcon", "secret"], min_count=[2])
+    with pytest.raises(TypeError, match="min_count must be"):
+        TextMatcher(["falcon"], min_count="2")
+
+    path = tmp_path / "notes.txt"
+    path.write_bytes(b"falcon")
+    with pytest.raises(ValueError, match="min counts"):
+        TextMatcher(["falcon"], min_count=2).match_file_spooled(str(path))
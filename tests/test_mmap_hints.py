@@ -0,0 +1,68 @@
+import pytest
+from voluta import ScanIOError, TextMatcher, warmup
+
+PATTERNS = ["error", "warning"]
+
+
+def test_default_hints():
+    """Test that files are mapped without hints by default."""
+    matcher = TextMatcher(PATTERNS)
+    assert matcher.madvise == "normal"
+    assert matcher.huge_pages is False
+    assert matcher.prefault is False
+
+
+@pytest.mark.parametrize(
+    "hints",
+    [
+        {"madvise": "sequential"},
+        {"madvise": "willneed"},
+        {"huge_pages": True},
+        {"prefault": True},
+        {"madvise": "sequential", "huge_pages": True, "prefault": True},
+    ],
+)
+def test_hints_keep_matches(hints, tmp_path):
+    """Test that mapping hints do not change the matches of memory-mapped scans."""
+    path = tmp_path / "input.log"
+    path.write_bytes((b"x" * 4000 + b" error warning\n") * 500)
+    plain = TextMatcher(PATTERNS)
+    hinted = TextMatcher(PATTERNS, **hints)
+    for name, value in hints.items():
+        assert getattr(hinted, name) == value
+
+    assert hinted.match_file_memmap(str(path)) == plain.match_file_memmap(str(path))
+    assert sorted(hinted.match_file_memmap_parallel(str(path), 64 * 1024)) == sorted(
+        plain.match_file_memmap_parallel(str(path), 64 * 1024)
+    )
+    assert hinted.match_file_collection(str(path)).to_list() == (
+        plain.match_file_collection(str(path)).to_list()
+    )
+
+
+def test_prefault_empty_file(tmp_path):
+    """Test prefaulting empty files."""
+    path = tmp_path / "empty.log"
+    path.write_bytes(b"")
+    assert TextMatcher(PATTERNS, prefault=True).match_file_memmap(str(path)) == []
+
+
+def test_warmup(tmp_path):
+    """Test that warmup reads whole files and leaves their matches alone."""
+    path = tmp_path / "input.log"
+    path.write_bytes(b"x" * 100_000 + b" error")
+    assert warmup(str(path)) == 100_006
+    assert TextMatcher(PATTERNS).match_file_memmap(str(path)) == [(100_001, 100_006, "error")]
+
+    empty = tmp_path / "empty.log"
+    empty.write_bytes(b"")
+    assert warmup(str(empty)) == 0
+
+    with pytest.raises(ScanIOError):
+        warmup(str(tmp_path / "missing.log"))
+
+
+def test_unknown_madvise():
+    """Test that unknown madvise hints are rejected."""
+    with pytest.raises(ValueError, match="Unknown madvise hint"):
+        TextMatcher(PATTERNS, madvise="random")
@@ -0,0 +1,441 @@
+import zlib as zlib_module
+
+import pytest
+from voluta import DecodeError, ScanIOError, TextMatcher, TextMatcherBuilder
+
+MATCHER = TextMatcher(["secret", "4111"], case_insensitive=False)
+
+KINDS = {"int": 3, "string": 7, "binary": 8, "list": 10, "map": 11, "struct": 12, "union": 13, "varchar": 16}
+PRESENT, DATA, LENGTH, DICTIONARY_DATA = 0, 1, 2, 3
+DIRECT, DICTIONARY, DIRECT_V2, DICTIONARY_V2 = 0, 1, 2, 3
+
+USER = (
+    "struct",
+    [
+        ("id", "int"),
+        ("name", "string"),
+        ("token", "binary"),
+        ("emails", ("list", "varchar")),
+        ("labels", ("map", "string", "string")),
+        ("contact", ("union", ["int", "string"])),
+        ("card", ("struct", [("number", "string"), ("limit", "int")])),
+    ],
+)
+
+USERS = [
+    {"id": 0, "name": "alice", "token": b"\x00secret\xff", "emails": ["a@example.com"], "labels": {}, "contact": (1, "a secret"), "card": {"number": "0000", "limit": 1}},
+    {"id": 1, "name": None, "token": None, "emails": None, "labels": {"env": "prod"}, "contact": (0, 5), "card": None},
+    {"id": 2, "name": "secret agent", "token": b"", "emails": ["x@example.com", "secret@example.com"], "labels": {"secret": "none", "env": "secret"}, "contact": None, "card": {"number": "4111 1111", "limit": None}},
+]
+
+EXPECTED = [
+    (0, "token", 1, 7, "secret"),
+    (0, "contact", 2, 8, "secret"),
+    (2, "name", 0, 6, "secret"),
+    (2, "emails[1]", 0, 6, "secret"),
+    (2, "labels[env]", 0, 6, "secret"),
+    (2, "card.number", 0, 4, "4111"),
+]
+
+
+def varint(n):
+    out = bytearray()
+    while n >= 0x80:
+        out.append(n & 0x7F | 0x80)
+        n >>= 7
+    out.append(n)
+    return bytes(out)
+
+
+def message(*fields):
+    """A protobuf message of (number, value) fields, ints as varints and bytes as they are."""
+    out = b""
+    for number, value in fields:
+        if isinstance(value, int):
+            out += varint(number << 3) + varint(value)
+        else:
+            out += varint(number << 3 | 2) + varint(len(value)) + value
+    return out
+
+
+def byte_rle(data):
+    """Byte run-length encoding: runs of repeated bytes, literals otherwise."""
+    out, i = bytearray(), 0
+    while i < len(data):
+        run = 1
+        while i + run < len(data) and run < 130 and data[i + run] == data[i]:
+            run += 1
+        if run >= 3:
+            out += bytes([run - 3, data[i]])
+            i += run
+            continue
+        n = min(128, len(data) - i)
+        out += bytes([256 - n]) + bytes(data[i : i + n])
+        i += n
+    return bytes(out)
+
+
+def booleans(values):
+    data = bytearray((len(values) + 7) // 8)
+    for i, value in enumerate(values):
+        if value:
+            data[i // 8] |= 0x80 >> (i % 8)
+    return byte_rle(data)
+
+
+def rle_v1(values):
+    """Version 1 run-length encoding: runs of a fixed delta, literal varints otherwise."""
+    out, i = bytearray(), 0
+    while i < len(values):
+        delta = values[i + 1] - values[i] if i + 1 < len(values) else 0
+        run = 1
+        while i + run < len(values) and run < 130 and -128 <= delta < 128 and values[i + run] - values[i + run - 1] == delta:
+            run += 1
+        if run >= 3:
+            out += bytes([run - 3, delta & 0xFF]) + varint(values[i])
+            i += run
+            continue
+        n = min(128, len(values) - i)
+        out += bytes([256 - n]) + b"".join(varint(v) for v in values[i : i + n])
+        i += n
+    return bytes(out)
+
+
+def rle_v2(values):
+    """Version 2 run-length encoding: short repeats and direct runs of whole bytes."""
+    out, i = bytearray(), 0
+    while i < len(values):
+        run = 1
+        while i + run < len(values) and run < 10 and values[i + run] == values[i]:
+            run += 1
+        if run >= 3:
+            width = max(1, (values[i].bit_length() + 7) // 8)
+            out += bytes([(width - 1) << 3 | (run - 3)]) + values[i].to_bytes(width, "big")
+            i += run
+            continue
+        chunk = values[i : i + 512]
+        width = max(1, (max(chunk).bit_length() + 7) // 8)
+        code = {1: 7, 2: 15, 3: 23, 4: 27, 5: 28, 6: 29, 7: 30, 8: 31}[width]
+        out += bytes([0x40 | code << 1 | (len(chunk) - 1) >> 8, (len(chunk) - 1) & 0xFF])
+        out += b"".join(v.to_bytes(width, "big") for v in chunk)
+        i += len(chunk)
+    return bytes(out)
+
+
+def flatten(schema, types):
+    """Append the types of a schema in pre-order, returning its column."""
+    column = len(types)
+    types.append(None)
+    if isinstance(schema, str):
+        types[column] = message((1, KINDS[schema]))
+        return column
+    kind = schema[0]
+    if kind == "struct":
+        children = [flatten(child, types) for _, child in schema[1]]
+        names = [(3, name.encode()) for name, _ in schema[1]]
+    elif kind == "union":
+        children = [flatten(child, types) for child in schema[1]]
+        names = []
+    else:
+        children = [flatten(child, types) for child in schema[1:]]
+        names = []
+    packed = b"".join(varint(child) for child in children)
+    types[column] = message((1, KINDS[kind]), (2, packed), *names)
+    return column
+
+
+class Stripe:
+    """Streams and encodings of the columns of a stripe."""
+
+    def __init__(self, encoding):
+        self.encoding = encoding
+        self.streams = []
+        self.encodings = {}
+        self.dictionary_sizes = {}
+        self.column = 0
+
+    def rle(self, values):
+        return rle_v1(values) if self.encoding in (DIRECT, DICTIONARY) else rle_v2(values)
+
+    def write(self, schema, values):
+        column = self.column
+        self.column += 1
+        if any(value is None for value in values):
+            self.streams.append((PRESENT, column, booleans([value is not None for value in values])))
+        values = [value for value in values if value is not None]
+        kind = schema if isinstance(schema, str) else schema[0]
+        self.encodings[column] = DIRECT if self.encoding in (DIRECT, DICTIONARY) else DIRECT_V2
+        if kind == "int":
+            self.streams.append((DATA, column, self.rle([2 * v for v in values])))
+        elif kind in ("string", "binary", "varchar"):
+            values = [v.encode() if isinstance(v, str) else v for v in values]
+            if self.encoding in (DICTIONARY, DICTIONARY_V2):
+                dictionary = sorted(set(values))
+                self.encodings[column] = self.encoding
+                self.dictionary_sizes[column] = len(dictionary)
+                self.streams.append((DATA, column, self.rle([dictionary.index(v) for v in values])))
+                self.streams.append((LENGTH, column, self.rle([len(v) for v in dictionary])))
+                self.streams.append((DICTIONARY_DATA, column, b"".join(dictionary)))
+            else:
+                self.streams.append((DATA, column, b"".join(values)))
+                self.streams.append((LENGTH, column, self.rle([len(v) for v in values])))
+        elif kind == "struct":
+            for name, child in schema[1]:
+                self.write(child, [value[name] for value in values])
+        elif kind == "list":
+            self.streams.append((LENGTH, column, self.rle([len(v) for v in values])))
+            self.write(schema[1], [item for value in values for item in value])
+        elif kind == "map":
+            self.streams.append((LENGTH, column, self.rle([len(v) for v in values])))
+            self.write(schema[1], [key for value in values for key in value])
+            self.write(schema[2], [item for value in values for item in value.values()])
+        elif kind == "union":
+            self.streams.append((DATA, column, byte_rle(bytes(tag for tag, _ in values))))
+            for tag, child in enumerate(schema[1]):
+                self.write(child, [value for t, value in values if t == tag])
+
+    def footer(self, compress):
+        streams = [message((1, kind), (2, column), (3, len(compress(data)))) for kind, column, data in self.streams]
+        encodings = [
+            message((1, self.encodings[column]), (2, self.dictionary_sizes.get(column, 0)))
+            for column in range(self.column)
+        ]
+        return message(*[(1, s) for s in streams], *[(2, e) for e in encodings])
+
+
+def snappy(data):
+    """A raw Snappy block made of literals only."""
+    out = bytes([len(data)]) if len(data) < 0x80 else bytes([len(data) & 0x7F | 0x80, len(data) >> 7])
+    for i in range(0, len(data), 60):
+        chunk = data[i : i + 60]
+        out += bytes([(len(chunk) - 1) << 2]) + chunk
+    return out
+
+
+def lz4(data):
+    """A raw LZ4 block made of a single sequence of literals."""
+    if len(data) < 15:
+        return bytes([len(data) << 4]) + data
+    n, extra = len(data) - 15, b""
+    while n >= 255:
+        extra += b"\xff"
+        n -= 255
+    return b"\xf0" + extra + bytes([n]) + data
+
+
+def zlib(data):
+    compressor = zlib_module.compressobj(wbits=-15)
+    return compressor.compress(data) + compressor.flush()
+
+
+CODECS = {"zlib": (1, zlib), "snappy": (2, snappy), "lz4": (4, lz4)}
+
+
+def chunks(data, compression, block_size=64):
+    """Compression in chunks of block_size bytes, incompressible chunks stored as they are."""
+    if compression == "none":
+        return data
+    out = b""
+    for i in range(0, len(data), block_size):
+        chunk = data[i : i + block_size]
+        compressed = CODECS[compression][1](chunk)
+        if len(compressed) < len(chunk) or compression != "zlib":
+            out += (len(compressed) << 1).to_bytes(3, "little") + compressed
+        else:
+            out += (len(chunk) << 1 | 1).to_bytes(3, "little") + chunk
+    return out
+
+
+def orc(schema, stripes, encoding=DIRECT_V2, compression="none"):
+    """An ORC file of stripes of rows."""
+    def compress(data):
+        return chunks(data, compression)
+
+    types = []
+    flatten(schema, types)
+    out = b"ORC"
+    infos = []
+    for rows in stripes:
+        stripe = Stripe(encoding)
+        stripe.write(schema, rows)
+        offset = len(out)
+        data = b"".join(compress(data) for _, _, data in stripe.streams)
+        footer = compress(stripe.footer(compress))
+        out += data + footer
+        infos.append(message((1, offset), (2, 0), (3, len(data)), (4, len(footer)), (5, len(rows))))
+    footer = compress(
+        message(
+            (1, 3),
+            (2, len(out) - 3),
+            *[(3, info) for info in infos],
+            *[(4, t) for t in types],
+            (6, sum(len(rows) for rows in stripes)),
+        )
+    )
+    postscript = message((1, len(footer)), (2, CODECS[compression][0] if compression != "none" else 0), (3, 64), (8000, b"ORC"))
+    return out + footer + postscript + bytes([len(postscript)])
+
+
+def by_hand(types, streams, rows, encodings=None):
+    """An uncompressed ORC file of a stripe written by hand, its streams given as (kind, column, bytes)."""
+    encodings = encodings or [message((1, DIRECT_V2))] * len(types)
+    data = b"".join(stream for _, _, stream in streams)
+    streams = [message((1, kind), (2, column), (3, len(stream))) for kind, column, stream in streams]
+    footer = message(*[(1, s) for s in streams], *[(2, e) for e in encodings])
+    stripe = message((1, 3), (2, 0), (3, len(data)), (4, len(footer)), (5, rows))
+    body = b"ORC" + data + footer
+    tail = message((1, 3), (2, len(body) - 3), (3, stripe), *[(4, t) for t in types], (6, rows))
+    postscript = message((1, len(tail)), (2, 0), (8000, b"ORC"))
+    return body + tail + postscript + bytes([len(postscript)])
+
+
+@pytest.mark.parametrize("encoding", [DIRECT, DICTIONARY, DIRECT_V2, DICTIONARY_V2])
+@pytest.mark.parametrize("compression", ["none", "zlib", "snappy", "lz4"])
+def test_encodings(tmp_path, encoding, compression):
+    """Test that rows are reported by index and field path whatever the encoding."""
+    path = tmp_path / "users.orc"
+    path.write_bytes(orc(USER, [USERS], encoding, compression))
+    assert MATCHER.match_orc(str(path)) == EXPECTED
+
+
+def test_stripes(tmp_path):
+    """Test that rows count across stripes."""
+    path = tmp_path / "users.orc"
+    path.write_bytes(orc(USER, [USERS[:1], USERS[1:]], compression="zlib"))
+    assert MATCHER.match_orc(str(path)) == EXPECTED
+
+
+def test_runs(tmp_path):
+    """Test that long runs of repeated values and of nulls are decoded."""
+    rows = [None] * 300 + ["public"] * 500 + ["a secret"] * 20 + [None] * 5 + ["secret"]
+    for encoding in (DIRECT, DIRECT_V2, DICTIONARY_V2):
+        path = tmp_path / "notes.orc"
+        path.write_bytes(orc(("struct", [("note", "string")]), [[{"note": note} for note in rows]], encoding))
+        matches = MATCHER.match_orc(str(path))
+        assert [m[0] for m in matches] == list(range(800, 820)) + [825]
+        assert {m[1] for m in matches} == {"note"}
+
+
+def test_rle_v2_sub_encodings(tmp_path):
+    """Test the short repeat, direct, delta and patched base examples of the specification."""
+    lengths = [10000] * 5 + [23713, 43806, 57005, 48879] + [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]
+    lengths += [2030, 2000, 2020, 1000000] + list(range(2040, 2200, 10))
+    stream = bytes.fromhex("0a2710" "5e035ca1ab1edeadbeef" "c609020222424246")
+    stream += bytes.fromhex("8e132b2107d01e00147028323c46505a646e78828c96a0aab4befce8")
+    data = b"".join((b"secret" + b"." * n)[:n] if i % 2 else b"." * n for i, n in enumerate(lengths))
+
+    # A single string column, its lengths encoded as in the specification
+    path = tmp_path / "lengths.orc"
+    path.write_bytes(by_hand([message((1, KINDS["string"]))], [(DATA, 0, data), (LENGTH, 0, stream)], len(lengths)))
+    matches = MATCHER.match_orc(str(path))
+    assert [m[0] for m in matches] == [i for i, n in enumerate(lengths) if i % 2 and n >= 6]
+    assert {m[1:] for m in matches} == {("", 0, 6, "secret")}
+
+
+def test_errors(tmp_path):
+    """Test that missing, foreign, truncated and unsupported files are reported."""
+    with pytest.raises(ScanIOError):
+        MATCHER.match_orc(str(tmp_path / "missing.orc"))
+
+    path = tmp_path / "text.orc"
+    path.write_text("not an orc file")
+    with pytest.raises(DecodeError, match="Not an ORC"):
+        MATCHER.match_orc(str(path))
+
+    # A stripe past the end of the file
+    stripe = message((1, 1 << 20), (3, 10), (4, 10), (5, 1))
+    tail = message((3, stripe), (4, message((1, KINDS["string"]))), (6, 1))
+    postscript = message((1, len(tail)), (2, 0), (8000, b"ORC"))
+    path.write_bytes(b"ORC" + tail + postscript + bytes([len(postscript)]))
+    with pytest.raises(DecodeError, match="Truncated"):
+        MATCHER.match_orc(str(path))
+
+    # A footer compressed with zstd
+    postscript = message((1, 5), (2, 5), (8000, b"ORC"))
+    path.write_bytes(b"ORC" + (2 << 1).to_bytes(3, "little") + b"xx" + postscript + bytes([len(postscript)]))
+    with pytest.raises(DecodeError, match="zstd"):
+        MATCHER.match_orc(str(path))
+
+
+def test_inflated_counts(tmp_path):
+    """Test that counts and offsets past the bytes of the streams fail without exhausting memory."""
+    string = [message((1, KINDS["string"]))]
+    secret = [(DATA, 0, b"secret"), (LENGTH, 0, rle_v2([6]))]
+    path = tmp_path / "counts.orc"
+    path.write_bytes(by_hand(string, secret, 1))
+    assert MATCHER.match_orc(str(path)) == [(0, "", 0, 6, "secret")]
+
+    files = [
+        # Rows
+        by_hand(string, secret, 1 << 62),
+        # Dictionary entries
+        by_hand(string, [(DATA, 0, rle_v2([0])), (LENGTH, 0, rle_v2([6])), (DICTIONARY_DATA, 0, b"secret")], 1, [message((1, DICTIONARY_V2), (2, 1 << 40))]),
+        # Items of a list, repeating a length of 2^50
+        by_hand(
+            [message((1, KINDS["list"]), (2, b"\x01")), *string],
+            [(LENGTH, 0, bytes([0x38]) + (1 << 50).to_bytes(8, "big")), (DATA, 1, b"secret"), (LENGTH, 1, rle_v2([6]))],
+            1,
+        ),
+        # A patched base run of a 64-bit patch width
+        by_hand(string, [(DATA, 0, b"secret"), (LENGTH, 0, bytes([0x80, 0x00, 0x1F, 0x01, 0x00, 0x00]) + bytes(8))], 1),
+    ]
+    # Streams and stripes past the end of the file, their offsets overflowing
+    stream = message((1, DATA), (2, 0), (3, (1 << 64) - 1))
+    footer = message((1, stream))
+    for stripe in (
+        message((1, 3), (3, 0), (4, len(footer)), (5, 1)),
+        message((1, (1 << 64) - 1), (2, 1), (3, 1), (4, 1), (5, 1)),
+    ):
+        tail = message((3, stripe), *[(4, t) for t in string], (6, 1))
+        postscript = message((1, len(tail)), (2, 0), (8000, b"ORC"))
+        files.append(b"ORC" + footer + tail + postscript + bytes([len(postscript)]))
+    for data in files:
+        path.write_bytes(data)
+        with pytest.raises(DecodeError):
+            MATCHER.match_orc(str(path))
+
+
+@pytest.mark.parametrize("compression", ["none", "zlib", "snappy", "lz4"])
+def test_corrupt_bytes(tmp_path, compression):
+    """Test that truncated files and files with any byte overwritten decode or fail cleanly."""
+    path = tmp_path / "users.orc"
+    data = orc(USER, [USERS], compression=compression)
+    corrupted = [data[:end] for end in range(len(data))]
+    corrupted += [data[:i] + bytes([byte]) + data[i + 1 :] for i in range(len(data)) for byte in (0x00, 0x7F, 0xFF)]
+    for corrupt in corrupted:
+        path.write_bytes(corrupt)
+        try:
+            MATCHER.match_orc(str(path))
+        except DecodeError:
+            pass
+
+
+def test_pyarrow(tmp_path):
+    """Test files written by pyarrow, when it is installed."""
+    pa = pytest.importorskip("pyarrow")
+    orc_module = pytest.importorskip("pyarrow.orc")
+    table = pa.table(
+        {
+            "name": ["alice", None, "secret agent"],
+            "emails": [["a@example.com"], None, ["x@example.com", "secret@example.com"]],
+            "card": [{"number": "0000"}, None, {"number": "4111 1111"}],
+        }
+    )
+    path = tmp_path / "users.orc"
+    orc_module.write_table(table, str(path), compression="zlib")
+    assert MATCHER.match_orc(str(path)) == [
+        (2, "name", 0, 6, "secret"),
+        (2, "emails[1]", 0, 6, "secret"),
+        (2, "card.number", 0, 4, "4111"),
+    ]
+
+
+@pytest.mark.parametrize("compression", ["zlib", "snappy", "lz4"])
+def test_max_decoded_size(tmp_path, compression):
+    """Test that streams decompressing past max_decoded_size fail instead of exhausting memory."""
+    path = tmp_path / "large.orc"
+    path.write_bytes(orc(("struct", [("note", "string")]), [[{"note": "x" * 100_000 + "secret"}]], compression=compression))
+    assert MATCHER.match_orc(str(path)) == [(0, "note", 100_000, 100_006, "secret")]
+    matcher = TextMatcherBuilder(["secret"]).options(max_decoded_size=64 * 1024).build()
+    with pytest.raises(DecodeError, match="max_decoded_size"):
+        matcher.match_orc(str(path))
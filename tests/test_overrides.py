@@ -0,0 +1,74 @@
+import asyncio
+
+import pytest
+from voluta import TextMatcher
+
+DATA = b"he said hello to the shell"
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides an overlapping matcher without whole word matching."""
+    return TextMatcher(["he", "hello", "hell"])
+
+
+def test_overlapping_override(matcher):
+    """Test that one call can ask for non-overlapping matches of an overlapping matcher."""
+    assert len(matcher.match_bytes(DATA)) == 7
+    assert matcher.match_bytes(DATA, overlapping=False) == [
+        (0, 2, "he"),
+        (8, 10, "he"),
+        (18, 20, "he"),
+        (22, 24, "he"),
+    ]
+    assert matcher.overlapping is True
+    assert len(matcher.match_bytes(DATA)) == 7
+
+
+def test_whole_word_override(matcher):
+    """Test that one call can turn whole word matching on or off."""
+    assert matcher.match_bytes(DATA, whole_word=True) == [(0, 2, "he"), (8, 13, "hello")]
+    strict = TextMatcher(["he", "hello", "hell"], whole_word=True)
+    assert strict.match_bytes(DATA, whole_word=False) == matcher.match_bytes(DATA)
+
+
+def test_combined_overrides(matcher):
+    """Test both overrides together, with post-processing still applied."""
+    assert matcher.match_bytes(DATA, overlapping=False, whole_word=True) == [(0, 2, "he")]
+    longest = TextMatcher(["he", "hello", "hell"], postprocess="longest")
+    assert longest.match_bytes(DATA, whole_word=True) == [(0, 2, "he"), (8, 13, "hello")]
+
+
+@pytest.mark.parametrize(
+    "overrides",
+    [{}, {"overlapping": False}, {"whole_word": True}, {"overlapping": False, "whole_word": True}],
+)
+def test_scans_honor_overrides(matcher, tmp_path, overrides):
+    """Test that file, stream and parallel scans apply the overrides like match_bytes."""
+    data = DATA + b"\n" + b"hello shell he\n" * 5000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    expected = matcher.match_bytes(data, **overrides)
+
+    assert matcher.match_file_memmap(str(path), **overrides) == expected
+    found = matcher.match_file_memmap_parallel(str(path), chunk_size=4096, n_threads=4, **overrides)
+    assert sorted(found) == expected
+    assert sorted(matcher.match_file_stream(str(path), **overrides)) == expected
+    assert sorted(matcher.match_stream(data, buffer_size=4096, **overrides)) == expected
+    lines = matcher.match_file(str(path), **overrides)
+    assert len(lines) == len(expected)
+
+
+def test_async_overrides(matcher, tmp_path):
+    """Test that the async scans accept the overrides."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(DATA)
+
+    async def scan():
+        return await matcher.amatch_bytes(DATA, overlapping=False), await matcher.amatch_file(
+            str(path), whole_word=True
+        )
+
+    in_bytes, in_file = asyncio.run(scan())
+    assert in_bytes == matcher.match_bytes(DATA, overlapping=False)
+    assert in_file == matcher.match_file(str(path), whole_word=True)
@@ -0,0 +1,96 @@
+import json
+
+import pytest
+from voluta import TextMatcher, run_pattern_tests
+
+MATCHER = TextMatcher(["acme corp", "invoice", "initech"])
+
+
+def test_passing_cases():
+    """Test that cases met by the matcher pass."""
+    report = run_pattern_tests(
+        MATCHER,
+        [
+            {
+                "name": "invoice",
+                "text": "Customer ACME Corp paid invoice 1234",
+                "matches": ["acme corp", {"pattern": "invoice", "count": 1}],
+                "no_matches": ["initech"],
+            },
+            {"text": "nothing here", "exact": True},
+            {"text": "ACME Corp", "matches": [{"pattern": "acme corp", "text": "ACME Corp", "start": 0}]},
+        ],
+    )
+    assert report
+    assert report.passed
+    assert (report.cases, report.failures) == (3, [])
+
+
+def test_failures():
+    """Test that unmet expectations are reported with their case, kind and counts."""
+    report = run_pattern_tests(
+        MATCHER,
+        [
+            {"name": "missing", "text": "initech invoice", "matches": ["acme corp"]},
+            {"text": "invoice invoice", "matches": [{"pattern": "invoice", "count": 1}]},
+            {"name": "rejected", "text": "initech", "no_matches": ["initech"]},
+            {"name": "exact", "text": "invoice initech", "matches": ["invoice"], "exact": True},
+        ],
+    )
+    assert not report.passed
+    summary = [(f.case, f.kind, f.pattern, f.expected, f.found) for f in report.failures]
+    assert summary == [
+        ("missing", "missing", "acme corp", None, 0),
+        ("#2", "missing", "invoice", 1, 2),
+        ("rejected", "unexpected", "initech", 0, 1),
+        ("exact", "unexpected", "initech", None, 1),
+    ]
+    assert report.failures[1].message == "expected 1 matches of 'invoice', found 2"
+    assert report.failures[3].message == "unexpected match of 'initech' at 8..15: 'initech'"
+
+
+def test_text_and_start_filters():
+    """Test that expected text and offsets narrow the matches counted."""
+    report = run_pattern_tests(
+        MATCHER,
+        [{"text": "Invoice invoice", "matches": [{"pattern": "invoice", "text": "Invoice", "start": 8}]}],
+    )
+    (failure,) = report.failures
+    assert failure.message == "expected a match of 'invoice' matching 'Invoice' at 8, found none"
+
+
+def test_json_spec_with_files(tmp_path):
+    """Test JSON spec files, sample files being relative to them."""
+    (tmp_path / "samples").mkdir()
+    (tmp_path / "samples" / "ticket.txt").write_text("Escalated by Initech")
+    spec = tmp_path / "tests.json"
+    spec.write_text(
+        json.dumps(
+            [
+                {"name": "ticket", "file": "samples/ticket.txt", "matches": ["initech"]},
+                {"name": "gone", "file": "samples/missing.txt"},
+            ]
+        )
+    )
+    report = run_pattern_tests(MATCHER, str(spec))
+    (failure,) = report.failures
+    assert (failure.case, failure.kind) == ("gone", "error")
+    assert "samples/missing.txt" in failure.message
+
+
+def test_yaml_spec(tmp_path):
+    """Test YAML spec files."""
+    pytest.importorskip("yaml")
+    spec = tmp_path / "tests.yaml"
+    spec.write_text("- text: ACME Corp\n  matches: [acme corp]\n")
+    assert run_pattern_tests(MATCHER, str(spec)).passed
+
+
+def test_invalid_cases():
+    """Test that malformed cases are rejected."""
+    with pytest.raises(ValueError, match="Invalid pattern tests"):
+        run_pattern_tests(MATCHER, [{"text": "x", "expect": ["acme"]}])
+    with pytest.raises(ValueError, match="Invalid pattern tests"):
+        run_pattern_tests(MATCHER, {"text": "x"})
+    report = run_pattern_tests(MATCHER, [{"name": "empty"}])
+    assert report.failures[0].message == "a case needs one of text and file"
@@ -0,0 +1,176 @@
+import gzip
+import socket
+import struct
+
+import pytest
+from voluta import DecodeError, TextMatcher
+
+CLIENT = ("10.0.0.1", 51000)
+SERVER = ("10.0.0.2", 80)
+
+FIN, SYN, RST, ACK = 0x01, 0x02, 0x04, 0x10
+
+
+def ipv4(src, dst, protocol, payload):
+    """An IPv4 packet, checksums left at zero."""
+    header = struct.pack(
+        "!BBHHHBBH4s4s",
+        0x45, 0, 20 + len(payload), 0, 0, 64, protocol, 0,
+        socket.inet_aton(src), socket.inet_aton(dst),
+    )
+    return header + payload
+
+
+def ipv6(src, dst, protocol, payload):
+    """An IPv6 packet."""
+    header = struct.pack(
+        "!IHBB16s16s",
+        6 << 28, len(payload), protocol, 64,
+        socket.inet_pton(socket.AF_INET6, src), socket.inet_pton(socket.AF_INET6, dst),
+    )
+    return header + payload
+
+
+def tcp(src, dst, seq, payload=b"", flags=ACK, ip=ipv4):
+    """An Ethernet frame carrying a TCP segment from src to dst (address, port) pairs."""
+    segment = struct.pack("!HHIIBBHHH", src[1], dst[1], seq, 0, 5 << 4, flags, 65535, 0, 0)
+    return ethernet(ip(src[0], dst[0], 6, segment + payload), ip)
+
+
+def udp(src, dst, payload, ip=ipv4):
+    """An Ethernet frame carrying a UDP datagram."""
+    datagram = struct.pack("!HHHH", src[1], dst[1], 8 + len(payload), 0) + payload
+    return ethernet(ip(src[0], dst[0], 17, datagram), ip)
+
+
+def ethernet(packet, ip=ipv4):
+    """An Ethernet frame, padded to its minimum size."""
+    ethertype = 0x0800 if ip is ipv4 else 0x86DD
+    frame = b"\x02" * 6 + b"\x04" * 6 + struct.pack("!H", ethertype) + packet
+    return frame.ljust(60, b"\x00")
+
+
+def pcap(frames):
+    """A little-endian pcap file of Ethernet frames."""
+    out = struct.pack("<IHHiIII", 0xA1B2C3D4, 2, 4, 0, 0, 65535, 1)
+    for i, frame in enumerate(frames):
+        out += struct.pack("<IIII", i, 0, len(frame), len(frame)) + frame
+    return out
+
+
+def pcapng(frames):
+    """A pcapng file of Ethernet frames in enhanced packet blocks."""
+
+    def block(kind, body):
+        body = body.ljust((len(body) + 3) // 4 * 4, b"\x00")
+        return struct.pack("<II", kind, len(body) + 12) + body + struct.pack("<I", len(body) + 12)
+
+    out = block(0x0A0D0D0A, struct.pack("<IHHq", 0x1A2B3C4D, 1, 0, -1))
+    out += block(1, struct.pack("<HHI", 1, 0, 65535))
+    for frame in frames:
+        out += block(6, struct.pack("<IIIII", 0, 0, 0, len(frame), len(frame)) + frame)
+    return out
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["password", "token"])
+
+
+@pytest.fixture
+def frames():
+    """Fixture that provides a TCP connection with reordered and retransmitted segments."""
+    return [
+        tcp(CLIENT, SERVER, 999, flags=SYN),
+        tcp(SERVER, CLIENT, 4999, flags=SYN | ACK),
+        tcp(CLIENT, SERVER, 1000, b"user=bob&pass"),
+        # The rest of the password arrives after the next segment
+        tcp(CLIENT, SERVER, 1018, b"=hunter2"),
+        tcp(CLIENT, SERVER, 1013, b"word="),
+        # Retransmission of data already received
+        tcp(CLIENT, SERVER, 1000, b"user=bob&pass"),
+        tcp(SERVER, CLIENT, 5000, b"HTTP/1.1 200 OK\r\n\r\ntoken: abc", FIN | ACK),
+        udp(("10.0.0.3", 5353), ("10.0.0.4", 53), b"pass"),
+        udp(("10.0.0.3", 5353), ("10.0.0.4", 53), b"word token"),
+    ]
+
+
+CLIENT_FLOW = ("tcp", "10.0.0.1", 51000, "10.0.0.2", 80)
+SERVER_FLOW = ("tcp", "10.0.0.2", 80, "10.0.0.1", 51000)
+UDP_FLOW = ("udp", "10.0.0.3", 5353, "10.0.0.4", 53)
+
+EXPECTED = [
+    (CLIENT_FLOW, 9, 17, "password"),
+    (SERVER_FLOW, 19, 24, "token"),
+    # Datagrams are scanned one at a time, offsets being those of the flow's payload
+    (UDP_FLOW, 9, 14, "token"),
+]
+
+
+def test_pcap(matcher, frames, tmp_path):
+    """Test that TCP streams are reassembled and matches reported with their flow."""
+    path = tmp_path / "capture.pcap"
+    path.write_bytes(pcap(frames))
+    assert matcher.match_pcap(str(path)) == EXPECTED
+
+
+def test_pcapng_gzipped(matcher, frames, tmp_path):
+    """Test that pcapng captures, gzipped or not, are read like pcap ones."""
+    path = tmp_path / "capture.pcapng"
+    path.write_bytes(pcapng(frames))
+    assert matcher.match_pcap(str(path)) == EXPECTED
+    path = tmp_path / "capture.pcapng.gz"
+    path.write_bytes(gzip.compress(pcapng(frames)))
+    assert matcher.match_pcap(str(path)) == EXPECTED
+
+
+def test_ipv6_and_midstream(matcher, tmp_path):
+    """Test IPv6 flows and connections already open when the capture started."""
+    src, dst = ("2001:db8::1", 443), ("2001:db8::2", 60000)
+    path = tmp_path / "v6.pcap"
+    path.write_bytes(
+        pcap(
+            [
+                tcp(src, dst, 70000, b"tok", ip=ipv6),
+                tcp(src, dst, 70003, b"en", ip=ipv6),
+            ]
+        )
+    )
+    assert matcher.match_pcap(str(path)) == [
+        (("tcp", "2001:db8::1", 443, "2001:db8::2", 60000), 0, 5, "token")
+    ]
+
+
+def test_lost_segment(matcher, tmp_path):
+    """Test that the bytes of a segment never captured are skipped."""
+    path = tmp_path / "lost.pcap"
+    path.write_bytes(
+        pcap(
+            [
+                tcp(CLIENT, SERVER, 0, flags=SYN),
+                tcp(CLIENT, SERVER, 1, b"pass"),
+                # Bytes 5 to 9 are missing
+                tcp(CLIENT, SERVER, 10, b"word token"),
+                tcp(CLIENT, SERVER, 20, flags=RST),
+            ]
+        )
+    )
+    assert matcher.match_pcap(str(path)) == [(CLIENT_FLOW, 9, 14, "token")]
+
+
+def test_truncated_capture(matcher, frames, tmp_path):
+    """Test that a capture cut short is scanned up to its last complete packet."""
+    path = tmp_path / "cut.pcap"
+    path.write_bytes(pcap(frames)[:-10])
+    assert matcher.match_pcap(str(path)) == EXPECTED[:2]
+
+
+def test_invalid_capture(matcher, tmp_path):
+    """Test that files that are not captures raise DecodeError or IOError."""
+    path = tmp_path / "not.pcap"
+    path.write_bytes(b"hello password\n")
+    with pytest.raises(DecodeError, match="Not a pcap or pcapng file"):
+        matcher.match_pcap(str(path))
+    with pytest.raises(IOError):
+        matcher.match_pcap(str(tmp_path / "missing.pcap"))
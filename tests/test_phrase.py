@@ -0,0 +1,86 @@
+import pytest
+from voluta import PhraseMatcher
+
+PHRASES = ["social security number", "security number", "account  number", "SSN"]
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher for a few phrases."""
+    return PhraseMatcher(PHRASES)
+
+
+def test_line_wraps(matcher):
+    """Test that the words of a phrase match across runs of whitespace and line wraps."""
+    data = b"Your Social\n  Security\r\nnumber and account number"
+    assert matcher.match_bytes(data) == [
+        (5, 30, "social security number"),
+        (14, 30, "security number"),
+        (35, 49, "account  number"),
+    ]
+    start, end, _ = matcher.match_bytes(data)[0]
+    assert data[start:end] == b"Social\n  Security\r\nnumber"
+
+
+def test_gaps_must_be_whitespace(matcher):
+    """Test that other characters between the words break the phrase."""
+    assert matcher.match_bytes(b"social-security number") == [(7, 22, "security number")]
+    assert matcher.match_bytes(b"social security, number") == []
+    assert matcher.match_bytes(b"socialsecurity number") == []
+
+
+def test_whole_words(matcher):
+    """Test that words only match whole words."""
+    assert matcher.match_bytes(b"antisocial security numbers") == []
+    assert matcher.match_bytes(b"ssn: SSNs") == [(0, 3, "SSN")]
+
+
+def test_max_gap():
+    """Test that max_gap bounds the whitespace between two words."""
+    matcher = PhraseMatcher(["security number"], max_gap=2)
+    assert matcher.max_gap == 2
+    assert matcher.match_bytes(b"security \nnumber security   number") == [(0, 16, "security number")]
+    with pytest.raises(ValueError, match="max_gap must be positive"):
+        PhraseMatcher(["security number"], max_gap=0)
+
+
+def test_case_sensitive():
+    """Test that case-sensitive matchers only match the exact case of the words."""
+    matcher = PhraseMatcher(["Social Security"], case_insensitive=False)
+    assert matcher.match_bytes(b"social security Social\nSecurity") == [(16, 31, "Social Security")]
+
+
+def test_repeated_words():
+    """Test phrases repeating a word, and words shared between phrases."""
+    matcher = PhraseMatcher(["very very hot", "very hot"])
+    assert matcher.word_count == 2
+    assert matcher.match_bytes(b"very very very hot") == [
+        (5, 18, "very very hot"),
+        (10, 18, "very hot"),
+    ]
+
+
+def test_file(matcher, tmp_path):
+    """Test memory-mapped file matching."""
+    data = b"my social security\nnumber\n" * 1000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    assert matcher.match_file_memmap(str(path)) == matcher.match_bytes(data)
+    assert len(matcher.match_bytes(data)) == 2000
+    with pytest.raises(IOError):
+        matcher.match_file_memmap(str(tmp_path / "missing.txt"))
+
+
+def test_options(matcher):
+    """Test the options, the repr and phrase validation."""
+    assert len(matcher) == 4
+    assert matcher.pattern_count == 4
+    assert matcher.patterns == PHRASES
+    assert matcher.word_count == 5
+    assert matcher.case_insensitive is True
+    assert matcher.max_gap is None
+    assert matcher.memory_usage() > 0
+    assert repr(matcher) == "PhraseMatcher(phrases=4, words=5, case_insensitive=True)"
+    assert PhraseMatcher(["", " \n", "ssn"]).patterns == ["ssn"]
+    with pytest.raises(ValueError, match="Phrase set cannot be empty"):
+        PhraseMatcher(["", "  "])
@@ -0,0 +1,134 @@
+import os
+import tempfile
+
+import pytest
+from voluta import TextMatcher
+
+
+def test_postprocess_property():
+    """Test the postprocess property reflects the constructor argument."""
+    assert TextMatcher(["a"]).postprocess is None
+    assert TextMatcher(["a"], postprocess="merge").postprocess == "merge"
+    assert TextMatcher(["a"], postprocess="longest").postprocess == "longest"
+    assert TextMatcher(["a"], postprocess="priority").postprocess == "priority"
+
+
+def test_invalid_postprocess():
+    """Test that unknown strategies are rejected."""
+    with pytest.raises(ValueError) as excinfo:
+        TextMatcher(["a"], postprocess="shortest")
+
+    assert "Unknown postprocess strategy" in str(excinfo.value)
+
+
+def test_merge_overlapping_and_adjacent():
+    """Test that merge joins overlapping and touching spans."""
+    matcher = TextMatcher(["new york", "york city", "cityhall"], postprocess="merge")
+
+    # "new york", "york city" and "cityhall" form one overlapping chain
+    matches = matcher.match_bytes(b"new york cityhall and new york")
+    assert matches == [(0, 17, "york city"), (22, 30, "new york")]
+
+    # Adjacent spans are merged as well
+    matcher = TextMatcher(["ab", "cd"], postprocess="merge")
+    assert matcher.match_bytes(b"abcd ab") == [(0, 4, "ab"), (5, 7, "ab")]
+
+
+def test_longest_wins():
+    """Test that longest keeps only the longest of overlapping matches."""
+    matcher = TextMatcher(["new", "new york", "york", "york city"], postprocess="longest")
+
+    # "new york" loses to "york city", but "new" does not overlap "york city" and is kept
+    matches = matcher.match_bytes(b"new york city")
+    assert matches == [(0, 3, "new"), (4, 13, "york city")]
+
+    # Equal lengths are resolved in favor of the leftmost match
+    matcher = TextMatcher(["abc", "cde", "bcd"], postprocess="longest")
+    assert matcher.match_bytes(b"abcde") == [(0, 3, "abc")]
+
+
+def test_priority_wins():
+    """Test that patterns listed first take precedence over overlapping matches."""
+    matcher = TextMatcher(["york", "new york"], postprocess="priority")
+    assert matcher.match_bytes(b"new york") == [(4, 8, "york")]
+
+    matcher = TextMatcher(["new york", "york"], postprocess="priority")
+    assert matcher.match_bytes(b"new york") == [(0, 8, "new york")]
+
+
+def test_no_overlap_is_unchanged():
+    """Test that non-overlapping matches pass through every strategy."""
+    data = b"error then warning then error"
+    expected = TextMatcher(["error", "warning"]).match_bytes(data)
+
+    for strategy in ["merge", "longest", "priority"]:
+        matcher = TextMatcher(["error", "warning"], postprocess=strategy)
+        assert matcher.match_bytes(data) == expected
+
+
+def test_postprocess_file_methods():
+    """Test that all file methods agree when post-processing is enabled."""
+    content = "the quick brown fox jumped over the lazy dog\n" * 2000
+    patterns = ["quick brown", "brown fox", "fox", "lazy", "lazy dog"]
+
+    with tempfile.NamedTemporaryFile(mode="w", delete=False) as f:
+        f.write(content)
+        temp_path = f.name
+
+    try:
+        for strategy in ["merge", "longest", "priority"]:
+            matcher = TextMatcher(patterns, postprocess=strategy)
+            expected = matcher.match_bytes(content.encode())
+
+            assert matcher.match_file_memmap(temp_path, 1000) == expected
+            assert matcher.match_file_memmap_parallel(temp_path, 1000) == expected
+            assert matcher.match_file_stream(temp_path) == expected
+            assert matcher.match_stream(content.encode(), 1000) == expected
+
+            # Line-based results are resolved per line
+            line_matches = matcher.match_file(temp_path)
+            assert len(line_matches) == len(expected)
+    finally:
+        os.unlink(temp_path)
+
+
+def test_explicit_priorities():
+    """Test that higher priorities win regardless of pattern order."""
+    patterns = ["card", "credit card", "credit card number"]
+    data = b"credit card number"
+
+    matcher = TextMatcher(patterns, priorities=[0, 10, 10])
+    assert matcher.postprocess == "priority"
+    assert matcher.prefer_longer is True
+    assert matcher.match_bytes(data) == [(0, 18, "credit card number")]
+
+    # The generic term wins when it gets the highest priority
+    matcher = TextMatcher(patterns, priorities=[20, 10, 10])
+    assert matcher.match_bytes(data) == [(7, 11, "card")]
+
+
+def test_priority_length_tiebreak():
+    """Test that prefer_longer decides between equal priorities."""
+    patterns = ["card", "credit card", "credit card number"]
+    data = b"credit card number"
+
+    matcher = TextMatcher(patterns, priorities=[0, 10, 10], prefer_longer=False)
+    assert matcher.prefer_longer is False
+    assert matcher.match_bytes(data) == [(0, 11, "credit card")]
+
+
+def test_priorities_with_empty_patterns():
+    """Test that priorities stay aligned when empty patterns are filtered out."""
+    matcher = TextMatcher(["", "york", "", "new york"], priorities=[100, 1, 100, 2])
+    assert matcher.match_bytes(b"new york") == [(0, 8, "new york")]
+
+
+def test_invalid_priorities():
+    """Test that misaligned or conflicting priorities are rejected."""
+    with pytest.raises(ValueError) as excinfo:
+        TextMatcher(["a", "b"], priorities=[1])
+    assert "one entry per pattern" in str(excinfo.value)
+
+    with pytest.raises(ValueError) as excinfo:
+        TextMatcher(["a", "b"], priorities=[1, 2], postprocess="merge")
+    assert "cannot be combined" in str(excinfo.value)
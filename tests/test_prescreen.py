@@ -0,0 +1,90 @@
+import random
+
+import pytest
+from voluta import PatternError, PreScreen, TextMatcher
+
+PATTERNS = ["password", "api_key", "BEGIN RSA PRIVATE KEY", "ssn:", "4111111111111111"]
+
+
+def random_docs(seed, count, length):
+    rng = random.Random(seed)
+    alphabet = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 _:\n"
+    return [bytes(rng.choice(alphabet) for _ in range(length)) for _ in range(count)]
+
+
+def test_no_false_negatives():
+    """Test that every document with a match passes the pre-screen."""
+    matcher = TextMatcher(PATTERNS)
+    screen = matcher.prescreen()
+    docs = random_docs(1, 200, 500)
+    rng = random.Random(2)
+    for idx in range(0, len(docs), 3):
+        pos = rng.randrange(len(docs[idx]))
+        docs[idx] = docs[idx][:pos] + rng.choice(PATTERNS).encode() + docs[idx][pos:]
+    for doc in docs:
+        if matcher.match_bytes(doc):
+            assert screen.might_contain(doc)
+
+
+def test_rejects_documents_without_matches():
+    """Test that documents without matches fail the pre-screen but for rare false positives."""
+    matcher = TextMatcher(PATTERNS)
+    screen = matcher.prescreen()
+    docs = [doc for doc in random_docs(3, 200, 2000) if not matcher.match_bytes(doc)]
+    assert sum(screen.might_contain(doc) for doc in docs) <= 2
+    assert screen.ngram == 4
+    assert screen.size == 64
+
+
+@pytest.mark.parametrize("whole_word", [False, True])
+def test_case_insensitive(whole_word):
+    """Test that case-insensitive pre-screens pass documents in any case."""
+    matcher = TextMatcher(["Secret", "token"], case_insensitive=True, whole_word=whole_word)
+    screen = matcher.prescreen()
+    assert screen.might_contain(b"the SECRET is out")
+    assert screen.might_contain(b"a Token")
+    assert not screen.might_contain(b"nothing to see here")
+
+
+def test_case_sensitive():
+    """Test that case-sensitive pre-screens tell cases apart."""
+    screen = TextMatcher(["Secret"], case_insensitive=False).prescreen()
+    assert screen.might_contain(b"my Secret")
+    assert not screen.might_contain(b"my SECRET")
+
+
+def test_short_documents():
+    """Test that documents shorter than the n-grams cannot match."""
+    screen = TextMatcher(["abc", "abcdefghijkl"]).prescreen()
+    assert screen.ngram == 3
+    assert not screen.might_contain(b"")
+    assert not screen.might_contain(b"ab")
+    assert screen.might_contain(b"abc")
+
+
+def test_round_trip():
+    """Test that serialized pre-screens behave as the original."""
+    screen = TextMatcher(PATTERNS, case_insensitive=True).prescreen()
+    data = screen.to_bytes()
+    assert len(data) == 8 + screen.size
+    copy = PreScreen.from_bytes(data)
+    assert (copy.ngram, copy.size) == (screen.ngram, screen.size)
+    assert copy.to_bytes() == data
+    for doc in random_docs(4, 50, 300) + [b"my PASSWORD"]:
+        assert copy.might_contain(doc) == screen.might_contain(doc)
+
+
+@pytest.mark.parametrize("data", [b"", b"VPS1", b"XXXX\x04\x00\x03\x00" + bytes(64), b"VPS1\x04\x00\x03\x00" + bytes(63)])
+def test_invalid_bytes(data):
+    """Test that bytes not produced by to_bytes are rejected."""
+    with pytest.raises(ValueError, match="Invalid pre-screen data"):
+        PreScreen.from_bytes(data)
+
+
+@pytest.mark.parametrize(
+    "options", [{"fuzzy": 1}, {"ignore_separators": True}, {"encoding": "latin-1"}]
+)
+def test_unsupported_matchers(options):
+    """Test that matchers matching text without the pattern n-grams are rejected."""
+    with pytest.raises(PatternError):
+        TextMatcher(PATTERNS, **options).prescreen()
@@ -0,0 +1,49 @@
+import os
+import subprocess
+import sys
+
+import pytest
+from voluta import ScanIOError, TextMatcher
+
+if sys.platform != "linux":
+    pytest.skip("process memory scans are Linux only", allow_module_level=True)
+
+# Offset of the data of a bytes object from its address, after its header
+BYTES_HEADER = sys.getsizeof(b"") - 1
+
+
+def test_own_memory():
+    """Test that a secret on the heap is found at its address."""
+    marker = "-".join(["voluta", "process", "marker"]).encode()
+    matcher = TextMatcher([marker.decode()])
+    matches = matcher.match_process(os.getpid())
+    addresses = {base + start for base, _, start, _, _ in matches}
+    assert id(marker) + BYTES_HEADER in addresses
+    assert all(end - start == len(marker) for _, _, start, end, _ in matches)
+    assert all(pattern == marker.decode() for *_, pattern in matches)
+
+
+def test_child_process():
+    """Test scanning another process, with and without its file mappings."""
+    code = "import sys; s = 'child' + '-secret-' + 'value'; print(flush=True); sys.stdin.read()"
+    child = subprocess.Popen(
+        [sys.executable, "-c", code], stdin=subprocess.PIPE, stdout=subprocess.PIPE
+    )
+    try:
+        child.stdout.readline()
+        matcher = TextMatcher(["child-secret-value"])
+        try:
+            matches = matcher.match_process(child.pid, include_files=False)
+        except ScanIOError:
+            pytest.skip("ptrace access to child processes is restricted")
+        assert matches
+        assert all(not region.startswith("/") for _, region, *_ in matches)
+    finally:
+        child.stdin.close()
+        child.wait()
+
+
+def test_missing_process():
+    """Test that scanning a process that does not exist raises ScanIOError."""
+    with pytest.raises(ScanIOError):
+        TextMatcher(["x"]).match_process(2**22 + 1)
@@ -0,0 +1,72 @@
+import gzip
+import io
+import random
+
+import pytest
+from voluta import TextMatcher
+
+
+@pytest.mark.parametrize(
+    "options",
+    [
+        {},
+        {"whole_word": True},
+        {"postprocess": "longest"},
+        {"case_insensitive": False, "whole_word": True},
+    ],
+)
+def test_matches_bytes(options):
+    """Test that chunked parallel scans find the same matches as a scan of the whole data."""
+    rng = random.Random(3)
+    patterns = ["ab", "abc", "ba", "b a", "cab"]
+    matcher = TextMatcher(patterns, engine="aho", **options)
+    for _ in range(200):
+        data = "".join(rng.choice("abc ") for _ in range(rng.randint(0, 200))).encode()
+        buffer_size = rng.randint(1, 16)
+        result = matcher.match_readable_parallel(io.BytesIO(data), buffer_size, rng.randint(1, 4))
+        assert result == matcher.match_bytes(data), (data, buffer_size)
+
+
+def test_non_overlapping_deterministic():
+    """Test that non-overlapping results depend on the chunk size only, not on the workers."""
+    rng = random.Random(5)
+    matcher = TextMatcher(["ab", "abc", "ba", "b a"], overlapping=False)
+    data = "".join(rng.choice("abc ") for _ in range(5000)).encode()
+    expected = matcher.match_readable_parallel(io.BytesIO(data), 64, 1)
+    for n_threads in (2, 3, 8):
+        assert matcher.match_readable_parallel(io.BytesIO(data), 64, n_threads) == expected
+
+
+def test_matches_spanning_chunks():
+    """Test that matches crossing chunk boundaries are found once with their stream offsets."""
+    matcher = TextMatcher(["password"])
+    data = b"x" * 1000 + b"password" + b"y" * 995 + b"PASSWORD"
+    assert matcher.match_readable_parallel(io.BytesIO(data), 1004, 3) == [
+        (1000, 1008, "password"),
+        (2003, 2011, "password"),
+    ]
+
+
+def test_decompression_stream(tmp_path):
+    """Test scanning a gzip stream, which cannot be memory-mapped."""
+    path = tmp_path / "input.log.gz"
+    data = b"info ok\nerror: disk full\nwarning: slow\n" * 5000
+    with gzip.open(path, "wb") as f:
+        f.write(data)
+
+    matcher = TextMatcher(["error", "warning"])
+    with gzip.open(path, "rb") as f:
+        result = matcher.match_readable_parallel(f, 64 * 1024)
+    assert result == matcher.match_bytes(data)
+    assert len(result) == 10000
+
+
+def test_empty_source():
+    """Test that an empty source has no matches."""
+    assert TextMatcher(["error"]).match_readable_parallel(io.BytesIO(b"")) == []
+
+
+def test_read_errors():
+    """Test that objects whose read() does not return bytes are rejected."""
+    with pytest.raises(TypeError):
+        TextMatcher(["error"]).match_readable_parallel(io.StringIO("error"))
@@ -0,0 +1,64 @@
+import asyncio
+
+import pytest
+from voluta import TextMatcher
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["secret", "token"])
+
+
+def test_nul_delimited(matcher, tmp_path):
+    """Test that NUL-delimited records are numbered and offsets are relative to the record."""
+    path = tmp_path / "export.bin"
+    path.write_bytes(b"id=1 secret\x00id=2\nnothing\x00id=3 token secret")
+    assert matcher.match_file(str(path), delimiter=b"\x00") == [
+        (1, 5, 11, "secret"),
+        (3, 5, 10, "token"),
+        (3, 11, 17, "secret"),
+    ]
+
+
+def test_multi_byte_delimiter(matcher, tmp_path):
+    """Test that multi-line records separated by blank lines count as one record each."""
+    path = tmp_path / "app.log"
+    path.write_bytes(b"ERROR\r\n  token leaked\r\n\r\nINFO ok\r\n\r\nWARN\r\n  secret\r\n")
+    assert matcher.match_file(str(path), delimiter="\r\n\r\n") == [
+        (1, 9, 14, "token"),
+        (3, 8, 14, "secret"),
+    ]
+
+
+def test_sentinel_string(matcher, tmp_path):
+    """Test a sentinel string delimiter, whose first bytes also appear in the records."""
+    path = tmp_path / "dump.txt"
+    path.write_text("a -- token ---END--- b secret ---END---")
+    assert matcher.match_file(str(path), delimiter="---END---") == [
+        (1, 5, 10, "token"),
+        (2, 3, 9, "secret"),
+    ]
+
+
+def test_default_is_lines(matcher, tmp_path):
+    """Test that the default delimiter is the newline."""
+    path = tmp_path / "app.log"
+    path.write_text("secret\ntoken\n")
+    assert matcher.match_file(str(path)) == matcher.match_file(str(path), delimiter="\n")
+    assert matcher.match_file(str(path)) == [(1, 0, 6, "secret"), (2, 0, 5, "token")]
+
+    async def scan():
+        return await matcher.amatch_file(str(path), delimiter=b"\n")
+
+    assert asyncio.run(scan()) == matcher.match_file(str(path))
+
+
+def test_delimiter_validated(matcher, tmp_path):
+    """Test the delimiter validation."""
+    path = tmp_path / "app.log"
+    path.write_text("secret")
+    with pytest.raises(ValueError, match="delimiter cannot be empty"):
+        matcher.match_file(str(path), delimiter=b"")
+    with pytest.raises(TypeError, match="delimiter must be bytes or str"):
+        matcher.match_file(str(path), delimiter=0)
@@ -0,0 +1,140 @@
+import re
+
+import pytest
+from voluta import ScanReport, TextMatcher
+
+
+@pytest.fixture
+def scan_files(tmp_path):
+    """Fixture that provides log files with and without findings."""
+    first = tmp_path / "app.log"
+    first.write_bytes(b"boot ok\nerror: disk <full>\nwarning: cpu | hot\r\nerror again\n")
+    second = tmp_path / "clean.log"
+    second.write_bytes(b"all good\n")
+    return [str(first), str(second)]
+
+
+@pytest.fixture
+def report(scan_files):
+    """Fixture that provides a report over the scan files and a missing file."""
+    matcher = TextMatcher(["error", "warning", "critical"])
+    return matcher.scan_report(
+        scan_files + ["nonexistent_file.txt"], context=6, title="Nightly scan", scan_id="scan-1"
+    )
+
+
+def test_report_summary(report, scan_files):
+    """Test the statistics of a report."""
+    assert isinstance(report, ScanReport)
+    assert report.title == "Nightly scan"
+    assert report.scan_id == "scan-1"
+    assert re.fullmatch(r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z", report.started_at)
+    assert report.duration >= 0
+    assert report.files_scanned == 3
+    assert report.total_matches == 3
+    assert report.bytes_scanned == 59 + 9
+    assert report.file_matches == [
+        (scan_files[0], 3),
+        (scan_files[1], 0),
+        ("nonexistent_file.txt", 0),
+    ]
+    assert report.pattern_tallies == [("error", 2, 1), ("warning", 1, 1), ("critical", 0, 0)]
+    assert [path for path, _ in report.errors] == ["nonexistent_file.txt"]
+    assert repr(report) == "ScanReport(files_scanned=3, matches=3, bytes_scanned=68)"
+
+
+def test_report_html(report, scan_files):
+    """Test the HTML rendering of a report."""
+    html = report.to_html()
+    assert html.startswith("<!DOCTYPE html>")
+    assert "<title>Nightly scan</title>" in html
+    assert "<tr><th>Matches</th><td>3</td></tr>" in html
+    assert "<tr><td><code>critical</code></td><td>0</td><td>0</td></tr>" in html
+    # Line, column, offset, pattern and escaped context clipped to the line
+    assert (
+        "<tr><td>2</td><td>1</td><td>8</td><td><code>error</code></td>"
+        "<td><code><mark>error</mark>: disk</code></td></tr>"
+    ) in html
+    assert "<td><code><mark>warning</mark>: cpu </code></td>" in html
+    assert "Could not be scanned" in html
+
+
+def test_report_markdown(report):
+    """Test the Markdown rendering of a report."""
+    md = report.to_markdown()
+    assert md.startswith("# Nightly scan\n")
+    assert "| Matches | 3 |" in md
+    assert "| critical | 0 | 0 |" in md
+    assert "| 2 | 1 | 8 | error | **error**: disk |" in md
+    assert "| 3 | 1 | 27 | warning | **warning**: cpu  |" in md
+    assert "| 4 | 1 | 47 | error | **error** again |" in md
+
+
+def test_report_escaping(tmp_path):
+    """Test that matched text is escaped in both formats."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(b"x <b>|* y\n")
+    report = TextMatcher(["<b>|*"]).scan_report([str(path)])
+
+    assert "<mark>&lt;b&gt;|*</mark>" in report.to_html()
+    assert "**\\<b\\>\\|\\***" in report.to_markdown()
+
+
+def test_report_max_findings(tmp_path):
+    """Test that listed findings are capped while tallies count every match."""
+    path = tmp_path / "input.txt"
+    path.write_bytes(b"error\n" * 10)
+    report = TextMatcher(["error"]).scan_report([str(path)], max_findings=3)
+
+    assert report.total_matches == 10
+    assert report.pattern_tallies == [("error", 10, 1)]
+    assert "10 matches in 60 bytes, showing the first 3" in report.to_html()
+    assert report.to_markdown().count("**error**") == 3
+
+    uncapped = TextMatcher(["error"]).scan_report([str(path)], max_findings=None)
+    assert uncapped.to_markdown().count("**error**") == 10
+
+
+def test_report_save(report, tmp_path):
+    """Test saving a report with the format inferred from the extension or given explicitly."""
+    report.save(str(tmp_path / "report.html"))
+    assert (tmp_path / "report.html").read_text() == report.to_html()
+
+    report.save(str(tmp_path / "report.md"))
+    assert (tmp_path / "report.md").read_text() == report.to_markdown()
+
+    report.save(str(tmp_path / "report.txt"), format="markdown")
+    assert (tmp_path / "report.txt").read_text() == report.to_markdown()
+
+    with pytest.raises(ValueError):
+        report.save(str(tmp_path / "report.txt"))
+
+    with pytest.raises(ValueError):
+        report.save(str(tmp_path / "report.html"), format="pdf")
+
+
+def test_report_keeps_path_order(tmp_path):
+    """Test that files are reported in the given order whatever order they are scanned in."""
+    paths = []
+    for idx, size in enumerate([10, 50_000, 0, 2_000_000, 300]):
+        path = tmp_path / f"file{idx}.log"
+        path.write_bytes((b"error " * size)[:size])
+        paths.append(str(path))
+    report = TextMatcher(["error"]).scan_report(paths)
+    assert [path for path, _ in report.file_matches] == paths
+    assert [count for _, count in report.file_matches] == [1, 8333, 0, 333333, 50]
+
+
+def test_report_splits_large_files(tmp_path):
+    """Test that files scanned in parallel chunks report every match once, in order."""
+    path = tmp_path / "large.log"
+    line = b"x" * 1000 + b" error warning\n"
+    path.write_bytes(line * (65 * 1024 * 1024 // len(line) + 1))
+    matcher = TextMatcher(["error", "warning"])
+    report = matcher.scan_report([str(path)], max_findings=None)
+
+    expected = matcher.match_file_collection(str(path)).to_list()
+    assert report.total_matches == len(expected)
+    markdown = report.to_markdown()
+    assert markdown.count("**error**") == markdown.count("**warning**") == len(expected) // 2
+
@@ -0,0 +1,79 @@
+import warnings
+
+import pytest
+from voluta import ScanStats, TextMatcher
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["password", "token"])
+
+
+@pytest.fixture
+def data():
+    """Fixture that provides data with matches of both patterns."""
+    return (b"password=x token=y " * 100) + b"password"
+
+
+def test_bytes_stats(matcher, data):
+    """Test that match_bytes returns statistics only when asked to."""
+    assert isinstance(matcher.match_bytes(data), list)
+
+    matches, stats = matcher.match_bytes(data, stats=True)
+    assert matches == matcher.match_bytes(data)
+    assert isinstance(stats, ScanStats)
+    assert stats.bytes_scanned == len(data)
+    assert stats.chunks == 1
+    assert stats.matches_per_pattern == {"password": 101, "token": 100}
+    assert stats.total_matches == len(matches)
+    assert stats.elapsed >= 0
+    assert stats.throughput >= 0
+    assert "ScanStats(bytes_scanned=" in repr(stats)
+
+
+def test_file_stats(matcher, data, tmp_path):
+    """Test that chunked file scans count their chunks and never find a match twice."""
+    path = tmp_path / "data.txt"
+    path.write_bytes(data)
+
+    # Chunks of 20 bytes overlap by 7, the longest pattern but one, and a chunk ends once it
+    # reaches the end; the matches inside an overlap belong to the chunk they start in
+    chunks = -(-(len(data) - 7) // 20)
+    matches, stats = matcher.match_file_memmap(str(path), chunk_size=20, stats=True)
+    assert stats.bytes_scanned == len(data)
+    assert stats.chunks == chunks
+    assert stats.total_matches == len(matches) == 201
+
+    matches, stats = matcher.match_file_memmap_parallel(
+        str(path), chunk_size=20, n_threads=2, stats=True
+    )
+    assert stats.chunks == chunks
+    assert stats.matches_per_pattern == {"password": 101, "token": 100}
+
+    _, stats = matcher.match_file_stream(str(path), buffer_size=500, stats=True)
+    assert stats.chunks == -(-len(data) // 500)
+    assert stats.bytes_scanned == len(data)
+
+
+def test_dedup_collisions_deprecated(matcher, data):
+    """Test that the deprecated dedup_collisions is still 0 and warns when read."""
+    _, stats = matcher.match_bytes(data, stats=True)
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        assert stats.dedup_collisions == 0
+    assert [w.category for w in caught] == [DeprecationWarning]
+    assert "dedup_collisions" not in repr(stats)
+
+
+def test_stream_stats(matcher, data):
+    """Test statistics of match_stream."""
+    matches, stats = matcher.match_stream(data, buffer_size=1000, stats=True)
+    assert stats.chunks == 2
+    assert stats.total_matches == len(matches)
+
+
+def test_stats_errors(matcher, tmp_path):
+    """Test that failing scans raise rather than return statistics."""
+    with pytest.raises(IOError):
+        matcher.match_file_memmap(str(tmp_path / "missing"), stats=True)
@@ -0,0 +1,87 @@
+import pytest
+from voluta import TextMatcher
+
+DATA = b"card: 4111 1111-1111 1111, ssn: 123 45 6789. raw: 4111111111111111"
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher ignoring separators."""
+    return TextMatcher(["4111111111111111", "SSN 123-45-6789"], ignore_separators=True)
+
+
+def test_formatted_numbers(matcher):
+    """Test that separators are ignored and offsets point into the raw input."""
+    assert matcher.ignore_separators is True
+    assert matcher.match_bytes(DATA) == [
+        (6, 25, "4111111111111111"),
+        (27, 43, "SSN 123-45-6789"),
+        (50, 66, "4111111111111111"),
+    ]
+    start, end, _ = matcher.match_bytes(DATA)[0]
+    assert DATA[start:end] == b"4111 1111-1111 1111"
+
+
+def test_default_is_exact():
+    """Test that separators are significant by default."""
+    matcher = TextMatcher(["4111111111111111"])
+    assert matcher.ignore_separators is False
+    assert matcher.match_bytes(DATA) == [(50, 66, "4111111111111111")]
+
+
+def test_separator_only_patterns_dropped():
+    """Test that patterns made only of separators are dropped."""
+    matcher = TextMatcher(["--", "a-b"], ignore_separators=True)
+    assert matcher.patterns == ["a-b"]
+    assert matcher.match_bytes(b"ab a b a--b") == [(0, 2, "a-b"), (3, 6, "a-b"), (7, 11, "a-b")]
+    with pytest.raises(ValueError, match="Pattern set cannot be empty"):
+        TextMatcher([" ", "..."], ignore_separators=True)
+
+
+def test_combined_options():
+    """Test whole words, postprocessing and fuzzy distances on texts with separators."""
+    matcher = TextMatcher(["password"], ignore_separators=True, fuzzy=1, whole_word=True)
+    assert matcher.match_bytes(b"p-a-s-w-o-r-d pass word passwords") == [
+        (0, 13, "password"),
+        (14, 23, "password"),
+    ]
+    matcher = TextMatcher(["12 34", "3456"], ignore_separators=True, postprocess="merge")
+    assert matcher.match_bytes(b"1-2-3-4-5-6") == [(0, 11, "12 34")]
+    with pytest.raises(ValueError, match="hash_key"):
+        TextMatcher(["4111"], ignore_separators=True, hash_key=b"key")
+
+
+def test_scans_match_bytes(matcher, tmp_path):
+    """Test that chunked, parallel and streamed scans find the matches of match_bytes."""
+    data = DATA + b"\n" + b"x 4111 1111 1111 1111 y\n" * 20000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    expected = matcher.match_bytes(data)
+    assert len(expected) == 20003
+
+    found = matcher.match_file_memmap_parallel(str(path), chunk_size=4096, n_threads=4)
+    assert sorted(found) == expected
+    streamed = matcher.match_stream(data, buffer_size=4096)
+    assert sorted((start, end, name) for start, end, name in streamed) == expected
+
+
+@pytest.mark.parametrize("chunk_size", [8, 16, 32, 100, 4096])
+def test_long_separator_runs_across_chunks(tmp_path, chunk_size):
+    """Test that matches spread over more separators than twice the pattern are found by every
+    scan whatever the chunk size, and runs longer than the limit split matches everywhere."""
+    matcher = TextMatcher(["4111", "secret"], ignore_separators=True)
+    spread = b"4" + b" - . - " + b"1" + b"........" + b"1" + b"\t\t\t" + b"1"
+    split = b"s e c r e" + b" " * 9 + b"t"
+    data = b"x" * 10 + spread + b" y " + split + b"\n" + (b"z" * 5 + spread) * 3
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    expected = matcher.match_bytes(data)
+    assert [(start, end) for start, end, _ in expected][0] == (10, 10 + len(spread))
+    assert [name for _, _, name in expected] == ["4111"] * 4
+
+    assert matcher.match_file_memmap(str(path), chunk_size=chunk_size) == expected
+    found = matcher.match_file_memmap_parallel(str(path), chunk_size=chunk_size, n_threads=4)
+    assert sorted(found) == expected
+    assert matcher.match_file_stream(str(path), buffer_size=chunk_size) == expected
+    streamed = matcher.match_stream(data, buffer_size=chunk_size)
+    assert sorted((start, end, name) for start, end, name in streamed) == expected
@@ -0,0 +1,38 @@
+from voluta import ConfirmMatcher, TextMatcher
+
+DATA = b"error warning error ERROR warning"
+
+
+def test_results_share_names():
+    """Test that every match of a pattern reports the same string object."""
+    matcher = TextMatcher(["error", "warning"])
+    first = matcher.match_bytes(DATA)
+    second = matcher.match_bytes(DATA)
+    errors = [name for _, _, name in first + second if name == "error"]
+    assert len(errors) == 6
+    assert all(name is errors[0] for name in errors)
+
+
+def test_collections_share_names():
+    """Test that collections and the collections derived from them share names."""
+    matcher = TextMatcher(["error", "warning"])
+    collection = matcher.match_bytes_collection(DATA)
+    assert collection[0][2] is collection[2][2] is collection.to_list()[3][2]
+    assert collection.filter("error").to_list()[0][2] is collection[0][2]
+
+
+def test_line_results_share_names(tmp_path):
+    """Test that line-based results share names too."""
+    path = tmp_path / "input.log"
+    path.write_bytes(DATA.replace(b" ", b"\n"))
+    result = TextMatcher(["error"]).match_file(str(path))
+    assert [line for line, _, _, _ in result] == [1, 3, 4]
+    assert result[0][3] is result[1][3] is result[2][3]
+
+
+def test_rule_names_shared():
+    """Test that confirmed matches share their rule names."""
+    matcher = ConfirmMatcher([("level", ["error"], r"error")], case_insensitive=False)
+    result = matcher.match_bytes(DATA)
+    assert len(result) == 2
+    assert result[0][2] is result[1][2]
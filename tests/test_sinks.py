@@ -0,0 +1,475 @@
+import csv
+import json
+import os
+import tempfile
+
+import pytest
+from voluta import TextMatcher, TextMatcherBuilder
+
+
+@pytest.fixture
+def sink_dir():
+    """Fixture that provides a temporary directory for sink files."""
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        yield tmp_dir
+
+
+def test_bytes_to_ndjson_sink(sink_dir):
+    """Test writing matches from bytes to an NDJSON file."""
+    matcher = TextMatcher(["error", "warning"])
+    data = b"error: disk full\nwarning: cpu hot\nerror again"
+    sink_path = os.path.join(sink_dir, "findings.ndjson")
+
+    summary = matcher.match_bytes_to_sink(data, sink_path)
+    assert summary.sink == sink_path
+    assert summary.format == "ndjson"
+    assert summary.matches_written == 3
+    assert summary.bytes_scanned == len(data)
+
+    with open(sink_path) as f:
+        records = [json.loads(line) for line in f]
+
+    expected = [
+        {"start": start, "end": end, "pattern": pattern}
+        for start, end, pattern in matcher.match_bytes(data)
+    ]
+    assert records == expected
+
+
+def test_file_to_csv_sink(sink_dir):
+    """Test writing matches from a file to a CSV file."""
+    matcher = TextMatcher(["fox", "dog"])
+    content = "the quick fox jumps over the lazy dog\n" * 1000
+    path = os.path.join(sink_dir, "input.txt")
+    with open(path, "w") as f:
+        f.write(content)
+
+    sink_path = os.path.join(sink_dir, "findings.csv")
+    summary = matcher.match_file_to_sink(path, sink_path)
+    assert summary.format == "csv"
+    assert summary.matches_written == 2000
+    assert summary.bytes_scanned == len(content)
+
+    with open(sink_path, newline="") as f:
+        rows = list(csv.DictReader(f))
+
+    assert len(rows) == 2000
+    rows_as_tuples = [(int(r["start"]), int(r["end"]), r["pattern"]) for r in rows]
+    assert rows_as_tuples == matcher.match_file_memmap(path)
+
+
+def test_csv_quoting(sink_dir):
+    """Test that patterns with separators and quotes are quoted in CSV."""
+    patterns = ["a,b", 'say "hi"', "line\nbreak"]
+    matcher = TextMatcher(patterns)
+    data = 'a,b say "hi" line\nbreak'.encode()
+    sink_path = os.path.join(sink_dir, "findings.csv")
+
+    matcher.match_bytes_to_sink(data, sink_path)
+
+    with open(sink_path, newline="") as f:
+        rows = list(csv.DictReader(f))
+    assert [r["pattern"] for r in rows] == patterns
+
+
+def test_explicit_format(sink_dir):
+    """Test that an explicit format overrides the extension."""
+    matcher = TextMatcher(["x"])
+    sink_path = os.path.join(sink_dir, "findings.out")
+
+    with pytest.raises(ValueError):
+        matcher.match_bytes_to_sink(b"x", sink_path)
+
+    with pytest.raises(ValueError):
+        matcher.match_bytes_to_sink(b"x", sink_path, format="parquet")
+
+    summary = matcher.match_bytes_to_sink(b"x x", sink_path, format="jsonl")
+    assert summary.format == "ndjson"
+    with open(sink_path) as f:
+        assert len(f.readlines()) == 2
+
+
+def test_sink_with_postprocess(sink_dir):
+    """Test that post-processing applies to sink output."""
+    matcher = TextMatcher(["new york", "york"], postprocess="longest")
+    sink_path = os.path.join(sink_dir, "findings.ndjson")
+
+    summary = matcher.match_bytes_to_sink(b"new york", sink_path)
+    assert summary.matches_written == 1
+
+
+def test_sink_errors(sink_dir):
+    """Test that unreadable inputs and unwritable sinks raise IOError."""
+    matcher = TextMatcher(["x"])
+
+    with pytest.raises(IOError):
+        matcher.match_file_to_sink("/nonexistent/file.txt", os.path.join(sink_dir, "a.csv"))
+
+    with pytest.raises(IOError):
+        matcher.match_bytes_to_sink(b"x", os.path.join(sink_dir, "missing", "a.csv"))
+
+
+def test_sqlite_sink(sink_dir):
+    """Test writing findings to an SQLite database."""
+    import sqlite3
+
+    matcher = TextMatcher(["error", "warning"])
+    content = "error then warning\n" * 100
+    path = os.path.join(sink_dir, "input.log")
+    with open(path, "w") as f:
+        f.write(content)
+
+    db_path = os.path.join(sink_dir, "findings.db")
+    summary = matcher.match_file_to_sink(path, db_path, scan_id="scan-1")
+    assert summary.format == "sqlite"
+    assert summary.scan_id == "scan-1"
+    assert summary.matches_written == 200
+
+    with sqlite3.connect(db_path) as conn:
+        rows = conn.execute(
+            "SELECT scan_id, file, start_offset, end_offset, pattern, tag, found_at "
+            "FROM findings ORDER BY start_offset"
+        ).fetchall()
+
+    assert len(rows) == 200
+    assert all(r[0] == "scan-1" and r[1] == path and r[5] is None for r in rows)
+    assert [(r[2], r[3], r[4]) for r in rows] == matcher.match_file_memmap(path)
+    assert rows[0][6].endswith("Z")
+
+
+def test_sqlite_sink_tags(sink_dir):
+    """Test that the tags of each pattern are stored comma-joined, NULL for untagged patterns."""
+    import sqlite3
+
+    tags = [["severe", "ops"], ["ops"], []]
+    matcher = TextMatcherBuilder().add_patterns(["error", "warning", "info"], tags=tags).build()
+    db_path = os.path.join(sink_dir, "findings.db")
+    matcher.match_bytes_to_sink(b"info error warning", db_path)
+
+    with sqlite3.connect(db_path) as conn:
+        rows = conn.execute("SELECT pattern, tag FROM findings ORDER BY start_offset").fetchall()
+    assert rows == [("info", None), ("error", "severe,ops"), ("warning", "ops")]
+
+
+def test_sqlite_sink_appends_scans(sink_dir):
+    """Test that several scans accumulate in one database with distinct scan ids."""
+    import sqlite3
+
+    matcher = TextMatcher(["x"])
+    db_path = os.path.join(sink_dir, "findings.sqlite")
+
+    first = matcher.match_bytes_to_sink(b"x x", db_path)
+    second = matcher.match_bytes_to_sink(b"x", db_path)
+    assert first.scan_id != second.scan_id
+
+    with sqlite3.connect(db_path) as conn:
+        counts = dict(
+            conn.execute("SELECT scan_id, COUNT(*) FROM findings GROUP BY scan_id").fetchall()
+        )
+        indexes = {
+            row[0]
+            for row in conn.execute(
+                "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'findings'"
+            )
+        }
+
+    assert counts == {first.scan_id: 2, second.scan_id: 1}
+    assert {"findings_scan_id", "findings_file", "findings_pattern"} <= indexes
+
+
+def test_ndjson_provenance(sink_dir):
+    """Test that every NDJSON record carries the provenance of its scan when requested."""
+    matcher = TextMatcher(["error"])
+    path = os.path.join(sink_dir, "input.log")
+    with open(path, "w") as f:
+        f.write("error one\nerror two\n")
+    sink_path = os.path.join(sink_dir, "findings.ndjson")
+
+    summary = matcher.match_file_to_sink(path, sink_path, scan_id="scan-9", provenance=True)
+    with open(sink_path) as f:
+        records = [json.loads(line) for line in f]
+
+    assert len(records) == 2
+    provenance = records[0]["provenance"]
+    assert all(r["provenance"] == provenance for r in records)
+    assert provenance["scan_id"] == "scan-9" == summary.scan_id
+    assert provenance["fingerprint"] == matcher.fingerprint == summary.fingerprint
+    assert provenance["source"] == path
+    assert provenance["host"]
+    assert len(provenance["timestamp"]) == 20 and provenance["timestamp"].endswith("Z")
+
+    # Buffers have no source, and records have no envelope by default
+    matcher.match_bytes_to_sink(b"error", sink_path, provenance=True)
+    with open(sink_path) as f:
+        assert json.loads(f.readline())["provenance"]["source"] is None
+    matcher.match_bytes_to_sink(b"error", sink_path)
+    with open(sink_path) as f:
+        assert "provenance" not in json.loads(f.readline())
+
+
+def test_csv_provenance(sink_dir):
+    """Test that CSV sinks append provenance columns when requested."""
+    matcher = TextMatcher(["fox", "a,b"])
+    sink_path = os.path.join(sink_dir, "findings.csv")
+
+    matcher.match_bytes_to_sink(b"fox a,b", sink_path, scan_id="scan,1", provenance=True)
+    with open(sink_path, newline="") as f:
+        rows = list(csv.DictReader(f))
+
+    assert list(rows[0]) == [
+        "start", "end", "pattern", "scan_id", "host", "fingerprint", "source", "timestamp"
+    ]
+    assert [(r["start"], r["end"], r["pattern"]) for r in rows] == [("0", "3", "fox"), ("4", "7", "a,b")]
+    assert all(r["scan_id"] == "scan,1" and r["fingerprint"] == matcher.fingerprint for r in rows)
+    assert all(r["source"] == "" for r in rows)
+
+
+def test_sqlite_provenance(sink_dir):
+    """Test that SQLite sinks record the provenance of scans in a scans table."""
+    import sqlite3
+
+    matcher = TextMatcher(["x"])
+    db_path = os.path.join(sink_dir, "findings.db")
+
+    matcher.match_bytes_to_sink(b"x x", db_path, scan_id="with", provenance=True)
+    matcher.match_bytes_to_sink(b"x", db_path, scan_id="without")
+
+    with sqlite3.connect(db_path) as conn:
+        rows = conn.execute(
+            "SELECT f.scan_id, s.fingerprint, s.host IS NOT NULL, s.started_at "
+            "FROM findings f LEFT JOIN scans s ON s.scan_id = f.scan_id ORDER BY f.id"
+        ).fetchall()
+
+    assert [r[:3] for r in rows] == [
+        ("with", matcher.fingerprint, 1),
+        ("with", matcher.fingerprint, 1),
+        ("without", None, 0),
+    ]
+    assert rows[0][3].endswith("Z")
+
+
+def test_ndjson_schema_v1(sink_dir):
+    """Test that NDJSON lines follow version 1 of the output schema when selected."""
+    matcher = TextMatcher(["error"], schema_version=1)
+    assert matcher.schema_version == 1
+    assert TextMatcher(["error"]).schema_version == 0
+
+    path = os.path.join(sink_dir, "input.log")
+    with open(path, "w") as f:
+        f.write("error one")
+    sink_path = os.path.join(sink_dir, "findings.ndjson")
+
+    matcher.match_file_to_sink(path, sink_path, scan_id="scan-1")
+    with open(sink_path) as f:
+        records = [json.loads(line) for line in f]
+    assert records == [
+        {"schema_version": 1, "scan_id": "scan-1", "file": path, "start": 0, "end": 5, "pattern": "error"}
+    ]
+
+    matcher.match_bytes_to_sink(b"error", sink_path, scan_id="scan-2", provenance=True)
+    with open(sink_path) as f:
+        record = json.loads(f.readline())
+    assert record["file"] is None
+    assert record["provenance"]["scan_id"] == "scan-2"
+
+
+def test_unknown_schema_version():
+    """Test that unknown schema versions are rejected."""
+    with pytest.raises(ValueError, match="Unknown schema version 2"):
+        TextMatcher(["error"], schema_version=2)
+
+
+class _WebhookServer:
+    """A local HTTP server recording JSON bodies, failing the first `failures` requests."""
+
+    def __init__(self, failures=0, status=500):
+        import http.server
+        import threading
+
+        server = self
+
+        class Handler(http.server.BaseHTTPRequestHandler):
+            def do_POST(self):
+                body = self.rfile.read(int(self.headers["Content-Length"]))
+                if server.failures > 0:
+                    server.failures -= 1
+                    self.send_response(server.status)
+                else:
+                    server.bodies.append(json.loads(body))
+                    server.headers.append(self.headers)
+                    self.send_response(200)
+                self.end_headers()
+
+            def log_message(self, *args):
+                pass
+
+        self.failures = failures
+        self.status = status
+        self.bodies = []
+        self.headers = []
+        self.httpd = http.server.ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+        self.url = f"http://127.0.0.1:{self.httpd.server_address[1]}/ingest"
+        self.thread = threading.Thread(target=self.httpd.serve_forever, daemon=True)
+        self.thread.start()
+
+    def close(self):
+        self.httpd.shutdown()
+        self.httpd.server_close()
+
+
+def test_webhook_sink_batches():
+    """Test that findings are POSTed in batches with headers and metadata."""
+    from voluta import WebhookSink
+
+    server = _WebhookServer()
+    try:
+        matcher = TextMatcher(["key"])
+        data = b"key " * 25
+        sink = WebhookSink(server.url, headers=[("X-Token", "secret")], batch_size=10)
+
+        summary = matcher.match_bytes_to_sink(data, sink, scan_id="scan-7")
+        assert summary.format == "webhook"
+        assert summary.sink == server.url
+        assert summary.matches_written == 25
+
+        assert [len(b["findings"]) for b in server.bodies] == [10, 10, 5]
+        assert all(b["scan_id"] == "scan-7" and b["file"] is None for b in server.bodies)
+        assert server.headers[0]["X-Token"] == "secret"
+
+        findings = [f for b in server.bodies for f in b["findings"]]
+        assert [(f["start"], f["end"], f["pattern"]) for f in findings] == matcher.match_bytes(data)
+    finally:
+        server.close()
+
+
+def test_webhook_provenance():
+    """Test that every batch POSTed carries the provenance of its scan when requested."""
+    from voluta import WebhookSink
+
+    server = _WebhookServer()
+    try:
+        matcher = TextMatcher(["key"])
+        sink = WebhookSink(server.url, batch_size=2)
+        matcher.match_bytes_to_sink(b"key key key", sink, scan_id="scan-3", provenance=True)
+        matcher.match_bytes_to_sink(b"key", sink)
+
+        assert len(server.bodies) == 3
+        provenance = server.bodies[0]["provenance"]
+        assert provenance == server.bodies[1]["provenance"]
+        assert provenance["scan_id"] == "scan-3"
+        assert provenance["fingerprint"] == matcher.fingerprint
+        assert "provenance" not in server.bodies[2]
+    finally:
+        server.close()
+
+
+def test_webhook_schema_v1():
+    """Test that webhook bodies hold version 1 findings when selected."""
+    from voluta import WebhookSink
+
+    server = _WebhookServer()
+    try:
+        matcher = TextMatcher(["key"], schema_version=1)
+        sink = WebhookSink(server.url, batch_size=2)
+        matcher.match_bytes_to_sink(b"key key key", sink, scan_id="scan-4")
+
+        assert [b["schema_version"] for b in server.bodies] == [1, 1]
+        assert [len(b["findings"]) for b in server.bodies] == [2, 1]
+        assert server.bodies[1]["findings"] == [
+            {"schema_version": 1, "scan_id": "scan-4", "file": None, "start": 8, "end": 11, "pattern": "key"}
+        ]
+    finally:
+        server.close()
+
+
+def test_webhook_url_sink(sink_dir):
+    """Test that a plain URL is accepted as a webhook sink."""
+    server = _WebhookServer()
+    try:
+        path = os.path.join(sink_dir, "input.txt")
+        with open(path, "w") as f:
+            f.write("error warning error")
+
+        summary = TextMatcher(["error"]).match_file_to_sink(path, server.url)
+        assert summary.matches_written == 2
+        assert server.bodies[0]["file"] == path
+    finally:
+        server.close()
+
+
+def test_webhook_retries():
+    """Test that server errors are retried and client errors are not."""
+    from voluta import WebhookSink
+
+    server = _WebhookServer(failures=2, status=503)
+    try:
+        sink = WebhookSink(server.url, max_retries=2)
+        summary = TextMatcher(["x"]).match_bytes_to_sink(b"x x", sink)
+        assert summary.matches_written == 2
+        assert len(server.bodies) == 1
+    finally:
+        server.close()
+
+    server = _WebhookServer(failures=1, status=400)
+    try:
+        with pytest.raises(IOError) as excinfo:
+            TextMatcher(["x"]).match_bytes_to_sink(b"x", WebhookSink(server.url))
+        assert "400" in str(excinfo.value)
+    finally:
+        server.close()
+
+    server = _WebhookServer(failures=10, status=500)
+    try:
+        with pytest.raises(IOError) as excinfo:
+            TextMatcher(["x"]).match_bytes_to_sink(b"x", WebhookSink(server.url, max_retries=1))
+        assert "after 2 attempts" in str(excinfo.value)
+    finally:
+        server.close()
+
+
+def test_webhook_invalid_url():
+    """Test that webhook sinks require an http(s) URL."""
+    from voluta import WebhookSink
+
+    with pytest.raises(ValueError):
+        WebhookSink("ftp://example.com")
+
+    with pytest.raises(TypeError):
+        TextMatcher(["x"]).match_bytes_to_sink(b"x", 42)
+
+
+def test_webhook_invalid_timeout():
+    """Test that webhook sinks require a positive timeout."""
+    from voluta import WebhookSink
+
+    for timeout in (0.0, -1.0, float("nan"), float("inf"), 1e300):
+        with pytest.raises(ValueError, match="timeout must be a positive number"):
+            WebhookSink("http://localhost:1/hook", timeout=timeout)
+    assert WebhookSink("http://localhost:1/hook", timeout=0.5).timeout == 0.5
+
+
+def test_kafka_sink_config():
+    """Test the Kafka sink configuration when built with the kafka feature."""
+    import voluta
+
+    if not hasattr(voluta, "KafkaSink"):
+        pytest.skip("voluta was built without the kafka feature")
+
+    sink = voluta.KafkaSink(["localhost:9092"], "findings", serialization="avro")
+    assert sink.topic == "findings"
+    assert sink.serialization == "avro"
+    assert json.loads(voluta.KafkaSink.AVRO_SCHEMA)["name"] == "Finding"
+    assert repr(sink) == (
+        "KafkaSink(brokers=['localhost:9092'], topic='findings', serialization='avro', "
+        "batch_size=1000, ack_timeout=30.0, client_id=None)"
+    )
+
+    with pytest.raises(ValueError):
+        voluta.KafkaSink([], "findings")
+
+    with pytest.raises(ValueError):
+        voluta.KafkaSink(["localhost:9092"], "findings", serialization="xml")
+
+    for ack_timeout in (0.0, -1.0, float("nan"), float("inf"), 1e300):
+        with pytest.raises(ValueError, match="ack_timeout must be a positive number"):
+            voluta.KafkaSink(["localhost:9092"], "findings", ack_timeout=ack_timeout)
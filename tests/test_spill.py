@@ -0,0 +1,56 @@
+import pytest
+from voluta import SpooledMatches, TextMatcher
+
+
+@pytest.fixture
+def input_file(tmp_path):
+    """Fixture that provides a file dense with matches."""
+    path = tmp_path / "input.log"
+    path.write_bytes(b"error warning " * 5000)
+    return str(path)
+
+
+def test_in_memory(input_file):
+    """Test that results under the cap are not spilled."""
+    matcher = TextMatcher(["error", "warning"])
+    matches = matcher.match_file_spooled(input_file)
+    assert isinstance(matches, SpooledMatches)
+    assert len(matches) == 10000
+    assert matches.spilled == 0
+    assert list(matches) == matcher.match_file_memmap(input_file)
+
+
+@pytest.mark.parametrize("max_in_memory", [0, 1, 999, 9999])
+def test_spilled(input_file, tmp_path, max_in_memory):
+    """Test that spilled matches are streamed back in order and the spill file removed."""
+    spill_dir = tmp_path / "spill"
+    spill_dir.mkdir()
+    matcher = TextMatcher(["error", "warning"])
+    matches = matcher.match_file_spooled(input_file, max_in_memory, str(spill_dir))
+    assert len(matches) == 10000
+    assert matches.spilled > 0
+    assert len(list(spill_dir.iterdir())) == 1
+    assert repr(matches) == f"SpooledMatches(len=10000, spilled={matches.spilled})"
+
+    assert list(matches) == matcher.match_file_memmap(input_file)
+    assert list(spill_dir.iterdir()) == []
+    assert list(matches) == []
+
+
+def test_spill_file_removed_on_drop(input_file, tmp_path):
+    """Test that the spill file is removed when the iterator is dropped early."""
+    matches = TextMatcher(["error"]).match_file_spooled(input_file, 10, str(tmp_path))
+    next(iter(matches))
+    assert len(list(tmp_path.glob("voluta-spill-*"))) == 1
+    del matches
+    assert list(tmp_path.glob("voluta-spill-*")) == []
+
+
+def test_spool_errors(input_file, tmp_path):
+    """Test spooling errors."""
+    with pytest.raises(ValueError, match="post-process"):
+        TextMatcher(["error"], postprocess="merge").match_file_spooled(input_file)
+    with pytest.raises(IOError):
+        TextMatcher(["error"]).match_file_spooled("nonexistent_file.txt")
+    with pytest.raises(IOError):
+        TextMatcher(["error"]).match_file_spooled(input_file, 10, str(tmp_path / "missing"))
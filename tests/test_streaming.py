@@ -1,6 +1,7 @@
 import pytest
 import os
 import tempfile
+import time
 from voluta import TextMatcher
 
 
@@ -151,3 +152,22 @@ def test_streaming_invalid_path():
 
     with pytest.raises(IOError):
         matcher.match_file_stream("nonexistent_file.txt")
+
+
+def test_streaming_throttle():
+    """Test that streaming respects the read rate limit without changing results."""
+    matcher = TextMatcher(["test"])
+
+    with tempfile.NamedTemporaryFile(mode="w", delete=False) as f:
+        f.write("test " * 200)
+        temp_path = f.name
+
+    try:
+        started = time.monotonic()
+        throttled = matcher.match_file_stream(temp_path, max_bytes_per_sec=4000)
+        elapsed = time.monotonic() - started
+
+        assert throttled == matcher.match_file_stream(temp_path)
+        assert elapsed >= 0.2
+    finally:
+        os.unlink(temp_path)
@@ -0,0 +1,164 @@
+import asyncio
+
+import pytest
+from voluta import PatternError, TextMatcher, TextMatcherBuilder
+
+PATTERNS = ["4111", "iban", "passport", "name"]
+TAGS = [["pci"], ["pci", "banking"], ["pii"], []]
+DATA = b"name 4111 passport iban"
+
+
+def test_tags_restrict_matches():
+    """Test that a scan only reports the patterns with one of the given tags."""
+    matcher = TextMatcherBuilder().add_patterns(PATTERNS, tags=TAGS).build()
+    assert matcher.tags == TAGS
+
+    assert matcher.match_bytes(DATA, tags=["pci"]) == [(5, 9, "4111"), (19, 23, "iban")]
+    assert matcher.match_bytes(DATA, tags=["banking"]) == [(19, 23, "iban")]
+    assert matcher.match_bytes(DATA, tags=["pii", "banking"]) == [(10, 18, "passport"), (19, 23, "iban")]
+    assert matcher.match_bytes(DATA, tags=[]) == []
+    # Untagged patterns only show up in unfiltered scans
+    assert len(matcher.match_bytes(DATA)) == 4
+
+
+def test_tags_unknown():
+    """Test that unknown tags and misshapen tag lists are rejected."""
+    matcher = TextMatcherBuilder().add_patterns(PATTERNS, tags=TAGS).build()
+    with pytest.raises(PatternError, match="Unknown tag 'phi'"):
+        matcher.match_bytes(DATA, tags=["phi"])
+    with pytest.raises(PatternError, match="Unknown tag 'pci'"):
+        TextMatcher(PATTERNS).match_bytes(DATA, tags=["pci"])
+    with pytest.raises(ValueError, match="one list per pattern"):
+        TextMatcherBuilder().add_patterns(PATTERNS, tags=[["pci"]])
+
+
+def test_tags_many_tags():
+    """Test that tag bitsets span several words."""
+    patterns = [f"p{i:03}" for i in range(150)]
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(patterns, tags=[[f"t{i}", "all"] for i in range(150)])
+        .build()
+    )
+    data = " ".join(patterns).encode()
+    assert matcher.match_bytes(data, tags=["t130"]) == [(130 * 5, 130 * 5 + 4, "p130")]
+    assert [m[2] for m in matcher.match_bytes(data, tags=["t3", "t70", "t149"])] == ["p003", "p070", "p149"]
+    assert len(matcher.match_bytes(data, tags=["all"])) == 150
+
+
+def test_tags_file_methods(tmp_path):
+    """Test that the file scans take the tags as well."""
+    matcher = TextMatcherBuilder().add_patterns(PATTERNS, tags=TAGS).build()
+    path = tmp_path / "data.txt"
+    path.write_bytes((DATA + b"\n") * 50)
+    expected = [m for m in TextMatcher(PATTERNS).match_file_memmap(str(path)) if m[2] in ("passport",)]
+
+    assert matcher.match_file_memmap(str(path), tags=["pii"]) == expected
+    assert sorted(matcher.match_file_memmap_parallel(str(path), chunk_size=64, n_threads=4, tags=["pii"])) == expected
+    assert matcher.match_file_stream(str(path), tags=["pii"]) == expected
+    assert matcher.match_stream(path.read_bytes(), tags=["pii"]) == expected
+    assert matcher.match_file(str(path), tags=["pii"]) == [(line, 10, 18, "passport") for line in range(1, 51)]
+
+    with open(path, "rb") as f:
+        assert matcher.match_readable_parallel(f, buffer_size=64, tags=["pii"]) == expected
+
+
+def test_tags_async():
+    """Test that the async variants take the tags."""
+    matcher = TextMatcherBuilder().add_patterns(PATTERNS, tags=TAGS).build()
+
+    async def scan():
+        return await matcher.amatch_bytes(DATA, tags=["banking"])
+
+    assert asyncio.run(scan()) == [(19, 23, "iban")]
+
+
+def test_tags_with_overrides():
+    """Test that tags combine with the other per-call overrides."""
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(["id", "identity"], tags=[["short"], ["long"]])
+        .build()
+    )
+    data = b"identity id"
+    assert matcher.match_bytes(data, tags=["short"]) == [(0, 2, "id"), (9, 11, "id")]
+    assert matcher.match_bytes(data, tags=["short"], whole_word=True) == [(9, 11, "id")]
+
+
+def test_tags_dropped_with_empty_patterns():
+    """Test that tags follow their patterns when empty patterns are dropped."""
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(["card", "", "name"], tags=[["pci"], ["none"], ["pii"]])
+        .build()
+    )
+    assert matcher.tags == [["pci"], ["pii"]]
+    with pytest.raises(PatternError, match="Unknown tag 'none'"):
+        matcher.match_bytes(b"card", tags=["none"])
+
+
+def test_tags_builder_and_union():
+    """Test that the builder and union keep the tags."""
+    builder = (
+        TextMatcherBuilder()
+        .add_pattern("4111", tags=["pci"])
+        .add_patterns(["passport", "ssn"], tags=["pii"])
+    )
+    matcher = builder.build()
+    assert matcher.tags == [["pci"], ["pii"], ["pii"]]
+
+    combined = matcher.union([TextMatcherBuilder().add_pattern("iban", tags=["banking"]).build()])
+    assert combined.tags == [["pci"], ["pii"], ["pii"], ["banking"]]
+    assert combined.match_bytes(b"ssn iban 4111", tags=["banking", "pci"]) == [(4, 8, "iban"), (9, 13, "4111")]
+
+    with pytest.raises(ValueError, match="'tags' cannot be set as an option"):
+        builder.options(tags=[["pci"]])
+    # The constructor arguments are frozen, tags are only added through the builder
+    with pytest.raises(TypeError):
+        TextMatcher(["iban"], tags=[["banking"]])
+
+
+def test_subset():
+    """Test that a subset only holds the tagged patterns, with their settings."""
+    owners = [{"owner": "payments"}, {}, {"owner": "identity"}, {}]
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(PATTERNS, metadata=owners, tags=TAGS)
+        .whole_word()
+        .build()
+    )
+    pci = matcher.subset(["pci"])
+    assert pci.patterns == ["4111", "iban"]
+    assert pci.tags == [["pci"], ["pci", "banking"]]
+    assert pci.metadata == [{"owner": "payments"}, {}]
+    assert pci.whole_word
+    assert pci.match_bytes(DATA) == matcher.match_bytes(DATA, tags=["pci"])
+    assert pci.fingerprint == TextMatcher(["4111", "iban"], whole_word=True).fingerprint
+
+    # The subset can be filtered further by the tags it kept
+    assert pci.match_bytes(DATA, tags=["banking"]) == [(19, 23, "iban")]
+    assert matcher.subset(["pii", "banking"]).patterns == ["iban", "passport"]
+
+
+def test_subset_settings_follow_patterns():
+    """Test that priorities and fuzzy distances are remapped to the kept patterns."""
+    matcher = (
+        TextMatcherBuilder()
+        .add_patterns(["credit card", "card number", "colour"], tags=[["a"], ["a"], ["b"]])
+        .options(priorities=[1, 5, 0], fuzzy=[0, 0, 1])
+        .build()
+    )
+    subset = matcher.subset(["a"])
+    assert subset.postprocess == "priority"
+    assert subset.match_bytes(b"credit card number") == [(7, 18, "card number")]
+    assert matcher.subset(["b"]).fuzzy == [1]
+    assert matcher.subset(["b"]).match_bytes(b"color") == [(0, 5, "colour")]
+
+
+def test_subset_errors():
+    """Test that unknown tags and empty subsets are rejected."""
+    matcher = TextMatcherBuilder().add_patterns(PATTERNS, tags=TAGS).build()
+    with pytest.raises(PatternError, match="Unknown tag 'phi'"):
+        matcher.subset(["phi"])
+    with pytest.raises(PatternError, match="No pattern has any of the tags"):
+        matcher.subset([])
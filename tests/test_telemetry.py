@@ -0,0 +1,55 @@
+import http.server
+import threading
+
+import pytest
+import voluta
+from voluta import TextMatcher
+
+
+@pytest.fixture
+def collector():
+    """Fixture that provides a local OTLP/HTTP endpoint recording the request paths."""
+    paths = []
+
+    class Handler(http.server.BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers["Content-Length"]))
+            paths.append(self.path)
+            self.send_response(200)
+            self.send_header("Content-Type", "application/x-protobuf")
+            self.send_header("Content-Length", "0")
+            self.end_headers()
+
+        def log_message(self, *args):
+            pass
+
+    httpd = http.server.ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{httpd.server_address[1]}", paths
+    httpd.shutdown()
+    httpd.server_close()
+
+
+def test_telemetry_export(collector, tmp_path):
+    """Test that scans export spans and metrics when built with the otel feature."""
+    if not hasattr(voluta, "enable_telemetry"):
+        pytest.skip("voluta was built without the otel feature")
+
+    endpoint, paths = collector
+    path = tmp_path / "input.txt"
+    path.write_bytes(b"error warning error\n" * 100)
+
+    voluta.enable_telemetry(endpoint, service_name="voluta-tests")
+    try:
+        matcher = TextMatcher(["error", "warning"])
+        assert len(matcher.match_bytes(b"error warning")) == 2
+        assert len(matcher.match_file_memmap_parallel(str(path), 256)) == 300
+    finally:
+        voluta.shutdown_telemetry()
+
+    assert "/v1/traces" in paths
+    assert "/v1/metrics" in paths
+
+    # Scans keep working once telemetry is shut down
+    assert len(TextMatcher(["error"]).match_bytes(b"error")) == 1
@@ -0,0 +1,35 @@
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+from voluta import TextMatcher
+
+
+@pytest.fixture
+def shared_matcher():
+    """Fixture that provides one matcher shared by all threads."""
+    return TextMatcher(["error", "warning", "timeout"], whole_word=True)
+
+
+def test_shared_matcher_bytes(shared_matcher):
+    """Test that threads scanning bytes with one matcher get consistent results."""
+    data = b"error warning timeout errors " * 1000
+    expected = shared_matcher.match_bytes(data)
+
+    with ThreadPoolExecutor(max_workers=8) as pool:
+        results = list(pool.map(shared_matcher.match_bytes, [data] * 32))
+
+    assert all(result == expected for result in results)
+
+
+def test_shared_matcher_files(shared_matcher, tmp_path):
+    """Test that threads scanning different files with one matcher get consistent results."""
+    paths = []
+    for i in range(8):
+        path = tmp_path / f"log_{i}.txt"
+        path.write_text("error warning\n" * (i + 1))
+        paths.append(str(path))
+
+    with ThreadPoolExecutor(max_workers=8) as pool:
+        results = list(pool.map(shared_matcher.match_file_memmap, paths))
+
+    assert [len(result) for result in results] == [2 * (i + 1) for i in range(8)]
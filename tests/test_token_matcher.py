@@ -0,0 +1,129 @@
+import pytest
+from voluta import TokenMatcher
+
+TERMS = ["Jane Doe", "Jane", "ACME Corp", "Zoë", "doe"]
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher for a few names."""
+    return TokenMatcher(TERMS)
+
+
+def test_whole_tokens(matcher):
+    """Test that terms match whole tokens only, and every term of a token run is reported."""
+    data = b"Janet met jane  doe, then JANE-DOE from acme corp."
+    assert matcher.match_bytes(data) == [
+        (10, 14, "Jane"),
+        (10, 19, "Jane Doe"),
+        (16, 19, "doe"),
+        (26, 30, "Jane"),
+        (26, 34, "Jane Doe"),
+        (31, 34, "doe"),
+        (40, 49, "ACME Corp"),
+    ]
+
+
+def test_non_overlapping():
+    """Test that without overlaps the longest term wins and the scan resumes after it."""
+    matcher = TokenMatcher(TERMS, overlapping=False)
+    assert matcher.match_bytes(b"jane doe doe jane") == [
+        (0, 8, "Jane Doe"),
+        (9, 12, "doe"),
+        (13, 17, "Jane"),
+    ]
+
+
+def test_case_sensitive():
+    """Test that case-sensitive matchers only match the exact case."""
+    matcher = TokenMatcher(TERMS, case_insensitive=False)
+    assert matcher.match_bytes(b"jane Jane Doe") == [(5, 9, "Jane"), (5, 13, "Jane Doe")]
+
+
+def test_non_ascii_tokens(matcher):
+    """Test that non-ASCII characters are part of tokens."""
+    assert matcher.match_bytes("Zoë and Zoëlla".encode()) == [(0, 4, "Zoë")]
+
+
+def test_line_breaks_split_terms(matcher):
+    """Test that the tokens of a term cannot span a line break."""
+    assert matcher.match_bytes(b"Jane\nDoe") == [(0, 4, "Jane"), (5, 8, "doe")]
+
+
+def test_duplicate_terms():
+    """Test that terms equal once normalized report the first of them."""
+    matcher = TokenMatcher(["Jane  Doe", "jane-doe", "", "--"])
+    assert len(matcher) == 2
+    assert matcher.match_bytes(b"JANE DOE") == [(0, 8, "Jane  Doe")]
+    with pytest.raises(ValueError, match="Term set cannot be empty"):
+        TokenMatcher(["", " - "])
+
+
+def test_file_matches_bytes(matcher, tmp_path):
+    """Test that parallel file scans return the matches of match_bytes."""
+    data = b"Jane Doe works at ACME Corp\nzoe doe\n" * 200000
+    path = tmp_path / "names.txt"
+    path.write_bytes(data)
+    assert matcher.match_file_memmap(str(path)) == matcher.match_bytes(data)
+    with pytest.raises(IOError):
+        matcher.match_file_memmap(str(tmp_path / "missing.txt"))
+
+
+def test_options_and_size(matcher):
+    """Test the options, the repr and that the transducer stays small."""
+    assert matcher.pattern_count == 5
+    assert matcher.overlapping is True
+    assert matcher.case_insensitive is True
+    assert matcher.phonetic is None
+    assert repr(matcher) == "TokenMatcher(terms=5, overlapping=True, case_insensitive=True)"
+
+    terms = [f"customer{i} name{i}" for i in range(100000)]
+    assert TokenMatcher(terms).memory_usage() < 4 * sum(len(term) for term in terms)
+
+
+@pytest.mark.parametrize(
+    "term, spellings",
+    [
+        ("Robert", ["Rupert", "ROBERT"]),
+        ("Ashcraft", ["Ashcroft"]),
+        ("Tymczak", ["Tymchak"]),
+        ("Pfister", ["Pfyster"]),
+        ("Honeyman", ["Honeymann"]),
+    ],
+)
+def test_soundex(term, spellings):
+    """Test that Soundex matches spellings sharing the code of a term."""
+    matcher = TokenMatcher([term], phonetic="soundex")
+    for spelling in spellings:
+        data = f"to {spelling}.".encode()
+        assert matcher.match_bytes(data) == [(3, 3 + len(spelling), term)]
+    assert matcher.match_bytes(b"Rodriguez") == []
+
+
+def test_metaphone():
+    """Test that Metaphone matches names pronounced alike, reporting every term they sound like."""
+    matcher = TokenMatcher(["Smith", "Smyth", "Catherine", "Knight", "Philip"], phonetic="metaphone")
+    assert matcher.match_bytes(b"Smythe met Kathryn, Nite and Filip") == [
+        (0, 6, "Smith"),
+        (0, 6, "Smyth"),
+        (11, 18, "Catherine"),
+        (20, 24, "Knight"),
+        (29, 34, "Philip"),
+    ]
+    assert matcher.phonetic == "metaphone"
+    assert repr(matcher) == (
+        "TokenMatcher(terms=5, overlapping=True, case_insensitive=True, phonetic='metaphone')"
+    )
+
+
+def test_phonetic_phrases():
+    """Test that phonetic terms of several tokens keep the longest term without overlaps."""
+    matcher = TokenMatcher(["Jon Smith", "John", "Agent 007"], phonetic="metaphone", overlapping=False)
+    assert matcher.match_bytes(b"john smyth and jon, agent 007") == [
+        (0, 10, "Jon Smith"),
+        (15, 18, "John"),
+        (20, 29, "Agent 007"),
+    ]
+    assert matcher.match_bytes(b"agent 008") == []
+    with pytest.raises(ValueError, match="Unknown phonetic algorithm"):
+        TokenMatcher(["John"], phonetic="nysiis")
@@ -0,0 +1,67 @@
+import pytest
+from voluta import TextMatcher
+
+MB = 1024 * 1024
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a matcher for a couple of log levels."""
+    return TextMatcher(["error", "warning"])
+
+
+def test_small_file_single_chunk(matcher, tmp_path):
+    """Test that small files are scanned as a single chunk on one thread."""
+    path = tmp_path / "small.log"
+    path.write_bytes(b"error\n" * 1000)
+    chunk_size, n_threads = matcher.tune(str(path), 8)
+    assert chunk_size >= path.stat().st_size
+    assert n_threads == 1
+
+
+def test_large_file_split_across_workers(matcher, tmp_path):
+    """Test that larger files are split into several chunks per worker."""
+    path = tmp_path / "large.log"
+    path.write_bytes(b"x" * (32 * MB))
+    chunk_size, n_threads = matcher.tune(str(path), 4)
+    assert chunk_size == 2 * MB
+    assert n_threads == 4
+
+    chunk_size, n_threads = matcher.tune(str(path), 1)
+    assert chunk_size == 32 * MB
+    assert n_threads == 1
+
+
+def test_chunks_cover_long_patterns(tmp_path):
+    """Test that chunks stay large relative to the longest pattern."""
+    path = tmp_path / "large.log"
+    path.write_bytes(b"x" * (8 * MB))
+    chunk_size, _ = TextMatcher(["y" * 16384]).tune(str(path), 8)
+    assert chunk_size >= 256 * 16384
+
+
+def test_auto_matches_fixed(matcher, tmp_path):
+    """Test that auto-tuned scans return the same matches as fixed chunk sizes."""
+    path = tmp_path / "input.log"
+    path.write_bytes((b"x" * 1021 + b"error warning ") * 3000)
+    expected = matcher.match_file_memmap(str(path), 4096)
+    assert matcher.match_file_memmap(str(path)) == expected
+    assert matcher.match_file_memmap(str(path), "auto") == expected
+    assert sorted(matcher.match_file_memmap_parallel(str(path), "auto", "auto")) == sorted(
+        expected
+    )
+    assert matcher.match_file_collection(str(path)).to_list() == sorted(expected)
+
+
+def test_invalid_settings(matcher, tmp_path):
+    """Test errors for chunk sizes and thread counts that are neither integers nor auto."""
+    path = tmp_path / "input.log"
+    path.write_bytes(b"error")
+    with pytest.raises(ValueError, match="chunk_size"):
+        matcher.match_file_memmap(str(path), "fast")
+    with pytest.raises(ValueError, match="chunk_size"):
+        matcher.match_file_memmap(str(path), 0)
+    with pytest.raises(TypeError, match="n_threads"):
+        matcher.match_file_memmap_parallel(str(path), None, 1.5)
+    with pytest.raises(IOError):
+        matcher.tune("nonexistent_file.txt")
@@ -0,0 +1,61 @@
+import pytest
+from voluta import PatternError, TextMatcher, TextMatcherBuilder
+
+
+def test_union_patterns_and_metadata():
+    """Test that patterns and metadata of every matcher are appended in order."""
+    payments = TextMatcherBuilder().add_patterns(["card", "iban"], metadata={"team": "payments"}).build()
+    identity = TextMatcherBuilder().add_pattern("passport", metadata={"team": "identity"}).build()
+    misc = TextMatcher(["name"])
+
+    combined = payments.union([identity, misc])
+    assert combined.patterns == ["card", "iban", "passport", "name"]
+    assert combined.metadata == [{"team": "payments"}, {"team": "payments"}, {"team": "identity"}, {}]
+
+    data = b"name card passport iban"
+    assert combined.match_bytes(data) == TextMatcher(combined.patterns).match_bytes(data)
+    assert combined.fingerprint == TextMatcher(["card", "iban", "passport", "name"]).fingerprint
+
+    # The sources are left untouched
+    assert payments.patterns == ["card", "iban"]
+    assert payments.union([]).patterns == ["card", "iban"]
+
+
+def test_union_remaps_pattern_settings():
+    """Test that per-pattern settings follow their patterns into the union."""
+    first = TextMatcher(["color", "size"], fuzzy=[1, 0], min_count=[1, 2])
+    second = TextMatcher(["weight"], min_count=3)
+    combined = first.union([second])
+    assert combined.fuzzy == [1, 0, 0]
+
+    assert combined.match_bytes(b"colour size") == [(0, 6, "color")]
+    assert combined.match_bytes(b"colour size size") == [(0, 6, "color"), (7, 11, "size"), (12, 16, "size")]
+    assert combined.match_bytes(b"weight weight") == []
+    assert len(combined.match_bytes(b"weight weight weight")) == 3
+
+
+def test_union_priorities():
+    """Test that priorities are kept when overlaps are resolved by priority."""
+    first = TextMatcher(["credit card"], priorities=[1])
+    second = TextMatcher(["card number"], priorities=[5])
+    combined = first.union([second])
+    assert combined.postprocess == "priority"
+    assert combined.match_bytes(b"credit card number") == [(7, 18, "card number")]
+
+
+def test_union_options():
+    """Test that the union keeps the options of the first matcher and refuses different ones."""
+    first = TextMatcher(["id"], whole_word=True, engine="memmem")
+    second = TextMatcher(["key"], whole_word=True, engine="aho")
+    combined = first.union([second])
+    assert combined.whole_word
+    assert combined.match_bytes(b"idea id key keys") == [(5, 7, "id"), (8, 11, "key")]
+
+    with pytest.raises(PatternError, match="different options: whole_word"):
+        first.union([TextMatcher(["key"])])
+    with pytest.raises(PatternError, match="different options: case_insensitive"):
+        second.union([TextMatcher(["key"], whole_word=True, case_insensitive=False)])
+    with pytest.raises(PatternError, match="different options: hash_key"):
+        TextMatcher(["a"], hash_key=b"one").union([TextMatcher(["b"], hash_key=b"two")])
+    with pytest.raises(TypeError):
+        first.union(["key"])
@@ -0,0 +1,182 @@
+import gzip
+import logging
+import zlib
+
+import pytest
+from voluta import DecodeError, TextMatcher, TextMatcherBuilder
+
+
+def record(kind, uri, block):
+    """A WARC record of the given type and block."""
+    headers = (
+        f"WARC/1.1\r\nWARC-Type: {kind}\r\nWARC-Target-URI: {uri}\r\n"
+        f"Content-Length: {len(block)}\r\n\r\n"
+    )
+    return headers.encode() + block + b"\r\n\r\n"
+
+
+def response(body, headers=b""):
+    """An HTTP response block with the given body and extra headers."""
+    return b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n" + headers + b"\r\n" + body
+
+
+def chunked(data, size=7):
+    """Data in the chunked transfer encoding."""
+    chunks = [data[i : i + size] for i in range(0, len(data), size)]
+    return b"".join(b"%x;ext=1\r\n%s\r\n" % (len(c), c) for c in chunks) + b"0\r\n\r\n"
+
+
+class Records(logging.Handler):
+    """Handler keeping the records it receives."""
+
+    def __init__(self):
+        super().__init__(logging.DEBUG)
+        self.records = []
+
+    def emit(self, record):
+        self.records.append(record)
+
+
+@pytest.fixture
+def records():
+    """Fixture that provides the records of a small crawl."""
+    deflated = zlib.compress(b"leaked api_key here")
+    return [
+        record("warcinfo", "", b"software: test api_key"),
+        record("request", "http://a.example/", b"GET / HTTP/1.1\r\nX-Key: api_key\r\n\r\n"),
+        record("response", "http://a.example/", response(b"<p>api_key=1</p>")),
+        record(
+            "response",
+            "http://b.example/page",
+            response(
+                chunked(gzip.compress(b"no secret, then an api_key")),
+                b"Transfer-Encoding: chunked\r\nContent-Encoding: gzip\r\n",
+            ),
+        ),
+        record("response", "<http://c.example/>", response(deflated, b"Content-Encoding: deflate\r\n")),
+        record("response", "http://d.example/", response(b"nothing here")),
+    ]
+
+
+@pytest.fixture
+def matcher():
+    """Fixture that provides a simple matcher."""
+    return TextMatcher(["api_key", "secret"])
+
+
+EXPECTED = [
+    ("http://a.example/", 3, 10, "api_key"),
+    ("http://b.example/page", 3, 9, "secret"),
+    ("http://b.example/page", 19, 26, "api_key"),
+    ("http://c.example/", 7, 14, "api_key"),
+]
+
+
+def test_plain_warc(matcher, records, tmp_path):
+    """Test that only response payloads are scanned, decoded, with their target URI."""
+    path = tmp_path / "crawl.warc"
+    path.write_bytes(b"".join(records))
+    assert matcher.match_warc(str(path)) == EXPECTED
+
+
+def test_gzipped_warc(matcher, records, tmp_path):
+    """Test that WARC files gzipped record by record are decompressed."""
+    path = tmp_path / "crawl.warc.gz"
+    path.write_bytes(b"".join(gzip.compress(r) for r in records))
+    assert matcher.match_warc(str(path)) == EXPECTED
+
+
+def test_many_records(matcher, tmp_path):
+    """Test that records scanned in parallel keep their order."""
+    path = tmp_path / "many.warc"
+    path.write_bytes(
+        b"".join(
+            record("response", f"http://x.example/{i}", response(b"%d api_key" % i))
+            for i in range(500)
+        )
+    )
+    matches = matcher.match_warc(str(path))
+    assert len(matches) == 500
+    assert matches[123] == ("http://x.example/123", 4, 11, "api_key")
+
+
+def test_undecodable_payload(matcher, tmp_path):
+    """Test that payloads that cannot be decoded are scanned as they are."""
+    path = tmp_path / "bad.warc"
+    path.write_bytes(
+        record("response", "http://x/", response(b"api_key", b"Content-Encoding: gzip\r\n"))
+    )
+    assert matcher.match_warc(str(path)) == [("http://x/", 0, 7, "api_key")]
+
+
+def test_invalid_warc(matcher, tmp_path):
+    """Test that files that are not WARC and truncated records raise DecodeError or IOError."""
+    path = tmp_path / "not.warc"
+    path.write_bytes(b"hello api_key\n")
+    with pytest.raises(DecodeError, match="Not a WARC file"):
+        matcher.match_warc(str(path))
+    path.write_bytes(record("response", "http://x/", response(b"api_key"))[:-20])
+    with pytest.raises(IOError, match="Truncated WARC record"):
+        matcher.match_warc(str(path))
+    with pytest.raises(IOError):
+        matcher.match_warc(str(tmp_path / "missing.warc"))
+
+
+def test_max_record_size(matcher, records, tmp_path):
+    """Test that records larger than max_record_size are skipped, and logged as limits hit."""
+    path = tmp_path / "crawl.warc"
+    large = record("response", "http://large.example/", response(b"api_key " * 100))
+    path.write_bytes(b"".join(records[:3]) + large + b"".join(records[3:]))
+    assert matcher.match_warc(str(path), max_record_size=200) == [EXPECTED[0], EXPECTED[3]]
+    assert matcher.match_warc(str(path), max_record_size=1 << 20)[:2] == [
+        EXPECTED[0],
+        ("http://large.example/", 0, 7, "api_key"),
+    ]
+
+    logger = logging.getLogger("voluta.test_warc")
+    logger.propagate = False
+    logger.setLevel(logging.DEBUG)
+    handler = Records()
+    logger.handlers = [handler]
+    TextMatcher(["api_key"], events=logger).match_warc(str(path), max_record_size=200)
+    assert [r.error for r in handler.records if r.event == "limit_hit"] == [
+        "record of http://large.example/ larger than max_record_size",
+        "record of http://b.example/page larger than max_record_size",
+    ]
+
+    path.write_bytes(large[:-100])
+    with pytest.raises(IOError, match="Truncated WARC record"):
+        matcher.match_warc(str(path), max_record_size=200)
+
+
+def test_max_decoded_size(tmp_path):
+    """Test that payloads decoding and gzipped records past max_decoded_size are cut short."""
+    path = tmp_path / "crawl.warc.gz"
+    bomb = record(
+        "response",
+        "http://bomb.example/",
+        response(gzip.compress(b"api_key " + b"x" * 100_000 + b" api_key"), b"Content-Encoding: gzip\r\n"),
+    )
+    large = record("response", "http://large.example/", response(b"api_key " * 20_000))
+    small = record("response", "http://small.example/", response(b"api_key"))
+    path.write_bytes(b"".join(gzip.compress(r) for r in (bomb, large, small)))
+    assert len(TextMatcher(["api_key"]).match_warc(str(path))) == 20_003
+
+    logger = logging.getLogger("voluta.test_warc")
+    logger.propagate = False
+    logger.setLevel(logging.DEBUG)
+    handler = Records()
+    logger.handlers = [handler]
+    matcher = (
+        TextMatcherBuilder(["api_key"])
+        .options(max_decoded_size=64 * 1024, events=logger)
+        .build()
+    )
+    assert matcher.match_warc(str(path)) == [
+        ("http://bomb.example/", 0, 7, "api_key"),
+        ("http://small.example/", 0, 7, "api_key"),
+    ]
+    assert sorted(r.error for r in handler.records if r.event == "limit_hit") == [
+        "payload of http://bomb.example/ truncated to max_decoded_size",
+        "record of http://large.example/ larger than max_decoded_size",
+    ]
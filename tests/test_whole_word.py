@@ -1,3 +1,4 @@
+import pytest
 from voluta import TextMatcher
 
 
@@ -386,3 +387,45 @@ def test_case_sensitivity_edge_cases():
 
     # Case insensitive should match all variations
     assert len(insensitive_words) >= 6  # At least 6 matches for all variations
+
+
+def test_custom_word_chars_hostnames():
+    """Test that dots and dashes count as word characters when listed in word_chars."""
+    data = b"host example.com, sub.example.com and my-example.com"
+    matcher = TextMatcher(["example.com"], whole_word=True)
+    assert len(matcher.match_bytes(data)) == 3
+
+    matcher = TextMatcher(["example.com"], whole_word=True, word_chars="A-Za-z0-9_.-")
+    assert matcher.word_chars == ".0-9A-Z_a-z-"
+    assert matcher.match_bytes(data) == [(5, 16, "example.com")]
+
+
+def test_custom_word_chars_digits_excluded():
+    """Test that digits can be left out of the word characters."""
+    data = b"id42 ids id_x"
+    assert TextMatcher(["id"], whole_word=True).match_bytes(data) == []
+    matcher = TextMatcher(["id"], whole_word=True, word_chars="A-Za-z_")
+    assert matcher.match_bytes(data) == [(0, 2, "id")]
+
+
+def test_custom_word_chars_parallel(tmp_path):
+    """Test that chunked file scans honor custom word characters."""
+    data = b"a.example.com example.com example.com-x\n" * 5000
+    path = tmp_path / "input.txt"
+    path.write_bytes(data)
+    matcher = TextMatcher(["example.com"], whole_word=True, word_chars="a-z.-")
+    expected = matcher.match_bytes(data)
+    assert len(expected) == 5000
+    found = matcher.match_file_memmap_parallel(str(path), chunk_size=4096, n_threads=4)
+    assert sorted(found) == expected
+
+
+def test_word_chars_validated():
+    """Test the default word characters and the word_chars validation."""
+    assert TextMatcher(["id"]).word_chars == "0-9A-Z_a-z"
+    assert TextMatcher(["id"], word_chars="-a-c").word_chars == "a-c-"
+    assert TextMatcher(["id"], word_chars="").word_chars == ""
+    with pytest.raises(ValueError, match="Invalid range 'z-a'"):
+        TextMatcher(["id"], word_chars="z-a")
+    with pytest.raises(ValueError, match="ASCII"):
+        TextMatcher(["id"], word_chars="a-zé")
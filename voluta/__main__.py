@@ -0,0 +1,112 @@
+"""Command line of voluta, compiling matcher bundles in CI for agents to load at runtime.
+
+    python -m voluta compile secrets.txt names.txt -o matcher.vb --golden golden.yaml
+    python -m voluta inspect matcher.vb
+"""
+
+import argparse
+import json
+import sys
+
+from .voluta import VolutaError, bundle_info, compile_bundle
+
+
+def _validator(spec):
+    name, sep, arg = spec.partition("=")
+    return (name, int(arg)) if sep else name
+
+
+def _option(spec):
+    key, sep, value = spec.partition("=")
+    if not sep:
+        raise argparse.ArgumentTypeError(f"expected KEY=VALUE, got {spec!r}")
+    try:
+        return key, json.loads(value)
+    except json.JSONDecodeError:
+        return key, value
+
+
+def _parser():
+    parser = argparse.ArgumentParser(prog="voluta")
+    commands = parser.add_subparsers(dest="command", required=True)
+
+    compile_parser = commands.add_parser(
+        "compile", help="compile pattern files into a matcher bundle"
+    )
+    compile_parser.add_argument("pattern_files", nargs="+", metavar="PATTERN_FILE")
+    compile_parser.add_argument("-o", "--output", required=True, help="path of the bundle")
+    compile_parser.add_argument(
+        "--golden", help="YAML or JSON golden tests the matcher must pass"
+    )
+    compile_parser.add_argument(
+        "--validate",
+        action="append",
+        default=[],
+        type=_validator,
+        metavar="NAME[=ARG]",
+        help="built-in validator, such as unique or min_length=4 (repeatable)",
+    )
+    # Tri-state, so --no-case-insensitive and --no-overlapping turn off options on by default
+    for flag in ("--case-insensitive", "--whole-word", "--overlapping"):
+        compile_parser.add_argument(flag, action=argparse.BooleanOptionalAction)
+    compile_parser.add_argument("--engine")
+    compile_parser.add_argument("--max-memory-bytes", type=int)
+    compile_parser.add_argument("--on-memory-limit", choices=["error", "downgrade"])
+    compile_parser.add_argument(
+        "--signing-key",
+        type=argparse.FileType("rb"),
+        metavar="FILE",
+        help="file holding the 32-byte Ed25519 seed signing the bundle",
+    )
+    compile_parser.add_argument(
+        "--option",
+        action="append",
+        default=[],
+        type=_option,
+        metavar="KEY=VALUE",
+        help="any other TextMatcherBuilder option, the value parsed as JSON (repeatable)",
+    )
+
+    inspect_parser = commands.add_parser("inspect", help="print what a bundle holds")
+    inspect_parser.add_argument("bundle")
+    return parser
+
+
+def main(argv=None):
+    args = _parser().parse_args(argv)
+    try:
+        if args.command == "compile":
+            options = dict(args.option)
+            for key in (
+                "case_insensitive",
+                "whole_word",
+                "overlapping",
+                "engine",
+                "max_memory_bytes",
+                "on_memory_limit",
+            ):
+                if getattr(args, key) is not None:
+                    options[key] = getattr(args, key)
+            signing_key = None
+            if args.signing_key is not None:
+                with args.signing_key:
+                    signing_key = args.signing_key.read()
+            info = compile_bundle(
+                args.pattern_files,
+                args.output,
+                golden=args.golden,
+                validators=args.validate,
+                signing_key=signing_key,
+                **options,
+            )
+        else:
+            info = bundle_info(args.bundle)
+    except (VolutaError, OSError, ValueError, TypeError) as e:
+        print(f"voluta: {e}", file=sys.stderr)
+        return 1
+    print(info.to_json())
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
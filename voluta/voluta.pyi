@@ -1,141 +1,3201 @@
-from typing import List, Optional, Tuple
+import logging
+from typing import Any, Awaitable, Callable, Dict, List, Optional, Set, Tuple, Union
+
+class VolutaError(Exception):
+    """Base class of the errors raised by voluta."""
+
+class PatternError(VolutaError, ValueError):
+    """Invalid patterns or matcher options."""
+
+class LimitExceeded(VolutaError, ValueError):
+    """Patterns or options exceed a limit of voluta or of the engine, e.g. fuzzy distances."""
+
+class ScanIOError(VolutaError, OSError):
+    """A file or readable cannot be read."""
+
+class DecodeError(ScanIOError):
+    """Scanned bytes cannot be decoded, e.g. invalid UTF-8 with invalid_utf8="error"."""
+
+class MatchCollection:
+    """
+    A collection of matches kept in Rust until Python objects are requested.
+    Supports len(), indexing and iteration, yielding (start_index, end_index, matched_pattern) tuples.
+    """
+
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int) -> Tuple[int, int, str]: ...
+    def __repr__(self) -> str: ...
+    def _repr_html_(self) -> str:
+        """Table of the first 20 matches, displayed by Jupyter."""
+        ...
+
+    def filter(
+        self,
+        pattern: Optional[Union[str, List[str]]] = None,
+        range: Optional[Tuple[int, int]] = None,
+        tag: Optional[Union[str, List[str]]] = None,
+    ) -> "MatchCollection":
+        """
+        Keep only the matches of the given pattern(s), of the patterns with one of the given
+        tag(s), lying entirely inside range.
+
+        Args:
+            pattern: A pattern or list of patterns to keep (default: all patterns)
+            range: (start, end) byte offsets, end exclusive (default: everything)
+            tag: A tag or list of tags, keeping the matches of the patterns with any of them
+                (default: all patterns)
+
+        Returns:
+            A new MatchCollection
+
+        Raises:
+            PatternError: If a tag is not a tag of the matcher's patterns
+        """
+        ...
+
+    def group_by_pattern(self) -> Dict[str, "MatchCollection"]:
+        """Split the collection into one collection per matched pattern."""
+        ...
+
+    def to_html(self, data: bytes, context: Optional[int] = None) -> str:
+        """
+        Render the data the matches were found in as HTML, with matched regions highlighted.
+
+        Overlapping matches are merged into one <mark> element titled with their patterns.
+
+        Args:
+            data: The bytes the matches were found in
+            context: Only keep this many bytes around each match (default: the whole data)
+
+        Returns:
+            A <pre class="voluta-highlight"> element, e.g. for IPython.display.HTML
+        """
+        ...
+
+    def to_list(self) -> List[Tuple[int, int, str]]:
+        """Returns a list of (start_index, end_index, matched_pattern) tuples."""
+        ...
+
+    def to_dicts(self) -> List[Dict[str, Any]]:
+        """Returns a list of {"start", "end", "pattern"} dicts."""
+        ...
+
+    def to_json(self) -> str:
+        """
+        Serialize the matches as a JSON array of {"start", "end", "pattern"} objects, or of
+        version 1 findings for collections of matchers built with schema_version=1.
+        """
+        ...
+
+    def to_arrow(self) -> Any:
+        """
+        Build a pyarrow Table with "start", "end" and "pattern" columns.
+
+        The columns are built in Rust without creating a Python object per match: offsets are
+        uint64 and patterns are dictionary-encoded over the names of the matched patterns.
+
+        Raises:
+            ImportError: If pyarrow is not installed
+        """
+        ...
+
+    def to_pandas(self) -> Any:
+        """
+        Build a pandas DataFrame with "start", "end" and "pattern" columns.
+
+        The columns are built in Rust without creating a Python object per match: offsets are
+        uint64 and patterns are categorical over the names of the matched patterns.
+
+        Raises:
+            ImportError: If pandas is not installed
+        """
+        ...
+
+class SpooledMatches:
+    """
+    Iterator over the matches of a spooled scan, see TextMatcher.match_file_spooled.
+    Yields (start_index, end_index, matched_pattern) tuples in offset order; the temporary
+    file holding spilled matches is removed once the iterator is exhausted or dropped.
+    """
+
+    spilled: int
+    """Number of matches that did not fit in memory and were written to the temporary file."""
+
+    def __len__(self) -> int:
+        """Number of matches of the scan."""
+        ...
+
+    def __iter__(self) -> "SpooledMatches": ...
+    def __next__(self) -> Tuple[int, int, str]: ...
+    def __repr__(self) -> str: ...
+
+class SinkSummary:
+    """Summary of a scan whose matches were written to a sink."""
+
+    sink: str
+    """Path or URL the matches were written to."""
+
+    format: str
+    """Format of the sink ("ndjson", "csv", "sqlite" or "webhook")."""
+
+    scan_id: str
+    """Identifier of the scan, recorded with each finding in SQLite sinks."""
+
+    fingerprint: str
+    """Fingerprint of the matcher that found the matches, see TextMatcher.fingerprint."""
+
+    matches_written: int
+    """Number of matches written to the sink."""
+
+    bytes_scanned: int
+    """Number of bytes scanned."""
+
+class ScanStats:
+    """Statistics of one scan, returned alongside the matches when requested with stats=True."""
+
+    bytes_scanned: int
+    """Number of bytes scanned."""
+
+    elapsed: float
+    """Wall time of the scan in seconds."""
+
+    throughput: float
+    """Bytes scanned per second of wall time."""
+
+    chunks: int
+    """Number of chunks or buffers the input was scanned in, 1 when scanned at once."""
+
+    dedup_collisions: int
+    """Deprecated, always 0, and reading it warns with DeprecationWarning. It counted matches
+    found twice where chunks overlap, but every chunked scan now assigns each match to the
+    chunk it starts in and never finds one twice. It will be removed in a future release."""
+
+    matches_per_pattern: Dict[str, int]
+    """Number of matches of each pattern that matched."""
+
+    total_matches: int
+    """Number of matches returned."""
+
+class BenchResult:
+    """Timings of one configuration of a benchmark, see benchmark."""
+
+    engine: str
+    """Engine as configured."""
+
+    resolved_engine: str
+    """Engine that ran, the one picked when configured as "auto"."""
+
+    chunk_size: Optional[int]
+    """Bytes per chunk, the tuned size when only n_threads was configured, None for single-pass scans."""
+
+    n_threads: Optional[int]
+    """Threads of the chunked scan, None for the global thread pool."""
+
+    build_time: float
+    """Time to build the matcher in seconds."""
+
+    times: List[float]
+    """Wall time of every run in seconds."""
+
+    best: float
+    """Fastest run in seconds."""
+
+    mean: float
+    """Mean run time in seconds."""
+
+    throughput: float
+    """Bytes scanned per second by the fastest run."""
+
+    matches: int
+    """Number of matches found."""
+
+class ScanReport:
+    """Report of a scan run over a set of files, see TextMatcher.scan_report."""
+
+    title: str
+    scan_id: str
+
+    fingerprint: str
+    """Fingerprint of the matcher that produced the report, see TextMatcher.fingerprint."""
+
+    started_at: str
+    """When the scan started, as an RFC 3339 UTC timestamp."""
+
+    duration: float
+    """Duration of the scan in seconds."""
+
+    files_scanned: int
+    bytes_scanned: int
+    total_matches: int
+
+    file_matches: List[Tuple[str, int]]
+    """(path, matches) for every file."""
+
+    pattern_tallies: List[Tuple[str, int, int]]
+    """(pattern, matches, files) for every pattern, including patterns never matched."""
+
+    errors: List[Tuple[str, str]]
+    """(path, error) of the files that could not be scanned."""
+
+    def to_html(self) -> str:
+        """Render the report as a standalone HTML document."""
+        ...
+
+    def to_markdown(self) -> str:
+        """Render the report as a Markdown document."""
+        ...
+
+    def save(self, path: str, format: Optional[str] = None) -> None:
+        """
+        Write the report to a file.
+
+        Args:
+            path: Destination path
+            format: "html" or "markdown" (default: inferred from the .html, .htm, .md or
+                .markdown extension)
+
+        Raises:
+            IOError: If the file cannot be written
+            ValueError: If the format is unknown or cannot be inferred
+        """
+        ...
+
+class FileSummary:
+    """
+    Outcome of one file of a directory scan, see TextMatcher.match_dir. A file is scanned,
+    skipped with a reason, or failed with an error, and only scanned files have matches.
+    """
+
+    path: str
+    bytes_scanned: int
+
+    elapsed: float
+    """Seconds spent scanning the file."""
+
+    matches: int
+
+    skipped: Optional[str]
+    """Why the file was not scanned, e.g. "symbolic link" or "larger than max_file_size"."""
+
+    error: Optional[str]
+    """Why the file could not be scanned, a directory that cannot be listed included."""
+
+    scanned: bool
+    """Whether the file was read and searched."""
+
+class WebhookSink:
+    """
+    A sink that POSTs batches of findings as JSON to an HTTP endpoint.
+
+    Each request body is {"scan_id": ..., "file": ..., "findings": [{"start", "end", "pattern"}, ...]},
+    with a "provenance" object for scans run with provenance=True, or
+    {"schema_version": 1, "findings": [...]} of version 1 findings for matchers built with
+    schema_version=1.
+    Batches are sent from a background thread through a bounded queue, so a slow endpoint
+    slows the scan down instead of letting findings pile up in memory. Transport errors,
+    429 and 5xx responses are retried with exponential backoff.
+    """
+
+    url: str
+    headers: List[Tuple[str, str]]
+    batch_size: int
+    max_retries: int
+    timeout: float
+    queue_size: int
+
+    def __init__(
+        self,
+        url: str,
+        headers: Optional[List[Tuple[str, str]]] = None,
+        batch_size: Optional[int] = 1000,
+        max_retries: Optional[int] = 3,
+        timeout: Optional[float] = 30.0,
+        queue_size: Optional[int] = 4,
+    ) -> None:
+        """
+        Args:
+            url: http:// or https:// endpoint to POST findings to
+            headers: Extra request headers, e.g. authorization (default: none)
+            batch_size: Number of findings per request (default: 1000)
+            max_retries: Retries per request before the scan fails (default: 3)
+            timeout: Timeout of each request in seconds, positive (default: 30)
+            queue_size: Number of batches buffered before the scan waits (default: 4)
+
+        Raises:
+            ValueError: If the URL is not an http(s) URL or timeout is not a positive number
+                of seconds
+        """
+        ...
+
+class KafkaSink:
+    """
+    A sink that publishes each finding as a message to a Kafka topic.
+
+    Only available when voluta is built with the `kafka` cargo feature. Messages are keyed by
+    scan id and hold {"scan_id", "file", "start", "end", "pattern", "host", "fingerprint",
+    "timestamp"} as JSON or as an Avro datum of AVRO_SCHEMA, the last three being null unless
+    the scan runs with provenance=True. JSON messages are version 1 findings instead for
+    matchers built with schema_version=1. Batches wait for acknowledgement from all in-sync replicas.
+    """
+
+    AVRO_SCHEMA: str
+    brokers: List[str]
+    topic: str
+    serialization: str
+    batch_size: int
+    ack_timeout: float
+    client_id: Optional[str]
+
+    def __init__(
+        self,
+        brokers: List[str],
+        topic: str,
+        serialization: Optional[str] = "json",
+        batch_size: Optional[int] = 1000,
+        ack_timeout: Optional[float] = 30.0,
+        client_id: Optional[str] = None,
+    ) -> None:
+        """
+        Args:
+            brokers: Bootstrap brokers as "host:port" strings
+            topic: Topic to publish findings to
+            serialization: "json" or "avro" (default: "json")
+            batch_size: Number of findings per produce request (default: 1000)
+            ack_timeout: Seconds to wait for broker acknowledgements, positive (default: 30)
+            client_id: Client id reported to the brokers (default: none)
+
+        Raises:
+            ValueError: If no broker is given, the serialization is unknown or ack_timeout is
+                not a positive number of seconds
+        """
+        ...
+
+def enable_telemetry(endpoint: Optional[str] = None, service_name: Optional[str] = "voluta") -> None:
+    """
+    Start exporting scan spans and metrics over OTLP/HTTP.
+
+    Only available when voluta is built with the `otel` cargo feature. Every scan records a span
+    with one child span per chunk for chunked file scans, and the voluta.bytes_scanned and
+    voluta.matches counters and the voluta.scan.duration histogram.
+
+    Args:
+        endpoint: Collector base URL, e.g. "http://localhost:4318" (default: the standard
+            OTEL_EXPORTER_OTLP_* environment variables)
+        service_name: service.name resource attribute (default: "voluta")
+
+    Raises:
+        RuntimeError: If the exporters cannot be created
+    """
+    ...
+
+def shutdown_telemetry() -> None:
+    """
+    Flush pending spans and metrics and stop exporting them.
+
+    Only available when voluta is built with the `otel` cargo feature.
+    """
+    ...
+
+def warmup(path: str) -> int:
+    """
+    Read a file into the page cache ahead of timing-critical scans.
+
+    The kernel is asked to read the file in, then every page is touched so the file is
+    resident when this returns, unlike the prefault option that touches pages from a background
+    thread while a scan runs. Useful before benchmarks and latency-sensitive repeated scans of
+    the same large file.
+
+    Args:
+        path: Path of the file
+
+    Returns:
+        The size of the file in bytes
+
+    Raises:
+        IOError: If the file cannot be read
+    """
+    ...
+
+def run_pattern_tests(
+    matcher: TextMatcher, spec: Union[str, List[Dict[str, Any]]]
+) -> GoldenReport:
+    """
+    Check a matcher against golden test cases, sample documents declared with the matches
+    expected in them, so rule regressions show up before production.
+
+    Each case is a mapping with:
+        - "text": the sample, or "file": a sample file, relative to the spec file
+        - "name": name of the case in failures (default: its position, "#1", ...)
+        - "matches": expected matches, each a pattern, meaning at least one match of it, or a
+          mapping with "pattern" and any of "text" (the matched text), "start" (its byte
+          offset) and "count" (the exact number of such matches)
+        - "no_matches": patterns that must not match
+        - "exact": fail on matches not listed in "matches" (default: False)
+
+    Patterns are named as in results, by their digests for matchers built with a hash_key.
+
+    Args:
+        matcher: Matcher under test
+        spec: Path of a YAML file, read with PyYAML, or of a .json file, holding a list of
+            cases, or the list of cases itself
+
+    Returns:
+        A GoldenReport, truthy when every case passed
+
+    Raises:
+        IOError: If the spec file cannot be read
+        ImportError: If the spec is a YAML file and PyYAML is not installed
+        ValueError: If the cases are not valid
+    """
+    ...
+
+def compile_bundle(
+    pattern_files: List[str],
+    output: str,
+    golden: Optional[Union[str, List[Dict[str, Any]]]] = None,
+    validators: Optional[List[Union[str, Callable[[str], bool], Tuple[str, int]]]] = None,
+    signing_key: Optional[bytes] = None,
+    **options: Any,
+) -> BundleInfo:
+    """
+    Compile pattern files into a matcher bundle ahead of time, as a CI step, so runtime agents
+    load the bundle with TextMatcher.load_bundle instead of building from the pattern files.
+
+    The patterns of each file are added with the file as their "source" metadata, checked by
+    the validators, built into a matcher and checked against the golden tests before the
+    bundle is written. Nothing is written when any step fails. Also available as
+    `python -m voluta compile`.
+
+    Args:
+        pattern_files: Pattern files, one pattern per line, see
+            TextMatcherBuilder.add_patterns_from_file
+        output: Path of the bundle, written through a temporary file renamed over it
+        golden: Golden test cases the matcher must pass, see run_pattern_tests
+            (default: none)
+        validators: Validators of TextMatcherBuilder.validate, or tuples of its arguments
+            such as ("min_length", 4) (default: none)
+        signing_key: Key signing the bundle, see TextMatcher.save_bundle (default: unsigned)
+        **options: Options of TextMatcherBuilder.options, hash_key excepted
+
+    Returns:
+        The BundleInfo of the bundle written
+
+    Raises:
+        IOError: If a pattern file or the golden tests cannot be read or the bundle cannot
+            be written
+        PatternError: If a validator rejects a pattern, the patterns are invalid, a golden
+            test fails, hash_key is given or signing_key is not 32 bytes
+        LimitExceeded: If the matcher exceeds max_memory_bytes
+        ValueError: If an option or the golden tests are not valid
+    """
+    ...
+
+def bundle_verify_key(signing_key: bytes) -> bytes:
+    """
+    Ed25519 public key of a signing key, handed to the agents that check the bundles it
+    signs with load_bundle.
+
+    Args:
+        signing_key: 32-byte seed of the signing key
+
+    Returns:
+        The 32-byte verify key
+
+    Raises:
+        PatternError: If signing_key is not 32 bytes
+    """
+    ...
+
+def bundle_info(path: str) -> BundleInfo:
+    """
+    Read what a matcher bundle holds without building its matcher. Also available as
+    `python -m voluta inspect`.
+
+    Args:
+        path: Path of the bundle
+
+    Returns:
+        The BundleInfo recorded when the bundle was written
+
+    Raises:
+        IOError: If the bundle cannot be read
+        DecodeError: If the file is not a bundle or was written by another voluta version
+    """
+    ...
+
+def benchmark(
+    patterns: List[str],
+    data: bytes,
+    configs: Union[List[Dict[str, Any]], Dict[str, List[Any]]],
+    repeat: int = 3,
+    overlapping: Optional[bool] = None,
+    case_insensitive: Optional[bool] = None,
+    whole_word: Optional[bool] = None,
+) -> List[BenchResult]:
+    """
+    Time scans of sample data under every configuration, to pick one empirically.
+
+    Each configuration builds its own matcher and scans the data `repeat` times after a warmup
+    run. Without chunk_size and n_threads the data is scanned in a single pass, otherwise in
+    overlapping chunks on a pool of n_threads threads, the chunk size tuned when not given.
+
+    Args:
+        patterns: The patterns to search for
+        data: Sample data, representative of the data to scan
+        configs: List of dicts, or dict of lists expanded to every combination, with any of
+            the "engine", "chunk_size" and "n_threads" keys
+        repeat: Timed runs of each configuration (default: 3)
+        overlapping: Overlapping option of the matchers (default: False)
+        case_insensitive: Case insensitive option of the matchers (default: False)
+        whole_word: Whole word option of the matchers (default: False)
+
+    Returns:
+        One BenchResult per configuration, in order
+
+    Raises:
+        PatternError: If the patterns are invalid or an engine is not compiled in
+        LimitExceeded: If an engine cannot search the patterns
+        ValueError: If a configuration has an unknown key or value, or repeat is 0
+        TypeError: If configs is not a list of dicts or a dict of lists
+    """
+    ...
+
+def format_grep(
+    path: str,
+    matches: Union[MatchCollection, List[Tuple[int, int, str]]],
+    color: bool = False,
+) -> str:
+    """
+    Format matches found in a file grep-style.
+
+    Each line holding a match is printed once as "path:line:col: text", with 1-based line and
+    byte column numbers of its first match.
+
+    Args:
+        path: Path of the file the matches were found in
+        matches: Byte offset matches, e.g. from match_file_memmap or match_file_collection
+        color: Highlight matches and decorate the prefix with ANSI escape codes (default: False)
+
+    Raises:
+        IOError: If the file cannot be read
+        TypeError: If matches is not a MatchCollection or a list of tuples
+    """
+    ...
+
+def hash_text(key: Union[bytes, str], text: Union[bytes, str], case_insensitive: bool = False) -> str:
+    """
+    Hex HMAC-SHA256 of a text, as reported by matchers built with the same hash_key.
+
+    Useful to look up a known value among stored hash-only findings.
+
+    Args:
+        key: The hash_key of the matcher
+        text: The text to hash
+        case_insensitive: Lowercase ASCII letters first, like a case-insensitive matcher (default: False)
+    """
+    ...
+
+class ConfirmMatcher:
+    """
+    Two-stage matcher: hits of cheap literal anchors, found by the Aho-Corasick engine, are
+    confirmed by a regex run only on a small window around each hit.
+    """
+
+    rules: List[str]
+    """Names of the rules."""
+
+    window: int
+    """Bytes searched by the regexes on each side of an anchor hit."""
+
+    case_insensitive: bool
+    """Whether anchors are found regardless of ASCII case."""
+
+    def __init__(
+        self,
+        rules: List[Tuple[str, List[str], str]],
+        window: Optional[int] = 64,
+        case_insensitive: Optional[bool] = True,
+    ) -> None:
+        """
+        Initialize a ConfirmMatcher.
+
+        Args:
+            rules: (name, anchors, regex) tuples. A match of the regex is reported when it
+                overlaps a hit of one of the anchors, literals that occur in or next to every
+                match of the regex
+            window: Bytes searched by the regex on each side of an anchor hit, regex matches
+                must fit inside the window to be found (default: 64)
+            case_insensitive: Find anchors regardless of ASCII case (default: True). Regexes
+                keep their own flags, use (?i) for case-insensitive regexes
+
+        Raises:
+            ValueError: If the rule set is empty, a regex is invalid or a rule has no anchor
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of rules."""
+        ...
+
+    def match_bytes(self, data: bytes) -> List[Tuple[int, int, str]]:
+        """
+        Match provided bytes.
+
+        Returns:
+            List of (start_index, end_index, rule_name) tuples sorted by offset
+        """
+        ...
+
+    def match_file_memmap(self, path: str) -> List[Tuple[int, int, str]]:
+        """
+        Match a memory-mapped file, confirming anchor hits in parallel.
+
+        Returns:
+            List of (start_index, end_index, rule_name) tuples sorted by offset
+
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+
+    def match_bytes_collection(self, data: bytes) -> MatchCollection:
+        """Match provided bytes, returning a MatchCollection with rule names as patterns."""
+        ...
+
+    def match_file_collection(self, path: str) -> MatchCollection:
+        """Match a memory-mapped file, returning a MatchCollection with rule names as patterns."""
+        ...
+
+class TokenMatcher:
+    """
+    Matcher of whole tokens against huge term lists, such as customer names, stored in a
+    finite state transducer far smaller than the DFA of a TextMatcher over the same terms.
+    Tokens are runs of ASCII letters, digits, underscores and non-ASCII characters. A term of
+    several tokens matches them separated by any non-word characters other than line breaks.
+    """
+
+    pattern_count: int
+    overlapping: bool
+    case_insensitive: bool
+
+    phonetic: Optional[str]
+    """Phonetic algorithm encoding the tokens, "soundex" or "metaphone", if any."""
+
+    def __init__(
+        self,
+        terms: List[str],
+        overlapping: Optional[bool] = True,
+        case_insensitive: Optional[bool] = True,
+        phonetic: Optional[str] = None,
+    ) -> None:
+        """
+        Initialize a TokenMatcher.
+
+        Args:
+            terms: Terms to match, terms without any token are dropped and terms equal once
+                normalized report the first of them
+            overlapping: Report every term, including terms overlapping other terms
+                (default: True). Otherwise the longest term starting at the leftmost token
+                wins and the scan resumes after it
+            case_insensitive: Match ASCII letters regardless of case (default: True)
+            phonetic: Match tokens by their phonetic code instead of their spelling, encoded
+                from their ASCII letters; tokens without any match as spelled (default: None).
+                Every term sharing the codes of the matched tokens is reported
+                - "soundex": American Soundex
+                - "metaphone": original Metaphone, finer than Soundex on English names
+
+        Raises:
+            ValueError: If no term has a token, or the phonetic algorithm is unknown
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of terms."""
+        ...
+
+    def memory_usage(self) -> int:
+        """Bytes of the transducer and the terms."""
+        ...
+
+    def match_bytes(self, data: bytes) -> List[Tuple[int, int, str]]:
+        """
+        Match provided bytes.
+
+        Returns:
+            List of (start_index, end_index, matched_term) tuples sorted by offset
+        """
+        ...
+
+    def match_file_memmap(self, path: str) -> List[Tuple[int, int, str]]:
+        """
+        Match a memory-mapped file, scanning chunks cut at line breaks in parallel.
+
+        Returns:
+            List of (start_index, end_index, matched_term) tuples sorted by offset
+
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+
+class PhraseMatcher:
+    """
+    Matcher of multi-word phrases whose words appear in order, separated by any run of ASCII
+    whitespace, line wraps included. One automaton finds the words of every phrase, which match
+    whole words only, and the hits of consecutive words are chained across the gaps.
+    """
+
+    pattern_count: int
+    patterns: List[str]
+    case_insensitive: bool
+
+    word_count: int
+    """Number of distinct words searched by the automaton."""
+
+    max_gap: Optional[int]
+    """Most whitespace bytes between two words of a phrase, None for any run."""
+
+    def __init__(
+        self,
+        phrases: List[str],
+        case_insensitive: Optional[bool] = True,
+        max_gap: Optional[int] = None,
+    ) -> None:
+        """
+        Initialize a PhraseMatcher.
+
+        Args:
+            phrases: Phrases to match, split into words at whitespace; phrases without any word
+                are dropped
+            case_insensitive: Match ASCII letters regardless of case (default: True)
+            max_gap: Most whitespace bytes between two words (default: any run of whitespace)
+
+        Raises:
+            ValueError: If no phrase has a word, or max_gap is 0
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of phrases."""
+        ...
+
+    def memory_usage(self) -> int:
+        """Heap bytes used by the word automaton and the phrases."""
+        ...
+
+    def match_bytes(self, data: bytes) -> List[Tuple[int, int, str]]:
+        """
+        Match provided bytes.
+
+        Returns:
+            List of (start_index, end_index, matched_phrase) tuples sorted by offset, spanning
+            from the first byte of the first word to the last byte of the last word
+        """
+        ...
+
+    def match_file_memmap(self, path: str) -> List[Tuple[int, int, str]]:
+        """
+        Match a memory-mapped file.
+
+        Returns:
+            List of (start_index, end_index, matched_phrase) tuples sorted by offset
+
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+
+class LineIndex:
+    """
+    Newline index of a file or buffer, built once so each conversion of a byte offset to a
+    (line, column) position is a binary search. Lines are numbered from 1 like the results of
+    match_file, columns from 0.
+    """
+
+    line_count: int
+    """Number of lines, a trailing newline starting an empty last line."""
+
+    byte_count: int
+    """Number of bytes indexed."""
+
+    def __init__(self, data_or_path: Union[bytes, str]) -> None:
+        """
+        Index bytes, or a file given by its path which stays memory-mapped.
+
+        Raises:
+            IOError: If the file cannot be read
+            TypeError: If data_or_path is neither bytes nor a path
+        """
+        ...
+
+    def line_start(self, line: int) -> Optional[int]:
+        """Byte offset of the first byte of a line, None past the last line."""
+        ...
+
+    def position(self, offset: int, chars: bool = False) -> Tuple[int, int]:
+        """
+        (line, column) of a byte offset. The offset may be the length of the data, for the
+        exclusive ends of matches.
+
+        Args:
+            offset: Byte offset into the data
+            chars: Count the column in UTF-8 characters instead of bytes (default: False)
+
+        Raises:
+            ValueError: If the offset is past the end of the data
+        """
+        ...
+
+    def positions(self, offsets: List[int], chars: bool = False) -> List[Tuple[int, int]]:
+        """(line, column) of every byte offset, see position."""
+        ...
+
+    def locate(
+        self, matches: List[Tuple[int, int, Any]], chars: bool = False
+    ) -> List[Tuple[int, int, int, int, Any]]:
+        """
+        Positions of (start, end, pattern) matches, e.g. the results of match_bytes or
+        match_file_memmap.
+
+        Returns:
+            List of (line, column, end_line, end_column, pattern) tuples
+        """
+        ...
+
+class IncrementalMatcher:
+    """
+    Matcher whose pattern set grows while it is in use. Added patterns are built into small
+    Aho-Corasick layers, merged as they accumulate, so an addition never rebuilds the whole
+    dictionary. Scans running meanwhile keep using the layers they started with.
+    Results match those of a TextMatcher built on every pattern with engine="aho".
+    """
+
+    patterns: List[str]
+    """Patterns in the order they were added."""
+
+    pattern_count: int
+
+    layer_count: int
+    """Number of automaton layers searched by each scan."""
+
+    build_time_ms: float
+    """Time taken by the last addition or compaction in milliseconds."""
+
+    overlapping: bool
+    case_insensitive: bool
+    whole_word: bool
+    postprocess: Optional[str]
+    max_layers: int
+
+    def __init__(
+        self,
+        patterns: Optional[List[str]] = None,
+        overlapping: Optional[bool] = True,
+        case_insensitive: Optional[bool] = True,
+        whole_word: Optional[bool] = False,
+        postprocess: Optional[str] = None,
+        max_layers: Optional[int] = 16,
+        events: Optional[Union[str, logging.Logger]] = None,
+    ) -> None:
+        """
+        Initialize an IncrementalMatcher.
+
+        Args:
+            patterns: Initial patterns, empty patterns are dropped (default: none)
+            overlapping: Report overlapping matches (default: True)
+            case_insensitive: Match ASCII letters regardless of case (default: True)
+            whole_word: Only report matches surrounded by non-word characters (default: False)
+            postprocess: "merge" or "longest", see TextMatcher (default: None)
+            max_layers: Most layers kept, the newest are merged beyond it (default: 16)
+            events: Where an event for every rebuild of the layers goes, see TextMatcher
+                (default: None)
+
+        Raises:
+            ValueError: If postprocess is unknown or "priority", or max_layers is 0
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of patterns."""
+        ...
+
+    def add_patterns(self, patterns: List[str]) -> int:
+        """
+        Add patterns, visible to scans started once this returns. Empty patterns are dropped.
+
+        Returns:
+            Index of the first added pattern
+        """
+        ...
+
+    def compact(self) -> None:
+        """Merge every layer into a single automaton."""
+        ...
+
+    def memory_usage(self) -> int:
+        """Heap bytes used by the layers and the patterns."""
+        ...
+
+    def match_bytes(self, data: bytes) -> List[Tuple[int, int, str]]:
+        """
+        Match provided bytes against the patterns added so far.
+
+        Returns:
+            List of (start_index, end_index, matched_pattern) tuples
+        """
+        ...
+
+    def match_file_memmap(self, path: str) -> List[Tuple[int, int, str]]:
+        """
+        Match a memory-mapped file against the patterns added so far.
+
+        Returns:
+            List of (start_index, end_index, matched_pattern) tuples
+
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+
+class PlanResult:
+    """Outcome of a ScanPlan on one file or buffer."""
+
+    path: Optional[str]
+    """Path of the scanned file, None for in-memory data."""
+
+    bytes_scanned: int
+
+    deep_bytes: int
+    """Bytes scanned by the second pass."""
+
+    error: Optional[str]
+    """Why the file could not be scanned, see ScanPlan.scan_files."""
+
+    hits: List[Tuple[int, int, str]]
+    """(start_index, end_index, matched_pattern) tuples of the first pass, sorted by offset."""
+
+    regions: List[Tuple[int, int]]
+    """(start_index, end_index) ranges scanned by the second pass."""
+
+    findings: List[Tuple[int, int, str, str, str, str]]
+    """
+    (start_index, end_index, rule_name, before, text, after) tuples of the second pass, sorted
+    by offset, with the context before and after the match on its line.
+    """
+
+    def __len__(self) -> int:
+        """Number of findings."""
+        ...
+
+class DensityEstimate:
+    """Matches of a file extrapolated from a random sample of its chunks, see TextMatcher.estimate_density."""
+
+    bytes: int
+    chunks: int
+    sampled_chunks: int
+    sampled_bytes: int
+
+    sampled_matches: int
+    """Matches found in the sampled chunks."""
+
+    estimated_matches: float
+    """Matches expected in the whole file."""
+
+    lower: float
+    """Lower bound of the 95% confidence interval, at least sampled_matches."""
+
+    upper: float
+    """Upper bound of the 95% confidence interval, infinite when a single chunk of several was sampled."""
+
+    matches_per_mb: float
+    """Matches expected per MB (10^6 bytes)."""
+
+    seed: int
+    """Seed of the chunk draw, passing it again samples the same chunks."""
+
+    def __repr__(self) -> str: ...
+
+class GoldenFailure:
+    """Expectation of a golden test case that the matcher does not meet."""
+
+    case: str
+    """Name of the case, its position "#1", "#2", ... when unnamed."""
+
+    kind: str
+    """
+    "missing" for expected matches not found, or found a different number of times,
+    "unexpected" for matches of no_matches patterns or not listed in an exact case, and
+    "error" for samples that cannot be read.
+    """
+
+    pattern: Optional[str]
+
+    expected: Optional[int]
+    """Number of matches expected, None for at least one."""
+
+    found: int
+    """Number of matches found."""
+
+    message: str
+
+    def __repr__(self) -> str: ...
+
+class GoldenReport:
+    """Outcome of run_pattern_tests, truthy when every case passed."""
+
+    cases: int
+    """Number of cases run."""
+
+    passed: bool
+
+    failures: List[GoldenFailure]
+    """Failures in case order."""
+
+    def __bool__(self) -> bool: ...
+    def __repr__(self) -> str: ...
+
+class Comparison:
+    """
+    Findings of an A/B scan by two TextMatchers, see TextMatcher.compare_scan. Each list holds
+    (start_index, end_index, matched_pattern) tuples sorted by offset then pattern.
+    """
+
+    only_a: List[Tuple[int, int, str]]
+    """Findings of matcher A only."""
+
+    only_b: List[Tuple[int, int, str]]
+    """Findings of matcher B only."""
+
+    common: List[Tuple[int, int, str]]
+    """Findings of both matchers."""
+
+    def __repr__(self) -> str: ...
+
+class PatternDiff:
+    """
+    Differences from an old TextMatcher to a new one, see TextMatcher.diff_patterns.
+    Falsy when the matchers have the same patterns and options.
+    """
+
+    added: List[str]
+    """Patterns of the new matcher only, in its order."""
+
+    removed: List[str]
+    """Patterns of the old matcher only, in its order."""
+
+    changed: List[Tuple[str, str, int, int]]
+    """
+    (pattern, field, old, new) tuples of the patterns of both matchers whose "priority",
+    "fuzzy" distance or "min_count" changed, in the old order. Priorities are only compared
+    between matchers built with postprocess="priority".
+    """
+
+    options: List[Tuple[str, Any, Any]]
+    """(option, old, new) tuples of the options changing the matches that differ."""
+
+    def to_json(self) -> str:
+        """
+        Serialize the diff as a JSON object with "added" and "removed" lists of patterns,
+        "changed" {"pattern", "field", "old", "new"} objects and "options"
+        {"option", "old", "new"} objects.
+        """
+        ...
+
+    def __bool__(self) -> bool: ...
+    def __repr__(self) -> str: ...
+
+class PreScreen:
+    """
+    Blocked Bloom filter of an n-gram of every pattern of a matcher, see TextMatcher.prescreen.
+
+    Documents the matcher finds a match in always pass might_contain; documents without matches
+    fail it but for rare false positives.
+    """
+
+    ngram: int
+    """Length of the indexed n-grams: the length of the shortest pattern, up to 8 bytes."""
+
+    size: int
+    """Bytes of the filter, 8 per pattern rounded up to a power of two."""
+
+    def might_contain(self, data: bytes) -> bool:
+        """
+        Tell whether data might match, by probing every n-gram of it.
+
+        Args:
+            data: Document to route
+
+        Returns:
+            False if the matcher cannot find a match in data, True otherwise
+        """
+        ...
+
+    def to_bytes(self) -> bytes:
+        """Serialize the pre-screen, e.g. to ship it to the routing tier."""
+        ...
+
+    @staticmethod
+    def from_bytes(data: bytes) -> PreScreen:
+        """
+        Read a pre-screen serialized by to_bytes.
+
+        Raises:
+            ValueError: If data is not a serialized pre-screen
+        """
+        ...
+
+    def __repr__(self) -> str: ...
+
+class BundleInfo:
+    """What a matcher bundle holds, as recorded when it was written."""
+
+    fingerprint: str
+    """Fingerprint of the bundled matcher, see TextMatcher.fingerprint."""
+
+    content_hash: str
+    """
+    Hex SHA-256 of the patterns, metadata, tags and options of the bundle, checked on load. Pin
+    it where the bundle is loaded, see TextMatcher.load_bundle.
+    """
+
+    voluta_version: str
+    """Version of voluta that wrote the bundle, the only one that loads it."""
+
+    pattern_count: int
+    """Number of patterns, empty patterns are dropped."""
+
+    state_count: int
+    """Number of states of the pattern trie, see TextMatcher.state_count."""
+
+    engine: str
+    """Engine the bundled matcher searches with."""
+
+    automaton: Optional[str]
+    """Kind of Aho-Corasick automaton of the bundled matcher, see TextMatcher.automaton."""
+
+    memory_usage: int
+    """Heap bytes of the bundled matcher."""
+
+    signed: bool
+    """Whether the bundle is signed, the signature being checked by load_bundle with a verify_key."""
+
+    def to_json(self) -> str:
+        """Serialize the fields as a JSON object."""
+        ...
+
+    def __repr__(self) -> str: ...
+
+class ScanPlan:
+    """
+    Two-pass scan: a cheap TextMatcher runs over everything, and a ConfirmMatcher only on the
+    regions around its hits. Each file is mapped once for both passes.
+    """
+
+    coarse: TextMatcher
+    deep: ConfirmMatcher
+
+    region: Optional[int]
+    """Bytes scanned by the second pass on each side of a hit, None when it scans whole files."""
+
+    context: int
+    """Bytes of context kept on each side of a finding, within its line."""
+
+    def __init__(
+        self,
+        coarse: TextMatcher,
+        deep: ConfirmMatcher,
+        region: Union[int, str, None] = 4096,
+        context: Optional[int] = 40,
+    ) -> None:
+        """
+        Initialize a ScanPlan.
+
+        Args:
+            coarse: Matcher of the first pass, run over all the data
+            deep: Matcher of the second pass, run on the regions around first pass hits
+            region: Bytes around each first pass hit scanned by the second pass, nearby
+                regions merged, or "file" to scan the whole file once it has a hit
+                (default: 4096). Second pass matches must fit inside a region to be found
+            context: Bytes of context kept on each side of a finding (default: 40)
+
+        Raises:
+            ValueError: If region is a string other than "file"
+            TypeError: If region is neither a number nor a string
+        """
+        ...
+
+    def scan_bytes(self, data: bytes) -> PlanResult:
+        """Run both passes on provided bytes."""
+        ...
+
+    def scan_file(self, path: str) -> PlanResult:
+        """
+        Run both passes on a memory-mapped file.
+
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+
+    def scan_files(self, paths: List[str]) -> List[PlanResult]:
+        """
+        Run both passes on files in parallel, returning one result per path in order. Files
+        that cannot be read are reported with their error instead of failing the scan.
+        """
+        ...
 
 class TextMatcher:
     """A high-performance text pattern matcher using Aho-Corasick algorithm."""
 
-    overlapping: bool
-    """Whether to find overlapping matches."""
+    overlapping: bool
+    """Whether to find overlapping matches."""
+
+    case_insensitive: bool
+    """Whether the pattern matching is case insensitive."""
+
+    whole_word: bool
+    """Whether to match only whole words at word boundaries."""
+
+    postprocess: Optional[str]
+    """Post-processing strategy applied to overlapping spans, if any."""
+
+    prefer_longer: bool
+    """Whether longer spans win between overlapping matches of equal priority."""
+
+    patterns: List[str]
+    """Patterns of the matcher, empty patterns are dropped."""
+
+    hashed: bool
+    """Whether results report digests of the matched text instead of the patterns."""
+
+    fuzzy: List[int]
+    """Edit distance each pattern is matched at, 0 for exact matching."""
+
+    ignore_separators: bool
+    """Whether ASCII whitespace and punctuation are ignored in the patterns and the text."""
+
+    exclude: List[str]
+    """Exclusion patterns, matches overlapping one of their occurrences are dropped."""
+
+    line_filter: Optional[List[str]]
+    """Anchors a line must contain for match_file to search it, if any."""
+
+    encoding: str
+    """Encoding of the scanned bytes, "utf-8", "latin-1", "windows-1252" or "auto"."""
+
+    invalid_utf8: str
+    """Handling of the lines of match_file that are not valid UTF-8, "error", "lossy", "skip" or "binary"."""
+
+    word_chars: str
+    """Class of the bytes counting as word characters for whole_word, for instance "0-9A-Z_a-z"."""
+
+    engine: str
+    """Engine searching for the patterns, "aho", "memmem", "hyperscan" or "gpu", the one picked by "auto"."""
+
+    automaton: Optional[str]
+    """
+    Kind of Aho-Corasick automaton searching for the patterns, "dfa", "contiguous_nfa" or
+    "noncontiguous_nfa", None when the engine does not build one.
+    """
+
+    madvise: str
+    """madvise hint applied to memory-mapped files, "normal", "sequential" or "willneed"."""
+
+    huge_pages: bool
+    """Whether memory-mapped files ask for transparent huge pages."""
+
+    prefault: bool
+    """Whether memory-mapped files are prefaulted from a background thread during scans."""
+
+    fingerprint: str
+    """
+    Hex SHA-256 of the voluta version, the patterns and the options changing the matches.
+    Matchers with the same fingerprint find the same matches, whatever their engine or
+    memory-mapping hints, so it identifies the rule set behind results and keys caches of them.
+    """
+
+    audit_log: Optional[str]
+    """Path of the JSON Lines file every scan run is recorded in, if any."""
+
+    schema_version: int
+    """JSON schema of the findings exported by sinks and collections, 0 for the legacy shapes."""
+
+    metadata: List[Dict[str, str]]
+    """Metadata of each pattern, empty dicts for patterns given none."""
+
+    tags: List[List[str]]
+    """Tags of each pattern, empty lists for patterns given none."""
+
+    pattern_count: int
+    """Number of patterns, empty patterns are dropped."""
+
+    state_count: int
+    """
+    Number of states of the pattern trie, one per distinct pattern prefix (ASCII-folded when
+    case-insensitive) plus the root, whatever the engine.
+    """
+
+    build_time_ms: float
+    """Milliseconds taken to build the matcher."""
+
+    def memory_usage(self) -> int:
+        """Heap bytes used by the search engine and the patterns."""
+        ...
+
+    @staticmethod
+    def load_bundle(
+        path: str,
+        progress: Optional[Callable[[str, int, int], Any]] = None,
+        expected_hash: Optional[str] = None,
+        verify_key: Optional[bytes] = None,
+    ) -> TextMatcher:
+        """
+        Load a matcher bundle written by save_bundle or compile_bundle. The automaton is
+        rebuilt, without the GIL, and checked to have the fingerprint recorded in the bundle.
+
+        The recorded fingerprint and content hash catch bundles corrupted or edited by mistake,
+        not tampering: whoever can write the bundle can record matching ones. Load bundles from
+        a location only the CI that compiled them can write, pass the content_hash of the
+        compiled bundle as expected_hash, delivered to the agents separately, or have the CI
+        sign the bundles and pass its verify key as verify_key.
+
+        Args:
+            path: Path of the bundle
+            progress: Callback of the build, see TextMatcherBuilder.options (default: None)
+            expected_hash: Content hash the bundle must have, see BundleInfo.content_hash
+                (default: None)
+            verify_key: 32-byte Ed25519 public key the bundle must be signed for, see
+                bundle_verify_key (default: None)
+
+        Returns:
+            The bundled matcher
+
+        Raises:
+            IOError: If the bundle cannot be read
+            DecodeError: If the file is not a bundle, was written by another voluta version,
+                does not have its recorded content hash or does not have expected_hash, or is
+                not signed with the signing key of verify_key
+            PatternError: If the rebuilt matcher does not have the recorded fingerprint, or
+                verify_key is not 32 bytes
+            LimitExceeded: If the automaton exceeds the max_memory_bytes of the bundle
+        """
+        ...
+
+    def save_bundle(self, path: str, signing_key: Optional[bytes] = None) -> BundleInfo:
+        """
+        Write the patterns, per-pattern metadata and tags, and options of the matcher to a
+        bundle that load_bundle builds it back from, through a temporary file renamed over
+        path.
+
+        Args:
+            path: Path of the bundle
+            signing_key: 32-byte seed of the Ed25519 key signing the content hash of the
+                bundle, as the raw private bytes of an Ed25519 key (default: unsigned)
+
+        Returns:
+            The BundleInfo of the bundle written
+
+        Raises:
+            IOError: If the bundle cannot be written
+            PatternError: If the matcher was built with a hash_key, which the bundle would
+                hold in clear, or signing_key is not 32 bytes
+        """
+        ...
+
+    def prescreen(self) -> PreScreen:
+        """
+        Build a pre-screen of the matcher, a compact filter telling documents that cannot match
+        apart from those that might, several times faster than a scan, to route documents in
+        high-throughput pipelines before scanning those that pass.
+
+        Returns:
+            A PreScreen
+
+        Raises:
+            PatternError: If the matcher searches fuzzy variants, ignores separators or uses
+                an encoding other than utf-8
+        """
+        ...
+
+    def estimate_density(
+        self,
+        path: str,
+        sample_fraction: float = 0.01,
+        chunk_size: int = 65536,
+        seed: Optional[int] = None,
+    ) -> DensityEstimate:
+        """
+        Estimate the matches of a memory-mapped file from a random sample of its chunks, to
+        prioritize which files or archives to scan in full.
+
+        Matches are counted as match_bytes reports them, those starting in a sampled chunk
+        being found with the bytes around it, and extrapolated to the whole file with 95%
+        confidence bounds. Sampling every chunk gives the exact count.
+
+        Args:
+            path: Path of the file
+            sample_fraction: Share of the chunks scanned, in (0, 1], at least one chunk being
+                scanned (default: 0.01)
+            chunk_size: Size of the chunks in bytes (default: 65536)
+            seed: Seed of the chunk draw, for reproducible estimates (default: drawn from the clock)
+
+        Returns:
+            A DensityEstimate
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If sample_fraction is not in (0, 1] or chunk_size is 0
+        """
+        ...
+
+    def match_first(self, data_or_path: Union[bytes, str]) -> List[Tuple[int, int, str]]:
+        """
+        Find the first match of every pattern, for inventories of which patterns occur
+        rather than of every occurrence.
+
+        Matches are those match_bytes reports, and the scan stops as soon as every pattern has
+        been seen. Patterns of the same name count as one, and matchers with a postprocess scan
+        all of the data.
+
+        Args:
+            data_or_path: Bytes to match against, or the path of a file to memory-map
+
+        Returns:
+            List of (start_index, end_index, matched_pattern) tuples sorted by offset, at most
+            one per pattern
+
+        Raises:
+            ScanIOError: If the file cannot be read
+            TypeError: If data_or_path is neither bytes nor a path
+        """
+        ...
+
+    def compare_scan(self, other: TextMatcher, data_or_path: Union[bytes, str]) -> Comparison:
+        """
+        Scan the same data with this matcher (A) and `other` (B), e.g. a candidate rule set
+        against the production one.
+
+        Files are memory-mapped once for both matchers, which scan in parallel. Findings are
+        told apart by their span and pattern, so the patterns of hashed matchers only compare
+        equal under the same key.
+
+        Args:
+            other: Matcher B
+            data_or_path: Bytes to scan, or the path of a file
+
+        Returns:
+            A Comparison of the findings of A only, of B only and of both
+
+        Raises:
+            ScanIOError: If the file cannot be read
+            TypeError: If data_or_path is neither bytes nor a path
+        """
+        ...
+
+    def diff_patterns(self, other: TextMatcher) -> PatternDiff:
+        """
+        Compare the patterns and options of this matcher, the old one, with those of `other`.
+
+        Patterns are compared as named in results, by their digests for matchers built with a
+        hash_key, so the patterns of hashed matchers only compare equal under the same key.
+        Engines and memory-mapping hints, which do not change the matches, are left out.
+
+        Returns:
+            A PatternDiff of the patterns added, removed and changed and the options changed
+        """
+        ...
+
+    def union(self, others: List["TextMatcher"]) -> "TextMatcher":
+        """
+        Combine rule packs built separately into one matcher, without listing their patterns
+        again.
+
+        The patterns of this matcher come first, then those of each matcher of `others` in
+        order, with their priorities, fuzzy distances, min counts, metadata and tags: pattern i
+        of a matcher becomes pattern i plus the pattern counts of the matchers before it. The
+        union keeps the options of this matcher and picks its engine again for the combined
+        patterns.
+
+        Args:
+            others: Matchers whose patterns are appended
+
+        Returns:
+            The combined TextMatcher
+
+        Raises:
+            PatternError: If a matcher has different options changing the matches, as listed
+                by diff_patterns, or a different hash_key
+        """
+        ...
+
+    def subset(self, tags: List[str]) -> "TextMatcher":
+        """
+        Build a dedicated matcher of the patterns with one of `tags`, for hot paths where
+        filtering the scans of this matcher with tags is too slow and memory allows a smaller
+        automaton of its own.
+
+        The patterns keep their order, priorities, fuzzy distances, min counts, metadata and
+        tags, and the options of this matcher; the engine is picked again for them.
+
+        Args:
+            tags: Tags of the patterns kept
+
+        Returns:
+            The TextMatcher of the tagged patterns
+
+        Raises:
+            PatternError: If a tag is unknown or no pattern has any of the tags
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of patterns."""
+        ...
+
+    def __repr__(self) -> str: ...
+
+    def __init__(
+        self,
+        patterns: List[str],
+        overlapping: Optional[bool] = True,
+        case_insensitive: Optional[bool] = True,
+        whole_word: Optional[bool] = False,
+        postprocess: Optional[str] = None,
+        priorities: Optional[List[int]] = None,
+        prefer_longer: Optional[bool] = True,
+        hash_key: Optional[Union[bytes, str]] = None,
+        engine: Optional[str] = "auto",
+        madvise: Optional[str] = "normal",
+        huge_pages: Optional[bool] = False,
+        prefault: Optional[bool] = False,
+        fuzzy: Optional[Union[int, List[int]]] = None,
+        ignore_separators: Optional[bool] = False,
+        word_chars: Optional[str] = None,
+        exclude: Optional[List[str]] = None,
+        line_filter: Optional[List[str]] = None,
+        invalid_utf8: Optional[str] = "error",
+        encoding: Optional[str] = "utf-8",
+        events: Optional[Union[str, logging.Logger]] = None,
+        audit_log: Optional[str] = None,
+        min_count: Optional[Union[int, List[int], Dict[str, int]]] = None,
+        schema_version: Optional[int] = 0,
+    ) -> None:
+        """
+        Create a new TextMatcher instance. The constructor arguments are frozen, options
+        added since are set through TextMatcherBuilder.
+
+        Args:
+            patterns: List of string patterns to match
+            overlapping: Whether to find overlapping matches (default: True)
+            case_insensitive: Whether the pattern matching is case insensitive (default: True)
+            whole_word: Whether to match only whole words at word boundaries (default: False)
+            postprocess: How to resolve overlapping spans before returning them (default: None)
+                - "merge": merge overlapping and adjacent spans into one span
+                - "longest": keep the longest match wherever matches overlap
+                - "priority": keep the highest-priority pattern wherever matches overlap
+            priorities: One integer per pattern, higher values win overlaps (default: patterns
+                listed first win). Implies postprocess="priority"
+            prefer_longer: Whether longer spans win between matches of equal priority (default: True)
+            hash_key: Report the hex HMAC-SHA256 of the matched text under this key in place of
+                the pattern, in results, collections, sinks and reports (default: None). With
+                case_insensitive the text is ASCII lowercased first, so equal texts hash equally
+            engine: Engine searching for the patterns, results are the same with either
+                (default: "auto")
+                - "auto": memmem for tiny pattern sets, Vectorscan for large pattern sets
+                  when available, Aho-Corasick otherwise
+                - "aho": Aho-Corasick DFA
+                - "memmem": memchr::memmem or the Teddy SIMD searcher without an automaton,
+                  for up to 8 patterns of at most 32 bytes
+                - "hyperscan": Vectorscan, requires the hyperscan cargo feature
+                - "gpu": OpenCL kernel for buffers of 1 MiB and more, experimental, requires
+                  the gpu cargo feature, "aho" when no OpenCL GPU is found
+            madvise: Access pattern announced to the kernel for memory-mapped files, ignored
+                where madvise is not available (default: "normal")
+                - "normal": no hint
+                - "sequential": aggressive read-ahead, pages dropped once read
+                - "willneed": start reading the whole file in right away
+            huge_pages: Ask for transparent huge pages on memory-mapped files, Linux only
+                (default: False)
+            prefault: Touch every page of memory-mapped files from a background thread while
+                they are scanned, taking page faults ahead of the scan (default: False)
+            fuzzy: Also match texts within this many character substitutions, insertions and
+                deletions of the patterns, 1 or 2, as one distance for every pattern or a list of
+                one per pattern (default: exact matching). Edits leave the first and last
+                characters of a pattern in place, and a fuzzy pattern needs more than twice its
+                distance in characters
+            ignore_separators: Ignore ASCII whitespace and punctuation in the patterns and the
+                text, so "4111 1111-1111 1111" matches "4111111111111111" (default: False).
+                Offsets point into the raw input, patterns of separators only are dropped
+            word_chars: Bytes counting as word characters for whole_word, a class like the inside
+                of a regex bracket expression such as "A-Za-z0-9_.-" for hostnames
+                (default: ASCII letters, digits and underscore)
+            exclude: Texts whose occurrences are exclusion zones, such as documented example keys
+                or test fixtures: matches overlapping one by at least a byte are dropped during the
+                scan, across chunk boundaries too (default: None)
+            line_filter: Cheap anchor literals, folded like the patterns: match_file only searches
+                the lines containing one of them for the patterns (default: None)
+            invalid_utf8: What match_file does with lines that are not valid UTF-8, as found in
+                mixed-encoding log directories (default: "error")
+                - "error": raise a DecodeError, the scan stops at the first such line
+                - "lossy": replace invalid sequences with U+FFFD and search the replaced line,
+                  offsets are relative to the replaced line
+                - "skip": skip the line, which still counts in line numbers
+                - "binary": search the raw bytes of the line
+            encoding: Encoding of the scanned bytes, decoded to UTF-8 before the search so
+                patterns with accented characters hit files of older Windows systems; offsets
+                still point into the raw bytes (default: "utf-8")
+                - "utf-8": search the bytes as they are
+                - "latin-1": ISO-8859-1
+                - "windows-1252": Latin-1 with printable characters such as "€" in place of
+                  the C1 controls
+                - "auto": UTF-8 for inputs whose first 64 KiB are valid UTF-8, Windows-1252
+                  for the others, detected once per input whatever its chunks, and once per
+                  line by line scans
+                Exclusion patterns and line_filter anchors are searched in the raw bytes
+            events: Where structured events of the build and the scans go (default: None)
+                - a logging.Logger: one record per event, with the event's fields as record
+                  attributes: event, operation, path, bytes, matches, elapsed, patterns, error
+                - "logging": the "voluta" logger
+                - "tracing": Rust tracing subscribers, requires the tracing cargo feature
+                Events are "built" and "scan_finished" at INFO, "scan_started" at DEBUG,
+                "limit_hit" at WARNING and "scan_failed" at ERROR
+            audit_log: JSON Lines file, created if missing, that a record of every scan run
+                of a file, a directory or a set of files is appended to: scan_id, operation,
+                started_at, duration, user, host, pid, voluta_version, matcher (fingerprint,
+                engine and options), roots, files_scanned, files_failed, bytes_scanned,
+                matches and error. Scans whose record cannot be written raise ScanIOError
+                (default: None)
+            min_count: Matches a pattern needs in a scanned input, such as a buffer, a file or
+                each file of a directory scan, before any of them is reported, so noisy
+                low-signal patterns only show up in bulk; one count for every pattern, a list
+                of one per pattern, or a dict of counts by pattern, the others reporting every
+                match (default: every match is reported). Applied after postprocess; density
+                rules, reverse scans and density estimates count every match, and spooled
+                scans are not supported
+            schema_version: JSON schema of the findings exported by sinks and collections:
+                0 for the legacy shapes of earlier releases, or 1 for findings of
+                {"schema_version", "scan_id", "file", "start", "end", "pattern"} objects,
+                plus "provenance" when attached, in every JSON export (default: 0)
+        Raises:
+            ValueError: If pattern set is empty after filtering, postprocess, engine or
+                madvise is unknown, priorities do not fit the patterns or the postprocess strategy,
+                fuzzy distances are too large for their patterns, fuzzy or ignore_separators are
+                combined with hash_key, word_chars is not a valid class, line_filter has no
+                non-empty anchor, invalid_utf8 or encoding is unknown, events is unknown,
+                schema_version is unknown, or the engine is not available in this build
+        """
+        ...
+
+    def match_file(
+        self,
+        path: str,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        delimiter: Union[bytes, str, None] = None,
+        encoding: Optional[str] = None,
+        tags: Optional[List[str]] = None,
+    ) -> List[Tuple[int, int, int, str]]:
+        """
+        Match patterns in a file, line by line or record by record.
+        With a line_filter, only the lines containing one of its anchors are searched.
+
+        Args:
+            path: Path to the file to match
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            delimiter: Bytes ending each record in place of the newline, e.g. b"\\0" for
+                NUL-delimited exports or "\\r\\n\\r\\n" for multi-line log records; results then
+                carry record numbers (default: "\\n")
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (line_number, start_idx, end_idx, matched_pattern) tuples, offsets relative
+            to the line or record
+
+        Raises:
+            IOError: If the file cannot be read, or a line is not valid UTF-8 with
+                invalid_utf8="error"
+            ValueError: If delimiter is empty
+            TypeError: If delimiter is not bytes or str
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_file_parallel(
+        self,
+        path: str,
+        chunk_size: Union[int, str, None] = None,
+        n_threads: Union[int, str, None] = None,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        encoding: Optional[str] = None,
+        tags: Optional[List[str]] = None,
+    ) -> List[Tuple[int, int, int, str]]:
+        """
+        Match patterns in a memory-mapped file line by line, blocks of whole lines being
+        searched in parallel. Returns the same matches as match_file, in the same order.
+
+        Args:
+            path: Path to the file to match
+            chunk_size: Approximate size of the blocks of lines, or "auto" to size them from
+                the file, the threads and the pattern set (default: "auto")
+            n_threads: Number of threads to use, or "auto" for the available cores
+                (default: "auto")
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (line_number, start_idx, end_idx, matched_pattern) tuples, offsets relative
+            to the line
+
+        Raises:
+            IOError: If the file cannot be read, or a line is not valid UTF-8 with
+                invalid_utf8="error"
+            ValueError: If chunk_size or n_threads is not a positive integer or "auto"
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_file_memmap(
+        self,
+        path: str,
+        chunk_size: Union[int, str, None] = None,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        ignore_spans: Optional[List[Tuple[int, int]]] = None,
+        encoding: Optional[str] = None,
+        stats: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, str]], Tuple[List[Tuple[int, int, str]], ScanStats]]:
+        """
+        Faster file matching using memory mapping for large files.
+
+        Args:
+            path: Path to the file to match
+            chunk_size: Size of chunks to process, or "auto" to size them from the file
+                and the pattern set (default: "auto")
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            ignore_spans: (start, end) byte ranges, end exclusive, where matches are suppressed
+                for this call: matches overlapping one are dropped (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            stats: Also return the ScanStats of the scan (default: False)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (byte_offset, start_index, matched_pattern) tuples,
+            or a (matches, ScanStats) tuple with stats
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If chunk_size is not a positive integer or "auto"
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_file_memmap_parallel(
+        self,
+        path: str,
+        chunk_size: Union[int, str, None] = None,
+        n_threads: Union[int, str, None] = None,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        ignore_spans: Optional[List[Tuple[int, int]]] = None,
+        encoding: Optional[str] = None,
+        stats: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, str]], Tuple[List[Tuple[int, int, str]], ScanStats]]:
+        """
+        Parallel matching of large files with memory mapping.
+        Splits the file into chunks and processes them in parallel, finding the same matches
+        in the same order as match_file_memmap.
+
+        Args:
+            path: Path to the file to match
+            chunk_size: Size of chunks to process, or "auto" to size them from the file,
+                the threads and the pattern set (default: "auto")
+            n_threads: Number of threads to use, or "auto" for the available cores
+                (default: "auto")
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            ignore_spans: (start, end) byte ranges, end exclusive, where matches are suppressed
+                for this call: matches overlapping one are dropped (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            stats: Also return the ScanStats of the scan (default: False)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (byte_offset, start_index, matched_pattern) tuples,
+            or a (matches, ScanStats) tuple with stats
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If chunk_size or n_threads is not a positive integer or "auto"
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def tune(self, path: str, n_threads: Optional[int] = None) -> Tuple[int, int]:
+        """
+        Chunking picked by chunk_size="auto" for a file.
+
+        Args:
+            path: Path to the file to scan
+            n_threads: Number of threads the scan will use (default: the available cores)
+
+        Returns:
+            (chunk_size, n_threads) tuple, n_threads being the chunks scanned at once
+
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+
+    def match_bytes(
+        self,
+        data: bytes,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        ignore_spans: Optional[List[Tuple[int, int]]] = None,
+        encoding: Optional[str] = None,
+        stats: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, str]], Tuple[List[Tuple[int, int, str]], ScanStats]]:
+        """
+        Raw byte matching on provided byte data.
+        This allows for maximum performance by avoiding file I/O overhead.
+        overlapping and whole_word override the settings of the matcher for this call only,
+        so one compiled matcher can serve several policies.
+
+        Args:
+            data: Bytes to match against
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            ignore_spans: (start, end) byte ranges, end exclusive, where matches are suppressed
+                for this call: matches overlapping one are dropped (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            stats: Also return the ScanStats of the scan (default: False)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (start_index, end_index, matched_pattern) tuples,
+            or a (matches, ScanStats) tuple with stats
+
+        Raises:
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_file_stream(
+        self,
+        path: str,
+        buffer_size: Optional[int] = None,
+        max_bytes_per_sec: Optional[int] = None,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        encoding: Optional[str] = None,
+        stats: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, str]], Tuple[List[Tuple[int, int, str]], ScanStats]]:
+        """
+        Stream-based file matching that processes the file in chunks.
+        Useful for very large files or when memory efficiency is important.
+        Any buffer size finds the same matches as match_bytes on the file contents.
+
+        Args:
+            path: Path to the file to match
+            buffer_size: Size of the buffer to use for streaming (default: 8MB)
+            max_bytes_per_sec: Limit on the average read rate, for background scans that
+                share the disk with other workloads (default: unlimited)
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            stats: Also return the ScanStats of the scan (default: False)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (byte_offset, start_index, matched_pattern) tuples,
+            or a (matches, ScanStats) tuple with stats
+
+        Raises:
+            IOError: If the file cannot be read
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_stream(
+        self,
+        stream: bytes,
+        buffer_size: Optional[int] = None,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        ignore_spans: Optional[List[Tuple[int, int]]] = None,
+        encoding: Optional[str] = None,
+        stats: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, str]], Tuple[List[Tuple[int, int, str]], ScanStats]]:
+        """
+        Stream-based matching from any byte data source.
+        Useful for processing data from network streams, memory buffers, etc.
+        Any buffer size finds the same matches as match_bytes.
+
+        Args:
+            stream: Bytes to match against
+            buffer_size: Size of chunks to process (default: 8MB)
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            ignore_spans: (start, end) byte ranges, end exclusive, where matches are suppressed
+                for this call: matches overlapping one are dropped (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            stats: Also return the ScanStats of the scan (default: False)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (start_index, end_index, matched_pattern) tuples,
+            or a (matches, ScanStats) tuple with stats
+
+        Raises:
+            IOError: If there is an error processing the stream
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_readable_parallel(
+        self,
+        readable: Any,
+        buffer_size: Optional[int] = None,
+        n_threads: Optional[int] = None,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        encoding: Optional[str] = None,
+        tags: Optional[List[str]] = None,
+    ) -> List[Tuple[int, int, str]]:
+        """
+        Parallel matching of a binary file-like object with a read(size) method.
+        One thread reads chunks that a pool of workers scans, so pipes, sockets and
+        decompression streams use every core. The matches are those of match_bytes on the
+        same bytes, across chunk boundaries too.
+
+        Args:
+            readable: Object whose read(size) returns bytes, e.g. sys.stdin.buffer or gzip.open(path)
+            buffer_size: Size of the chunks handed to the workers (default: 8MB)
+            n_threads: Number of workers (default: one per core)
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            List of (start_index, end_index, matched_pattern) tuples
+
+        Raises:
+            TypeError: If read() does not return bytes
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_density(
+        self,
+        data: bytes,
+        min_matches: int,
+        window: int,
+        unit: Optional[str] = None,
+        patterns: Optional[List[str]] = None,
+        evidence: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, int]], List[Tuple[int, int, int, List[Tuple[int, int, str]]]]]:
+        """
+        Report regions where at least `min_matches` matches fall inside a sliding window.
+        A match's position is measured at its end offset, and overlapping dense windows
+        are merged into a single region.
+
+        Args:
+            data: Bytes to match against
+            min_matches: Minimum number of matches inside one window
+            window: Size of the sliding window
+            unit: "bytes" or "lines" (default: "bytes")
+            patterns: Only count matches of these patterns (default: all patterns)
+            evidence: Also return the matches counted in every region (default: False)
+            tags: Only count matches of the patterns with one of these tags, see the tags
+                argument of match_bytes (default: None)
+
+        Returns:
+            List of (start_index, end_index, match_count) tuples, or with evidence
+            (start_index, end_index, match_count, matches) tuples, matches being the
+            (start_index, end_index, matched_pattern) tuples counted in the region in the
+            order they were found
+
+        Raises:
+            ValueError: If the unit or a pattern is unknown
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_file_density(
+        self,
+        path: str,
+        min_matches: int,
+        window: int,
+        unit: Optional[str] = None,
+        patterns: Optional[List[str]] = None,
+        evidence: bool = False,
+        tags: Optional[List[str]] = None,
+    ) -> Union[List[Tuple[int, int, int]], List[Tuple[int, int, int, List[Tuple[int, int, str]]]]]:
+        """
+        Density rule over a memory-mapped file, see `match_density`.
+
+        Args:
+            path: Path to the file to match
+            min_matches: Minimum number of matches inside one window
+            window: Size of the sliding window
+            unit: "bytes" or "lines" (default: "bytes")
+            patterns: Only count matches of these patterns (default: all patterns)
+            evidence: Also return the matches counted in every region (default: False)
+            tags: Only count matches of the patterns with one of these tags, see the tags
+                argument of match_bytes (default: None)
+
+        Returns:
+            List of (start_index, end_index, match_count) tuples, or with evidence
+            (start_index, end_index, match_count, matches) tuples, matches being the
+            (start_index, end_index, matched_pattern) tuples counted in the region in the
+            order they were found
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If the unit or a pattern is unknown
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def match_dedup(
+        self, data: bytes, window: Optional[int] = None, unit: Optional[str] = None
+    ) -> List[Tuple[int, int, str, int]]:
+        """
+        Match provided bytes, folding repeats of identical findings into the first.
+
+        Findings are identical when they share their pattern and matched text. A repeat is
+        folded when it lies at most `window` bytes or lines after the previous occurrence, so
+        the window slides with every repeat and a steadily repeated key is reported once.
+
+        Args:
+            data: Bytes to match against
+            window: Largest distance between occurrences folded together (default: None,
+                folding every repeat in the data)
+            unit: "bytes" or "lines" (default: "bytes")
+
+        Returns:
+            List of (start_index, end_index, matched_pattern, count) tuples, count being the
+            number of occurrences folded into the finding, itself included
+
+        Raises:
+            ValueError: If the unit is unknown
+        """
+        ...
+
+    def match_file_dedup(
+        self, path: str, window: Optional[int] = None, unit: Optional[str] = None
+    ) -> List[Tuple[int, int, str, int]]:
+        """
+        Match a memory-mapped file, folding repeats of identical findings, see `match_dedup`.
+
+        Args:
+            path: Path to the file to match
+            window: Largest distance between occurrences folded together (default: None,
+                folding every repeat in the file)
+            unit: "bytes" or "lines" (default: "bytes")
+
+        Returns:
+            List of (start_index, end_index, matched_pattern, count) tuples
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If the unit is unknown
+        """
+        ...
+
+    def contains_all(self, data: bytes, patterns: List[str]) -> bool:
+        """
+        Tell whether every one of the given patterns has a match in data, for classification
+        rules like "the document mentions X, Y and Z", without materializing the matches.
+
+        Matches are those match_bytes reports, and the scan stops as soon as every pattern has
+        been seen. Matchers with a postprocess scan the whole of data.
+
+        Args:
+            data: Bytes to match against
+            patterns: Patterns that must all match; an empty list is always satisfied
+
+        Returns:
+            True if every pattern has a match in data
+
+        Raises:
+            PatternError: If a pattern is not one of the matcher's
+        """
+        ...
+
+    def match_histogram(
+        self,
+        data: bytes,
+        bucket_size: Optional[int] = None,
+        timestamp_format: Optional[str] = None,
+        interval: int = 3600,
+        patterns: Optional[List[str]] = None,
+    ) -> Tuple[List[Tuple[int, int, int]], int]:
+        """
+        Count matches per bucket without retrieving every match, e.g. to chart when a leak
+        started.
+
+        Buckets are either ranges of `bucket_size` bytes, or periods of `interval` seconds of
+        the timestamp at the start of the line holding each match. Timestamps are parsed with
+        `timestamp_format`, which supports %Y, %m, %b, %d, %e, %H, %M, %S, %f, %z, %s and %%.
+        Lines without a timestamp, like continuation lines, take the timestamp of the closest
+        line above that has one.
+
+        Args:
+            data: Bytes to match against
+            bucket_size: Size of byte range buckets
+            timestamp_format: Format of the timestamps prefixing lines, e.g. "%Y-%m-%dT%H:%M:%S"
+            interval: Length of time buckets in seconds (default: 3600)
+            patterns: Only count matches of these patterns (default: all patterns)
+
+        Returns:
+            ([(bucket_start, bucket_end, match_count)], unbucketed) with the buckets holding
+            matches in order, as byte offsets or seconds since the epoch, and the number of
+            matches found before the first line with a timestamp
+
+        Raises:
+            ValueError: If neither or both of bucket_size and timestamp_format are given, a
+                size is not positive, or a pattern or timestamp directive is unknown
+        """
+        ...
+
+    def match_file_histogram(
+        self,
+        path: str,
+        bucket_size: Optional[int] = None,
+        timestamp_format: Optional[str] = None,
+        interval: int = 3600,
+        patterns: Optional[List[str]] = None,
+    ) -> Tuple[List[Tuple[int, int, int]], int]:
+        """
+        Count the matches of a memory-mapped file per bucket, see `match_histogram`.
+
+        Args:
+            path: Path to the file to match
+            bucket_size: Size of byte range buckets
+            timestamp_format: Format of the timestamps prefixing lines, e.g. "%Y-%m-%dT%H:%M:%S"
+            interval: Length of time buckets in seconds (default: 3600)
+            patterns: Only count matches of these patterns (default: all patterns)
+
+        Returns:
+            ([(bucket_start, bucket_end, match_count)], unbucketed)
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If the buckets are misconfigured or a pattern is unknown
+        """
+        ...
+
+    def extract_unique(
+        self,
+        data_or_path: Union[bytes, str],
+        max_values: Optional[int] = None,
+        normalize: Optional[str] = None,
+    ) -> Dict[str, Set[str]]:
+        """
+        Distinct strings matched by each pattern, in bytes or in a memory-mapped file.
+
+        Args:
+            data_or_path: The bytes to scan, or the path of a file to scan
+            max_values: Keep at most this many distinct strings per pattern, the first ones
+                found (default: None, no limit)
+            normalize: 'lower' or 'upper' to change the case of the strings before
+                deduplicating them (default: None)
+
+        Returns:
+            Dict mapping each pattern that matched to the set of strings it matched. Matchers
+            built with a hash_key report the digests of the strings instead
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If the normalization is unknown
+            TypeError: If data_or_path is neither bytes nor a path
+        """
+        ...
+
+    def case_variants(self, data_or_path: Union[bytes, str]) -> Dict[str, Dict[str, int]]:
+        """
+        Original-case forms matched by each pattern and how many times each was found, in
+        bytes or in a memory-mapped file. Run on a case-insensitive matcher, this shows which
+        spellings such as "Password", "PASSWORD" and "password" actually occur.
+
+        Args:
+            data_or_path: The bytes to scan, or the path of a file to scan
+
+        Returns:
+            Dict mapping each pattern that matched to a dict of the forms it matched, as they
+            appear in the data, and their counts. Matchers built with a hash_key report the
+            digests of the forms instead
+
+        Raises:
+            IOError: If the file cannot be read
+            TypeError: If data_or_path is neither bytes nor a path
+        """
+        ...
+
+    def find_last(self, data: bytes, count: int = 1) -> List[Tuple[int, int, str]]:
+        """
+        The last matches of the data, the last one first, found by searching windows of
+        growing size back from the end instead of scanning forward and discarding.
+        Every occurrence counts whatever the overlapping setting, and post-processing is not
+        applied; whole_word and exclusion zones apply.
+
+        Args:
+            data: Bytes to match against
+            count: Number of matches to return from the end (default: 1)
+
+        Returns:
+            List of at most count (start_index, end_index, matched_pattern) tuples, by
+            decreasing start, end then pattern
+
+        Raises:
+            ValueError: If count is not positive
+        """
+        ...
+
+    def find_last_file(self, path: str, count: int = 1) -> List[Tuple[int, int, str]]:
+        """
+        The last matches of a memory-mapped file, the last one first, see find_last. Only the
+        end of the file is read when the matches are there, e.g. the most recent credential of
+        an append-only log.
+
+        Args:
+            path: Path to the file to match
+            count: Number of matches to return from the end (default: 1)
+
+        Returns:
+            List of at most count (start_index, end_index, matched_pattern) tuples, by
+            decreasing start, end then pattern
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If count is not positive
+        """
+        ...
+
+    def match_bytes_collection(self, data: bytes) -> MatchCollection:
+        """
+        Raw byte matching returning a MatchCollection instead of a list of tuples.
+
+        Args:
+            data: Bytes to match against
+
+        Returns:
+            MatchCollection of the matches
+        """
+        ...
+
+    def match_file_collection(
+        self,
+        path: str,
+        chunk_size: Union[int, str, None] = None,
+        n_threads: Union[int, str, None] = None,
+    ) -> MatchCollection:
+        """
+        Parallel memory-mapped file matching returning a MatchCollection sorted by offset.
+
+        Args:
+            path: Path to the file to match
+            chunk_size: Size of chunks to process, or "auto" to size them from the file,
+                the threads and the pattern set (default: "auto")
+            n_threads: Number of threads to use, or "auto" for the available cores
+                (default: "auto")
+
+        Returns:
+            MatchCollection of the matches
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If chunk_size or n_threads is not a positive integer or "auto"
+        """
+        ...
+
+    def match_bytes_np(self, data: bytes) -> Any:
+        """
+        Raw byte matching returning a NumPy structured array, filled in Rust without a
+        Python object per match.
+
+        Args:
+            data: Bytes to match against
+
+        Returns:
+            Array of records with start (uint64), end (uint64) and pattern_id (uint32) fields,
+            pattern_id being an index into patterns
+
+        Raises:
+            ImportError: If numpy is not installed
+        """
+        ...
+
+    def match_bytes_chars(self, data: bytes) -> List[Tuple[int, int, str, int, int]]:
+        """
+        Raw byte matching reporting the UTF-8 character offsets of the matches too.
+
+        Character offsets count code points from the start of the data; in invalid UTF-8,
+        lead bytes count as one character each and stray continuation bytes as none.
+
+        Args:
+            data: Bytes to match against
+
+        Returns:
+            List of (start_index, end_index, matched_pattern, char_start, char_end) tuples
+        """
+        ...
+
+    def match_file_chars(
+        self,
+        path: str,
+        chunk_size: Union[int, str, None] = None,
+        n_threads: Union[int, str, None] = None,
+    ) -> List[Tuple[int, int, str, int, int]]:
+        """
+        Parallel memory-mapped file matching reporting the UTF-8 character offsets of the
+        matches too, sorted by offset.
+
+        Args:
+            path: Path to the file to match
+            chunk_size: Size of chunks to process, or "auto" to size them from the file,
+                the threads and the pattern set (default: "auto")
+            n_threads: Number of threads to use, or "auto" for the available cores
+                (default: "auto")
+
+        Returns:
+            List of (start_index, end_index, matched_pattern, char_start, char_end) tuples
+
+        Raises:
+            IOError: If the file cannot be read
+            ValueError: If chunk_size or n_threads is not a positive integer or "auto"
+        """
+        ...
+
+    def match_file_spooled(
+        self,
+        path: str,
+        max_in_memory: Optional[int] = 1_000_000,
+        spill_dir: Optional[str] = None,
+    ) -> SpooledMatches:
+        """
+        Memory-mapped file matching for result sets too large for memory.
+        At most max_in_memory matches are kept in memory; the others are written to a
+        temporary file and read back while iterating.
+
+        Args:
+            path: Path to the file to match
+            max_in_memory: Matches kept in memory (default: 1,000,000)
+            spill_dir: Directory of the temporary file (default: the system temporary directory)
+
+        Returns:
+            SpooledMatches iterating over the matches in order
+
+        Raises:
+            IOError: If the file cannot be read or the temporary file cannot be written
+            ValueError: If the matcher post-processes matches, which needs them all in memory
+        """
+        ...
+
+    def match_file_to_sink(
+        self,
+        path: str,
+        sink: Union[str, WebhookSink, KafkaSink],
+        format: Optional[str] = None,
+        scan_id: Optional[str] = None,
+        provenance: bool = False,
+    ) -> SinkSummary:
+        """
+        Match a memory-mapped file and write the matches to a file as they are found.
+        Only a summary is returned, so the matches never need to fit in memory.
+
+        Args:
+            path: Path to the file to match
+            sink: File path, http(s) URL, WebhookSink or KafkaSink to write the matches to
+            format: File format, "ndjson", "csv" or "sqlite" (default: inferred from the extension)
+            scan_id: Identifier recorded with each finding in SQLite sinks (default: generated)
+            provenance: Attach the scan id, host, matcher fingerprint, source and start time of
+                the scan to every finding (default: False)
+
+        Returns:
+            SinkSummary of the scan
+
+        Raises:
+            IOError: If the file cannot be read or the sink cannot be written to
+            ValueError: If the format is unknown or cannot be inferred
+        """
+        ...
+
+    def match_bytes_to_sink(
+        self,
+        data: bytes,
+        sink: Union[str, WebhookSink, KafkaSink],
+        format: Optional[str] = None,
+        scan_id: Optional[str] = None,
+        provenance: bool = False,
+    ) -> SinkSummary:
+        """
+        Match provided bytes and write the matches to a file as they are found.
+
+        Args:
+            data: Bytes to match against
+            sink: File path, http(s) URL, WebhookSink or KafkaSink to write the matches to
+            format: File format, "ndjson", "csv" or "sqlite" (default: inferred from the extension)
+            scan_id: Identifier recorded with each finding in SQLite sinks (default: generated)
+            provenance: Attach the scan id, host, matcher fingerprint, source and start time of
+                the scan to every finding (default: False)
+
+        Returns:
+            SinkSummary of the scan
+
+        Raises:
+            IOError: If the sink cannot be written to
+            ValueError: If the format is unknown or cannot be inferred
+        """
+        ...
+
+    def match_journald_to_sink(
+        self,
+        sink: Union[str, WebhookSink, KafkaSink],
+        format: Optional[str] = None,
+        scan_id: Optional[str] = None,
+        follow: bool = True,
+        args: Optional[List[str]] = None,
+        max_records: Optional[int] = None,
+        idle_timeout: Optional[float] = None,
+        journalctl: str = "journalctl",
+        provenance: bool = False,
+    ) -> SinkSummary:
+        """
+        Scan the MESSAGE field of journald entries as they are logged and write the matches to
+        a sink, flushing it several times a second.
+
+        Entries are read from `journalctl --output=export`. Offsets are those of a log holding
+        the messages one per line. The call blocks until the journal ends, `max_records`
+        entries were scanned, no entry arrived for `idle_timeout` seconds, or KeyboardInterrupt.
+
+        Args:
+            sink: File path, http(s) URL, WebhookSink or KafkaSink to write the matches to
+            format: File format, "ndjson", "csv" or "sqlite" (default: inferred from the extension)
+            scan_id: Identifier recorded with each finding in SQLite sinks (default: generated)
+            follow: Keep waiting for new entries at the end of the journal (default: True)
+            args: Extra journalctl arguments selecting the entries, e.g. ["--since=now"]
+            max_records: Stop after this many entries (default: no limit)
+            idle_timeout: Stop once no entry arrived for this many seconds (default: no limit)
+            journalctl: journalctl executable (default: "journalctl")
+            provenance: Attach the scan id, host, matcher fingerprint, source and start time of
+                the scan to every finding (default: False)
+
+        Returns:
+            SinkSummary of the scan
 
-    case_insensitive: bool
-    """Whether the pattern matching is case insensitive."""
+        Raises:
+            IOError: If journalctl cannot be run or fails, or the sink cannot be written to
+            ValueError: If the format is unknown or a limit is not positive
+        """
+        ...
 
-    whole_word: bool
-    """Whether to match only whole words at word boundaries."""
+    def match_syslog_to_sink(
+        self,
+        address: str,
+        sink: Union[str, WebhookSink, KafkaSink],
+        format: Optional[str] = None,
+        scan_id: Optional[str] = None,
+        max_records: Optional[int] = None,
+        idle_timeout: Optional[float] = None,
+        provenance: bool = False,
+    ) -> SinkSummary:
+        """
+        Listen for syslog messages, scan them as they are received and write the matches to a
+        sink, flushing it several times a second.
 
-    def __init__(
+        TCP connections may frame messages with newlines or with octet counts (RFC 6587).
+        Offsets are those of a log holding the messages one per line. The call blocks until
+        `max_records` messages were scanned, no message arrived for `idle_timeout` seconds, or
+        KeyboardInterrupt.
+
+        Args:
+            address: "udp://host:port", "tcp://host:port" or "unix:///path", the Unix datagram
+                socket being created at the path and removed when the scan ends
+            sink: File path, http(s) URL, WebhookSink or KafkaSink to write the matches to
+            format: File format, "ndjson", "csv" or "sqlite" (default: inferred from the extension)
+            scan_id: Identifier recorded with each finding in SQLite sinks (default: generated)
+            max_records: Stop after this many messages (default: no limit)
+            idle_timeout: Stop once no message arrived for this many seconds (default: no limit)
+            provenance: Attach the scan id, host, matcher fingerprint, source and start time of
+                the scan to every finding (default: False)
+
+        Returns:
+            SinkSummary of the scan
+
+        Raises:
+            IOError: If the address cannot be bound or the sink cannot be written to
+            ValueError: If the address or format is invalid or a limit is not positive
+        """
+        ...
+
+    def match_dir(
         self,
-        patterns: List[str],
-        overlapping: Optional[bool] = True,
-        case_insensitive: Optional[bool] = True,
-        whole_word: Optional[bool] = False,
-    ) -> None:
+        path: str,
+        recursive: Optional[bool] = True,
+        follow_links: Optional[bool] = False,
+        max_file_size: Optional[int] = None,
+        ignore_files: Optional[bool] = True,
+        first_only: bool = False,
+        max_bytes_per_sec: Optional[int] = None,
+    ) -> Tuple[List[Tuple[str, int, int, str]], List[FileSummary]]:
         """
-        Create a new TextMatcher instance.
+        Scan every regular file under a directory in parallel, largest files first, and
+        summarize each file alongside the matches.
+
+        Files that cannot be read are summarized with their error instead of raising.
 
         Args:
-            patterns: List of string patterns to match
-            overlapping: Whether to find overlapping matches (default: True)
-            case_insensitive: Whether the pattern matching is case insensitive (default: True)
-            whole_word: Whether to match only whole words at word boundaries (default: False)
+            path: Directory to scan
+            recursive: Whether to scan the subdirectories too (default: True)
+            follow_links: Whether to follow symbolic links, each directory being walked once;
+                links are skipped otherwise (default: False)
+            max_file_size: Skip the files larger than this many bytes (default: None)
+            ignore_files: Whether to honor the .gitignore and .volutaignore files of the
+                scanned directories and skip .git directories; excluded paths are not visited
+                nor summarized (default: True)
+            first_only: Keep only the first match of every pattern in each file, see
+                match_first (default: False)
+            max_bytes_per_sec: Limit on the average read rate of the whole walk, all files
+                and threads together, for background sweeps that share the disk with other
+                workloads (default: unlimited)
+
+        Returns:
+            (matches, summaries): (path, start_index, end_index, matched_pattern) tuples sorted
+            by path then offset, and a FileSummary of every file visited, sorted by path
 
         Raises:
-            ValueError: If pattern set is empty after filtering
+            ScanIOError: If the directory itself cannot be listed
         """
         ...
 
-    def match_file(self, path: str) -> List[Tuple[int, int, int, str]]:
+    def match_git_repo(
+        self,
+        path: str,
+        revisions: Optional[List[str]] = None,
+        history: Optional[bool] = False,
+    ) -> List[Tuple[str, str, int, int, str]]:
         """
-        Match patterns in a file.
+        Scan the blobs of a git repository, read through a single git cat-file process.
+
+        Each distinct blob is scanned once and reported at the first commit and path it was
+        found at. Symbolic links and submodules are not scanned. Requires git on the PATH.
 
         Args:
-            path: Path to the file to match
+            path: Path of the repository or of a directory inside it
+            revisions: Revisions to scan (default: ["HEAD"])
+            history: Scan every blob added or modified by the commits reachable from the
+                revisions, reported at the oldest commit introducing it, instead of only the
+                trees of the revisions (default: False)
 
         Returns:
-            List of (line_number, start_idx, end_idx, matched_pattern) tuples
+            A list of (commit, path, start_index, end_index, matched_pattern) tuples, sorted
+            by blob then offset
 
         Raises:
-            IOError: If the file cannot be read
+            ScanIOError: If git cannot be run, path is not a repository or a revision is unknown
         """
         ...
 
-    def match_file_memmap(
-        self, path: str, chunk_size: Optional[int] = None
-    ) -> List[Tuple[int, int, str]]:
+    def match_warc(
+        self, path: str, max_record_size: Optional[int] = None
+    ) -> List[Tuple[str, int, int, str]]:
         """
-        Faster file matching using memory mapping for large files.
+        Scan the HTTP responses of a WARC web archive, plain or gzipped, record by record.
+
+        Payloads are decoded from their chunked transfer encoding and gzip or deflate content
+        encoding before the search; payloads that cannot be decoded are scanned as they are.
+        Other records, such as requests and metadata, are skipped.
 
         Args:
-            path: Path to the file to match
-            chunk_size: Size of chunks to process (default: 8MB)
+            path: Path to the .warc or .warc.gz file
+            max_record_size: Skip the records larger than this many bytes without reading
+                them into memory, logging a limit_hit event for the responses skipped; the
+                records of gzipped files are held to the matcher's max_decoded_size too, and
+                payloads decoding past it are truncated (default: None)
 
         Returns:
-            List of (byte_offset, start_index, matched_pattern) tuples
+            A list of (target_uri, start_index, end_index, matched_pattern) tuples sorted by
+            record then offset, offsets being those of the decoded payload
 
         Raises:
-            IOError: If the file cannot be read
+            DecodeError: If the file is not a WARC file
+            ScanIOError: If the file cannot be read or a record is truncated
         """
         ...
 
-    def match_file_memmap_parallel(
+    def match_disk_image(
         self,
         path: str,
+        sector_size: Optional[int] = None,
         chunk_size: Optional[int] = None,
         n_threads: Optional[int] = None,
-    ) -> List[Tuple[int, int, str]]:
+    ) -> List[Tuple[int, int, int, str]]:
         """
-        Parallel matching of large files with memory mapping.
-        Splits the file into chunks and processes them in parallel.
+        Scan a raw disk image or block device whatever its filesystem, unallocated space included.
+
+        The image is read sequentially in large chunks aligned on sectors, scanned in parallel,
+        and matches spanning two chunks are found. EnCase E01 images, in one or more segment
+        files, are read when voluta is built with the ewf feature.
 
         Args:
-            path: Path to the file to match
-            chunk_size: Size of chunks to process (default: 8MB)
-            n_threads: Number of threads to use (default: automatic)
+            path: Path to the raw image (dd), block device or first segment of an E01 image
+            sector_size: Bytes per sector, by default that recorded in an E01 image or 512
+            chunk_size: Bytes read at a time, rounded up to whole sectors, 16MB by default
+            n_threads: Scanning threads, one per core by default
+
+        Returns:
+            A list of (sector, start_index, end_index, matched_pattern) tuples sorted by offset,
+            the sector being the one holding the first byte of the match and offsets being
+            those of the image's media
+
+        Raises:
+            ValueError: If sector_size, chunk_size or n_threads is not positive
+            DecodeError: If an E01 image is corrupt
+            ScanIOError: If the image cannot be read, or is an E01 image and voluta was built
+                without the ewf feature
+        """
+        ...
+
+    def match_pcap(
+        self, path: str
+    ) -> List[Tuple[Tuple[str, str, int, str, int], int, int, str]]:
+        """
+        Scan the TCP and UDP payloads of a pcap or pcapng capture, plain or gzipped.
+
+        Each direction of a TCP connection is reassembled best effort: segments are put back in
+        order, retransmissions are dropped and the bytes of lost segments are skipped. UDP
+        datagrams are scanned one at a time. Ethernet, VLAN, Linux cooked, loopback and raw IP
+        captures are understood, IPv4 and IPv6; IP fragments after the first are skipped. A
+        capture cut short is scanned up to its last complete packet.
+
+        Args:
+            path: Path to the .pcap, .pcapng or gzipped capture
+
+        Returns:
+            A list of ((transport, src, src_port, dst, dst_port), start_index, end_index,
+            matched_pattern) tuples sorted by flow then offset, transport being "tcp" or "udp"
+            and offsets being those of the flow's reassembled payload
+
+        Raises:
+            DecodeError: If the file is not a capture or a record length is corrupt
+            ScanIOError: If the file cannot be read
+        """
+        ...
+
+    def match_process(
+        self, pid: int, include_files: bool = True
+    ) -> List[Tuple[int, str, int, int, str]]:
+        """
+        Scan the readable memory of a running process, Linux only.
+
+        Regions are listed by /proc/<pid>/maps, read from /proc/<pid>/mem and scanned in
+        parallel. Pages the kernel refuses to read, such as those of [vvar] or pages unmapped
+        while the scan runs, end the scan of their region. Reading the memory of another
+        process needs the ptrace permission over it, as a debugger does.
+
+        Args:
+            pid: Process ID
+            include_files: Scan the mappings of files, such as executables and libraries, and
+                not only anonymous memory, the heap and the stacks
+
+        Returns:
+            A list of (base_address, region, start_index, end_index, matched_pattern) tuples
+            sorted by address, region being the mapped file or pseudo-path like "[heap]", empty
+            for anonymous memory, and offsets being relative to the base address
+
+        Raises:
+            ScanIOError: If the process does not exist, its memory cannot be read, or the
+                platform is not Linux
+        """
+        ...
+
+    def match_db(
+        self,
+        source: Union[str, Any],
+        tables: Optional[List[str]] = None,
+        columns: Optional[List[str]] = None,
+        query: Optional[str] = None,
+        key: Optional[List[str]] = None,
+        batch_size: int = 1000,
+    ) -> List[Tuple[Optional[str], str, Any, int, int, str]]:
+        """
+        Scan the text and binary values of a database, streamed row by row instead of exported.
+
+        SQLite databases, given by path, `file:` URI or `sqlite:///<path>` URL, are opened
+        read-only and read without the GIL: every table but SQLite's own and virtual tables is
+        scanned, in its columns declared with a text or blob type, and rows are identified by
+        their primary key, or their rowid without one. Other databases are scanned through a
+        DB-API connection or cursor of their driver (psycopg, mysqlclient, ...), whose rows are
+        fetched batch_size at a time and scanned without the GIL batch by batch. Text is
+        scanned as UTF-8 and binary values (bytes, bytearray, memoryview) as they are; other
+        values are skipped.
+
+        Args:
+            source: Path or URL of an SQLite database, or a DB-API connection or cursor
+            tables: Tables scanned (default: every table of an SQLite database). With a DB-API
+                connection, each is read with SELECT * FROM "<table>"
+            columns: Columns scanned (default: the text and blob columns of SQLite tables,
+                every column of other results)
+            query: Query whose results are scanned instead of tables. A cursor given without
+                query or tables is scanned for the results already executed on it
+            key: Columns identifying the rows (default: the primary key of SQLite tables, the
+                row number of other results, counted from 0)
+            batch_size: Rows fetched at once from DB-API cursors (default: 1000)
+
+        Returns:
+            A list of (table, column, key, start_index, end_index, matched_pattern) tuples
+            sorted by row then column, table being None for the results of a query or cursor,
+            key the value of the key column, or a tuple of them for several, and offsets being
+            those of the UTF-8 text or the binary value
+
+        Raises:
+            ScanIOError: If the SQLite database cannot be opened or read, a table or key column
+                does not exist, or the URL is not an SQLite one
+            ValueError: If a key or scanned column is not in the results of a DB-API cursor,
+                tables and query are both given, or a DB-API connection is given neither
+        """
+        ...
+
+    def match_avro(self, path: str) -> List[Tuple[int, str, int, int, str]]:
+        """
+        Scan the string and bytes fields of the records of an Avro object container file.
+
+        The file is decoded natively with the schema it embeds, blocks being uncompressed or
+        compressed with deflate or snappy, and decoded and scanned without the GIL. Unions are
+        transparent in field paths; map keys, enums, fixed values and decimals are not scanned.
+
+        Args:
+            path: Path to the .avro file
+
+        Returns:
+            A list of (record, field, start_index, end_index, matched_pattern) tuples sorted by
+            record then field, record counting from 0 across the file and field being a path
+            like "user.emails[0]" or "labels[env]", empty for files of plain strings
+
+        Raises:
+            DecodeError: If the file is not an Avro file, is truncated or corrupt, uses
+                another codec, or has a block decompressing past max_decoded_size
+            ScanIOError: If the file cannot be read
+        """
+        ...
+
+    def match_orc(self, path: str) -> List[Tuple[int, str, int, int, str]]:
+        """
+        Scan the string, varchar, char and binary columns of the rows of an ORC file.
+
+        The file is read natively stripe by stripe, uncompressed or compressed with zlib,
+        snappy or lz4, decoding only the string columns and the structs, lists, maps and unions
+        holding them, without the GIL. Unions are transparent in field paths and map keys are
+        not scanned.
+
+        Args:
+            path: Path to the .orc file
+
+        Returns:
+            A list of (row, field, start_index, end_index, matched_pattern) tuples sorted by
+            row then column, row counting from 0 across the stripes and field being a path
+            like "user.emails[0]" or "labels[env]"
+
+        Raises:
+            DecodeError: If the file is not an ORC file, is truncated or corrupt, uses
+                another compression, or has a stream decompressing past max_decoded_size
+            ScanIOError: If the file cannot be read
+        """
+        ...
+
+    def match_diff(self, diff: bytes) -> List[Tuple[str, int, int, int, str]]:
+        """
+        Scan only the lines added by a unified diff, as printed by git diff or diff -u.
+
+        Args:
+            diff: The diff to scan
+
+        Returns:
+            A list of (path, line_number, start_index, end_index, matched_pattern) tuples, the
+            path and line numbers being those of the new version of the file and the offsets
+            relative to the line
+        """
+        ...
+
+    def match_staged(self, path: str = ".") -> List[Tuple[str, int, int, int, str]]:
+        """
+        Scan only the lines added by the changes staged in a git repository, e.g. from a
+        pre-commit hook. Requires git on the PATH.
+
+        Args:
+            path: Path of the repository or of a directory inside it (default: ".")
+
+        Returns:
+            The tuples of match_diff
+
+        Raises:
+            ScanIOError: If git cannot be run or path is not a repository
+        """
+        ...
+
+    def scan_report(
+        self,
+        paths: List[str],
+        context: int = 40,
+        max_findings: Optional[int] = 100,
+        title: Optional[str] = None,
+        scan_id: Optional[str] = None,
+    ) -> ScanReport:
+        """
+        Scan files in parallel and build a report with summary statistics, per-pattern tallies
+        and per-file findings with context snippets.
+
+        Files are scheduled largest first and files of 64MB or more are split into chunks
+        scanned in parallel. Files that cannot be read are listed in the report instead of
+        raising.
+
+        Args:
+            paths: Files to scan
+            context: Bytes of context shown on each side of a finding, within its line (default: 40)
+            max_findings: Findings listed per file, None lists all of them (default: 100).
+                Tallies always count every match
+            title: Title of the report (default: "Voluta scan report")
+            scan_id: Identifier of the scan (default: generated)
 
         Returns:
-            List of (byte_offset, start_index, matched_pattern) tuples
+            A ScanReport, rendered with to_html, to_markdown or save
+
+        Raises:
+            ScanIOError: If the run cannot be recorded in the audit log
+        """
+        ...
+
+    def grep(self, path: str, color: bool = False) -> str:
+        """
+        Scan a memory-mapped file and format the matches grep-style, see format_grep.
 
         Raises:
             IOError: If the file cannot be read
+            ValueError: If the matcher was built with a hash_key
         """
         ...
 
-    def match_bytes(self, data: bytes) -> List[Tuple[int, int, str]]:
+    def amatch_file(
+        self,
+        path: str,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        delimiter: Union[bytes, str, None] = None,
+        encoding: Optional[str] = None,
+        tags: Optional[List[str]] = None,
+    ) -> Awaitable[List[Tuple[int, int, int, str]]]:
         """
-        Raw byte matching on provided byte data.
-        This allows for maximum performance by avoiding file I/O overhead.
+        Asynchronous match_file, the file is scanned on a worker thread.
+        Must be called while an asyncio event loop is running.
+
+        Args:
+            path: Path to the file to match
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            delimiter: Bytes ending each record in place of the newline (default: "\\n")
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
+
+        Returns:
+            Awaitable resolving to a list of (line_number, start_index, end_index, matched_pattern) tuples
+
+        Raises:
+            IOError: If the file cannot be read (when awaited)
+            PatternError: If a tag is unknown
+        """
+        ...
+
+    def amatch_bytes(
+        self,
+        data: bytes,
+        overlapping: Optional[bool] = None,
+        whole_word: Optional[bool] = None,
+        encoding: Optional[str] = None,
+        tags: Optional[List[str]] = None,
+    ) -> Awaitable[List[Tuple[int, int, str]]]:
+        """
+        Asynchronous match_bytes, the data is copied and scanned on a worker thread.
+        Must be called while an asyncio event loop is running.
 
         Args:
             data: Bytes to match against
+            overlapping: Override the matcher's overlapping setting for this call (default: None)
+            whole_word: Override the matcher's whole_word setting for this call (default: None)
+            encoding: Override the matcher's encoding for this call, "utf-8", "latin-1",
+                "windows-1252" or "auto" (default: None)
+            tags: Only report the matches of the patterns with one of these tags, for this
+                call (default: None)
 
         Returns:
-            List of (start_index, end_index, matched_pattern) tuples
+            Awaitable resolving to a list of (start_index, end_index, matched_pattern) tuples
+
+        Raises:
+            PatternError: If a tag is unknown
         """
         ...
 
-    def match_file_stream(
-        self, path: str, buffer_size: Optional[int] = None
-    ) -> List[Tuple[int, int, str]]:
+    def amatch_readable(
+        self, readable: Any, buffer_size: Optional[int] = None
+    ) -> Awaitable[List[Tuple[int, int, str]]]:
         """
-        Stream-based file matching that processes the file in chunks.
-        Useful for very large files or when memory efficiency is important.
+        Asynchronous matching of a binary file-like object with a read(size) method.
+        The object is read in chunks on a worker thread, matches spanning chunks are found.
+        Must be called while an asyncio event loop is running.
 
         Args:
-            path: Path to the file to match
-            buffer_size: Size of the buffer to use for streaming (default: 8MB)
+            readable: Object whose read(size) returns bytes, e.g. io.BytesIO or open(path, "rb")
+            buffer_size: Number of bytes requested per read (default: 8MB)
 
         Returns:
-            List of (byte_offset, start_index, matched_pattern) tuples
+            Awaitable resolving to a list of (start_index, end_index, matched_pattern) tuples
 
         Raises:
-            IOError: If the file cannot be read
+            TypeError: If read() does not return bytes (when awaited)
         """
         ...
 
-    def match_stream(
-        self, stream: bytes, buffer_size: Optional[int] = None
-    ) -> List[Tuple[int, int, str]]:
+class TextMatcherBuilder:
+    """
+    Chained construction of a TextMatcher, in place of one constructor call with every
+    argument. Patterns come from several sources along with their metadata, are rewritten by
+    normalizers and checked by validators, and nothing is checked until build(), which
+    reports every rejected pattern at once along with invalid option combinations.
+
+    Every method but build() returns the builder itself, so calls can be chained:
+
+        matcher = (
+            TextMatcherBuilder()
+            .add_patterns(["alice", "bob"], metadata={"team": "red"})
+            .add_patterns_from_file("rules.txt", metadata={"team": "blue"})
+            .normalize("trim")
+            .validate("unique")
+            .engine("aho")
+            .build()
+        )
+    """
+
+    def __init__(self, patterns: Optional[List[str]] = None) -> None:
         """
-        Stream-based matching from any byte data source.
-        Useful for processing data from network streams, memory buffers, etc.
+        Initialize a builder.
 
         Args:
-            stream: Bytes to match against
-            buffer_size: Size of chunks to process (default: 8MB)
+            patterns: Initial patterns, without metadata (default: none)
+        """
+        ...
+
+    def __len__(self) -> int:
+        """Number of patterns added so far."""
+        ...
+
+    def __repr__(self) -> str: ...
+
+    def add_pattern(
+        self,
+        pattern: str,
+        metadata: Optional[Dict[str, str]] = None,
+        tags: Optional[List[str]] = None,
+    ) -> "TextMatcherBuilder":
+        """
+        Add a pattern.
+
+        Args:
+            pattern: Pattern to add
+            metadata: Metadata of the pattern (default: none)
+            tags: Tags of the pattern (default: none)
 
         Returns:
-            List of (start_index, end_index, matched_pattern) tuples
+            The builder
+        """
+        ...
+
+    def add_patterns(
+        self,
+        patterns: List[str],
+        metadata: Union[Dict[str, str], List[Dict[str, str]], None] = None,
+        tags: Union[List[str], List[List[str]], None] = None,
+    ) -> "TextMatcherBuilder":
+        """
+        Add patterns.
+
+        Args:
+            patterns: Patterns to add
+            metadata: A dict shared by every pattern, or a list of one dict per pattern
+                (default: none)
+            tags: Tags shared by every pattern, or a list of one list of tags per pattern,
+                such as ["pci"], that the tags argument of the match methods restricts a scan
+                to (default: none)
+
+        Returns:
+            The builder
 
         Raises:
-            IOError: If there is an error processing the stream
+            ValueError: If metadata or the lists of tags do not have one entry per pattern
+        """
+        ...
+
+    def add_patterns_from_file(
+        self,
+        path: str,
+        metadata: Optional[Dict[str, str]] = None,
+        tags: Optional[List[str]] = None,
+    ) -> "TextMatcherBuilder":
+        """
+        Add one pattern per line of a UTF-8 file, skipping blank lines and lines starting
+        with "#".
+
+        Args:
+            path: Path of the pattern file
+            metadata: Metadata shared by the patterns of the file (default: none)
+            tags: Tags shared by the patterns of the file (default: none)
+
+        Returns:
+            The builder
+
+        Raises:
+            IOError: If the file cannot be read or is not valid UTF-8
+        """
+        ...
+
+    def engine(self, name: str) -> "TextMatcherBuilder":
+        """
+        Set the engine searching for the patterns, see TextMatcher.
+
+        Raises:
+            ValueError: If the engine is unknown
+        """
+        ...
+
+    def overlapping(self, enabled: bool = True) -> "TextMatcherBuilder": ...
+    def case_insensitive(self, enabled: bool = True) -> "TextMatcherBuilder": ...
+    def whole_word(self, enabled: bool = True) -> "TextMatcherBuilder": ...
+
+    def options(self, **options: Any) -> "TextMatcherBuilder":
+        """
+        Set any other option by name: the TextMatcher arguments, such as postprocess or
+        exclude, and the options only the builder sets. Values are checked by build().
+
+        Args:
+            max_memory_bytes: Largest size in bytes of the Aho-Corasick automaton, estimated
+                from the patterns before it is built so an oversized dictionary fails in
+                seconds instead of running the worker out of memory; the estimate is within a
+                few percent of memory_usage() for dictionaries of words and tokens
+                (default: unlimited)
+            on_memory_limit: What the build does when the automaton would exceed
+                max_memory_bytes (default: "error")
+                - "error": raise LimitExceeded
+                - "downgrade": build a contiguous NFA in place of the DFA, a fraction of its
+                  size and slower to search, raising LimitExceeded only when that does not fit
+                  either; see the automaton property
+            max_decoded_size: Bytes an input decompressed by a scan takes at most, a record
+                of a gzipped WARC file, a WARC payload, an Avro block or an ORC stream, so a
+                compression bomb cannot exhaust memory; see match_warc, match_avro and
+                match_orc for what happens past it (default: 256MB)
+            progress: Called as progress(stage, done, total) as the build goes, at the start
+                and end of each stage and every 0.1 seconds in between; an exception it raises
+                cancels the build and is raised by build() (default: None)
+                - "prepare": checking the options and dropping empty patterns
+                - "expand": expanding fuzzy patterns, done and total counting patterns
+                - "automaton": building the automaton, 0 of 1 until it is built; the
+                  build returns within 0.1 seconds of a cancellation, and the automaton
+                  being built is dropped once its thread finishes it
+                - "labels": hashing the patterns under hash_key
+                The build runs without the GIL, so other threads keep running, and
+                KeyboardInterrupt cancels it too
+
+        Raises:
+            ValueError: If patterns, metadata or tags are given, they are added with
+                add_patterns
+        """
+        ...
+
+    def normalize(self, name: str) -> "TextMatcherBuilder":
+        """
+        Rewrite every pattern before it is validated, in the order normalizers are added.
+
+        Args:
+            name: Normalizer
+                - "trim": strip leading and trailing whitespace
+                - "lower": lowercase the pattern
+                - "collapse_whitespace": replace runs of whitespace by a single space
+
+        Returns:
+            The builder
+
+        Raises:
+            ValueError: If the normalizer is unknown
+        """
+        ...
+
+    def validate(
+        self, validator: Union[str, Callable[[str], bool]], arg: Optional[int] = None
+    ) -> "TextMatcherBuilder":
+        """
+        Check every normalized pattern at build().
+
+        Args:
+            validator: A callable returning whether it accepts a pattern, or a built-in
+                validator:
+                - "non_empty": reject empty patterns, otherwise dropped silently
+                - "ascii": reject patterns with non-ASCII characters
+                - "unique": reject patterns listed more than once
+                - "min_length": reject patterns shorter than arg bytes
+                - "max_length": reject patterns longer than arg bytes
+            arg: Length limit of "min_length" and "max_length" (default: None)
+
+        Returns:
+            The builder
+
+        Raises:
+            ValueError: If the validator is unknown or arg does not fit it
+        """
+        ...
+
+    def build(self) -> TextMatcher:
+        """
+        Normalize and validate the patterns, then build the matcher. The builder can keep
+        being used afterwards.
+
+        Returns:
+            The TextMatcher, with the metadata of each pattern
+
+        Raises:
+            LimitExceeded: If the automaton would exceed max_memory_bytes
+            PatternError: If patterns fail validation, listing every failure (the first 10,
+                then a count of the others), or the options are invalid as in TextMatcher
+                or on_memory_limit is unknown
+            TypeError: If an option is unknown or progress is not callable
         """
         ...